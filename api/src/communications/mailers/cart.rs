@@ -1,11 +1,14 @@
 use bigneon_db::models::enums::OrderItemTypes;
-use bigneon_db::models::DisplayOrder;
+use bigneon_db::models::{DisplayOrder, ResaleListing, TicketInstance, User, Wallet};
 use config::Config;
 use diesel::PgConnection;
 use errors::*;
+use std::collections::BTreeMap;
 use utils::communication::*;
+use uuid::Uuid;
 
 pub fn purchase_completed(
+    user_id: Uuid,
     user_first_name: &String,
     user_email: String,
     display_order: DisplayOrder,
@@ -23,8 +26,12 @@ pub fn purchase_completed(
     item_breakdown
         .push_str("<tr><th>Units</th><th>Description</th><th>Unit Price</th><th>Total</th></tr>");
     let mut total_fees = 0;
+    let mut taxes = BTreeMap::new();
     for oi in &display_order.items {
-        if oi.item_type == OrderItemTypes::Tickets {
+        if oi.item_type == OrderItemTypes::Tickets
+            || oi.item_type == OrderItemTypes::Resale
+            || oi.item_type == OrderItemTypes::GiftCard
+        {
             item_breakdown.push_str(r#"<tr><th align="center">"#);
             item_breakdown.push_str(&oi.quantity.to_string());
             item_breakdown.push_str("</th><th>");
@@ -38,11 +45,21 @@ pub fn purchase_completed(
                 (oi.quantity * oi.unit_price_in_cents) as f64 / 100.0
             ));
             item_breakdown.push_str("</th></tr>");
+        } else if oi.item_type == OrderItemTypes::Tax {
+            //Accumulate taxes per rule, included taxes are already part of the prices above
+            *taxes.entry(oi.description.clone()).or_insert(0) += oi.quantity * oi.tax_in_cents;
         } else {
             //Accumulate fees
             total_fees += oi.quantity * oi.unit_price_in_cents;
         }
     }
+    for (description, tax_in_cents) in taxes {
+        item_breakdown.push_str(r#"<tr><th></th><th>"#);
+        item_breakdown.push_str(&description);
+        item_breakdown.push_str(r#"</th><th></th><th align="right">$"#);
+        item_breakdown.push_str(&format!("{:.*}", 2, tax_in_cents as f64 / 100.0));
+        item_breakdown.push_str("</th></tr>");
+    }
     item_breakdown.push_str("</tbody></table>");
 
     template_data.insert(
@@ -50,7 +67,9 @@ pub fn purchase_completed(
         display_order
             .items
             .iter()
-            .filter(|i| i.item_type == OrderItemTypes::Tickets)
+            .filter(|i| {
+                i.item_type == OrderItemTypes::Tickets || i.item_type == OrderItemTypes::Resale
+            })
             .map(|i| i.quantity)
             .sum::<i64>()
             .to_string(),
@@ -59,6 +78,10 @@ pub fn purchase_completed(
         "total_fees".to_string(),
         format!("{:.*}", 2, total_fees as f64 / 100.0),
     );
+    template_data.insert(
+        "total_tax".to_string(),
+        format!("{:.*}", 2, display_order.tax_in_cents as f64 / 100.0),
+    );
     template_data.insert(
         "total_price".to_string(),
         format!("{:.*}", 2, display_order.total_in_cents as f64 / 100.0),
@@ -69,6 +92,38 @@ pub fn purchase_completed(
         format!("{}/hub", config.front_end_url),
     );
 
+    // Only the tickets the user holds are attached, those of a split order's other payers are
+    // sent to them
+    let wallet_ids: Vec<Uuid> = Wallet::find_for_user(user_id, conn)?
+        .iter()
+        .map(|w| w.id)
+        .collect();
+    let mut tickets = Vec::new();
+    for oi in &display_order.items {
+        if oi.item_type == OrderItemTypes::Tickets {
+            tickets.extend(TicketInstance::find_for_order_item(oi.id, conn)?);
+        } else if oi.item_type == OrderItemTypes::Resale {
+            if let Some(listing) = ResaleListing::find_by_order_item_id(oi.id, conn)? {
+                tickets.push(TicketInstance::find(listing.ticket_instance_id, conn)?);
+            }
+        }
+    }
+    let ticket_instance_ids: Vec<Uuid> = tickets
+        .iter()
+        .filter(|t| wallet_ids.contains(&t.wallet_id))
+        .map(|t| t.id)
+        .collect();
+    // The PDF is rendered when the email is sent
+    let attachments = if ticket_instance_ids.is_empty() {
+        Vec::new()
+    } else {
+        vec![CommAttachment::TicketPdf {
+            title: title.clone(),
+            ticket_instance_ids,
+            holder_user_id: user_id,
+        }]
+    };
+
     // TODO: Perhaps move this to an event subscription
     Communication::new(
         CommunicationType::EmailTemplate,
@@ -79,5 +134,149 @@ pub fn purchase_completed(
         Some(template_id),
         Some(vec![template_data]),
     )
+    .with_attachments(attachments)
+    .queue(conn)
+}
+
+/// Confirmation for someone who paid a share of another user's order, listing the tickets that
+/// were allocated to them. Fees and taxes are shown in proportion to their tickets, as the share
+/// amounts are.
+pub fn share_purchase_completed(
+    user_id: Uuid,
+    user_first_name: &String,
+    user_email: String,
+    display_order: &DisplayOrder,
+    tickets: &[TicketInstance],
+    amount_paid_in_cents: i64,
+    config: &Config,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let source = CommAddress::from(config.communication_default_source_email.clone());
+    let destinations = CommAddress::from(user_email);
+    let title = "BigNeon Purchase Completed".to_string();
+    let template_id = config.sendgrid_template_bn_purchase_completed.clone();
+    let mut template_data = TemplateData::new();
+    template_data.insert(String::from("name"), user_first_name.clone());
+    //Construct an itemised breakdown of the allocated tickets using a HTML table
+    let mut item_breakdown = r#"<table style="width:100%"><tbody>"#.to_string();
+    item_breakdown
+        .push_str("<tr><th>Units</th><th>Description</th><th>Unit Price</th><th>Total</th></tr>");
+    let mut order_ticket_count = 0;
+    let mut total_fees = 0;
+    for oi in &display_order.items {
+        if oi.item_type == OrderItemTypes::Tickets {
+            order_ticket_count += oi.quantity - oi.refunded_quantity;
+            let quantity = tickets
+                .iter()
+                .filter(|t| t.order_item_id == Some(oi.id))
+                .count() as i64;
+            if quantity == 0 {
+                continue;
+            }
+            item_breakdown.push_str(r#"<tr><th align="center">"#);
+            item_breakdown.push_str(&quantity.to_string());
+            item_breakdown.push_str("</th><th>");
+            item_breakdown.push_str(&oi.description);
+            item_breakdown.push_str(r#"</th><th align="right">$"#);
+            item_breakdown.push_str(&format!("{:.*}", 2, oi.unit_price_in_cents as f64 / 100.0));
+            item_breakdown.push_str(r#"</th><th align="right">$"#);
+            item_breakdown.push_str(&format!(
+                "{:.*}",
+                2,
+                (quantity * oi.unit_price_in_cents) as f64 / 100.0
+            ));
+            item_breakdown.push_str("</th></tr>");
+        } else if oi.item_type != OrderItemTypes::Tax {
+            //Accumulate fees
+            total_fees += (oi.quantity - oi.refunded_quantity) * oi.unit_price_in_cents;
+        }
+    }
+    item_breakdown.push_str("</tbody></table>");
+
+    let ticket_count = tickets.len() as i64;
+    let (share_fees, share_tax) = if order_ticket_count == 0 {
+        (0, 0)
+    } else {
+        (
+            total_fees * ticket_count / order_ticket_count,
+            display_order.tax_in_cents * ticket_count / order_ticket_count,
+        )
+    };
+    template_data.insert("ticket_count".to_string(), ticket_count.to_string());
+    template_data.insert(
+        "total_fees".to_string(),
+        format!("{:.*}", 2, share_fees as f64 / 100.0),
+    );
+    template_data.insert(
+        "total_tax".to_string(),
+        format!("{:.*}", 2, share_tax as f64 / 100.0),
+    );
+    template_data.insert(
+        "total_price".to_string(),
+        format!("{:.*}", 2, amount_paid_in_cents as f64 / 100.0),
+    );
+    template_data.insert("item_breakdown".to_string(), item_breakdown);
+    template_data.insert(
+        "tickets_link".to_string(),
+        format!("{}/hub", config.front_end_url),
+    );
+
+    // The PDF is rendered when the email is sent
+    let attachments = if tickets.is_empty() {
+        Vec::new()
+    } else {
+        vec![CommAttachment::TicketPdf {
+            title: title.clone(),
+            ticket_instance_ids: tickets.iter().map(|t| t.id).collect(),
+            holder_user_id: user_id,
+        }]
+    };
+
+    Communication::new(
+        CommunicationType::EmailTemplate,
+        title,
+        None,
+        Some(source),
+        destinations,
+        Some(template_id),
+        Some(vec![template_data]),
+    )
+    .with_attachments(attachments)
+    .queue(conn)
+}
+
+pub fn cart_reminder(
+    user: &User,
+    email: String,
+    display_order: &DisplayOrder,
+    config: &Config,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let source = CommAddress::from(config.communication_default_source_email.clone());
+    let destinations = CommAddress::from(email);
+    let title = "You left tickets in your cart".to_string();
+    let items: Vec<String> = display_order
+        .items
+        .iter()
+        .filter(|i| i.item_type == OrderItemTypes::Tickets || i.item_type == OrderItemTypes::Resale)
+        .map(|i| format!("{} x {}", i.quantity, i.description))
+        .collect();
+    let body = format!(
+        "Hi {}, you still have these tickets in your cart:\n\n{}\n\nThey are not reserved for you anymore, but you can pick up where you left off here: {}/cart/restore?order_id={}",
+        user.first_name.clone().unwrap_or_else(|| "there".to_string()),
+        items.join("\n"),
+        config.front_end_url,
+        display_order.id
+    );
+
+    Communication::new(
+        CommunicationType::Email,
+        title,
+        Some(body),
+        Some(source),
+        destinations,
+        None,
+        None,
+    )
     .queue(conn)
 }
@@ -0,0 +1,54 @@
+use bigneon_db::models::{Event, EventReschedule, User};
+use config::Config;
+use diesel::PgConnection;
+use errors::*;
+use utils::communication::*;
+
+/// Tells a ticket holder the event was rescheduled. Only holders with tickets from their own
+/// orders are pointed to the refund, others have to ask whoever gave them the tickets.
+pub fn rescheduled(
+    config: &Config,
+    user: &User,
+    has_order: bool,
+    event: &Event,
+    reschedule: &EventReschedule,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let email = match user.email.clone() {
+        Some(email) => email,
+        None => return Ok(()),
+    };
+
+    let source = CommAddress::from(config.communication_default_source_email.clone());
+    let destinations = CommAddress::from(email);
+    let title = format!("{} has been rescheduled", event.name);
+    let new_date = reschedule
+        .new_event_start
+        .map(|d| format!(" to {} UTC", d.format("%b %e, %Y %l:%M %p")))
+        .unwrap_or_default();
+    let refund_info = if has_order {
+        format!(
+            "If you can no longer attend, you can request a refund from your orders until {} UTC.\n\n{}/orders",
+            reschedule.refund_deadline.format("%b %e, %Y %l:%M %p"),
+            config.front_end_url
+        )
+    } else {
+        "If you can no longer attend, please contact the person who sent you your tickets."
+            .to_string()
+    };
+    let body = format!(
+        "{} has been rescheduled{}. Your tickets remain valid for the new date.\n\n{}",
+        event.name, new_date, refund_info
+    );
+
+    Communication::new(
+        CommunicationType::Email,
+        title,
+        Some(body),
+        Some(source),
+        destinations,
+        None,
+        None,
+    )
+    .queue(conn)
+}
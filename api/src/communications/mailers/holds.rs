@@ -0,0 +1,75 @@
+use bigneon_db::models::{Event, Hold, TicketType, User};
+use config::Config;
+use diesel::PgConnection;
+use errors::*;
+use utils::communication::*;
+
+pub fn hold_expired(
+    config: &Config,
+    hold: &Hold,
+    event: &Event,
+    ticket_type: &TicketType,
+    quantity_released: u32,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    // The user who created the hold is told rather than the comp's recipient
+    let email = match hold.created_by_user_id {
+        Some(user_id) => match User::find(user_id, conn)?.email {
+            Some(email) => email,
+            None => return Ok(()),
+        },
+        None => return Ok(()),
+    };
+
+    let source = CommAddress::from(config.communication_default_source_email.clone());
+    let destinations = CommAddress::from(email);
+    let title = format!("Your hold for {} has ended", event.name);
+    let body = format!(
+        "Your hold \"{}\" for {} has ended. {} unclaimed {} ticket(s) have been released back to general sale.",
+        hold.name, event.name, quantity_released, ticket_type.name
+    );
+
+    Communication::new(
+        CommunicationType::Email,
+        title,
+        Some(body),
+        Some(source),
+        destinations,
+        None,
+        None,
+    )
+    .queue(conn)
+}
+
+pub fn comp_claim(
+    config: &Config,
+    email: String,
+    comp: &Hold,
+    event: &Event,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let claim_link = format!(
+        "{}/comps/claim?claim_key={}",
+        config.front_end_url,
+        comp.claim_key.map(|k| k.to_string()).unwrap_or_default()
+    );
+
+    let source = CommAddress::from(config.communication_default_source_email.clone());
+    let destinations = CommAddress::from(email);
+    let title = format!("You have been sent tickets to {}", event.name);
+    let body = format!(
+        "Hi {}, you have been sent complimentary tickets to {}. Sign in or create an account to add them to your wallet: {}",
+        comp.name, event.name, claim_link
+    );
+
+    Communication::new(
+        CommunicationType::Email,
+        title,
+        Some(body),
+        Some(source),
+        destinations,
+        None,
+        None,
+    )
+    .queue(conn)
+}
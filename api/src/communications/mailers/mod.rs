@@ -1,5 +1,8 @@
 pub mod cart;
+pub mod events;
+pub mod holds;
 pub mod orders;
 pub mod organization_invites;
 pub mod tickets;
 pub mod user;
+pub mod waitlist;
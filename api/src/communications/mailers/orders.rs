@@ -3,6 +3,7 @@ use bigneon_db::models::DisplayOrder;
 use config::Config;
 use diesel::PgConnection;
 use errors::*;
+use std::collections::BTreeMap;
 use utils::communication::*;
 
 pub fn refund_email(
@@ -24,6 +25,7 @@ pub fn refund_email(
     item_breakdown
         .push_str("<tr><th>Units</th><th>Units Refunded</th><th>Description</th><th>Unit Price</th><th>Total</th></tr>");
     let mut total_fees = 0;
+    let mut taxes = BTreeMap::new();
     for oi in &display_order.items {
         if oi.item_type == OrderItemTypes::Tickets {
             item_breakdown.push_str(r#"<tr><th align="center">"#);
@@ -41,11 +43,22 @@ pub fn refund_email(
                 ((oi.quantity - oi.refunded_quantity) * oi.unit_price_in_cents) as f64 / 100.0
             ));
             item_breakdown.push_str("</th></tr>");
+        } else if oi.item_type == OrderItemTypes::Tax {
+            //Accumulate taxes per rule, included taxes are already part of the prices above
+            *taxes.entry(oi.description.clone()).or_insert(0) +=
+                (oi.quantity - oi.refunded_quantity) * oi.tax_in_cents;
         } else {
             //Accumulate fees
             total_fees += (oi.quantity - oi.refunded_quantity) * oi.unit_price_in_cents;
         }
     }
+    for (description, tax_in_cents) in taxes {
+        item_breakdown.push_str(r#"<tr><th></th><th></th><th>"#);
+        item_breakdown.push_str(&description);
+        item_breakdown.push_str(r#"</th><th></th><th align="right">$"#);
+        item_breakdown.push_str(&format!("{:.*}", 2, tax_in_cents as f64 / 100.0));
+        item_breakdown.push_str("</th></tr>");
+    }
     item_breakdown.push_str("</tbody></table>");
 
     template_data.insert("amount_refunded".to_string(), amount_refunded.to_string());
@@ -63,6 +76,10 @@ pub fn refund_email(
         "total_fees".to_string(),
         format!("{:.*}", 2, total_fees as f64 / 100.0),
     );
+    template_data.insert(
+        "total_tax".to_string(),
+        format!("{:.*}", 2, display_order.tax_in_cents as f64 / 100.0),
+    );
     template_data.insert(
         "total_price".to_string(),
         format!("{:.*}", 2, display_order.total_in_cents as f64 / 100.0),
@@ -0,0 +1,47 @@
+use bigneon_db::models::{Event, Hold, TicketType, User, WaitlistEntry};
+use config::Config;
+use diesel::PgConnection;
+use errors::*;
+use utils::communication::*;
+
+pub fn offer_available(
+    config: &Config,
+    user: &User,
+    event: &Event,
+    ticket_type: &TicketType,
+    entry: &WaitlistEntry,
+    hold: &Hold,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let email = match user.email.clone() {
+        Some(email) => email,
+        None => return Ok(()),
+    };
+
+    let source = CommAddress::from(config.communication_default_source_email.clone());
+    let destinations = CommAddress::from(email);
+    let title = format!("Tickets are available for {}", event.name);
+    let offer_link = format!(
+        "{}/events/{}?code={}",
+        config.front_end_url, event.id, hold.redemption_code
+    );
+    let expires_at = entry
+        .offer_expires_at
+        .map(|e| format!(" before {} UTC", e.format("%b %e, %Y %l:%M %p")))
+        .unwrap_or_default();
+    let body = format!(
+        "Good news! {} {} ticket(s) for {} have become available. Use the link below to purchase them{}.\n\n{}",
+        entry.quantity, ticket_type.name, event.name, expires_at, offer_link
+    );
+
+    Communication::new(
+        CommunicationType::Email,
+        title,
+        Some(body),
+        Some(source),
+        destinations,
+        None,
+        None,
+    )
+    .queue(conn)
+}
@@ -0,0 +1,42 @@
+use bigneon_db::models::{Event, EventReschedule};
+use config::Config;
+use diesel::pg::PgConnection;
+use errors::*;
+use utils::communication::CommAddress;
+use utils::communication::Communication;
+use utils::communication::CommunicationType;
+
+pub fn rescheduled(
+    config: &Config,
+    phone: String,
+    has_order: bool,
+    event: &Event,
+    reschedule: &EventReschedule,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let source = CommAddress::from(config.communication_default_source_phone.clone());
+    let destinations = CommAddress::from(phone);
+    let body = if has_order {
+        format!(
+            "{} has been rescheduled. Refunds are available until {} UTC: {}/orders",
+            event.name,
+            reschedule.refund_deadline.format("%b %e, %Y"),
+            config.front_end_url
+        )
+    } else {
+        format!(
+            "{} has been rescheduled. Your tickets remain valid for the new date.",
+            event.name
+        )
+    };
+    Communication::new(
+        CommunicationType::Sms,
+        body,
+        None,
+        Some(source),
+        destinations,
+        None,
+        None,
+    )
+    .queue(conn)
+}
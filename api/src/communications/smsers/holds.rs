@@ -0,0 +1,34 @@
+use bigneon_db::models::{Event, Hold};
+use config::Config;
+use diesel::pg::PgConnection;
+use errors::*;
+use utils::communication::CommAddress;
+use utils::communication::Communication;
+use utils::communication::CommunicationType;
+
+pub fn comp_claim(
+    config: &Config,
+    phone: String,
+    comp: &Hold,
+    event: &Event,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let source = CommAddress::from(config.communication_default_source_phone.clone());
+    let destinations = CommAddress::from(phone);
+    let body = format!(
+        "You have been sent tickets to {}: {}/comps/claim?claim_key={}",
+        event.name,
+        config.front_end_url,
+        comp.claim_key.map(|k| k.to_string()).unwrap_or_default()
+    );
+    Communication::new(
+        CommunicationType::Sms,
+        body,
+        None,
+        Some(source),
+        destinations,
+        None,
+        None,
+    )
+    .queue(conn)
+}
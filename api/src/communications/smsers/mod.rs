@@ -1 +1,3 @@
+pub mod events;
+pub mod holds;
 pub mod tickets;
@@ -16,6 +16,17 @@ pub struct Config {
     pub api_url: String,
     pub api_port: String,
     pub app_name: String,
+    pub apple_pass_assets_path: Option<String>,
+    pub apple_pass_certificate: Option<String>,
+    pub apple_pass_certificate_password: String,
+    pub apple_pass_type_identifier: Option<String>,
+    pub apple_pass_web_service_url: Option<String>,
+    pub apple_team_identifier: Option<String>,
+    pub apple_wwdr_certificate: Option<String>,
+    pub braintree_environment: String,
+    pub braintree_private_key: Option<String>,
+    pub braintree_public_key: Option<String>,
+    pub cart_reminder_delay_in_minutes: i64,
     pub database_url: String,
     pub database_pool_size: u32,
     pub domain: String,
@@ -23,10 +34,14 @@ pub struct Config {
     pub facebook_app_id: Option<String>,
     pub facebook_app_secret: Option<String>,
     pub google_recaptcha_secret_key: Option<String>,
+    pub google_wallet_issuer_id: Option<String>,
+    pub google_wallet_private_key: Option<String>,
+    pub google_wallet_service_account_email: Option<String>,
     pub http_keep_alive: usize,
     pub block_external_comms: bool,
     pub primary_currency: String,
     pub stripe_secret_key: String,
+    pub stripe_webhook_secret: Option<String>,
     pub token_secret: String,
     pub token_issuer: String,
     pub tari_client: Box<TariClient + Send + Sync>,
@@ -49,6 +64,10 @@ pub struct Config {
 
 const ALLOWED_ORIGINS: &str = "ALLOWED_ORIGINS";
 const APP_NAME: &str = "APP_NAME";
+const BRAINTREE_ENVIRONMENT: &str = "BRAINTREE_ENVIRONMENT";
+const BRAINTREE_PRIVATE_KEY: &str = "BRAINTREE_PRIVATE_KEY";
+const BRAINTREE_PUBLIC_KEY: &str = "BRAINTREE_PUBLIC_KEY";
+const CART_REMINDER_DELAY_MINUTES: &str = "CART_REMINDER_DELAY_MINUTES";
 const API_URL: &str = "API_URL";
 const API_PORT: &str = "API_PORT";
 const DATABASE_URL: &str = "DATABASE_URL";
@@ -59,6 +78,7 @@ const FACEBOOK_APP_SECRET: &str = "FACEBOOK_APP_SECRET";
 const GOOGLE_RECAPTCHA_SECRET_KEY: &str = "GOOGLE_RECAPTCHA_SECRET_KEY";
 const PRIMARY_CURRENCY: &str = "PRIMARY_CURRENCY";
 const STRIPE_SECRET_KEY: &str = "STRIPE_SECRET_KEY";
+const STRIPE_WEBHOOK_SECRET: &str = "STRIPE_WEBHOOK_SECRET";
 const TARI_URL: &str = "TARI_URL";
 const TEST_DATABASE_URL: &str = "TEST_DATABASE_URL";
 const TOKEN_SECRET: &str = "TOKEN_SECRET";
@@ -82,6 +102,22 @@ const SENDGRID_TEMPLATE_BN_TRANSFER_TICKETS: &str = "SENDGRID_TEMPLATE_BN_TRANSF
 const SENDGRID_TEMPLATE_BN_PASSWORD_RESET: &str = "SENDGRID_TEMPLATE_BN_PASSWORD_RESET";
 const SENDGRID_TEMPLATE_BN_USER_INVITE: &str = "SENDGRID_TEMPLATE_BN_USER_INVITE";
 
+//Wallet pass settings
+// Directory holding the icon and logo images bundled into every Apple Wallet pass
+const APPLE_PASS_ASSETS_PATH: &str = "APPLE_PASS_ASSETS_PATH";
+// Base64 encoded PKCS#12 pass type certificate
+const APPLE_PASS_CERTIFICATE: &str = "APPLE_PASS_CERTIFICATE";
+const APPLE_PASS_CERTIFICATE_PASSWORD: &str = "APPLE_PASS_CERTIFICATE_PASSWORD";
+const APPLE_PASS_TYPE_IDENTIFIER: &str = "APPLE_PASS_TYPE_IDENTIFIER";
+const APPLE_PASS_WEB_SERVICE_URL: &str = "APPLE_PASS_WEB_SERVICE_URL";
+const APPLE_TEAM_IDENTIFIER: &str = "APPLE_TEAM_IDENTIFIER";
+// PEM encoded Apple Worldwide Developer Relations intermediate certificate
+const APPLE_WWDR_CERTIFICATE: &str = "APPLE_WWDR_CERTIFICATE";
+const GOOGLE_WALLET_ISSUER_ID: &str = "GOOGLE_WALLET_ISSUER_ID";
+// PEM encoded private key of the Google Pay API for Passes service account
+const GOOGLE_WALLET_PRIVATE_KEY: &str = "GOOGLE_WALLET_PRIVATE_KEY";
+const GOOGLE_WALLET_SERVICE_ACCOUNT_EMAIL: &str = "GOOGLE_WALLET_SERVICE_ACCOUNT_EMAIL";
+
 //Spotify settings
 const SPOTIFY_AUTH_TOKEN: &str = "SPOTIFY_AUTH_TOKEN";
 
@@ -120,6 +156,11 @@ impl Config {
         let primary_currency = env::var(&PRIMARY_CURRENCY).unwrap_or_else(|_| "usd".to_string());
         let stripe_secret_key =
             env::var(&STRIPE_SECRET_KEY).unwrap_or_else(|_| "<stripe not enabled>".to_string());
+        let stripe_webhook_secret = env::var(&STRIPE_WEBHOOK_SECRET).ok();
+        let braintree_public_key = env::var(&BRAINTREE_PUBLIC_KEY).ok();
+        let braintree_private_key = env::var(&BRAINTREE_PRIVATE_KEY).ok();
+        let braintree_environment =
+            env::var(&BRAINTREE_ENVIRONMENT).unwrap_or_else(|_| "sandbox".to_string());
         let token_secret =
             env::var(&TOKEN_SECRET).unwrap_or_else(|_| panic!("{} must be defined.", TOKEN_SECRET));
 
@@ -184,6 +225,19 @@ impl Config {
         let sendgrid_template_bn_user_invite = env::var(&SENDGRID_TEMPLATE_BN_USER_INVITE)
             .unwrap_or_else(|_| panic!("{} must be defined.", SENDGRID_TEMPLATE_BN_USER_INVITE));
 
+        let apple_pass_assets_path = env::var(&APPLE_PASS_ASSETS_PATH).ok();
+        let apple_pass_certificate = env::var(&APPLE_PASS_CERTIFICATE).ok();
+        let apple_pass_certificate_password =
+            env::var(&APPLE_PASS_CERTIFICATE_PASSWORD).unwrap_or_else(|_| "".to_string());
+        let apple_pass_type_identifier = env::var(&APPLE_PASS_TYPE_IDENTIFIER).ok();
+        let apple_pass_web_service_url = env::var(&APPLE_PASS_WEB_SERVICE_URL).ok();
+        let apple_team_identifier = env::var(&APPLE_TEAM_IDENTIFIER).ok();
+        let apple_wwdr_certificate = env::var(&APPLE_WWDR_CERTIFICATE).ok();
+        let google_wallet_issuer_id = env::var(&GOOGLE_WALLET_ISSUER_ID).ok();
+        let google_wallet_private_key = env::var(&GOOGLE_WALLET_PRIVATE_KEY).ok();
+        let google_wallet_service_account_email =
+            env::var(&GOOGLE_WALLET_SERVICE_ACCOUNT_EMAIL).ok();
+
         let spotify_auth_token = env::var(&SPOTIFY_AUTH_TOKEN).ok();
 
         let twilio_api_key = env::var(&TWILIO_API_KEY)
@@ -213,10 +267,26 @@ impl Config {
             .parse()
             .unwrap();
 
+        let cart_reminder_delay_in_minutes = env::var(&CART_REMINDER_DELAY_MINUTES)
+            .unwrap_or("60".to_string())
+            .parse()
+            .unwrap();
+
         Config {
             allowed_origins,
             app_name,
+            apple_pass_assets_path,
+            apple_pass_certificate,
+            apple_pass_certificate_password,
+            apple_pass_type_identifier,
+            apple_pass_web_service_url,
+            apple_team_identifier,
+            apple_wwdr_certificate,
             api_url,
+            braintree_environment,
+            braintree_private_key,
+            braintree_public_key,
+            cart_reminder_delay_in_minutes,
             api_port,
             database_url,
             database_pool_size,
@@ -225,10 +295,14 @@ impl Config {
             facebook_app_id,
             facebook_app_secret,
             google_recaptcha_secret_key,
+            google_wallet_issuer_id,
+            google_wallet_private_key,
+            google_wallet_service_account_email,
             http_keep_alive,
             block_external_comms,
             primary_currency,
             stripe_secret_key,
+            stripe_webhook_secret,
             token_secret,
             token_issuer,
             front_end_url,
@@ -5,11 +5,13 @@ use bigneon_db::models::TicketType as Dbticket_types;
 use bigneon_db::models::User as DbUser;
 use bigneon_db::models::*;
 use bigneon_db::utils::errors::Optional;
+use chrono::Duration;
 use communications::mailers;
+use controllers::order_shares::DisplayOrderShare;
 use db::Connection;
 use errors::BigNeonError;
 use extractors::*;
-use helpers::application;
+use helpers::{application, idempotency, resales};
 use itertools::Itertools;
 use payments::PaymentProcessor;
 use server::AppState;
@@ -23,6 +25,8 @@ pub struct CartItem {
     pub quantity: u32,
     #[serde(default, deserialize_with = "deserialize_unless_blank")]
     pub redemption_code: Option<String>,
+    #[serde(default)]
+    pub seat_ids: Option<Vec<Uuid>>,
 }
 
 #[derive(Deserialize)]
@@ -31,8 +35,28 @@ pub struct UpdateCartRequest {
     pub box_office_pricing: Option<bool>,
 }
 
+#[derive(Deserialize, Serialize)]
+pub struct RestoreCartRequest {
+    pub order_id: Uuid,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct UpdateGiftCardsRequest {
+    pub items: Vec<UpdateGiftCardItem>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct UpdateResaleListingsRequest {
+    pub resale_listing_ids: Vec<Uuid>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SplitCartRequest {
+    pub ticket_quantities: Vec<u32>,
+}
+
 pub fn update_cart(
-    (connection, json, user): (Connection, Json<UpdateCartRequest>, User),
+    (connection, json, user, state): (Connection, Json<UpdateCartRequest>, User, State<AppState>),
 ) -> Result<HttpResponse, BigNeonError> {
     let connection = connection.get();
 
@@ -66,6 +90,7 @@ pub fn update_cart(
             quantity: i.quantity,
             ticket_type_id: i.ticket_type_id,
             redemption_code: i.redemption_code.clone(),
+            seat_ids: i.seat_ids.clone(),
         })
         .collect();
 
@@ -76,8 +101,13 @@ pub fn update_cart(
         }
     }
     cart.update_quantities(&order_items, box_office_pricing, false, connection)?;
+    let cart = Order::find(cart.id, connection)?;
+    cart.schedule_cart_reminder(
+        Duration::minutes(state.config.cart_reminder_delay_in_minutes),
+        connection,
+    )?;
 
-    Ok(HttpResponse::Ok().json(Order::find(cart.id, connection)?.for_display(connection)?))
+    Ok(HttpResponse::Ok().json(cart.for_display(connection)?))
 }
 
 pub fn destroy((connection, user): (Connection, User)) -> Result<HttpResponse, BigNeonError> {
@@ -86,12 +116,14 @@ pub fn destroy((connection, user): (Connection, User)) -> Result<HttpResponse, B
     // Find the current cart of the user, if it exists.
     let mut cart = Order::find_or_create_cart(&user.user, connection)?;
     cart.update_quantities(&[], false, true, connection)?;
+    cart.update_gift_cards(&[], connection)?;
+    cart.update_resale_listings(&[], connection)?;
 
     Ok(HttpResponse::Ok().json(Order::find(cart.id, connection)?.for_display(connection)?))
 }
 
 pub fn replace_cart(
-    (connection, json, user): (Connection, Json<UpdateCartRequest>, User),
+    (connection, json, user, state): (Connection, Json<UpdateCartRequest>, User, State<AppState>),
 ) -> Result<HttpResponse, BigNeonError> {
     let connection = connection.get();
 
@@ -125,6 +157,7 @@ pub fn replace_cart(
             quantity: i.quantity,
             ticket_type_id: i.ticket_type_id,
             redemption_code: i.redemption_code.clone(),
+            seat_ids: i.seat_ids.clone(),
         })
         .collect();
 
@@ -136,26 +169,91 @@ pub fn replace_cart(
     }
 
     cart.update_quantities(&order_items, box_office_pricing, true, connection)?;
+    let cart = Order::find(cart.id, connection)?;
+    cart.schedule_cart_reminder(
+        Duration::minutes(state.config.cart_reminder_delay_in_minutes),
+        connection,
+    )?;
+
+    Ok(HttpResponse::Ok().json(cart.for_display(connection)?))
+}
+
+/// Adds the tickets from an expired cart, such as one linked from a cart reminder, back into
+/// the user's cart
+pub fn restore(
+    (connection, json, user, state): (Connection, Json<RestoreCartRequest>, User, State<AppState>),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let order = Order::find(json.order_id, connection)?;
+    let cart = order.restore_to_cart(&user.user, connection)?;
+    cart.schedule_cart_reminder(
+        Duration::minutes(state.config.cart_reminder_delay_in_minutes),
+        connection,
+    )?;
+
+    Ok(HttpResponse::Ok().json(cart.for_display(connection)?))
+}
+
+/// Replaces the gift cards in the user's cart
+pub fn update_gift_cards(
+    (connection, json, user): (Connection, Json<UpdateGiftCardsRequest>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let mut cart = Order::find_or_create_cart(&user.user, connection)?;
+    cart.update_gift_cards(&json.items, connection)?;
+
+    Ok(HttpResponse::Ok().json(Order::find(cart.id, connection)?.for_display(connection)?))
+}
+
+/// Splits the cart between several payers, returning a link for each share that the user can
+/// send to the people paying for it
+pub fn split(
+    (connection, json, user, state): (Connection, Json<SplitCartRequest>, User, State<AppState>),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let mut cart = match Order::find_cart_for_user(user.id(), connection)? {
+        Some(cart) => cart,
+        None => return application::unprocessable("No cart exists for user"),
+    };
+    let shares = cart
+        .split(&json.ticket_quantities, connection)?
+        .into_iter()
+        .map(|share| DisplayOrderShare::from_share(share, &state.config))
+        .collect::<Vec<DisplayOrderShare>>();
+
+    Ok(HttpResponse::Ok().json(json!({
+        "order": Order::find(cart.id, connection)?.for_display(connection)?,
+        "shares": shares,
+    })))
+}
+
+/// Replaces the resale tickets in the user's cart
+pub fn update_resale_listings(
+    (connection, json, user): (Connection, Json<UpdateResaleListingsRequest>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let mut cart = Order::find_or_create_cart(&user.user, connection)?;
+    cart.update_resale_listings(&json.resale_listing_ids, connection)?;
 
     Ok(HttpResponse::Ok().json(Order::find(cart.id, connection)?.for_display(connection)?))
 }
 
 pub fn show((connection, user): (Connection, User)) -> Result<HttpResponse, BigNeonError> {
     let connection = connection.get();
-    let order = match Order::find_cart_for_user(user.id(), connection)? {
+    let order = match Order::find_cart_for_checkout(user.id(), connection)? {
         Some(o) => o,
         None => return Ok(HttpResponse::Ok().json(json!({}))),
     };
     Ok(HttpResponse::Ok().json(order.for_display(connection)?))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct CheckoutCartRequest {
     pub amount: i64,
     pub method: PaymentRequest,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 #[serde(tag = "type")]
 pub enum PaymentRequest {
     External {
@@ -180,22 +278,55 @@ pub enum PaymentRequest {
         #[serde(default, deserialize_with = "deserialize_unless_blank")]
         provider: Option<String>,
     },
+    // Pays the amount from the user's account credit, the rest can be paid with another method
+    Credit,
     // Only for 0 amount carts
     Free,
 }
 
 pub fn checkout(
-    (connection, json, user, state): (Connection, Json<CheckoutCartRequest>, User, State<AppState>),
+    (connection, json, user, state, idempotency_key): (
+        Connection,
+        Json<CheckoutCartRequest>,
+        User,
+        State<AppState>,
+        IdempotencyKey,
+    ),
 ) -> Result<HttpResponse, BigNeonError> {
     // TODO: Change application::unprocesable's in this method to validation errors.
     let req = json.into_inner();
+    let request_json = json!(req);
+
+    if let Some(response) = idempotency::replay(
+        &idempotency_key,
+        user.id(),
+        IdempotencyKeyOperations::Checkout,
+        &request_json,
+        connection.get(),
+    )? {
+        info!("CART: Replaying checkout response for idempotency key");
+        return Ok(response);
+    }
 
     info!("CART: Checking out");
-    let mut order = match Order::find_cart_for_user(user.id(), connection.get())? {
+    let mut order = match Order::find_cart_for_checkout(user.id(), connection.get())? {
         Some(o) => o,
         None => return application::unprocessable("No cart exists for user"),
     };
+    if !order.shares(connection.get())?.is_empty() {
+        return application::unprocessable(
+            "This order has been split between several payers, each share must be paid separately",
+        );
+    }
     let order_id = order.id;
+    let idempotency_claim = idempotency::claim(
+        &idempotency_key,
+        order_id,
+        user.id(),
+        IdempotencyKeyOperations::Checkout,
+        &request_json,
+        connection.get(),
+    )?;
     order.lock_version(connection.get())?;
 
     let order_items = order.items(connection.get())?;
@@ -217,7 +348,11 @@ pub fn checkout(
     }
     info!("CART: Verifying asset");
     //Just confirming that the asset is setup correctly before proceeding to payment.
-    for asset_id in tokens_per_asset.keys() {
+    let resold_asset_ids: Vec<Uuid> = resales::resold_tickets(&order, connection.get())?
+        .into_iter()
+        .map(|(_, ticket)| ticket.asset_id)
+        .collect();
+    for asset_id in tokens_per_asset.keys().chain(resold_asset_ids.iter()) {
         let asset = Asset::find(*asset_id, connection.get())?;
         if asset.blockchain_asset_id.is_none() {
             return application::internal_server_error(
@@ -237,6 +372,10 @@ pub fn checkout(
             }
             checkout_free(&connection, order, &user)?
         }
+        PaymentRequest::Credit => {
+            info!("CART: Received account credit payment");
+            checkout_credit(&connection, order, &req, &user)?
+        }
         PaymentRequest::External {
             reference,
             first_name,
@@ -281,13 +420,14 @@ pub fn checkout(
                 &connection,
                 &mut order,
                 None,
-                &req,
+                req.amount,
                 &user,
                 &state.config.primary_currency,
                 &provider,
                 true,
                 false,
                 false,
+                None,
                 &state.service_locator,
             )?
         }
@@ -300,18 +440,23 @@ pub fn checkout(
             &connection,
             &mut order,
             Some(&token),
-            &req,
+            req.amount,
             &user,
             &state.config.primary_currency,
             provider,
             false,
             *save_payment_method,
             *set_default,
+            None,
             &state.service_locator,
         )?,
     };
 
-    if payment_response.status() == StatusCode::OK {
+    // Tickets are only transferred once the order is fully paid, a partial payment such as
+    // account credit leaves the order waiting for the remainder
+    if payment_response.status() == StatusCode::OK
+        && Order::find(order_id, connection.get())?.status == OrderStatus::Paid
+    {
         let conn = connection.get();
         let new_owner_wallet = Wallet::find_default_for_user(user.id(), conn)?;
         for (asset_id, token_ids) in &tokens_per_asset {
@@ -338,7 +483,6 @@ pub fn checkout(
         }
 
         let order = Order::find(order_id, conn)?;
-
         let display_order = order.for_display(conn)?;
 
         let user = DbUser::find(order.on_behalf_of_user_id.unwrap_or(order.user_id), conn)?;
@@ -346,6 +490,7 @@ pub fn checkout(
         //Communicate purchase completed to user
         if let (Some(first_name), Some(email)) = (user.first_name, user.email) {
             mailers::cart::purchase_completed(
+                user.id,
                 &first_name,
                 email,
                 display_order,
@@ -355,6 +500,8 @@ pub fn checkout(
         }
     }
 
+    idempotency::complete(idempotency_claim, &payment_response, connection.get())?;
+
     Ok(payment_response)
 }
 
@@ -376,6 +523,23 @@ fn checkout_free(
     Ok(HttpResponse::Ok().json(json!(order.for_display(conn)?)))
 }
 
+fn checkout_credit(
+    conn: &Connection,
+    order: Order,
+    checkout_request: &CheckoutCartRequest,
+    user: &User,
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = conn.get();
+    if order.user_id != user.id() {
+        return application::forbidden("This cart does not belong to you");
+    }
+    let mut order = order;
+    order.add_credit_payment(user.id(), checkout_request.amount, conn)?;
+
+    let order = Order::find(order.id, conn)?;
+    Ok(HttpResponse::Ok().json(json!(order.for_display(conn)?)))
+}
+
 // TODO: This should actually probably move to an `orders` controller, since the
 // user will not be calling this.
 fn checkout_external(
@@ -435,37 +599,82 @@ fn checkout_external(
     Ok(HttpResponse::Ok().json(json!(order.for_display(conn)?)))
 }
 
-fn checkout_payment_processor(
+/// Charges `amount` to the given card or stored payment method. Payments for a share of a split
+/// order can be made by anyone holding the share's link, the share is marked paid along with the
+/// payment.
+pub(crate) fn checkout_payment_processor(
     conn: &Connection,
     order: &mut Order,
     token: Option<&str>,
-    req: &CheckoutCartRequest,
+    amount: i64,
     auth_user: &User,
     currency: &str,
     provider_name: &str,
     use_stored_payment: bool,
     save_payment_method: bool,
     set_default: bool,
+    order_share: Option<&OrderShare>,
     service_locator: &ServiceLocator,
 ) -> Result<HttpResponse, BigNeonError> {
     info!("CART: Executing provider payment");
     let connection = conn.get();
 
-    if order.user_id != auth_user.id() {
+    if order_share.is_none() && order.user_id != auth_user.id() {
         return application::forbidden("This cart does not belong to you");
-    } else if order.status != OrderStatus::Draft {
+    } else if order.status != OrderStatus::Draft && order.status != OrderStatus::PartiallyPaid {
         return application::unprocessable(
             "Could not complete this cart because it is not in the correct status",
         );
     }
 
-    let client = service_locator.create_payment_processor(provider_name)?;
+    // All events in the cart must charge through the same provider and merchant account
+    let organizations = order.organizations(connection)?;
+    let (payment_provider, merchant_account_id) = match organizations.first() {
+        Some(organization) => (
+            organization.payment_provider.clone(),
+            organization.merchant_account_id.clone(),
+        ),
+        // Gift cards do not belong to an organization so are charged to the platform account
+        None if order
+            .items(connection)?
+            .iter()
+            .any(|i| i.item_type == OrderItemTypes::GiftCard) =>
+        {
+            (provider_name.to_string(), None)
+        }
+        None => {
+            return application::unprocessable(
+                "Could not complete this cart because it does not contain any tickets",
+            );
+        }
+    };
+    if organizations.iter().any(|organization| {
+        organization.payment_provider != payment_provider
+            || organization.merchant_account_id != merchant_account_id
+    }) {
+        return application::unprocessable(
+            "Could not complete this cart because it contains tickets from organizations using different payment providers",
+        );
+    }
+    if provider_name != payment_provider {
+        return application::unprocessable(&format!(
+            "Could not complete this cart because payments for these events must be made through {}",
+            payment_provider
+        ));
+    }
+
+    let client =
+        service_locator.create_payment_processor(provider_name, merchant_account_id.clone())?;
 
     let token = if use_stored_payment {
         info!("CART: Using stored payment");
         match auth_user
             .user
-            .payment_method(provider_name.to_string(), connection)
+            .payment_method(
+                provider_name.to_string(),
+                merchant_account_id.clone(),
+                connection,
+            )
             .optional()?
         {
             Some(payment_method) => payment_method.provider,
@@ -490,7 +699,11 @@ fn checkout_payment_processor(
             info!("CART: User has requested to save the payment method");
             match auth_user
                 .user
-                .payment_method(provider_name.to_string(), connection)
+                .payment_method(
+                    provider_name.to_string(),
+                    merchant_account_id.clone(),
+                    connection,
+                )
                 .optional()?
             {
                 Some(payment_method) => {
@@ -518,6 +731,7 @@ fn checkout_payment_processor(
                         provider_name.to_string(),
                         set_default,
                         repeat_token.token.clone(),
+                        merchant_account_id.clone(),
                         repeat_token.to_json()?,
                     )
                     .commit(auth_user.id(), connection)?;
@@ -532,22 +746,30 @@ fn checkout_payment_processor(
     info!("CART: Auth'ing to payment provider");
     let auth_result = client.auth(
         &token,
-        req.amount,
+        amount,
         currency,
         "Tickets from Bigneon",
         vec![("order_id".to_string(), order.id.to_string())],
     )?;
 
     info!("CART: Saving payment to order");
-    let payment = match order.add_credit_card_payment(
-        auth_user.id(),
-        req.amount,
-        provider_name.to_string(),
-        auth_result.id.clone(),
-        PaymentStatus::Authorized,
-        auth_result.to_json()?,
-        connection,
-    ) {
+    let payment = match order
+        .add_credit_card_payment(
+            auth_user.id(),
+            amount,
+            provider_name.to_string(),
+            merchant_account_id,
+            auth_result.id.clone(),
+            PaymentStatus::Authorized,
+            auth_result.to_json()?,
+            connection,
+        )
+        .and_then(|payment| match order_share {
+            Some(order_share) => order_share
+                .mark_paid(auth_user.id(), payment.id, connection)
+                .map(|_| payment),
+            None => Ok(payment),
+        }) {
         Ok(p) => p,
         Err(e) => {
             client.refund(&auth_result.id)?;
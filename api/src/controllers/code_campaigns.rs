@@ -0,0 +1,132 @@
+use actix_web::{HttpResponse, Path, Query};
+use auth::user::User;
+use bigneon_db::models::*;
+use chrono::prelude::*;
+use db::Connection;
+use errors::BigNeonError;
+use extractors::*;
+use helpers::application;
+use models::PathParameters;
+use utils::csv;
+use uuid::Uuid;
+
+#[derive(Deserialize, Serialize)]
+pub struct CreateCodeCampaignRequest {
+    pub name: String,
+    pub quantity: u32,
+    pub code_type: CodeTypes,
+    pub discount_in_cents: Option<u32>,
+    pub discount_as_percentage: Option<u32>,
+    pub buy_quantity: Option<u32>,
+    pub get_quantity: Option<u32>,
+    pub minimum_order_total_in_cents: Option<u32>,
+    pub start_date: NaiveDateTime,
+    pub end_date: NaiveDateTime,
+    pub max_tickets_per_user: Option<u32>,
+    pub ticket_type_ids: Vec<Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct CodeCampaignParameters {
+    pub format: Option<String>,
+}
+
+pub fn index(
+    (conn, path, user): (Connection, Path<PathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = conn.get();
+    user.requires_scope_for_organization(
+        Scopes::CodeRead,
+        &Organization::find_for_event(path.id, conn)?,
+        conn,
+    )?;
+
+    Ok(HttpResponse::Ok().json(CodeCampaign::find_for_event(path.id, conn)?))
+}
+
+/// Creates the campaign along with `quantity` single use codes that share its settings
+pub fn create(
+    (conn, req, path, user): (
+        Connection,
+        Json<CreateCodeCampaignRequest>,
+        Path<PathParameters>,
+        User,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = conn.get();
+    let event = Event::find(path.id, conn)?;
+    user.requires_scope_for_organization(Scopes::CodeWrite, &event.organization(conn)?, conn)?;
+
+    let campaign = CodeCampaign::create(event.id, req.name.clone()).commit(conn)?;
+    let template = Code::create(
+        req.name.clone(),
+        event.id,
+        req.code_type,
+        String::new(),
+        1,
+        req.discount_in_cents,
+        req.discount_as_percentage,
+        req.buy_quantity,
+        req.get_quantity,
+        req.minimum_order_total_in_cents,
+        req.start_date,
+        req.end_date,
+        req.max_tickets_per_user,
+    );
+    campaign.generate_codes(req.quantity, template, &req.ticket_type_ids, conn)?;
+
+    application::created(json!(campaign.for_display(conn)?))
+}
+
+/// Usage of each code in the campaign, as JSON or as a CSV export when `format=csv`
+pub fn show(
+    (conn, path, query, user): (
+        Connection,
+        Path<PathParameters>,
+        Query<CodeCampaignParameters>,
+        User,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = conn.get();
+    let campaign = CodeCampaign::find(path.id, conn)?;
+    user.requires_scope_for_organization(Scopes::CodeRead, &campaign.organization(conn)?, conn)?;
+
+    let display_campaign = campaign.for_display(conn)?;
+    if query.format.as_ref().map(|f| f.as_str()) == Some("csv") {
+        let rows: Vec<Vec<String>> = display_campaign
+            .codes
+            .iter()
+            .map(|c| {
+                vec![
+                    c.redemption_code.clone(),
+                    c.max_uses.to_string(),
+                    c.uses.to_string(),
+                    c.tickets_sold.to_string(),
+                    c.last_used_at.map(|d| d.to_string()).unwrap_or_default(),
+                ]
+            })
+            .collect();
+        let body = csv::write(
+            &[
+                "redemption_code",
+                "max_uses",
+                "uses",
+                "tickets_sold",
+                "last_used_at",
+            ],
+            &rows,
+        );
+        return Ok(HttpResponse::Ok()
+            .content_type(csv::CSV_CONTENT_TYPE)
+            .header(
+                "Content-Disposition",
+                format!(
+                    "attachment; filename=\"{}.csv\"",
+                    campaign.name.replace("\"", "")
+                ),
+            )
+            .body(body));
+    }
+
+    Ok(HttpResponse::Ok().json(display_campaign))
+}
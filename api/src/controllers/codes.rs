@@ -17,6 +17,10 @@ pub struct CreateCodeRequest {
     pub code_type: CodeTypes,
     pub max_uses: u32,
     pub discount_in_cents: Option<u32>,
+    pub discount_as_percentage: Option<u32>,
+    pub buy_quantity: Option<u32>,
+    pub get_quantity: Option<u32>,
+    pub minimum_order_total_in_cents: Option<u32>,
     pub start_date: NaiveDateTime,
     pub end_date: NaiveDateTime,
     pub max_tickets_per_user: Option<u32>,
@@ -30,6 +34,14 @@ pub struct UpdateCodeRequest {
     pub max_uses: Option<i64>,
     #[serde(default, deserialize_with = "double_option::deserialize")]
     pub discount_in_cents: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "double_option::deserialize")]
+    pub discount_as_percentage: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "double_option::deserialize")]
+    pub buy_quantity: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "double_option::deserialize")]
+    pub get_quantity: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "double_option::deserialize")]
+    pub minimum_order_total_in_cents: Option<Option<u32>>,
     pub start_date: Option<NaiveDateTime>,
     pub end_date: Option<NaiveDateTime>,
     #[serde(default, deserialize_with = "double_option::deserialize")]
@@ -44,6 +56,14 @@ impl From<UpdateCodeRequest> for UpdateCodeAttributes {
             redemption_code: attributes.redemption_code,
             max_uses: attributes.max_uses.map(|m| m as i64),
             discount_in_cents: attributes.discount_in_cents.map(|d| d.map(|d2| d2 as i64)),
+            discount_as_percentage: attributes
+                .discount_as_percentage
+                .map(|d| d.map(|d2| d2 as i64)),
+            buy_quantity: attributes.buy_quantity.map(|q| q.map(|q2| q2 as i64)),
+            get_quantity: attributes.get_quantity.map(|q| q.map(|q2| q2 as i64)),
+            minimum_order_total_in_cents: attributes
+                .minimum_order_total_in_cents
+                .map(|m| m.map(|m2| m2 as i64)),
             start_date: attributes.start_date,
             end_date: attributes.end_date,
             max_tickets_per_user: attributes
@@ -75,18 +95,22 @@ pub fn create(
     let event = Event::find(path.id, conn)?;
     user.requires_scope_for_organization(Scopes::CodeWrite, &event.organization(conn)?, conn)?;
 
-    let code = Code::create(
+    let new_code = Code::create(
         req.name.clone(),
         path.id,
         req.code_type,
         req.redemption_code.clone(),
         req.max_uses,
         req.discount_in_cents,
+        req.discount_as_percentage,
+        req.buy_quantity,
+        req.get_quantity,
+        req.minimum_order_total_in_cents,
         req.start_date,
         req.end_date,
         req.max_tickets_per_user,
-    )
-    .commit(conn)?;
+    );
+    let code = new_code.commit(conn)?;
 
     code.update_ticket_types(req.ticket_type_ids.clone(), conn)?;
     application::created(json!(code.for_display(conn)?))
@@ -1,12 +1,20 @@
-use actix_web::{http::StatusCode, HttpResponse, Path, Query};
+use actix_web::{http::StatusCode, HttpResponse, Path, Query, State};
 use auth::user::User;
 use bigneon_db::models::*;
 use chrono::prelude::*;
+use communications::{mailers, smsers};
+use config::Config;
 use controllers::holds::UpdateHoldRequest;
 use db::Connection;
-use errors::BigNeonError;
+use diesel::PgConnection;
+use errors::*;
 use extractors::*;
+use helpers::application;
 use models::{PathParameters, WebPayload, WebResult};
+use server::AppState;
+use std::collections::HashMap;
+use utils::csv;
+use uuid::Uuid;
 
 pub fn index(
     (conn, path, query_parameters, user): (
@@ -63,8 +71,20 @@ pub struct NewCompRequest {
     pub max_per_order: Option<u32>,
 }
 
+#[derive(Deserialize, Serialize)]
+pub struct ClaimCompRequest {
+    pub claim_key: Uuid,
+}
+
+/// Creates a comp and sends its claim link if it has an email or phone number
 pub fn create(
-    (conn, new_comp, path, user): (Connection, Json<NewCompRequest>, Path<PathParameters>, User),
+    (conn, new_comp, path, user, state): (
+        Connection,
+        Json<NewCompRequest>,
+        Path<PathParameters>,
+        User,
+        State<AppState>,
+    ),
 ) -> Result<WebResult<DisplayHold>, BigNeonError> {
     let conn = conn.get();
     let hold = Hold::find(path.id, conn)?;
@@ -79,8 +99,14 @@ pub fn create(
         new_comp.end_at,
         new_comp.max_per_order,
         new_comp.quantity,
+        Some(user.id()),
         conn,
     )?;
+    let comp = if comp.email.is_some() || comp.phone.is_some() {
+        deliver(&state.config, &comp, user.id(), conn)?
+    } else {
+        comp
+    };
 
     Ok(WebResult::new(
         StatusCode::CREATED,
@@ -88,6 +114,75 @@ pub fn create(
     ))
 }
 
+/// Creates comps in the hold from a CSV guest list with `name`, `email`, `phone` and `quantity`
+/// columns. No comps are created if any row is invalid, and the errors for each row are returned.
+pub fn import(
+    (conn, body, path, user, state): (
+        Connection,
+        String,
+        Path<PathParameters>,
+        User,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = conn.get();
+    let hold = Hold::find(path.id, conn)?;
+    user.requires_scope_for_organization(Scopes::CompWrite, &hold.organization(conn)?, conn)?;
+
+    let mut rows = csv::parse(&body).into_iter();
+    let headers: Vec<String> = match rows.next() {
+        Some(headers) => headers.iter().map(|h| h.trim().to_lowercase()).collect(),
+        None => return application::unprocessable("CSV is empty"),
+    };
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let (name_column, quantity_column) = match (column("name"), column("quantity")) {
+        (Some(name_column), Some(quantity_column)) => (name_column, quantity_column),
+        _ => return application::unprocessable("CSV must have name and quantity columns"),
+    };
+    let email_column = column("email");
+    let phone_column = column("phone");
+
+    let recipients: Vec<CompRecipient> = rows
+        .map(|row| {
+            let field = |index: Option<usize>| {
+                index
+                    .and_then(|i| row.get(i))
+                    .map(|f| f.trim().to_string())
+                    .filter(|f| !f.is_empty())
+            };
+            CompRecipient {
+                name: field(Some(name_column)).unwrap_or_default(),
+                email: field(email_column),
+                phone: field(phone_column),
+                // Quantities that are not numbers are reported as less than one
+                quantity: field(Some(quantity_column))
+                    .and_then(|q| q.parse().ok())
+                    .unwrap_or(0),
+            }
+        })
+        .collect();
+    if recipients.is_empty() {
+        return application::unprocessable("CSV has no comps");
+    }
+
+    match hold.import_comps(recipients, Some(user.id()), conn)? {
+        Ok(comps) => {
+            let mut list = Vec::<DisplayHold>::new();
+            for comp in comps {
+                let comp = if comp.email.is_some() || comp.phone.is_some() {
+                    deliver(&state.config, &comp, user.id(), conn)?
+                } else {
+                    comp
+                };
+                list.push(comp.into_display(conn)?);
+            }
+            Ok(HttpResponse::Created().json(list))
+        }
+        Err(row_errors) => Ok(HttpResponse::UnprocessableEntity()
+            .json(json!({"error": "Invalid comps".to_string(), "rows": row_errors}))),
+    }
+}
+
 pub fn update(
     (conn, req, path, user): (
         Connection,
@@ -122,3 +217,100 @@ pub fn destroy(
     comp.destroy(&*conn)?;
     Ok(HttpResponse::Ok().json(json!({})))
 }
+
+/// Sends (or resends) the comp's claim link to its recipient
+pub fn send(
+    (conn, path, user, state): (Connection, Path<PathParameters>, User, State<AppState>),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = conn.get();
+    let comp = Hold::find(path.id, conn)?;
+    user.requires_scope_for_organization(Scopes::CompWrite, &comp.organization(conn)?, conn)?;
+
+    let comp = deliver(&state.config, &comp, user.id(), conn)?;
+    Ok(HttpResponse::Ok().json(comp.into_display(conn)?))
+}
+
+/// Claims a comp for the current user, transferring its tickets from the user who sent it
+pub fn claim(
+    (conn, req, user, state): (Connection, Json<ClaimCompRequest>, User, State<AppState>),
+) -> Result<HttpResponse, BigNeonError> {
+    user.requires_scope(Scopes::TicketTransfer)?;
+    let conn = conn.get();
+    let comp = Hold::find_by_claim_key(req.claim_key, conn)?;
+    let (sender_wallet, tickets) = comp.claim(&user.user, conn)?;
+
+    let receiver_wallet = Wallet::find_default_for_user(user.id(), conn)?;
+    transfer_tokens(
+        &state.config,
+        &sender_wallet,
+        &receiver_wallet,
+        &tickets,
+        "Could not claim comp because the asset has not been assigned on the blockchain",
+        conn,
+    )?;
+
+    Ok(HttpResponse::Ok().json(&tickets))
+}
+
+fn deliver(
+    config: &Config,
+    comp: &Hold,
+    sender_id: Uuid,
+    conn: &PgConnection,
+) -> Result<Hold, BigNeonError> {
+    let (comp, issued_tickets) = comp.prepare_claim(sender_id, conn)?;
+    if !issued_tickets.is_empty() {
+        let org_wallet = Wallet::find_default_for_organization(comp.organization(conn)?.id, conn)?;
+        let sender_wallet = Wallet::find_default_for_user(sender_id, conn)?;
+        transfer_tokens(
+            config,
+            &org_wallet,
+            &sender_wallet,
+            &issued_tickets,
+            "Could not send comp because the asset has not been assigned on the blockchain",
+            conn,
+        )?;
+    }
+
+    let event = Event::find(comp.event_id, conn)?;
+    if let Some(ref email) = comp.email {
+        mailers::holds::comp_claim(config, email.clone(), &comp, &event, conn)?;
+    }
+    if let Some(ref phone) = comp.phone {
+        smsers::holds::comp_claim(config, phone.clone(), &comp, &event, conn)?;
+    }
+    Ok(comp)
+}
+
+/// Transfers the tickets on chain in batches per asset
+fn transfer_tokens(
+    config: &Config,
+    from_wallet: &Wallet,
+    to_wallet: &Wallet,
+    tickets: &[TicketInstance],
+    unassigned_asset_error: &str,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let mut tokens_per_asset: HashMap<Uuid, Vec<u64>> = HashMap::new();
+    for ticket in tickets {
+        tokens_per_asset
+            .entry(ticket.asset_id)
+            .or_insert_with(|| Vec::new())
+            .push(ticket.token_id as u64);
+    }
+
+    for (asset_id, token_ids) in &tokens_per_asset {
+        let asset = Asset::find(*asset_id, conn)?;
+        match asset.blockchain_asset_id {
+            Some(a) => config.tari_client.transfer_tokens(
+                &from_wallet.secret_key,
+                &from_wallet.public_key,
+                &a,
+                token_ids.clone(),
+                to_wallet.public_key.clone(),
+            )?,
+            None => return Err(ApplicationError::new(unassigned_asset_error.to_string()).into()),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use actix_web::{HttpResponse, Path, State};
+use auth::user::User;
+use bigneon_db::models::*;
+use bigneon_db::utils::errors::DatabaseError;
+use db::Connection;
+use errors::*;
+use extractors::*;
+use helpers::assets;
+use models::PathParameters;
+use server::AppState;
+use uuid::Uuid;
+
+#[derive(Deserialize, Serialize)]
+pub struct CreateEventSeriesRequest {
+    pub frequency: RecurrenceFrequency,
+    pub interval: Option<i32>,
+    pub occurrence_count: i32,
+}
+
+pub fn create(
+    (connection, path, json, user, state): (
+        Connection,
+        Path<PathParameters>,
+        Json<CreateEventSeriesRequest>,
+        User,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event = Event::find(path.id, connection)?;
+    let organization = event.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::EventWrite, &organization, connection)?;
+
+    let series = EventSeries::create_from_event(
+        &event,
+        json.frequency.clone(),
+        json.interval.unwrap_or(1),
+        json.occurrence_count,
+        connection,
+    )?;
+    for occurrence in series.occurrences(connection)? {
+        assets::create_missing_blockchain_assets(&occurrence, &state.config, connection)?;
+    }
+
+    Ok(HttpResponse::Created().json(&series.for_display(connection)?))
+}
+
+pub fn show(
+    (connection, path, user): (Connection, Path<PathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let series = EventSeries::find(path.id, connection)?;
+    let organization = series.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::EventWrite, &organization, connection)?;
+
+    Ok(HttpResponse::Ok().json(&series.for_display(connection)?))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateEventSeriesRequest {
+    /// Occurrence whose ticket types and pricing are copied to the future occurrences
+    pub ticket_types_from_event_id: Option<Uuid>,
+    #[serde(flatten)]
+    pub event: EventEditableAttributes,
+}
+
+/// Propagates the changes to all future occurrences of the series
+pub fn update(
+    (connection, path, json, user, state): (
+        Connection,
+        Path<PathParameters>,
+        Json<UpdateEventSeriesRequest>,
+        User,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let series = EventSeries::find(path.id, connection)?;
+    let organization = series.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::EventWrite, &organization, connection)?;
+
+    let json = json.into_inner();
+    let mut updated_events =
+        series.update_future_occurrences(Some(user.id()), json.event, connection)?;
+    if let Some(event_id) = json.ticket_types_from_event_id {
+        let event = Event::find(event_id, connection)?;
+        for occurrence in series.update_future_ticket_types(&event, connection)? {
+            assets::create_missing_blockchain_assets(&occurrence, &state.config, connection)?;
+        }
+        // Reloaded for the ticket price ranges
+        updated_events = updated_events
+            .iter()
+            .map(|e| Event::find(e.id, connection))
+            .collect::<Result<Vec<Event>, DatabaseError>>()?;
+    }
+    Ok(HttpResponse::Ok().json(&updated_events))
+}
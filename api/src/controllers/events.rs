@@ -7,7 +7,7 @@ use chrono::Duration;
 use db::Connection;
 use errors::*;
 use extractors::*;
-use helpers::application;
+use helpers::{application, assets};
 use models::{PathParameters, RedeemTicketPathParameters, UserDisplayTicketType, WebPayload};
 use serde_json::Value;
 use serde_with::{self, CommaSeparator};
@@ -35,6 +35,7 @@ pub struct SearchParameters {
     sort: Option<String>,
     dir: Option<SortingDir>,
     past_or_upcoming: Option<String>,
+    group_by_series: Option<bool>,
 }
 
 impl From<SearchParameters> for Paging {
@@ -59,6 +60,9 @@ impl From<SearchParameters> for Paging {
         if let Some(ref i) = s.end_utc {
             default_tags.insert("end_utc".to_owned(), json!(i));
         }
+        if let Some(ref i) = s.group_by_series {
+            default_tags.insert("group_by_series".to_owned(), json!(i));
+        }
 
         PagingParameters {
             page: s.page,
@@ -137,6 +141,12 @@ pub fn index(
         past_or_upcoming,
         connection,
     )?;
+    // Occurrences of a series are represented by the first one matching the search
+    let events = if query.group_by_series.unwrap_or(false) {
+        Event::group_by_series(events)
+    } else {
+        events
+    };
 
     #[derive(Serialize)]
     struct EventVenueEntry {
@@ -162,6 +172,7 @@ pub fn index(
         user_is_interested: bool,
         localized_times: EventLocalizedTimeStrings,
         tracking_keys: TrackingKeys,
+        event_series_id: Option<Uuid>,
     }
 
     let mut venue_ids: Vec<Uuid> = events
@@ -233,6 +244,7 @@ pub fn index(
                 .unwrap_or(false),
             localized_times,
             tracking_keys,
+            event_series_id: event.event_series_id,
         });
         results
     });
@@ -444,6 +456,9 @@ pub fn unpublish(
 #[derive(Deserialize, Serialize, Debug)]
 pub struct TicketRedeemRequest {
     pub redeem_key: String,
+    pub direction: Option<TicketScanDirections>,
+    pub device_id: Option<String>,
+    pub gate_name: Option<String>,
 }
 
 pub fn redeem_ticket(
@@ -468,11 +483,22 @@ pub fn redeem_ticket(
     let redeemable =
         TicketInstance::show_redeemable_ticket(parameters.ticket_instance_id, connection)?;
 
-    let result =
-        TicketInstance::redeem_ticket(ticket.id, redeem_parameters.redeem_key.clone(), connection)?;
+    let redeem_parameters = redeem_parameters.into_inner();
+    let scan = TicketScan::scan(
+        db_event.id,
+        ticket.id,
+        redeem_parameters.redeem_key,
+        redeem_parameters
+            .direction
+            .unwrap_or(TicketScanDirections::In),
+        Some(auth_user.id()),
+        redeem_parameters.device_id,
+        redeem_parameters.gate_name,
+        connection,
+    )?;
 
-    match result {
-        RedeemResults::TicketRedeemSuccess => {
+    match scan.result {
+        TicketScanResults::Redeemed => {
             //Redeem ticket on chain
             let asset = Asset::find(ticket.asset_id, connection)?;
             match asset.blockchain_asset_id {
@@ -488,14 +514,101 @@ pub fn redeem_ticket(
                 None => Ok(HttpResponse::BadRequest().json(json!({ "error": "Could not complete this checkout because the asset has not been assigned on the blockchain.".to_string()}))),
             }
         }
-        RedeemResults::TicketAlreadyRedeemed => Ok(HttpResponse::Conflict()
+        TicketScanResults::ReEntered | TicketScanResults::ScannedOut => {
+            Ok(HttpResponse::Ok().json(redeemable))
+        }
+        TicketScanResults::AlreadyRedeemed => Ok(HttpResponse::Conflict()
             .json(json!({"error": "Ticket has already been redeemed.".to_string()}))),
-        RedeemResults::TicketInvalid => {
+        TicketScanResults::Invalid => {
             Ok(HttpResponse::BadRequest().json(json!({"error": "Ticket is invalid.".to_string()})))
         }
     }
 }
 
+pub fn scans(
+    (connection, path, query, user): (
+        Connection,
+        Path<PathParameters>,
+        Query<PagingParameters>,
+        User,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event = Event::find(path.id, connection)?;
+    let organization = event.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::EventScan, &organization, connection)?;
+
+    let scans = TicketScan::find_for_event(event.id, query.page(), query.limit(), connection)?;
+    Ok(HttpResponse::Ok().json(&scans))
+}
+
+/// Public key and revocation list for scanner devices that verify signed tickets offline
+pub fn offline_scanning(
+    (connection, path, user): (Connection, Path<PathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event = Event::find(path.id, connection)?;
+    let organization = event.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::RedeemTicket, &organization, connection)?;
+
+    let signing_key = EventSigningKey::find_or_create_for_event(event.id, connection)?;
+    Ok(HttpResponse::Ok().json(&signing_key.offline_scanning_data(connection)?))
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct OfflineScansRequest {
+    pub scans: Vec<OfflineScan>,
+    pub device_id: Option<String>,
+    pub gate_name: Option<String>,
+}
+
+pub fn upload_offline_scans(
+    (connection, path, json, user, state): (
+        Connection,
+        Path<PathParameters>,
+        Json<OfflineScansRequest>,
+        User,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event = Event::find(path.id, connection)?;
+    let organization = event.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::RedeemTicket, &organization, connection)?;
+
+    let signing_key = EventSigningKey::find_or_create_for_event(event.id, connection)?;
+    let json = json.into_inner();
+    let results = signing_key.redeem_offline_scans(
+        json.scans,
+        Some(user.id()),
+        json.device_id,
+        json.gate_name,
+        connection,
+    )?;
+
+    //Redeem tickets on chain
+    for result in &results {
+        if result.result != OfflineScanResults::Redeemed {
+            continue;
+        }
+        if let Some(ticket_instance_id) = result.ticket_instance_id {
+            let ticket = TicketInstance::find(ticket_instance_id, connection)?;
+            let asset = Asset::find(ticket.asset_id, connection)?;
+            if let Some(blockchain_asset_id) = asset.blockchain_asset_id {
+                let wallet = Wallet::find(ticket.wallet_id, connection)?;
+                state.config.tari_client.modify_asset_redeem_token(
+                    &wallet.secret_key,
+                    &wallet.public_key,
+                    &blockchain_asset_id,
+                    vec![ticket.token_id as u64],
+                )?;
+            }
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(&results))
+}
+
 pub fn show_from_organizations(
     (connection, path, paging, user): (
         Connection,
@@ -617,7 +730,7 @@ pub fn update(
     let organization = event.organization(connection)?;
     user.requires_scope_for_organization(Scopes::EventWrite, &organization, connection)?;
 
-    let updated_event = event.update(event_parameters.into_inner(), connection)?;
+    let updated_event = event.update(Some(user.id()), event_parameters.into_inner(), connection)?;
     Ok(HttpResponse::Ok().json(&updated_event))
 }
 
@@ -630,11 +743,59 @@ pub fn cancel(
     user.requires_scope_for_organization(Scopes::EventWrite, &organization, connection)?;
 
     //Doing this in the DB layer so it can use the DB time as now.
-    let updated_event = event.cancel(connection)?;
+    let updated_event = event.cancel(user.id(), connection)?;
 
     Ok(HttpResponse::Ok().json(&updated_event))
 }
 
+pub fn cancellations(
+    (connection, path, query, user): (
+        Connection,
+        Path<PathParameters>,
+        Query<PagingParameters>,
+        User,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event = Event::find(path.id, connection)?;
+    let organization = event.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::EventWrite, &organization, connection)?;
+
+    let mut cancellations = Vec::new();
+    for cancellation in EventCancellation::find_for_event(event.id, connection)? {
+        cancellations.push(cancellation.for_display(connection)?);
+    }
+
+    Ok(HttpResponse::Ok().json(&Payload::new(cancellations, query.into_inner().into())))
+}
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct CloneEventRequest {
+    pub event_start: Option<NaiveDateTime>,
+}
+
+/// Copies the event into a new draft. Dates are shifted relative to the new event start when one
+/// is given.
+pub fn clone(
+    (connection, path, json, user, state): (
+        Connection,
+        Path<PathParameters>,
+        Json<CloneEventRequest>,
+        User,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event = Event::find(path.id, connection)?;
+    let organization = event.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::EventWrite, &organization, connection)?;
+
+    let new_event = event.duplicate(json.event_start, EventStatus::Draft, None, connection)?;
+    assets::create_missing_blockchain_assets(&new_event, &state.config, connection)?;
+
+    Ok(HttpResponse::Created().json(&new_event))
+}
+
 pub fn list_interested_users(
     (connection, path_parameters, query, user): (
         Connection,
@@ -832,6 +993,7 @@ pub fn holds(
         pub event_id: Uuid,
         pub redemption_code: String,
         pub discount_in_cents: Option<i64>,
+        pub discount_as_percentage: Option<i64>,
         pub end_at: Option<NaiveDateTime>,
         pub max_per_order: Option<i64>,
         pub hold_type: HoldTypes,
@@ -850,6 +1012,7 @@ pub fn holds(
             event_id: hold.event_id,
             redemption_code: hold.redemption_code,
             discount_in_cents: hold.discount_in_cents,
+            discount_as_percentage: hold.discount_as_percentage,
             end_at: hold.end_at,
             max_per_order: hold.max_per_order,
             hold_type: hold.hold_type,
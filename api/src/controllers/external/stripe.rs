@@ -0,0 +1,80 @@
+use actix_web::{HttpRequest, HttpResponse};
+use bigneon_db::models::Payment;
+use db::Connection;
+use errors::*;
+use helpers::application;
+use server::AppState;
+use stripe::WebhookEvent;
+
+const STRIPE_SIGNATURE_HEADER: &str = "Stripe-Signature";
+const STRIPE_PROVIDER: &str = "stripe";
+
+pub fn webhook(
+    (connection, request, body): (Connection, HttpRequest<AppState>, String),
+) -> Result<HttpResponse, BigNeonError> {
+    let webhook_secret = match request.state().config.stripe_webhook_secret {
+        Some(ref secret) => secret.clone(),
+        None => return application::unprocessable("Stripe webhooks are not enabled"),
+    };
+    let signature = match request
+        .headers()
+        .get(STRIPE_SIGNATURE_HEADER)
+        .and_then(|s| s.to_str().ok())
+    {
+        Some(s) => s.to_string(),
+        None => {
+            return application::unauthorized_with_message("Missing Stripe signature", None, None);
+        }
+    };
+    let event = match WebhookEvent::construct(&body, &signature, &webhook_secret) {
+        Ok(event) => event,
+        Err(e) => {
+            warn!("Rejected Stripe webhook: {}", e);
+            return application::unauthorized_with_message("Invalid Stripe signature", None, None);
+        }
+    };
+
+    info!("Stripe webhook {} received: {}", event.id, event.event_type);
+    let connection = connection.get();
+
+    // Disputes reference the charge, all other events are charges themselves
+    let charge_id = match event.event_type.as_str() {
+        "charge.dispute.created" => event.object_str("charge"),
+        _ => event.object_str("id"),
+    };
+    let payment = match charge_id {
+        Some(charge_id) => {
+            Payment::find_by_external_reference(STRIPE_PROVIDER, charge_id, connection)?
+        }
+        None => None,
+    };
+    // Stripe retries deliveries until it receives a 2xx, so unknown charges are acknowledged
+    let payment = match payment {
+        Some(payment) => payment,
+        None => return Ok(HttpResponse::Ok().finish()),
+    };
+
+    match event.event_type.as_str() {
+        "charge.refunded" => {
+            // amount_refunded is cumulative, only record what we have not seen yet
+            let amount_refunded = event.object_i64("amount_refunded").unwrap_or(0);
+            let unrecorded = amount_refunded - payment.refunded_amount(connection)?;
+            if unrecorded > 0 {
+                payment.log_external_refund(
+                    unrecorded as u32,
+                    Some(event.data.object.clone()),
+                    connection,
+                )?;
+            }
+        }
+        "charge.dispute.created" => {
+            payment.mark_disputed(event.data.object.clone(), connection)?;
+        }
+        "charge.failed" => {
+            payment.mark_failed(event.data.object.clone(), connection)?;
+        }
+        _ => {}
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
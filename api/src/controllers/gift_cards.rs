@@ -0,0 +1,31 @@
+use actix_web::HttpResponse;
+use auth::user::User;
+use bigneon_db::models::*;
+use db::Connection;
+use errors::*;
+use extractors::*;
+
+#[derive(Deserialize, Serialize)]
+pub struct RedeemGiftCardRequest {
+    pub redemption_code: String,
+}
+
+/// Gift cards bought by the current user along with their redemption codes
+pub fn index((connection, user): (Connection, User)) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let gift_cards = GiftCard::find_purchased_by_user(user.id(), connection)?;
+    Ok(HttpResponse::Ok().json(gift_cards))
+}
+
+/// Redeems a gift card, adding its value to the current user's account credit
+pub fn redeem(
+    (connection, json, user): (Connection, Json<RedeemGiftCardRequest>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let gift_card = GiftCard::find_by_redemption_code(&json.redemption_code, connection)?;
+    gift_card.redeem(&user.user, connection)?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "credit_balance_in_cents": CreditTransaction::balance_for_user(user.id(), connection)?
+    })))
+}
@@ -15,6 +15,7 @@ pub struct CreateHoldRequest {
     pub name: String,
     pub redemption_code: String,
     pub discount_in_cents: Option<u32>,
+    pub discount_as_percentage: Option<u32>,
     pub hold_type: HoldTypes,
     pub quantity: u32,
     pub ticket_type_id: Uuid,
@@ -30,6 +31,8 @@ pub struct UpdateHoldRequest {
     pub quantity: Option<u32>,
     #[serde(default, deserialize_with = "double_option::deserialize")]
     pub discount_in_cents: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "double_option::deserialize")]
+    pub discount_as_percentage: Option<Option<i64>>,
     #[serde(default, deserialize_with = "double_option_deserialize_unless_blank")]
     pub email: Option<Option<String>>,
     #[serde(default, deserialize_with = "double_option_deserialize_unless_blank")]
@@ -46,6 +49,7 @@ impl From<UpdateHoldRequest> for UpdateHoldAttributes {
             name: attributes.name,
             hold_type: attributes.hold_type.and_then(|hold_type| Some(hold_type)),
             discount_in_cents: attributes.discount_in_cents,
+            discount_as_percentage: attributes.discount_as_percentage,
             email: attributes.email,
             phone: attributes.phone,
             end_at: attributes.end_at,
@@ -74,10 +78,12 @@ pub fn create(
         path.id,
         req.redemption_code.clone(),
         req.discount_in_cents,
+        req.discount_as_percentage,
         req.end_at,
         req.max_per_order,
         req.hold_type,
         req.ticket_type_id,
+        Some(user.id()),
     )
     .commit(conn)?;
 
@@ -90,6 +96,7 @@ pub fn create(
         pub event_id: Uuid,
         pub redemption_code: String,
         pub discount_in_cents: Option<i64>,
+        pub discount_as_percentage: Option<i64>,
         pub end_at: Option<NaiveDateTime>,
         pub max_per_order: Option<i64>,
         pub hold_type: HoldTypes,
@@ -106,6 +113,7 @@ pub fn create(
         event_id: hold.event_id,
         redemption_code: hold.redemption_code,
         discount_in_cents: hold.discount_in_cents,
+        discount_as_percentage: hold.discount_as_percentage,
         end_at: hold.end_at,
         max_per_order: hold.max_per_order,
         hold_type: hold.hold_type,
@@ -152,6 +160,7 @@ pub fn show(
         pub event_id: Uuid,
         pub redemption_code: String,
         pub discount_in_cents: Option<i64>,
+        pub discount_as_percentage: Option<i64>,
         pub end_at: Option<NaiveDateTime>,
         pub max_per_order: Option<i64>,
         pub hold_type: HoldTypes,
@@ -168,6 +177,7 @@ pub fn show(
         event_id: hold.event_id,
         redemption_code: hold.redemption_code,
         discount_in_cents: hold.discount_in_cents,
+        discount_as_percentage: hold.discount_as_percentage,
         end_at: hold.end_at,
         max_per_order: hold.max_per_order,
         hold_type: hold.hold_type,
@@ -189,6 +199,7 @@ pub struct SplitHoldRequest {
     pub name: String,
     pub redemption_code: String,
     pub discount_in_cents: Option<u32>,
+    pub discount_as_percentage: Option<u32>,
 
     pub hold_type: HoldTypes,
     pub quantity: u32,
@@ -212,6 +223,7 @@ pub fn split(
         req.redemption_code.clone(),
         req.quantity,
         req.discount_in_cents,
+        req.discount_as_percentage,
         req.hold_type,
         req.end_at,
         req.max_per_order,
@@ -1,11 +1,15 @@
 pub mod artists;
 pub mod auth;
 pub mod cart;
+pub mod code_campaigns;
 pub mod codes;
 pub mod comps;
+pub mod event_series;
 pub mod events;
 pub mod external;
+pub mod gift_cards;
 pub mod holds;
+pub mod order_shares;
 pub mod orders;
 pub mod organization_invites;
 pub mod organizations;
@@ -14,9 +18,15 @@ pub mod payment_methods;
 pub mod redemption_codes;
 pub mod regions;
 pub mod reports;
+pub mod resale_listings;
+pub mod resale_payouts;
+pub mod seat_sections;
 pub mod stages;
+pub mod tax_rules;
 pub mod ticket_types;
 pub mod tickets;
 pub mod user_invites;
 pub mod users;
 pub mod venues;
+pub mod waitlist_entries;
+pub mod wallet_passes;
@@ -0,0 +1,266 @@
+use actix_web::{http::StatusCode, HttpResponse, Path, State};
+use auth::user::User;
+use bigneon_db::models::User as DbUser;
+use bigneon_db::models::*;
+use bigneon_db::utils::errors::Optional;
+use communications::mailers;
+use config::Config;
+use controllers::cart::{self, PaymentRequest};
+use db::Connection;
+use diesel::PgConnection;
+use errors::*;
+use extractors::*;
+use helpers::application;
+use models::PathParameters;
+use server::AppState;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Serialize)]
+pub struct DisplayOrderShare {
+    #[serde(flatten)]
+    pub share: OrderShare,
+    pub link: String,
+}
+
+impl DisplayOrderShare {
+    pub fn from_share(share: OrderShare, config: &Config) -> DisplayOrderShare {
+        let link = format!("{}/order_shares/{}", config.front_end_url, share.share_key);
+        DisplayOrderShare { share, link }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct CheckoutOrderShareRequest {
+    pub method: PaymentRequest,
+}
+
+/// Shows the share with the given share key along with the order it is for
+pub fn show(
+    (connection, path, _user): (Connection, Path<PathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let share = OrderShare::find_by_share_key(path.id, connection)?;
+    let order = share.order(connection)?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "share": share,
+        "order": order.for_display(connection)?,
+    })))
+}
+
+/// Pays for the share with the given share key. Once every share is paid the tickets are moved
+/// into the wallets of the users who paid for them.
+pub fn checkout(
+    (connection, path, json, user, state): (
+        Connection,
+        Path<PathParameters>,
+        Json<CheckoutOrderShareRequest>,
+        User,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let conn = connection.get();
+    let share = OrderShare::find_by_share_key(path.id, conn)?;
+    if share.paid_at.is_some() {
+        return application::unprocessable("This share has already been paid");
+    }
+    let mut order = share.order(conn)?;
+    if order.status != OrderStatus::PartiallyPaid {
+        return application::unprocessable("This order is no longer waiting for payment");
+    }
+    order.lock_version(conn)?;
+
+    // Tickets are still in the organization wallets until the order is paid
+    let mut wallet_id_per_asset: HashMap<Uuid, Uuid> = HashMap::new();
+    for item in order.items(conn)? {
+        for ticket in TicketInstance::find_for_order_item(item.id, conn)? {
+            wallet_id_per_asset
+                .entry(ticket.asset_id)
+                .or_insert(ticket.wallet_id);
+        }
+    }
+
+    let payment_response = match &json.method {
+        PaymentRequest::Card {
+            token,
+            provider,
+            save_payment_method,
+            set_default,
+        } => cart::checkout_payment_processor(
+            &connection,
+            &mut order,
+            Some(&token),
+            share.amount_in_cents,
+            &user,
+            &state.config.primary_currency,
+            provider,
+            false,
+            *save_payment_method,
+            *set_default,
+            Some(&share),
+            &state.service_locator,
+        )?,
+        PaymentRequest::PaymentMethod { provider } => {
+            let provider = match provider {
+                Some(provider) => provider.clone(),
+                None => match user.user.default_payment_method(conn).optional()? {
+                    Some(payment_method) => payment_method.name,
+                    None => {
+                        return application::unprocessable(
+                            "Could not complete this payment because user has no default payment method",
+                        );
+                    }
+                },
+            };
+            cart::checkout_payment_processor(
+                &connection,
+                &mut order,
+                None,
+                share.amount_in_cents,
+                &user,
+                &state.config.primary_currency,
+                &provider,
+                true,
+                false,
+                false,
+                Some(&share),
+                &state.service_locator,
+            )?
+        }
+        _ => return application::unprocessable("Shares can only be paid for by card"),
+    };
+
+    let conn = connection.get();
+    let order = Order::find(order.id, conn)?;
+    if payment_response.status() == StatusCode::OK && order.status == OrderStatus::Paid {
+        transfer_allocated_tickets(&order, &wallet_id_per_asset, &state.config, conn)?;
+
+        let owner = DbUser::find(order.user_id, conn)?;
+        if let (Some(first_name), Some(email)) = (owner.first_name, owner.email) {
+            mailers::cart::purchase_completed(
+                owner.id,
+                &first_name,
+                email,
+                order.for_display(conn)?,
+                &state.config,
+                conn,
+            )?;
+        }
+        send_share_confirmations(&order, &state.config, conn)?;
+    }
+
+    Ok(payment_response)
+}
+
+/// Sends everyone other than the order's owner who paid for a share a confirmation listing the
+/// tickets that were allocated to them
+fn send_share_confirmations(
+    order: &Order,
+    config: &Config,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let shares = order.shares(conn)?;
+    let mut payer_ids: Vec<Uuid> = shares
+        .iter()
+        .filter_map(|s| s.paid_by_user_id)
+        .filter(|user_id| *user_id != order.user_id)
+        .collect();
+    payer_ids.sort();
+    payer_ids.dedup();
+    if payer_ids.is_empty() {
+        return Ok(());
+    }
+
+    let mut tickets_per_wallet: HashMap<Uuid, Vec<TicketInstance>> = HashMap::new();
+    for item in order
+        .items(conn)?
+        .iter()
+        .filter(|i| i.item_type == OrderItemTypes::Tickets)
+    {
+        for ticket in TicketInstance::find_for_order_item(item.id, conn)? {
+            tickets_per_wallet
+                .entry(ticket.wallet_id)
+                .or_insert_with(|| Vec::new())
+                .push(ticket);
+        }
+    }
+    let display_order = order.for_display(conn)?;
+
+    for payer_id in payer_ids {
+        let payer = DbUser::find(payer_id, conn)?;
+        let (first_name, email) = match (payer.first_name, payer.email) {
+            (Some(first_name), Some(email)) => (first_name, email),
+            _ => continue,
+        };
+        let wallet = Wallet::find_default_for_user(payer_id, conn)?;
+        let payer_tickets = tickets_per_wallet.remove(&wallet.id).unwrap_or_default();
+        let amount_paid_in_cents = shares
+            .iter()
+            .filter(|s| s.paid_by_user_id == Some(payer_id))
+            .map(|s| s.amount_in_cents)
+            .sum();
+
+        mailers::cart::share_purchase_completed(
+            payer_id,
+            &first_name,
+            email,
+            &display_order,
+            &payer_tickets,
+            amount_paid_in_cents,
+            config,
+            conn,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Transfers each ticket's token from the organization wallet it was reserved from to the wallet
+/// it was allocated to when the order was paid
+fn transfer_allocated_tickets(
+    order: &Order,
+    wallet_id_per_asset: &HashMap<Uuid, Uuid>,
+    config: &Config,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let mut tokens_per_wallet: HashMap<(Uuid, Uuid), Vec<u64>> = HashMap::new();
+    for item in order.items(conn)? {
+        for ticket in TicketInstance::find_for_order_item(item.id, conn)? {
+            tokens_per_wallet
+                .entry((ticket.asset_id, ticket.wallet_id))
+                .or_insert_with(|| Vec::new())
+                .push(ticket.token_id as u64);
+        }
+    }
+
+    for ((asset_id, wallet_id), token_ids) in tokens_per_wallet {
+        let asset = Asset::find(asset_id, conn)?;
+        let blockchain_asset_id = match asset.blockchain_asset_id {
+            Some(blockchain_asset_id) => blockchain_asset_id,
+            None => {
+                return application::internal_server_error(
+                    "Could not complete this checkout because the asset has not been assigned on the blockchain",
+                );
+            }
+        };
+        let org_wallet = match wallet_id_per_asset.get(&asset_id) {
+            Some(org_wallet_id) => Wallet::find(*org_wallet_id, conn)?,
+            None => {
+                return application::internal_server_error(
+                    "Could not complete this checkout because wallet id not found for asset",
+                );
+            }
+        };
+        let new_owner_wallet = Wallet::find(wallet_id, conn)?;
+        config.tari_client.transfer_tokens(
+            &org_wallet.secret_key,
+            &org_wallet.public_key,
+            &blockchain_asset_id,
+            token_ids,
+            new_owner_wallet.public_key,
+        )?;
+    }
+
+    Ok(())
+}
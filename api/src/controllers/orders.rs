@@ -4,16 +4,15 @@ use bigneon_db::models::User as DbUser;
 use bigneon_db::models::*;
 use communications::mailers;
 use config::Environment;
+use controllers::cart::PaymentRequest;
 use db::Connection;
-use diesel::Connection as DieselConnection;
 use errors::BigNeonError;
 use extractors::*;
-use helpers::application;
+use helpers::{application, exchanges, idempotency, refunds};
 use models::PathParameters;
-use payments::PaymentProcessor;
 use server::AppState;
-use std::cmp;
 use std::collections::HashMap;
+use utils::ticket_pdfs;
 use uuid::Uuid;
 
 pub fn index(
@@ -77,25 +76,44 @@ pub fn details(
 #[derive(Deserialize, Serialize)]
 pub struct RefundAttributes {
     pub items: Vec<RefundItem>,
+    /// Refund the amount to the user's account credit instead of the original payment method
+    #[serde(default)]
+    pub to_credit: bool,
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct RefundResponse {
     pub amount_refunded: u32,
     pub refund_breakdown: HashMap<PaymentMethods, u32>,
+    /// Tickets left out of the refund because they were transferred to someone else
+    #[serde(default)]
+    pub transferred_ticket_instance_ids: Vec<Uuid>,
 }
 
 pub fn refund(
-    (conn, path, json, user, state): (
+    (conn, path, json, user, state, idempotency_key): (
         Connection,
         Path<PathParameters>,
         Json<RefundAttributes>,
         User,
         State<AppState>,
+        IdempotencyKey,
     ),
 ) -> Result<HttpResponse, BigNeonError> {
     let connection = conn.get();
     let refund_attributes = json.into_inner();
+    let request_json = json!({ "order_id": path.id, "refund": refund_attributes });
+
+    if let Some(response) = idempotency::replay(
+        &idempotency_key,
+        user.id(),
+        IdempotencyKeyOperations::Refund,
+        &request_json,
+        connection,
+    )? {
+        return Ok(response);
+    }
+
     let items = refund_attributes.items;
     let order = Order::find(path.id, connection)?;
 
@@ -128,197 +146,129 @@ pub fn refund(
         return application::unauthorized(Some(user), Some(details_data));
     }
 
-    let ticket_instance_ids = items
-        .iter()
-        .filter(|i| i.ticket_instance_id.is_some())
-        .map(|i| i.ticket_instance_id.unwrap())
-        .collect::<Vec<Uuid>>();
-
-    // Refund amount is fee inclusive if fee no longer applies to the order
-    let refund_due = order.refund(items, connection)?;
-
-    // Transfer tickets back to the organization wallets
-    let mut tokens_per_asset: HashMap<Uuid, Vec<u64>> = HashMap::new();
-    let mut wallet_id_per_asset: HashMap<Uuid, Uuid> = HashMap::new();
-    let mut ticket_instances_per_asset: HashMap<Uuid, Vec<TicketInstance>> = HashMap::new();
-    let refunded_tickets =
-        RefundedTicket::find_by_ticket_instance_ids(ticket_instance_ids, connection)?
-            .into_iter()
-            .filter(|refund_data| refund_data.ticket_refunded_at.is_some());
-    for refunded_ticket in refunded_tickets {
-        let ticket = TicketInstance::find(refunded_ticket.ticket_instance_id, connection)?;
-        tokens_per_asset
-            .entry(ticket.asset_id)
-            .or_insert_with(|| Vec::new())
-            .push(ticket.token_id as u64);
-        wallet_id_per_asset
-            .entry(ticket.asset_id)
-            .or_insert(ticket.wallet_id);
-        ticket_instances_per_asset
-            .entry(ticket.asset_id)
-            .or_insert_with(|| Vec::new())
-            .push(ticket);
-    }
-    let mut modified_tokens: HashMap<Uuid, Vec<u64>> = HashMap::new();
-
-    let mut refund_breakdown: HashMap<PaymentMethods, u32> = HashMap::new();
-    let mut payment_remaining_balance_map: HashMap<Option<String>, i64> = HashMap::new();
-    let mut amount_refunded = 0;
-
-    // Begin transaction, if it fails at this point all transferred tickets are returned to wallets
-    match connection.transaction::<_, BigNeonError, _>(|| {
-        for (asset_id, token_ids) in &tokens_per_asset {
-            let organization_id = Organization::find_by_asset_id(*asset_id, connection)?.id;
-            let organization_wallet =
-                Wallet::find_default_for_organization(organization_id, connection)?;
-            let asset = Asset::find(*asset_id, connection)?;
-            match asset.blockchain_asset_id {
-                Some(a) => {
-                    let wallet_id = match wallet_id_per_asset.get(asset_id) {
-                        Some(w) => w.clone(),
-                        None => return Err(application::internal_server_error::<HttpResponse>(
-                            "Could not complete this refund because wallet id not found for asset",
-                        ).unwrap_err()),
-                    };
-                    let user_wallet = Wallet::find(wallet_id, connection)?;
-                    state.config.tari_client.transfer_tokens(&user_wallet.secret_key, &user_wallet.public_key,
-                                                             &a,
-                                                             token_ids.clone(),
-                                                             organization_wallet.public_key.clone(),
-                    )?;
-                    modified_tokens.insert(*asset_id, token_ids.clone());
-                    match ticket_instances_per_asset.get(asset_id) {
-                        Some(ticket_instances) => {
-                            for ticket_instance in ticket_instances {
-                                ticket_instance.set_wallet(&organization_wallet, connection)?;
-                            }
-                        }
-                        None => return Err(application::internal_server_error::<HttpResponse>(
-                            "No ticket instances exist for transferred tokens",
-                        ).unwrap_err()),
-                    }
-                },
-                None => return Err(application::internal_server_error::<HttpResponse>(
-                    "Could not complete this refund because the asset is not assigned on the blockchain",
-                ).unwrap_err())
-            }
-        }
+    let idempotency_claim = idempotency::claim(
+        &idempotency_key,
+        order.id,
+        user.id(),
+        IdempotencyKeyOperations::Refund,
+        &request_json,
+        connection,
+    )?;
 
-        // Perform refunds
+    let (amount_refunded, refund_breakdown) = refunds::refund_order_items(
+        &order,
+        items,
+        refund_attributes.to_credit,
+        user.id(),
+        &state.config,
+        &state.service_locator,
+        connection,
+    )?;
 
-        // Negative payments / refunds cancel out remaining payment balance
-        for payment in order.payments(connection)? {
-            // Ignore payments that were only authorized
-            if payment.status == PaymentStatus::Authorized {
-                continue;
-            }
+    // Store the response with the refund so a retry replays it rather than refunding again
+    let response = HttpResponse::Ok().json(json!(RefundResponse {
+        amount_refunded,
+        refund_breakdown,
+        transferred_ticket_instance_ids: vec![],
+    }));
+    idempotency::complete(idempotency_claim, &response, connection)?;
 
-            *payment_remaining_balance_map
-                .entry(payment.external_reference)
-                .or_insert(0) += payment.amount;
-        }
+    // Commit changes as payment completed
+    if state.config.environment != Environment::Test {
+        conn.commit_transaction()?;
+        conn.begin_transaction()?;
+    }
 
-        for payment in order.payments(connection)? {
-            if payment.status != PaymentStatus::Completed {
-                continue;
-            }
+    // Reload order
+    let order = Order::find(order.id, connection)?;
+    let display_order = order.for_display(connection)?;
+    let user = DbUser::find(
+        order.on_behalf_of_user_id.unwrap_or(order.user_id),
+        connection,
+    )?;
 
-            let remaining_balance = payment_remaining_balance_map
-                .get(&payment.external_reference)
-                .map(|n| *n)
-                .unwrap_or(0);
-            if remaining_balance == 0 {
-                continue;
-            }
+    // Communicate refund to user
+    if let (Some(first_name), Some(email)) = (user.first_name, user.email) {
+        mailers::orders::refund_email(
+            &first_name,
+            email,
+            display_order,
+            amount_refunded,
+            &state.config,
+            connection,
+        )?;
+    }
 
-            let amount_to_refund = cmp::min(refund_due - amount_refunded, remaining_balance as u32);
-            let mut refund_data = None;
-            if payment.payment_method == PaymentMethods::CreditCard {
-                let client = &state
-                    .service_locator
-                    .create_payment_processor(&payment.provider)?;
-
-                refund_data = match payment.external_reference {
-                    Some(ref external_reference) => Some(
-                        client
-                            .partial_refund(external_reference, amount_to_refund)?
-                            .to_json()?,
-                    ),
-                    None => {
-                        return Err(application::internal_server_error::<HttpResponse>(&format!(
-                            "Unable to refund amount owed payment {} lacks external reference",
-                            payment.id
-                        )).unwrap_err())
-                    }
-                };
-            }
-            payment.log_refund(user.id(), amount_to_refund, refund_data, connection)?;
-            *refund_breakdown.entry(payment.payment_method).or_insert(0) += amount_to_refund;
-            amount_refunded += amount_to_refund;
-        }
+    Ok(response)
+}
 
-        if amount_refunded < refund_due {
-            return Err(application::internal_server_error::<HttpResponse>(&format!(
-                "Unable to refund amount owed {} refunded, {} due",
-                amount_refunded, refund_due
-            )).unwrap_err());
-        }
+/// Lets a purchaser opt out of a rescheduled event, refunding their tickets for it while the
+/// event's refund window is open
+pub fn reschedule_refund(
+    (conn, path, user, state): (Connection, Path<PathParameters>, User, State<AppState>),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = conn.get();
+    user.requires_scope(Scopes::OrderReadOwn)?;
+    let order = Order::find(path.id, connection)?;
 
-        Ok(())
-    }) {
-        Err(error) => {
-            for (asset_id, token_ids) in &modified_tokens {
-                let organization_id = Organization::find_by_asset_id(*asset_id, connection)?.id;
-                let organization_wallet =
-                    Wallet::find_default_for_organization(organization_id, connection)?;
-                let asset = Asset::find(*asset_id, connection)?;
-                match asset.blockchain_asset_id {
-                    Some(a) => {
-                        let wallet_id = match wallet_id_per_asset.get(asset_id) {
-                            Some(w) => w.clone(),
-                            None => return application::internal_server_error(
-                                "Could not complete this refund because wallet id not found for asset",
-                            ),
-                        };
-                        let user_wallet = Wallet::find(wallet_id, connection)?;
-                        state.config.tari_client.transfer_tokens(&organization_wallet.secret_key, &organization_wallet.public_key,
-                                                                 &a,
-                                                                 token_ids.clone(),
-                                                                 user_wallet.public_key.clone(),
-                        )?;
-                    },
-                    None => return application::internal_server_error(
-                        "Could not complete this refund because the asset is not assigned on the blockchain",
-                    ),
-                }
-            }
+    if order.user_id != user.id() && order.on_behalf_of_user_id != Some(user.id()) {
+        return application::forbidden("You do not have access to this order");
+    }
+    if !vec![OrderStatus::Paid, OrderStatus::PartiallyPaid].contains(&order.status) {
+        return application::unprocessable("Only paid orders can be refunded");
+    }
 
-            // Return error
-            return Err(error);
+    let mut event_ids: Vec<Uuid> = order
+        .items(connection)?
+        .into_iter()
+        .filter(|i| i.item_type == OrderItemTypes::Tickets)
+        .filter_map(|i| i.event_id)
+        .collect();
+    event_ids.sort();
+    event_ids.dedup();
+
+    // Transferred tickets are no longer the purchaser's to return, the rest are still refunded
+    let mut items = Vec::new();
+    let mut transferred_ticket_instance_ids = Vec::new();
+    for event_id in event_ids {
+        if EventReschedule::find_open_for_event(event_id, connection)?.is_some() {
+            items.append(&mut order.refundable_ticket_items(event_id, None, connection)?);
+            transferred_ticket_instance_ids
+                .append(&mut order.transferred_ticket_ids(event_id, None, connection)?);
         }
-        _ => (),
+    }
+    if items.is_empty() {
+        return application::unprocessable(
+            "This order has no tickets for a rescheduled event that can still be refunded",
+        );
     }
 
+    let (amount_refunded, refund_breakdown) = refunds::refund_order_items(
+        &order,
+        items,
+        false,
+        user.id(),
+        &state.config,
+        &state.service_locator,
+        connection,
+    )?;
+
     // Commit changes as payment completed
     if state.config.environment != Environment::Test {
         conn.commit_transaction()?;
         conn.begin_transaction()?;
     }
 
-    // Reload order
     let order = Order::find(order.id, connection)?;
-    let display_order = order.for_display(connection)?;
     let user = DbUser::find(
         order.on_behalf_of_user_id.unwrap_or(order.user_id),
         connection,
     )?;
-
-    // Communicate refund to user
     if let (Some(first_name), Some(email)) = (user.first_name, user.email) {
         mailers::orders::refund_email(
             &first_name,
             email,
-            display_order,
+            order.for_display(connection)?,
             amount_refunded,
             &state.config,
             connection,
@@ -326,6 +276,99 @@ pub fn refund(
     }
 
     Ok(HttpResponse::Ok().json(json!(RefundResponse {
+        amount_refunded,
+        refund_breakdown,
+        transferred_ticket_instance_ids,
+    })))
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ExchangeAttributes {
+    pub ticket_instance_ids: Vec<Uuid>,
+    pub ticket_type_id: Uuid,
+    /// Card or stored payment method used when the new tickets cost more than the old ones
+    #[serde(default)]
+    pub method: Option<PaymentRequest>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ExchangeResponse {
+    pub exchange: OrderExchange,
+    pub amount_charged: u32,
+    pub amount_refunded: u32,
+    pub refund_breakdown: HashMap<PaymentMethods, u32>,
+}
+
+/// Exchanges tickets in the order for another ticket type of the same event or another date in
+/// its series, charging or refunding only the difference in price and fees
+pub fn exchange(
+    (conn, path, json, user, state): (
+        Connection,
+        Path<PathParameters>,
+        Json<ExchangeAttributes>,
+        User,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = conn.get();
+    user.requires_scope(Scopes::OrderReadOwn)?;
+    let mut order = Order::find(path.id, connection)?;
+
+    if order.user_id != user.id() && order.on_behalf_of_user_id != Some(user.id()) {
+        return application::forbidden("You do not have access to this order");
+    }
+
+    // Wallets the tickets are in before they are nullified
+    let mut old_tickets = Vec::new();
+    for ticket_instance_id in &json.ticket_instance_ids {
+        old_tickets.push(TicketInstance::find(*ticket_instance_id, connection)?);
+    }
+
+    let exchange = order.exchange(
+        &json.ticket_instance_ids,
+        json.ticket_type_id,
+        user.id(),
+        connection,
+    )?;
+
+    let mut amount_charged = 0;
+    let mut refund_breakdown = HashMap::new();
+    if exchange.difference_in_cents > 0 {
+        exchanges::charge_difference(
+            &order,
+            &exchange,
+            json.method.as_ref(),
+            &user,
+            &state.config,
+            &state.service_locator,
+            connection,
+        )?;
+        amount_charged = exchange.difference_in_cents as u32;
+    } else if exchange.difference_in_cents < 0 {
+        refund_breakdown = refunds::refund_payments(
+            &order,
+            -exchange.difference_in_cents as u32,
+            &[],
+            false,
+            user.id(),
+            &state.service_locator,
+            connection,
+        )?;
+    }
+    let amount_refunded = refund_breakdown.values().sum();
+
+    // Commit the exchange as soon as the money has moved so it is recorded even if moving the
+    // tokens fails, the tickets themselves are already exchanged in the database
+    if state.config.environment != Environment::Test {
+        conn.commit_transaction()?;
+        conn.begin_transaction()?;
+    }
+
+    exchanges::transfer_exchanged_tickets(&old_tickets, &exchange, &state.config, connection)?;
+
+    Ok(HttpResponse::Ok().json(json!(ExchangeResponse {
+        exchange,
+        amount_charged,
         amount_refunded,
         refund_breakdown
     })))
@@ -352,8 +395,19 @@ pub fn update(
     Ok(HttpResponse::Ok().json(order.for_display(conn)?))
 }
 
+#[derive(Deserialize)]
+pub struct TicketsParameters {
+    pub format: Option<String>,
+}
+
+/// Tickets in the order, as JSON or as a PDF with one page per ticket when `format=pdf`
 pub fn tickets(
-    (conn, path, user): (Connection, Path<PathParameters>, User),
+    (conn, path, query, user): (
+        Connection,
+        Path<PathParameters>,
+        Query<TicketsParameters>,
+        User,
+    ),
 ) -> Result<HttpResponse, BigNeonError> {
     let conn = conn.get();
     let order = Order::find(path.id, conn)?;
@@ -382,5 +436,14 @@ pub fn tickets(
             results.push(TicketInstance::show_redeemable_ticket(t.id, conn)?);
         }
     }
+
+    if query.format.as_ref().map(|f| f.as_str()) == Some("pdf") {
+        let ticket_instance_ids: Vec<Uuid> = results.iter().map(|t| t.id).collect();
+        let pdf = ticket_pdfs::render("Tickets", &ticket_instance_ids, user.id(), conn)?;
+        return Ok(HttpResponse::Ok()
+            .content_type(ticket_pdfs::PDF_CONTENT_TYPE)
+            .body(pdf));
+    }
+
     Ok(HttpResponse::Ok().json(results))
 }
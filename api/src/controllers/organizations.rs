@@ -29,6 +29,18 @@ pub struct FeeScheduleWithRanges {
     pub ranges: Vec<FeeScheduleRange>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct PaymentProviderRequest {
+    pub payment_provider: String,
+    #[serde(default, deserialize_with = "deserialize_unless_blank")]
+    pub merchant_account_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ResalePriceCapRequest {
+    pub resale_price_cap_percent: Option<i32>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct NewOrganizationRequest {
     pub name: String,
@@ -396,6 +408,55 @@ pub fn add_fee_schedule(
     }))
 }
 
+pub fn update_payment_provider(
+    (connection, parameters, json, user, state): (
+        Connection,
+        Path<PathParameters>,
+        Json<PaymentProviderRequest>,
+        User,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    user.requires_scope(Scopes::OrgAdmin)?;
+    let connection = connection.get();
+    let json = json.into_inner();
+
+    if !state
+        .service_locator
+        .payment_providers()
+        .contains(&json.payment_provider)
+    {
+        return application::unprocessable(&format!(
+            "Unknown payment provider {}",
+            json.payment_provider
+        ));
+    }
+
+    let organization = Organization::find(parameters.id, connection)?.set_payment_provider(
+        &json.payment_provider,
+        json.merchant_account_id,
+        connection,
+    )?;
+    Ok(HttpResponse::Ok().json(&organization))
+}
+
+pub fn update_resale_price_cap(
+    (connection, parameters, json, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<ResalePriceCapRequest>,
+        User,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(parameters.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgWrite, &organization, connection)?;
+
+    let organization =
+        organization.set_resale_price_cap(json.resale_price_cap_percent, connection)?;
+    Ok(HttpResponse::Ok().json(&organization))
+}
+
 pub fn search_fans(
     (connection, path, query, user): (
         Connection,
@@ -22,12 +22,14 @@ pub fn show(
         redemption_code: String,
         max_per_order: Option<i64>,
         discount_in_cents: Option<i64>,
+        discount_as_percentage: Option<i64>,
         hold_type: HoldTypes,
     }
 
     let redemption_code = hold.redemption_code.clone();
     let max_per_order = hold.max_per_order;
     let discount_in_cents = hold.discount_in_cents;
+    let discount_as_percentage = hold.discount_as_percentage;
     let hold_type = hold.hold_type;
 
     let ticket_type = UserDisplayTicketType::from_ticket_type_and_hold(
@@ -46,6 +48,7 @@ pub fn show(
         redemption_code,
         max_per_order,
         discount_in_cents,
+        discount_as_percentage,
         hold_type,
     };
     return Ok(HttpResponse::Ok().json(r));
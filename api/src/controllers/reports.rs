@@ -30,6 +30,7 @@ pub fn get_report(
         "event_summary" => event_summary_report((connection, query, path, user)),
         "weekly_settlement" => weekly_settlement_report((connection, query, path, user)),
         "ticket_count" => ticket_counts((connection, query, path, user)),
+        "promo_code_performance" => promo_code_performance_report((connection, query, path, user)),
         _ => application::not_found(),
     }
 }
@@ -140,3 +141,30 @@ pub fn ticket_counts(
     let result = Report::ticket_count_report(query.event_id, Some(path.id), connection)?;
     Ok(HttpResponse::Ok().json(result))
 }
+
+pub fn promo_code_performance_report(
+    (connection, query, path, user): (
+        Connection,
+        Query<ReportQueryParameters>,
+        Path<PathParameters>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    //Check if they have org admin permissions
+    let organization = Organization::find(path.id, connection)?;
+    if query.event_id.is_some() {
+        user.requires_scope_for_organization(Scopes::EventReports, &organization, connection)?;
+    } else {
+        user.requires_scope_for_organization(Scopes::OrgReports, &organization, connection)?;
+    }
+
+    let result = Report::promo_code_performance_report(
+        query.event_id,
+        Some(path.id),
+        query.start_utc,
+        query.end_utc,
+        connection,
+    )?;
+    Ok(HttpResponse::Ok().json(result))
+}
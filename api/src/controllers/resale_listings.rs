@@ -0,0 +1,50 @@
+use actix_web::{HttpResponse, Path};
+use auth::user::User;
+use bigneon_db::models::*;
+use db::Connection;
+use errors::*;
+use extractors::*;
+use models::PathParameters;
+use uuid::Uuid;
+
+#[derive(Deserialize, Serialize)]
+pub struct NewResaleListingRequest {
+    pub ticket_instance_id: Uuid,
+    pub price_in_cents: i64,
+}
+
+/// Resale listings created by the current user
+pub fn index((connection, user): (Connection, User)) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let resale_listings = ResaleListing::find_for_seller(user.id(), connection)?;
+    Ok(HttpResponse::Ok().json(resale_listings))
+}
+
+/// Resale listings that can still be bought for the event
+pub fn index_for_event(
+    (connection, path): (Connection, Path<PathParameters>),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let resale_listings = ResaleListing::find_active_for_event(path.id, connection)?;
+    Ok(HttpResponse::Ok().json(resale_listings))
+}
+
+/// Lists one of the current user's tickets for resale at or below the organization's price cap
+pub fn create(
+    (connection, json, user): (Connection, Json<NewResaleListingRequest>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    user.requires_scope(Scopes::TicketTransfer)?;
+    let connection = connection.get();
+    let resale_listing =
+        ResaleListing::create(json.ticket_instance_id, user.id(), json.price_in_cents)
+            .commit(connection)?;
+    Ok(HttpResponse::Created().json(&resale_listing))
+}
+
+pub fn destroy(
+    (connection, path, user): (Connection, Path<PathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let resale_listing = ResaleListing::find(path.id, connection)?.cancel(user.id(), connection)?;
+    Ok(HttpResponse::Ok().json(&resale_listing))
+}
@@ -0,0 +1,34 @@
+use actix_web::{HttpResponse, Path};
+use auth::user::User;
+use bigneon_db::models::*;
+use db::Connection;
+use errors::*;
+use models::PathParameters;
+
+/// Resale payouts the organization still owes to sellers
+pub fn index(
+    (connection, path, user): (Connection, Path<PathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let organization = Organization::find(path.id, connection)?;
+    user.requires_scope_for_organization(Scopes::OrgReports, &organization, connection)?;
+
+    let resale_payouts = ResalePayout::find_unpaid_for_organization(organization.id, connection)?;
+    Ok(HttpResponse::Ok().json(resale_payouts))
+}
+
+/// Records that the organization has paid the seller
+pub fn paid(
+    (connection, path, user): (Connection, Path<PathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let resale_payout = ResalePayout::find(path.id, connection)?;
+    user.requires_scope_for_organization(
+        Scopes::OrgWrite,
+        &resale_payout.organization(connection)?,
+        connection,
+    )?;
+
+    let resale_payout = resale_payout.mark_paid(user.id(), connection)?;
+    Ok(HttpResponse::Ok().json(&resale_payout))
+}
@@ -0,0 +1,139 @@
+use actix_web::{HttpResponse, Path, Query};
+use auth::user::User as AuthUser;
+use bigneon_db::models::*;
+use db::Connection;
+use diesel::PgConnection;
+use errors::*;
+use extractors::*;
+use models::PathParameters;
+use uuid::Uuid;
+
+pub fn index(
+    (connection, path_parameters, query_parameters): (
+        Connection,
+        Path<PathParameters>,
+        Query<PagingParameters>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let seat_sections = SeatSection::find_by_venue_id(path_parameters.id, connection.get())?;
+
+    Ok(HttpResponse::Ok().json(&Payload::from_data(
+        seat_sections,
+        query_parameters.page(),
+        query_parameters.limit(),
+    )))
+}
+
+#[derive(Serialize)]
+pub struct SeatSectionWithSeats {
+    #[serde(flatten)]
+    pub seat_section: SeatSection,
+    pub seats: Vec<Seat>,
+}
+
+pub fn show(
+    (connection, parameters): (Connection, Path<PathParameters>),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let seat_section = SeatSection::find(parameters.id, connection)?;
+    let seats = seat_section.seats(connection)?;
+
+    Ok(HttpResponse::Ok().json(&SeatSectionWithSeats {
+        seat_section,
+        seats,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct CreateSeatSection {
+    pub name: String,
+    pub stage_id: Option<Uuid>,
+}
+
+pub fn create(
+    (connection, parameters, create_seat_section, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<CreateSeatSection>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let venue = Venue::find(parameters.id, connection)?;
+    check_venue_write(&venue, &user, connection)?;
+
+    let seat_section = SeatSection::create(
+        parameters.id,
+        create_seat_section.stage_id,
+        create_seat_section.name.clone(),
+    )
+    .commit(connection)?;
+
+    Ok(HttpResponse::Created().json(&seat_section))
+}
+
+pub fn update(
+    (connection, parameters, seat_section_parameters, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<SeatSectionEditableAttributes>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let seat_section = SeatSection::find(parameters.id, connection)?;
+    let venue = Venue::find(seat_section.venue_id, connection)?;
+    check_venue_write(&venue, &user, connection)?;
+
+    let updated_seat_section =
+        seat_section.update(seat_section_parameters.into_inner(), connection)?;
+    Ok(HttpResponse::Ok().json(updated_seat_section))
+}
+
+pub fn delete(
+    (connection, parameters, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let seat_section = SeatSection::find(parameters.id, connection)?;
+    let venue = Venue::find(seat_section.venue_id, connection)?;
+    check_venue_write(&venue, &user, connection)?;
+
+    seat_section.destroy(connection)?;
+    Ok(HttpResponse::Ok().json(json!({})))
+}
+
+#[derive(Deserialize)]
+pub struct AddSeatsRequest {
+    pub seats: Vec<NewSeat>,
+}
+
+pub fn add_seats(
+    (connection, parameters, add_seats_request, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<AddSeatsRequest>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let seat_section = SeatSection::find(parameters.id, connection)?;
+    let venue = Venue::find(seat_section.venue_id, connection)?;
+    check_venue_write(&venue, &user, connection)?;
+
+    let seats = seat_section.add_seats(add_seats_request.into_inner().seats, connection)?;
+    Ok(HttpResponse::Created().json(&seats))
+}
+
+fn check_venue_write(
+    venue: &Venue,
+    user: &AuthUser,
+    connection: &PgConnection,
+) -> Result<(), BigNeonError> {
+    if !venue.is_private || venue.organization_id.is_none() {
+        user.requires_scope(Scopes::VenueWrite)?;
+    } else {
+        let organization = venue.organization(connection)?.unwrap();
+        user.requires_scope_for_organization(Scopes::VenueWrite, &organization, connection)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use actix_web::{HttpResponse, Path};
+use auth::user::User as AuthUser;
+use bigneon_db::models::*;
+use db::Connection;
+use diesel::PgConnection;
+use errors::*;
+use extractors::*;
+use models::PathParameters;
+
+#[derive(Deserialize)]
+pub struct CreateTaxRule {
+    pub name: String,
+    pub rate_in_basis_points: i32,
+    #[serde(default)]
+    pub inclusive: bool,
+    pub applies_to_tickets: Option<bool>,
+    pub applies_to_fees: Option<bool>,
+}
+
+pub fn index_for_region(
+    (connection, parameters): (Connection, Path<PathParameters>),
+) -> Result<HttpResponse, BigNeonError> {
+    let tax_rules = TaxRule::find_for_region(parameters.id, connection.get())?;
+    Ok(HttpResponse::Ok().json(&tax_rules))
+}
+
+pub fn create_for_region(
+    (connection, parameters, create_tax_rule, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<CreateTaxRule>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    user.requires_scope(Scopes::RegionWrite)?;
+    let connection = connection.get();
+    let region = Region::find(parameters.id, connection)?;
+
+    let tax_rule = TaxRule::create_for_region(
+        region.id,
+        create_tax_rule.name.clone(),
+        create_tax_rule.rate_in_basis_points,
+        create_tax_rule.inclusive,
+        create_tax_rule.applies_to_tickets.unwrap_or(true),
+        create_tax_rule.applies_to_fees.unwrap_or(false),
+    )
+    .commit(connection)?;
+
+    Ok(HttpResponse::Created().json(&tax_rule))
+}
+
+pub fn index_for_venue(
+    (connection, parameters): (Connection, Path<PathParameters>),
+) -> Result<HttpResponse, BigNeonError> {
+    let tax_rules = TaxRule::find_for_venue(parameters.id, connection.get())?;
+    Ok(HttpResponse::Ok().json(&tax_rules))
+}
+
+pub fn create_for_venue(
+    (connection, parameters, create_tax_rule, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<CreateTaxRule>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let venue = Venue::find(parameters.id, connection)?;
+    requires_venue_write(&user, &venue, connection)?;
+
+    let tax_rule = TaxRule::create_for_venue(
+        venue.id,
+        create_tax_rule.name.clone(),
+        create_tax_rule.rate_in_basis_points,
+        create_tax_rule.inclusive,
+        create_tax_rule.applies_to_tickets.unwrap_or(true),
+        create_tax_rule.applies_to_fees.unwrap_or(false),
+    )
+    .commit(connection)?;
+
+    Ok(HttpResponse::Created().json(&tax_rule))
+}
+
+pub fn update(
+    (connection, parameters, tax_rule_parameters, user): (
+        Connection,
+        Path<PathParameters>,
+        Json<TaxRuleEditableAttributes>,
+        AuthUser,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let tax_rule = TaxRule::find(parameters.id, connection)?;
+    requires_tax_rule_write(&user, &tax_rule, connection)?;
+
+    let updated_tax_rule = tax_rule.update(tax_rule_parameters.into_inner(), connection)?;
+    Ok(HttpResponse::Ok().json(updated_tax_rule))
+}
+
+pub fn destroy(
+    (connection, parameters, user): (Connection, Path<PathParameters>, AuthUser),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let tax_rule = TaxRule::find(parameters.id, connection)?;
+    requires_tax_rule_write(&user, &tax_rule, connection)?;
+
+    tax_rule.destroy(connection)?;
+    Ok(HttpResponse::Ok().json(json!({})))
+}
+
+fn requires_tax_rule_write(
+    user: &AuthUser,
+    tax_rule: &TaxRule,
+    connection: &PgConnection,
+) -> Result<(), BigNeonError> {
+    match tax_rule.venue_id {
+        Some(venue_id) => {
+            let venue = Venue::find(venue_id, connection)?;
+            requires_venue_write(user, &venue, connection)
+        }
+        None => user.requires_scope(Scopes::RegionWrite),
+    }
+}
+
+fn requires_venue_write(
+    user: &AuthUser,
+    venue: &Venue,
+    connection: &PgConnection,
+) -> Result<(), BigNeonError> {
+    if !venue.is_private || venue.organization_id.is_none() {
+        user.requires_scope(Scopes::VenueWrite)
+    } else {
+        let organization = venue.organization(connection)?.unwrap();
+        user.requires_scope_for_organization(Scopes::VenueWrite, &organization, connection)
+    }
+}
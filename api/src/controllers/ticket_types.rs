@@ -32,6 +32,7 @@ pub struct CreateTicketTypeRequest {
     pub increment: Option<i32>,
     pub limit_per_person: i32,
     pub price_in_cents: i64,
+    pub allow_reentry: Option<bool>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -56,6 +57,7 @@ pub struct UpdateTicketTypeRequest {
     pub increment: Option<i32>,
     pub limit_per_person: Option<i32>,
     pub price_in_cents: Option<i64>,
+    pub allow_reentry: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -92,6 +94,16 @@ pub fn create(
         data.price_in_cents,
         connection,
     )?;
+    let ticket_type = match data.allow_reentry {
+        Some(allow_reentry) => ticket_type.update(
+            TicketTypeEditableAttributes {
+                allow_reentry: Some(allow_reentry),
+                ..Default::default()
+            },
+            connection,
+        )?,
+        None => ticket_type,
+    };
     //Add each ticket pricing entry for newly created ticket type
     for current_pricing_entry in &data.ticket_pricing {
         let _pricing_result = ticket_type.add_ticket_pricing(
@@ -176,11 +188,58 @@ pub fn cancel(
 
     let ticket_type = TicketType::find(path.ticket_type_id, connection)?;
 
-    ticket_type.cancel(connection)?;
+    ticket_type.cancel(user.id(), connection)?;
 
     Ok(HttpResponse::Ok().finish())
 }
 
+/// Seat map for the ticket type. Buyers pick their seats from it once the event is published,
+/// before that it is only shown to the event's organization.
+pub fn seats(
+    (connection, path, user): (Connection, Path<EventTicketPathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event = Event::find(path.event_id, connection)?;
+    if event.status != EventStatus::Published {
+        let organization = event.organization(connection)?;
+        user.requires_scope_for_organization(Scopes::EventWrite, &organization, connection)?;
+    }
+
+    let ticket_type = TicketType::find(path.ticket_type_id, connection)?;
+    if ticket_type.event_id != event.id {
+        return application::not_found();
+    }
+
+    Ok(HttpResponse::Ok().json(&ticket_type.seats(connection)?))
+}
+
+#[derive(Deserialize)]
+pub struct AssignSeatsRequest {
+    pub seat_ids: Vec<Uuid>,
+}
+
+pub fn assign_seats(
+    (connection, path, data, user): (
+        Connection,
+        Path<EventTicketPathParameters>,
+        Json<AssignSeatsRequest>,
+        User,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let event = Event::find(path.event_id, connection)?;
+    let organization = event.organization(connection)?;
+    user.requires_scope_for_organization(Scopes::EventWrite, &organization, connection)?;
+
+    let ticket_type = TicketType::find(path.ticket_type_id, connection)?;
+    if ticket_type.event_id != event.id {
+        return application::not_found();
+    }
+    ticket_type.assign_seats(&data.seat_ids, connection)?;
+
+    Ok(HttpResponse::Ok().json(&ticket_type.seats(connection)?))
+}
+
 pub fn update(
     (connection, path, data, user, state): (
         Connection,
@@ -268,6 +327,7 @@ pub fn update(
         increment: data.increment,
         limit_per_person: data.limit_per_person,
         price_in_cents: data.price_in_cents,
+        allow_reentry: data.allow_reentry,
     };
     let updated_ticket_type = ticket_type.update(update_parameters, connection)?;
 
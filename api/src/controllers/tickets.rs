@@ -118,6 +118,32 @@ pub fn show_redeemable_ticket(
     Ok(HttpResponse::Ok().json(&redeemable_ticket))
 }
 
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct SignedRedeemPayloadResponse {
+    pub payload: Option<String>,
+}
+
+/// Signed payload that door scanners can verify without connectivity. Like the redeem key it is
+/// only available once the event's redeem date has passed, and only to the ticket's holder.
+pub fn show_signed_redeem_payload(
+    (connection, parameters, auth_user): (Connection, Path<PathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let (event, user, _ticket) = TicketInstance::find_for_display(parameters.id, connection)?;
+    let db_event = Event::find(event.id, connection)?;
+    let organization = db_event.organization(connection)?;
+
+    if user.as_ref().map_or(false, |u| u.id != auth_user.id()) {
+        auth_user.requires_scope_for_organization(Scopes::TicketRead, &organization, connection)?;
+    }
+
+    let ticket = TicketInstance::find(parameters.id, connection)?;
+    let payload =
+        EventSigningKey::signed_payload_for_ticket(&ticket, &db_event, auth_user.id(), connection)?;
+
+    Ok(HttpResponse::Ok().json(&SignedRedeemPayloadResponse { payload }))
+}
+
 pub fn send_via_email_or_phone(
     (connection, send_tickets_request, auth_user, state): (
         Connection,
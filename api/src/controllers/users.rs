@@ -30,6 +30,7 @@ pub struct CurrentUser {
     pub scopes: Vec<Scopes>,
     pub organization_roles: HashMap<Uuid, Vec<Roles>>,
     pub organization_scopes: HashMap<Uuid, Vec<Scopes>>,
+    pub credit_balance_in_cents: i64,
 }
 
 impl Responder for CurrentUser {
@@ -304,6 +305,7 @@ fn current_user_from_user(
         scopes: user.get_global_scopes(),
         organization_roles: roles_by_organization,
         organization_scopes: scopes_by_organization,
+        credit_balance_in_cents: CreditTransaction::balance_for_user(user.id, connection)?,
     })
 }
 
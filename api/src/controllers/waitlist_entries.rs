@@ -0,0 +1,85 @@
+use actix_web::{HttpResponse, Path};
+use auth::user::User;
+use bigneon_db::models::*;
+use db::Connection;
+use diesel::PgConnection;
+use errors::*;
+use extractors::*;
+use helpers::application;
+use models::EventTicketPathParameters;
+
+#[derive(Deserialize)]
+pub struct JoinWaitlistRequest {
+    pub quantity: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct DisplayWaitlistEntry {
+    #[serde(flatten)]
+    pub entry: WaitlistEntry,
+    pub position: Option<u32>,
+    pub redemption_code: Option<String>,
+}
+
+impl DisplayWaitlistEntry {
+    fn from_entry(
+        entry: WaitlistEntry,
+        conn: &PgConnection,
+    ) -> Result<DisplayWaitlistEntry, BigNeonError> {
+        let position = entry.position(conn)?;
+        let redemption_code = match entry.status {
+            WaitlistEntryStatus::Offered => entry.hold(conn)?.map(|h| h.redemption_code),
+            _ => None,
+        };
+        Ok(DisplayWaitlistEntry {
+            entry,
+            position,
+            redemption_code,
+        })
+    }
+}
+
+pub fn show(
+    (connection, path, user): (Connection, Path<EventTicketPathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    match WaitlistEntry::find_active_for_user(path.ticket_type_id, user.id(), connection)? {
+        Some(entry) => {
+            Ok(HttpResponse::Ok().json(&DisplayWaitlistEntry::from_entry(entry, connection)?))
+        }
+        None => application::not_found(),
+    }
+}
+
+pub fn create(
+    (connection, path, data, user): (
+        Connection,
+        Path<EventTicketPathParameters>,
+        Json<JoinWaitlistRequest>,
+        User,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    let ticket_type = TicketType::find(path.ticket_type_id, connection)?;
+    if ticket_type.event_id != path.event_id {
+        return application::not_found();
+    }
+
+    let entry = WaitlistEntry::create(ticket_type.id, user.id(), data.quantity.unwrap_or(1))
+        .commit(connection)?;
+
+    Ok(HttpResponse::Created().json(&DisplayWaitlistEntry::from_entry(entry, connection)?))
+}
+
+pub fn destroy(
+    (connection, path, user): (Connection, Path<EventTicketPathParameters>, User),
+) -> Result<HttpResponse, BigNeonError> {
+    let connection = connection.get();
+    match WaitlistEntry::find_active_for_user(path.ticket_type_id, user.id(), connection)? {
+        Some(entry) => {
+            entry.cancel(connection)?;
+            Ok(HttpResponse::Ok().finish())
+        }
+        None => application::not_found(),
+    }
+}
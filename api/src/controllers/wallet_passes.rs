@@ -0,0 +1,295 @@
+use actix_web::{HttpRequest, HttpResponse, Path, Query, State};
+use auth::user::User;
+use bigneon_db::models::*;
+use chrono::prelude::*;
+use db::Connection;
+use diesel::PgConnection;
+use errors::*;
+use extractors::*;
+use helpers::application;
+use models::PathParameters;
+use server::AppState;
+use utils::wallet_passes::{apple, google, WalletPassDetails};
+use uuid::Uuid;
+
+/// The log endpoint is unauthenticated so only this much of what devices send is logged
+const MAX_LOG_MESSAGES: usize = 10;
+const MAX_LOG_MESSAGE_LENGTH: usize = 500;
+
+#[derive(Deserialize)]
+pub struct DeviceRegistrationPathParameters {
+    pub device_library_identifier: String,
+    pub pass_type_identifier: String,
+    pub serial_number: Uuid,
+}
+
+#[derive(Deserialize)]
+pub struct DevicePassesPathParameters {
+    pub device_library_identifier: String,
+    pub pass_type_identifier: String,
+}
+
+#[derive(Deserialize)]
+pub struct PassPathParameters {
+    pub pass_type_identifier: String,
+    pub serial_number: Uuid,
+}
+
+#[derive(Deserialize)]
+pub struct UpdatedPassesParameters {
+    #[serde(rename = "passesUpdatedSince")]
+    pub passes_updated_since: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DeviceRegistrationRequest {
+    #[serde(rename = "pushToken")]
+    pub push_token: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct UpdatedPassesResponse {
+    #[serde(rename = "lastUpdated")]
+    pub last_updated: String,
+    #[serde(rename = "serialNumbers")]
+    pub serial_numbers: Vec<Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct LogRequest {
+    pub logs: Vec<String>,
+}
+
+pub fn show_apple(
+    (connection, parameters, auth_user, state): (
+        Connection,
+        Path<PathParameters>,
+        User,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    if !apple::is_enabled(&state.config) {
+        return application::unprocessable("Apple Wallet passes are not enabled");
+    }
+    let connection = connection.get();
+    let details = load_for_user(parameters.id, &auth_user, connection)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(apple::PKPASS_CONTENT_TYPE)
+        .body(apple::pkpass(&state.config, &details)?))
+}
+
+pub fn show_google(
+    (connection, parameters, auth_user, state): (
+        Connection,
+        Path<PathParameters>,
+        User,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    if !google::is_enabled(&state.config) {
+        return application::unprocessable("Google Wallet passes are not enabled");
+    }
+    let connection = connection.get();
+    let details = load_for_user(parameters.id, &auth_user, connection)?;
+
+    Ok(HttpResponse::Ok().json(&google::pass(&state.config, &details)?))
+}
+
+/// Registers the device for updates to the pass, as called by Apple Wallet
+pub fn register_device(
+    (connection, parameters, json, request): (
+        Connection,
+        Path<DeviceRegistrationPathParameters>,
+        Json<DeviceRegistrationRequest>,
+        HttpRequest<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    if !authorized_for_pass(
+        &request,
+        &parameters.pass_type_identifier,
+        parameters.serial_number,
+    ) {
+        return application::unauthorized_with_message("Invalid pass authorization", None, None);
+    }
+    let connection = connection.get();
+    let existing =
+        WalletPassRegistration::find_for_ticket_instance(parameters.serial_number, connection)?
+            .into_iter()
+            .any(|r| r.device_library_identifier == parameters.device_library_identifier);
+
+    WalletPassRegistration::create(
+        parameters.serial_number,
+        parameters.device_library_identifier.clone(),
+        json.into_inner().push_token,
+    )
+    .commit(connection)?;
+
+    if existing {
+        Ok(HttpResponse::Ok().finish())
+    } else {
+        Ok(HttpResponse::Created().finish())
+    }
+}
+
+pub fn unregister_device(
+    (connection, parameters, request): (
+        Connection,
+        Path<DeviceRegistrationPathParameters>,
+        HttpRequest<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    if !authorized_for_pass(
+        &request,
+        &parameters.pass_type_identifier,
+        parameters.serial_number,
+    ) {
+        return application::unauthorized_with_message("Invalid pass authorization", None, None);
+    }
+    let connection = connection.get();
+    WalletPassRegistration::destroy(
+        &parameters.device_library_identifier,
+        parameters.serial_number,
+        connection,
+    )?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Serial numbers of the passes on the device that changed since the tag it last received
+pub fn updated_passes(
+    (connection, parameters, query, state): (
+        Connection,
+        Path<DevicePassesPathParameters>,
+        Query<UpdatedPassesParameters>,
+        State<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    if state.config.apple_pass_type_identifier.as_ref() != Some(&parameters.pass_type_identifier) {
+        return application::not_found();
+    }
+    let connection = connection.get();
+    let updated_since = query
+        .passes_updated_since
+        .as_ref()
+        .and_then(|tag| tag.parse::<i64>().ok())
+        .map(|timestamp| NaiveDateTime::from_timestamp(timestamp, 0));
+    let updated_passes = WalletPassRegistration::find_updated_for_device(
+        &parameters.device_library_identifier,
+        updated_since,
+        connection,
+    )?;
+
+    let last_updated = match updated_passes.iter().map(|p| p.updated_at).max() {
+        Some(last_updated) => last_updated,
+        None => return application::no_content(),
+    };
+
+    Ok(HttpResponse::Ok().json(&UpdatedPassesResponse {
+        last_updated: last_updated.timestamp().to_string(),
+        serial_numbers: updated_passes
+            .into_iter()
+            .map(|p| p.ticket_instance_id)
+            .collect(),
+    }))
+}
+
+/// Latest version of the pass. A holder who has since transferred the ticket away receives a
+/// voided pass.
+pub fn show_latest_apple(
+    (connection, parameters, request): (
+        Connection,
+        Path<PassPathParameters>,
+        HttpRequest<AppState>,
+    ),
+) -> Result<HttpResponse, BigNeonError> {
+    let config = &request.state().config;
+    let wallet_id = match authorized_wallet(
+        &request,
+        &parameters.pass_type_identifier,
+        parameters.serial_number,
+    ) {
+        Some(wallet_id) => wallet_id,
+        None => {
+            return application::unauthorized_with_message(
+                "Invalid pass authorization",
+                None,
+                None,
+            );
+        }
+    };
+    let connection = connection.get();
+    let mut details = WalletPassDetails::load(parameters.serial_number, connection)?;
+    if details.wallet_id != wallet_id {
+        details = details.for_previous_holder();
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type(apple::PKPASS_CONTENT_TYPE)
+        .body(apple::pkpass(config, &details)?))
+}
+
+pub fn log(json: Json<LogRequest>) -> Result<HttpResponse, BigNeonError> {
+    for message in json.logs.iter().take(MAX_LOG_MESSAGES) {
+        let message: String = message.chars().take(MAX_LOG_MESSAGE_LENGTH).collect();
+        warn!("Apple Wallet: {}", message);
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+fn load_for_user(
+    ticket_instance_id: Uuid,
+    auth_user: &User,
+    connection: &PgConnection,
+) -> Result<WalletPassDetails, BigNeonError> {
+    let mut details = WalletPassDetails::load(ticket_instance_id, connection)?;
+    if details
+        .user
+        .as_ref()
+        .map_or(false, |u| u.id != auth_user.id())
+    {
+        let organization = Event::find(details.event.id, connection)?.organization(connection)?;
+        auth_user.requires_scope_for_organization(Scopes::TicketRead, &organization, connection)?;
+        // Only the holder's pass can be used at the door
+        details.barcode = None;
+    }
+
+    Ok(details)
+}
+
+fn authorized_for_pass(
+    request: &HttpRequest<AppState>,
+    pass_type_identifier: &str,
+    ticket_instance_id: Uuid,
+) -> bool {
+    authorized_wallet(request, pass_type_identifier, ticket_instance_id).is_some()
+}
+
+/// Wallet the pass was issued to, if the request carries a valid `ApplePass` authorization for it
+fn authorized_wallet(
+    request: &HttpRequest<AppState>,
+    pass_type_identifier: &str,
+    ticket_instance_id: Uuid,
+) -> Option<Uuid> {
+    let config = &request.state().config;
+    if config
+        .apple_pass_type_identifier
+        .as_ref()
+        .map(|p| p.as_str())
+        != Some(pass_type_identifier)
+    {
+        return None;
+    }
+
+    let authorization = request
+        .headers()
+        .get("Authorization")
+        .and_then(|a| a.to_str().ok())?;
+    let mut parts = authorization.splitn(2, ' ');
+    if parts.next() != Some(apple::AUTHORIZATION_SCHEME) {
+        return None;
+    }
+
+    apple::verify_authentication_token(config, ticket_instance_id, parts.next()?.trim())
+}
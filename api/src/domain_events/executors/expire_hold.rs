@@ -0,0 +1,75 @@
+use bigneon_db::prelude::*;
+use communications::mailers;
+use config::Config;
+use db::Connection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::BigNeonError;
+use futures::future;
+use log::Level::*;
+use uuid::Uuid;
+
+const LOG_TARGET: &'static str = "bigneon::domain_actions::expire_hold";
+
+pub struct ExpireHoldExecutor {
+    config: Config,
+}
+
+impl ExpireHoldExecutor {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExpireHoldPayload {
+    pub hold_id: Uuid,
+}
+
+impl DomainActionExecutor for ExpireHoldExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, LOG_TARGET, "Error in ExpireHoldExecutor", {
+                    "innerError": format!("{}", e)
+                });
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl ExpireHoldExecutor {
+    fn perform_job(
+        &self,
+        action: &DomainAction,
+        connection: &Connection,
+    ) -> Result<(), BigNeonError> {
+        let payload = serde_json::from_value::<ExpireHoldPayload>(action.payload.clone())?;
+        let conn = connection.get();
+
+        let hold = match Hold::find(payload.hold_id, conn) {
+            Ok(hold) => hold,
+            // Deleting a hold already releases its tickets
+            Err(ref e) if e.error_code == ErrorCode::NoResults => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let released = hold.expire(conn)?;
+        if released == 0 {
+            return Ok(());
+        }
+
+        jlog!(Info, LOG_TARGET, &format!("Released {} ticket(s) from hold {}", released, hold.id), {
+            "action_id": action.id,
+            "hold_id": hold.id,
+        });
+
+        let event = Event::find(hold.event_id, conn)?;
+        let ticket_type = TicketType::find(hold.ticket_type_id, conn)?;
+        mailers::holds::hold_expired(&self.config, &hold, &event, &ticket_type, released, conn)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+use bigneon_db::prelude::*;
+use config::{Config, Environment};
+use db::Connection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::BigNeonError;
+use futures::future;
+use log::Level::*;
+use payments::PaymentProcessor;
+use utils::ServiceLocator;
+use uuid::Uuid;
+
+const LOG_TARGET: &'static str = "bigneon::domain_actions::expire_order_shares";
+
+pub struct ExpireOrderSharesExecutor {
+    config: Config,
+    service_locator: ServiceLocator,
+}
+
+impl ExpireOrderSharesExecutor {
+    pub fn new(config: Config) -> Self {
+        let service_locator = ServiceLocator::new(&config);
+        Self {
+            config,
+            service_locator,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExpireOrderSharesPayload {
+    pub order_id: Uuid,
+}
+
+impl DomainActionExecutor for ExpireOrderSharesExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, LOG_TARGET, "Error in ExpireOrderSharesExecutor", {
+                    "innerError": format!("{}", e)
+                });
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl ExpireOrderSharesExecutor {
+    fn perform_job(
+        &self,
+        action: &DomainAction,
+        connection: &Connection,
+    ) -> Result<(), BigNeonError> {
+        let payload = serde_json::from_value::<ExpireOrderSharesPayload>(action.payload.clone())?;
+        let conn = connection.get();
+
+        let mut order = Order::find(payload.order_id, conn)?;
+        // Every share was paid in time
+        if order.status != OrderStatus::PartiallyPaid {
+            return Ok(());
+        }
+
+        let payments = order.payments(conn)?;
+        for share in order.shares(conn)? {
+            let (payment_id, paid_by_user_id) = match (share.payment_id, share.paid_by_user_id) {
+                (Some(payment_id), Some(paid_by_user_id)) => (payment_id, paid_by_user_id),
+                _ => continue,
+            };
+            let payment = match payments.iter().find(|p| {
+                p.id == payment_id
+                    && (p.status == PaymentStatus::Completed || p.status == PaymentStatus::Disputed)
+            }) {
+                Some(payment) => payment,
+                None => continue,
+            };
+
+            let amount_to_refund = payment.amount - payment.refunded_amount(conn)?;
+            if amount_to_refund <= 0 {
+                continue;
+            }
+
+            // Refunds go back through the merchant account the payment was charged to
+            let client = self
+                .service_locator
+                .create_payment_processor(&payment.provider, payment.merchant_account_id.clone())?;
+            let refund_data = match payment.external_reference {
+                Some(ref external_reference) => client
+                    .partial_refund(external_reference, amount_to_refund as u32)?
+                    .to_json()?,
+                None => continue,
+            };
+            payment.log_refund(
+                paid_by_user_id,
+                amount_to_refund as u32,
+                Some(refund_data),
+                conn,
+            )?;
+
+            // Commit each refund so a later failure retries without refunding this payment again
+            if self.config.environment != Environment::Test {
+                connection.commit_transaction()?;
+                connection.begin_transaction()?;
+            }
+
+            jlog!(Info, LOG_TARGET, &format!("Refunded {} for unpaid split order {}", amount_to_refund, order.id), {
+                "action_id": action.id,
+                "order_share_id": share.id,
+                "payment_id": payment.id,
+            });
+        }
+
+        order.cancel_unpaid_split(conn)?;
+
+        Ok(())
+    }
+}
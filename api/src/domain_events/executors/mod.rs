@@ -1,2 +1,11 @@
+pub mod expire_hold;
+pub mod expire_order_shares;
 pub mod marketing_contacts;
+pub mod notify_event_reschedule;
+pub mod process_waitlist;
+pub mod refund_cancelled_order;
+pub mod return_cart_credit;
+pub mod send_cart_reminder;
 pub mod send_communication;
+pub mod transfer_resold_ticket;
+pub mod update_wallet_passes;
@@ -0,0 +1,77 @@
+use bigneon_db::prelude::*;
+use communications::{mailers, smsers};
+use config::Config;
+use db::Connection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::BigNeonError;
+use futures::future;
+use log::Level::*;
+
+const LOG_TARGET: &'static str = "bigneon::domain_actions::notify_event_reschedule";
+
+pub struct NotifyEventRescheduleExecutor {
+    config: Config,
+}
+
+impl NotifyEventRescheduleExecutor {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+}
+
+impl DomainActionExecutor for NotifyEventRescheduleExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, LOG_TARGET, "Error in NotifyEventRescheduleExecutor", {
+                    "innerError": format!("{}", e)
+                });
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl NotifyEventRescheduleExecutor {
+    fn perform_job(
+        &self,
+        action: &DomainAction,
+        connection: &Connection,
+    ) -> Result<(), BigNeonError> {
+        let conn = connection.get();
+
+        let reschedule = EventReschedule::find(action.main_table_id, conn)?;
+        let event = Event::find(reschedule.event_id, conn)?;
+        let ticket_holders = reschedule.ticket_holders(conn)?;
+
+        jlog!(Info, LOG_TARGET, &format!("Notifying {} ticket holder(s) of rescheduled event {}", ticket_holders.len(), event.id), {
+            "action_id": action.id,
+            "event_reschedule_id": reschedule.id,
+        });
+
+        for (user, has_order) in ticket_holders {
+            mailers::events::rescheduled(
+                &self.config,
+                &user,
+                has_order,
+                &event,
+                &reschedule,
+                conn,
+            )?;
+            if let Some(phone) = user.phone.clone() {
+                smsers::events::rescheduled(
+                    &self.config,
+                    phone,
+                    has_order,
+                    &event,
+                    &reschedule,
+                    conn,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
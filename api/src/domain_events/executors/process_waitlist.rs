@@ -0,0 +1,81 @@
+use bigneon_db::prelude::*;
+use communications::mailers;
+use config::Config;
+use db::Connection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::BigNeonError;
+use futures::future;
+use log::Level::*;
+use uuid::Uuid;
+
+const LOG_TARGET: &'static str = "bigneon::domain_actions::process_waitlist";
+
+pub struct ProcessWaitlistExecutor {
+    config: Config,
+}
+
+impl ProcessWaitlistExecutor {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProcessWaitlistPayload {
+    pub ticket_type_id: Uuid,
+}
+
+impl DomainActionExecutor for ProcessWaitlistExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, LOG_TARGET, "Error in ProcessWaitlistExecutor", {
+                    "innerError": format!("{}", e)
+                });
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl ProcessWaitlistExecutor {
+    fn perform_job(
+        &self,
+        action: &DomainAction,
+        connection: &Connection,
+    ) -> Result<(), BigNeonError> {
+        let payload = serde_json::from_value::<ProcessWaitlistPayload>(action.payload.clone())?;
+        let conn = connection.get();
+
+        let offers = WaitlistEntry::process(payload.ticket_type_id, conn)?;
+        if offers.is_empty() {
+            return Ok(());
+        }
+
+        jlog!(Info, LOG_TARGET, &format!("Made {} waitlist offer(s) for ticket type {}", offers.len(), payload.ticket_type_id), {
+            "action_id": action.id,
+            "ticket_type_id": payload.ticket_type_id,
+        });
+
+        let ticket_type = TicketType::find(payload.ticket_type_id, conn)?;
+        let event = Event::find(ticket_type.event_id, conn)?;
+        for entry in offers {
+            let user = User::find(entry.user_id, conn)?;
+            if let Some(hold) = entry.hold(conn)? {
+                mailers::waitlist::offer_available(
+                    &self.config,
+                    &user,
+                    &event,
+                    &ticket_type,
+                    &entry,
+                    &hold,
+                    conn,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
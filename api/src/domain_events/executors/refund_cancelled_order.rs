@@ -0,0 +1,108 @@
+use bigneon_db::prelude::*;
+use communications::mailers;
+use config::{Config, Environment};
+use db::Connection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::BigNeonError;
+use futures::future;
+use helpers::refunds;
+use log::Level::*;
+use utils::ServiceLocator;
+use uuid::Uuid;
+
+const LOG_TARGET: &'static str = "bigneon::domain_actions::refund_cancelled_order";
+
+pub struct RefundCancelledOrderExecutor {
+    config: Config,
+    service_locator: ServiceLocator,
+}
+
+impl RefundCancelledOrderExecutor {
+    pub fn new(config: Config) -> Self {
+        let service_locator = ServiceLocator::new(&config);
+        Self {
+            config,
+            service_locator,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RefundCancelledOrderPayload {
+    pub order_id: Uuid,
+}
+
+impl DomainActionExecutor for RefundCancelledOrderExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, LOG_TARGET, "Error in RefundCancelledOrderExecutor", {
+                    "innerError": format!("{}", e)
+                });
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl RefundCancelledOrderExecutor {
+    fn perform_job(
+        &self,
+        action: &DomainAction,
+        connection: &Connection,
+    ) -> Result<(), BigNeonError> {
+        let payload =
+            serde_json::from_value::<RefundCancelledOrderPayload>(action.payload.clone())?;
+        let conn = connection.get();
+
+        let cancellation = EventCancellation::find(action.main_table_id, conn)?;
+        let order = Order::find(payload.order_id, conn)?;
+
+        // Refunded tickets are released back to the organization, keep them off sale
+        let items = cancellation.refund_items(&order, conn)?;
+        if items.is_empty() {
+            cancellation.withdraw_inventory(conn)?;
+            return Ok(());
+        }
+
+        let (amount_refunded, _) = refunds::refund_cancelled_order_items(
+            &order,
+            items,
+            cancellation.cancelled_by_user_id,
+            &self.config,
+            &self.service_locator,
+            conn,
+        )?;
+
+        // Commit the refund so a later failure retries without refunding the payment again
+        if self.config.environment != Environment::Test {
+            connection.commit_transaction()?;
+            connection.begin_transaction()?;
+        }
+
+        cancellation.withdraw_inventory(conn)?;
+
+        jlog!(Info, LOG_TARGET, &format!("Refunded {} for cancelled order {}", amount_refunded, order.id), {
+            "action_id": action.id,
+            "event_cancellation_id": cancellation.id,
+            "order_id": order.id,
+        });
+
+        let order = Order::find(order.id, conn)?;
+        let user = User::find(order.on_behalf_of_user_id.unwrap_or(order.user_id), conn)?;
+        if let (Some(first_name), Some(email)) = (user.first_name, user.email) {
+            mailers::orders::refund_email(
+                &first_name,
+                email,
+                order.for_display(conn)?,
+                amount_refunded,
+                &self.config,
+                conn,
+            )?;
+        }
+
+        Ok(())
+    }
+}
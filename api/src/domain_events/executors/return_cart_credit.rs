@@ -0,0 +1,73 @@
+use bigneon_db::prelude::*;
+use chrono::prelude::*;
+use config::Config;
+use db::Connection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::BigNeonError;
+use futures::future;
+use log::Level::*;
+use uuid::Uuid;
+
+const LOG_TARGET: &'static str = "bigneon::domain_actions::return_cart_credit";
+
+pub struct ReturnCartCreditExecutor {}
+
+impl ReturnCartCreditExecutor {
+    pub fn new(_config: Config) -> Self {
+        Self {}
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReturnCartCreditPayload {
+    pub order_id: Uuid,
+}
+
+impl DomainActionExecutor for ReturnCartCreditExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, LOG_TARGET, "Error in ReturnCartCreditExecutor", {
+                    "innerError": format!("{}", e)
+                });
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl ReturnCartCreditExecutor {
+    fn perform_job(
+        &self,
+        action: &DomainAction,
+        connection: &Connection,
+    ) -> Result<(), BigNeonError> {
+        let payload = serde_json::from_value::<ReturnCartCreditPayload>(action.payload.clone())?;
+        let conn = connection.get();
+
+        let mut order = Order::find(payload.order_id, conn)?;
+        // The cart was checked out or its credit was already returned
+        if order.status != OrderStatus::PartiallyPaid || !order.paid_with_credit_only(conn)? {
+            return Ok(());
+        }
+        // The cart was paid with credit again after this was scheduled, which scheduled another
+        // return for its new expiry
+        if order
+            .expires_at
+            .map_or(false, |expires_at| expires_at > Utc::now().naive_utc())
+        {
+            return Ok(());
+        }
+
+        order.return_credit_payments(conn)?;
+
+        jlog!(Info, LOG_TARGET, "Returned credit for expired cart", {
+            "action_id": action.id,
+            "order_id": order.id,
+        });
+
+        Ok(())
+    }
+}
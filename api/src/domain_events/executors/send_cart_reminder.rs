@@ -0,0 +1,103 @@
+use bigneon_db::prelude::*;
+use chrono::prelude::*;
+use chrono::Duration;
+use communications::mailers;
+use config::Config;
+use db::Connection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::BigNeonError;
+use futures::future;
+use log::Level::*;
+use uuid::Uuid;
+
+const LOG_TARGET: &'static str = "bigneon::domain_actions::send_cart_reminder";
+
+pub struct SendCartReminderExecutor {
+    config: Config,
+}
+
+impl SendCartReminderExecutor {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SendCartReminderPayload {
+    pub order_id: Uuid,
+}
+
+impl DomainActionExecutor for SendCartReminderExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, LOG_TARGET, "Error in SendCartReminderExecutor", {
+                    "innerError": format!("{}", e)
+                });
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl SendCartReminderExecutor {
+    fn perform_job(
+        &self,
+        action: &DomainAction,
+        connection: &Connection,
+    ) -> Result<(), BigNeonError> {
+        let payload = serde_json::from_value::<SendCartReminderPayload>(action.payload.clone())?;
+        let conn = connection.get();
+
+        let order = match Order::find(payload.order_id, conn) {
+            Ok(order) => order,
+            // The cart was cleared
+            Err(ref e) if e.error_code == ErrorCode::NoResults => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        if order.status != OrderStatus::Draft {
+            return Ok(());
+        }
+
+        // The cart was extended after the reminder was scheduled, so wait for it to expire again
+        let delay = Duration::minutes(self.config.cart_reminder_delay_in_minutes);
+        if let Some(expires_at) = order.expires_at {
+            if expires_at + delay > Utc::now().naive_utc() {
+                order.schedule_cart_reminder(delay, conn)?;
+                return Ok(());
+            }
+        }
+
+        let display_order = order.for_display(conn)?;
+        if !display_order
+            .items
+            .iter()
+            .any(|i| i.item_type == OrderItemTypes::Tickets)
+        {
+            return Ok(());
+        }
+
+        let user = User::find(order.user_id, conn)?;
+        // The user has moved on to another cart or already bought tickets since
+        if user.last_cart_id != Some(order.id) || order.superseded_by_paid_order(conn)? {
+            return Ok(());
+        }
+
+        // Reminders are marketing, so only users who opted in receive them
+        let email = match user.email.clone() {
+            Some(email) if user.marketing_opt_in => email,
+            _ => return Ok(()),
+        };
+
+        mailers::cart::cart_reminder(&user, email, &display_order, &self.config, conn)?;
+
+        jlog!(Info, LOG_TARGET, "Sent cart reminder", {
+            "action_id": action.id,
+            "order_id": order.id,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+use bigneon_db::prelude::*;
+use config::Config;
+use db::Connection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::*;
+use futures::future;
+use log::Level::*;
+use uuid::Uuid;
+
+const LOG_TARGET: &'static str = "bigneon::domain_actions::transfer_resold_ticket";
+
+pub struct TransferResoldTicketExecutor {
+    config: Config,
+}
+
+impl TransferResoldTicketExecutor {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TransferResoldTicketPayload {
+    pub resale_listing_id: Uuid,
+    pub from_wallet_id: Uuid,
+    pub to_wallet_id: Uuid,
+}
+
+impl DomainActionExecutor for TransferResoldTicketExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, LOG_TARGET, "Error in TransferResoldTicketExecutor", {
+                    "innerError": format!("{}", e)
+                });
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl TransferResoldTicketExecutor {
+    /// Moves the token of a resold ticket from the seller's wallet to the buyer's. The ticket
+    /// already belongs to the buyer in the database, failed transfers are retried until the
+    /// action runs out of attempts.
+    fn perform_job(
+        &self,
+        action: &DomainAction,
+        connection: &Connection,
+    ) -> Result<(), BigNeonError> {
+        let payload =
+            serde_json::from_value::<TransferResoldTicketPayload>(action.payload.clone())?;
+        let conn = connection.get();
+
+        let listing = ResaleListing::find(payload.resale_listing_id, conn)?;
+        let ticket = TicketInstance::find(listing.ticket_instance_id, conn)?;
+        let asset = Asset::find(ticket.asset_id, conn)?;
+        let blockchain_asset_id = match asset.blockchain_asset_id {
+            Some(blockchain_asset_id) => blockchain_asset_id,
+            None => {
+                return Err(ApplicationError::new(
+                    "Could not transfer resold ticket because the asset is not assigned on the blockchain".to_string(),
+                )
+                .into());
+            }
+        };
+        let from_wallet = Wallet::find(payload.from_wallet_id, conn)?;
+        let to_wallet = Wallet::find(payload.to_wallet_id, conn)?;
+        self.config.tari_client.transfer_tokens(
+            &from_wallet.secret_key,
+            &from_wallet.public_key,
+            &blockchain_asset_id,
+            vec![ticket.token_id as u64],
+            to_wallet.public_key,
+        )?;
+
+        jlog!(Info, LOG_TARGET, "Transferred resold ticket", {
+            "action_id": action.id,
+            "resale_listing_id": listing.id,
+            "ticket_instance_id": ticket.id,
+        });
+
+        Ok(())
+    }
+}
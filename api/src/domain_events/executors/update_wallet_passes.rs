@@ -0,0 +1,134 @@
+use bigneon_db::prelude::*;
+use chrono::prelude::*;
+use config::Config;
+use db::Connection;
+use domain_events::executor_future::ExecutorFuture;
+use domain_events::routing::DomainActionExecutor;
+use errors::*;
+use futures::future;
+use log::Level::*;
+use utils::wallet_passes::{apple, google, WalletPassDetails};
+use uuid::Uuid;
+
+const LOG_TARGET: &'static str = "bigneon::domain_actions::update_wallet_passes";
+
+pub struct UpdateWalletPassesExecutor {
+    config: Config,
+}
+
+impl UpdateWalletPassesExecutor {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+}
+
+impl DomainActionExecutor for UpdateWalletPassesExecutor {
+    fn execute(&self, action: DomainAction, conn: Connection) -> ExecutorFuture {
+        match self.perform_job(&action, &conn) {
+            Ok(_) => ExecutorFuture::new(action, conn, Box::new(future::ok(()))),
+            Err(e) => {
+                jlog!(Error, LOG_TARGET, "Error in UpdateWalletPassesExecutor", {
+                    "innerError": format!("{}", e)
+                });
+                ExecutorFuture::new(action, conn, Box::new(future::err(e)))
+            }
+        }
+    }
+}
+
+impl UpdateWalletPassesExecutor {
+    /// A rescheduled event updates every pass for the event, as does reaching the event's redeem
+    /// date which adds the barcode to the passes. A transferred ticket updates its own pass and
+    /// deactivates the previous holder's Google Wallet pass.
+    fn perform_job(
+        &self,
+        action: &DomainAction,
+        connection: &Connection,
+    ) -> Result<(), BigNeonError> {
+        let conn = connection.get();
+
+        let registrations = if action.main_table == Tables::Events.table_name() {
+            let event = Event::find(action.main_table_id, conn)?;
+            let redeem_date_reached = !action.payload["redeem_date"].is_null();
+            if redeem_date_reached {
+                // The redeem date was moved later, another update is scheduled for it
+                if event
+                    .redeem_date
+                    .map_or(false, |redeem_date| redeem_date > Utc::now().naive_utc())
+                {
+                    return Ok(());
+                }
+                if google::is_enabled(&self.config) {
+                    for ticket in TicketInstance::find_purchased_for_event(event.id, conn)? {
+                        google::update_event_ticket_object(
+                            &self.config,
+                            &WalletPassDetails::load(ticket.id, conn)?,
+                        )?;
+                    }
+                }
+            } else if google::is_enabled(&self.config) {
+                let localized_times = event.get_all_localized_times(&event.venue(conn)?);
+                google::update_event_ticket_class(
+                    &self.config,
+                    &event.for_display(conn)?,
+                    &localized_times,
+                )?;
+            }
+            WalletPassRegistration::find_for_event(action.main_table_id, conn)?
+        } else if action.main_table == Tables::TicketInstances.table_name() {
+            let previous_wallet_id = action.payload["previous_wallet_id"]
+                .as_str()
+                .and_then(|id| Uuid::parse_str(id).ok());
+            if let Some(previous_wallet_id) =
+                previous_wallet_id.filter(|_| google::is_enabled(&self.config))
+            {
+                google::deactivate_event_ticket_object(
+                    &self.config,
+                    action.main_table_id,
+                    previous_wallet_id,
+                )?;
+            }
+            WalletPassRegistration::find_for_ticket_instance(action.main_table_id, conn)?
+        } else {
+            return Err(ApplicationError::new(format!(
+                "Cannot update wallet passes for {}",
+                action.main_table
+            ))
+            .into());
+        };
+
+        if !apple::is_enabled(&self.config) {
+            return Ok(());
+        }
+
+        jlog!(Info, LOG_TARGET, &format!("Pushing wallet pass updates to {} device(s)", registrations.len()), {
+            "action_id": action.id,
+            "main_table": action.main_table,
+            "main_table_id": action.main_table_id,
+        });
+
+        // A device registers a pass per ticket but only needs to be told once
+        let mut push_tokens: Vec<String> =
+            registrations.into_iter().map(|r| r.push_token).collect();
+        push_tokens.sort();
+        push_tokens.dedup();
+
+        // One device failing should not stop the others from being updated
+        for push_token in push_tokens {
+            match apple::push_update(&self.config, &push_token) {
+                Ok(true) => (),
+                Ok(false) => {
+                    WalletPassRegistration::destroy_for_push_token(&push_token, conn)?;
+                }
+                Err(e) => {
+                    jlog!(Warn, LOG_TARGET, "Could not push wallet pass update to device", {
+                        "action_id": action.id,
+                        "innerError": format!("{}", e)
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
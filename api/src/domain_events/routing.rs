@@ -4,10 +4,19 @@ use config::Config;
 use db::Connection;
 use domain_events::errors::DomainActionError;
 use domain_events::executor_future::ExecutorFuture;
+use domain_events::executors::expire_hold::ExpireHoldExecutor;
+use domain_events::executors::expire_order_shares::ExpireOrderSharesExecutor;
 use domain_events::executors::marketing_contacts::{
     BulkEventFanListImportExecutor, CreateEventListExecutor,
 };
+use domain_events::executors::notify_event_reschedule::NotifyEventRescheduleExecutor;
+use domain_events::executors::process_waitlist::ProcessWaitlistExecutor;
+use domain_events::executors::refund_cancelled_order::RefundCancelledOrderExecutor;
+use domain_events::executors::return_cart_credit::ReturnCartCreditExecutor;
+use domain_events::executors::send_cart_reminder::SendCartReminderExecutor;
 use domain_events::executors::send_communication::SendCommunicationExecutor;
+use domain_events::executors::transfer_resold_ticket::TransferResoldTicketExecutor;
+use domain_events::executors::update_wallet_passes::UpdateWalletPassesExecutor;
 use std::borrow::Borrow;
 use std::collections::HashMap;
 
@@ -55,10 +64,19 @@ impl DomainActionRouter {
             let conf = conf.clone();
             match action_type {
                 Communication => Box::new(SendCommunicationExecutor::new(conf)),
+                ExpireHold => Box::new(ExpireHoldExecutor::new(conf)),
+                ExpireOrderShares => Box::new(ExpireOrderSharesExecutor::new(conf)),
                 MarketingContactsBulkEventFanListImport => {
                     Box::new(BulkEventFanListImportExecutor::new(conf))
                 }
                 MarketingContactsCreateEventList => Box::new(CreateEventListExecutor::new(conf)),
+                NotifyEventReschedule => Box::new(NotifyEventRescheduleExecutor::new(conf)),
+                ProcessWaitlist => Box::new(ProcessWaitlistExecutor::new(conf)),
+                RefundCancelledOrder => Box::new(RefundCancelledOrderExecutor::new(conf)),
+                ReturnCartCredit => Box::new(ReturnCartCreditExecutor::new(conf)),
+                SendCartReminder => Box::new(SendCartReminderExecutor::new(conf)),
+                TransferResoldTicket => Box::new(TransferResoldTicketExecutor::new(conf)),
+                UpdateWalletPasses => Box::new(UpdateWalletPassesExecutor::new(conf)),
                 //
                 // DO NOT add
                 // _ =>
@@ -68,6 +86,12 @@ impl DomainActionRouter {
         self.add_executor(Communication, find_executor(Communication))
             .expect("Configuration error");
 
+        self.add_executor(ExpireHold, find_executor(ExpireHold))
+            .expect("Configuration error");
+
+        self.add_executor(ExpireOrderShares, find_executor(ExpireOrderShares))
+            .expect("Configuration error");
+
         self.add_executor(
             MarketingContactsCreateEventList,
             find_executor(MarketingContactsCreateEventList),
@@ -79,5 +103,26 @@ impl DomainActionRouter {
             find_executor(MarketingContactsBulkEventFanListImport),
         )
         .expect("Configuration error");
+
+        self.add_executor(NotifyEventReschedule, find_executor(NotifyEventReschedule))
+            .expect("Configuration error");
+
+        self.add_executor(ProcessWaitlist, find_executor(ProcessWaitlist))
+            .expect("Configuration error");
+
+        self.add_executor(RefundCancelledOrder, find_executor(RefundCancelledOrder))
+            .expect("Configuration error");
+
+        self.add_executor(ReturnCartCredit, find_executor(ReturnCartCredit))
+            .expect("Configuration error");
+
+        self.add_executor(SendCartReminder, find_executor(SendCartReminder))
+            .expect("Configuration error");
+
+        self.add_executor(TransferResoldTicket, find_executor(TransferResoldTicket))
+            .expect("Configuration error");
+
+        self.add_executor(UpdateWalletPasses, find_executor(UpdateWalletPasses))
+            .expect("Configuration error");
     }
 }
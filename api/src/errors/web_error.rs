@@ -131,6 +131,9 @@ impl ConvertToWebError for ApplicationError {
         match self.error_type {
             ApplicationErrorType::Internal => internal_error("Internal error"),
             ApplicationErrorType::Unprocessable => unprocessable(&self.reason),
+            ApplicationErrorType::Conflict => {
+                status_code_and_message(StatusCode::CONFLICT, &self.reason)
+            }
         }
     }
 }
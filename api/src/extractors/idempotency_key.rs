@@ -0,0 +1,31 @@
+use actix_web::error::*;
+use actix_web::{FromRequest, HttpRequest};
+use server::AppState;
+
+const IDEMPOTENCY_KEY_HEADER: &'static str = "Idempotency-Key";
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+/// Value of the optional `Idempotency-Key` header sent by clients that may retry a request
+#[derive(Clone, Debug, Default)]
+pub struct IdempotencyKey(pub Option<String>);
+
+impl FromRequest<AppState> for IdempotencyKey {
+    type Config = ();
+    type Result = Result<IdempotencyKey, Error>;
+
+    fn from_request(req: &HttpRequest<AppState>, _cfg: &Self::Config) -> Self::Result {
+        match req.headers().get(IDEMPOTENCY_KEY_HEADER) {
+            Some(header) => {
+                let key = header
+                    .to_str()
+                    .map_err(|_| ErrorBadRequest("Invalid Idempotency-Key header"))?
+                    .trim();
+                if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+                    return Err(ErrorBadRequest("Invalid Idempotency-Key header"));
+                }
+                Ok(IdempotencyKey(Some(key.to_string())))
+            }
+            None => Ok(IdempotencyKey(None)),
+        }
+    }
+}
@@ -1,7 +1,9 @@
+pub use self::idempotency_key::*;
 pub use self::json::*;
 pub use self::optional_user::*;
 pub use self::user::*;
 
+mod idempotency_key;
 mod json;
 mod optional_user;
 mod user;
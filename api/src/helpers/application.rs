@@ -38,6 +38,10 @@ pub fn unprocessable<T: Responder>(message: &str) -> Result<T, BigNeonError> {
     )
 }
 
+pub fn conflict<T: Responder>(message: &str) -> Result<T, BigNeonError> {
+    Err(ApplicationError::new_with_type(ApplicationErrorType::Conflict, message.to_string()).into())
+}
+
 pub fn internal_server_error<T: Responder>(message: &str) -> Result<T, BigNeonError> {
     error!("Internal Server Error: {}", message);
     Err(ApplicationError::new(message.to_string()).into())
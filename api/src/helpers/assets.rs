@@ -0,0 +1,36 @@
+use bigneon_db::models::*;
+use config::Config;
+use diesel::PgConnection;
+use errors::*;
+use tari_client::MessagePayloadCreateAsset as TariNewAsset;
+
+/// Issues blockchain assets for the event's ticket types that do not have one yet, e.g. ticket
+/// types copied from another event
+pub fn create_missing_blockchain_assets(
+    event: &Event,
+    config: &Config,
+    connection: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let org_wallet = Wallet::find_default_for_organization(event.organization_id, connection)?;
+    for ticket_type in event.ticket_types(connection)? {
+        let asset = Asset::find_by_ticket_type(&ticket_type.id, connection)?;
+        if asset.blockchain_asset_id.is_some() {
+            continue;
+        }
+
+        let tari_asset_id = config.tari_client.create_asset(
+            &org_wallet.secret_key,
+            &org_wallet.public_key,
+            TariNewAsset {
+                name: format!("{}.{}", event.id, ticket_type.name),
+                total_supply: ticket_type.valid_ticket_count(connection)? as u64,
+                authorised_signers: Vec::new(),
+                rule_flags: 0,
+                rule_metadata: "".to_string(),
+                expiry_date: ticket_type.end_date.timestamp(),
+            },
+        )?;
+        asset.update_blockchain_id(tari_asset_id, connection)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use auth::user::User;
+use bigneon_db::models::*;
+use bigneon_db::utils::errors::Optional;
+use config::Config;
+use controllers::cart::PaymentRequest;
+use diesel::PgConnection;
+use errors::*;
+use payments::PaymentProcessor;
+use std::collections::HashMap;
+use utils::ServiceLocator;
+use uuid::Uuid;
+
+/// Charges the difference owed on an exchange to the card or stored payment method given,
+/// through the merchant account of the organization the new tickets belong to
+pub fn charge_difference(
+    order: &Order,
+    exchange: &OrderExchange,
+    method: Option<&PaymentRequest>,
+    user: &User,
+    config: &Config,
+    service_locator: &ServiceLocator,
+    connection: &PgConnection,
+) -> Result<Payment, BigNeonError> {
+    let (token, provider_name, use_stored_payment) = match method {
+        Some(PaymentRequest::Card {
+            token, provider, ..
+        }) => (Some(token.clone()), provider.clone(), false),
+        Some(PaymentRequest::PaymentMethod { provider }) => {
+            let provider = match provider {
+                Some(provider) => provider.clone(),
+                None => match user.user.default_payment_method(connection).optional()? {
+                    Some(payment_method) => payment_method.name,
+                    None => {
+                        return Err(ApplicationError::new_with_type(
+                            ApplicationErrorType::Unprocessable,
+                            "Could not complete this exchange because user has no default payment method".to_string(),
+                        )
+                        .into());
+                    }
+                },
+            };
+            (None, provider, true)
+        }
+        _ => {
+            return Err(ApplicationError::new_with_type(
+                ApplicationErrorType::Unprocessable,
+                "A card or stored payment method is required to pay the difference for this exchange".to_string(),
+            )
+            .into());
+        }
+    };
+
+    let organization =
+        Organization::find_by_order_item_ids(vec![exchange.to_order_item_id], connection)?
+            .into_iter()
+            .next();
+    let merchant_account_id = match organization {
+        Some(ref organization) if organization.payment_provider != provider_name => {
+            return Err(ApplicationError::new_with_type(
+                ApplicationErrorType::Unprocessable,
+                format!(
+                    "Could not complete this exchange because payments for this event must be made through {}",
+                    organization.payment_provider
+                ),
+            )
+            .into());
+        }
+        Some(organization) => organization.merchant_account_id,
+        None => None,
+    };
+    let client =
+        service_locator.create_payment_processor(&provider_name, merchant_account_id.clone())?;
+
+    let token = if use_stored_payment {
+        match user
+            .user
+            .payment_method(
+                provider_name.clone(),
+                merchant_account_id.clone(),
+                connection,
+            )
+            .optional()?
+        {
+            Some(payment_method) => payment_method.provider,
+            None => {
+                return Err(ApplicationError::new_with_type(
+                    ApplicationErrorType::Unprocessable,
+                    "Could not complete this exchange because stored provider does not exist"
+                        .to_string(),
+                )
+                .into());
+            }
+        }
+    } else {
+        match token {
+            Some(token) => token,
+            None => {
+                return Err(ApplicationError::new_with_type(
+                    ApplicationErrorType::Unprocessable,
+                    "Could not complete this exchange because no token provided".to_string(),
+                )
+                .into());
+            }
+        }
+    };
+
+    let auth_result = client.auth(
+        &token,
+        exchange.difference_in_cents,
+        &config.primary_currency,
+        "Ticket exchange from Bigneon",
+        vec![
+            ("order_id".to_string(), order.id.to_string()),
+            ("order_exchange_id".to_string(), exchange.id.to_string()),
+        ],
+    )?;
+
+    let payment = match order.add_exchange_payment(
+        exchange,
+        user.id(),
+        provider_name,
+        merchant_account_id,
+        auth_result.id.clone(),
+        PaymentStatus::Authorized,
+        auth_result.to_json()?,
+        connection,
+    ) {
+        Ok(payment) => payment,
+        Err(e) => {
+            client.refund(&auth_result.id)?;
+            return Err(e.into());
+        }
+    };
+
+    let charge_result = client.complete_authed_charge(&auth_result.id)?;
+    match payment.mark_complete(charge_result.to_json()?, user.id(), connection) {
+        Ok(_) => Ok(payment),
+        Err(e) => {
+            client.refund(&auth_result.id)?;
+            Err(e.into())
+        }
+    }
+}
+
+/// Moves the exchanged tickets' tokens back to the organization wallets they were nullified
+/// into and the new tickets' tokens into the wallets they were purchased into
+pub fn transfer_exchanged_tickets(
+    old_tickets: &[TicketInstance],
+    exchange: &OrderExchange,
+    config: &Config,
+    connection: &PgConnection,
+) -> Result<(), BigNeonError> {
+    // Keyed on asset, sending wallet and receiving wallet
+    let mut tokens_per_transfer: HashMap<(Uuid, Uuid, Uuid), Vec<u64>> = HashMap::new();
+    for old_ticket in old_tickets {
+        let nullified_ticket = TicketInstance::find(old_ticket.id, connection)?;
+        tokens_per_transfer
+            .entry((
+                old_ticket.asset_id,
+                old_ticket.wallet_id,
+                nullified_ticket.wallet_id,
+            ))
+            .or_insert_with(|| Vec::new())
+            .push(old_ticket.token_id as u64);
+    }
+    for ticket in TicketInstance::find_for_order_item(exchange.to_order_item_id, connection)? {
+        let organization_id = Organization::find_by_asset_id(ticket.asset_id, connection)?.id;
+        let organization_wallet =
+            Wallet::find_default_for_organization(organization_id, connection)?;
+        tokens_per_transfer
+            .entry((ticket.asset_id, organization_wallet.id, ticket.wallet_id))
+            .or_insert_with(|| Vec::new())
+            .push(ticket.token_id as u64);
+    }
+
+    for ((asset_id, from_wallet_id, to_wallet_id), token_ids) in tokens_per_transfer {
+        let asset = Asset::find(asset_id, connection)?;
+        let blockchain_asset_id = match asset.blockchain_asset_id {
+            Some(blockchain_asset_id) => blockchain_asset_id,
+            None => {
+                return Err(ApplicationError::new(
+                    "Could not complete this exchange because the asset is not assigned on the blockchain".to_string(),
+                )
+                .into());
+            }
+        };
+        let from_wallet = Wallet::find(from_wallet_id, connection)?;
+        let to_wallet = Wallet::find(to_wallet_id, connection)?;
+        config.tari_client.transfer_tokens(
+            &from_wallet.secret_key,
+            &from_wallet.public_key,
+            &blockchain_asset_id,
+            token_ids,
+            to_wallet.public_key,
+        )?;
+    }
+
+    Ok(())
+}
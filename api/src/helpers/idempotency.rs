@@ -0,0 +1,107 @@
+use actix_web::{http::StatusCode, Body, HttpResponse};
+use bigneon_db::models::*;
+use diesel::PgConnection;
+use errors::*;
+use extractors::IdempotencyKey;
+use helpers::application;
+use serde_json::Value;
+use std::str;
+use uuid::Uuid;
+
+/// Returns the stored response if a request with this idempotency key has already completed.
+/// Reusing a key with a different request, while the original request is still being
+/// processed, or after it failed part way through, is a conflict.
+pub fn replay(
+    idempotency_key: &IdempotencyKey,
+    user_id: Uuid,
+    operation: IdempotencyKeyOperations,
+    request: &Value,
+    conn: &PgConnection,
+) -> Result<Option<HttpResponse>, BigNeonError> {
+    let key = match idempotency_key.0 {
+        Some(ref key) => key,
+        None => return Ok(None),
+    };
+
+    let existing = match OrderIdempotencyKey::find(user_id, operation, key, conn)? {
+        Some(existing) => existing,
+        None => return Ok(None),
+    };
+
+    if !existing.matches_request(request) {
+        return application::conflict(
+            "Idempotency-Key has already been used for a different request",
+        );
+    }
+
+    match (existing.response_status, existing.response_body.clone()) {
+        (Some(status), Some(body)) => {
+            let status =
+                StatusCode::from_u16(status as u16).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            Ok(Some(
+                HttpResponse::build(status)
+                    .content_type("application/json")
+                    .header("Idempotent-Replayed", "true")
+                    .body(body),
+            ))
+        }
+        _ => {
+            if existing.is_locked() {
+                return application::conflict(
+                    "A request with this Idempotency-Key is still being processed",
+                );
+            }
+            // Claims only become visible once the original request has committed work, such as
+            // a payment, so it is never safe to run the request again under this key
+            application::conflict(
+                "A request with this Idempotency-Key did not complete, check the order before retrying with a new key",
+            )
+        }
+    }
+}
+
+/// Claims the idempotency key for this request so that concurrent retries are rejected
+pub fn claim(
+    idempotency_key: &IdempotencyKey,
+    order_id: Uuid,
+    user_id: Uuid,
+    operation: IdempotencyKeyOperations,
+    request: &Value,
+    conn: &PgConnection,
+) -> Result<Option<OrderIdempotencyKey>, BigNeonError> {
+    let key = match idempotency_key.0 {
+        Some(ref key) => key.clone(),
+        None => return Ok(None),
+    };
+
+    match OrderIdempotencyKey::create(order_id, user_id, operation, key, request).commit(conn)? {
+        Some(claimed) => Ok(Some(claimed)),
+        None => {
+            application::conflict("A request with this Idempotency-Key is still being processed")
+        }
+    }
+}
+
+/// Stores the response so that it can be replayed for repeated requests
+pub fn complete(
+    claimed: Option<OrderIdempotencyKey>,
+    response: &HttpResponse,
+    conn: &PgConnection,
+) -> Result<(), BigNeonError> {
+    let claimed = match claimed {
+        Some(claimed) => claimed,
+        None => return Ok(()),
+    };
+
+    match response.body() {
+        Body::Binary(binary) if !response.status().is_server_error() => {
+            let body = str::from_utf8(binary.as_ref()).unwrap_or("").to_string();
+            claimed.set_response(response.status().as_u16(), body, conn)?;
+        }
+        _ => {
+            claimed.destroy(conn)?;
+        }
+    }
+
+    Ok(())
+}
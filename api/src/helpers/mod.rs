@@ -1 +1,6 @@
 pub mod application;
+pub mod assets;
+pub mod exchanges;
+pub mod idempotency;
+pub mod refunds;
+pub mod resales;
@@ -0,0 +1,288 @@
+use actix_web::HttpResponse;
+use bigneon_db::models::*;
+use config::Config;
+use diesel::Connection;
+use diesel::PgConnection;
+use errors::*;
+use helpers::application;
+use payments::PaymentProcessor;
+use std::cmp;
+use std::collections::HashMap;
+use utils::ServiceLocator;
+use uuid::Uuid;
+
+/// Refunds the given items, returning their tickets to the organization wallets and refunding
+/// the amount due through the payments made on the order. When `to_credit` is set the amount is
+/// added to the user's account credit instead, as are refunds of payments made with credit.
+/// Returns the amount refunded with its breakdown per payment method.
+pub fn refund_order_items(
+    order: &Order,
+    items: Vec<RefundItem>,
+    to_credit: bool,
+    current_user_id: Uuid,
+    config: &Config,
+    service_locator: &ServiceLocator,
+    connection: &PgConnection,
+) -> Result<(u32, HashMap<PaymentMethods, u32>), BigNeonError> {
+    let ticket_instance_ids = ticket_instance_ids(&items);
+
+    // Refund amount is fee inclusive if fee no longer applies to the order
+    let refund_due = order.refund(items, connection)?;
+
+    return_tickets_and_refund_payments(
+        order,
+        ticket_instance_ids,
+        refund_due,
+        to_credit,
+        current_user_id,
+        config,
+        service_locator,
+        connection,
+    )
+}
+
+/// Refunds tickets for a cancelled event like `refund_order_items`, taking the tickets back from
+/// whoever holds them, see `Order::refund_for_cancellation`
+pub fn refund_cancelled_order_items(
+    order: &Order,
+    items: Vec<RefundItem>,
+    current_user_id: Uuid,
+    config: &Config,
+    service_locator: &ServiceLocator,
+    connection: &PgConnection,
+) -> Result<(u32, HashMap<PaymentMethods, u32>), BigNeonError> {
+    let ticket_instance_ids = ticket_instance_ids(&items);
+    let refund_due = order.refund_for_cancellation(items, connection)?;
+
+    return_tickets_and_refund_payments(
+        order,
+        ticket_instance_ids,
+        refund_due,
+        false,
+        current_user_id,
+        config,
+        service_locator,
+        connection,
+    )
+}
+
+fn ticket_instance_ids(items: &[RefundItem]) -> Vec<Uuid> {
+    items
+        .iter()
+        .filter_map(|i| i.ticket_instance_id)
+        .collect::<Vec<Uuid>>()
+}
+
+fn return_tickets_and_refund_payments(
+    order: &Order,
+    ticket_instance_ids: Vec<Uuid>,
+    refund_due: u32,
+    to_credit: bool,
+    current_user_id: Uuid,
+    config: &Config,
+    service_locator: &ServiceLocator,
+    connection: &PgConnection,
+) -> Result<(u32, HashMap<PaymentMethods, u32>), BigNeonError> {
+    // Found while the tickets are still with the share payers
+    let share_payment_ids =
+        order.share_payment_ids_for_tickets(&ticket_instance_ids, connection)?;
+
+    // Transfer tickets back to the organization wallets, grouped by asset and the wallet that
+    // holds them as transferred tickets can be spread across several wallets
+    let mut tokens_per_asset: HashMap<(Uuid, Uuid), Vec<u64>> = HashMap::new();
+    let mut ticket_instances_per_asset: HashMap<(Uuid, Uuid), Vec<TicketInstance>> = HashMap::new();
+    let refunded_tickets =
+        RefundedTicket::find_by_ticket_instance_ids(ticket_instance_ids, connection)?
+            .into_iter()
+            .filter(|refund_data| refund_data.ticket_refunded_at.is_some());
+    for refunded_ticket in refunded_tickets {
+        let ticket = TicketInstance::find(refunded_ticket.ticket_instance_id, connection)?;
+        tokens_per_asset
+            .entry((ticket.asset_id, ticket.wallet_id))
+            .or_insert_with(|| Vec::new())
+            .push(ticket.token_id as u64);
+        ticket_instances_per_asset
+            .entry((ticket.asset_id, ticket.wallet_id))
+            .or_insert_with(|| Vec::new())
+            .push(ticket);
+    }
+    let mut modified_tokens: HashMap<(Uuid, Uuid), Vec<u64>> = HashMap::new();
+
+    let mut refund_breakdown: HashMap<PaymentMethods, u32> = HashMap::new();
+    let mut amount_refunded = 0;
+
+    // Begin transaction, if it fails at this point all transferred tickets are returned to wallets
+    match connection.transaction::<_, BigNeonError, _>(|| {
+        for ((asset_id, wallet_id), token_ids) in &tokens_per_asset {
+            let organization_id = Organization::find_by_asset_id(*asset_id, connection)?.id;
+            let organization_wallet =
+                Wallet::find_default_for_organization(organization_id, connection)?;
+            let asset = Asset::find(*asset_id, connection)?;
+            match asset.blockchain_asset_id {
+                Some(a) => {
+                    let user_wallet = Wallet::find(*wallet_id, connection)?;
+                    config.tari_client.transfer_tokens(&user_wallet.secret_key, &user_wallet.public_key,
+                                                             &a,
+                                                             token_ids.clone(),
+                                                             organization_wallet.public_key.clone(),
+                    )?;
+                    modified_tokens.insert((*asset_id, *wallet_id), token_ids.clone());
+                    match ticket_instances_per_asset.get(&(*asset_id, *wallet_id)) {
+                        Some(ticket_instances) => {
+                            for ticket_instance in ticket_instances {
+                                ticket_instance.set_wallet(&organization_wallet, connection)?;
+                            }
+                        }
+                        None => return Err(application::internal_server_error::<HttpResponse>(
+                            "No ticket instances exist for transferred tokens",
+                        ).unwrap_err()),
+                    }
+                },
+                None => return Err(application::internal_server_error::<HttpResponse>(
+                    "Could not complete this refund because the asset is not assigned on the blockchain",
+                ).unwrap_err())
+            }
+        }
+
+        refund_breakdown = refund_payments(
+            order,
+            refund_due,
+            &share_payment_ids,
+            to_credit,
+            current_user_id,
+            service_locator,
+            connection,
+        )?;
+        amount_refunded = refund_breakdown.values().sum();
+
+        Ok(())
+    }) {
+        Err(error) => {
+            for ((asset_id, wallet_id), token_ids) in &modified_tokens {
+                let organization_id = Organization::find_by_asset_id(*asset_id, connection)?.id;
+                let organization_wallet =
+                    Wallet::find_default_for_organization(organization_id, connection)?;
+                let asset = Asset::find(*asset_id, connection)?;
+                match asset.blockchain_asset_id {
+                    Some(a) => {
+                        let user_wallet = Wallet::find(*wallet_id, connection)?;
+                        config.tari_client.transfer_tokens(&organization_wallet.secret_key, &organization_wallet.public_key,
+                                                                 &a,
+                                                                 token_ids.clone(),
+                                                                 user_wallet.public_key.clone(),
+                        )?;
+                    },
+                    None => return Err(application::internal_server_error::<HttpResponse>(
+                        "Could not complete this refund because the asset is not assigned on the blockchain",
+                    ).unwrap_err()),
+                }
+            }
+
+            // Return error
+            return Err(error);
+        }
+        _ => (),
+    }
+
+    Ok((amount_refunded, refund_breakdown))
+}
+
+/// Refunds the amount due through the payments made on the order, or to the user's account
+/// credit when `to_credit` is set or the payment was made with credit. Payments listed in
+/// `preferred_payment_ids` are refunded first. Returns the breakdown of the amount refunded per
+/// payment method.
+pub fn refund_payments(
+    order: &Order,
+    refund_due: u32,
+    preferred_payment_ids: &[Uuid],
+    to_credit: bool,
+    current_user_id: Uuid,
+    service_locator: &ServiceLocator,
+    connection: &PgConnection,
+) -> Result<HashMap<PaymentMethods, u32>, BigNeonError> {
+    let mut refund_breakdown: HashMap<PaymentMethods, u32> = HashMap::new();
+    let mut payment_remaining_balance_map: HashMap<Option<String>, i64> = HashMap::new();
+    let mut amount_refunded = 0;
+
+    // Negative payments / refunds cancel out remaining payment balance
+    for payment in order.payments(connection)? {
+        // Ignore payments that were only authorized
+        if payment.status == PaymentStatus::Authorized {
+            continue;
+        }
+
+        *payment_remaining_balance_map
+            .entry(payment.external_reference)
+            .or_insert(0) += payment.amount;
+    }
+
+    let mut payments = order.payments(connection)?;
+    payments.sort_by_key(|p| !preferred_payment_ids.contains(&p.id));
+    for payment in payments {
+        if amount_refunded >= refund_due {
+            break;
+        } else if payment.status != PaymentStatus::Completed
+            && payment.status != PaymentStatus::Disputed
+        {
+            continue;
+        }
+
+        let remaining_balance = payment_remaining_balance_map
+            .get(&payment.external_reference)
+            .map(|n| *n)
+            .unwrap_or(0);
+        if remaining_balance == 0 {
+            continue;
+        }
+
+        let amount_to_refund = cmp::min(refund_due - amount_refunded, remaining_balance as u32);
+        let mut refund_data = None;
+        let mut refund_method = payment.payment_method;
+        if to_credit || payment.payment_method == PaymentMethods::Credit {
+            let credit_transaction = CreditTransaction::create(
+                order.on_behalf_of_user_id.unwrap_or(order.user_id),
+                CreditTransactionTypes::Refund,
+                amount_to_refund as i64,
+                Some(order.id),
+                None,
+                Some(current_user_id),
+            )
+            .commit(connection)?;
+            refund_data = Some(json!({ "credit_transaction_id": credit_transaction.id }));
+            refund_method = PaymentMethods::Credit;
+        } else if payment.payment_method == PaymentMethods::CreditCard {
+            // Refunds go back through the merchant account the payment was charged to, even if
+            // the organization has since moved to another account
+            let client = service_locator
+                .create_payment_processor(&payment.provider, payment.merchant_account_id.clone())?;
+
+            refund_data = match payment.external_reference {
+                Some(ref external_reference) => Some(
+                    client
+                        .partial_refund(external_reference, amount_to_refund)?
+                        .to_json()?,
+                ),
+                None => {
+                    return Err(application::internal_server_error::<HttpResponse>(&format!(
+                        "Unable to refund amount owed payment {} lacks external reference",
+                        payment.id
+                    ))
+                    .unwrap_err())
+                }
+            };
+        }
+        payment.log_refund(current_user_id, amount_to_refund, refund_data, connection)?;
+        *refund_breakdown.entry(refund_method).or_insert(0) += amount_to_refund;
+        amount_refunded += amount_to_refund;
+    }
+
+    if amount_refunded < refund_due {
+        return Err(application::internal_server_error::<HttpResponse>(&format!(
+            "Unable to refund amount owed {} refunded, {} due",
+            amount_refunded, refund_due
+        ))
+        .unwrap_err());
+    }
+
+    Ok(refund_breakdown)
+}
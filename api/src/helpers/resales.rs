@@ -0,0 +1,21 @@
+use bigneon_db::models::*;
+use diesel::PgConnection;
+use errors::*;
+
+/// Resale listings bought with the order along with the tickets they are for
+pub fn resold_tickets(
+    order: &Order,
+    connection: &PgConnection,
+) -> Result<Vec<(ResaleListing, TicketInstance)>, BigNeonError> {
+    let mut resold_tickets = Vec::new();
+    for item in order.items(connection)? {
+        if item.item_type != OrderItemTypes::Resale {
+            continue;
+        }
+        if let Some(listing) = ResaleListing::find_by_order_item_id(item.id, connection)? {
+            let ticket = TicketInstance::find(listing.ticket_instance_id, connection)?;
+            resold_tickets.push((listing, ticket));
+        }
+    }
+    Ok(resold_tickets)
+}
@@ -21,6 +21,9 @@ extern crate lettre_email;
 extern crate log;
 #[macro_use]
 extern crate logging;
+extern crate openssl;
+extern crate printpdf;
+extern crate qrcode;
 extern crate r2d2;
 extern crate regex;
 extern crate reqwest;
@@ -39,6 +42,7 @@ extern crate uuid;
 extern crate validator;
 #[macro_use]
 extern crate validator_derive;
+extern crate zip;
 
 pub mod auth;
 pub mod communications;
@@ -18,6 +18,7 @@ pub struct AdminDisplayTicketType {
     pub limit_per_person: u32,
     pub ticket_pricing: Vec<DisplayTicketPricing>,
     pub price_in_cents: i64,
+    pub allow_reentry: bool,
 }
 
 impl AdminDisplayTicketType {
@@ -50,6 +51,7 @@ impl AdminDisplayTicketType {
             increment: ticket_type.increment as u32,
             limit_per_person: ticket_type.limit_per_person as u32,
             price_in_cents: ticket_type.price_in_cents,
+            allow_reentry: ticket_type.allow_reentry,
         })
     }
 }
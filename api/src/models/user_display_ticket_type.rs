@@ -2,7 +2,6 @@ use bigneon_db::prelude::*;
 use chrono::NaiveDateTime;
 use diesel::PgConnection;
 use models::DisplayTicketPricing;
-use std::cmp;
 use uuid::Uuid;
 
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
@@ -94,10 +93,7 @@ impl UserDisplayTicketType {
                 }
                 HoldTypes::Discount => {
                     result.ticket_pricing = result.ticket_pricing.map(|tp| DisplayTicketPricing {
-                        price_in_cents: cmp::max(
-                            0,
-                            tp.price_in_cents - h.discount_in_cents.unwrap_or(0),
-                        ),
+                        price_in_cents: h.discounted_price(tp.price_in_cents),
                         ..tp
                     })
                 }
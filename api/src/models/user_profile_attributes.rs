@@ -21,6 +21,7 @@ pub struct UserProfileAttributes {
     #[validate(url(message = "Cover photo URL is invalid"))]
     #[serde(default, deserialize_with = "double_option_deserialize_unless_blank")]
     pub cover_photo_url: Option<Option<String>>,
+    pub marketing_opt_in: Option<bool>,
 }
 
 impl From<UserProfileAttributes> for UserEditableAttributes {
@@ -33,6 +34,7 @@ impl From<UserProfileAttributes> for UserEditableAttributes {
             profile_pic_url: attributes.profile_pic_url,
             thumb_profile_pic_url: attributes.thumb_profile_pic_url,
             cover_photo_url: attributes.cover_photo_url,
+            marketing_opt_in: attributes.marketing_opt_in,
             ..Default::default()
         }
     }
@@ -0,0 +1,219 @@
+use payments::*;
+use reqwest;
+use serde_json;
+
+const BRAINTREE_VERSION: &'static str = "2019-01-01";
+const PRODUCTION_URL: &'static str = "https://payments.braintree-api.com/graphql";
+const SANDBOX_URL: &'static str = "https://payments.sandbox.braintree-api.com/graphql";
+
+const VAULT_PAYMENT_METHOD: &'static str =
+    "mutation VaultPaymentMethod($input: VaultPaymentMethodInput!) {
+    vaultPaymentMethod(input: $input) { paymentMethod { id } }
+}";
+const AUTHORIZE_PAYMENT_METHOD: &'static str =
+    "mutation AuthorizePaymentMethod($input: AuthorizePaymentMethodInput!) {
+    authorizePaymentMethod(input: $input) { transaction { id status } }
+}";
+const CAPTURE_TRANSACTION: &'static str =
+    "mutation CaptureTransaction($input: CaptureTransactionInput!) {
+    captureTransaction(input: $input) { transaction { id status } }
+}";
+const REVERSE_TRANSACTION: &'static str = "mutation ReverseTransaction($input: ReverseTransactionInput!) {
+    reverseTransaction(input: $input) { reversal { ... on Transaction { id } ... on Refund { id } } }
+}";
+const REFUND_TRANSACTION: &'static str =
+    "mutation RefundTransaction($input: RefundTransactionInput!) {
+    refundTransaction(input: $input) { refund { id status } }
+}";
+
+/// Adapter for the Braintree GraphQL API. Charges are made against the organization's merchant
+/// account when one is configured, otherwise the gateway's default merchant account is used.
+pub struct BraintreePaymentProcessor {
+    public_key: String,
+    private_key: String,
+    url: &'static str,
+    merchant_account_id: Option<String>,
+}
+
+impl BraintreePaymentProcessor {
+    pub fn new(
+        public_key: String,
+        private_key: String,
+        production: bool,
+        merchant_account_id: Option<String>,
+    ) -> BraintreePaymentProcessor {
+        BraintreePaymentProcessor {
+            public_key,
+            private_key,
+            url: if production {
+                PRODUCTION_URL
+            } else {
+                SANDBOX_URL
+            },
+            merchant_account_id,
+        }
+    }
+
+    /// Executes the mutation and returns the raw response along with the value at `path`
+    fn execute(
+        &self,
+        query: &str,
+        input: serde_json::Value,
+        path: &[&str],
+    ) -> Result<(String, serde_json::Value), PaymentProcessorError> {
+        let client = reqwest::Client::new();
+        let mut resp = client
+            .post(self.url)
+            .basic_auth(&self.public_key, Some(&self.private_key))
+            .header("Braintree-Version", BRAINTREE_VERSION)
+            .json(&json!({ "query": query, "variables": { "input": input } }))
+            .send()
+            .map_err(|e| braintree_error(format!("Error calling Braintree: {}", e)))?;
+        let raw = resp
+            .text()
+            .map_err(|e| braintree_error(format!("Error reading Braintree response: {}", e)))?;
+        if !resp.status().is_success() {
+            return Err(braintree_error(format!(
+                "Error calling Braintree: HTTP Code {}: Body:{}",
+                resp.status(),
+                raw
+            )));
+        }
+
+        let body: serde_json::Value = serde_json::from_str(&raw)
+            .map_err(|e| braintree_error(format!("Error deserializing response:{}", e)))?;
+        if let Some(errors) = body.get("errors") {
+            return Err(braintree_error(format!("Braintree error: {}", errors)));
+        }
+
+        let mut value = &body["data"];
+        for key in path {
+            value = &value[*key];
+        }
+        Ok((raw.clone(), value.clone()))
+    }
+
+    fn amount(amount: i64) -> String {
+        format!("{}.{:02}", amount / 100, amount % 100)
+    }
+}
+
+fn braintree_error(description: String) -> PaymentProcessorError {
+    PaymentProcessorError {
+        description,
+        cause: None,
+    }
+}
+
+fn id_from(value: serde_json::Value) -> Result<String, PaymentProcessorError> {
+    value
+        .as_str()
+        .map(|id| id.to_string())
+        .ok_or_else(|| braintree_error("Braintree response did not contain an id".to_string()))
+}
+
+impl PaymentProcessor for BraintreePaymentProcessor {
+    fn create_token_for_repeat_charges(
+        &self,
+        token: &str,
+        _description: &str,
+    ) -> Result<RepeatChargeToken, PaymentProcessorError> {
+        let (raw, id) = self.execute(
+            VAULT_PAYMENT_METHOD,
+            json!({ "paymentMethodId": token }),
+            &["vaultPaymentMethod", "paymentMethod", "id"],
+        )?;
+        Ok(RepeatChargeToken {
+            token: id_from(id)?,
+            raw,
+        })
+    }
+
+    fn update_repeat_token(
+        &self,
+        _repeat_token: &str,
+        token: &str,
+        description: &str,
+    ) -> Result<RepeatChargeToken, PaymentProcessorError> {
+        // Vaulted payment methods cannot be changed, the new card replaces the old one
+        self.create_token_for_repeat_charges(token, description)
+    }
+
+    fn auth(
+        &self,
+        token: &str,
+        amount: i64,
+        _currency: &str,
+        description: &str,
+        metadata: Vec<(String, String)>,
+    ) -> Result<ChargeAuthResult, PaymentProcessorError> {
+        let order_id = metadata
+            .iter()
+            .find(|(key, _)| key == "order_id")
+            .map(|(_, value)| value.clone());
+        let (raw, id) = self.execute(
+            AUTHORIZE_PAYMENT_METHOD,
+            json!({
+                "paymentMethodId": token,
+                "transaction": {
+                    "amount": BraintreePaymentProcessor::amount(amount),
+                    "merchantAccountId": self.merchant_account_id,
+                    "orderId": order_id,
+                    "descriptor": { "name": description },
+                }
+            }),
+            &["authorizePaymentMethod", "transaction", "id"],
+        )?;
+        Ok(ChargeAuthResult {
+            id: id_from(id)?,
+            raw,
+        })
+    }
+
+    fn refund(&self, auth_token: &str) -> Result<ChargeAuthResult, PaymentProcessorError> {
+        // Reversing voids authorized transactions and refunds settled ones
+        let (raw, id) = self.execute(
+            REVERSE_TRANSACTION,
+            json!({ "transactionId": auth_token }),
+            &["reverseTransaction", "reversal", "id"],
+        )?;
+        Ok(ChargeAuthResult {
+            id: id_from(id)?,
+            raw,
+        })
+    }
+
+    fn partial_refund(
+        &self,
+        auth_token: &str,
+        amount: u32,
+    ) -> Result<ChargeAuthResult, PaymentProcessorError> {
+        let (raw, id) = self.execute(
+            REFUND_TRANSACTION,
+            json!({
+                "transactionId": auth_token,
+                "refund": { "amount": BraintreePaymentProcessor::amount(amount as i64) }
+            }),
+            &["refundTransaction", "refund", "id"],
+        )?;
+        Ok(ChargeAuthResult {
+            id: id_from(id)?,
+            raw,
+        })
+    }
+
+    fn complete_authed_charge(
+        &self,
+        auth_token: &str,
+    ) -> Result<ChargeResult, PaymentProcessorError> {
+        let (raw, id) = self.execute(
+            CAPTURE_TRANSACTION,
+            json!({ "transactionId": auth_token }),
+            &["captureTransaction", "transaction", "id"],
+        )?;
+        Ok(ChargeResult {
+            id: id_from(id)?,
+            raw,
+        })
+    }
+}
@@ -0,0 +1,108 @@
+use serde_json;
+use std::error::Error;
+use std::fmt;
+
+pub use self::braintree::*;
+pub use self::sandbox::*;
+pub use self::stripe::*;
+
+mod braintree;
+mod sandbox;
+mod stripe;
+
+pub trait PaymentProcessor {
+    fn create_token_for_repeat_charges(
+        &self,
+        token: &str,
+        description: &str,
+    ) -> Result<RepeatChargeToken, PaymentProcessorError>;
+
+    fn update_repeat_token(
+        &self,
+        repeat_token: &str,
+        token: &str,
+        description: &str,
+    ) -> Result<RepeatChargeToken, PaymentProcessorError>;
+
+    fn auth(
+        &self,
+        token: &str,
+        amount: i64,
+        currency: &str,
+        description: &str,
+        metadata: Vec<(String, String)>,
+    ) -> Result<ChargeAuthResult, PaymentProcessorError>;
+
+    fn refund(&self, auth_token: &str) -> Result<ChargeAuthResult, PaymentProcessorError>;
+
+    fn partial_refund(
+        &self,
+        auth_token: &str,
+        amount: u32,
+    ) -> Result<ChargeAuthResult, PaymentProcessorError>;
+
+    fn complete_authed_charge(
+        &self,
+        auth_token: &str,
+    ) -> Result<ChargeResult, PaymentProcessorError>;
+}
+
+pub struct RepeatChargeToken {
+    pub token: String,
+    pub(crate) raw: String,
+}
+
+use serde_json::Error as SerdeError;
+impl RepeatChargeToken {
+    pub fn to_json(&self) -> Result<serde_json::Value, SerdeError> {
+        serde_json::from_str(&self.raw)
+    }
+}
+
+#[derive(Debug)]
+pub struct PaymentProcessorError {
+    pub description: String,
+
+    pub cause: Option<Box<dyn Error>>,
+}
+
+unsafe impl Send for PaymentProcessorError {}
+unsafe impl Sync for PaymentProcessorError {}
+
+impl Error for PaymentProcessorError {
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+impl fmt::Display for PaymentProcessorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match &self.cause {
+            Some(c) => write!(f, "{} caused by: {}", self.description, c.description()),
+            None => write!(f, "{}", self.description),
+        }
+    }
+}
+
+pub struct ChargeAuthResult {
+    pub id: String,
+    pub(crate) raw: String,
+}
+
+impl ChargeAuthResult {
+    pub fn to_json(&self) -> Result<serde_json::Value, serde_json::Error> {
+        serde_json::from_str(&self.raw)
+    }
+}
+
+#[derive(Debug)]
+pub struct ChargeResult {
+    pub id: String,
+    pub(crate) raw: String,
+}
+
+impl ChargeResult {
+    pub fn to_json(&self) -> Result<serde_json::Value, serde_json::Error> {
+        serde_json::from_str(&self.raw)
+    }
+}
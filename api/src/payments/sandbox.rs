@@ -0,0 +1,118 @@
+use payments::*;
+use serde_json;
+use uuid::Uuid;
+
+/// Local payment processor for test and demo environments. No money is moved; every request
+/// succeeds unless the token contains `decline`.
+pub struct SandboxPaymentProcessor {
+    merchant_account_id: Option<String>,
+}
+
+impl SandboxPaymentProcessor {
+    pub fn new(merchant_account_id: Option<String>) -> SandboxPaymentProcessor {
+        SandboxPaymentProcessor {
+            merchant_account_id,
+        }
+    }
+
+    fn check_token(&self, token: &str) -> Result<(), PaymentProcessorError> {
+        if token.contains("decline") {
+            return Err(PaymentProcessorError {
+                description: "Sandbox payment was declined".to_string(),
+                cause: None,
+            });
+        }
+        Ok(())
+    }
+
+    fn new_id(prefix: &str) -> String {
+        format!("sandbox_{}_{}", prefix, Uuid::new_v4().simple())
+    }
+}
+
+impl PaymentProcessor for SandboxPaymentProcessor {
+    fn create_token_for_repeat_charges(
+        &self,
+        token: &str,
+        description: &str,
+    ) -> Result<RepeatChargeToken, PaymentProcessorError> {
+        self.check_token(token)?;
+        let id = SandboxPaymentProcessor::new_id("cus");
+        Ok(RepeatChargeToken {
+            raw: json!({ "id": id, "description": description }).to_string(),
+            token: id,
+        })
+    }
+
+    fn update_repeat_token(
+        &self,
+        repeat_token: &str,
+        token: &str,
+        description: &str,
+    ) -> Result<RepeatChargeToken, PaymentProcessorError> {
+        self.check_token(token)?;
+        Ok(RepeatChargeToken {
+            token: repeat_token.to_string(),
+            raw: json!({ "id": repeat_token, "description": description }).to_string(),
+        })
+    }
+
+    fn auth(
+        &self,
+        token: &str,
+        amount: i64,
+        currency: &str,
+        description: &str,
+        metadata: Vec<(String, String)>,
+    ) -> Result<ChargeAuthResult, PaymentProcessorError> {
+        self.check_token(token)?;
+        let id = SandboxPaymentProcessor::new_id("ch");
+        let metadata: serde_json::Map<String, serde_json::Value> = metadata
+            .into_iter()
+            .map(|(key, value)| (key, json!(value)))
+            .collect();
+        Ok(ChargeAuthResult {
+            raw: json!({
+                "id": id,
+                "amount": amount,
+                "currency": currency,
+                "description": description,
+                "merchant_account_id": self.merchant_account_id,
+                "metadata": metadata,
+                "captured": false,
+            })
+            .to_string(),
+            id,
+        })
+    }
+
+    fn refund(&self, auth_token: &str) -> Result<ChargeAuthResult, PaymentProcessorError> {
+        let id = SandboxPaymentProcessor::new_id("re");
+        Ok(ChargeAuthResult {
+            raw: json!({ "id": id, "charge": auth_token }).to_string(),
+            id,
+        })
+    }
+
+    fn partial_refund(
+        &self,
+        auth_token: &str,
+        amount: u32,
+    ) -> Result<ChargeAuthResult, PaymentProcessorError> {
+        let id = SandboxPaymentProcessor::new_id("re");
+        Ok(ChargeAuthResult {
+            raw: json!({ "id": id, "charge": auth_token, "amount": amount }).to_string(),
+            id,
+        })
+    }
+
+    fn complete_authed_charge(
+        &self,
+        auth_token: &str,
+    ) -> Result<ChargeResult, PaymentProcessorError> {
+        Ok(ChargeResult {
+            id: auth_token.to_string(),
+            raw: json!({ "id": auth_token, "captured": true }).to_string(),
+        })
+    }
+}
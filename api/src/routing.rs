@@ -32,16 +32,41 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     .resource("/cart/checkout", |r| {
         r.method(Method::POST).with(cart::checkout);
     })
+    .resource("/cart/gift_cards", |r| {
+        r.method(Method::PUT).with(cart::update_gift_cards);
+    })
+    .resource("/cart/resale_listings", |r| {
+        r.method(Method::PUT).with(cart::update_resale_listings);
+    })
+    .resource("/cart/restore", |r| {
+        r.method(Method::POST).with(cart::restore);
+    })
+    .resource("/cart/split", |r| {
+        r.method(Method::POST).with(cart::split);
+    })
+    .resource("/code_campaigns/{id}", |r| {
+        r.method(Method::GET).with(code_campaigns::show);
+    })
     .resource("/codes/{id}", |r| {
         r.method(Method::GET).with(codes::show);
         r.method(Method::PUT).with(codes::update);
         r.method(Method::DELETE).with(codes::destroy);
     })
+    .resource("/comps/claim", |r| {
+        r.method(Method::POST).with(comps::claim);
+    })
     .resource("/comps/{id}", |r| {
         r.method(Method::GET).with(comps::show);
         r.method(Method::PATCH).with(comps::update);
         r.method(Method::DELETE).with(comps::destroy);
     })
+    .resource("/comps/{id}/send", |r| {
+        r.method(Method::POST).with(comps::send);
+    })
+    .resource("/event_series/{id}", |r| {
+        r.method(Method::GET).with(event_series::show);
+        r.method(Method::PUT).with(event_series::update);
+    })
     .resource("/events", |r| {
         r.method(Method::GET).with(events::index);
         r.method(Method::POST).with(events::create);
@@ -58,6 +83,16 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
         r.method(Method::POST).with(events::add_artist);
         r.method(Method::PUT).with(events::update_artists);
     })
+    .resource("/events/{id}/cancellations", |r| {
+        r.method(Method::GET).with(events::cancellations);
+    })
+    .resource("/events/{id}/clone", |r| {
+        r.method(Method::POST).with(events::clone);
+    })
+    .resource("/events/{id}/code_campaigns", |r| {
+        r.method(Method::GET).with(code_campaigns::index);
+        r.method(Method::POST).with(code_campaigns::create);
+    })
     .resource("/events/{id}/codes", |r| {
         r.method(Method::GET).with(events::codes);
         r.method(Method::POST).with(codes::create);
@@ -80,6 +115,12 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
         r.method(Method::POST).with(events::add_interest);
         r.method(Method::DELETE).with(events::remove_interest);
     })
+    .resource("/events/{id}/offline_scanning", |r| {
+        r.method(Method::GET).with(events::offline_scanning);
+    })
+    .resource("/events/{id}/offline_scans", |r| {
+        r.method(Method::POST).with(events::upload_offline_scans);
+    })
     .resource("/events/{id}/publish", |r| {
         r.method(Method::POST).with(events::publish);
     })
@@ -89,6 +130,15 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     .resource("/events/{id}/redeem/{ticket_instance_id}", |r| {
         r.method(Method::POST).with(events::redeem_ticket);
     })
+    .resource("/events/{id}/resale_listings", |r| {
+        r.method(Method::GET).with(resale_listings::index_for_event);
+    })
+    .resource("/events/{id}/scans", |r| {
+        r.method(Method::GET).with(events::scans);
+    })
+    .resource("/events/{id}/series", |r| {
+        r.method(Method::POST).with(event_series::create);
+    })
     .resource("/events/{id}/tickets", |r| {
         r.method(Method::GET).with(tickets::index);
     })
@@ -96,6 +146,21 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
         r.method(Method::GET).with(ticket_types::index);
         r.method(Method::POST).with(ticket_types::create);
     })
+    .resource(
+        "/events/{event_id}/ticket_types/{ticket_type_id}/seats",
+        |r| {
+            r.method(Method::GET).with(ticket_types::seats);
+            r.method(Method::POST).with(ticket_types::assign_seats);
+        },
+    )
+    .resource(
+        "/events/{event_id}/ticket_types/{ticket_type_id}/waitlist",
+        |r| {
+            r.method(Method::GET).with(waitlist_entries::show);
+            r.method(Method::POST).with(waitlist_entries::create);
+            r.method(Method::DELETE).with(waitlist_entries::destroy);
+        },
+    )
     .resource("/events/{event_id}/ticket_types/{ticket_type_id}", |r| {
         r.method(Method::PATCH).with(ticket_types::update);
         r.method(Method::DELETE).with(ticket_types::cancel);
@@ -103,6 +168,15 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     .resource("/external/facebook/web_login", |r| {
         r.method(Method::POST).with(external::facebook::web_login)
     })
+    .resource("/external/stripe/webhook", |r| {
+        r.method(Method::POST).with(external::stripe::webhook)
+    })
+    .resource("/gift_cards", |r| {
+        r.method(Method::GET).with(gift_cards::index);
+    })
+    .resource("/gift_cards/redeem", |r| {
+        r.method(Method::POST).with(gift_cards::redeem);
+    })
     .resource("/invitations/{id}", |r| {
         r.method(Method::GET).with(organization_invites::view);
     })
@@ -114,6 +188,9 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
         r.method(Method::GET).with(comps::index);
         r.method(Method::POST).with(comps::create);
     })
+    .resource("/holds/{id}/comps/import", |r| {
+        r.method(Method::POST).with(comps::import);
+    })
     .resource("/holds/{id}/split", |r| {
         r.method(Method::POST).with(holds::split);
     })
@@ -122,15 +199,27 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
         r.method(Method::GET).with(holds::show);
         r.method(Method::DELETE).with(holds::destroy);
     })
+    .resource("/order_shares/{id}/checkout", |r| {
+        r.method(Method::POST).with(order_shares::checkout);
+    })
+    .resource("/order_shares/{id}", |r| {
+        r.method(Method::GET).with(order_shares::show);
+    })
     .resource("/orders", |r| {
         r.method(Method::GET).with(orders::index);
     })
     .resource("/orders/{id}/details", |r| {
         r.method(Method::GET).with(orders::details);
     })
+    .resource("/orders/{id}/exchange", |r| {
+        r.method(Method::POST).with(orders::exchange);
+    })
     .resource("/orders/{id}/refund", |r| {
         r.method(Method::PATCH).with(orders::refund);
     })
+    .resource("/orders/{id}/reschedule_refund", |r| {
+        r.method(Method::POST).with(orders::reschedule_refund);
+    })
     .resource("/orders/{id}/tickets", |r| {
         r.method(Method::GET).with(orders::tickets);
     })
@@ -165,6 +254,17 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
         r.method(Method::GET).with(organization_invites::index);
         r.method(Method::POST).with(organization_invites::create);
     })
+    .resource("/organizations/{id}/payment_provider", |r| {
+        r.method(Method::PUT)
+            .with(organizations::update_payment_provider);
+    })
+    .resource("/organizations/{id}/resale_payouts", |r| {
+        r.method(Method::GET).with(resale_payouts::index);
+    })
+    .resource("/organizations/{id}/resale_price_cap", |r| {
+        r.method(Method::PUT)
+            .with(organizations::update_resale_price_cap);
+    })
     .resource("/organizations/{id}/users", |r| {
         r.method(Method::POST)
             .with(organizations::add_or_replace_user);
@@ -188,6 +288,28 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
         r.method(Method::GET).with(organizations::index);
         r.method(Method::POST).with(organizations::create);
     })
+    .resource(
+        "/passes/v1/devices/{device_library_identifier}/registrations/{pass_type_identifier}/{serial_number}",
+        |r| {
+            r.method(Method::POST).with(wallet_passes::register_device);
+            r.method(Method::DELETE).with(wallet_passes::unregister_device);
+        },
+    )
+    .resource(
+        "/passes/v1/devices/{device_library_identifier}/registrations/{pass_type_identifier}",
+        |r| {
+            r.method(Method::GET).with(wallet_passes::updated_passes);
+        },
+    )
+    .resource("/passes/v1/log", |r| {
+        r.method(Method::POST).with(wallet_passes::log);
+    })
+    .resource(
+        "/passes/v1/passes/{pass_type_identifier}/{serial_number}",
+        |r| {
+            r.method(Method::GET).with(wallet_passes::show_latest_apple);
+        },
+    )
     .resource("/password_reset", |r| {
         r.method(Method::POST).with(password_resets::create);
         r.method(Method::PUT).with(password_resets::update);
@@ -198,6 +320,10 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     .resource("/redemption_codes/{code}", |r| {
         r.method(Method::GET).with(redemption_codes::show)
     })
+    .resource("/regions/{id}/tax_rules", |r| {
+        r.method(Method::GET).with(tax_rules::index_for_region);
+        r.method(Method::POST).with(tax_rules::create_for_region);
+    })
     .resource("/regions/{id}", |r| {
         r.method(Method::GET).with(regions::show);
         r.method(Method::PUT).with(regions::update);
@@ -209,14 +335,36 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     .resource("/reports/{id}", |r| {
         r.method(Method::GET).with(reports::get_report);
     })
+    .resource("/resale_listings/{id}", |r| {
+        r.method(Method::DELETE).with(resale_listings::destroy);
+    })
+    .resource("/resale_listings", |r| {
+        r.method(Method::GET).with(resale_listings::index);
+        r.method(Method::POST).with(resale_listings::create);
+    })
+    .resource("/resale_payouts/{id}/paid", |r| {
+        r.method(Method::POST).with(resale_payouts::paid);
+    })
     .resource("/status", |r| {
         r.method(Method::GET).f(|_| HttpResponse::Ok())
     })
+    .resource("/seat_sections/{id}/seats", |r| {
+        r.method(Method::POST).with(seat_sections::add_seats);
+    })
+    .resource("/seat_sections/{id}", |r| {
+        r.method(Method::GET).with(seat_sections::show);
+        r.method(Method::PUT).with(seat_sections::update);
+        r.method(Method::DELETE).with(seat_sections::delete);
+    })
     .resource("/stages/{id}", |r| {
         r.method(Method::GET).with(stages::show);
         r.method(Method::PUT).with(stages::update);
         r.method(Method::DELETE).with(stages::delete);
     })
+    .resource("/tax_rules/{id}", |r| {
+        r.method(Method::PUT).with(tax_rules::update);
+        r.method(Method::DELETE).with(tax_rules::destroy);
+    })
     .resource("/tickets/transfer", |r| {
         r.method(Method::POST).with(tickets::transfer_authorization);
     })
@@ -236,6 +384,16 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     .resource("/tickets/{id}/redeem", |r| {
         r.method(Method::GET).with(tickets::show_redeemable_ticket);
     })
+    .resource("/tickets/{id}/redeem/signed", |r| {
+        r.method(Method::GET)
+            .with(tickets::show_signed_redeem_payload);
+    })
+    .resource("/tickets/{id}/wallet_pass/apple", |r| {
+        r.method(Method::GET).with(wallet_passes::show_apple);
+    })
+    .resource("/tickets/{id}/wallet_pass/google", |r| {
+        r.method(Method::GET).with(wallet_passes::show_google);
+    })
     .resource("/users/me", |r| {
         r.method(Method::GET).with(users::current_user);
         r.method(Method::PUT).with(users::update_current_user);
@@ -275,10 +433,18 @@ pub fn routes(app: &mut CorsBuilder<AppState>) -> App<AppState> {
     .resource("/venues/{id}/organizations", |r| {
         r.method(Method::POST).with(venues::add_to_organization);
     })
+    .resource("/venues/{id}/seat_sections", |r| {
+        r.method(Method::POST).with(seat_sections::create);
+        r.method(Method::GET).with(seat_sections::index);
+    })
     .resource("/venues/{id}/stages", |r| {
         r.method(Method::POST).with(stages::create);
         r.method(Method::GET).with(stages::index);
     })
+    .resource("/venues/{id}/tax_rules", |r| {
+        r.method(Method::GET).with(tax_rules::index_for_venue);
+        r.method(Method::POST).with(tax_rules::create_for_venue);
+    })
     .resource("/venues/{id}/toggle_privacy", |r| {
         r.method(Method::PUT).with(venues::toggle_privacy);
     })
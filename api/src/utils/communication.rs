@@ -11,7 +11,9 @@ use bigneon_db::models::*;
 use config::{Config, Environment};
 use errors::*;
 use futures::future::Either;
+use log::Level::Error;
 use utils::sendgrid::mail as sendgrid;
+use utils::ticket_pdfs;
 use utils::twilio;
 
 pub type TemplateData = HashMap<String, String>;
@@ -64,6 +66,42 @@ impl CommAddress {
         self.addresses.push(address.clone());
     }
 }
+
+/// Files attached to an email. They are rendered when the email is sent so the queued
+/// communication only holds what is needed to render them.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub enum CommAttachment {
+    TicketPdf {
+        title: String,
+        ticket_instance_ids: Vec<Uuid>,
+        holder_user_id: Uuid,
+    },
+}
+
+impl CommAttachment {
+    pub fn filename(&self) -> String {
+        match self {
+            CommAttachment::TicketPdf { .. } => "tickets.pdf".to_string(),
+        }
+    }
+
+    pub fn content_type(&self) -> String {
+        match self {
+            CommAttachment::TicketPdf { .. } => ticket_pdfs::PDF_CONTENT_TYPE.to_string(),
+        }
+    }
+
+    pub fn render(&self, connection: &PgConnection) -> Result<Vec<u8>, BigNeonError> {
+        match self {
+            CommAttachment::TicketPdf {
+                title,
+                ticket_instance_ids,
+                holder_user_id,
+            } => ticket_pdfs::render(title, ticket_instance_ids, *holder_user_id, connection),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Communication {
     pub comm_type: CommunicationType,
@@ -73,6 +111,8 @@ pub struct Communication {
     pub destinations: CommAddress,
     pub template_id: Option<String>,
     pub template_data: Option<Vec<TemplateData>>,
+    #[serde(default)]
+    pub attachments: Vec<CommAttachment>,
 }
 
 impl Communication {
@@ -93,9 +133,37 @@ impl Communication {
             destinations,
             template_id,
             template_data,
+            attachments: Vec::new(),
         }
     }
 
+    /// Attachments are only sent with emails
+    pub fn with_attachments(mut self, attachments: Vec<CommAttachment>) -> Communication {
+        self.attachments = attachments;
+        self
+    }
+
+    /// Attachments that can't be rendered are left out rather than holding up the email
+    fn render_attachments(&self, connection: &PgConnection) -> Vec<sendgrid::SGAttachment> {
+        self.attachments
+            .iter()
+            .filter_map(|attachment| match attachment.render(connection) {
+                Ok(data) => Some(sendgrid::SGAttachment::new(
+                    attachment.filename(),
+                    attachment.content_type(),
+                    &data,
+                )),
+                Err(e) => {
+                    jlog!(Error, "Could not render email attachment", {
+                        "filename": attachment.filename(),
+                        "error": e.to_string()
+                    });
+                    None
+                }
+            })
+            .collect()
+    }
+
     pub fn queue(&self, connection: &PgConnection) -> Result<(), BigNeonError> {
         DomainAction::create(
             None,
@@ -121,6 +189,7 @@ impl Communication {
     pub fn send_async(
         domain_action: &DomainAction,
         config: &Config,
+        connection: &PgConnection,
     ) -> impl Future<Item = (), Error = BigNeonError> {
         let communication: Communication =
             match serde_json::from_value(domain_action.payload.clone()) {
@@ -153,6 +222,7 @@ impl Communication {
                                     &destination_addresses,
                                     communication.template_id.clone().unwrap(),
                                     communication.template_data.as_ref().unwrap(),
+                                    communication.render_attachments(connection),
                                 )
                             }
                             CommunicationType::Sms => twilio::send_sms_async(
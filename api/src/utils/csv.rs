@@ -0,0 +1,75 @@
+pub const CSV_CONTENT_TYPE: &str = "text/csv";
+
+/// Writes the header and rows as RFC 4180 CSV, quoting fields that contain commas, quotes or
+/// line breaks
+pub fn write(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut csv = write_row(headers.iter().map(|h| h.to_string()));
+    for row in rows {
+        csv.push_str(&write_row(row.iter().cloned()));
+    }
+    csv
+}
+
+/// Reads RFC 4180 CSV into rows of fields, skipping blank lines. Quoted fields may contain
+/// commas, escaped quotes and line breaks.
+pub fn parse(content: &str) -> Vec<Vec<String>> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_quotes = true,
+            ',' => row.push(field.split_off(0)),
+            '\r' | '\n' => {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                row.push(field.split_off(0));
+                push_row(&mut rows, row.split_off(0));
+            }
+            _ => field.push(c),
+        }
+    }
+    row.push(field);
+    push_row(&mut rows, row);
+
+    rows
+}
+
+fn push_row(rows: &mut Vec<Vec<String>>, row: Vec<String>) {
+    if row.iter().any(|f| !f.trim().is_empty()) {
+        rows.push(row);
+    }
+}
+
+fn write_row<I: Iterator<Item = String>>(fields: I) -> String {
+    let mut row = fields
+        .map(|f| escape(&f))
+        .collect::<Vec<String>>()
+        .join(",");
+    row.push_str("\r\n");
+    row
+}
+
+fn escape(field: &str) -> String {
+    if field.contains(|c| c == ',' || c == '"' || c == '\r' || c == '\n') {
+        format!("\"{}\"", field.replace("\"", "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -1,9 +1,12 @@
 pub use self::service_locator::*;
 
 pub mod communication;
+pub mod csv;
 pub mod google_recaptcha;
 pub mod marketing_contacts;
 pub mod sendgrid;
 mod service_locator;
 pub mod spotify;
+pub mod ticket_pdfs;
 pub mod twilio;
+pub mod wallet_passes;
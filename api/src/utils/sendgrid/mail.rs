@@ -1,5 +1,6 @@
 use errors::*;
 use futures::future::Either;
+use openssl::base64;
 use reqwest::async::Client as AsyncClient;
 use reqwest::Client;
 use serde_json;
@@ -105,6 +106,7 @@ pub fn send_email_template_async(
     dest_email_addresses: &[String],
     template_id: String,
     template_data: &[TemplateData],
+    attachments: Vec<SGAttachment>,
 ) -> Box<Future<Item = (), Error = BigNeonError>> {
     Box::new(if dest_email_addresses.len() != template_data.len() {
         Either::A(future::err(
@@ -129,6 +131,7 @@ pub fn send_email_template_async(
 
         let msg_content = SGContent::new();
         sg_message.content.push(msg_content);
+        sg_message.attachments = attachments;
 
         Either::B(sg_message.send_async(&sg_api_key))
     })
@@ -177,6 +180,26 @@ impl SGContent {
     }
 }
 
+#[derive(Clone, Serialize)]
+pub struct SGAttachment {
+    pub content: String,
+    #[serde(rename = "type")]
+    pub content_type: String,
+    pub filename: String,
+    pub disposition: String,
+}
+
+impl SGAttachment {
+    pub fn new(filename: String, content_type: String, data: &[u8]) -> SGAttachment {
+        SGAttachment {
+            content: base64::encode_block(data),
+            content_type,
+            filename,
+            disposition: "attachment".to_string(),
+        }
+    }
+}
+
 #[derive(Serialize)]
 pub struct SGPersonalization {
     pub to: Vec<SGEmail>,
@@ -220,6 +243,8 @@ pub struct SGMailMessage {
     pub personalizations: Vec<SGPersonalization>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub template_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<SGAttachment>,
 }
 
 impl SGMailMessage {
@@ -230,6 +255,7 @@ impl SGMailMessage {
             content: Vec::new(),
             personalizations: Vec::new(),
             template_id: None,
+            attachments: Vec::new(),
         }
     }
 
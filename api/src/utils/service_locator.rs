@@ -1,28 +1,86 @@
-use config::Config;
+use config::{Config, Environment};
 use errors::*;
-use payments::PaymentProcessor;
-use payments::StripePaymentProcessor;
+use payments::*;
+use std::collections::HashMap;
+
+/// Creates a payment processor for the given merchant account, or the provider's default account
+pub type PaymentProcessorFactory = Box<Fn(Option<String>) -> Box<PaymentProcessor> + Send + Sync>;
 
 pub struct ServiceLocator {
-    stripe_secret_key: String,
+    payment_processors: HashMap<String, PaymentProcessorFactory>,
 }
 
 impl ServiceLocator {
     pub fn new(config: &Config) -> ServiceLocator {
-        ServiceLocator {
-            stripe_secret_key: config.stripe_secret_key.to_string(),
+        let mut service_locator = ServiceLocator {
+            payment_processors: HashMap::new(),
+        };
+
+        let stripe_secret_key = config.stripe_secret_key.to_string();
+        service_locator.register_payment_processor(
+            "stripe",
+            Box::new(move |merchant_account_id| {
+                Box::new(StripePaymentProcessor::new(
+                    stripe_secret_key.to_string(),
+                    merchant_account_id,
+                ))
+            }),
+        );
+
+        if let (Some(public_key), Some(private_key)) = (
+            config.braintree_public_key.clone(),
+            config.braintree_private_key.clone(),
+        ) {
+            let production = config.braintree_environment == "production";
+            service_locator.register_payment_processor(
+                "braintree",
+                Box::new(move |merchant_account_id| {
+                    Box::new(BraintreePaymentProcessor::new(
+                        public_key.to_string(),
+                        private_key.to_string(),
+                        production,
+                        merchant_account_id,
+                    ))
+                }),
+            );
         }
+
+        if config.environment != Environment::Production {
+            service_locator.register_payment_processor(
+                "sandbox",
+                Box::new(|merchant_account_id| {
+                    Box::new(SandboxPaymentProcessor::new(merchant_account_id))
+                }),
+            );
+        }
+
+        service_locator
+    }
+
+    pub fn register_payment_processor(
+        &mut self,
+        provider_name: &str,
+        factory: PaymentProcessorFactory,
+    ) {
+        self.payment_processors
+            .insert(provider_name.to_string(), factory);
+    }
+
+    /// Names of the payment providers that are available in this environment
+    pub fn payment_providers(&self) -> Vec<String> {
+        let mut providers: Vec<String> = self.payment_processors.keys().cloned().collect();
+        providers.sort();
+        providers
     }
 
     pub fn create_payment_processor(
         &self,
         provider_name: &str,
-    ) -> Result<impl PaymentProcessor, BigNeonError> {
-        match provider_name {
-            "stripe" => Ok(StripePaymentProcessor::new(
-                self.stripe_secret_key.to_string(),
-            )),
-            _ => return Err(ApplicationError::new("Unknown payment provider".into()).into()),
+        merchant_account_id: Option<String>,
+    ) -> Result<Box<PaymentProcessor>, BigNeonError> {
+        match self.payment_processors.get(provider_name) {
+            Some(factory) => Ok(factory(merchant_account_id)),
+            None => return Err(ApplicationError::new("Unknown payment provider".into()).into()),
         }
     }
 }
@@ -0,0 +1,178 @@
+use bigneon_db::models::{TicketInstanceStatus, Wallet};
+use diesel::PgConnection;
+use errors::*;
+use printpdf::*;
+use qrcode::{Color as QrColor, QrCode};
+use std::fmt::Display;
+use std::io::{BufWriter, Cursor};
+use utils::wallet_passes::WalletPassDetails;
+use uuid::Uuid;
+
+pub const PDF_CONTENT_TYPE: &str = "application/pdf";
+
+// US Letter
+const PAGE_WIDTH: f64 = 215.9;
+const PAGE_HEIGHT: f64 = 279.4;
+const MARGIN: f64 = 20.0;
+const QR_CODE_SIZE: f64 = 70.0;
+const DATE_FORMAT: &str = "%A, %B %e, %Y %l:%M %p %Z";
+
+/// Renders one page per ticket with the event, venue, door and start times in the venue's time
+/// zone, the ticket type and seat, and a QR code for the door. The QR code holds the signed
+/// redeem payload so it is left out before the event's redeem date, the holder can download the
+/// tickets again once it has passed. It is also left out of tickets that are no longer in one of
+/// `holder_user_id`'s wallets, e.g. because they were transferred, resold or paid for by another
+/// payer of a split order.
+pub fn render(
+    title: &str,
+    ticket_instance_ids: &[Uuid],
+    holder_user_id: Uuid,
+    conn: &PgConnection,
+) -> Result<Vec<u8>, BigNeonError> {
+    let holder_wallet_ids: Vec<Uuid> = Wallet::find_for_user(holder_user_id, conn)?
+        .iter()
+        .map(|w| w.id)
+        .collect();
+
+    let (document, first_page, first_layer) =
+        PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Ticket");
+    let font = document
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(pdf_error)?;
+    let bold_font = document
+        .add_builtin_font(BuiltinFont::HelveticaBold)
+        .map_err(pdf_error)?;
+
+    for (index, ticket_instance_id) in ticket_instance_ids.iter().enumerate() {
+        let mut details = WalletPassDetails::load(*ticket_instance_id, conn)?;
+        if !holder_wallet_ids.contains(&details.wallet_id) {
+            details = details.for_previous_holder();
+        }
+        let layer = if index == 0 {
+            document.get_page(first_page).get_layer(first_layer)
+        } else {
+            let (page, layer) = document.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Ticket");
+            document.get_page(page).get_layer(layer)
+        };
+
+        let mut lines: Vec<(String, &IndirectFontRef, i64)> =
+            vec![(details.event.name.clone(), &bold_font, 22)];
+        if let Some(ref venue) = details.event.venue {
+            lines.push((venue.name.clone(), &bold_font, 14));
+            lines.push((venue.address.clone(), &font, 12));
+            lines.push((
+                format!(
+                    "{}, {} {}, {}",
+                    venue.city, venue.state, venue.postal_code, venue.country
+                ),
+                &font,
+                12,
+            ));
+        }
+        if let Some(door_time) = details.localized_times.door_time {
+            lines.push((
+                format!("Doors: {}", door_time.format(DATE_FORMAT)),
+                &font,
+                12,
+            ));
+        }
+        if let Some(event_start) = details.localized_times.event_start {
+            lines.push((
+                format!("Starts: {}", event_start.format(DATE_FORMAT)),
+                &font,
+                12,
+            ));
+        }
+        lines.push((details.ticket.ticket_type_name.clone(), &bold_font, 14));
+        if let Some(seat) = details.seat_description() {
+            lines.push((seat, &font, 12));
+        }
+        if let Some(holder_name) = details.holder_name() {
+            lines.push((format!("Ticket holder: {}", holder_name), &font, 12));
+        }
+        lines.push((format!("Ticket ID: {}", details.ticket.id), &font, 10));
+        if details.barcode.is_none() {
+            let message = match details.localized_times.redeem_date {
+                _ if details.ticket.status == TicketInstanceStatus::Redeemed => {
+                    "This ticket has been redeemed".to_string()
+                }
+                _ if details.voided => "This ticket is no longer valid".to_string(),
+                Some(redeem_date) => format!(
+                    "The QR code for this ticket will be available from {}",
+                    redeem_date.format(DATE_FORMAT)
+                ),
+                None => {
+                    "The QR code for this ticket will be available closer to the event".to_string()
+                }
+            };
+            lines.push((message, &font, 12));
+        }
+
+        let mut y = PAGE_HEIGHT - MARGIN - 10.0;
+        for (text, font, size) in lines {
+            layer.use_text(text, size, Mm(MARGIN), Mm(y), font);
+            y -= size as f64 * 0.6;
+        }
+
+        if let Some(ref barcode) = details.barcode {
+            draw_qr_code(
+                &layer,
+                barcode,
+                PAGE_WIDTH - MARGIN - QR_CODE_SIZE,
+                PAGE_HEIGHT - MARGIN - QR_CODE_SIZE,
+            )?;
+        }
+    }
+
+    let mut writer = BufWriter::new(Cursor::new(Vec::new()));
+    document.save(&mut writer).map_err(pdf_error)?;
+    let cursor = writer.into_inner().map_err(pdf_error)?;
+
+    Ok(cursor.into_inner())
+}
+
+/// Draws the QR code as filled rectangles, merging runs of dark modules in each row
+fn draw_qr_code(layer: &PdfLayerReference, data: &str, x: f64, y: f64) -> Result<(), BigNeonError> {
+    let code = QrCode::new(data.as_bytes()).map_err(pdf_error)?;
+    let width = code.width();
+    let colors = code.to_colors();
+    let module_size = QR_CODE_SIZE / width as f64;
+
+    layer.set_fill_color(Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None)));
+    for row in 0..width {
+        let mut column = 0;
+        while column < width {
+            if colors[row * width + column] != QrColor::Dark {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < width && colors[row * width + column] == QrColor::Dark {
+                column += 1;
+            }
+
+            let left = x + start as f64 * module_size;
+            let right = x + column as f64 * module_size;
+            let top = y + QR_CODE_SIZE - row as f64 * module_size;
+            let bottom = top - module_size;
+            layer.add_shape(Line {
+                points: vec![
+                    (Point::new(Mm(left), Mm(bottom)), false),
+                    (Point::new(Mm(right), Mm(bottom)), false),
+                    (Point::new(Mm(right), Mm(top)), false),
+                    (Point::new(Mm(left), Mm(top)), false),
+                ],
+                is_closed: true,
+                has_fill: true,
+                has_stroke: false,
+                is_clipping_path: false,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn pdf_error<E: Display>(error: E) -> ApplicationError {
+    ApplicationError::new(format!("Could not render ticket PDF: {}", error))
+}
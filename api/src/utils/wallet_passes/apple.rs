@@ -0,0 +1,302 @@
+use config::Config;
+use errors::*;
+use openssl::base64;
+use openssl::hash::MessageDigest;
+use openssl::memcmp;
+use openssl::pkcs12::{ParsedPkcs12, Pkcs12};
+use openssl::pkcs7::{Pkcs7, Pkcs7Flags};
+use openssl::pkey::PKey;
+use openssl::sha;
+use openssl::sign::Signer;
+use openssl::stack::Stack;
+use openssl::x509::X509;
+use reqwest;
+use serde_json::{self, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Cursor, Write};
+use utils::wallet_passes::{hex_string, wallet_pass_error, WalletPassDetails};
+use uuid::Uuid;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+pub const PKPASS_CONTENT_TYPE: &str = "application/vnd.apple.pkpass";
+pub const AUTHORIZATION_SCHEME: &str = "ApplePass";
+const APNS_URL: &str = "https://api.push.apple.com/3/device";
+const AUTHENTICATION_TOKEN_SEPARATOR: &str = ".";
+
+pub fn is_enabled(config: &Config) -> bool {
+    config.apple_pass_type_identifier.is_some()
+        && config.apple_team_identifier.is_some()
+        && config.apple_pass_certificate.is_some()
+        && config.apple_wwdr_certificate.is_some()
+}
+
+/// Builds the signed `.pkpass` bundle for the ticket. The bundle holds the pass definition, the
+/// images in the configured assets directory, a manifest of their SHA1 hashes and a detached
+/// signature of the manifest.
+pub fn pkpass(config: &Config, details: &WalletPassDetails) -> Result<Vec<u8>, BigNeonError> {
+    let mut files: Vec<(String, Vec<u8>)> = vec![(
+        "pass.json".to_string(),
+        serde_json::to_vec(&pass_json(config, details)?)?,
+    )];
+
+    if let Some(ref assets_path) = config.apple_pass_assets_path {
+        let entries = fs::read_dir(assets_path)
+            .map_err(|e| wallet_pass_error("Could not read wallet pass assets", e))?;
+        for entry in entries {
+            let path = entry
+                .map_err(|e| wallet_pass_error("Could not read wallet pass assets", e))?
+                .path();
+            if !path.is_file() {
+                continue;
+            }
+            if let Some(file_name) = path.file_name() {
+                let data = fs::read(&path)
+                    .map_err(|e| wallet_pass_error("Could not read wallet pass asset", e))?;
+                files.push((file_name.to_string_lossy().to_string(), data));
+            }
+        }
+    }
+
+    let manifest: BTreeMap<String, String> = files
+        .iter()
+        .map(|(name, data)| (name.clone(), hex_string(&sha::sha1(data))))
+        .collect();
+    let manifest = serde_json::to_vec(&manifest)?;
+    let signature = sign_manifest(config, &manifest)?;
+    files.push(("manifest.json".to_string(), manifest));
+    files.push(("signature".to_string(), signature));
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, data) in files {
+        zip.start_file(name, FileOptions::default())
+            .map_err(|e| wallet_pass_error("Could not create pass bundle", e))?;
+        zip.write_all(&data)
+            .map_err(|e| wallet_pass_error("Could not create pass bundle", e))?;
+    }
+    let bundle = zip
+        .finish()
+        .map_err(|e| wallet_pass_error("Could not create pass bundle", e))?;
+
+    Ok(bundle.into_inner())
+}
+
+fn pass_json(config: &Config, details: &WalletPassDetails) -> Result<Value, BigNeonError> {
+    let event = &details.event;
+    let times = &details.localized_times;
+
+    let mut secondary_fields = Vec::new();
+    if let Some(ref venue) = event.venue {
+        secondary_fields.push(json!({"key": "venue", "label": "VENUE", "value": venue.name}));
+    }
+    if let Some(event_start) = times.event_start {
+        secondary_fields.push(date_field(
+            "event_start",
+            "STARTS",
+            event_start.to_rfc3339(),
+        ));
+    }
+
+    let mut auxiliary_fields = vec![
+        json!({"key": "ticket_type", "label": "TICKET", "value": details.ticket.ticket_type_name}),
+    ];
+    if let Some(door_time) = times.door_time {
+        auxiliary_fields.push(date_field("door_time", "DOORS", door_time.to_rfc3339()));
+    }
+    if let Some(seat) = details.seat_description() {
+        auxiliary_fields.push(json!({"key": "seat", "label": "SEAT", "value": seat}));
+    }
+
+    let mut back_fields = Vec::new();
+    if let Some(holder_name) = details.holder_name() {
+        back_fields.push(json!({"key": "holder", "label": "TICKET HOLDER", "value": holder_name}));
+    }
+    if let Some(ref venue) = event.venue {
+        back_fields.push(json!({
+            "key": "address",
+            "label": "ADDRESS",
+            "value": format!("{}, {}, {} {}, {}", venue.address, venue.city, venue.state, venue.postal_code, venue.country)
+        }));
+    }
+    back_fields.push(json!({"key": "ticket_id", "label": "TICKET ID", "value": details.ticket.id}));
+
+    let mut pass = json!({
+        "formatVersion": 1,
+        "passTypeIdentifier": config.apple_pass_type_identifier,
+        "teamIdentifier": config.apple_team_identifier,
+        "serialNumber": details.ticket.id,
+        "organizationName": config.app_name,
+        "description": format!("Ticket for {}", event.name),
+        "voided": details.voided,
+        "eventTicket": {
+            "primaryFields": [{"key": "event", "label": "EVENT", "value": event.name}],
+            "secondaryFields": secondary_fields,
+            "auxiliaryFields": auxiliary_fields,
+            "backFields": back_fields
+        }
+    });
+
+    if let Some(event_start) = times.event_start {
+        pass["relevantDate"] = json!(event_start.to_rfc3339());
+    }
+    if let Some(ref barcode) = details.barcode {
+        pass["barcodes"] = json!([{
+            "format": "PKBarcodeFormatQR",
+            "message": barcode,
+            "messageEncoding": "iso-8859-1"
+        }]);
+    }
+    if let Some(ref web_service_url) = config.apple_pass_web_service_url {
+        pass["webServiceURL"] = json!(web_service_url);
+        pass["authenticationToken"] = json!(authentication_token(
+            config,
+            details.ticket.id,
+            details.wallet_id
+        )?);
+    }
+
+    Ok(pass)
+}
+
+/// Dates are shown in the venue's time zone rather than the device's
+fn date_field(key: &str, label: &str, value: String) -> Value {
+    json!({
+        "key": key,
+        "label": label,
+        "value": value,
+        "dateStyle": "PKDateStyleMedium",
+        "timeStyle": "PKDateStyleShort",
+        "ignoresTimeZone": true
+    })
+}
+
+fn certificate(config: &Config) -> Result<(Vec<u8>, ParsedPkcs12), BigNeonError> {
+    let encoded = match config.apple_pass_certificate {
+        Some(ref certificate) => certificate,
+        None => {
+            return Err(
+                ApplicationError::new("Apple Wallet passes are not enabled".to_string()).into(),
+            );
+        }
+    };
+    let der = base64::decode_block(encoded)
+        .map_err(|e| wallet_pass_error("Invalid pass type certificate", e))?;
+    let parsed = Pkcs12::from_der(&der)
+        .and_then(|pkcs12| pkcs12.parse(&config.apple_pass_certificate_password))
+        .map_err(|e| wallet_pass_error("Invalid pass type certificate", e))?;
+
+    Ok((der, parsed))
+}
+
+fn sign_manifest(config: &Config, manifest: &[u8]) -> Result<Vec<u8>, BigNeonError> {
+    let (_, certificate) = certificate(config)?;
+    let wwdr_certificate = X509::from_pem(
+        config
+            .apple_wwdr_certificate
+            .as_ref()
+            .map(|c| c.as_bytes())
+            .unwrap_or_default(),
+    )
+    .map_err(|e| wallet_pass_error("Invalid WWDR certificate", e))?;
+
+    let mut chain = Stack::new().map_err(|e| wallet_pass_error("Could not sign pass", e))?;
+    chain
+        .push(wwdr_certificate)
+        .map_err(|e| wallet_pass_error("Could not sign pass", e))?;
+    let signature = Pkcs7::sign(
+        &certificate.cert,
+        &certificate.pkey,
+        &chain,
+        manifest,
+        Pkcs7Flags::BINARY | Pkcs7Flags::DETACHED,
+    )
+    .and_then(|signature| signature.to_der())
+    .map_err(|e| wallet_pass_error("Could not sign pass", e))?;
+
+    Ok(signature)
+}
+
+/// Token the device presents to the pass web service. It records the wallet that held the ticket
+/// when the pass was issued so that previous holders can be sent a voided pass after a transfer.
+pub fn authentication_token(
+    config: &Config,
+    ticket_instance_id: Uuid,
+    wallet_id: Uuid,
+) -> Result<String, BigNeonError> {
+    Ok(vec![
+        wallet_id.simple().to_string(),
+        token_signature(config, ticket_instance_id, wallet_id)?,
+    ]
+    .join(AUTHENTICATION_TOKEN_SEPARATOR))
+}
+
+/// Returns the wallet the token was issued to if the token is valid for the ticket
+pub fn verify_authentication_token(
+    config: &Config,
+    ticket_instance_id: Uuid,
+    token: &str,
+) -> Option<Uuid> {
+    let parts: Vec<&str> = token.split(AUTHENTICATION_TOKEN_SEPARATOR).collect();
+    if parts.len() != 2 {
+        return None;
+    }
+    let wallet_id = Uuid::parse_str(parts[0]).ok()?;
+    let expected = token_signature(config, ticket_instance_id, wallet_id).ok()?;
+
+    if expected.len() == parts[1].len() && memcmp::eq(expected.as_bytes(), parts[1].as_bytes()) {
+        Some(wallet_id)
+    } else {
+        None
+    }
+}
+
+fn token_signature(
+    config: &Config,
+    ticket_instance_id: Uuid,
+    wallet_id: Uuid,
+) -> Result<String, BigNeonError> {
+    let key = PKey::hmac(config.token_secret.as_bytes())
+        .map_err(|e| wallet_pass_error("Could not create authentication token", e))?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)
+        .map_err(|e| wallet_pass_error("Could not create authentication token", e))?;
+    signer
+        .update(format!("{}:{}", ticket_instance_id, wallet_id).as_bytes())
+        .map_err(|e| wallet_pass_error("Could not create authentication token", e))?;
+    let signature = signer
+        .sign_to_vec()
+        .map_err(|e| wallet_pass_error("Could not create authentication token", e))?;
+
+    Ok(hex_string(&signature))
+}
+
+/// Tells the device to fetch the latest version of its passes. The push carries no content, the
+/// device calls back into the pass web service to find out what changed. Returns false when APNs
+/// reports that the push token is no longer active, e.g. because the pass was removed.
+pub fn push_update(config: &Config, push_token: &str) -> Result<bool, BigNeonError> {
+    let (der, _) = certificate(config)?;
+    let identity =
+        reqwest::Identity::from_pkcs12_der(&der, &config.apple_pass_certificate_password)?;
+    let client = reqwest::Client::builder()
+        .identity(identity)
+        .h2_prior_knowledge()
+        .build()?;
+
+    let response = client
+        .post(&format!("{}/{}", APNS_URL, push_token))
+        .header(
+            "apns-topic",
+            config
+                .apple_pass_type_identifier
+                .clone()
+                .unwrap_or_default(),
+        )
+        .body("{}")
+        .send()?;
+    if response.status() == reqwest::StatusCode::GONE {
+        return Ok(false);
+    }
+    response.error_for_status()?;
+
+    Ok(true)
+}
@@ -0,0 +1,289 @@
+use bigneon_db::models::*;
+use chrono::prelude::*;
+use config::Config;
+use errors::*;
+use jwt::{encode, Algorithm, Header};
+use openssl::pkey::PKey;
+use reqwest::{self, StatusCode};
+use serde_json::Value;
+use std::collections::HashMap;
+use utils::wallet_passes::{wallet_pass_error, WalletPassDetails};
+use uuid::Uuid;
+
+const GOOGLE_WALLET_API_URL: &str = "https://walletobjects.googleapis.com/walletobjects/v1";
+const GOOGLE_WALLET_SAVE_URL: &str = "https://pay.google.com/gp/v/save";
+const GOOGLE_WALLET_SCOPE: &str = "https://www.googleapis.com/auth/wallet_object.issuer";
+const GOOGLE_OAUTH_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const LANGUAGE: &str = "en-US";
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct GoogleWalletPass {
+    pub event_ticket_class: Value,
+    pub event_ticket_object: Value,
+    pub save_url: String,
+}
+
+#[derive(Serialize)]
+struct SaveToWalletClaims<'a> {
+    iss: &'a str,
+    aud: &'a str,
+    typ: &'a str,
+    iat: i64,
+    origins: Vec<String>,
+    payload: Value,
+}
+
+#[derive(Serialize)]
+struct AccessTokenClaims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: i64,
+    exp: i64,
+}
+
+#[derive(Deserialize)]
+struct AccessTokenResponse {
+    access_token: String,
+}
+
+pub fn is_enabled(config: &Config) -> bool {
+    config.google_wallet_issuer_id.is_some()
+        && config.google_wallet_service_account_email.is_some()
+        && config.google_wallet_private_key.is_some()
+}
+
+/// The event ticket class and object for the ticket along with the link that saves them to the
+/// holder's Google account
+pub fn pass(
+    config: &Config,
+    details: &WalletPassDetails,
+) -> Result<GoogleWalletPass, BigNeonError> {
+    let event_ticket_class = event_ticket_class(config, &details.event, &details.localized_times);
+    let event_ticket_object = event_ticket_object(config, details);
+
+    let claims = SaveToWalletClaims {
+        iss: service_account_email(config),
+        aud: "google",
+        typ: "savetowallet",
+        iat: Utc::now().timestamp(),
+        origins: vec![config.front_end_url.clone()],
+        payload: json!({
+            "eventTicketClasses": [event_ticket_class],
+            "eventTicketObjects": [event_ticket_object]
+        }),
+    };
+    let save_url = format!("{}/{}", GOOGLE_WALLET_SAVE_URL, sign(config, &claims)?);
+
+    Ok(GoogleWalletPass {
+        event_ticket_class,
+        event_ticket_object,
+        save_url,
+    })
+}
+
+/// Every ticket for an event shares the event's class so rescheduling only needs one update
+pub fn event_ticket_class(
+    config: &Config,
+    event: &DisplayEvent,
+    localized_times: &EventLocalizedTimes,
+) -> Value {
+    let mut class = json!({
+        "id": class_id(config, event.id),
+        "issuerName": config.app_name,
+        "reviewStatus": "underReview",
+        "eventName": localized_string(&event.name),
+        "dateTime": {
+            "doorsOpen": localized_times.door_time.map(|t| t.to_rfc3339()),
+            "start": localized_times.event_start.map(|t| t.to_rfc3339()),
+            "end": localized_times.event_end.map(|t| t.to_rfc3339())
+        }
+    });
+
+    if let Some(ref venue) = event.venue {
+        class["venue"] = json!({
+            "name": localized_string(&venue.name),
+            "address": localized_string(&format!(
+                "{}, {}, {} {}, {}",
+                venue.address, venue.city, venue.state, venue.postal_code, venue.country
+            ))
+        });
+    }
+
+    class
+}
+
+/// Each holder of the ticket gets their own object so a previous holder's pass can be
+/// deactivated without affecting the new one
+pub fn event_ticket_object(config: &Config, details: &WalletPassDetails) -> Value {
+    let mut object = json!({
+        "id": object_id(config, details.ticket.id, details.wallet_id),
+        "classId": class_id(config, details.event.id),
+        "state": if details.voided { "inactive" } else { "active" },
+        "ticketNumber": details.ticket.id,
+        "ticketType": localized_string(&details.ticket.ticket_type_name)
+    });
+
+    if let Some(holder_name) = details.holder_name() {
+        object["ticketHolderName"] = json!(holder_name);
+    }
+    if let Some(ref seat) = details.ticket.seat {
+        object["seatInfo"] = json!({
+            "section": localized_string(&seat.section_name),
+            "row": localized_string(&seat.row_name),
+            "seat": localized_string(&seat.seat_number)
+        });
+    }
+    if let Some(ref barcode) = details.barcode {
+        object["barcode"] = json!({"type": "qrCode", "value": barcode});
+    }
+
+    object
+}
+
+/// Pushes the event's latest details to every saved pass. Nothing is updated if no one has
+/// saved a pass for the event yet.
+pub fn update_event_ticket_class(
+    config: &Config,
+    event: &DisplayEvent,
+    localized_times: &EventLocalizedTimes,
+) -> Result<(), BigNeonError> {
+    let class = event_ticket_class(config, event, localized_times);
+    patch(
+        config,
+        &format!("eventTicketClass/{}", class_id(config, event.id)),
+        &class,
+    )
+}
+
+/// Pushes the ticket's latest details to the holder's saved pass, e.g. its barcode once the
+/// event's redeem date is reached
+pub fn update_event_ticket_object(
+    config: &Config,
+    details: &WalletPassDetails,
+) -> Result<(), BigNeonError> {
+    let object = event_ticket_object(config, details);
+    patch(
+        config,
+        &format!(
+            "eventTicketObject/{}",
+            object_id(config, details.ticket.id, details.wallet_id)
+        ),
+        &object,
+    )
+}
+
+/// Deactivates the pass saved by the wallet that previously held the ticket
+pub fn deactivate_event_ticket_object(
+    config: &Config,
+    ticket_instance_id: Uuid,
+    wallet_id: Uuid,
+) -> Result<(), BigNeonError> {
+    patch(
+        config,
+        &format!(
+            "eventTicketObject/{}",
+            object_id(config, ticket_instance_id, wallet_id)
+        ),
+        &json!({ "state": "inactive" }),
+    )
+}
+
+fn patch(config: &Config, path: &str, body: &Value) -> Result<(), BigNeonError> {
+    let client = reqwest::Client::new();
+    let response = client
+        .patch(&format!("{}/{}", GOOGLE_WALLET_API_URL, path))
+        .header(
+            "Authorization",
+            format!("Bearer {}", access_token(config, &client)?),
+        )
+        .json(body)
+        .send()?;
+
+    // Classes and objects only exist once a holder has saved the pass
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(());
+    }
+    response.error_for_status()?;
+
+    Ok(())
+}
+
+fn access_token(config: &Config, client: &reqwest::Client) -> Result<String, BigNeonError> {
+    let now = Utc::now().timestamp();
+    let claims = AccessTokenClaims {
+        iss: service_account_email(config),
+        scope: GOOGLE_WALLET_SCOPE,
+        aud: GOOGLE_OAUTH_TOKEN_URL,
+        iat: now,
+        exp: now + 3600,
+    };
+    let mut params = HashMap::new();
+    params.insert(
+        "grant_type",
+        "urn:ietf:params:oauth:grant-type:jwt-bearer".to_string(),
+    );
+    params.insert("assertion", sign(config, &claims)?);
+
+    let response: AccessTokenResponse = client
+        .post(GOOGLE_OAUTH_TOKEN_URL)
+        .form(&params)
+        .send()?
+        .error_for_status()?
+        .json()?;
+
+    Ok(response.access_token)
+}
+
+/// Signs the claims with the service account's key. Google issues the keys as PKCS#8 PEM while
+/// the JWT library expects a DER encoded RSA key.
+fn sign<T: ::serde::Serialize>(config: &Config, claims: &T) -> Result<String, BigNeonError> {
+    let private_key = match config.google_wallet_private_key {
+        Some(ref private_key) => private_key,
+        None => {
+            return Err(
+                ApplicationError::new("Google Wallet passes are not enabled".to_string()).into(),
+            );
+        }
+    };
+    let der = PKey::private_key_from_pem(private_key.replace("\\n", "\n").as_bytes())
+        .and_then(|key| key.rsa())
+        .and_then(|rsa| rsa.private_key_to_der())
+        .map_err(|e| wallet_pass_error("Invalid Google Wallet private key", e))?;
+
+    Ok(encode(&Header::new(Algorithm::RS256), claims, &der)?)
+}
+
+fn service_account_email(config: &Config) -> &str {
+    config
+        .google_wallet_service_account_email
+        .as_ref()
+        .map(|email| email.as_str())
+        .unwrap_or_default()
+}
+
+fn class_id(config: &Config, event_id: Uuid) -> String {
+    format!(
+        "{}.{}",
+        config.google_wallet_issuer_id.clone().unwrap_or_default(),
+        event_id.simple()
+    )
+}
+
+fn object_id(config: &Config, ticket_instance_id: Uuid, wallet_id: Uuid) -> String {
+    format!(
+        "{}.{}-{}",
+        config.google_wallet_issuer_id.clone().unwrap_or_default(),
+        ticket_instance_id.simple(),
+        wallet_id.simple()
+    )
+}
+
+fn localized_string(value: &str) -> Value {
+    json!({
+        "defaultValue": {
+            "language": LANGUAGE,
+            "value": value
+        }
+    })
+}
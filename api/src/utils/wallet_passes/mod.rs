@@ -0,0 +1,95 @@
+use bigneon_db::models::*;
+use diesel::PgConnection;
+use errors::*;
+use std::fmt::Display;
+use uuid::Uuid;
+
+pub mod apple;
+pub mod google;
+
+/// Everything shown on a ticket's wallet pass. Times are localized to the venue so they display
+/// the same wherever the holder's device happens to be.
+pub struct WalletPassDetails {
+    pub event: DisplayEvent,
+    pub user: Option<DisplayUser>,
+    pub ticket: DisplayTicket,
+    pub localized_times: EventLocalizedTimes,
+    pub barcode: Option<String>,
+    pub wallet_id: Uuid,
+    pub voided: bool,
+}
+
+impl WalletPassDetails {
+    pub fn load(
+        ticket_instance_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<WalletPassDetails, BigNeonError> {
+        let (event, user, ticket) = TicketInstance::find_for_display(ticket_instance_id, conn)?;
+        let db_event = Event::find(event.id, conn)?;
+        let localized_times = db_event.get_all_localized_times(&db_event.venue(conn)?);
+        let ticket_instance = TicketInstance::find(ticket_instance_id, conn)?;
+        // The pass belongs to the ticket's holder, tickets held by an organization have no barcode
+        let barcode = match user {
+            Some(ref user) => EventSigningKey::signed_payload_for_ticket(
+                &ticket_instance,
+                &db_event,
+                user.id,
+                conn,
+            )?,
+            None => None,
+        };
+        let voided = ticket_instance.status != TicketInstanceStatus::Purchased
+            && ticket_instance.status != TicketInstanceStatus::Redeemed;
+
+        Ok(WalletPassDetails {
+            event,
+            user,
+            ticket,
+            localized_times,
+            barcode,
+            wallet_id: ticket_instance.wallet_id,
+            voided,
+        })
+    }
+
+    /// The pass as seen by someone who transferred the ticket away, without the barcode or the
+    /// new holder's details
+    pub fn for_previous_holder(mut self) -> WalletPassDetails {
+        self.user = None;
+        self.barcode = None;
+        self.voided = true;
+        self
+    }
+
+    pub fn holder_name(&self) -> Option<String> {
+        self.user.as_ref().and_then(|user| {
+            let name = vec![user.first_name.clone(), user.last_name.clone()]
+                .into_iter()
+                .filter_map(|n| n)
+                .collect::<Vec<String>>()
+                .join(" ");
+            if name.is_empty() {
+                None
+            } else {
+                Some(name)
+            }
+        })
+    }
+
+    pub fn seat_description(&self) -> Option<String> {
+        self.ticket.seat.as_ref().map(|seat| {
+            format!(
+                "{}, Row {}, Seat {}",
+                seat.section_name, seat.row_name, seat.seat_number
+            )
+        })
+    }
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn wallet_pass_error<E: Display>(message: &str, error: E) -> ApplicationError {
+    ApplicationError::new(format!("{}: {}", message, error))
+}
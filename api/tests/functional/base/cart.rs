@@ -5,6 +5,7 @@ use bigneon_db::models::*;
 use chrono::prelude::*;
 use support;
 use support::database::TestDatabase;
+use support::test_request::TestRequest;
 
 pub fn update_box_office_pricing(role: Roles, should_test_succeed: bool) {
     let database = TestDatabase::new();
@@ -41,11 +42,17 @@ pub fn update_box_office_pricing(role: Roles, should_test_succeed: bool) {
             ticket_type_id,
             quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
     });
 
-    let response: HttpResponse =
-        cart::update_cart((database.connection.clone().into(), input, auth_user)).into();
+    let response: HttpResponse = cart::update_cart((
+        database.connection.clone().into(),
+        input,
+        auth_user,
+        TestRequest::create().extract_state(),
+    ))
+    .into();
 
     if should_test_succeed {
         assert_eq!(response.status(), StatusCode::OK);
@@ -121,6 +128,7 @@ pub fn replace_box_office_pricing(role: Roles, should_test_succeed: bool) {
             ticket_type_id: old_ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -144,11 +152,17 @@ pub fn replace_box_office_pricing(role: Roles, should_test_succeed: bool) {
             ticket_type_id,
             quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
     });
 
-    let response: HttpResponse =
-        cart::replace_cart((database.connection.clone().into(), input, auth_user)).into();
+    let response: HttpResponse = cart::replace_cart((
+        database.connection.clone().into(),
+        input,
+        auth_user,
+        TestRequest::create().extract_state(),
+    ))
+    .into();
 
     if should_test_succeed {
         assert_eq!(response.status(), StatusCode::OK);
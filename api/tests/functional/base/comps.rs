@@ -133,7 +133,13 @@ pub fn create(role: Roles, should_test_succeed: bool) {
     let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
     path.id = hold.id;
 
-    let response = comps::create((database.connection.clone().into(), json, path, auth_user));
+    let response = comps::create((
+        database.connection.clone().into(),
+        json,
+        path,
+        auth_user,
+        test_request.extract_state(),
+    ));
 
     if should_test_succeed {
         let response = response.unwrap();
@@ -143,6 +149,7 @@ pub fn create(role: Roles, should_test_succeed: bool) {
         assert_eq!(comp.parent_hold_id, Some(hold.id));
         assert_eq!(comp.email, email);
         assert_eq!(comp.quantity, 10);
+        assert!(comp.claim_sent_at.is_some());
     } else {
         assert_eq!(
             response.err().unwrap().to_string(),
@@ -160,6 +160,268 @@ pub fn cancel(role: Roles, should_test_succeed: bool) {
     }
 }
 
+pub fn cancellations(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_ticket_pricing()
+        .finish();
+    database
+        .create_order()
+        .for_event(&event)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let conn = database.connection.get();
+    let event = event.cancel(user.id, conn).unwrap();
+
+    let test_request = TestRequest::create_with_uri(&format!("/events/{}/cancellations", event.id));
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = event.id;
+    let query_parameters = Query::<PagingParameters>::extract(&test_request.request).unwrap();
+
+    let response: HttpResponse = events::cancellations((
+        database.connection.clone().into(),
+        path,
+        query_parameters,
+        auth_user,
+    ))
+    .into();
+    if should_test_succeed {
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let cancellations: Payload<DisplayEventCancellation> = serde_json::from_str(&body).unwrap();
+        assert_eq!(cancellations.data.len(), 1);
+        assert_eq!(
+            cancellations.data[0].progress,
+            EventCancellationProgress {
+                order_count: 1,
+                pending_count: 1,
+                refunded_count: 0,
+                failed_count: 0,
+                unrefunded_ticket_count: 2,
+            }
+        );
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
+
+pub fn clone(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+    let event_start = Utc::now().naive_utc() + Duration::days(7);
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_event_start(event_start)
+        .with_ticket_pricing()
+        .finish();
+    let conn = database.connection.get();
+
+    let test_request = TestRequest::create_with_uri(&format!("/events/{}/clone", event.id));
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = event.id;
+    let new_event_start = event_start + Duration::days(14);
+    let json = Json(CloneEventRequest {
+        event_start: Some(new_event_start),
+    });
+
+    let response: HttpResponse = events::clone((
+        database.connection.clone().into(),
+        path,
+        json,
+        auth_user,
+        test_request.extract_state(),
+    ))
+    .into();
+    if should_test_succeed {
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let new_event: Event = serde_json::from_str(&body).unwrap();
+        assert_ne!(new_event.id, event.id);
+        assert_eq!(new_event.status, EventStatus::Draft);
+        assert_eq!(new_event.event_start, Some(new_event_start));
+
+        let ticket_types = new_event.ticket_types(conn).unwrap();
+        assert_eq!(ticket_types.len(), 1);
+        let asset = Asset::find_by_ticket_type(&ticket_types[0].id, conn).unwrap();
+        assert!(asset.blockchain_asset_id.is_some());
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
+
+pub fn offline_scanning(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_ticket_pricing()
+        .finish();
+
+    let test_request =
+        TestRequest::create_with_uri(&format!("/events/{}/offline_scanning", event.id));
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = event.id;
+
+    let response: HttpResponse =
+        events::offline_scanning((database.connection.clone().into(), path, auth_user)).into();
+    if should_test_succeed {
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let data: OfflineScanningData = serde_json::from_str(&body).unwrap();
+        let signing_key =
+            EventSigningKey::find_or_create_for_event(event.id, database.connection.get()).unwrap();
+        assert_eq!(data.event_id, event.id);
+        assert_eq!(data.public_key, signing_key.public_key);
+        assert!(data.revoked_tickets.is_empty());
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
+
+pub fn upload_offline_scans(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_event_start(Utc::now().naive_utc() + Duration::days(1))
+        .with_ticket_pricing()
+        .finish();
+    database
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .is_paid()
+        .finish();
+    let conn = database.connection.get();
+    let ticket = TicketInstance::find_for_user(user.id, conn)
+        .unwrap()
+        .remove(0);
+    let signing_key = EventSigningKey::find_or_create_for_event(event.id, conn).unwrap();
+    let payload = signing_key
+        .sign_ticket(&ticket, EventSigningKey::payload_expiry(&event))
+        .unwrap();
+
+    let test_request = TestRequest::create_with_uri(&format!("/events/{}/offline_scans", event.id));
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = event.id;
+    let scanned_at = Utc::now().naive_utc();
+    let json = Json(OfflineScansRequest {
+        scans: vec![
+            OfflineScan {
+                payload: payload.clone(),
+                scanned_at,
+            },
+            OfflineScan {
+                payload: payload.clone(),
+                scanned_at: scanned_at + Duration::minutes(1),
+            },
+        ],
+        device_id: Some("Scanner 1".to_string()),
+        gate_name: Some("North Gate".to_string()),
+    });
+
+    let response: HttpResponse = events::upload_offline_scans((
+        database.connection.clone().into(),
+        path,
+        json,
+        auth_user,
+        test_request.extract_state(),
+    ))
+    .into();
+    if should_test_succeed {
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let results: Vec<OfflineScanResult> = serde_json::from_str(&body).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].result, OfflineScanResults::Redeemed);
+        assert_eq!(results[1].result, OfflineScanResults::Duplicate);
+        let ticket = TicketInstance::find(ticket.id, conn).unwrap();
+        assert_eq!(ticket.status, TicketInstanceStatus::Redeemed);
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
+
+pub fn scans(role: Roles, should_test_succeed: bool) {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let organization = database.create_organization().finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, role, Some(&organization), &database);
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_ticket_pricing()
+        .finish();
+    let user2 = database.create_user().finish();
+    database
+        .create_order()
+        .for_event(&event)
+        .for_user(&user2)
+        .is_paid()
+        .finish();
+    let conn = database.connection.get();
+    let ticket = TicketInstance::find_for_user(user2.id, conn)
+        .unwrap()
+        .remove(0);
+    TicketScan::scan(
+        event.id,
+        ticket.id,
+        ticket.redeem_key.unwrap(),
+        TicketScanDirections::In,
+        Some(user.id),
+        Some("Scanner 1".to_string()),
+        Some("North Gate".to_string()),
+        conn,
+    )
+    .unwrap();
+
+    let test_request = TestRequest::create_with_uri(&format!("/events/{}/scans", event.id));
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = event.id;
+    let query_parameters = Query::<PagingParameters>::extract(&test_request.request).unwrap();
+
+    let response: HttpResponse = events::scans((
+        database.connection.clone().into(),
+        path,
+        query_parameters,
+        auth_user,
+    ))
+    .into();
+    if should_test_succeed {
+        let body = support::unwrap_body_to_string(&response).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let scans: Payload<TicketScan> = serde_json::from_str(&body).unwrap();
+        assert_eq!(scans.data.len(), 1);
+        assert_eq!(scans.data[0].ticket_instance_id, Some(ticket.id));
+        assert_eq!(scans.data[0].scanned_by_user_id, Some(user.id));
+        assert_eq!(scans.data[0].gate_name, Some("North Gate".to_string()));
+        assert_eq!(scans.data[0].result, TicketScanResults::Redeemed);
+    } else {
+        support::expects_unauthorized(&response);
+    }
+}
+
 pub fn add_artist(role: Roles, should_test_succeed: bool) {
     let database = TestDatabase::new();
     let user = database.create_user().finish();
@@ -399,6 +661,7 @@ pub fn dashboard(role: Roles, should_test_succeed: bool) {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -30,6 +30,7 @@ pub fn create(role: Roles, should_test_succeed: bool) {
         name: name.clone(),
         redemption_code: redemption_code.clone(),
         discount_in_cents,
+        discount_as_percentage: None,
         hold_type,
         end_at: None,
         max_per_order: None,
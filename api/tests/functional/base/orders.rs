@@ -1,6 +1,6 @@
 use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path};
 use bigneon_api::controllers::orders::{self, *};
-use bigneon_api::extractors::Json;
+use bigneon_api::extractors::{IdempotencyKey, Json};
 use bigneon_api::models::PathParameters;
 use bigneon_db::models::*;
 use serde_json;
@@ -35,6 +35,7 @@ pub fn details(role: Roles, should_succeed: bool) {
             ticket_type_id: ticket_type.id,
             quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -162,6 +163,7 @@ pub fn refund(role: Roles, should_succeed: bool) {
             ticket_type_id: ticket_type.id,
             quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -201,6 +203,7 @@ pub fn refund(role: Roles, should_succeed: bool) {
     ];
     let json = Json(RefundAttributes {
         items: refund_items,
+        to_credit: false,
     });
 
     let test_request = TestRequest::create();
@@ -212,6 +215,7 @@ pub fn refund(role: Roles, should_succeed: bool) {
         json,
         auth_user,
         test_request.extract_state(),
+        IdempotencyKey(None),
     ))
     .into();
 
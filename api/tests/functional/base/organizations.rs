@@ -529,3 +529,37 @@ pub fn add_fee_schedule(role: Roles, should_succeed: bool) {
     let result: FeeScheduleWithRanges = serde_json::from_str(&body).unwrap();
     assert_eq!(result.name, "Fees".to_string());
 }
+
+pub fn update_payment_provider(role: Roles, should_succeed: bool) {
+    let database = TestDatabase::new();
+    let organization = database.create_organization().finish();
+    let auth_user = support::create_auth_user(role, Some(&organization), &database);
+
+    let json = Json(PaymentProviderRequest {
+        payment_provider: "sandbox".to_string(),
+        merchant_account_id: Some("merchant-1".to_string()),
+    });
+    let test_request = TestRequest::create();
+    let state = test_request.extract_state();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+
+    let response: HttpResponse = organizations::update_payment_provider((
+        database.connection.into(),
+        path,
+        json,
+        auth_user,
+        state,
+    ))
+    .into();
+
+    if !should_succeed {
+        support::expects_unauthorized(&response);
+        return;
+    }
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let result: Organization = serde_json::from_str(&body).unwrap();
+    assert_eq!(result.payment_provider, "sandbox".to_string());
+    assert_eq!(result.merchant_account_id, Some("merchant-1".to_string()));
+}
@@ -0,0 +1,79 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path};
+use bigneon_api::controllers::seat_sections;
+use bigneon_api::extractors::*;
+use bigneon_api::models::PathParameters;
+use bigneon_db::models::{NewSeat, Roles, Seat, SeatSection};
+use serde_json;
+use support;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+
+pub fn create(role: Roles, should_succeed: bool) {
+    let database = TestDatabase::new();
+    let venue = database.create_venue().finish();
+    let name = "Balcony";
+
+    let user = support::create_auth_user(role, None, &database);
+    let json = Json(seat_sections::CreateSeatSection {
+        name: name.to_string(),
+        stage_id: None,
+    });
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = venue.id;
+    let response: HttpResponse =
+        seat_sections::create((database.connection.into(), path, json, user)).into();
+
+    if !should_succeed {
+        support::expects_unauthorized(&response);
+        return;
+    }
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let seat_section: SeatSection = serde_json::from_str(&body).unwrap();
+    assert_eq!(seat_section.name, name);
+    assert_eq!(seat_section.venue_id, venue.id);
+}
+
+pub fn add_seats(role: Roles, should_succeed: bool) {
+    let database = TestDatabase::new();
+    let venue = database.create_venue().finish();
+    let seat_section = database
+        .create_seat_section()
+        .with_venue_id(venue.id)
+        .finish();
+
+    let user = support::create_auth_user(role, None, &database);
+    let json = Json(seat_sections::AddSeatsRequest {
+        seats: vec![
+            NewSeat {
+                row_name: "A".to_string(),
+                seat_number: "1".to_string(),
+                ..Default::default()
+            },
+            NewSeat {
+                row_name: "A".to_string(),
+                seat_number: "2".to_string(),
+                is_accessible: true,
+                ..Default::default()
+            },
+        ],
+    });
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = seat_section.id;
+    let response: HttpResponse =
+        seat_sections::add_seats((database.connection.into(), path, json, user)).into();
+
+    if !should_succeed {
+        support::expects_unauthorized(&response);
+        return;
+    }
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let seats: Vec<Seat> = serde_json::from_str(&body).unwrap();
+    assert_eq!(seats.len(), 2);
+    assert!(seats.iter().all(|s| s.seat_section_id == seat_section.id));
+}
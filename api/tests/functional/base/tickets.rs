@@ -53,6 +53,7 @@ pub fn show_other_user_ticket(role: Roles, should_test_succeed: bool) {
             status: TicketInstanceStatus::Purchased,
             redeem_key: ticket_response.ticket.redeem_key.clone(),
             pending_transfer: false,
+            seat: None,
         };
 
         let expected_result = ShowTicketResponse {
@@ -96,6 +97,9 @@ pub fn redeem_ticket(role: Roles, should_test_succeed: bool) {
     //First try when Redeem code is wrong
     let request_data = TicketRedeemRequest {
         redeem_key: "WrongKey".to_string(),
+        direction: None,
+        device_id: None,
+        gate_name: None,
     };
 
     let response: HttpResponse = events::redeem_ticket((
@@ -112,6 +116,9 @@ pub fn redeem_ticket(role: Roles, should_test_succeed: bool) {
         //Now try with redeem code being correct
         let request_data = TicketRedeemRequest {
             redeem_key: ticket.redeem_key.unwrap(),
+            direction: None,
+            device_id: None,
+            gate_name: None,
         };
 
         println!("{:?}", request_data);
@@ -153,6 +160,7 @@ pub fn show_redeemable_ticket(role: Roles, should_test_succeed: bool) {
             ticket_type_id: ticket_type.id,
             quantity: 1,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
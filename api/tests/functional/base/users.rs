@@ -38,6 +38,7 @@ pub fn profile(role: Roles, should_test_true: bool) {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -107,6 +108,7 @@ pub fn history(role: Roles, should_test_true: bool) {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
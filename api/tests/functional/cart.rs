@@ -3,7 +3,7 @@ use bigneon_api::controllers::cart;
 use bigneon_api::controllers::cart::*;
 use bigneon_api::extractors::*;
 use bigneon_db::models::*;
-use bigneon_db::schema::orders;
+use bigneon_db::schema::{order_idempotency_keys, orders};
 use chrono::prelude::*;
 use chrono::Duration;
 use diesel;
@@ -122,6 +122,7 @@ fn destroy() {
             ticket_type_id: ticket_type_id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -179,12 +180,18 @@ fn update() {
             ticket_type_id,
             quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
     });
 
     let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
-    let response =
-        cart::update_cart((database.connection.clone().into(), input, auth_user)).unwrap();
+    let response = cart::update_cart((
+        database.connection.clone().into(),
+        input,
+        auth_user,
+        TestRequest::create().extract_state(),
+    ))
+    .unwrap();
     assert_eq!(response.status(), StatusCode::OK);
 
     let cart = Order::find_cart_for_user(user.id, &connection)
@@ -232,13 +239,19 @@ fn update_with_draft_event() {
             ticket_type_id,
             quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
         box_office_pricing: None,
     });
 
     let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
-    let response =
-        cart::update_cart((database.connection.clone().into(), input, auth_user)).unwrap();
+    let response = cart::update_cart((
+        database.connection.clone().into(),
+        input,
+        auth_user,
+        TestRequest::create().extract_state(),
+    ))
+    .unwrap();
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 }
 
@@ -264,17 +277,24 @@ fn update_multiple() {
                 ticket_type_id,
                 quantity: 2,
                 redemption_code: None,
+                seat_ids: None,
             },
             cart::CartItem {
                 ticket_type_id: ticket_type_id2,
                 quantity: 3,
                 redemption_code: None,
+                seat_ids: None,
             },
         ],
     });
     let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
-    let response =
-        cart::update_cart((database.connection.clone().into(), input, auth_user)).unwrap();
+    let response = cart::update_cart((
+        database.connection.clone().into(),
+        input,
+        auth_user,
+        TestRequest::create().extract_state(),
+    ))
+    .unwrap();
     assert_eq!(response.status(), StatusCode::OK);
     let cart = Order::find_cart_for_user(user.id, connection)
         .unwrap()
@@ -347,11 +367,18 @@ fn add_with_increment() {
             ticket_type_id,
             quantity: 4,
             redemption_code: None,
+            seat_ids: None,
         }],
     });
 
     let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
-    let response = cart::update_cart((database.connection.clone(), input, auth_user)).unwrap();
+    let response = cart::update_cart((
+        database.connection.clone(),
+        input,
+        auth_user,
+        TestRequest::create().extract_state(),
+    ))
+    .unwrap();
     assert_eq!(response.status(), StatusCode::OK);
 
     let cart = Order::find_cart_for_user(user.id, connection)
@@ -402,12 +429,18 @@ fn update_with_increment_failure_invalid_quantity() {
             ticket_type_id,
             quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
     });
 
     let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
-    let response: HttpResponse =
-        cart::update_cart((database.connection.clone().into(), input, auth_user)).into();
+    let response: HttpResponse = cart::update_cart((
+        database.connection.clone().into(),
+        input,
+        auth_user,
+        TestRequest::create().extract_state(),
+    ))
+    .into();
     assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
 
     let validation_response = support::validation_response_from_response(&response).unwrap();
@@ -439,12 +472,18 @@ fn update_with_existing_cart() {
             ticket_type_id,
             quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
     });
 
     let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
-    let response =
-        cart::update_cart((database.connection.clone().into(), input, auth_user)).unwrap();
+    let response = cart::update_cart((
+        database.connection.clone().into(),
+        input,
+        auth_user,
+        TestRequest::create().extract_state(),
+    ))
+    .unwrap();
     assert_eq!(response.status(), StatusCode::OK);
     let items = cart.items(connection).unwrap();
     let order_item = items
@@ -486,6 +525,7 @@ fn reduce() {
             ticket_type_id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -519,12 +559,18 @@ fn reduce() {
             ticket_type_id,
             quantity: 6,
             redemption_code: None,
+            seat_ids: None,
         }],
     });
 
     let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
-    let response =
-        cart::update_cart((database.connection.clone().into(), input, auth_user)).unwrap();
+    let response = cart::update_cart((
+        database.connection.clone().into(),
+        input,
+        auth_user,
+        TestRequest::create().extract_state(),
+    ))
+    .unwrap();
     assert_eq!(response.status(), StatusCode::OK);
 
     // Contains additional item quantity so cart response still includes cart object
@@ -567,6 +613,7 @@ fn remove() {
             ticket_type_id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -601,12 +648,18 @@ fn remove() {
             ticket_type_id,
             quantity: 0,
             redemption_code: None,
+            seat_ids: None,
         }],
     });
 
     let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
-    let response =
-        cart::update_cart((database.connection.clone().into(), input, auth_user)).unwrap();
+    let response = cart::update_cart((
+        database.connection.clone().into(),
+        input,
+        auth_user,
+        TestRequest::create().extract_state(),
+    ))
+    .unwrap();
     assert_eq!(response.status(), StatusCode::OK);
 
     // Contains additional item quantity so cart response still includes cart object
@@ -642,6 +695,7 @@ fn remove_with_increment() {
             ticket_type_id,
             quantity: 12,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -676,12 +730,18 @@ fn remove_with_increment() {
             ticket_type_id,
             quantity: 8,
             redemption_code: None,
+            seat_ids: None,
         }],
     });
 
     let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
-    let response =
-        cart::update_cart((database.connection.clone().into(), input, auth_user)).unwrap();
+    let response = cart::update_cart((
+        database.connection.clone().into(),
+        input,
+        auth_user,
+        TestRequest::create().extract_state(),
+    ))
+    .unwrap();
     assert_eq!(response.status(), StatusCode::OK);
 
     // Contains additional item quantity so cart response still includes cart object
@@ -729,6 +789,7 @@ fn remove_with_increment_failure_invalid_quantity() {
             ticket_type_id,
             quantity: 12,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -763,12 +824,18 @@ fn remove_with_increment_failure_invalid_quantity() {
             ticket_type_id,
             quantity: 5,
             redemption_code: None,
+            seat_ids: None,
         }],
     });
 
     let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
-    let response: HttpResponse =
-        cart::update_cart((database.connection.clone().into(), input, auth_user)).into();
+    let response: HttpResponse = cart::update_cart((
+        database.connection.clone().into(),
+        input,
+        auth_user,
+        TestRequest::create().extract_state(),
+    ))
+    .into();
     assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
     let validation_response = support::validation_response_from_response(&response).unwrap();
     let quantity = validation_response.fields.get("quantity").unwrap();
@@ -779,6 +846,52 @@ fn remove_with_increment_failure_invalid_quantity() {
     );
 }
 
+#[test]
+fn split() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let event = database
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let user = database.create_user().finish();
+    let order = database
+        .create_cart()
+        .for_user(&user)
+        .for_event(&event)
+        .quantity(3)
+        .finish();
+    let request = TestRequest::create();
+
+    let input = Json(cart::SplitCartRequest {
+        ticket_quantities: vec![1, 2],
+    });
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let response: HttpResponse = cart::split((
+        database.connection.clone().into(),
+        input,
+        auth_user,
+        request.extract_state(),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let result: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let shares = result["shares"].as_array().unwrap();
+    assert_eq!(shares.len(), 2);
+    assert_eq!(shares[0]["ticket_quantity"], 1);
+    assert!(shares[0]["link"]
+        .as_str()
+        .unwrap()
+        .ends_with(shares[0]["share_key"].as_str().unwrap()));
+
+    let order = Order::find(order.id, connection).unwrap();
+    assert_eq!(order.status, OrderStatus::PartiallyPaid);
+    assert_eq!(order.shares(connection).unwrap().len(), 2);
+}
+
 #[test]
 fn checkout_external() {
     let database = TestDatabase::new();
@@ -817,6 +930,7 @@ fn checkout_external() {
         input,
         user,
         request.extract_state(),
+        IdempotencyKey(None),
     ))
     .unwrap();
     assert_eq!(response.status(), StatusCode::OK);
@@ -854,6 +968,7 @@ fn checkout_free() {
         input,
         user,
         request.extract_state(),
+        IdempotencyKey(None),
     ))
     .unwrap();
     assert_eq!(response.status(), StatusCode::OK);
@@ -863,6 +978,135 @@ fn checkout_free() {
     assert_eq!(order.status, OrderStatus::Paid);
 }
 
+#[test]
+fn checkout_with_idempotency_key() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let event = database
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+
+    let user = database.create_user().finish();
+
+    let order = database
+        .create_cart()
+        .with_free_items()
+        .for_user(&user)
+        .for_event(&event)
+        .finish();
+    let request = TestRequest::create();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let response = cart::checkout((
+        database.connection.clone().into(),
+        Json(cart::CheckoutCartRequest {
+            amount: 0,
+            method: PaymentRequest::Free,
+        }),
+        auth_user.clone(),
+        request.extract_state(),
+        IdempotencyKey(Some("checkout-key".to_string())),
+    ))
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = unwrap_body_to_string(&response).unwrap().to_string();
+    let order = Order::find(order.id, connection).unwrap();
+    assert_eq!(order.status, OrderStatus::Paid);
+
+    // Retry is replayed even though the cart no longer exists
+    let response = cart::checkout((
+        database.connection.clone().into(),
+        Json(cart::CheckoutCartRequest {
+            amount: 0,
+            method: PaymentRequest::Free,
+        }),
+        auth_user.clone(),
+        request.extract_state(),
+        IdempotencyKey(Some("checkout-key".to_string())),
+    ))
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(unwrap_body_to_string(&response).unwrap(), body);
+
+    // Same key with a different request
+    let response: HttpResponse = cart::checkout((
+        database.connection.clone().into(),
+        Json(cart::CheckoutCartRequest {
+            amount: 100,
+            method: PaymentRequest::Free,
+        }),
+        auth_user,
+        request.extract_state(),
+        IdempotencyKey(Some("checkout-key".to_string())),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[test]
+fn checkout_with_idempotency_key_left_incomplete() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let event = database
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+
+    let user = database.create_user().finish();
+
+    let order = database
+        .create_cart()
+        .with_free_items()
+        .for_user(&user)
+        .for_event(&event)
+        .finish();
+    let request = TestRequest::create();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let checkout_request = cart::CheckoutCartRequest {
+        amount: 0,
+        method: PaymentRequest::Free,
+    };
+
+    // Original request committed its claim but never stored a response
+    let key = OrderIdempotencyKey::create(
+        order.id,
+        user.id,
+        IdempotencyKeyOperations::Checkout,
+        "checkout-key".to_string(),
+        &json!(checkout_request),
+    )
+    .commit(connection)
+    .unwrap()
+    .unwrap();
+    diesel::update(&key)
+        .set(order_idempotency_keys::updated_at.eq(Utc::now().naive_utc() - Duration::minutes(10)))
+        .execute(connection)
+        .unwrap();
+
+    let response: HttpResponse = cart::checkout((
+        database.connection.clone().into(),
+        Json(checkout_request),
+        auth_user,
+        request.extract_state(),
+        IdempotencyKey(Some("checkout-key".to_string())),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let order = Order::find(order.id, connection).unwrap();
+    assert_eq!(order.status, OrderStatus::Draft);
+    assert!(OrderIdempotencyKey::find(
+        user.id,
+        IdempotencyKeyOperations::Checkout,
+        "checkout-key",
+        connection
+    )
+    .unwrap()
+    .is_some());
+}
+
 #[test]
 fn checkout_free_for_paid_items() {
     let database = TestDatabase::new();
@@ -894,6 +1138,7 @@ fn checkout_free_for_paid_items() {
         input,
         user,
         request.extract_state(),
+        IdempotencyKey(None),
     ))
     .into();
     assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
@@ -909,3 +1154,59 @@ fn checkout_free_for_paid_items() {
     let order = Order::find(order.id, connection).unwrap();
     assert_eq!(order.status, OrderStatus::Draft);
 }
+
+#[test]
+fn checkout_with_provider_not_used_by_organization() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let event = database
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+
+    let user = database.create_user().finish();
+
+    let order = database
+        .create_cart()
+        .for_user(&user)
+        .for_event(&event)
+        .finish();
+    let request = TestRequest::create();
+
+    // Organization charges through stripe by default
+    let input = Json(cart::CheckoutCartRequest {
+        amount: order.calculate_total(connection).unwrap(),
+        method: PaymentRequest::Card {
+            token: "tok_sandbox".to_string(),
+            provider: "sandbox".to_string(),
+            save_payment_method: false,
+            set_default: false,
+        },
+    });
+
+    let user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let response: HttpResponse = cart::checkout((
+        database.connection.clone().into(),
+        input,
+        user,
+        request.extract_state(),
+        IdempotencyKey(None),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let expected_json = HttpResponse::new(StatusCode::UNPROCESSABLE_ENTITY)
+        .into_builder()
+        .json(json!({
+            "error": "Could not complete this cart because payments for these events must be made through stripe"
+        }));
+    let expected_text = unwrap_body_to_string(&expected_json).unwrap();
+    let body = unwrap_body_to_string(&response).unwrap();
+    assert_eq!(body, expected_text);
+
+    // Reload order
+    let order = Order::find(order.id, connection).unwrap();
+    assert_eq!(order.status, OrderStatus::Draft);
+}
@@ -162,6 +162,10 @@ fn create_with_validation_errors() {
         code_type: CodeTypes::Discount,
         max_uses: 10,
         discount_in_cents: Some(100),
+        discount_as_percentage: None,
+        buy_quantity: None,
+        get_quantity: None,
+        minimum_order_total_in_cents: None,
         start_date,
         end_date,
         max_tickets_per_user: None,
@@ -212,6 +216,10 @@ fn create_fails_adding_ticket_type_id_from_other_event() {
         code_type: CodeTypes::Discount,
         max_uses: 10,
         discount_in_cents: Some(100),
+        discount_as_percentage: None,
+        buy_quantity: None,
+        get_quantity: None,
+        minimum_order_total_in_cents: None,
         start_date,
         end_date,
         max_tickets_per_user: None,
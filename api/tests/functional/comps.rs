@@ -1,6 +1,6 @@
 use actix_web::error::ResponseError;
 use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path};
-use bigneon_api::controllers::comps::{self, NewCompRequest};
+use bigneon_api::controllers::comps::{self, ClaimCompRequest, NewCompRequest};
 use bigneon_api::controllers::holds::UpdateHoldRequest;
 use bigneon_api::extractors::*;
 use bigneon_api::models::PathParameters;
@@ -207,7 +207,13 @@ fn create_with_validation_errors() {
     let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
     path.id = hold.id;
 
-    let response = comps::create((database.connection.clone(), json, path, auth_user));
+    let response = comps::create((
+        database.connection.clone(),
+        json,
+        path,
+        auth_user,
+        test_request.extract_state(),
+    ));
     let err = response.err().unwrap();
 
     let response: HttpResponse = err.error_response();
@@ -254,3 +260,63 @@ fn update_with_validation_errors() {
         "Email is invalid"
     );
 }
+
+#[test]
+fn send() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let comp = database.create_comp().finish();
+    let event = Event::find(comp.event_id, connection).unwrap();
+    let organization = event.organization(connection).unwrap();
+    let auth_user =
+        support::create_auth_user_from_user(&user, Roles::OrgOwner, Some(&organization), &database);
+
+    let test_request = TestRequest::create_with_uri_custom_params("/", vec!["id"]);
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = comp.id;
+
+    let response: HttpResponse = comps::send((
+        database.connection.clone(),
+        path,
+        auth_user,
+        test_request.extract_state(),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let comp = Hold::find(comp.id, connection).unwrap();
+    assert!(comp.claim_key.is_some());
+    assert!(comp.claim_sent_at.is_some());
+}
+
+#[test]
+fn claim() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let sender = database.create_user().finish();
+    let user = database.create_user().finish();
+    let comp = database.create_comp().with_quantity(2).finish();
+    let (comp, _) = comp.prepare_claim(sender.id, connection).unwrap();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let test_request = TestRequest::create();
+    let json = Json(ClaimCompRequest {
+        claim_key: comp.claim_key.unwrap(),
+    });
+    let response: HttpResponse = comps::claim((
+        database.connection.clone(),
+        json,
+        auth_user,
+        test_request.extract_state(),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let tickets = TicketInstance::find_for_user(user.id, connection).unwrap();
+    assert_eq!(tickets.len(), 2);
+    assert!(TicketInstance::find_for_user(sender.id, connection)
+        .unwrap()
+        .is_empty());
+    let comp = Hold::find(comp.id, connection).unwrap();
+    assert_eq!(comp.claimed_by_user_id, Some(user.id));
+}
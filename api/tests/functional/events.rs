@@ -304,6 +304,7 @@ pub fn index_search_with_filter() {
         tracking_keys: TrackingKeys {
             ..Default::default()
         },
+        event_series_id: None,
     }];
 
     let test_request = TestRequest::create_with_uri("/events?query=NewEvent1");
@@ -406,7 +407,7 @@ fn show_with_cancelled_ticket_type() {
     event.add_artist(artist2.id, conn).unwrap();
 
     let ticket_type = &event.ticket_types(conn).unwrap()[0];
-    let _cancelled_ticket_type = ticket_type.cancel(conn).unwrap();
+    let _cancelled_ticket_type = ticket_type.cancel(user.id, conn).unwrap();
 
     let _event_interest = EventInterest::create(event.id, user.id).commit(conn);
     let test_request = TestRequest::create_with_uri(&format!("/events/{}", event.id));
@@ -569,6 +570,146 @@ mod cancel_tests {
     }
 }
 
+#[cfg(test)]
+mod cancellations_tests {
+    use super::*;
+
+    #[test]
+    fn cancellations_org_member() {
+        base::events::cancellations(Roles::OrgMember, true);
+    }
+
+    #[test]
+    fn cancellations_admin() {
+        base::events::cancellations(Roles::Admin, true);
+    }
+
+    #[test]
+    fn cancellations_user() {
+        base::events::cancellations(Roles::User, false);
+    }
+
+    #[test]
+    fn cancellations_org_owner() {
+        base::events::cancellations(Roles::OrgOwner, true);
+    }
+}
+
+#[cfg(test)]
+mod clone_tests {
+    use super::*;
+
+    #[test]
+    fn clone_org_member() {
+        base::events::clone(Roles::OrgMember, true);
+    }
+
+    #[test]
+    fn clone_admin() {
+        base::events::clone(Roles::Admin, true);
+    }
+
+    #[test]
+    fn clone_user() {
+        base::events::clone(Roles::User, false);
+    }
+
+    #[test]
+    fn clone_org_owner() {
+        base::events::clone(Roles::OrgOwner, true);
+    }
+}
+
+#[cfg(test)]
+mod offline_scanning_tests {
+    use super::*;
+
+    #[test]
+    fn offline_scanning_org_member() {
+        base::events::offline_scanning(Roles::OrgMember, true);
+    }
+
+    #[test]
+    fn offline_scanning_admin() {
+        base::events::offline_scanning(Roles::Admin, true);
+    }
+
+    #[test]
+    fn offline_scanning_user() {
+        base::events::offline_scanning(Roles::User, false);
+    }
+
+    #[test]
+    fn offline_scanning_org_owner() {
+        base::events::offline_scanning(Roles::OrgOwner, true);
+    }
+
+    #[test]
+    fn offline_scanning_door_person() {
+        base::events::offline_scanning(Roles::DoorPerson, true);
+    }
+}
+
+#[cfg(test)]
+mod upload_offline_scans_tests {
+    use super::*;
+
+    #[test]
+    fn upload_offline_scans_org_member() {
+        base::events::upload_offline_scans(Roles::OrgMember, true);
+    }
+
+    #[test]
+    fn upload_offline_scans_admin() {
+        base::events::upload_offline_scans(Roles::Admin, true);
+    }
+
+    #[test]
+    fn upload_offline_scans_user() {
+        base::events::upload_offline_scans(Roles::User, false);
+    }
+
+    #[test]
+    fn upload_offline_scans_org_owner() {
+        base::events::upload_offline_scans(Roles::OrgOwner, true);
+    }
+
+    #[test]
+    fn upload_offline_scans_door_person() {
+        base::events::upload_offline_scans(Roles::DoorPerson, true);
+    }
+}
+
+#[cfg(test)]
+mod scans_tests {
+    use super::*;
+
+    #[test]
+    fn scans_org_member() {
+        base::events::scans(Roles::OrgMember, true);
+    }
+
+    #[test]
+    fn scans_admin() {
+        base::events::scans(Roles::Admin, true);
+    }
+
+    #[test]
+    fn scans_user() {
+        base::events::scans(Roles::User, false);
+    }
+
+    #[test]
+    fn scans_org_owner() {
+        base::events::scans(Roles::OrgOwner, true);
+    }
+
+    #[test]
+    fn scans_door_person() {
+        base::events::scans(Roles::DoorPerson, true);
+    }
+}
+
 #[cfg(test)]
 mod add_artist_tests {
     use super::*;
@@ -802,6 +943,7 @@ fn dashboard_with_default_range() {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -1050,6 +1192,7 @@ struct EventVenueEntry {
     user_is_interested: bool,
     localized_times: EventLocalizedTimeStrings,
     tracking_keys: TrackingKeys,
+    event_series_id: Option<Uuid>,
 }
 
 fn event_venue_entry(
@@ -1086,5 +1229,6 @@ fn event_venue_entry(
         tracking_keys: TrackingKeys {
             ..Default::default()
         },
+        event_series_id: event.event_series_id,
     }
 }
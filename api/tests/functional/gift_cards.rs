@@ -0,0 +1,69 @@
+use actix_web::{http::StatusCode, HttpResponse};
+use bigneon_api::controllers::gift_cards::{self, RedeemGiftCardRequest};
+use bigneon_api::extractors::*;
+use bigneon_db::models::*;
+use serde_json;
+use support;
+use support::database::TestDatabase;
+
+#[test]
+fn index() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_gift_cards(
+        &[UpdateGiftCardItem {
+            value_in_cents: 2500,
+            quantity: 2,
+        }],
+        connection,
+    )
+    .unwrap();
+    cart.add_external_payment(Some("test".to_string()), user.id, 5000, connection)
+        .unwrap();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let response: HttpResponse = gift_cards::index((database.connection.clone(), auth_user)).into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let found_gift_cards: Vec<GiftCard> = serde_json::from_str(&body).unwrap();
+    assert_eq!(found_gift_cards.len(), 2);
+    assert!(found_gift_cards.iter().all(|g| g.value_in_cents == 2500));
+}
+
+#[test]
+fn redeem() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let purchaser = database.create_user().finish();
+    let user = database.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&purchaser, connection).unwrap();
+    cart.update_gift_cards(
+        &[UpdateGiftCardItem {
+            value_in_cents: 2500,
+            quantity: 1,
+        }],
+        connection,
+    )
+    .unwrap();
+    cart.add_external_payment(Some("test".to_string()), purchaser.id, 2500, connection)
+        .unwrap();
+    let gift_card = GiftCard::find_for_order(cart.id, connection)
+        .unwrap()
+        .remove(0);
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let json = Json(RedeemGiftCardRequest {
+        redemption_code: gift_card.redemption_code.clone(),
+    });
+    let response: HttpResponse =
+        gift_cards::redeem((database.connection.clone(), json, auth_user)).into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    assert_eq!(body, json!({"credit_balance_in_cents": 2500}).to_string());
+    assert_eq!(
+        CreditTransaction::balance_for_user(user.id, connection).unwrap(),
+        2500
+    );
+}
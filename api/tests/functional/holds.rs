@@ -96,6 +96,7 @@ fn create_with_validation_errors() {
         name: name.clone(),
         redemption_code,
         discount_in_cents: None,
+        discount_as_percentage: None,
         hold_type,
         end_at: None,
         max_per_order: None,
@@ -181,6 +182,7 @@ pub fn read_hold() {
         name: name.clone(),
         redemption_code,
         discount_in_cents: Some(100),
+        discount_as_percentage: None,
         hold_type,
         end_at: None,
         max_per_order: None,
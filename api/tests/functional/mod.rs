@@ -5,6 +5,7 @@ mod cart;
 mod codes;
 mod comps;
 mod events;
+mod gift_cards;
 mod holds;
 mod orders;
 mod organization_invites;
@@ -12,9 +13,14 @@ mod organizations;
 mod password_resets;
 mod payment_methods;
 mod regions;
+mod resale_listings;
+mod resale_payouts;
+mod seat_sections;
 mod stages;
+mod tax_rules;
 mod ticket_types;
 mod tickets;
 mod user_invites;
 mod users;
 mod venues;
+mod waitlist_entries;
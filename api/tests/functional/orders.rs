@@ -1,10 +1,11 @@
-use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path, Query};
+use actix_web::{http::StatusCode, Body, FromRequest, HttpResponse, Path, Query};
 use bigneon_api::controllers::orders::{self, *};
-use bigneon_api::extractors::Json;
+use bigneon_api::extractors::{IdempotencyKey, Json};
 use bigneon_api::models::PathParameters;
 use bigneon_db::models::*;
 use bigneon_db::schema;
 use chrono::prelude::*;
+use chrono::Duration;
 use diesel;
 use diesel::prelude::*;
 use functional::base;
@@ -201,11 +202,13 @@ pub fn details_with_tickets_user_has_no_access_to() {
                 ticket_type_id: ticket_type.id,
                 quantity: 2,
                 redemption_code: None,
+                seat_ids: None,
             },
             UpdateOrderItem {
                 ticket_type_id: ticket_type2.id,
                 quantity: 2,
                 redemption_code: None,
+                seat_ids: None,
             },
         ],
         false,
@@ -332,6 +335,7 @@ pub fn refund_for_non_refundable_tickets() {
             ticket_type_id: ticket_type.id,
             quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -385,6 +389,7 @@ pub fn refund_for_non_refundable_tickets() {
     ];
     let json = Json(RefundAttributes {
         items: refund_items,
+        to_credit: false,
     });
 
     let test_request = TestRequest::create();
@@ -396,6 +401,7 @@ pub fn refund_for_non_refundable_tickets() {
         json,
         auth_user,
         test_request.extract_state(),
+        IdempotencyKey(None),
     ))
     .into();
 
@@ -444,6 +450,7 @@ pub fn refund_removes_event_fee_if_all_event_tickets_refunded() {
             ticket_type_id: ticket_type.id,
             quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -484,6 +491,7 @@ pub fn refund_removes_event_fee_if_all_event_tickets_refunded() {
     ];
     let json = Json(RefundAttributes {
         items: refund_items,
+        to_credit: false,
     });
 
     let test_request = TestRequest::create();
@@ -495,6 +503,7 @@ pub fn refund_removes_event_fee_if_all_event_tickets_refunded() {
         json,
         auth_user,
         test_request.extract_state(),
+        IdempotencyKey(None),
     ))
     .into();
 
@@ -529,3 +538,263 @@ pub fn refund_removes_event_fee_if_all_event_tickets_refunded() {
     let event_fee_item = OrderItem::find(event_fee_item.id, connection).unwrap();
     assert_eq!(event_fee_item.refunded_quantity, 1);
 }
+
+#[test]
+pub fn exchange_for_cheaper_ticket_type() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let creator = database.create_user().finish();
+    let organization = database
+        .create_organization()
+        .with_fee_schedule(&database.create_fee_schedule().finish(creator.id))
+        .finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let cheaper_ticket_type = event
+        .add_ticket_type(
+            "Balcony".to_string(),
+            None,
+            10,
+            Utc::now().naive_utc() - Duration::days(1),
+            Utc::now().naive_utc() + Duration::days(2),
+            event.issuer_wallet(connection).unwrap().id,
+            None,
+            0,
+            50,
+            connection,
+        )
+        .unwrap();
+    Asset::find_by_ticket_type(&cheaper_ticket_type.id, connection)
+        .unwrap()
+        .update_blockchain_id("Balcony".to_string(), connection)
+        .unwrap();
+
+    let user = database.create_user().finish();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 1,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(Some("Test".to_string()), user.id, total, connection)
+        .unwrap();
+
+    let order_item = cart
+        .items(connection)
+        .unwrap()
+        .into_iter()
+        .find(|i| i.ticket_type_id == Some(ticket_type.id))
+        .unwrap();
+    let ticket = &TicketInstance::find_for_order_item(order_item.id, connection).unwrap()[0];
+
+    let json = Json(ExchangeAttributes {
+        ticket_instance_ids: vec![ticket.id],
+        ticket_type_id: cheaper_ticket_type.id,
+        method: None,
+    });
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = cart.id;
+    let response: HttpResponse = orders::exchange((
+        database.connection.clone(),
+        path,
+        json,
+        auth_user,
+        test_request.extract_state(),
+    ))
+    .into();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let exchange_response: ExchangeResponse = serde_json::from_str(&body).unwrap();
+    assert!(exchange_response.exchange.difference_in_cents < 0);
+    assert_eq!(exchange_response.amount_charged, 0);
+    assert_eq!(
+        exchange_response.amount_refunded as i64,
+        -exchange_response.exchange.difference_in_cents
+    );
+    let mut expected_refund_breakdown = HashMap::new();
+    expected_refund_breakdown.insert(PaymentMethods::External, exchange_response.amount_refunded);
+    assert_eq!(
+        exchange_response.refund_breakdown,
+        expected_refund_breakdown
+    );
+
+    let ticket = TicketInstance::find(ticket.id, connection).unwrap();
+    assert_eq!(ticket.status, TicketInstanceStatus::Nullified);
+    let new_tickets = TicketInstance::find_for_order_item(
+        exchange_response.exchange.to_order_item_id,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(new_tickets.len(), 1);
+    assert_eq!(new_tickets[0].status, TicketInstanceStatus::Purchased);
+}
+
+#[test]
+pub fn reschedule_refund() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let event = database
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let user = database.create_user().finish();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(Some("Test".to_string()), user.id, total, connection)
+        .unwrap();
+
+    // Event has not been rescheduled
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = cart.id;
+    let response: HttpResponse = orders::reschedule_refund((
+        database.connection.clone(),
+        path,
+        auth_user.clone(),
+        test_request.extract_state(),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Transferred tickets are left out of the refund
+    let ticket = TicketInstance::find_for_user(user.id, connection)
+        .unwrap()
+        .remove(0);
+    let receiver = database.create_user().finish();
+    let sender_wallet = Wallet::find_default_for_user(user.id, connection).unwrap();
+    let receiver_wallet = Wallet::find_default_for_user(receiver.id, connection).unwrap();
+    let transfer_auth =
+        TicketInstance::authorize_ticket_transfer(user.id, vec![ticket.id], 3600, connection)
+            .unwrap();
+    TicketInstance::receive_ticket_transfer(
+        transfer_auth,
+        &sender_wallet,
+        &receiver_wallet.id,
+        connection,
+    )
+    .unwrap();
+
+    event
+        .update(
+            None,
+            EventEditableAttributes {
+                event_start: Some(NaiveDate::from_ymd(2016, 8, 8).and_hms(9, 10, 11)),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = cart.id;
+    let response: HttpResponse = orders::reschedule_refund((
+        database.connection.clone(),
+        path,
+        auth_user,
+        test_request.extract_state(),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let refund_response: RefundResponse = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        refund_response.transferred_ticket_instance_ids,
+        vec![ticket.id]
+    );
+    assert!(refund_response.amount_refunded > 0);
+    assert!(refund_response.amount_refunded < total as u32);
+
+    let order = Order::find(cart.id, connection).unwrap();
+    assert!(order
+        .refundable_ticket_items(event.id, None, connection)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+pub fn tickets_as_pdf() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let venue = database.create_venue().finish();
+    let event = database
+        .create_event()
+        .with_venue(&venue)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let order = database
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    assert_eq!(
+        TicketInstance::find_for_user(user.id, connection)
+            .unwrap()
+            .len(),
+        2
+    );
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let test_request = TestRequest::create_with_uri("/?format=pdf");
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = order.id;
+    let query = Query::<TicketsParameters>::extract(&test_request.request).unwrap();
+    let response: HttpResponse =
+        orders::tickets((database.connection.clone(), path, query, auth_user.clone())).into();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("Content-Type").unwrap(),
+        "application/pdf"
+    );
+    match response.body() {
+        Body::Binary(binary) => assert!(binary.as_ref().starts_with(b"%PDF")),
+        _ => panic!("Expected a PDF body"),
+    }
+
+    // Tickets are returned as JSON by default
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = order.id;
+    let query = Query::<TicketsParameters>::extract(&test_request.request).unwrap();
+    let response: HttpResponse =
+        orders::tickets((database.connection.clone(), path, query, auth_user)).into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let tickets: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+    assert_eq!(tickets.len(), 2);
+}
@@ -396,3 +396,36 @@ mod add_fee_schedule_tests {
         organizations::add_fee_schedule(Roles::OrgBoxOffice, false);
     }
 }
+
+#[cfg(test)]
+mod update_payment_provider_tests {
+    use super::*;
+    #[test]
+    fn update_payment_provider_org_member() {
+        organizations::update_payment_provider(Roles::OrgMember, false);
+    }
+    #[test]
+    fn update_payment_provider_admin() {
+        organizations::update_payment_provider(Roles::Admin, true);
+    }
+    #[test]
+    fn update_payment_provider_user() {
+        organizations::update_payment_provider(Roles::User, false);
+    }
+    #[test]
+    fn update_payment_provider_org_owner() {
+        organizations::update_payment_provider(Roles::OrgOwner, false);
+    }
+    #[test]
+    fn update_payment_provider_door_person() {
+        organizations::update_payment_provider(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn update_payment_provider_org_admin() {
+        organizations::update_payment_provider(Roles::OrgAdmin, false);
+    }
+    #[test]
+    fn update_payment_provider_box_office() {
+        organizations::update_payment_provider(Roles::OrgBoxOffice, false);
+    }
+}
@@ -0,0 +1,108 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path};
+use bigneon_api::controllers::resale_listings::{self, NewResaleListingRequest};
+use bigneon_api::extractors::*;
+use bigneon_api::models::PathParameters;
+use bigneon_db::models::*;
+use chrono::prelude::*;
+use chrono::Duration;
+use serde_json;
+use support;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+
+fn purchased_ticket(user: &User, database: &TestDatabase) -> (Event, TicketInstance, OrderItem) {
+    let connection = database.connection.get();
+    let creator = database.create_user().finish();
+    let organization = database
+        .create_organization()
+        .with_fee_schedule(&database.create_fee_schedule().finish(creator.id))
+        .finish()
+        .set_resale_price_cap(Some(100), connection)
+        .unwrap();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_event_start(Utc::now().naive_utc() + Duration::days(7))
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let mut cart = Order::find_or_create_cart(user, connection).unwrap();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 1,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(Some("test".to_string()), user.id, total, connection)
+        .unwrap();
+
+    let order_item = cart
+        .items(connection)
+        .unwrap()
+        .into_iter()
+        .find(|i| i.item_type == OrderItemTypes::Tickets)
+        .unwrap();
+    let ticket = TicketInstance::find_for_order_item(order_item.id, connection)
+        .unwrap()
+        .remove(0);
+    (event, ticket, order_item)
+}
+
+#[test]
+fn create() {
+    let database = TestDatabase::new();
+    let user = database.create_user().finish();
+    let (event, ticket, order_item) = purchased_ticket(&user, &database);
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let json = Json(NewResaleListingRequest {
+        ticket_instance_id: ticket.id,
+        price_in_cents: order_item.unit_price_in_cents,
+    });
+    let response: HttpResponse =
+        resale_listings::create((database.connection.clone(), json, auth_user)).into();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let resale_listing: ResaleListing = serde_json::from_str(&body).unwrap();
+    assert_eq!(resale_listing.ticket_instance_id, ticket.id);
+    assert_eq!(resale_listing.status, ResaleListingStatus::Active);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = event.id;
+    let response: HttpResponse =
+        resale_listings::index_for_event((database.connection.clone(), path)).into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let found_resale_listings: Vec<ResaleListing> = serde_json::from_str(&body).unwrap();
+    assert_eq!(found_resale_listings, vec![resale_listing]);
+}
+
+#[test]
+fn destroy() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let (_event, ticket, order_item) = purchased_ticket(&user, &database);
+    let resale_listing = ResaleListing::create(ticket.id, user.id, order_item.unit_price_in_cents)
+        .commit(connection)
+        .unwrap();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = resale_listing.id;
+    let response: HttpResponse =
+        resale_listings::destroy((database.connection.clone(), path, auth_user)).into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let resale_listing = ResaleListing::find(resale_listing.id, connection).unwrap();
+    assert_eq!(resale_listing.status, ResaleListingStatus::Cancelled);
+}
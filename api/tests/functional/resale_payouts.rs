@@ -0,0 +1,138 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path};
+use bigneon_api::controllers::resale_payouts;
+use bigneon_api::models::PathParameters;
+use bigneon_db::models::*;
+use chrono::prelude::*;
+use chrono::Duration;
+use serde_json;
+use support;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+
+fn sold_resale_payout(organization: &Organization, database: &TestDatabase) -> ResalePayout {
+    let connection = database.connection.get();
+    let event = database
+        .create_event()
+        .with_organization(organization)
+        .with_event_start(Utc::now().naive_utc() + Duration::days(7))
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let seller = database.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&seller, connection).unwrap();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 1,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(Some("test".to_string()), seller.id, total, connection)
+        .unwrap();
+    let order_item = cart
+        .items(connection)
+        .unwrap()
+        .into_iter()
+        .find(|i| i.item_type == OrderItemTypes::Tickets)
+        .unwrap();
+    let ticket = TicketInstance::find_for_order_item(order_item.id, connection)
+        .unwrap()
+        .remove(0);
+    let resale_listing =
+        ResaleListing::create(ticket.id, seller.id, order_item.unit_price_in_cents)
+            .commit(connection)
+            .unwrap();
+
+    let buyer = database.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&buyer, connection).unwrap();
+    cart.update_resale_listings(&[resale_listing.id], connection)
+        .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(Some("test".to_string()), buyer.id, total, connection)
+        .unwrap();
+
+    ResalePayout::find_unpaid_for_organization(organization.id, connection)
+        .unwrap()
+        .into_iter()
+        .find(|p| p.resale_listing_id == resale_listing.id)
+        .unwrap()
+}
+
+fn organization_with_resale(database: &TestDatabase) -> Organization {
+    let creator = database.create_user().finish();
+    database
+        .create_organization()
+        .with_fee_schedule(&database.create_fee_schedule().finish(creator.id))
+        .finish()
+        .set_resale_price_cap(Some(100), database.connection.get())
+        .unwrap()
+}
+
+#[test]
+fn index() {
+    let database = TestDatabase::new();
+    let organization = organization_with_resale(&database);
+    let resale_payout = sold_resale_payout(&organization, &database);
+
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let auth_user = support::create_auth_user(Roles::OrgAdmin, Some(&organization), &database);
+    let response: HttpResponse =
+        resale_payouts::index((database.connection.clone(), path, auth_user)).into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let found_resale_payouts: Vec<ResalePayout> = serde_json::from_str(&body).unwrap();
+    assert_eq!(found_resale_payouts, vec![resale_payout]);
+
+    // Org members cannot see what the organization owes
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = organization.id;
+    let auth_user = support::create_auth_user(Roles::OrgMember, Some(&organization), &database);
+    let response: HttpResponse =
+        resale_payouts::index((database.connection.clone(), path, auth_user)).into();
+    support::expects_unauthorized(&response);
+}
+
+#[test]
+fn paid() {
+    let database = TestDatabase::new();
+    let organization = organization_with_resale(&database);
+    let resale_payout = sold_resale_payout(&organization, &database);
+    let test_request = TestRequest::create();
+
+    // Admins of another organization cannot mark the payout paid
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = resale_payout.id;
+    let auth_user = support::create_auth_user(Roles::OrgAdmin, None, &database);
+    let response: HttpResponse =
+        resale_payouts::paid((database.connection.clone(), path, auth_user)).into();
+    support::expects_unauthorized(&response);
+
+    let user = database.create_user().finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, Roles::OrgAdmin, Some(&organization), &database);
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = resale_payout.id;
+    let response: HttpResponse =
+        resale_payouts::paid((database.connection.clone(), path, auth_user.clone())).into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let paid_resale_payout: ResalePayout = serde_json::from_str(&body).unwrap();
+    assert!(paid_resale_payout.paid_at.is_some());
+    assert_eq!(paid_resale_payout.paid_by_user_id, Some(user.id));
+
+    // A payout cannot be paid twice
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = resale_payout.id;
+    let response: HttpResponse =
+        resale_payouts::paid((database.connection.clone(), path, auth_user)).into();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
@@ -0,0 +1,68 @@
+use bigneon_db::models::*;
+use functional::base;
+
+#[cfg(test)]
+mod create_tests {
+    use super::*;
+    #[test]
+    fn create_org_member() {
+        base::seat_sections::create(Roles::OrgMember, false);
+    }
+    #[test]
+    fn create_admin() {
+        base::seat_sections::create(Roles::Admin, true);
+    }
+    #[test]
+    fn create_user() {
+        base::seat_sections::create(Roles::User, false);
+    }
+    #[test]
+    fn create_org_owner() {
+        base::seat_sections::create(Roles::OrgOwner, false);
+    }
+    #[test]
+    fn create_door_person() {
+        base::seat_sections::create(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn create_org_admin() {
+        base::seat_sections::create(Roles::OrgAdmin, false);
+    }
+    #[test]
+    fn create_box_office() {
+        base::seat_sections::create(Roles::OrgBoxOffice, false);
+    }
+}
+
+#[cfg(test)]
+mod add_seats_tests {
+    use super::*;
+    #[test]
+    fn add_seats_org_member() {
+        base::seat_sections::add_seats(Roles::OrgMember, false);
+    }
+    #[test]
+    fn add_seats_admin() {
+        base::seat_sections::add_seats(Roles::Admin, true);
+    }
+    #[test]
+    fn add_seats_user() {
+        base::seat_sections::add_seats(Roles::User, false);
+    }
+    #[test]
+    fn add_seats_org_owner() {
+        base::seat_sections::add_seats(Roles::OrgOwner, false);
+    }
+    #[test]
+    fn add_seats_door_person() {
+        base::seat_sections::add_seats(Roles::DoorPerson, false);
+    }
+    #[test]
+    fn add_seats_org_admin() {
+        base::seat_sections::add_seats(Roles::OrgAdmin, false);
+    }
+    #[test]
+    fn add_seats_box_office() {
+        base::seat_sections::add_seats(Roles::OrgBoxOffice, false);
+    }
+}
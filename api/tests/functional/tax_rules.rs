@@ -0,0 +1,85 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path};
+use bigneon_api::controllers::tax_rules::{self, CreateTaxRule};
+use bigneon_api::extractors::*;
+use bigneon_api::models::PathParameters;
+use bigneon_db::models::*;
+use serde_json;
+use support;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+
+#[test]
+fn create_for_region() {
+    let database = TestDatabase::new();
+    let region = database.create_region().finish();
+
+    let user = support::create_auth_user(Roles::User, None, &database);
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = region.id;
+    let json = Json(CreateTaxRule {
+        name: "GST".to_string(),
+        rate_in_basis_points: 500,
+        inclusive: false,
+        applies_to_tickets: None,
+        applies_to_fees: Some(true),
+    });
+    let response: HttpResponse =
+        tax_rules::create_for_region((database.connection.clone(), path, json, user)).into();
+    support::expects_unauthorized(&response);
+
+    let user = support::create_auth_user(Roles::Admin, None, &database);
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = region.id;
+    let json = Json(CreateTaxRule {
+        name: "GST".to_string(),
+        rate_in_basis_points: 500,
+        inclusive: false,
+        applies_to_tickets: None,
+        applies_to_fees: Some(true),
+    });
+    let response: HttpResponse =
+        tax_rules::create_for_region((database.connection.clone(), path, json, user)).into();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let tax_rule: TaxRule = serde_json::from_str(&body).unwrap();
+    assert_eq!(tax_rule.region_id, Some(region.id));
+    assert_eq!(tax_rule.rate_in_basis_points, 500);
+    assert!(tax_rule.applies_to_tickets);
+    assert!(tax_rule.applies_to_fees);
+
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = region.id;
+    let response: HttpResponse =
+        tax_rules::index_for_region((database.connection.clone(), path)).into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let found_tax_rules: Vec<TaxRule> = serde_json::from_str(&body).unwrap();
+    assert_eq!(found_tax_rules, vec![tax_rule]);
+}
+
+#[test]
+fn update() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let venue = database.create_venue().finish();
+    let tax_rule = TaxRule::create_for_venue(venue.id, "VAT".to_string(), 2000, true, true, false)
+        .commit(connection)
+        .unwrap();
+
+    let user = support::create_auth_user(Roles::Admin, None, &database);
+    let test_request = TestRequest::create();
+    let mut path = Path::<PathParameters>::extract(&test_request.request).unwrap();
+    path.id = tax_rule.id;
+    let json = Json(TaxRuleEditableAttributes {
+        rate_in_basis_points: Some(2100),
+        ..Default::default()
+    });
+    let response: HttpResponse =
+        tax_rules::update((database.connection.clone(), path, json, user)).into();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let updated_tax_rule: TaxRule = serde_json::from_str(&body).unwrap();
+    assert_eq!(updated_tax_rule.rate_in_basis_points, 2100);
+    assert!(updated_tax_rule.inclusive);
+}
@@ -181,6 +181,7 @@ pub fn create_with_validation_errors() {
         increment: None,
         limit_per_person: 0,
         price_in_cents: 10000,
+        allow_reentry: None,
     };
     let response: HttpResponse = ticket_types::create((
         database.connection.into(),
@@ -243,6 +244,7 @@ pub fn create_with_validation_errors_on_ticket_pricing() {
         increment: None,
         limit_per_person: 0,
         price_in_cents: 10000,
+        allow_reentry: None,
     };
     let response: HttpResponse = ticket_types::create((
         database.connection.into(),
@@ -314,6 +316,7 @@ pub fn create_with_overlapping_periods() {
         increment: None,
         limit_per_person: 0,
         price_in_cents: 20000,
+        allow_reentry: None,
     };
     let response: HttpResponse = ticket_types::create((
         database.connection.into(),
@@ -403,6 +406,7 @@ pub fn update_with_invalid_id() {
         increment: None,
         limit_per_person: Some(0),
         price_in_cents: Some(20000),
+        allow_reentry: None,
     };
 
     //Send update request
@@ -469,6 +473,7 @@ pub fn update_with_validation_errors() {
         increment: None,
         limit_per_person: Some(0),
         price_in_cents: Some(20000),
+        allow_reentry: None,
     };
 
     //Send update request
@@ -546,6 +551,7 @@ pub fn update_with_validation_errors_on_ticket_pricing() {
         increment: None,
         limit_per_person: Some(0),
         price_in_cents: Some(20000),
+        allow_reentry: None,
     };
 
     //Send update request
@@ -635,6 +641,7 @@ pub fn update_with_overlapping_periods() {
         increment: None,
         limit_per_person: Some(0),
         price_in_cents: Some(20000),
+        allow_reentry: None,
     };
 
     //Send update request
@@ -658,3 +665,92 @@ pub fn update_with_overlapping_periods() {
     let deserialized_response: Response = serde_json::from_str(&body).unwrap();
     assert_eq!(deserialized_response.error, "Validation error");
 }
+
+#[test]
+fn seats() {
+    let database = TestDatabase::new();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let other_event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_status(EventStatus::Draft)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let conn = database.connection.get();
+    let ticket_type = &event.ticket_types(conn).unwrap()[0];
+    let other_ticket_type = &other_event.ticket_types(conn).unwrap()[0];
+    let user = database.create_user().finish();
+    let test_request =
+        TestRequest::create_with_uri_custom_params("/", vec!["event_id", "ticket_type_id"]);
+
+    // Published events show their seats to buyers
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let mut path = Path::<EventTicketPathParameters>::extract(&test_request.request).unwrap();
+    path.event_id = event.id;
+    path.ticket_type_id = ticket_type.id;
+    let response: HttpResponse =
+        ticket_types::seats((database.connection.clone().into(), path, auth_user)).into();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The ticket type must belong to the event
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let mut path = Path::<EventTicketPathParameters>::extract(&test_request.request).unwrap();
+    path.event_id = event.id;
+    path.ticket_type_id = other_ticket_type.id;
+    let response: HttpResponse =
+        ticket_types::seats((database.connection.clone().into(), path, auth_user)).into();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // Only the organization can see the seats of events that are not published
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+    let mut path = Path::<EventTicketPathParameters>::extract(&test_request.request).unwrap();
+    path.event_id = other_event.id;
+    path.ticket_type_id = other_ticket_type.id;
+    let response: HttpResponse =
+        ticket_types::seats((database.connection.clone().into(), path, auth_user)).into();
+    support::expects_unauthorized(&response);
+}
+
+#[test]
+fn assign_seats_for_other_event() {
+    let database = TestDatabase::new();
+    let organization = database.create_organization().finish();
+    let event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let other_event = database
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let conn = database.connection.get();
+    let other_ticket_type = &other_event.ticket_types(conn).unwrap()[0];
+    let user = database.create_user().finish();
+    let auth_user =
+        support::create_auth_user_from_user(&user, Roles::OrgOwner, Some(&organization), &database);
+
+    let test_request =
+        TestRequest::create_with_uri_custom_params("/", vec!["event_id", "ticket_type_id"]);
+    let mut path = Path::<EventTicketPathParameters>::extract(&test_request.request).unwrap();
+    path.event_id = event.id;
+    path.ticket_type_id = other_ticket_type.id;
+    let response: HttpResponse = ticket_types::assign_seats((
+        database.connection.clone().into(),
+        path,
+        Json(AssignSeatsRequest { seat_ids: vec![] }),
+        auth_user,
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
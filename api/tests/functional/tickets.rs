@@ -52,6 +52,7 @@ pub fn index() {
             ticket_type_id: ticket_type.id,
             quantity: 1,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -64,6 +65,7 @@ pub fn index() {
             ticket_type_id: ticket_type2.id,
             quantity: 1,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -113,6 +115,7 @@ pub fn index() {
         status: TicketInstanceStatus::Purchased,
         redeem_key: ticket.redeem_key,
         pending_transfer: false,
+        seat: None,
     };
     assert_eq!(vec![expected_ticket.clone()], found_data.data);
     // Test without specified event
@@ -140,6 +143,7 @@ pub fn index() {
         status: TicketInstanceStatus::Purchased,
         redeem_key: ticket2.redeem_key,
         pending_transfer: false,
+        seat: None,
     };
     assert_eq!(
         vec![
@@ -204,6 +208,7 @@ pub fn show() {
             ticket_type_id: ticket_type.id,
             quantity: 1,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -232,6 +237,7 @@ pub fn show() {
         status: TicketInstanceStatus::Purchased,
         redeem_key: ticket.redeem_key,
         pending_transfer: false,
+        seat: None,
     };
 
     let expected_result = ShowTicketResponse {
@@ -367,6 +373,7 @@ fn ticket_transfer_authorization() {
             ticket_type_id: ticket_type.id,
             quantity: 5,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -447,6 +454,7 @@ fn receive_ticket_transfer() {
             ticket_type_id: ticket_type.id,
             quantity: 5,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -459,6 +459,7 @@ fn current_user() {
         response.scopes
     );
     assert!(response.organization_scopes.is_empty());
+    assert_eq!(0, response.credit_balance_in_cents);
 }
 
 #[test]
@@ -0,0 +1,96 @@
+use actix_web::{http::StatusCode, FromRequest, HttpResponse, Path};
+use bigneon_api::controllers::waitlist_entries::{self, *};
+use bigneon_api::extractors::*;
+use bigneon_api::models::EventTicketPathParameters;
+use bigneon_db::models::*;
+use serde_json::Value;
+use support;
+use support::database::TestDatabase;
+use support::test_request::TestRequest;
+
+fn sell_out(database: &TestDatabase) -> (Event, TicketType) {
+    let connection = database.connection.get();
+    let event = database
+        .create_event()
+        .with_a_specific_number_of_tickets(1)
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = event.ticket_types(connection).unwrap().remove(0);
+    let buyer = database.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&buyer, connection).unwrap();
+    cart.update_quantities(
+        &vec![UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 1,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    (event, ticket_type)
+}
+
+fn path_for(event: &Event, ticket_type: &TicketType) -> Path<EventTicketPathParameters> {
+    let test_request =
+        TestRequest::create_with_uri_custom_params("/", vec!["event_id", "ticket_type_id"]);
+    let mut path = Path::<EventTicketPathParameters>::extract(&test_request.request).unwrap();
+    path.event_id = event.id;
+    path.ticket_type_id = ticket_type.id;
+    path
+}
+
+#[test]
+fn create() {
+    let database = TestDatabase::new();
+    let (event, ticket_type) = sell_out(&database);
+    let user = database.create_user().finish();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let response: HttpResponse = waitlist_entries::create((
+        database.connection.clone(),
+        path_for(&event, &ticket_type),
+        Json(JoinWaitlistRequest { quantity: Some(1) }),
+        auth_user.clone(),
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = support::unwrap_body_to_string(&response).unwrap();
+    let entry: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(entry["status"], "Waiting");
+    assert_eq!(entry["position"], 1);
+
+    let response: HttpResponse = waitlist_entries::show((
+        database.connection.clone(),
+        path_for(&event, &ticket_type),
+        auth_user,
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[test]
+fn destroy() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let (event, ticket_type) = sell_out(&database);
+    let user = database.create_user().finish();
+    let entry = WaitlistEntry::create(ticket_type.id, user.id, 1)
+        .commit(connection)
+        .unwrap();
+    let auth_user = support::create_auth_user_from_user(&user, Roles::User, None, &database);
+
+    let response: HttpResponse = waitlist_entries::destroy((
+        database.connection.clone(),
+        path_for(&event, &ticket_type),
+        auth_user,
+    ))
+    .into();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        WaitlistEntry::find(entry.id, connection).unwrap().status,
+        WaitlistEntryStatus::Cancelled
+    );
+}
@@ -98,6 +98,10 @@ impl TestDatabase {
         StageBuilder::new(self.connection.get())
     }
 
+    pub fn create_seat_section(&self) -> SeatSectionBuilder {
+        SeatSectionBuilder::new(self.connection.get())
+    }
+
     pub fn create_fee_schedule(&self) -> FeeScheduleBuilder {
         FeeScheduleBuilder::new(self.connection.get())
     }
@@ -114,6 +118,7 @@ impl TestDatabase {
                 ticket_type_id,
                 quantity,
                 redemption_code: None,
+                seat_ids: None,
             }],
             false,
             false,
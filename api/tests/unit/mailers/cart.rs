@@ -0,0 +1,216 @@
+use bigneon_api::communications::mailers;
+use bigneon_api::config::{Config, Environment};
+use bigneon_api::utils::communication::{CommAttachment, Communication};
+use bigneon_db::prelude::*;
+use serde_json;
+use support::database::TestDatabase;
+
+#[test]
+fn purchase_completed() {
+    let config = Config::new(Environment::Test);
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let venue = database.create_venue().finish();
+    let event = database
+        .create_event()
+        .with_venue(&venue)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let order = database
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let mut ticket_instance_ids: Vec<_> = TicketInstance::find_for_user(user.id, connection)
+        .unwrap()
+        .iter()
+        .map(|t| t.id)
+        .collect();
+    ticket_instance_ids.sort();
+
+    // Tickets transferred away before the email is sent are not attached
+    let transferred_ticket_instance_id = ticket_instance_ids.remove(0);
+    let receiver = database.create_user().finish();
+    let sender_wallet = Wallet::find_default_for_user(user.id, connection).unwrap();
+    let receiver_wallet = Wallet::find_default_for_user(receiver.id, connection).unwrap();
+    let transfer_auth = TicketInstance::authorize_ticket_transfer(
+        user.id,
+        vec![transferred_ticket_instance_id],
+        3600,
+        connection,
+    )
+    .unwrap();
+    TicketInstance::receive_ticket_transfer(
+        transfer_auth,
+        &sender_wallet,
+        &receiver_wallet.id,
+        connection,
+    )
+    .unwrap();
+
+    mailers::cart::purchase_completed(
+        user.id,
+        &"Buyer".to_string(),
+        "buyer@example.com".to_string(),
+        order.for_display(connection).unwrap(),
+        &config,
+        connection,
+    )
+    .unwrap();
+
+    // The ticket PDF is queued by reference and rendered when the email is sent
+    let domain_actions =
+        DomainAction::find_pending(Some(DomainActionTypes::Communication), connection).unwrap();
+    assert_eq!(domain_actions.len(), 1);
+    let communication: Communication =
+        serde_json::from_value(domain_actions[0].payload.clone()).unwrap();
+    assert_eq!(communication.attachments.len(), 1);
+    let attachment = &communication.attachments[0];
+    match attachment {
+        CommAttachment::TicketPdf {
+            ticket_instance_ids: attached_ticket_instance_ids,
+            holder_user_id,
+            ..
+        } => {
+            let mut attached_ticket_instance_ids = attached_ticket_instance_ids.clone();
+            attached_ticket_instance_ids.sort();
+            assert_eq!(attached_ticket_instance_ids, ticket_instance_ids);
+            assert_eq!(*holder_user_id, user.id);
+        }
+    }
+    assert_eq!(attachment.filename(), "tickets.pdf".to_string());
+    assert!(attachment.render(connection).unwrap().starts_with(b"%PDF"));
+}
+
+#[test]
+fn purchase_completed_with_taxes() {
+    let config = Config::new(Environment::Test);
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let venue = database.create_venue().finish();
+    let event = database
+        .create_event()
+        .with_venue(&venue)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    TaxRule::create_for_venue(venue.id, "HST".to_string(), 1300, false, true, true)
+        .commit(connection)
+        .unwrap();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(Some("test".to_string()), user.id, total, connection)
+        .unwrap();
+    let display_order = cart.for_display(connection).unwrap();
+    assert!(display_order.tax_in_cents > 0);
+    let total_fees: i64 = display_order
+        .items
+        .iter()
+        .filter(|i| {
+            i.item_type == OrderItemTypes::PerUnitFees || i.item_type == OrderItemTypes::EventFees
+        })
+        .map(|i| i.quantity * i.unit_price_in_cents)
+        .sum();
+    let total_tax = display_order.tax_in_cents;
+
+    mailers::cart::purchase_completed(
+        user.id,
+        &"Buyer".to_string(),
+        "buyer@example.com".to_string(),
+        display_order,
+        &config,
+        connection,
+    )
+    .unwrap();
+
+    // Taxes are listed separately from fees
+    let domain_actions =
+        DomainAction::find_pending(Some(DomainActionTypes::Communication), connection).unwrap();
+    assert_eq!(domain_actions.len(), 1);
+    let communication: Communication =
+        serde_json::from_value(domain_actions[0].payload.clone()).unwrap();
+    let template_data = &communication.template_data.unwrap()[0];
+    assert_eq!(
+        template_data["total_fees"],
+        format!("{:.*}", 2, total_fees as f64 / 100.0)
+    );
+    assert_eq!(
+        template_data["total_tax"],
+        format!("{:.*}", 2, total_tax as f64 / 100.0)
+    );
+    assert!(template_data["item_breakdown"].contains("Tax - HST"));
+}
+
+#[test]
+fn share_purchase_completed() {
+    let config = Config::new(Environment::Test);
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let event = database
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let order = database
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let mut tickets = TicketInstance::find_for_user(user.id, connection).unwrap();
+    assert_eq!(tickets.len(), 2);
+    let ticket = tickets.remove(0);
+    let ticket_instance_id = ticket.id;
+
+    mailers::cart::share_purchase_completed(
+        user.id,
+        &"Payer".to_string(),
+        "payer@example.com".to_string(),
+        &order.for_display(connection).unwrap(),
+        &[ticket],
+        1000,
+        &config,
+        connection,
+    )
+    .unwrap();
+
+    // Only the payer's tickets are listed and attached
+    let domain_actions =
+        DomainAction::find_pending(Some(DomainActionTypes::Communication), connection).unwrap();
+    assert_eq!(domain_actions.len(), 1);
+    let communication: Communication =
+        serde_json::from_value(domain_actions[0].payload.clone()).unwrap();
+    assert_eq!(
+        communication.destinations.get(),
+        vec!["payer@example.com".to_string()]
+    );
+    let template_data = &communication.template_data.clone().unwrap()[0];
+    assert_eq!(template_data["ticket_count"], "1".to_string());
+    assert_eq!(template_data["total_price"], "10.00".to_string());
+    match &communication.attachments[0] {
+        CommAttachment::TicketPdf {
+            ticket_instance_ids,
+            ..
+        } => assert_eq!(ticket_instance_ids, &vec![ticket_instance_id]),
+    }
+}
@@ -0,0 +1,51 @@
+use bigneon_api::communications::mailers;
+use bigneon_api::config::{Config, Environment};
+use bigneon_api::utils::communication::Communication;
+use bigneon_db::prelude::*;
+use serde_json;
+use support::database::TestDatabase;
+
+#[test]
+fn hold_expired() {
+    let config = Config::new(Environment::Test);
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let event = database
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let hold = Hold::create_hold(
+        "Hold".to_string(),
+        event.id,
+        "EXPIRED".to_string(),
+        None,
+        None,
+        None,
+        None,
+        HoldTypes::Comp,
+        ticket_type.id,
+        Some(user.id),
+    )
+    .commit(connection)
+    .unwrap();
+
+    mailers::holds::hold_expired(&config, &hold, &event, ticket_type, 2, connection).unwrap();
+
+    // The user who created the hold is told it has ended
+    let domain_actions =
+        DomainAction::find_pending(Some(DomainActionTypes::Communication), connection).unwrap();
+    assert_eq!(domain_actions.len(), 1);
+    let communication: Communication =
+        serde_json::from_value(domain_actions[0].payload.clone()).unwrap();
+    assert_eq!(communication.destinations.get(), vec![user.email.unwrap()]);
+
+    // Holds without a recorded creator are not announced
+    let hold = database.create_hold().finish();
+    mailers::holds::hold_expired(&config, &hold, &event, ticket_type, 2, connection).unwrap();
+    let domain_actions =
+        DomainAction::find_pending(Some(DomainActionTypes::Communication), connection).unwrap();
+    assert_eq!(domain_actions.len(), 1);
+}
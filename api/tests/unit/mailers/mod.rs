@@ -1 +1,3 @@
+pub mod cart;
+pub mod holds;
 pub mod user;
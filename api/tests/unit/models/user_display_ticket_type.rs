@@ -78,6 +78,7 @@ fn from_ticket_type() {
                 ticket_type_id: ticket_type.id,
                 quantity: 100,
                 redemption_code: None,
+                seat_ids: None,
             }],
             false,
             false,
@@ -96,6 +97,7 @@ fn from_ticket_type() {
                 ticket_type_id: ticket_type.id,
                 quantity: 90,
                 redemption_code: None,
+                seat_ids: None,
             }],
             false,
             false,
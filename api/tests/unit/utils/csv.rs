@@ -0,0 +1,27 @@
+use bigneon_api::utils::csv;
+
+#[test]
+fn write() {
+    let rows = vec![
+        vec!["ABCDEF".to_string(), "1".to_string()],
+        vec!["Comma, \"quoted\"".to_string(), "".to_string()],
+    ];
+    assert_eq!(
+        csv::write(&["redemption_code", "uses"], &rows),
+        "redemption_code,uses\r\nABCDEF,1\r\n\"Comma, \"\"quoted\"\"\",\r\n"
+    );
+}
+
+#[test]
+fn parse() {
+    let content = "\u{feff}name,email,quantity\r\nJane Doe,jane@tari.com,2\n\n\"Doe, \"\"John\"\"\",,\"1\"\nLast,line,3";
+    assert_eq!(
+        csv::parse(content),
+        vec![
+            vec!["name", "email", "quantity"],
+            vec!["Jane Doe", "jane@tari.com", "2"],
+            vec!["Doe, \"John\"", "", "1"],
+            vec!["Last", "line", "3"],
+        ]
+    );
+}
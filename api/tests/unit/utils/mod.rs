@@ -0,0 +1,3 @@
+pub mod csv;
+pub mod ticket_pdfs;
+pub mod wallet_passes;
@@ -0,0 +1,38 @@
+use bigneon_api::utils::ticket_pdfs;
+use bigneon_db::prelude::*;
+use support::database::TestDatabase;
+
+#[test]
+fn render() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let user = database.create_user().finish();
+    let venue = database.create_venue().finish();
+    let event = database
+        .create_event()
+        .with_venue(&venue)
+        .with_ticket_pricing()
+        .finish();
+    database
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let ticket_instance_ids: Vec<_> = TicketInstance::find_for_user(user.id, connection)
+        .unwrap()
+        .iter()
+        .map(|t| t.id)
+        .collect();
+    assert_eq!(ticket_instance_ids.len(), 2);
+
+    let pdf = ticket_pdfs::render("Tickets", &ticket_instance_ids, user.id, connection).unwrap();
+    assert!(pdf.starts_with(b"%PDF"));
+
+    // Tickets are still rendered for someone who does not hold them, without their QR codes
+    let other_user = database.create_user().finish();
+    let other_pdf =
+        ticket_pdfs::render("Tickets", &ticket_instance_ids, other_user.id, connection).unwrap();
+    assert!(other_pdf.starts_with(b"%PDF"));
+}
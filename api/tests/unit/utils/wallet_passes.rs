@@ -0,0 +1,83 @@
+use bigneon_api::config::{Config, Environment};
+use bigneon_api::utils::wallet_passes::{apple, google, WalletPassDetails};
+use bigneon_db::prelude::*;
+use support::database::TestDatabase;
+use uuid::Uuid;
+
+#[test]
+fn authentication_token() {
+    let mut config = Config::new(Environment::Test);
+    config.token_secret = "test_secret".into();
+    let ticket_instance_id = Uuid::new_v4();
+    let wallet_id = Uuid::new_v4();
+
+    let token = apple::authentication_token(&config, ticket_instance_id, wallet_id).unwrap();
+    assert_eq!(
+        apple::verify_authentication_token(&config, ticket_instance_id, &token),
+        Some(wallet_id)
+    );
+    assert_eq!(
+        apple::verify_authentication_token(&config, Uuid::new_v4(), &token),
+        None
+    );
+
+    // Tokens cannot be moved to another wallet
+    let forged = token.replace(
+        &wallet_id.simple().to_string(),
+        &Uuid::new_v4().simple().to_string(),
+    );
+    assert_eq!(
+        apple::verify_authentication_token(&config, ticket_instance_id, &forged),
+        None
+    );
+    assert_eq!(
+        apple::verify_authentication_token(&config, ticket_instance_id, "invalid"),
+        None
+    );
+}
+
+#[test]
+fn event_ticket_object() {
+    let database = TestDatabase::new();
+    let connection = database.connection.get();
+    let mut config = Config::new(Environment::Test);
+    config.google_wallet_issuer_id = Some("3388000000000000000".to_string());
+    let user = database.create_user().finish();
+    let venue = database.create_venue().finish();
+    let event = database
+        .create_event()
+        .with_venue(&venue)
+        .with_ticket_pricing()
+        .finish();
+    database
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .is_paid()
+        .finish();
+    let ticket = &TicketInstance::find_for_user(user.id, connection).unwrap()[0];
+
+    let details = WalletPassDetails::load(ticket.id, connection).unwrap();
+    assert_eq!(details.wallet_id, ticket.wallet_id);
+    assert!(!details.voided);
+
+    let object = google::event_ticket_object(&config, &details);
+    assert_eq!(
+        object["id"],
+        json!(format!(
+            "3388000000000000000.{}-{}",
+            ticket.id.simple(),
+            ticket.wallet_id.simple()
+        ))
+    );
+    assert_eq!(
+        object["classId"],
+        json!(format!("3388000000000000000.{}", event.id.simple()))
+    );
+    assert_eq!(object["state"], json!("active"));
+
+    let object = google::event_ticket_object(&config, &details.for_previous_holder());
+    assert_eq!(object["state"], json!("inactive"));
+    assert!(object.get("ticketHolderName").is_none());
+    assert!(object.get("barcode").is_none());
+}
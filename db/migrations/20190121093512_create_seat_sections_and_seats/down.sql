@@ -0,0 +1,10 @@
+DROP INDEX IF EXISTS index_ticket_instances_seat_id;
+ALTER TABLE ticket_instances
+    DROP COLUMN seat_id;
+
+DROP INDEX IF EXISTS index_seats_seat_section_id_row_name_seat_number;
+DROP TABLE IF EXISTS seats;
+
+DROP INDEX IF EXISTS index_seat_sections_stage_id;
+DROP INDEX IF EXISTS index_seat_sections_venue_id_stage_id_name;
+DROP TABLE IF EXISTS seat_sections;
@@ -0,0 +1,37 @@
+CREATE TABLE seat_sections
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    venue_id UUID NOT NULL REFERENCES venues(id) ON DELETE CASCADE,
+    stage_id UUID NULL REFERENCES stages(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE TABLE seats
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    seat_section_id UUID NOT NULL REFERENCES seat_sections(id) ON DELETE CASCADE,
+    row_name TEXT NOT NULL,
+    seat_number TEXT NOT NULL,
+    is_accessible BOOLEAN NOT NULL DEFAULT 'F',
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+ALTER TABLE ticket_instances
+    ADD seat_id UUID NULL REFERENCES seats(id);
+
+-- Indices
+CREATE UNIQUE INDEX index_seat_sections_venue_id_stage_id_name ON seat_sections (
+    venue_id,
+    stage_id,
+    name
+);
+CREATE INDEX index_seat_sections_stage_id ON seat_sections (stage_id);
+CREATE UNIQUE INDEX index_seats_seat_section_id_row_name_seat_number ON seats (
+    seat_section_id,
+    row_name,
+    seat_number
+);
+CREATE INDEX index_ticket_instances_seat_id ON ticket_instances (seat_id);
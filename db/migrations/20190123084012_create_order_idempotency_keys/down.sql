@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS index_order_idempotency_keys_user_id_operation_idempotency_key;
+DROP INDEX IF EXISTS index_order_idempotency_keys_order_id;
+DROP TABLE IF EXISTS order_idempotency_keys;
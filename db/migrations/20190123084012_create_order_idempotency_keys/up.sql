@@ -0,0 +1,21 @@
+CREATE TABLE order_idempotency_keys
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    order_id UUID NOT NULL REFERENCES orders(id),
+    user_id UUID NOT NULL REFERENCES users(id),
+    operation TEXT NOT NULL,
+    idempotency_key TEXT NOT NULL,
+    request_hash TEXT NOT NULL,
+    response_status INTEGER NULL,
+    response_body TEXT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+-- Indices
+CREATE INDEX index_order_idempotency_keys_order_id ON order_idempotency_keys (order_id);
+CREATE UNIQUE INDEX index_order_idempotency_keys_user_id_operation_idempotency_key ON order_idempotency_keys (
+    user_id,
+    operation,
+    idempotency_key
+);
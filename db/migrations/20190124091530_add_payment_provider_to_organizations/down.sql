@@ -0,0 +1,2 @@
+ALTER TABLE organizations DROP COLUMN merchant_account_id;
+ALTER TABLE organizations DROP COLUMN payment_provider;
@@ -0,0 +1,2 @@
+ALTER TABLE organizations ADD payment_provider TEXT NOT NULL DEFAULT 'stripe';
+ALTER TABLE organizations ADD merchant_account_id TEXT NULL;
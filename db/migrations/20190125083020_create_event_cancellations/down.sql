@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS index_event_cancellations_cancelled_by_user_id;
+DROP INDEX IF EXISTS index_event_cancellations_ticket_type_id;
+DROP INDEX IF EXISTS index_event_cancellations_event_id;
+DROP TABLE IF EXISTS event_cancellations;
@@ -0,0 +1,14 @@
+CREATE TABLE event_cancellations
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    event_id UUID NOT NULL REFERENCES events(id),
+    ticket_type_id UUID NULL REFERENCES ticket_types(id),
+    cancelled_by_user_id UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+-- Indices
+CREATE INDEX index_event_cancellations_event_id ON event_cancellations (event_id);
+CREATE INDEX index_event_cancellations_ticket_type_id ON event_cancellations (ticket_type_id);
+CREATE INDEX index_event_cancellations_cancelled_by_user_id ON event_cancellations (cancelled_by_user_id);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS index_event_reschedules_rescheduled_by_user_id;
+DROP INDEX IF EXISTS index_event_reschedules_event_id;
+DROP TABLE IF EXISTS event_reschedules;
+ALTER TABLE events DROP COLUMN reschedule_refund_window_days;
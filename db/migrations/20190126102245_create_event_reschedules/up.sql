@@ -0,0 +1,17 @@
+ALTER TABLE events ADD reschedule_refund_window_days INTEGER NOT NULL DEFAULT 14;
+
+CREATE TABLE event_reschedules
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    event_id UUID NOT NULL REFERENCES events(id),
+    previous_event_start TIMESTAMP NULL,
+    new_event_start TIMESTAMP NULL,
+    refund_deadline TIMESTAMP NOT NULL,
+    rescheduled_by_user_id UUID NULL REFERENCES users(id),
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+-- Indices
+CREATE INDEX index_event_reschedules_event_id ON event_reschedules (event_id);
+CREATE INDEX index_event_reschedules_rescheduled_by_user_id ON event_reschedules (rescheduled_by_user_id);
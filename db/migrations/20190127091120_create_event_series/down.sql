@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS index_events_event_series_id;
+DROP INDEX IF EXISTS index_event_series_organization_id;
+ALTER TABLE events DROP COLUMN event_series_id;
+DROP TABLE IF EXISTS event_series;
@@ -0,0 +1,17 @@
+CREATE TABLE event_series
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    organization_id UUID NOT NULL REFERENCES organizations(id),
+    name TEXT NOT NULL,
+    frequency TEXT NOT NULL,
+    interval INTEGER NOT NULL DEFAULT 1,
+    occurrence_count INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+ALTER TABLE events ADD event_series_id UUID NULL REFERENCES event_series(id);
+
+-- Indices
+CREATE INDEX index_event_series_organization_id ON event_series (organization_id);
+CREATE INDEX index_events_event_series_id ON events (event_series_id);
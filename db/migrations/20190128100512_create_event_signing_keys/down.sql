@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS index_event_signing_keys_event_id;
+DROP TABLE IF EXISTS event_signing_keys;
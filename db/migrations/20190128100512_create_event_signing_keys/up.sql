@@ -0,0 +1,12 @@
+CREATE TABLE event_signing_keys
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    event_id UUID NOT NULL REFERENCES events(id),
+    secret_key TEXT NOT NULL,
+    public_key TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+-- Indices
+CREATE UNIQUE INDEX index_event_signing_keys_event_id ON event_signing_keys (event_id);
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS index_ticket_scans_scanned_by_user_id;
+DROP INDEX IF EXISTS index_ticket_scans_ticket_instance_id;
+DROP INDEX IF EXISTS index_ticket_scans_event_id;
+DROP TABLE IF EXISTS ticket_scans;
+ALTER TABLE ticket_types DROP COLUMN allow_reentry;
@@ -0,0 +1,21 @@
+ALTER TABLE ticket_types ADD allow_reentry BOOLEAN NOT NULL DEFAULT 'f';
+
+CREATE TABLE ticket_scans
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    event_id UUID NOT NULL REFERENCES events(id),
+    ticket_instance_id UUID NULL REFERENCES ticket_instances(id),
+    scanned_by_user_id UUID NULL REFERENCES users(id),
+    device_id TEXT NULL,
+    gate_name TEXT NULL,
+    direction TEXT NOT NULL,
+    result TEXT NOT NULL,
+    scanned_at TIMESTAMP NOT NULL DEFAULT now(),
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+-- Indices
+CREATE INDEX index_ticket_scans_event_id ON ticket_scans (event_id);
+CREATE INDEX index_ticket_scans_ticket_instance_id ON ticket_scans (ticket_instance_id);
+CREATE INDEX index_ticket_scans_scanned_by_user_id ON ticket_scans (scanned_by_user_id);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS index_wallet_pass_registrations_ticket_instance_id;
+DROP INDEX IF EXISTS index_wallet_pass_registrations_device_library_identifier_ticket_instance_id;
+DROP TABLE IF EXISTS wallet_pass_registrations;
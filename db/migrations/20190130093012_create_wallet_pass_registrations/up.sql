@@ -0,0 +1,13 @@
+CREATE TABLE wallet_pass_registrations
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    ticket_instance_id UUID NOT NULL REFERENCES ticket_instances(id),
+    device_library_identifier TEXT NOT NULL,
+    push_token TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+-- Indices
+CREATE UNIQUE INDEX index_wallet_pass_registrations_device_library_identifier_ticket_instance_id ON wallet_pass_registrations (device_library_identifier, ticket_instance_id);
+CREATE INDEX index_wallet_pass_registrations_ticket_instance_id ON wallet_pass_registrations (ticket_instance_id);
@@ -0,0 +1,15 @@
+ALTER TABLE holds
+    DROP COLUMN discount_as_percentage;
+
+ALTER TABLE codes
+    DROP CONSTRAINT codes_buy_quantity_and_get_quantity,
+    DROP CONSTRAINT codes_discount_present;
+
+ALTER TABLE codes
+    DROP COLUMN minimum_order_total_in_cents,
+    DROP COLUMN get_quantity,
+    DROP COLUMN buy_quantity,
+    DROP COLUMN discount_as_percentage;
+
+ALTER TABLE codes
+    ADD CONSTRAINT codes_discount_in_cents_check CHECK (code_type <> 'Discount' OR discount_in_cents > 0);
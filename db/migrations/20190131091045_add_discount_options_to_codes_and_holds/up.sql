@@ -0,0 +1,15 @@
+ALTER TABLE codes
+    DROP CONSTRAINT codes_discount_in_cents_check;
+
+ALTER TABLE codes
+    ADD discount_as_percentage BIGINT NULL CHECK (discount_as_percentage > 0 AND discount_as_percentage <= 100),
+    ADD buy_quantity BIGINT NULL CHECK (buy_quantity > 0),
+    ADD get_quantity BIGINT NULL CHECK (get_quantity > 0),
+    ADD minimum_order_total_in_cents BIGINT NULL CHECK (minimum_order_total_in_cents > 0);
+
+ALTER TABLE codes
+    ADD CONSTRAINT codes_discount_present CHECK (code_type <> 'Discount' OR discount_in_cents > 0 OR discount_as_percentage > 0),
+    ADD CONSTRAINT codes_buy_quantity_and_get_quantity CHECK ((buy_quantity IS NULL) = (get_quantity IS NULL));
+
+ALTER TABLE holds
+    ADD discount_as_percentage BIGINT NULL CHECK (discount_as_percentage > 0 AND discount_as_percentage <= 100);
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS index_codes_code_campaign_id;
+DROP INDEX IF EXISTS index_code_campaigns_event_id;
+
+ALTER TABLE codes DROP COLUMN code_campaign_id;
+
+DROP TABLE IF EXISTS code_campaigns;
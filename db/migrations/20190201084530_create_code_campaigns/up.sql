@@ -0,0 +1,14 @@
+CREATE TABLE code_campaigns
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    event_id UUID NOT NULL REFERENCES events(id),
+    name TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+ALTER TABLE codes ADD code_campaign_id UUID NULL REFERENCES code_campaigns(id);
+
+-- Indices
+CREATE INDEX index_code_campaigns_event_id ON code_campaigns (event_id);
+CREATE INDEX index_codes_code_campaign_id ON codes (code_campaign_id);
@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS index_holds_claimed_by_user_id;
+DROP INDEX IF EXISTS index_holds_claim_key;
+
+ALTER TABLE holds DROP COLUMN claimed_by_user_id;
+ALTER TABLE holds DROP COLUMN claimed_at;
+ALTER TABLE holds DROP COLUMN claim_sent_at;
+ALTER TABLE holds DROP COLUMN claim_key;
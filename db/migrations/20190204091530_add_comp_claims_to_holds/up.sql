@@ -0,0 +1,8 @@
+ALTER TABLE holds ADD claim_key UUID NULL;
+ALTER TABLE holds ADD claim_sent_at TIMESTAMP NULL;
+ALTER TABLE holds ADD claimed_at TIMESTAMP NULL;
+ALTER TABLE holds ADD claimed_by_user_id UUID NULL REFERENCES users(id);
+
+-- Indices
+CREATE UNIQUE INDEX index_holds_claim_key ON holds (claim_key);
+CREATE INDEX index_holds_claimed_by_user_id ON holds (claimed_by_user_id);
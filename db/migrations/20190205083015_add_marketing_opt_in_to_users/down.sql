@@ -0,0 +1 @@
+ALTER TABLE users DROP COLUMN marketing_opt_in;
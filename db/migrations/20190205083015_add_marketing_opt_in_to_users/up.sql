@@ -0,0 +1 @@
+ALTER TABLE users ADD marketing_opt_in BOOLEAN NOT NULL DEFAULT false;
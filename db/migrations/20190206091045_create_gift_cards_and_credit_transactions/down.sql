@@ -0,0 +1,9 @@
+DROP INDEX IF EXISTS index_credit_transactions_gift_card_id;
+DROP INDEX IF EXISTS index_credit_transactions_order_id;
+DROP INDEX IF EXISTS index_credit_transactions_user_id;
+DROP INDEX IF EXISTS index_gift_cards_redeemed_by_user_id;
+DROP INDEX IF EXISTS index_gift_cards_order_item_id;
+DROP INDEX IF EXISTS index_gift_cards_redemption_code;
+
+DROP TABLE IF EXISTS credit_transactions;
+DROP TABLE IF EXISTS gift_cards;
@@ -0,0 +1,31 @@
+CREATE TABLE gift_cards
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    order_item_id UUID NOT NULL REFERENCES order_items(id),
+    redemption_code TEXT NOT NULL,
+    value_in_cents BIGINT NOT NULL CHECK (value_in_cents > 0),
+    redeemed_by_user_id UUID NULL REFERENCES users(id),
+    redeemed_at TIMESTAMP NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE TABLE credit_transactions
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id),
+    transaction_type TEXT NOT NULL,
+    amount_in_cents BIGINT NOT NULL,
+    order_id UUID NULL REFERENCES orders(id),
+    gift_card_id UUID NULL REFERENCES gift_cards(id),
+    created_by UUID NULL REFERENCES users(id),
+    created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+-- Indices
+CREATE UNIQUE INDEX index_gift_cards_redemption_code ON gift_cards (redemption_code);
+CREATE INDEX index_gift_cards_order_item_id ON gift_cards (order_item_id);
+CREATE INDEX index_gift_cards_redeemed_by_user_id ON gift_cards (redeemed_by_user_id);
+CREATE INDEX index_credit_transactions_user_id ON credit_transactions (user_id);
+CREATE INDEX index_credit_transactions_order_id ON credit_transactions (order_id);
+CREATE INDEX index_credit_transactions_gift_card_id ON credit_transactions (gift_card_id);
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS index_order_shares_paid_by_user_id;
+DROP INDEX IF EXISTS index_order_shares_order_id;
+DROP INDEX IF EXISTS index_order_shares_share_key;
+
+DROP TABLE IF EXISTS order_shares;
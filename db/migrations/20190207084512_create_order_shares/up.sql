@@ -0,0 +1,18 @@
+CREATE TABLE order_shares
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    order_id UUID NOT NULL REFERENCES orders(id),
+    share_key UUID NOT NULL,
+    ticket_quantity BIGINT NOT NULL CHECK (ticket_quantity > 0),
+    amount_in_cents BIGINT NOT NULL,
+    paid_by_user_id UUID NULL REFERENCES users(id),
+    payment_id UUID NULL REFERENCES payments(id),
+    paid_at TIMESTAMP NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+-- Indices
+CREATE UNIQUE INDEX index_order_shares_share_key ON order_shares (share_key);
+CREATE INDEX index_order_shares_order_id ON order_shares (order_id);
+CREATE INDEX index_order_shares_paid_by_user_id ON order_shares (paid_by_user_id);
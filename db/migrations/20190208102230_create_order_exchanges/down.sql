@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS index_order_exchanges_to_order_item_id;
+DROP INDEX IF EXISTS index_order_exchanges_from_order_item_id;
+DROP INDEX IF EXISTS index_order_exchanges_order_id;
+
+DROP TABLE IF EXISTS order_exchanges;
@@ -0,0 +1,17 @@
+CREATE TABLE order_exchanges
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    order_id UUID NOT NULL REFERENCES orders(id),
+    from_order_item_id UUID NOT NULL REFERENCES order_items(id),
+    to_order_item_id UUID NOT NULL REFERENCES order_items(id),
+    quantity BIGINT NOT NULL CHECK (quantity > 0),
+    difference_in_cents BIGINT NOT NULL,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+-- Indices
+CREATE INDEX index_order_exchanges_order_id ON order_exchanges (order_id);
+CREATE INDEX index_order_exchanges_from_order_item_id ON order_exchanges (from_order_item_id);
+CREATE UNIQUE INDEX index_order_exchanges_to_order_item_id ON order_exchanges (to_order_item_id);
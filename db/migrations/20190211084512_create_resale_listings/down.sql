@@ -0,0 +1,8 @@
+DROP INDEX IF EXISTS index_resale_listings_order_item_id;
+DROP INDEX IF EXISTS index_resale_listings_seller_id;
+DROP INDEX IF EXISTS index_resale_listings_ticket_instance_id_active;
+DROP INDEX IF EXISTS index_resale_listings_ticket_instance_id;
+
+DROP TABLE IF EXISTS resale_listings;
+
+ALTER TABLE organizations DROP COLUMN resale_price_cap_percent;
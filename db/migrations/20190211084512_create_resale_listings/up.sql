@@ -0,0 +1,21 @@
+ALTER TABLE organizations ADD resale_price_cap_percent INTEGER NULL CHECK (resale_price_cap_percent >= 0);
+
+CREATE TABLE resale_listings
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    ticket_instance_id UUID NOT NULL REFERENCES ticket_instances(id),
+    seller_id UUID NOT NULL REFERENCES users(id),
+    price_in_cents BIGINT NOT NULL CHECK (price_in_cents > 0),
+    status TEXT NOT NULL,
+    order_item_id UUID NULL REFERENCES order_items(id) ON DELETE SET NULL,
+    seller_payout_in_cents BIGINT NULL,
+    sold_at TIMESTAMP NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+-- Indices
+CREATE INDEX index_resale_listings_ticket_instance_id ON resale_listings (ticket_instance_id);
+CREATE UNIQUE INDEX index_resale_listings_ticket_instance_id_active ON resale_listings (ticket_instance_id) WHERE status = 'Active';
+CREATE INDEX index_resale_listings_seller_id ON resale_listings (seller_id);
+CREATE INDEX index_resale_listings_order_item_id ON resale_listings (order_item_id);
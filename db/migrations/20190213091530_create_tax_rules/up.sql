@@ -0,0 +1,22 @@
+CREATE TABLE tax_rules
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    region_id UUID NULL REFERENCES regions(id),
+    venue_id UUID NULL REFERENCES venues(id),
+    name TEXT NOT NULL,
+    rate_in_basis_points INTEGER NOT NULL CHECK (rate_in_basis_points >= 0 AND rate_in_basis_points <= 10000),
+    inclusive BOOLEAN NOT NULL DEFAULT 'F',
+    applies_to_tickets BOOLEAN NOT NULL DEFAULT 'T',
+    applies_to_fees BOOLEAN NOT NULL DEFAULT 'F',
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now(),
+    CHECK ((region_id IS NULL) <> (venue_id IS NULL))
+);
+
+ALTER TABLE order_items ADD tax_rule_id UUID NULL REFERENCES tax_rules(id) ON DELETE SET NULL;
+ALTER TABLE order_items ADD tax_in_cents BIGINT NOT NULL DEFAULT 0;
+
+-- Indices
+CREATE INDEX index_tax_rules_region_id ON tax_rules (region_id);
+CREATE INDEX index_tax_rules_venue_id ON tax_rules (venue_id);
+CREATE INDEX index_order_items_tax_rule_id ON order_items (tax_rule_id);
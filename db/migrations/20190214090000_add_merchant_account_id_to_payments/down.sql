@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS index_payment_methods_user_id_name_merchant_account_id;
+CREATE UNIQUE INDEX index_payment_methods_user_id_name ON payment_methods (user_id, name);
+
+ALTER TABLE payment_methods DROP COLUMN merchant_account_id;
+ALTER TABLE payments DROP COLUMN merchant_account_id;
@@ -0,0 +1,6 @@
+ALTER TABLE payments ADD merchant_account_id TEXT NULL;
+ALTER TABLE payment_methods ADD merchant_account_id TEXT NULL;
+
+-- Repeat charge tokens are only valid for the merchant account they were created on
+DROP INDEX index_payment_methods_user_id_name;
+CREATE UNIQUE INDEX index_payment_methods_user_id_name_merchant_account_id ON payment_methods (user_id, name, COALESCE(merchant_account_id, ''));
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS index_holds_created_by_user_id;
+
+ALTER TABLE holds DROP COLUMN created_by_user_id;
@@ -0,0 +1,5 @@
+-- Whoever created the hold is told when it ends
+ALTER TABLE holds ADD created_by_user_id UUID NULL REFERENCES users(id);
+
+-- Indices
+CREATE INDEX index_holds_created_by_user_id ON holds (created_by_user_id);
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS index_resale_payouts_organization_id;
+DROP INDEX IF EXISTS index_resale_payouts_seller_id;
+DROP INDEX IF EXISTS index_resale_payouts_resale_listing_id;
+
+DROP TABLE IF EXISTS resale_payouts;
@@ -0,0 +1,18 @@
+-- What an organization owes a fan for a ticket they resold, until it is paid out
+CREATE TABLE resale_payouts
+(
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid() NOT NULL,
+    resale_listing_id UUID NOT NULL REFERENCES resale_listings(id),
+    seller_id UUID NOT NULL REFERENCES users(id),
+    organization_id UUID NOT NULL REFERENCES organizations(id),
+    amount_in_cents BIGINT NOT NULL CHECK (amount_in_cents > 0),
+    paid_at TIMESTAMP NULL,
+    paid_by_user_id UUID NULL REFERENCES users(id),
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+-- Indices
+CREATE UNIQUE INDEX index_resale_payouts_resale_listing_id ON resale_payouts (resale_listing_id);
+CREATE INDEX index_resale_payouts_seller_id ON resale_payouts (seller_id);
+CREATE INDEX index_resale_payouts_organization_id ON resale_payouts (organization_id);
@@ -13,7 +13,7 @@ use uuid::Uuid;
 #[table_name = "assets"]
 pub struct Asset {
     pub id: Uuid,
-    ticket_type_id: Uuid,
+    pub ticket_type_id: Uuid,
     blockchain_name: String,
     // TODO: This will be populated after it is created on the blockchain.
     pub blockchain_asset_id: Option<String>,
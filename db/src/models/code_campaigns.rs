@@ -0,0 +1,197 @@
+use chrono::prelude::*;
+use diesel;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Text, Timestamp, Uuid as dUuid};
+use models::*;
+use schema::{code_campaigns, codes};
+use utils::errors::*;
+use uuid::Uuid;
+use validators::{self, *};
+
+/// Upper bound on the number of codes generated for a campaign in a single batch
+pub const MAX_CAMPAIGN_CODES: u32 = 5000;
+const CAMPAIGN_REDEMPTION_CODE_LENGTH: usize = 10;
+
+#[derive(Associations, Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+#[belongs_to(Event)]
+#[table_name = "code_campaigns"]
+pub struct CodeCampaign {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Clone, Deserialize, Insertable, Serialize)]
+#[table_name = "code_campaigns"]
+pub struct NewCodeCampaign {
+    pub event_id: Uuid,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct DisplayCodeCampaign {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub codes: Vec<CodeUsage>,
+}
+
+/// Usage of a single redemption code across paid orders
+#[derive(Clone, Debug, Deserialize, PartialEq, QueryableByName, Serialize)]
+pub struct CodeUsage {
+    #[sql_type = "dUuid"]
+    pub code_id: Uuid,
+    #[sql_type = "Text"]
+    pub redemption_code: String,
+    #[sql_type = "BigInt"]
+    pub max_uses: i64,
+    #[sql_type = "BigInt"]
+    pub uses: i64,
+    #[sql_type = "BigInt"]
+    pub tickets_sold: i64,
+    #[sql_type = "Nullable<Timestamp>"]
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+impl NewCodeCampaign {
+    pub fn commit(&self, conn: &PgConnection) -> Result<CodeCampaign, DatabaseError> {
+        diesel::insert_into(code_campaigns::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create code campaign")
+    }
+}
+
+impl CodeCampaign {
+    pub fn create(event_id: Uuid, name: String) -> NewCodeCampaign {
+        NewCodeCampaign { event_id, name }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<CodeCampaign, DatabaseError> {
+        code_campaigns::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not retrieve code campaign")
+    }
+
+    pub fn find_for_event(
+        event_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<CodeCampaign>, DatabaseError> {
+        code_campaigns::table
+            .filter(code_campaigns::event_id.eq(event_id))
+            .order_by(code_campaigns::name)
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not retrieve code campaigns for event",
+            )
+    }
+
+    pub fn organization(&self, conn: &PgConnection) -> Result<Organization, DatabaseError> {
+        Organization::find_for_event(self.event_id, conn)
+    }
+
+    pub fn codes(&self, conn: &PgConnection) -> Result<Vec<Code>, DatabaseError> {
+        codes::table
+            .filter(codes::code_campaign_id.eq(self.id))
+            .order_by(codes::redemption_code)
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not retrieve codes for code campaign",
+            )
+    }
+
+    /// Generates `quantity` single use codes for the campaign. Each code copies the type, discount
+    /// and dates of `template` and is valid for `ticket_type_ids`, but gets its own randomly
+    /// generated redemption code.
+    pub fn generate_codes(
+        &self,
+        quantity: u32,
+        template: NewCode,
+        ticket_type_ids: &[Uuid],
+        conn: &PgConnection,
+    ) -> Result<Vec<Code>, DatabaseError> {
+        let validation_errors = validators::append_validation_error(
+            Ok(()),
+            "quantity",
+            validators::validate_greater_than(
+                quantity,
+                1,
+                "quantity_less_than_one",
+                "At least one code must be generated",
+            ),
+        );
+        let validation_errors = validators::append_validation_error(
+            validation_errors,
+            "quantity",
+            validators::validate_greater_than(
+                MAX_CAMPAIGN_CODES,
+                quantity,
+                "quantity_too_large",
+                "Too many codes requested for a single batch",
+            ),
+        );
+        validation_errors?;
+
+        let mut codes = Vec::with_capacity(quantity as usize);
+        for _ in 0..quantity {
+            let code = NewCode {
+                name: self.name.clone(),
+                event_id: self.event_id,
+                redemption_code: unique_redemption_code(CAMPAIGN_REDEMPTION_CODE_LENGTH, conn)?,
+                max_uses: 1,
+                code_campaign_id: Some(self.id),
+                ..template.clone()
+            }
+            .commit(conn)?;
+            code.update_ticket_types(ticket_type_ids.to_vec(), conn)?;
+            codes.push(code);
+        }
+
+        Ok(codes)
+    }
+
+    pub fn code_usage(&self, conn: &PgConnection) -> Result<Vec<CodeUsage>, DatabaseError> {
+        let query = r#"
+                SELECT
+                    c.id AS code_id,
+                    c.redemption_code,
+                    c.max_uses,
+                    COUNT(DISTINCT o.id) AS uses,
+                    CAST(COALESCE(SUM(oi.quantity - oi.refunded_quantity), 0) AS BIGINT) AS tickets_sold,
+                    MAX(o.paid_at) AS last_used_at
+                FROM codes c
+                LEFT JOIN (
+                    order_items oi
+                    JOIN orders o ON o.id = oi.order_id AND o.status = 'Paid'
+                ) ON oi.code_id = c.id AND oi.item_type = 'Tickets'
+                WHERE c.code_campaign_id = $1
+                GROUP BY c.id, c.redemption_code, c.max_uses
+                ORDER BY c.redemption_code;"#;
+
+        diesel::sql_query(query)
+            .bind::<dUuid, _>(self.id)
+            .get_results(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not retrieve usage for code campaign",
+            )
+    }
+
+    pub fn for_display(&self, conn: &PgConnection) -> Result<DisplayCodeCampaign, DatabaseError> {
+        Ok(DisplayCodeCampaign {
+            id: self.id,
+            event_id: self.event_id,
+            name: self.name.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            codes: self.code_usage(conn)?,
+        })
+    }
+}
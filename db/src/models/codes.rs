@@ -6,6 +6,8 @@ use diesel::sql_types::{Array, BigInt, Nullable, Text, Timestamp, Uuid as dUuid}
 use models::*;
 use schema::codes;
 use std::borrow::Cow;
+use std::cmp;
+use utils::discount_for_price;
 use utils::errors::*;
 use uuid::Uuid;
 use validator::*;
@@ -25,6 +27,11 @@ pub struct Code {
     pub max_tickets_per_user: Option<i64>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub discount_as_percentage: Option<i64>,
+    pub buy_quantity: Option<i64>,
+    pub get_quantity: Option<i64>,
+    pub minimum_order_total_in_cents: Option<i64>,
+    pub code_campaign_id: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Queryable, Serialize, QueryableByName)]
@@ -43,12 +50,22 @@ pub struct DisplayCode {
     pub max_uses: i64,
     #[sql_type = "Nullable<BigInt>"]
     pub discount_in_cents: Option<i64>,
+    #[sql_type = "Nullable<BigInt>"]
+    pub discount_as_percentage: Option<i64>,
+    #[sql_type = "Nullable<BigInt>"]
+    pub buy_quantity: Option<i64>,
+    #[sql_type = "Nullable<BigInt>"]
+    pub get_quantity: Option<i64>,
+    #[sql_type = "Nullable<BigInt>"]
+    pub minimum_order_total_in_cents: Option<i64>,
     #[sql_type = "Timestamp"]
     pub start_date: NaiveDateTime,
     #[sql_type = "Timestamp"]
     pub end_date: NaiveDateTime,
     #[sql_type = "Nullable<BigInt>"]
     pub max_tickets_per_user: Option<i64>,
+    #[sql_type = "Nullable<dUuid>"]
+    pub code_campaign_id: Option<Uuid>,
     #[sql_type = "Timestamp"]
     pub created_at: NaiveDateTime,
     #[sql_type = "Timestamp"]
@@ -68,6 +85,10 @@ pub struct UpdateCodeAttributes {
     pub redemption_code: Option<String>,
     pub max_uses: Option<i64>,
     pub discount_in_cents: Option<Option<i64>>,
+    pub discount_as_percentage: Option<Option<i64>>,
+    pub buy_quantity: Option<Option<i64>>,
+    pub get_quantity: Option<Option<i64>>,
+    pub minimum_order_total_in_cents: Option<Option<i64>>,
     pub start_date: Option<NaiveDateTime>,
     pub end_date: Option<NaiveDateTime>,
     pub max_tickets_per_user: Option<Option<i64>>,
@@ -87,6 +108,31 @@ impl Code {
             )
     }
 
+    /// Confirms the code can currently be used when purchasing the ticket type
+    pub fn confirm_valid_for_ticket_type(
+        &self,
+        ticket_type_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        let now = Utc::now().naive_utc();
+        if now < self.start_date || now > self.end_date {
+            return DatabaseError::validation_error(
+                "redemption_code",
+                "Redemption code is not currently active",
+            );
+        }
+        if !TicketType::find_for_code(self.id, conn)?
+            .iter()
+            .any(|tt| tt.id == ticket_type_id)
+        {
+            return DatabaseError::validation_error(
+                "redemption_code",
+                "Redemption code is not valid for this ticket type",
+            );
+        }
+        Ok(())
+    }
+
     pub fn update_ticket_types(
         &self,
         ticket_type_ids: Vec<Uuid>,
@@ -127,9 +173,14 @@ impl Code {
             redemption_code: self.redemption_code.clone(),
             max_uses: self.max_uses,
             discount_in_cents: self.discount_in_cents,
+            discount_as_percentage: self.discount_as_percentage,
+            buy_quantity: self.buy_quantity,
+            get_quantity: self.get_quantity,
+            minimum_order_total_in_cents: self.minimum_order_total_in_cents,
             start_date: self.start_date,
             end_date: self.end_date,
             max_tickets_per_user: self.max_tickets_per_user,
+            code_campaign_id: self.code_campaign_id,
             created_at: self.created_at,
             updated_at: self.updated_at,
             ticket_type_ids: ticket_type_ids,
@@ -143,6 +194,10 @@ impl Code {
         redemption_code: String,
         max_uses: u32,
         discount_in_cents: Option<u32>,
+        discount_as_percentage: Option<u32>,
+        buy_quantity: Option<u32>,
+        get_quantity: Option<u32>,
+        minimum_order_total_in_cents: Option<u32>,
         start_date: NaiveDateTime,
         end_date: NaiveDateTime,
         max_tickets_per_user: Option<u32>,
@@ -154,9 +209,14 @@ impl Code {
             redemption_code,
             max_uses: max_uses as i64,
             discount_in_cents: discount_in_cents.map(|max| max as i64),
+            discount_as_percentage: discount_as_percentage.map(|p| p as i64),
+            buy_quantity: buy_quantity.map(|q| q as i64),
+            get_quantity: get_quantity.map(|q| q as i64),
+            minimum_order_total_in_cents: minimum_order_total_in_cents.map(|m| m as i64),
             start_date,
             end_date,
             max_tickets_per_user: max_tickets_per_user.map(|max| max as i64),
+            code_campaign_id: None,
         }
     }
 
@@ -187,9 +247,14 @@ impl Code {
                     codes.redemption_code,
                     codes.max_uses,
                     codes.discount_in_cents,
+                    codes.discount_as_percentage,
+                    codes.buy_quantity,
+                    codes.get_quantity,
+                    codes.minimum_order_total_in_cents,
                     codes.start_date,
                     codes.end_date,
                     codes.max_tickets_per_user,
+                    codes.code_campaign_id,
                     codes.created_at,
                     codes.updated_at,
                     array(select ticket_type_id from ticket_type_codes where ticket_type_codes.code_id = codes.id) as ticket_type_ids
@@ -211,8 +276,12 @@ impl Code {
     pub fn discount_present_for_discount_type(
         code_type: CodeTypes,
         discount_in_cents: Option<i64>,
+        discount_as_percentage: Option<i64>,
     ) -> Result<(), ValidationError> {
-        if code_type == CodeTypes::Discount && discount_in_cents.is_none() {
+        if code_type == CodeTypes::Discount
+            && discount_in_cents.is_none()
+            && discount_as_percentage.is_none()
+        {
             let mut validation_error =
                 create_validation_error("required", "Discount required for Discount code type");
             validation_error.add_param(Cow::from("code_type"), &code_type);
@@ -222,6 +291,80 @@ impl Code {
         Ok(())
     }
 
+    /// Buy X get Y codes discount `get_quantity` of every `buy_quantity + get_quantity` tickets
+    /// so both quantities are required together
+    pub fn buy_quantity_and_get_quantity_valid(
+        buy_quantity: Option<i64>,
+        get_quantity: Option<i64>,
+    ) -> Result<(), ValidationError> {
+        if buy_quantity.is_some() != get_quantity.is_some()
+            || buy_quantity.map_or(false, |q| q < 1)
+            || get_quantity.map_or(false, |q| q < 1)
+        {
+            let mut validation_error = create_validation_error(
+                "buy_quantity_and_get_quantity_invalid",
+                "Buy quantity and get quantity must both be at least 1",
+            );
+            validation_error.add_param(Cow::from("buy_quantity"), &buy_quantity);
+            validation_error.add_param(Cow::from("get_quantity"), &get_quantity);
+            return Err(validation_error);
+        }
+        Ok(())
+    }
+
+    /// Discount for each of the order's ticket items that were added with this code, as
+    /// `(order_item_id, quantity, discount_in_cents)` where the discount is per ticket. Buy X get
+    /// Y codes only discount the cheapest tickets and order level codes only apply once the
+    /// tickets in the whole order reach the minimum order total, whether or not they were added
+    /// with the code. Fees, taxes and other discounts do not count towards the minimum.
+    pub fn discounts_for_items(&self, items: &[OrderItem]) -> Vec<(Uuid, i64, i64)> {
+        if self.code_type != CodeTypes::Discount {
+            return Vec::new();
+        }
+
+        let order_subtotal: i64 = items
+            .iter()
+            .filter(|i| i.item_type == OrderItemTypes::Tickets)
+            .map(|i| i.unit_price_in_cents * i.quantity)
+            .sum();
+        if self
+            .minimum_order_total_in_cents
+            .map_or(false, |minimum| order_subtotal < minimum)
+        {
+            return Vec::new();
+        }
+
+        let mut items: Vec<&OrderItem> = items
+            .iter()
+            .filter(|i| i.code_id == Some(self.id) && i.item_type == OrderItemTypes::Tickets)
+            .collect();
+
+        let total_quantity: i64 = items.iter().map(|i| i.quantity).sum();
+        let mut discounted_quantity = match (self.buy_quantity, self.get_quantity) {
+            (Some(buy_quantity), Some(get_quantity)) => {
+                total_quantity / (buy_quantity + get_quantity) * get_quantity
+            }
+            _ => total_quantity,
+        };
+
+        items.sort_by_key(|i| i.unit_price_in_cents);
+        let mut discounts = Vec::new();
+        for item in items {
+            let quantity = cmp::min(item.quantity, discounted_quantity);
+            let discount_in_cents = discount_for_price(
+                item.unit_price_in_cents,
+                self.discount_in_cents,
+                self.discount_as_percentage,
+            );
+            if quantity > 0 && discount_in_cents > 0 {
+                discounts.push((item.id, quantity, discount_in_cents));
+            }
+            discounted_quantity -= quantity;
+        }
+
+        discounts
+    }
+
     fn validate_record(
         &self,
         update_attrs: &UpdateCodeAttributes,
@@ -245,6 +388,29 @@ impl Code {
                 update_attrs
                     .discount_in_cents
                     .unwrap_or(self.discount_in_cents),
+                update_attrs
+                    .discount_as_percentage
+                    .unwrap_or(self.discount_as_percentage),
+            ),
+        );
+        validation_errors = validators::append_validation_error(
+            validation_errors,
+            "discount_as_percentage",
+            discount_valid(
+                update_attrs
+                    .discount_in_cents
+                    .unwrap_or(self.discount_in_cents),
+                update_attrs
+                    .discount_as_percentage
+                    .unwrap_or(self.discount_as_percentage),
+            ),
+        );
+        validation_errors = validators::append_validation_error(
+            validation_errors,
+            "buy_quantity",
+            Code::buy_quantity_and_get_quantity_valid(
+                update_attrs.buy_quantity.unwrap_or(self.buy_quantity),
+                update_attrs.get_quantity.unwrap_or(self.get_quantity),
             ),
         );
         validation_errors = validators::append_validation_error(
@@ -296,7 +462,7 @@ impl Code {
     }
 }
 
-#[derive(Deserialize, Insertable, Serialize, Validate)]
+#[derive(Clone, Deserialize, Insertable, Serialize, Validate)]
 #[table_name = "codes"]
 pub struct NewCode {
     pub name: String,
@@ -309,9 +475,14 @@ pub struct NewCode {
     pub redemption_code: String,
     pub max_uses: i64,
     pub discount_in_cents: Option<i64>,
+    pub discount_as_percentage: Option<i64>,
+    pub buy_quantity: Option<i64>,
+    pub get_quantity: Option<i64>,
+    pub minimum_order_total_in_cents: Option<i64>,
     pub start_date: NaiveDateTime,
     pub end_date: NaiveDateTime,
     pub max_tickets_per_user: Option<i64>,
+    pub code_campaign_id: Option<Uuid>,
 }
 
 impl NewCode {
@@ -333,8 +504,19 @@ impl NewCode {
             Code::discount_present_for_discount_type(
                 self.code_type.clone(),
                 self.discount_in_cents,
+                self.discount_as_percentage,
             ),
         );
+        validation_errors = validators::append_validation_error(
+            validation_errors,
+            "discount_as_percentage",
+            discount_valid(self.discount_in_cents, self.discount_as_percentage),
+        );
+        validation_errors = validators::append_validation_error(
+            validation_errors,
+            "buy_quantity",
+            Code::buy_quantity_and_get_quantity_valid(self.buy_quantity, self.get_quantity),
+        );
         validation_errors = validators::append_validation_error(
             validation_errors,
             "start_date",
@@ -0,0 +1,117 @@
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable};
+use models::*;
+use schema::{credit_transactions, users};
+use utils::errors::*;
+use uuid::Uuid;
+
+/// An entry in a user's account credit ledger. Credits (gift card redemptions and refunds) are
+/// positive amounts and payments made with credit are negative, so a user's balance is the sum
+/// of their transactions.
+#[derive(Clone, Debug, PartialEq, Identifiable, Queryable, Serialize, Deserialize)]
+pub struct CreditTransaction {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub transaction_type: CreditTransactionTypes,
+    pub amount_in_cents: i64,
+    pub order_id: Option<Uuid>,
+    pub gift_card_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+}
+
+impl CreditTransaction {
+    pub fn create(
+        user_id: Uuid,
+        transaction_type: CreditTransactionTypes,
+        amount_in_cents: i64,
+        order_id: Option<Uuid>,
+        gift_card_id: Option<Uuid>,
+        created_by: Option<Uuid>,
+    ) -> NewCreditTransaction {
+        NewCreditTransaction {
+            user_id,
+            transaction_type,
+            amount_in_cents,
+            order_id,
+            gift_card_id,
+            created_by,
+        }
+    }
+
+    pub fn find_for_user(
+        user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<CreditTransaction>, DatabaseError> {
+        credit_transactions::table
+            .filter(credit_transactions::user_id.eq(user_id))
+            .order_by(credit_transactions::created_at.desc())
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load credit transactions for user",
+            )
+    }
+
+    pub fn balance_for_user(user_id: Uuid, conn: &PgConnection) -> Result<i64, DatabaseError> {
+        #[derive(QueryableByName)]
+        struct ResultForSum {
+            #[sql_type = "Nullable<BigInt>"]
+            s: Option<i64>,
+        };
+        let query = diesel::sql_query(
+            "SELECT CAST(SUM(amount_in_cents) as BigInt) as s FROM credit_transactions WHERE user_id = $1;",
+        )
+        .bind::<diesel::sql_types::Uuid, _>(user_id);
+
+        let sum: ResultForSum = query
+            .get_result(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load credit balance")?;
+        Ok(sum.s.unwrap_or(0))
+    }
+}
+
+#[derive(Insertable, Clone)]
+#[table_name = "credit_transactions"]
+pub struct NewCreditTransaction {
+    pub user_id: Uuid,
+    pub transaction_type: CreditTransactionTypes,
+    pub amount_in_cents: i64,
+    pub order_id: Option<Uuid>,
+    pub gift_card_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+}
+
+impl NewCreditTransaction {
+    pub fn commit(self, conn: &PgConnection) -> Result<CreditTransaction, DatabaseError> {
+        if self.amount_in_cents == 0 {
+            return DatabaseError::business_process_error(
+                "Credit transaction amount must not be zero",
+            );
+        }
+
+        if self.amount_in_cents < 0 {
+            // Lock the user so concurrent debits cannot spend the same balance twice
+            users::table
+                .filter(users::id.eq(self.user_id))
+                .select(users::id)
+                .for_update()
+                .first::<Uuid>(conn)
+                .to_db_error(ErrorCode::QueryError, "Could not lock user for credit")?;
+
+            if CreditTransaction::balance_for_user(self.user_id, conn)? + self.amount_in_cents < 0 {
+                return DatabaseError::business_process_error("Insufficient account credit");
+            }
+        }
+
+        diesel::insert_into(credit_transactions::table)
+            .values(&self)
+            .get_result(conn)
+            .to_db_error(
+                ErrorCode::InsertError,
+                "Could not create credit transaction",
+            )
+    }
+}
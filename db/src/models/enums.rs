@@ -76,12 +76,19 @@ macro_rules! string_enum {
 string_enum! { AssetStatus [Unsynced] }
 string_enum! { CodeTypes [Access, Discount] }
 string_enum! { CommunicationChannelType [Email, Sms, Push]}
+string_enum! { CreditTransactionTypes [GiftCard, Payment, Refund] }
 string_enum! { DomainEventTypes [
+    EventCancelled,
+    EventRescheduled,
     FeeScheduleCreated,
+    GiftCardRedeemed,
+    HoldExpired,
     OrderBehalfOfUserChanged,
     OrganizationCreated,
     PaymentCreated,
     PaymentCompleted,
+    PaymentDisputed,
+    PaymentFailed,
     PaymentRefund,
     PaymentMethodCreated,
     PaymentMethodUpdated,
@@ -89,14 +96,34 @@ string_enum! { DomainEventTypes [
     LostPassword,
     PurchaseCompleted,
     TransferTicketStarted,
-    TransferTicketCompleted
+    TransferTicketCompleted,
+    WaitlistOfferCreated,
+    WaitlistOfferExpired
 ]}
 string_enum! { DomainActionTypes [
     // Email/SMS/Push Communication
     Communication,
     // Marketing Contacts
     MarketingContactsCreateEventList,
-    MarketingContactsBulkEventFanListImport
+    MarketingContactsBulkEventFanListImport,
+    // Release of unclaimed held tickets once a hold ends
+    ExpireHold,
+    // Refunds for split payment orders that were not fully paid in time
+    ExpireOrderShares,
+    // Ticket holder notifications for a rescheduled event
+    NotifyEventReschedule,
+    // Refunds for orders affected by an event or ticket type cancellation
+    RefundCancelledOrder,
+    // Waitlist offers for sold out ticket types
+    ProcessWaitlist,
+    // Account credit returned for carts partially paid with it that were not checked out
+    ReturnCartCredit,
+    // Reminders for carts that expired without being checked out
+    SendCartReminder,
+    // Blockchain transfers of resold tickets from the seller's wallet to the buyer's
+    TransferResoldTicket,
+    // Wallet pass updates for rescheduled events, reached redeem dates and transferred tickets
+    UpdateWalletPasses
 ]}
 string_enum! { DomainActionStatus [Pending, RetriesExceeded, Errored, Success, Cancelled]}
 string_enum! { EventStatus [Draft,Closed,Published,Offline]}
@@ -105,18 +132,25 @@ string_enum! { EventOverrideStatus [PurchaseTickets,SoldOut,OnSaleSoon,TicketsAt
 string_enum! { FanSortField [FirstName, LastName, Email, Phone, Orders, FirstOrder, LastOrder, Revenue] }
 string_enum! { HistoryType [Purchase]}
 string_enum! { HoldTypes [Discount, Comp] }
+string_enum! { IdempotencyKeyOperations [Checkout, Refund] }
+string_enum! { OfflineScanResults [Redeemed, AlreadyRedeemed, Duplicate, Expired, Revoked, Invalid] }
 string_enum! { OrderStatus [Cancelled, Draft, Paid, PartiallyPaid] }
-string_enum! { OrderItemTypes [Tickets, PerUnitFees, EventFees]}
+string_enum! { OrderItemTypes [Tickets, PerUnitFees, EventFees, Discount, GiftCard, Resale, Tax]}
 string_enum! { OrderTypes [Cart, BackOffice] }
-string_enum! { PaymentMethods [External, CreditCard] }
-string_enum! { PaymentStatus [Authorized, Completed, Refunded] }
+string_enum! { PaymentMethods [External, CreditCard, Credit] }
+string_enum! { PaymentStatus [Authorized, Completed, Disputed, Failed, Refunded] }
 string_enum! { PastOrUpcoming [Past,Upcoming]}
+string_enum! { RecurrenceFrequency [Daily, Weekly, Monthly] }
+string_enum! { ResaleListingStatus [Active, Sold, Cancelled] }
 string_enum! { Roles [Admin, OrgMember, OrgOwner, OrgAdmin, OrgBoxOffice, DoorPerson, User] }
 string_enum! { SortingDir[ Asc, Desc ] }
-string_enum! { Tables [Events, FeeSchedules, Orders, Organizations, Payments, PaymentMethods, TicketInstances] }
+string_enum! { Tables [EventCancellations, EventReschedules, Events, FeeSchedules, GiftCards, Holds, Orders, Organizations, Payments, PaymentMethods, TicketInstances, TicketTypes, WaitlistEntries] }
 string_enum! { TicketInstanceStatus [Available, Reserved, Purchased, Redeemed, Nullified]}
+string_enum! { TicketScanDirections [In, Out] }
+string_enum! { TicketScanResults [Redeemed, ReEntered, ScannedOut, AlreadyRedeemed, Invalid] }
 string_enum! { TicketPricingStatus [Published, Deleted, Default] }
 string_enum! { TicketTypeStatus [NoActivePricing, Published, SoldOut, Cancelled] }
+string_enum! { WaitlistEntryStatus [Waiting, Offered, Redeemed, Expired, Cancelled] }
 
 impl Default for EventStatus {
     fn default() -> EventStatus {
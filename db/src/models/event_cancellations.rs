@@ -0,0 +1,291 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use diesel;
+use diesel::dsl;
+use diesel::prelude::*;
+use diesel::sql_types::{Nullable, Uuid as dUuid};
+use models::*;
+use schema::{
+    assets, domain_actions, event_cancellations, order_items, orders, refunded_tickets,
+    ticket_instances, ticket_types,
+};
+use utils::errors::*;
+use uuid::Uuid;
+
+/// Refunds that have not completed within this time are left for manual follow up
+pub const CANCELLATION_REFUND_EXPIRY_DAYS: i64 = 7;
+
+#[derive(Associations, Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+#[belongs_to(Event)]
+#[table_name = "event_cancellations"]
+pub struct EventCancellation {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub ticket_type_id: Option<Uuid>,
+    pub cancelled_by_user_id: Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name = "event_cancellations"]
+pub struct NewEventCancellation {
+    pub event_id: Uuid,
+    pub ticket_type_id: Option<Uuid>,
+    pub cancelled_by_user_id: Uuid,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct EventCancellationProgress {
+    pub order_count: u32,
+    pub pending_count: u32,
+    pub refunded_count: u32,
+    pub failed_count: u32,
+    /// Tickets still held by fans that have not been refunded
+    pub unrefunded_ticket_count: u32,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DisplayEventCancellation {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub ticket_type_id: Option<Uuid>,
+    pub cancelled_by_user_id: Uuid,
+    pub created_at: NaiveDateTime,
+    pub progress: EventCancellationProgress,
+}
+
+impl NewEventCancellation {
+    /// Withdraws the remaining inventory and schedules a refund for every paid order holding
+    /// affected tickets.
+    pub fn commit(&self, conn: &PgConnection) -> Result<EventCancellation, DatabaseError> {
+        let cancellation: EventCancellation = diesel::insert_into(event_cancellations::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(
+                ErrorCode::InsertError,
+                "Could not create event cancellation",
+            )?;
+
+        cancellation.withdraw_inventory(conn)?;
+
+        let now = Utc::now().naive_utc();
+        for order_id in cancellation.affected_order_ids(conn)? {
+            DomainAction::create(
+                None,
+                DomainActionTypes::RefundCancelledOrder,
+                None,
+                json!({ "order_id": order_id }),
+                Tables::EventCancellations.table_name(),
+                cancellation.id,
+                now,
+                now + Duration::days(CANCELLATION_REFUND_EXPIRY_DAYS),
+                3,
+            )
+            .commit(conn)?;
+        }
+
+        DomainEvent::create(
+            DomainEventTypes::EventCancelled,
+            match self.ticket_type_id {
+                Some(_) => "Ticket type was cancelled".to_string(),
+                None => "Event was cancelled".to_string(),
+            },
+            Tables::Events,
+            Some(self.event_id),
+            Some(self.cancelled_by_user_id),
+            Some(json!({
+                "event_cancellation_id": cancellation.id,
+                "ticket_type_id": self.ticket_type_id
+            })),
+        )
+        .commit(conn)?;
+
+        Ok(cancellation)
+    }
+}
+
+impl EventCancellation {
+    pub fn create(
+        event_id: Uuid,
+        ticket_type_id: Option<Uuid>,
+        cancelled_by_user_id: Uuid,
+    ) -> NewEventCancellation {
+        NewEventCancellation {
+            event_id,
+            ticket_type_id,
+            cancelled_by_user_id,
+        }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<EventCancellation, DatabaseError> {
+        event_cancellations::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load event cancellation")
+    }
+
+    pub fn find_for_event(
+        event_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<EventCancellation>, DatabaseError> {
+        event_cancellations::table
+            .filter(event_cancellations::event_id.eq(event_id))
+            .order_by(event_cancellations::created_at.desc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load event cancellations")
+    }
+
+    /// Nullifies every ticket of the cancelled ticket types that is not held by a purchaser,
+    /// including tickets released back to the organization by refunds.
+    pub fn withdraw_inventory(&self, conn: &PgConnection) -> Result<(), DatabaseError> {
+        for ticket_type in self.ticket_types(conn)? {
+            let asset = Asset::find_by_ticket_type(&ticket_type.id, conn)?;
+            TicketInstance::nullify_tickets(asset.id, ticket_type.ticket_count(conn)?, conn)?;
+        }
+        Ok(())
+    }
+
+    fn ticket_types(&self, conn: &PgConnection) -> Result<Vec<TicketType>, DatabaseError> {
+        let mut query = ticket_types::table
+            .filter(ticket_types::event_id.eq(self.event_id))
+            .into_boxed();
+        if let Some(ticket_type_id) = self.ticket_type_id {
+            query = query.filter(ticket_types::id.eq(ticket_type_id));
+        }
+
+        query
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load ticket types")
+    }
+
+    fn affected_order_ids(&self, conn: &PgConnection) -> Result<Vec<Uuid>, DatabaseError> {
+        let mut query = order_items::table
+            .inner_join(orders::table)
+            .filter(orders::status.eq_any(vec![OrderStatus::Paid, OrderStatus::PartiallyPaid]))
+            .filter(
+                order_items::item_type
+                    .eq_any(vec![OrderItemTypes::Tickets, OrderItemTypes::Resale]),
+            )
+            .filter(order_items::event_id.eq(self.event_id))
+            .filter(order_items::refunded_quantity.lt(order_items::quantity))
+            .select(orders::id)
+            .distinct()
+            .into_boxed();
+        if let Some(ticket_type_id) = self.ticket_type_id {
+            query = query.filter(order_items::ticket_type_id.eq(ticket_type_id));
+        }
+
+        query
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load affected orders")
+    }
+
+    /// Tickets covered by this cancellation that have not been refunded yet and are paid for
+    /// through the order, to be refunded with `Order::refund_for_cancellation`. Fees are refunded
+    /// along with their tickets. Tickets are refunded to whoever paid for them even if they were
+    /// transferred to someone else, tickets resold by the buyer are refunded to the resale buyer.
+    pub fn refund_items(
+        &self,
+        order: &Order,
+        conn: &PgConnection,
+    ) -> Result<Vec<RefundItem>, DatabaseError> {
+        #[derive(QueryableByName)]
+        struct R {
+            #[sql_type = "dUuid"]
+            order_item_id: Uuid,
+            #[sql_type = "dUuid"]
+            ticket_instance_id: Uuid,
+        }
+
+        let query = include_str!("../queries/find_cancelled_tickets_to_refund.sql");
+        let tickets: Vec<R> = diesel::sql_query(query)
+            .bind::<dUuid, _>(order.id)
+            .bind::<dUuid, _>(self.event_id)
+            .bind::<Nullable<dUuid>, _>(self.ticket_type_id)
+            .get_results(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load tickets to refund")?;
+
+        Ok(tickets
+            .into_iter()
+            .map(|t| RefundItem {
+                order_item_id: t.order_item_id,
+                ticket_instance_id: Some(t.ticket_instance_id),
+            })
+            .collect())
+    }
+
+    /// Tickets covered by this cancellation that are still held by fans without having been
+    /// refunded, either because their refund has not run yet or because there is no paid order
+    /// to refund them through
+    fn unrefunded_ticket_count(&self, conn: &PgConnection) -> Result<i64, DatabaseError> {
+        let mut query = ticket_instances::table
+            .inner_join(assets::table.on(ticket_instances::asset_id.eq(assets::id)))
+            .inner_join(ticket_types::table.on(assets::ticket_type_id.eq(ticket_types::id)))
+            .left_join(
+                refunded_tickets::table
+                    .on(refunded_tickets::ticket_instance_id.eq(ticket_instances::id)),
+            )
+            .filter(ticket_types::event_id.eq(self.event_id))
+            .filter(ticket_instances::status.eq_any(vec![
+                TicketInstanceStatus::Purchased,
+                TicketInstanceStatus::Redeemed,
+            ]))
+            .filter(refunded_tickets::ticket_refunded_at.is_null())
+            .select(dsl::count(ticket_instances::id))
+            .into_boxed();
+        if let Some(ticket_type_id) = self.ticket_type_id {
+            query = query.filter(ticket_types::id.eq(ticket_type_id));
+        }
+
+        query
+            .get_result(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not count unrefunded tickets")
+    }
+
+    /// Refunds are tracked by their domain actions, actions that ran out of retries have failed
+    pub fn progress(
+        &self,
+        conn: &PgConnection,
+    ) -> Result<EventCancellationProgress, DatabaseError> {
+        let statuses: Vec<DomainActionStatus> = domain_actions::table
+            .filter(domain_actions::domain_action_type.eq(DomainActionTypes::RefundCancelledOrder))
+            .filter(domain_actions::main_table.eq(Tables::EventCancellations.table_name()))
+            .filter(domain_actions::main_table_id.eq(self.id))
+            .select(domain_actions::status)
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load event cancellation refunds",
+            )?;
+
+        let mut progress = EventCancellationProgress {
+            unrefunded_ticket_count: self.unrefunded_ticket_count(conn)? as u32,
+            ..Default::default()
+        };
+        for status in statuses {
+            progress.order_count += 1;
+            match status {
+                DomainActionStatus::Pending => progress.pending_count += 1,
+                DomainActionStatus::Success => progress.refunded_count += 1,
+                _ => progress.failed_count += 1,
+            }
+        }
+        Ok(progress)
+    }
+
+    pub fn for_display(
+        self,
+        conn: &PgConnection,
+    ) -> Result<DisplayEventCancellation, DatabaseError> {
+        let progress = self.progress(conn)?;
+        Ok(DisplayEventCancellation {
+            id: self.id,
+            event_id: self.event_id,
+            ticket_type_id: self.ticket_type_id,
+            cancelled_by_user_id: self.cancelled_by_user_id,
+            created_at: self.created_at,
+            progress,
+        })
+    }
+}
@@ -0,0 +1,165 @@
+use chrono::prelude::*;
+use diesel;
+use diesel::expression::sql_literal::sql;
+use diesel::prelude::*;
+use diesel::sql_types::Bool;
+use models::*;
+use schema::{
+    assets, event_reschedules, order_items, orders, ticket_instances, ticket_types, users, wallets,
+};
+use utils::errors::*;
+use uuid::Uuid;
+
+#[derive(Associations, Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+#[belongs_to(Event)]
+#[table_name = "event_reschedules"]
+pub struct EventReschedule {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub previous_event_start: Option<NaiveDateTime>,
+    pub new_event_start: Option<NaiveDateTime>,
+    pub refund_deadline: NaiveDateTime,
+    pub rescheduled_by_user_id: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name = "event_reschedules"]
+pub struct NewEventReschedule {
+    pub event_id: Uuid,
+    pub previous_event_start: Option<NaiveDateTime>,
+    pub new_event_start: Option<NaiveDateTime>,
+    pub refund_deadline: NaiveDateTime,
+    pub rescheduled_by_user_id: Option<Uuid>,
+}
+
+impl NewEventReschedule {
+    /// Records the reschedule and schedules the notification of its ticket holders
+    pub fn commit(&self, conn: &PgConnection) -> Result<EventReschedule, DatabaseError> {
+        let reschedule: EventReschedule = diesel::insert_into(event_reschedules::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create event reschedule")?;
+
+        let now = Utc::now().naive_utc();
+        DomainAction::create(
+            None,
+            DomainActionTypes::NotifyEventReschedule,
+            None,
+            json!({}),
+            Tables::EventReschedules.table_name(),
+            reschedule.id,
+            now,
+            reschedule.refund_deadline,
+            3,
+        )
+        .commit(conn)?;
+
+        WalletPassRegistration::schedule_update(Tables::Events, self.event_id, json!({}), conn)?;
+
+        DomainEvent::create(
+            DomainEventTypes::EventRescheduled,
+            "Event was rescheduled".to_string(),
+            Tables::Events,
+            Some(self.event_id),
+            self.rescheduled_by_user_id,
+            Some(json!({
+                "event_reschedule_id": reschedule.id,
+                "previous_event_start": self.previous_event_start,
+                "new_event_start": self.new_event_start,
+                "refund_deadline": self.refund_deadline
+            })),
+        )
+        .commit(conn)?;
+
+        Ok(reschedule)
+    }
+}
+
+impl EventReschedule {
+    pub fn create(
+        event_id: Uuid,
+        previous_event_start: Option<NaiveDateTime>,
+        new_event_start: Option<NaiveDateTime>,
+        refund_deadline: NaiveDateTime,
+        rescheduled_by_user_id: Option<Uuid>,
+    ) -> NewEventReschedule {
+        NewEventReschedule {
+            event_id,
+            previous_event_start,
+            new_event_start,
+            refund_deadline,
+            rescheduled_by_user_id,
+        }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<EventReschedule, DatabaseError> {
+        event_reschedules::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load event reschedule")
+    }
+
+    pub fn find_for_event(
+        event_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<EventReschedule>, DatabaseError> {
+        event_reschedules::table
+            .filter(event_reschedules::event_id.eq(event_id))
+            .order_by(event_reschedules::created_at.desc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load event reschedules")
+    }
+
+    /// Ticket holders may opt out of the new date and request a refund until the deadline
+    pub fn refund_window_open(&self) -> bool {
+        self.refund_deadline > Utc::now().naive_utc()
+    }
+
+    /// The most recent reschedule of the event whose refund window has not closed
+    pub fn find_open_for_event(
+        event_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Option<EventReschedule>, DatabaseError> {
+        event_reschedules::table
+            .filter(event_reschedules::event_id.eq(event_id))
+            .filter(event_reschedules::refund_deadline.gt(Utc::now().naive_utc()))
+            .order_by(event_reschedules::created_at.desc())
+            .first(conn)
+            .optional()
+            .to_db_error(ErrorCode::QueryError, "Could not load event reschedule")
+    }
+
+    /// Users currently holding purchased tickets for the event, along with whether any of those
+    /// tickets were bought on the user's own orders. Tickets the user was transferred or bought
+    /// on resale cannot be refunded from their orders.
+    pub fn ticket_holders(&self, conn: &PgConnection) -> Result<Vec<(User, bool)>, DatabaseError> {
+        let holders: Vec<(User, bool)> = ticket_instances::table
+            .inner_join(assets::table.inner_join(ticket_types::table))
+            .inner_join(wallets::table.inner_join(users::table))
+            .left_join(
+                order_items::table
+                    .on(ticket_instances::order_item_id.eq(order_items::id.nullable())),
+            )
+            .left_join(orders::table.on(order_items::order_id.eq(orders::id)))
+            .filter(ticket_types::event_id.eq(self.event_id))
+            .filter(ticket_instances::status.eq(TicketInstanceStatus::Purchased))
+            .select((
+                users::all_columns,
+                sql::<Bool>(&format!("coalesce(NOT ({}), false)", TRANSFERRED_SQL)),
+            ))
+            .distinct()
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load ticket holders")?;
+
+        let mut ticket_holders: Vec<(User, bool)> = Vec::new();
+        for (user, has_order) in holders {
+            match ticket_holders.iter_mut().find(|(u, _)| u.id == user.id) {
+                Some(holder) => holder.1 |= has_order,
+                None => ticket_holders.push((user, has_order)),
+            }
+        }
+        Ok(ticket_holders)
+    }
+}
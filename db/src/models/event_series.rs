@@ -0,0 +1,338 @@
+use chrono::prelude::*;
+use diesel;
+use diesel::dsl;
+use diesel::prelude::*;
+use models::*;
+use schema::{event_series, events};
+use time::Duration;
+use utils::errors::*;
+use uuid::Uuid;
+use validators;
+
+/// Upper bound on the number of events a single series can generate
+pub const MAX_SERIES_OCCURRENCES: i32 = 104;
+
+#[derive(Associations, Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+#[belongs_to(Organization)]
+#[table_name = "event_series"]
+pub struct EventSeries {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub name: String,
+    pub frequency: RecurrenceFrequency,
+    pub interval: i32,
+    pub occurrence_count: i32,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Clone, Deserialize, Insertable, Serialize)]
+#[table_name = "event_series"]
+pub struct NewEventSeries {
+    pub organization_id: Uuid,
+    pub name: String,
+    pub frequency: RecurrenceFrequency,
+    pub interval: i32,
+    pub occurrence_count: i32,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct DisplayEventSeries {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub name: String,
+    pub frequency: RecurrenceFrequency,
+    pub interval: i32,
+    pub occurrence_count: i32,
+    pub occurrences: Vec<Event>,
+}
+
+impl NewEventSeries {
+    pub fn commit(&self, conn: &PgConnection) -> Result<EventSeries, DatabaseError> {
+        self.validate_record()?;
+        diesel::insert_into(event_series::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create event series")
+    }
+
+    fn validate_record(&self) -> Result<(), DatabaseError> {
+        let validation_errors = validators::append_validation_error(
+            Ok(()),
+            "interval",
+            validators::validate_greater_than(
+                self.interval,
+                1,
+                "interval_less_than_one",
+                "Interval must be at least 1",
+            ),
+        );
+        let validation_errors = validators::append_validation_error(
+            validation_errors,
+            "occurrence_count",
+            validators::validate_greater_than(
+                self.occurrence_count,
+                1,
+                "occurrence_count_less_than_one",
+                "Series must have at least one occurrence",
+            ),
+        );
+        let validation_errors = validators::append_validation_error(
+            validation_errors,
+            "occurrence_count",
+            validators::validate_greater_than(
+                MAX_SERIES_OCCURRENCES,
+                self.occurrence_count,
+                "occurrence_count_too_large",
+                "Series has too many occurrences",
+            ),
+        );
+
+        Ok(validation_errors?)
+    }
+}
+
+impl EventSeries {
+    pub fn create(
+        organization_id: Uuid,
+        name: String,
+        frequency: RecurrenceFrequency,
+        interval: i32,
+        occurrence_count: i32,
+    ) -> NewEventSeries {
+        NewEventSeries {
+            organization_id,
+            name,
+            frequency,
+            interval,
+            occurrence_count,
+        }
+    }
+
+    /// Turns the event into the first occurrence of a new series and generates the remaining
+    /// occurrences from it
+    pub fn create_from_event(
+        event: &Event,
+        frequency: RecurrenceFrequency,
+        interval: i32,
+        occurrence_count: i32,
+        conn: &PgConnection,
+    ) -> Result<EventSeries, DatabaseError> {
+        if event.event_series_id.is_some() {
+            return DatabaseError::business_process_error("Event is already part of a series");
+        }
+        let event_start = match event.event_start {
+            Some(event_start) => event_start,
+            None => {
+                return DatabaseError::business_process_error(
+                    "Event must have a start date to create a series",
+                );
+            }
+        };
+
+        let series = EventSeries::create(
+            event.organization_id,
+            event.name.clone(),
+            frequency,
+            interval,
+            occurrence_count,
+        )
+        .commit(conn)?;
+
+        diesel::update(event)
+            .set((
+                events::event_series_id.eq(series.id),
+                events::updated_at.eq(dsl::now),
+            ))
+            .execute(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not add event to series")?;
+
+        for occurrence in 1..occurrence_count {
+            event.duplicate(
+                Some(series.occurrence_start(event_start, occurrence)),
+                event.status.clone(),
+                Some(series.id),
+                conn,
+            )?;
+        }
+
+        Ok(series)
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<EventSeries, DatabaseError> {
+        event_series::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load event series")
+    }
+
+    pub fn organization(&self, conn: &PgConnection) -> Result<Organization, DatabaseError> {
+        Organization::find(self.organization_id, conn)
+    }
+
+    pub fn occurrences(&self, conn: &PgConnection) -> Result<Vec<Event>, DatabaseError> {
+        events::table
+            .filter(events::event_series_id.eq(self.id))
+            .order_by(events::event_start.asc())
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load event series occurrences",
+            )
+    }
+
+    /// Applies the changes to every occurrence that has not started yet. Dates are specific to
+    /// each occurrence and are left unchanged.
+    pub fn update_future_occurrences(
+        &self,
+        current_user_id: Option<Uuid>,
+        attributes: EventEditableAttributes,
+        conn: &PgConnection,
+    ) -> Result<Vec<Event>, DatabaseError> {
+        let attributes = EventEditableAttributes {
+            event_start: None,
+            door_time: None,
+            publish_date: None,
+            redeem_date: None,
+            cancelled_at: None,
+            event_end: None,
+            sendgrid_list_id: None,
+            ..attributes
+        };
+
+        let now = Utc::now().naive_utc();
+        let mut updated_events = Vec::new();
+        for event in self.occurrences(conn)? {
+            if event.event_start.map(|start| start > now).unwrap_or(false) {
+                updated_events.push(event.update(current_user_id, attributes.clone(), conn)?);
+            }
+        }
+        Ok(updated_events)
+    }
+
+    /// Copies the ticket types and pricing of `event` to every other occurrence that has not
+    /// started yet, shifting their dates to the occurrence. Ticket types are matched by name and
+    /// added to occurrences that do not have them. Capacity is left as it is because changing it
+    /// issues tickets, and ticket types that `event` does not have are kept because cancelling
+    /// them refunds their orders.
+    pub fn update_future_ticket_types(
+        &self,
+        event: &Event,
+        conn: &PgConnection,
+    ) -> Result<Vec<Event>, DatabaseError> {
+        if event.event_series_id != Some(self.id) {
+            return DatabaseError::business_process_error("Event is not part of this series");
+        }
+        let event_start = match event.event_start {
+            Some(event_start) => event_start,
+            None => {
+                return DatabaseError::business_process_error(
+                    "Event must have a start date to copy its ticket types",
+                );
+            }
+        };
+        let ticket_types: Vec<TicketType> = event
+            .ticket_types(conn)?
+            .into_iter()
+            .filter(|t| t.status != TicketTypeStatus::Cancelled)
+            .collect();
+
+        let now = Utc::now().naive_utc();
+        let mut updated_events = Vec::new();
+        for occurrence in self.occurrences(conn)? {
+            let offset = match occurrence.event_start {
+                Some(start) if start > now && occurrence.id != event.id => start - event_start,
+                _ => continue,
+            };
+            let occurrence_ticket_types = occurrence.ticket_types(conn)?;
+            let wallet = occurrence.issuer_wallet(conn)?;
+
+            for ticket_type in &ticket_types {
+                let attributes = TicketTypeEditableAttributes {
+                    description: Some(ticket_type.description.clone()),
+                    start_date: Some(ticket_type.start_date + offset),
+                    end_date: Some(ticket_type.end_date + offset),
+                    increment: Some(ticket_type.increment),
+                    limit_per_person: Some(ticket_type.limit_per_person),
+                    price_in_cents: Some(ticket_type.price_in_cents),
+                    allow_reentry: Some(ticket_type.allow_reentry),
+                    ..Default::default()
+                };
+                let existing_ticket_type = occurrence_ticket_types.iter().find(|t| {
+                    t.name == ticket_type.name && t.status != TicketTypeStatus::Cancelled
+                });
+                let occurrence_ticket_type = match existing_ticket_type {
+                    Some(existing_ticket_type) => existing_ticket_type.update(attributes, conn)?,
+                    None => occurrence
+                        .add_ticket_type(
+                            ticket_type.name.clone(),
+                            ticket_type.description.clone(),
+                            ticket_type.valid_ticket_count(conn)?,
+                            ticket_type.start_date + offset,
+                            ticket_type.end_date + offset,
+                            wallet.id,
+                            Some(ticket_type.increment),
+                            ticket_type.limit_per_person,
+                            ticket_type.price_in_cents,
+                            conn,
+                        )?
+                        .update(attributes, conn)?,
+                };
+
+                // Pricing that has been sold is kept for reporting by `destroy`
+                for ticket_pricing in occurrence_ticket_type.ticket_pricing(conn)? {
+                    ticket_pricing.destroy(conn)?;
+                }
+                for ticket_pricing in ticket_type.ticket_pricing(conn)? {
+                    occurrence_ticket_type.add_ticket_pricing(
+                        ticket_pricing.name,
+                        ticket_pricing.start_date + offset,
+                        ticket_pricing.end_date + offset,
+                        ticket_pricing.price_in_cents,
+                        ticket_pricing.is_box_office_only,
+                        None,
+                        conn,
+                    )?;
+                }
+            }
+
+            updated_events.push(occurrence.update_cache(conn)?);
+        }
+        Ok(updated_events)
+    }
+
+    /// Start of the nth occurrence, the series' first occurrence being the 0th
+    pub fn occurrence_start(&self, first_start: NaiveDateTime, occurrence: i32) -> NaiveDateTime {
+        let steps = self.interval * occurrence;
+        match self.frequency {
+            RecurrenceFrequency::Daily => first_start + Duration::days(steps as i64),
+            RecurrenceFrequency::Weekly => first_start + Duration::weeks(steps as i64),
+            RecurrenceFrequency::Monthly => {
+                let month0 = first_start.month0() as i32 + steps;
+                let year = first_start.year() + month0 / 12;
+                let month = (month0 % 12) as u32 + 1;
+                // Clamp to the last day of shorter months
+                let mut day = first_start.day();
+                loop {
+                    if let Some(date) = NaiveDate::from_ymd_opt(year, month, day) {
+                        return date.and_time(first_start.time());
+                    }
+                    day -= 1;
+                }
+            }
+        }
+    }
+
+    pub fn for_display(self, conn: &PgConnection) -> Result<DisplayEventSeries, DatabaseError> {
+        let occurrences = self.occurrences(conn)?;
+        Ok(DisplayEventSeries {
+            id: self.id,
+            organization_id: self.organization_id,
+            name: self.name,
+            frequency: self.frequency,
+            interval: self.interval,
+            occurrence_count: self.occurrence_count,
+            occurrences,
+        })
+    }
+}
@@ -0,0 +1,346 @@
+use chrono::prelude::*;
+use diesel;
+use diesel::prelude::*;
+use diesel::sql_types::{Text, Timestamp, Uuid as dUuid};
+use models::*;
+use schema::{event_signing_keys, ticket_instances};
+use std::collections::{HashMap, HashSet};
+use tari_client::*;
+use time::Duration;
+use utils::errors::*;
+use uuid::Uuid;
+
+const PAYLOAD_SEPARATOR: &str = ":";
+
+/// Key pair used to sign ticket payloads so that door scanners can verify them offline. The
+/// secret key never leaves the server.
+#[derive(Associations, Clone, Debug, Identifiable, PartialEq, Queryable)]
+#[belongs_to(Event)]
+#[table_name = "event_signing_keys"]
+pub struct EventSigningKey {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    secret_key: String,
+    pub public_key: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name = "event_signing_keys"]
+struct NewEventSigningKey {
+    event_id: Uuid,
+    secret_key: String,
+    public_key: String,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SignedTicketPayload {
+    pub ticket_instance_id: Uuid,
+    pub event_id: Uuid,
+    pub token_id: i32,
+    pub issued_at: i64,
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Deserialize, PartialEq, QueryableByName, Serialize)]
+pub struct RevokedTicket {
+    #[sql_type = "dUuid"]
+    pub ticket_instance_id: Uuid,
+    #[sql_type = "Timestamp"]
+    pub revoked_at: NaiveDateTime,
+}
+
+/// Everything a scanner device needs to verify tickets while offline. Payloads issued before a
+/// ticket's `revoked_at` must be rejected.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct OfflineScanningData {
+    pub event_id: Uuid,
+    pub public_key: String,
+    pub revoked_tickets: Vec<RevokedTicket>,
+    pub generated_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OfflineScan {
+    pub payload: String,
+    pub scanned_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct OfflineScanResult {
+    pub payload: String,
+    pub ticket_instance_id: Option<Uuid>,
+    pub scanned_at: NaiveDateTime,
+    pub result: OfflineScanResults,
+}
+
+impl SignedTicketPayload {
+    fn message(&self) -> String {
+        vec![
+            self.ticket_instance_id.to_string(),
+            self.event_id.to_string(),
+            self.token_id.to_string(),
+            self.issued_at.to_string(),
+            self.expires_at.to_string(),
+        ]
+        .join(PAYLOAD_SEPARATOR)
+    }
+
+    /// Splits an encoded payload into its fields and signature
+    fn parse(data: &str) -> Option<(SignedTicketPayload, String)> {
+        let parts: Vec<&str> = data.trim().split(PAYLOAD_SEPARATOR).collect();
+        if parts.len() != 6 {
+            return None;
+        }
+
+        let payload = SignedTicketPayload {
+            ticket_instance_id: Uuid::parse_str(parts[0]).ok()?,
+            event_id: Uuid::parse_str(parts[1]).ok()?,
+            token_id: parts[2].parse().ok()?,
+            issued_at: parts[3].parse().ok()?,
+            expires_at: parts[4].parse().ok()?,
+        };
+        Some((payload, parts[5].to_string()))
+    }
+}
+
+impl EventSigningKey {
+    pub fn find_or_create_for_event(
+        event_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<EventSigningKey, DatabaseError> {
+        let signing_key = event_signing_keys::table
+            .filter(event_signing_keys::event_id.eq(event_id))
+            .first(conn)
+            .optional()
+            .to_db_error(ErrorCode::QueryError, "Could not load event signing key")?;
+        if let Some(signing_key) = signing_key {
+            return Ok(signing_key);
+        }
+
+        let (secret_key, public_key) = cryptographic_keypair();
+        diesel::insert_into(event_signing_keys::table)
+            .values(NewEventSigningKey {
+                event_id,
+                secret_key: convert_bytes_to_hexstring(&secret_key),
+                public_key: convert_bytes_to_hexstring(&public_key),
+            })
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create event signing key")?;
+
+        event_signing_keys::table
+            .filter(event_signing_keys::event_id.eq(event_id))
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load event signing key")
+    }
+
+    /// Payloads stay valid until the end of the event, or a day after it starts if it has no end
+    pub fn payload_expiry(event: &Event) -> NaiveDateTime {
+        event
+            .event_end
+            .or(event.event_start.map(|start| start + Duration::days(1)))
+            .unwrap_or(Utc::now().naive_utc() + Duration::days(1))
+    }
+
+    /// Encodes the ticket as `ticket_id:event_id:token_id:issued_at:expires_at:signature`
+    pub fn sign_ticket(
+        &self,
+        ticket: &TicketInstance,
+        expires_at: NaiveDateTime,
+    ) -> Result<String, DatabaseError> {
+        let payload = SignedTicketPayload {
+            ticket_instance_id: ticket.id,
+            event_id: self.event_id,
+            token_id: ticket.token_id,
+            issued_at: Utc::now().timestamp(),
+            expires_at: expires_at.timestamp(),
+        };
+        let message = payload.message();
+        let signature = convert_bytes_to_hexstring(&cryptographic_signature(
+            &message,
+            &convert_hexstring_to_bytes(&self.secret_key),
+        )?);
+
+        Ok(vec![message, signature].join(PAYLOAD_SEPARATOR))
+    }
+
+    /// Signed payload for the ticket. Like the redeem key it is only available while the ticket can
+    /// be redeemed and once the event's redeem date has passed, and only to the user holding the
+    /// ticket in one of their wallets.
+    pub fn signed_payload_for_ticket(
+        ticket: &TicketInstance,
+        event: &Event,
+        user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Option<String>, DatabaseError> {
+        let redeemable = ticket.status == TicketInstanceStatus::Purchased
+            && event
+                .redeem_date
+                .map_or(true, |redeem_date| redeem_date <= Utc::now().naive_utc());
+        if !redeemable {
+            return Ok(None);
+        }
+        let held_by_user = Wallet::find_for_user(user_id, conn)?
+            .iter()
+            .any(|w| w.id == ticket.wallet_id);
+        if !held_by_user {
+            return Ok(None);
+        }
+
+        let signing_key = EventSigningKey::find_or_create_for_event(event.id, conn)?;
+        Ok(Some(signing_key.sign_ticket(
+            ticket,
+            EventSigningKey::payload_expiry(event),
+        )?))
+    }
+
+    /// Returns the payload if it was signed with this key
+    pub fn verify(&self, data: &str) -> Option<SignedTicketPayload> {
+        let (payload, signature) = SignedTicketPayload::parse(data)?;
+        if payload.event_id == self.event_id
+            && cryptographic_verify(
+                &convert_hexstring_to_bytes(&signature),
+                &payload.message(),
+                &convert_hexstring_to_bytes(&self.public_key),
+            )
+        {
+            Some(payload)
+        } else {
+            None
+        }
+    }
+
+    /// Tickets that were refunded, exchanged or moved to another wallet along with the time it
+    /// happened
+    pub fn revoked_tickets(
+        &self,
+        conn: &PgConnection,
+    ) -> Result<Vec<RevokedTicket>, DatabaseError> {
+        let query = include_str!("../queries/find_revoked_tickets_for_event.sql");
+        diesel::sql_query(query)
+            .bind::<dUuid, _>(self.event_id)
+            .bind::<Text, _>(Tables::TicketInstances)
+            .bind::<Text, _>(DomainEventTypes::TransferTicketCompleted)
+            .get_results(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load revoked tickets")
+    }
+
+    pub fn offline_scanning_data(
+        &self,
+        conn: &PgConnection,
+    ) -> Result<OfflineScanningData, DatabaseError> {
+        Ok(OfflineScanningData {
+            event_id: self.event_id,
+            public_key: self.public_key.clone(),
+            revoked_tickets: self.revoked_tickets(conn)?,
+            generated_at: Utc::now().naive_utc(),
+        })
+    }
+
+    /// Redeems tickets scanned while the device was offline. Scans are applied in the order they
+    /// happened so the earliest scan of a ticket wins and later ones are reported as duplicates.
+    pub fn redeem_offline_scans(
+        &self,
+        mut scans: Vec<OfflineScan>,
+        scanned_by_user_id: Option<Uuid>,
+        device_id: Option<String>,
+        gate_name: Option<String>,
+        conn: &PgConnection,
+    ) -> Result<Vec<OfflineScanResult>, DatabaseError> {
+        let revoked_tickets: HashMap<Uuid, NaiveDateTime> = self
+            .revoked_tickets(conn)?
+            .into_iter()
+            .map(|r| (r.ticket_instance_id, r.revoked_at))
+            .collect();
+        scans.sort_by_key(|s| s.scanned_at);
+
+        let mut scanned_ticket_ids = HashSet::new();
+        let mut results = Vec::new();
+        for scan in scans {
+            let payload = self.verify(&scan.payload);
+            let result = match &payload {
+                None => OfflineScanResults::Invalid,
+                Some(payload) => {
+                    if scanned_ticket_ids.contains(&payload.ticket_instance_id) {
+                        OfflineScanResults::Duplicate
+                    } else if scan.scanned_at.timestamp() > payload.expires_at {
+                        OfflineScanResults::Expired
+                    } else if revoked_tickets
+                        .get(&payload.ticket_instance_id)
+                        .map_or(false, |revoked_at| {
+                            revoked_at.timestamp() >= payload.issued_at
+                        })
+                    {
+                        OfflineScanResults::Revoked
+                    } else {
+                        let result = EventSigningKey::redeem(payload, conn)?;
+                        if result != OfflineScanResults::Invalid {
+                            scanned_ticket_ids.insert(payload.ticket_instance_id);
+                        }
+                        result
+                    }
+                }
+            };
+
+            let ticket_instance_id = payload.map(|p| p.ticket_instance_id);
+            let scan_result = match result {
+                OfflineScanResults::Redeemed => TicketScanResults::Redeemed,
+                OfflineScanResults::AlreadyRedeemed | OfflineScanResults::Duplicate => {
+                    TicketScanResults::AlreadyRedeemed
+                }
+                _ => TicketScanResults::Invalid,
+            };
+            TicketScan::create(
+                self.event_id,
+                ticket_instance_id,
+                scanned_by_user_id,
+                device_id.clone(),
+                gate_name.clone(),
+                TicketScanDirections::In,
+                scan_result,
+                scan.scanned_at,
+            )
+            .commit(conn)?;
+
+            results.push(OfflineScanResult {
+                payload: scan.payload,
+                ticket_instance_id,
+                scanned_at: scan.scanned_at,
+                result,
+            });
+        }
+
+        Ok(results)
+    }
+
+    fn redeem(
+        payload: &SignedTicketPayload,
+        conn: &PgConnection,
+    ) -> Result<OfflineScanResults, DatabaseError> {
+        let updated = diesel::update(
+            ticket_instances::table
+                .filter(ticket_instances::id.eq(payload.ticket_instance_id))
+                .filter(ticket_instances::token_id.eq(payload.token_id))
+                .filter(ticket_instances::status.eq(TicketInstanceStatus::Purchased)),
+        )
+        .set((
+            ticket_instances::status.eq(TicketInstanceStatus::Redeemed),
+            ticket_instances::updated_at.eq(Utc::now().naive_utc()),
+        ))
+        .execute(conn)
+        .to_db_error(ErrorCode::UpdateError, "Could not set ticket to Redeemed")?;
+        if updated == 1 {
+            ResaleListing::cancel_active_for_tickets(&[payload.ticket_instance_id], conn)?;
+            return Ok(OfflineScanResults::Redeemed);
+        }
+
+        let ticket = TicketInstance::find(payload.ticket_instance_id, conn)?;
+        if ticket.status == TicketInstanceStatus::Redeemed && ticket.token_id == payload.token_id {
+            Ok(OfflineScanResults::AlreadyRedeemed)
+        } else {
+            Ok(OfflineScanResults::Invalid)
+        }
+    }
+}
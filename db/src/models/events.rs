@@ -14,12 +14,16 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 use time::Duration;
 use utils::errors::*;
+use utils::rand::random_alpha_string;
 use utils::text;
 use uuid::Uuid;
 use validator::{Validate, ValidationErrors};
 use validators;
 use validators::*;
 
+/// Longest refund window a rescheduled event can give its ticket holders
+pub const MAX_RESCHEDULE_REFUND_WINDOW_DAYS: i32 = 365;
+
 #[derive(Associations, Identifiable, Queryable, AsChangeset)]
 #[belongs_to(Organization)]
 #[derive(Clone, QueryableByName, Serialize, Deserialize, PartialEq, Debug)]
@@ -56,6 +60,8 @@ pub struct Event {
     pub settlement_amount_in_cents: Option<i64>,
     pub event_end: Option<NaiveDateTime>,
     pub sendgrid_list_id: Option<i64>,
+    pub reschedule_refund_window_days: i32,
+    pub event_series_id: Option<Uuid>,
 }
 
 #[derive(Default, Insertable, Serialize, Deserialize, Validate, Clone)]
@@ -95,6 +101,9 @@ pub struct NewEvent {
     #[serde(default, deserialize_with = "deserialize_unless_blank")]
     pub override_status: Option<EventOverrideStatus>,
     pub event_end: Option<NaiveDateTime>,
+    pub reschedule_refund_window_days: Option<i32>,
+    #[serde(skip_deserializing)]
+    pub event_series_id: Option<Uuid>,
 }
 
 #[derive(AsChangeset)]
@@ -107,9 +116,10 @@ pub struct EventMinMaxCache {
 impl NewEvent {
     pub fn commit(&self, conn: &PgConnection) -> Result<Event, DatabaseError> {
         self.validate()?;
+        Event::validate_reschedule_refund_window_days(self.reschedule_refund_window_days)?;
         let organization = Organization::find(self.organization_id, conn)?;
 
-        diesel::insert_into(events::table)
+        let event: Event = diesel::insert_into(events::table)
             .values((
                 self,
                 events::fee_in_cents.eq(organization.client_event_fee_in_cents
@@ -118,7 +128,11 @@ impl NewEvent {
                 events::company_fee_in_cents.eq(organization.company_event_fee_in_cents),
             ))
             .get_result(conn)
-            .to_db_error(ErrorCode::InsertError, "Could not create new event")
+            .to_db_error(ErrorCode::InsertError, "Could not create new event")?;
+
+        WalletPassRegistration::schedule_redeem_date_update(&event, conn)?;
+
+        Ok(event)
     }
 
     pub fn default_status() -> EventStatus {
@@ -130,7 +144,7 @@ impl NewEvent {
     }
 }
 
-#[derive(AsChangeset, Default, Deserialize, Validate)]
+#[derive(AsChangeset, Clone, Default, Deserialize, Validate)]
 #[table_name = "events"]
 pub struct EventEditableAttributes {
     pub name: Option<String>,
@@ -164,6 +178,7 @@ pub struct EventEditableAttributes {
     pub override_status: Option<Option<EventOverrideStatus>>,
     pub event_end: Option<NaiveDateTime>,
     pub sendgrid_list_id: Option<i64>,
+    pub reschedule_refund_window_days: Option<i32>,
 }
 
 #[derive(Debug, Default, PartialEq, Serialize)]
@@ -171,6 +186,7 @@ pub struct EventLocalizedTimes {
     pub event_start: Option<DateTime<Tz>>,
     pub event_end: Option<DateTime<Tz>>,
     pub door_time: Option<DateTime<Tz>>,
+    pub redeem_date: Option<DateTime<Tz>>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
@@ -246,14 +262,23 @@ impl Event {
         )
     }
 
+    /// Updates the event, changing the start of a published event reschedules it
     pub fn update(
         &self,
+        current_user_id: Option<Uuid>,
         attributes: EventEditableAttributes,
         conn: &PgConnection,
     ) -> Result<Event, DatabaseError> {
         attributes.validate()?;
+        Event::validate_reschedule_refund_window_days(attributes.reschedule_refund_window_days)?;
 
         let mut event = attributes;
+        let rescheduled = self.status == EventStatus::Published
+            && event.event_start.is_some()
+            && event.event_start != self.event_start;
+        if rescheduled && event.override_status.is_none() {
+            event.override_status = Some(Some(EventOverrideStatus::Rescheduled));
+        }
 
         if self.status == EventStatus::Published {
             if let Some(date) = event.publish_date {
@@ -286,12 +311,59 @@ impl Event {
             ),
         )?;
 
-        DatabaseError::wrap(
+        let updated_event: Event = DatabaseError::wrap(
             ErrorCode::UpdateError,
             "Could not update event",
             diesel::update(self)
                 .set((event, events::updated_at.eq(dsl::now)))
                 .get_result(conn),
+        )?;
+
+        if updated_event.redeem_date != self.redeem_date {
+            WalletPassRegistration::schedule_redeem_date_update(&updated_event, conn)?;
+        }
+
+        if rescheduled {
+            EventReschedule::create(
+                updated_event.id,
+                self.event_start,
+                updated_event.event_start,
+                Utc::now().naive_utc()
+                    + Duration::days(updated_event.reschedule_refund_window_days as i64),
+                current_user_id,
+            )
+            .commit(conn)?;
+        }
+
+        Ok(updated_event)
+    }
+
+    fn validate_reschedule_refund_window_days(
+        reschedule_refund_window_days: Option<i32>,
+    ) -> Result<(), ValidationErrors> {
+        let reschedule_refund_window_days = match reschedule_refund_window_days {
+            Some(days) => days,
+            None => return Ok(()),
+        };
+        let validation_errors = validators::append_validation_error(
+            Ok(()),
+            "reschedule_refund_window_days",
+            validators::validate_greater_than(
+                reschedule_refund_window_days,
+                0,
+                "reschedule_refund_window_days_negative",
+                "Refund window for rescheduled events cannot be negative",
+            ),
+        );
+        validators::append_validation_error(
+            validation_errors,
+            "reschedule_refund_window_days",
+            validators::validate_greater_than(
+                MAX_RESCHEDULE_REFUND_WINDOW_DAYS,
+                reschedule_refund_window_days,
+                "reschedule_refund_window_days_too_large",
+                "Refund window for rescheduled events is too long",
+            ),
         )
     }
 
@@ -381,11 +453,24 @@ impl Event {
         )
     }
 
-    pub fn cancel(self, conn: &PgConnection) -> Result<Event, DatabaseError> {
-        diesel::update(&self)
+    /// Cancels the event and schedules refunds for all of its paid orders
+    pub fn cancel(
+        self,
+        current_user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Event, DatabaseError> {
+        if self.cancelled_at.is_some() {
+            return DatabaseError::business_process_error("Event has already been cancelled");
+        }
+
+        let event: Event = diesel::update(&self)
             .set(events::cancelled_at.eq(dsl::now.nullable()))
             .get_result(conn)
-            .to_db_error(ErrorCode::UpdateError, "Could not update event")
+            .to_db_error(ErrorCode::UpdateError, "Could not update event")?;
+
+        EventCancellation::create(event.id, None, current_user_id).commit(conn)?;
+
+        Ok(event)
     }
 
     /**
@@ -408,6 +493,7 @@ impl Event {
             event_start: Event::localized_time_from_venue(&self.event_start, &venue),
             event_end: Event::localized_time_from_venue(&self.event_end, &venue),
             door_time: Event::localized_time_from_venue(&self.door_time, &venue),
+            redeem_date: Event::localized_time_from_venue(&self.redeem_date, &venue),
         };
 
         event_localized_times
@@ -912,6 +998,176 @@ impl Event {
         DatabaseError::wrap(ErrorCode::QueryError, "Unable to load all events", result)
     }
 
+    /// Keeps only the first occurrence of each event series, in the order the events were sorted
+    pub fn group_by_series(events: Vec<Event>) -> Vec<Event> {
+        let mut seen_series_ids = Vec::new();
+        events
+            .into_iter()
+            .filter(|event| match event.event_series_id {
+                Some(event_series_id) => {
+                    if seen_series_ids.contains(&event_series_id) {
+                        false
+                    } else {
+                        seen_series_ids.push(event_series_id);
+                        true
+                    }
+                }
+                None => true,
+            })
+            .collect()
+    }
+
+    /// Copies the event with its ticket types, pricing, holds, codes and artists. Dates are
+    /// shifted by the difference between the new and the current event start.
+    pub fn duplicate(
+        &self,
+        event_start: Option<NaiveDateTime>,
+        status: EventStatus,
+        event_series_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<Event, DatabaseError> {
+        let offset = match (event_start, self.event_start) {
+            (Some(new_start), Some(current_start)) => new_start - current_start,
+            _ => Duration::zero(),
+        };
+        let shift = |date: Option<NaiveDateTime>| date.map(|d| d + offset);
+
+        let event = NewEvent {
+            name: self.name.clone(),
+            organization_id: self.organization_id,
+            venue_id: self.venue_id,
+            event_start: shift(self.event_start),
+            door_time: shift(self.door_time),
+            status,
+            publish_date: shift(self.publish_date),
+            redeem_date: shift(self.redeem_date),
+            promo_image_url: self.promo_image_url.clone(),
+            additional_info: self.additional_info.clone(),
+            age_limit: self.age_limit,
+            min_ticket_price_cache: None,
+            max_ticket_price_cache: None,
+            top_line_info: self.top_line_info.clone(),
+            video_url: self.video_url.clone(),
+            is_external: self.is_external,
+            external_url: self.external_url.clone(),
+            override_status: None,
+            event_end: shift(self.event_end),
+            reschedule_refund_window_days: Some(self.reschedule_refund_window_days),
+            event_series_id,
+        }
+        .commit(conn)?;
+
+        let wallet = event.issuer_wallet(conn)?;
+        let mut ticket_type_ids = HashMap::new();
+        for ticket_type in self.ticket_types(conn)? {
+            if ticket_type.status == TicketTypeStatus::Cancelled {
+                continue;
+            }
+            let new_ticket_type = event.add_ticket_type(
+                ticket_type.name.clone(),
+                ticket_type.description.clone(),
+                ticket_type.valid_ticket_count(conn)?,
+                ticket_type.start_date + offset,
+                ticket_type.end_date + offset,
+                wallet.id,
+                Some(ticket_type.increment),
+                ticket_type.limit_per_person,
+                ticket_type.price_in_cents,
+                conn,
+            )?;
+            let new_ticket_type = if ticket_type.allow_reentry {
+                new_ticket_type.update(
+                    TicketTypeEditableAttributes {
+                        allow_reentry: Some(true),
+                        ..Default::default()
+                    },
+                    conn,
+                )?
+            } else {
+                new_ticket_type
+            };
+            for ticket_pricing in ticket_type.ticket_pricing(conn)? {
+                new_ticket_type.add_ticket_pricing(
+                    ticket_pricing.name,
+                    ticket_pricing.start_date + offset,
+                    ticket_pricing.end_date + offset,
+                    ticket_pricing.price_in_cents,
+                    ticket_pricing.is_box_office_only,
+                    None,
+                    conn,
+                )?;
+            }
+            ticket_type_ids.insert(ticket_type.id, new_ticket_type.id);
+        }
+
+        // Comps issued to individuals belong to the original event
+        for hold in Hold::find_for_event(self.id, conn)? {
+            if hold.parent_hold_id.is_some() {
+                continue;
+            }
+            let ticket_type_id = match ticket_type_ids.get(&hold.ticket_type_id) {
+                Some(ticket_type_id) => *ticket_type_id,
+                None => continue,
+            };
+            let (quantity, _) = hold.quantity(conn)?;
+            let new_hold = Hold::create_hold(
+                hold.name.clone(),
+                event.id,
+                random_alpha_string(10),
+                hold.discount_in_cents.map(|d| d as u32),
+                hold.discount_as_percentage.map(|d| d as u32),
+                shift(hold.end_at),
+                hold.max_per_order.map(|m| m as u32),
+                hold.hold_type.clone(),
+                ticket_type_id,
+                hold.created_by_user_id,
+            )
+            .commit(conn)?;
+            new_hold.set_quantity(quantity, conn)?;
+        }
+
+        // Redemption codes are unique so the copies are issued new ones
+        for code in Code::find_for_event(self.id, None, conn)? {
+            let new_code = Code::create(
+                code.name,
+                event.id,
+                code.code_type,
+                random_alpha_string(10).to_uppercase(),
+                code.max_uses as u32,
+                code.discount_in_cents.map(|d| d as u32),
+                code.discount_as_percentage.map(|d| d as u32),
+                code.buy_quantity.map(|q| q as u32),
+                code.get_quantity.map(|q| q as u32),
+                code.minimum_order_total_in_cents.map(|m| m as u32),
+                code.start_date + offset,
+                code.end_date + offset,
+                code.max_tickets_per_user.map(|m| m as u32),
+            )
+            .commit(conn)?;
+            new_code.update_ticket_types(
+                code.ticket_type_ids
+                    .iter()
+                    .filter_map(|id| ticket_type_ids.get(id).cloned())
+                    .collect(),
+                conn,
+            )?;
+        }
+
+        for event_artist in self.artists(conn)? {
+            EventArtist::create(
+                event.id,
+                event_artist.artist.id,
+                event_artist.rank,
+                shift(event_artist.set_time),
+                event_artist.importance,
+                event_artist.stage_id,
+            )
+            .commit(conn)?;
+        }
+
+        event.update_cache(conn)
+    }
+
     pub fn add_artist(&self, artist_id: Uuid, conn: &PgConnection) -> Result<(), DatabaseError> {
         EventArtist::create(self.id, artist_id, 0, None, 0, None)
             .commit(conn)
@@ -0,0 +1,149 @@
+use chrono::prelude::*;
+use diesel;
+use diesel::expression::dsl;
+use diesel::prelude::*;
+use models::*;
+use schema::{gift_cards, order_items, orders};
+use utils::errors::*;
+use utils::rand::random_alpha_string;
+use uuid::Uuid;
+
+const GIFT_CARD_REDEMPTION_CODE_LENGTH: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Identifiable, Queryable, Serialize, Deserialize)]
+pub struct GiftCard {
+    pub id: Uuid,
+    pub order_item_id: Uuid,
+    pub redemption_code: String,
+    pub value_in_cents: i64,
+    pub redeemed_by_user_id: Option<Uuid>,
+    pub redeemed_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl GiftCard {
+    /// Issues a gift card with a new redemption code for a purchased gift card order item
+    pub(crate) fn create_for_order_item(
+        order_item: &OrderItem,
+        conn: &PgConnection,
+    ) -> Result<GiftCard, DatabaseError> {
+        if order_item.item_type != OrderItemTypes::GiftCard {
+            return DatabaseError::business_process_error(
+                "Gift cards can only be issued for gift card order items",
+            );
+        }
+
+        NewGiftCard {
+            order_item_id: order_item.id,
+            redemption_code: random_alpha_string(GIFT_CARD_REDEMPTION_CODE_LENGTH).to_uppercase(),
+            value_in_cents: order_item.unit_price_in_cents,
+        }
+        .commit(conn)
+    }
+
+    pub fn find_by_redemption_code(
+        redemption_code: &str,
+        conn: &PgConnection,
+    ) -> Result<GiftCard, DatabaseError> {
+        gift_cards::table
+            .filter(gift_cards::redemption_code.eq(redemption_code.to_uppercase()))
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not find gift card")
+    }
+
+    pub fn find_for_order(
+        order_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<GiftCard>, DatabaseError> {
+        gift_cards::table
+            .inner_join(order_items::table)
+            .filter(order_items::order_id.eq(order_id))
+            .select(gift_cards::all_columns)
+            .order_by(gift_cards::created_at.asc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load gift cards for order")
+    }
+
+    /// Gift cards bought by the user, including ones bought on their behalf
+    pub fn find_purchased_by_user(
+        user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<GiftCard>, DatabaseError> {
+        gift_cards::table
+            .inner_join(order_items::table.inner_join(orders::table))
+            .filter(
+                orders::user_id
+                    .eq(user_id)
+                    .and(orders::on_behalf_of_user_id.is_null())
+                    .or(orders::on_behalf_of_user_id.eq(user_id)),
+            )
+            .select(gift_cards::all_columns)
+            .order_by(gift_cards::created_at.desc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load gift cards for user")
+    }
+
+    /// Redeems the gift card, adding its value to the user's account credit
+    pub fn redeem(
+        &self,
+        user: &User,
+        conn: &PgConnection,
+    ) -> Result<CreditTransaction, DatabaseError> {
+        let redeemed_at = Utc::now().naive_utc();
+        let rows_affected = diesel::update(
+            gift_cards::table
+                .filter(gift_cards::id.eq(self.id))
+                .filter(gift_cards::redeemed_at.is_null()),
+        )
+        .set((
+            gift_cards::redeemed_by_user_id.eq(user.id),
+            gift_cards::redeemed_at.eq(redeemed_at),
+            gift_cards::updated_at.eq(dsl::now),
+        ))
+        .execute(conn)
+        .to_db_error(ErrorCode::UpdateError, "Could not redeem gift card")?;
+        if rows_affected == 0 {
+            return DatabaseError::business_process_error("Gift card has already been redeemed");
+        }
+
+        let credit_transaction = CreditTransaction::create(
+            user.id,
+            CreditTransactionTypes::GiftCard,
+            self.value_in_cents,
+            None,
+            Some(self.id),
+            Some(user.id),
+        )
+        .commit(conn)?;
+
+        DomainEvent::create(
+            DomainEventTypes::GiftCardRedeemed,
+            "Gift card was redeemed".to_string(),
+            Tables::GiftCards,
+            Some(self.id),
+            Some(user.id),
+            None,
+        )
+        .commit(conn)?;
+
+        Ok(credit_transaction)
+    }
+}
+
+#[derive(Insertable, Clone)]
+#[table_name = "gift_cards"]
+struct NewGiftCard {
+    order_item_id: Uuid,
+    redemption_code: String,
+    value_in_cents: i64,
+}
+
+impl NewGiftCard {
+    fn commit(self, conn: &PgConnection) -> Result<GiftCard, DatabaseError> {
+        diesel::insert_into(gift_cards::table)
+            .values(&self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create gift card")
+    }
+}
@@ -11,4 +11,13 @@ pub enum HistoryItem {
         order_id: Uuid,
         order_date: NaiveDateTime,
     },
+    Exchange {
+        order_id: Uuid,
+        exchange_date: NaiveDateTime,
+        event_name: String,
+        from_ticket_type_name: String,
+        to_ticket_type_name: String,
+        quantity: u32,
+        difference_in_cents: i64,
+    },
 }
@@ -1,9 +1,13 @@
 use chrono::prelude::*;
+use chrono::Duration;
 use diesel;
-use diesel::dsl;
+use diesel::dsl::{self, sql};
 use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Timestamp};
 use models::*;
-use schema::holds;
+use schema::{holds, order_items, orders, ticket_instances, wallets};
+use std::collections::HashMap;
+use utils::discount_for_price;
 use utils::errors::*;
 use uuid::Uuid;
 use validator::Validate;
@@ -26,6 +30,32 @@ pub struct Hold {
     pub phone: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub discount_as_percentage: Option<i64>,
+    pub claim_key: Option<Uuid>,
+    pub claim_sent_at: Option<NaiveDateTime>,
+    pub claimed_at: Option<NaiveDateTime>,
+    pub claimed_by_user_id: Option<Uuid>,
+    pub created_by_user_id: Option<Uuid>,
+}
+
+const COMP_REDEMPTION_CODE_LENGTH: usize = 10;
+/// The transfer made when a comp is claimed is received straight away
+const COMP_CLAIM_TRANSFER_VALIDITY_SECONDS: u32 = 60;
+
+/// A person to comp tickets to when importing a guest list
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct CompRecipient {
+    pub name: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub quantity: u32,
+}
+
+/// Validation errors for one recipient of a comp import. `row` starts at 1.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CompImportRowError {
+    pub row: usize,
+    pub errors: HashMap<&'static str, Vec<ValidationError>>,
 }
 
 #[derive(AsChangeset, Default, Validate)]
@@ -35,6 +65,7 @@ pub struct UpdateHoldAttributes {
     pub redemption_code: Option<String>,
     pub hold_type: Option<HoldTypes>,
     pub discount_in_cents: Option<Option<i64>>,
+    pub discount_as_percentage: Option<Option<i64>>,
     #[validate(email(message = "Email is invalid"))]
     pub email: Option<Option<String>>,
     pub phone: Option<Option<String>>,
@@ -50,10 +81,12 @@ impl Hold {
         event_id: Uuid,
         redemption_code: String,
         discount_in_cents: Option<u32>,
+        discount_as_percentage: Option<u32>,
         end_at: Option<NaiveDateTime>,
         max_per_order: Option<u32>,
         hold_type: HoldTypes,
         ticket_type_id: Uuid,
+        created_by_user_id: Option<Uuid>,
     ) -> NewHold {
         NewHold {
             name,
@@ -63,10 +96,12 @@ impl Hold {
             phone: None,
             redemption_code: redemption_code.to_uppercase(),
             discount_in_cents: discount_in_cents.and_then(|discount| Some(discount as i64)),
+            discount_as_percentage: discount_as_percentage.map(|discount| discount as i64),
             end_at,
             max_per_order: max_per_order.map(|m| m as i64),
             hold_type,
             ticket_type_id,
+            created_by_user_id,
         }
     }
 
@@ -82,6 +117,7 @@ impl Hold {
         end_at: Option<NaiveDateTime>,
         max_per_order: Option<u32>,
         quantity: u32,
+        created_by_user_id: Option<Uuid>,
         conn: &PgConnection,
     ) -> Result<Hold, DatabaseError> {
         let hold = Hold::find(hold_id, conn)?;
@@ -94,10 +130,12 @@ impl Hold {
             phone,
             redemption_code: redemption_code.to_uppercase(),
             discount_in_cents: None,
+            discount_as_percentage: None,
             end_at,
             max_per_order: max_per_order.map(|m| m as i64),
             hold_type: HoldTypes::Comp,
             ticket_type_id: hold.ticket_type_id,
+            created_by_user_id,
         };
 
         let new_hold = new_hold.commit(conn)?;
@@ -107,6 +145,88 @@ impl Hold {
         Ok(new_hold)
     }
 
+    /// Creates a comp in this hold for each recipient, with a generated redemption code. Comps
+    /// are only created when every recipient is valid and the hold has enough tickets left for
+    /// all of them, otherwise the errors for each invalid recipient are returned.
+    pub fn import_comps(
+        &self,
+        recipients: Vec<CompRecipient>,
+        created_by_user_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<Result<Vec<Hold>, Vec<CompImportRowError>>, DatabaseError> {
+        let (_, available) = self.quantity(conn)?;
+        let mut remaining = available as i64;
+        let mut row_errors = vec![];
+        for (index, recipient) in recipients.iter().enumerate() {
+            let mut validation_errors = Ok(());
+            if recipient.name.trim().is_empty() {
+                validation_errors = validators::append_validation_error(
+                    validation_errors,
+                    "name",
+                    Err(create_validation_error("required", "Name is required")),
+                );
+            }
+            if let Some(ref email) = recipient.email {
+                if !validate_email(email) {
+                    validation_errors = validators::append_validation_error(
+                        validation_errors,
+                        "email",
+                        Err(create_validation_error("email", "Email is invalid")),
+                    );
+                }
+            }
+            validation_errors = validators::append_validation_error(
+                validation_errors,
+                "quantity",
+                validators::validate_greater_than(
+                    recipient.quantity,
+                    1,
+                    "quantity_less_than_one",
+                    "Quantity must be at least 1",
+                ),
+            );
+            remaining -= recipient.quantity as i64;
+            if remaining < 0 {
+                validation_errors = validators::append_validation_error(
+                    validation_errors,
+                    "quantity",
+                    Err(create_validation_error(
+                        "insufficient_quantity",
+                        "Not enough tickets remaining in the hold",
+                    )),
+                );
+            }
+
+            if let Err(errors) = validation_errors {
+                row_errors.push(CompImportRowError {
+                    row: index + 1,
+                    errors: errors.field_errors(),
+                });
+            }
+        }
+        if !row_errors.is_empty() {
+            return Ok(Err(row_errors));
+        }
+
+        let mut comps = Vec::with_capacity(recipients.len());
+        for recipient in recipients {
+            comps.push(Hold::create_comp_for_person(
+                recipient.name,
+                self.id,
+                recipient.email,
+                recipient.phone,
+                unique_redemption_code(COMP_REDEMPTION_CODE_LENGTH, conn)?,
+                self.end_at,
+                None,
+                recipient.quantity,
+                created_by_user_id,
+                conn,
+            )?);
+        }
+
+        Ok(Ok(comps))
+    }
+
     /// Updates a hold. Note, the quantity in the hold must be updated using
     /// `set_quantity`.
     pub fn update(
@@ -118,18 +238,28 @@ impl Hold {
         if update_attrs.hold_type == Some(HoldTypes::Comp) {
             // Remove discount
             update_attrs.discount_in_cents = Some(None);
+            update_attrs.discount_as_percentage = Some(None);
         }
 
         self.validate_record(&update_attrs, conn)?;
 
-        diesel::update(
+        let new_end_at = update_attrs.end_at.unwrap_or(self.end_at);
+        let hold: Hold = diesel::update(
             holds::table
                 .filter(holds::id.eq(self.id))
                 .filter(holds::updated_at.eq(self.updated_at)),
         )
         .set((update_attrs, holds::updated_at.eq(dsl::now)))
         .get_result(conn)
-        .to_db_error(ErrorCode::UpdateError, "Could not update hold")
+        .to_db_error(ErrorCode::UpdateError, "Could not update hold")?;
+
+        if let Some(end_at) = new_end_at {
+            if self.end_at != new_end_at {
+                hold.schedule_expiry(end_at, conn)?;
+            }
+        }
+
+        Ok(hold)
     }
 
     pub fn find(id: Uuid, conn: &PgConnection) -> Result<Hold, DatabaseError> {
@@ -206,6 +336,21 @@ impl Hold {
                 update_attrs
                     .discount_in_cents
                     .unwrap_or(self.discount_in_cents),
+                update_attrs
+                    .discount_as_percentage
+                    .unwrap_or(self.discount_as_percentage),
+            ),
+        );
+        let validation_errors = validators::append_validation_error(
+            validation_errors,
+            "discount_as_percentage",
+            discount_valid(
+                update_attrs
+                    .discount_in_cents
+                    .unwrap_or(self.discount_in_cents),
+                update_attrs
+                    .discount_as_percentage
+                    .unwrap_or(self.discount_as_percentage),
             ),
         );
         let validation_errors = validators::append_validation_error(
@@ -237,6 +382,7 @@ impl Hold {
         redemption_code: String,
         quantity: u32,
         discount_in_cents: Option<u32>,
+        discount_as_percentage: Option<u32>,
         hold_type: HoldTypes,
         end_at: Option<NaiveDateTime>,
         max_per_order: Option<u32>,
@@ -250,10 +396,12 @@ impl Hold {
             phone: None,
             redemption_code: redemption_code.to_uppercase(),
             discount_in_cents: discount_in_cents.map(|m| m as i64),
+            discount_as_percentage: discount_as_percentage.map(|m| m as i64),
             end_at,
             max_per_order: max_per_order.map(|m| m as i64),
             hold_type: hold_type,
             ticket_type_id: self.ticket_type_id,
+            created_by_user_id: self.created_by_user_id,
         };
 
         let new_hold = new_hold.commit(conn)?;
@@ -281,11 +429,89 @@ impl Hold {
         Ok(())
     }
 
+    /// Releases tickets that have not been purchased back to general inventory once the hold has
+    /// ended, returning the number released. Tickets still reserved in a cart are released by
+    /// another run scheduled for when their reservation lapses.
+    pub fn expire(&self, conn: &PgConnection) -> Result<u32, DatabaseError> {
+        if self
+            .end_at
+            .map_or(true, |end_at| end_at > Utc::now().naive_utc())
+        {
+            return Ok(0);
+        }
+
+        let (releasable, reserved_until): (Option<i64>, Option<NaiveDateTime>) =
+            ticket_instances::table
+                .filter(ticket_instances::hold_id.eq(self.id))
+                .select((
+                    sql::<Nullable<BigInt>>(
+                        "SUM(CASE WHEN status = 'Available'
+                            OR (status = 'Reserved' AND reserved_until < now()) THEN 1 ELSE 0 END)",
+                    ),
+                    sql::<Nullable<Timestamp>>(
+                        "MAX(CASE WHEN status = 'Reserved' AND reserved_until >= now()
+                            THEN reserved_until ELSE NULL END)",
+                    ),
+                ))
+                .first(conn)
+                .to_db_error(
+                    ErrorCode::QueryError,
+                    "Could not load ticket counts for hold",
+                )?;
+        let releasable = releasable.unwrap_or(0) as u32;
+
+        if releasable > 0 {
+            TicketInstance::release_from_hold(self.id, self.ticket_type_id, releasable, conn)?;
+            DomainEvent::create(
+                DomainEventTypes::HoldExpired,
+                "Hold expired".to_string(),
+                Tables::Holds,
+                Some(self.id),
+                None,
+                Some(json!({
+                    "ticket_type_id": self.ticket_type_id,
+                    "quantity_released": releasable
+                })),
+            )
+            .commit(conn)?;
+        }
+        if let Some(reserved_until) = reserved_until {
+            self.schedule_expiry(reserved_until, conn)?;
+        }
+
+        Ok(releasable)
+    }
+
+    fn schedule_expiry(
+        &self,
+        scheduled_at: NaiveDateTime,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        DomainAction::create(
+            None,
+            DomainActionTypes::ExpireHold,
+            None,
+            json!({ "hold_id": self.id }),
+            Tables::Holds.table_name(),
+            self.id,
+            scheduled_at,
+            scheduled_at + Duration::days(1),
+            3,
+        )
+        .commit(conn)?;
+
+        Ok(())
+    }
+
     pub fn discount_in_cents_valid(
         hold_type: HoldTypes,
         discount_in_cents: Option<i64>,
+        discount_as_percentage: Option<i64>,
     ) -> Result<(), ValidationError> {
-        if hold_type == HoldTypes::Discount && discount_in_cents.is_none() {
+        if hold_type == HoldTypes::Discount
+            && discount_in_cents.is_none()
+            && discount_as_percentage.is_none()
+        {
             let validation_error =
                 create_validation_error("required", "Discount required for hold type Discount");
             return Err(validation_error);
@@ -294,6 +520,21 @@ impl Hold {
         Ok(())
     }
 
+    /// Price of a ticket bought through this hold
+    pub fn discounted_price(&self, price_in_cents: i64) -> i64 {
+        match self.hold_type {
+            HoldTypes::Discount => {
+                price_in_cents
+                    - discount_for_price(
+                        price_in_cents,
+                        self.discount_in_cents,
+                        self.discount_as_percentage,
+                    )
+            }
+            HoldTypes::Comp => 0,
+        }
+    }
+
     /// Changes the quantity of tickets reserved in this hold. If the quantity is
     /// higher, it will attempt to reserve more tickets from either the main pool,
     /// or from the parent hold if `parent_hold_id` is not `None`. Likewise, if the
@@ -371,9 +612,12 @@ impl Hold {
             event_id: self.event_id,
             redemption_code: self.redemption_code,
             discount_in_cents: self.discount_in_cents,
+            discount_as_percentage: self.discount_as_percentage,
             max_per_order: self.max_per_order,
             email: self.email,
             phone: self.phone,
+            claim_sent_at: self.claim_sent_at,
+            claimed_at: self.claimed_at,
             available,
             quantity,
         })
@@ -382,6 +626,162 @@ impl Hold {
     pub fn comps(&self, conn: &PgConnection) -> Result<Vec<Hold>, DatabaseError> {
         Ok(Hold::find_by_parent_id(self.id, HoldTypes::Comp, 0, 100000, conn)?.data)
     }
+
+    pub fn find_by_claim_key(claim_key: Uuid, conn: &PgConnection) -> Result<Hold, DatabaseError> {
+        holds::table
+            .filter(holds::claim_key.eq(claim_key))
+            .first(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load comp with that claim key",
+            )
+    }
+
+    /// Marks the comp as sent to its recipient, generating the key for their claim link if it
+    /// does not have one yet. The first time the comp is sent its remaining tickets are issued
+    /// to `sender` in a zero total back office order, so that the recipient's claim is a ticket
+    /// transfer from the sender. Returns the comp and the tickets issued by this call.
+    pub fn prepare_claim(
+        &self,
+        sender_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<(Hold, Vec<TicketInstance>), DatabaseError> {
+        if self.hold_type != HoldTypes::Comp {
+            return DatabaseError::business_process_error("Only comps can be sent to recipients");
+        }
+        if self.claimed_at.is_some() {
+            return DatabaseError::business_process_error("Comp has already been claimed");
+        }
+        if self.email.is_none() && self.phone.is_none() {
+            return DatabaseError::business_process_error(
+                "Comp does not have an email or phone number to send to",
+            );
+        }
+
+        let mut issued_tickets = Vec::new();
+        if self.claim_key.is_none() {
+            if self
+                .end_at
+                .map(|end_at| end_at < Utc::now().naive_utc())
+                .unwrap_or(false)
+            {
+                return DatabaseError::business_process_error("Comp has ended");
+            }
+            let (_, available) = self.quantity(conn)?;
+            if available == 0 {
+                return DatabaseError::business_process_error("Comp has no tickets remaining");
+            }
+
+            let mut order = Order::create_back_office(sender_id, conn)?;
+            order.update_quantities(
+                &[UpdateOrderItem {
+                    ticket_type_id: self.ticket_type_id,
+                    quantity: available,
+                    redemption_code: Some(self.redemption_code.clone()),
+                    seat_ids: None,
+                }],
+                false,
+                true,
+                conn,
+            )?;
+            if order.calculate_total(conn)? > 0 {
+                return DatabaseError::business_process_error(
+                    "Could not send comp because its tickets are not free",
+                );
+            }
+            order.add_external_payment(Some("Comp".to_string()), sender_id, 0, conn)?;
+            for item in order.items(conn)? {
+                issued_tickets.append(&mut TicketInstance::find_for_order_item(item.id, conn)?);
+            }
+        }
+
+        let comp = diesel::update(self)
+            .set((
+                holds::claim_key.eq(self.claim_key.unwrap_or_else(Uuid::new_v4)),
+                holds::claim_sent_at.eq(dsl::now.nullable()),
+                holds::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not update comp")?;
+        Ok((comp, issued_tickets))
+    }
+
+    /// Tickets issued when the comp was sent that are still held by the sender, along with the
+    /// sender's id
+    fn unclaimed_tickets(
+        &self,
+        conn: &PgConnection,
+    ) -> Result<Vec<(TicketInstance, Uuid)>, DatabaseError> {
+        ticket_instances::table
+            .inner_join(
+                order_items::table
+                    .on(ticket_instances::order_item_id.eq(order_items::id.nullable())),
+            )
+            .inner_join(orders::table.on(order_items::order_id.eq(orders::id)))
+            .inner_join(wallets::table.on(ticket_instances::wallet_id.eq(wallets::id)))
+            .filter(order_items::hold_id.eq(self.id))
+            .filter(orders::order_type.eq(OrderTypes::BackOffice.to_string()))
+            .filter(orders::status.eq(OrderStatus::Paid))
+            .filter(wallets::user_id.eq(orders::user_id.nullable()))
+            .filter(ticket_instances::status.eq(TicketInstanceStatus::Purchased))
+            .select((ticket_instances::all_columns, orders::user_id))
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load tickets for comp")
+    }
+
+    /// Claims the comp for `user`, transferring the tickets issued when it was sent from the
+    /// sender's wallet to the user's. Returns the sender's wallet and the transferred tickets.
+    pub fn claim(
+        &self,
+        user: &User,
+        conn: &PgConnection,
+    ) -> Result<(Wallet, Vec<TicketInstance>), DatabaseError> {
+        if self.hold_type != HoldTypes::Comp {
+            return DatabaseError::business_process_error("Only comps can be claimed");
+        }
+        if self.claimed_at.is_some() {
+            return DatabaseError::business_process_error("Comp has already been claimed");
+        }
+        let tickets = self.unclaimed_tickets(conn)?;
+        if tickets.is_empty() {
+            return DatabaseError::business_process_error("Comp has no tickets to claim");
+        }
+
+        // Only one claim can succeed, even if the link is opened more than once
+        let claimed = diesel::update(
+            holds::table
+                .filter(holds::id.eq(self.id))
+                .filter(holds::claimed_at.is_null()),
+        )
+        .set((
+            holds::claimed_at.eq(dsl::now.nullable()),
+            holds::claimed_by_user_id.eq(user.id),
+            holds::updated_at.eq(dsl::now),
+        ))
+        .execute(conn)
+        .to_db_error(ErrorCode::UpdateError, "Could not claim comp")?;
+        if claimed == 0 {
+            return DatabaseError::business_process_error("Comp has already been claimed");
+        }
+
+        let sender_id = tickets[0].1;
+        let transfer_authorization = TicketInstance::authorize_ticket_transfer(
+            sender_id,
+            tickets.iter().map(|(t, _)| t.id).collect(),
+            COMP_CLAIM_TRANSFER_VALIDITY_SECONDS,
+            conn,
+        )?;
+        let sender_wallet = Wallet::find_default_for_user(sender_id, conn)?;
+        let receiver_wallet = Wallet::find_default_for_user(user.id, conn)?;
+        let tickets = TicketInstance::receive_ticket_transfer(
+            transfer_authorization,
+            &sender_wallet,
+            &receiver_wallet.id,
+            conn,
+        )?;
+
+        Ok((sender_wallet, tickets))
+    }
 }
 
 #[derive(Insertable, Validate)]
@@ -395,29 +795,47 @@ pub struct NewHold {
     pub phone: Option<String>,
     pub redemption_code: String,
     pub discount_in_cents: Option<i64>,
+    pub discount_as_percentage: Option<i64>,
     pub end_at: Option<NaiveDateTime>,
     pub max_per_order: Option<i64>,
     pub hold_type: HoldTypes,
     pub ticket_type_id: Uuid,
+    pub created_by_user_id: Option<Uuid>,
 }
 
 impl NewHold {
     pub fn commit(mut self, conn: &PgConnection) -> Result<Hold, DatabaseError> {
         if self.hold_type == HoldTypes::Comp {
-            self.discount_in_cents = None
+            self.discount_in_cents = None;
+            self.discount_as_percentage = None;
         }
         self.validate_record(conn)?;
-        diesel::insert_into(holds::table)
+        let hold: Hold = diesel::insert_into(holds::table)
             .values(self)
             .get_result(conn)
-            .to_db_error(ErrorCode::InsertError, "Could not create hold")
+            .to_db_error(ErrorCode::InsertError, "Could not create hold")?;
+
+        if let Some(end_at) = hold.end_at {
+            hold.schedule_expiry(end_at, conn)?;
+        }
+
+        Ok(hold)
     }
 
     fn validate_record(&self, conn: &PgConnection) -> Result<(), DatabaseError> {
         let validation_errors = validators::append_validation_error(
             self.validate(),
             "discount_in_cents",
-            Hold::discount_in_cents_valid(self.hold_type.clone(), self.discount_in_cents),
+            Hold::discount_in_cents_valid(
+                self.hold_type.clone(),
+                self.discount_in_cents,
+                self.discount_as_percentage,
+            ),
+        );
+        let validation_errors = validators::append_validation_error(
+            validation_errors,
+            "discount_as_percentage",
+            discount_valid(self.discount_in_cents, self.discount_as_percentage),
         );
         let validation_errors = validators::append_validation_error(
             validation_errors,
@@ -442,9 +860,12 @@ pub struct DisplayHold {
     pub event_id: Uuid,
     pub redemption_code: String,
     pub discount_in_cents: Option<i64>,
+    pub discount_as_percentage: Option<i64>,
     pub max_per_order: Option<i64>,
     pub email: Option<String>,
     pub phone: Option<String>,
+    pub claim_sent_at: Option<NaiveDateTime>,
+    pub claimed_at: Option<NaiveDateTime>,
     pub available: u32,
     pub quantity: u32,
 }
@@ -1,11 +1,17 @@
 pub use self::artists::*;
 pub use self::assets::*;
+pub use self::code_campaigns::*;
 pub use self::codes::*;
+pub use self::credit_transactions::*;
 pub use self::domain_actions::*;
 pub use self::domain_events::*;
 pub use self::enums::*;
 pub use self::event_artists::*;
+pub use self::event_cancellations::*;
 pub use self::event_interest::*;
+pub use self::event_reschedules::*;
+pub use self::event_series::*;
+pub use self::event_signing_keys::*;
 pub use self::events::*;
 pub use self::external_logins::FACEBOOK_SITE;
 pub use self::external_logins::*;
@@ -13,9 +19,13 @@ pub use self::fans::*;
 pub use self::fee_schedule_ranges::*;
 pub use self::fee_schedules::*;
 pub use self::for_display::*;
+pub use self::gift_cards::*;
 pub use self::history_item::*;
 pub use self::holds::*;
+pub use self::order_exchanges::*;
+pub use self::order_idempotency_keys::*;
 pub use self::order_items::*;
+pub use self::order_shares::*;
 pub use self::orders::*;
 pub use self::organization_invites::*;
 pub use self::organization_users::*;
@@ -28,15 +38,23 @@ pub use self::redeemable_ticket::*;
 pub use self::refunded_tickets::*;
 pub use self::regions::*;
 pub use self::reports::*;
+pub use self::resale_listings::*;
+pub use self::resale_payouts::*;
 pub use self::scopes::*;
+pub use self::seat_sections::*;
+pub use self::seats::*;
 pub use self::stages::*;
+pub use self::tax_rules::*;
 pub use self::ticket_instances::RedeemResults;
 pub use self::ticket_instances::*;
 pub use self::ticket_pricing::*;
+pub use self::ticket_scans::*;
 pub use self::ticket_type_codes::*;
 pub use self::ticket_types::*;
 pub use self::users::*;
 pub use self::venues::*;
+pub use self::waitlist_entries::*;
+pub use self::wallet_pass_registrations::*;
 pub use self::wallets::*;
 
 use serde::{Deserialize, Deserializer};
@@ -46,21 +64,31 @@ pub mod concerns;
 
 mod artists;
 mod assets;
+mod code_campaigns;
 mod codes;
+mod credit_transactions;
 mod domain_actions;
 mod domain_events;
 pub mod enums;
 mod event_artists;
+mod event_cancellations;
 mod event_interest;
+mod event_reschedules;
+mod event_series;
+mod event_signing_keys;
 mod events;
 mod external_logins;
 mod fans;
 mod fee_schedule_ranges;
 mod fee_schedules;
 mod for_display;
+mod gift_cards;
 mod history_item;
 mod holds;
+mod order_exchanges;
+mod order_idempotency_keys;
 mod order_items;
+mod order_shares;
 mod orders;
 mod organization_invites;
 mod organization_users;
@@ -73,14 +101,22 @@ mod redeemable_ticket;
 mod refunded_tickets;
 mod regions;
 mod reports;
+mod resale_listings;
+mod resale_payouts;
 pub mod scopes;
+mod seat_sections;
+mod seats;
 mod stages;
+mod tax_rules;
 mod ticket_instances;
 mod ticket_pricing;
+mod ticket_scans;
 mod ticket_type_codes;
 mod ticket_types;
 mod users;
 mod venues;
+mod waitlist_entries;
+mod wallet_pass_registrations;
 mod wallets;
 
 pub fn deserialize_unless_blank<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
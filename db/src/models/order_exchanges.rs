@@ -0,0 +1,73 @@
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::prelude::*;
+use schema::order_exchanges;
+use utils::errors::*;
+use uuid::Uuid;
+
+/// Tickets from one order item that were swapped for tickets of another ticket type. The
+/// difference is positive when the user paid more for the new tickets and negative when they
+/// were refunded.
+#[derive(Clone, Debug, PartialEq, Identifiable, Queryable, Serialize, Deserialize)]
+pub struct OrderExchange {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub from_order_item_id: Uuid,
+    pub to_order_item_id: Uuid,
+    pub quantity: i64,
+    pub difference_in_cents: i64,
+    pub created_by: Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl OrderExchange {
+    pub(crate) fn create(
+        order_id: Uuid,
+        from_order_item_id: Uuid,
+        to_order_item_id: Uuid,
+        quantity: i64,
+        difference_in_cents: i64,
+        created_by: Uuid,
+    ) -> NewOrderExchange {
+        NewOrderExchange {
+            order_id,
+            from_order_item_id,
+            to_order_item_id,
+            quantity,
+            difference_in_cents,
+            created_by,
+        }
+    }
+
+    pub fn find_for_order(
+        order_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<OrderExchange>, DatabaseError> {
+        order_exchanges::table
+            .filter(order_exchanges::order_id.eq(order_id))
+            .order_by(order_exchanges::created_at.asc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load order exchanges")
+    }
+}
+
+#[derive(Insertable, Clone)]
+#[table_name = "order_exchanges"]
+pub(crate) struct NewOrderExchange {
+    order_id: Uuid,
+    from_order_item_id: Uuid,
+    to_order_item_id: Uuid,
+    quantity: i64,
+    difference_in_cents: i64,
+    created_by: Uuid,
+}
+
+impl NewOrderExchange {
+    pub(crate) fn commit(self, conn: &PgConnection) -> Result<OrderExchange, DatabaseError> {
+        diesel::insert_into(order_exchanges::table)
+            .values(&self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create order exchange")
+    }
+}
@@ -0,0 +1,131 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use diesel;
+use diesel::dsl;
+use diesel::prelude::*;
+use hex;
+use models::*;
+use ring::digest;
+use schema::order_idempotency_keys;
+use serde_json;
+use utils::errors::*;
+use uuid::Uuid;
+
+/// Requests that have not stored a response within this time are assumed to have failed part way
+/// through, their key is kept so that the request is not run twice
+pub const IDEMPOTENCY_KEY_LOCK_TIMEOUT_MINUTES: i64 = 5;
+
+#[derive(Associations, Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+#[belongs_to(Order)]
+#[belongs_to(User)]
+#[table_name = "order_idempotency_keys"]
+pub struct OrderIdempotencyKey {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub user_id: Uuid,
+    pub operation: IdempotencyKeyOperations,
+    pub idempotency_key: String,
+    pub request_hash: String,
+    pub response_status: Option<i32>,
+    pub response_body: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name = "order_idempotency_keys"]
+pub struct NewOrderIdempotencyKey {
+    pub order_id: Uuid,
+    pub user_id: Uuid,
+    pub operation: IdempotencyKeyOperations,
+    pub idempotency_key: String,
+    pub request_hash: String,
+}
+
+impl NewOrderIdempotencyKey {
+    /// Returns `None` if another request has already claimed this key
+    pub fn commit(
+        &self,
+        conn: &PgConnection,
+    ) -> Result<Option<OrderIdempotencyKey>, DatabaseError> {
+        diesel::insert_into(order_idempotency_keys::table)
+            .values(self)
+            .on_conflict_do_nothing()
+            .get_result(conn)
+            .optional()
+            .to_db_error(ErrorCode::InsertError, "Could not store idempotency key")
+    }
+}
+
+impl OrderIdempotencyKey {
+    pub fn create(
+        order_id: Uuid,
+        user_id: Uuid,
+        operation: IdempotencyKeyOperations,
+        idempotency_key: String,
+        request: &serde_json::Value,
+    ) -> NewOrderIdempotencyKey {
+        NewOrderIdempotencyKey {
+            order_id,
+            user_id,
+            operation,
+            idempotency_key,
+            request_hash: OrderIdempotencyKey::hash_request(request),
+        }
+    }
+
+    pub fn find(
+        user_id: Uuid,
+        operation: IdempotencyKeyOperations,
+        idempotency_key: &str,
+        conn: &PgConnection,
+    ) -> Result<Option<OrderIdempotencyKey>, DatabaseError> {
+        order_idempotency_keys::table
+            .filter(order_idempotency_keys::user_id.eq(user_id))
+            .filter(order_idempotency_keys::operation.eq(operation))
+            .filter(order_idempotency_keys::idempotency_key.eq(idempotency_key))
+            .first(conn)
+            .optional()
+            .to_db_error(ErrorCode::QueryError, "Could not load idempotency key")
+    }
+
+    pub fn matches_request(&self, request: &serde_json::Value) -> bool {
+        self.request_hash == OrderIdempotencyKey::hash_request(request)
+    }
+
+    /// True while the original request is still being processed
+    pub fn is_locked(&self) -> bool {
+        self.response_status.is_none()
+            && self.updated_at
+                > Utc::now().naive_utc() - Duration::minutes(IDEMPOTENCY_KEY_LOCK_TIMEOUT_MINUTES)
+    }
+
+    pub fn set_response(
+        &self,
+        response_status: u16,
+        response_body: String,
+        conn: &PgConnection,
+    ) -> Result<OrderIdempotencyKey, DatabaseError> {
+        diesel::update(self)
+            .set((
+                order_idempotency_keys::response_status.eq(response_status as i32),
+                order_idempotency_keys::response_body.eq(response_body),
+                order_idempotency_keys::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(
+                ErrorCode::UpdateError,
+                "Could not store idempotent response",
+            )
+    }
+
+    pub fn destroy(&self, conn: &PgConnection) -> Result<usize, DatabaseError> {
+        diesel::delete(self)
+            .execute(conn)
+            .to_db_error(ErrorCode::DeleteError, "Could not remove idempotency key")
+    }
+
+    fn hash_request(request: &serde_json::Value) -> String {
+        hex::encode(digest::digest(&digest::SHA256, request.to_string().as_bytes()).as_ref())
+    }
+}
@@ -37,6 +37,10 @@ pub struct OrderItem {
     pub(crate) company_fee_in_cents: i64,
     pub(crate) client_fee_in_cents: i64,
     pub refunded_quantity: i64,
+    pub tax_rule_id: Option<Uuid>,
+    /// Tax per unit on tax items. Inclusive taxes are not charged on top of the price so their
+    /// unit price is zero.
+    pub tax_in_cents: i64,
 }
 
 impl OrderItem {
@@ -49,11 +53,34 @@ impl OrderItem {
             .to_db_error(ErrorCode::QueryError, "Could not retrieve order item fees")
     }
 
+    pub fn find_discount_item(
+        &self,
+        conn: &PgConnection,
+    ) -> Result<Option<OrderItem>, DatabaseError> {
+        order_items::table
+            .filter(order_items::parent_id.eq(self.id))
+            .filter(order_items::item_type.eq(OrderItemTypes::Discount))
+            .first(conn)
+            .optional()
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not retrieve order item discount",
+            )
+    }
+
+    pub fn find_tax_items(&self, conn: &PgConnection) -> Result<Vec<OrderItem>, DatabaseError> {
+        order_items::table
+            .filter(order_items::parent_id.eq(self.id))
+            .filter(order_items::item_type.eq(OrderItemTypes::Tax))
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not retrieve order item taxes")
+    }
+
     pub(crate) fn refund_one_unit(
         &mut self,
         refund_fees: bool,
         conn: &PgConnection,
-    ) -> Result<u32, DatabaseError> {
+    ) -> Result<i64, DatabaseError> {
         if !vec![OrderStatus::Paid, OrderStatus::PartiallyPaid].contains(&self.order(conn)?.status)
         {
             return DatabaseError::business_process_error(
@@ -68,13 +95,19 @@ impl OrderItem {
         self.refunded_quantity += 1;
 
         let mut refund_amount_in_cents = self.unit_price_in_cents;
-        // Refund fees if ticket is being refunded
-        if refund_fees && self.item_type == OrderItemTypes::Tickets {
-            let fee_item = self.find_fee_item(conn)?;
-            if let Some(mut fee_item) = fee_item {
-                refund_amount_in_cents += fee_item.refund_one_unit(true, conn)? as i64;
+        if self.item_type == OrderItemTypes::Tickets {
+            // Only the discounted amount paid for the ticket is refunded
+            refund_amount_in_cents += self.refund_one_discount_unit(conn)?;
+
+            // Refund fees if ticket is being refunded
+            if refund_fees {
+                let fee_item = self.find_fee_item(conn)?;
+                if let Some(mut fee_item) = fee_item {
+                    refund_amount_in_cents += fee_item.refund_one_unit(true, conn)?;
+                }
             }
         }
+        refund_amount_in_cents += self.refund_one_tax_unit(conn)?;
 
         diesel::update(order_items::table.filter(order_items::id.eq(self.id)))
             .set((
@@ -83,7 +116,80 @@ impl OrderItem {
             ))
             .execute(conn)
             .to_db_error(ErrorCode::UpdateError, "Could not refund ticket instance")?;
-        Ok(refund_amount_in_cents as u32)
+        Ok(refund_amount_in_cents)
+    }
+
+    /// Marks one unit of the ticket's discount as refunded, returning the (negative) amount to
+    /// add to the refund. Buy X get Y discounts only cover some of the tickets so nothing is
+    /// returned once every discounted unit has been refunded.
+    fn refund_one_discount_unit(&self, conn: &PgConnection) -> Result<i64, DatabaseError> {
+        let discount_item = match self.find_discount_item(conn)? {
+            Some(discount_item) => discount_item,
+            None => return Ok(0),
+        };
+        if discount_item.refunded_quantity >= discount_item.quantity {
+            return Ok(0);
+        }
+
+        diesel::update(order_items::table.filter(order_items::id.eq(discount_item.id)))
+            .set((
+                order_items::updated_at.eq(dsl::now),
+                order_items::refunded_quantity.eq(discount_item.refunded_quantity + 1),
+            ))
+            .execute(conn)
+            .to_db_error(
+                ErrorCode::UpdateError,
+                "Could not refund order item discount",
+            )?;
+        Ok(discount_item.unit_price_in_cents + discount_item.refund_one_tax_unit(conn)?)
+    }
+
+    /// Marks one unit of each tax charged on the item as refunded, returning the amount of tax
+    /// to add to the refund. Inclusive taxes were part of the price so they add nothing.
+    fn refund_one_tax_unit(&self, conn: &PgConnection) -> Result<i64, DatabaseError> {
+        let mut refund_amount_in_cents = 0;
+        for tax_item in self.find_tax_items(conn)? {
+            if tax_item.refunded_quantity >= tax_item.quantity {
+                continue;
+            }
+
+            diesel::update(order_items::table.filter(order_items::id.eq(tax_item.id)))
+                .set((
+                    order_items::updated_at.eq(dsl::now),
+                    order_items::refunded_quantity.eq(tax_item.refunded_quantity + 1),
+                ))
+                .execute(conn)
+                .to_db_error(ErrorCode::UpdateError, "Could not refund order item tax")?;
+            refund_amount_in_cents += tax_item.unit_price_in_cents;
+        }
+        Ok(refund_amount_in_cents)
+    }
+
+    /// Adds a tax item for each of the rules that apply to this item
+    pub(crate) fn create_tax_items(
+        &self,
+        tax_rules: &[TaxRule],
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        for tax_rule in tax_rules.iter().filter(|r| r.applies_to(&self.item_type)) {
+            let tax_in_cents = tax_rule.tax_for(self.unit_price_in_cents);
+            if tax_in_cents == 0 {
+                continue;
+            }
+
+            NewTaxOrderItem {
+                order_id: self.order_id,
+                item_type: OrderItemTypes::Tax,
+                event_id: self.event_id,
+                quantity: self.quantity,
+                unit_price_in_cents: if tax_rule.inclusive { 0 } else { tax_in_cents },
+                tax_in_cents,
+                tax_rule_id: Some(tax_rule.id),
+                parent_id: Some(self.id),
+            }
+            .commit(conn)?;
+        }
+        Ok(())
     }
 
     pub(crate) fn update_fees(
@@ -91,9 +197,7 @@ impl OrderItem {
         order: &Order,
         conn: &PgConnection,
     ) -> Result<(), DatabaseError> {
-        if self.item_type == OrderItemTypes::PerUnitFees
-            || self.item_type == OrderItemTypes::EventFees
-        {
+        if self.item_type != OrderItemTypes::Tickets {
             return Ok(());
         }
 
@@ -327,19 +431,29 @@ impl OrderItem {
            oi.quantity,
            oi.refunded_quantity,
            oi.unit_price_in_cents,
+           oi.tax_in_cents,
            oi.item_type,
            CASE
-             WHEN item_type = 'PerUnitFees' THEN 'Ticket Fees'
-             WHEN item_type = 'EventFees' THEN 'Event Fees - ' || e.name
+             WHEN oi.item_type = 'PerUnitFees' THEN 'Ticket Fees'
+             WHEN oi.item_type = 'EventFees' THEN 'Event Fees - ' || e.name
+             WHEN oi.item_type = 'Discount' THEN 'Discount - ' || pc.redemption_code
+             WHEN oi.item_type = 'GiftCard' THEN 'Gift Card'
+             WHEN oi.item_type = 'Resale' THEN 'Resale - ' || e.name
+             WHEN oi.item_type = 'Tax' AND oi.unit_price_in_cents = 0 THEN 'Included Tax - ' || COALESCE(tr.name, 'Tax')
+             WHEN oi.item_type = 'Tax' THEN 'Tax - ' || COALESCE(tr.name, 'Tax')
              ELSE e.name || ' - ' || tt.name END AS description,
-           h.redemption_code as redemption_code
+           COALESCE(h.redemption_code, c.redemption_code) as redemption_code
         FROM order_items oi
-           LEFT JOIN events e ON event_id = e.id
+           LEFT JOIN events e ON oi.event_id = e.id
            LEFT JOIN ticket_pricing tp
            INNER JOIN ticket_types tt
             ON tp.ticket_type_id = tt.id
             ON oi.ticket_pricing_id = tp.id
            LEFT JOIN holds h ON oi.hold_id = h.id
+           LEFT JOIN codes c ON oi.code_id = c.id
+           LEFT JOIN order_items p ON oi.parent_id = p.id
+           LEFT JOIN codes pc ON p.code_id = pc.id
+           LEFT JOIN tax_rules tr ON oi.tax_rule_id = tr.id
         WHERE oi.order_id = $1
         ORDER BY oi.item_type DESC
         "#,
@@ -460,6 +574,86 @@ impl NewFeesOrderItem {
     }
 }
 
+#[derive(Insertable, Serialize, Deserialize, PartialEq, Debug)]
+#[table_name = "order_items"]
+pub(crate) struct NewDiscountOrderItem {
+    pub order_id: Uuid,
+    pub item_type: OrderItemTypes,
+    pub event_id: Option<Uuid>,
+    pub quantity: i64,
+    pub unit_price_in_cents: i64,
+    pub parent_id: Option<Uuid>,
+}
+
+impl NewDiscountOrderItem {
+    pub(crate) fn commit(self, conn: &PgConnection) -> Result<OrderItem, DatabaseError> {
+        diesel::insert_into(order_items::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create order item")
+    }
+}
+
+#[derive(Insertable, Serialize, Deserialize, PartialEq, Debug)]
+#[table_name = "order_items"]
+pub(crate) struct NewGiftCardOrderItem {
+    pub order_id: Uuid,
+    pub item_type: OrderItemTypes,
+    pub quantity: i64,
+    pub unit_price_in_cents: i64,
+}
+
+impl NewGiftCardOrderItem {
+    pub(crate) fn commit(self, conn: &PgConnection) -> Result<OrderItem, DatabaseError> {
+        diesel::insert_into(order_items::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create order item")
+    }
+}
+
+#[derive(Insertable, Serialize, Deserialize, PartialEq, Debug)]
+#[table_name = "order_items"]
+pub(crate) struct NewResaleOrderItem {
+    pub order_id: Uuid,
+    pub item_type: OrderItemTypes,
+    pub ticket_type_id: Option<Uuid>,
+    pub event_id: Option<Uuid>,
+    pub quantity: i64,
+    pub unit_price_in_cents: i64,
+}
+
+impl NewResaleOrderItem {
+    pub(crate) fn commit(self, conn: &PgConnection) -> Result<OrderItem, DatabaseError> {
+        diesel::insert_into(order_items::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create order item")
+    }
+}
+
+#[derive(Insertable, Serialize, Deserialize, PartialEq, Debug)]
+#[table_name = "order_items"]
+pub(crate) struct NewTaxOrderItem {
+    pub order_id: Uuid,
+    pub item_type: OrderItemTypes,
+    pub event_id: Option<Uuid>,
+    pub quantity: i64,
+    pub unit_price_in_cents: i64,
+    pub tax_in_cents: i64,
+    pub tax_rule_id: Option<Uuid>,
+    pub parent_id: Option<Uuid>,
+}
+
+impl NewTaxOrderItem {
+    pub(crate) fn commit(self, conn: &PgConnection) -> Result<OrderItem, DatabaseError> {
+        diesel::insert_into(order_items::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create order item")
+    }
+}
+
 #[derive(Deserialize, Queryable, QueryableByName, Serialize)]
 pub struct DisplayOrderItem {
     #[sql_type = "dUuid"]
@@ -476,6 +670,8 @@ pub struct DisplayOrderItem {
     pub refunded_quantity: i64,
     #[sql_type = "BigInt"]
     pub unit_price_in_cents: i64,
+    #[sql_type = "BigInt"]
+    pub tax_in_cents: i64,
     #[sql_type = "Text"]
     pub item_type: OrderItemTypes,
     #[sql_type = "Text"]
@@ -0,0 +1,112 @@
+use chrono::prelude::*;
+use diesel;
+use diesel::expression::dsl;
+use diesel::prelude::*;
+use models::*;
+use schema::order_shares;
+use utils::errors::*;
+use uuid::Uuid;
+
+/// Part of a split payment order, paid for by whoever the share's link was sent to
+#[derive(Clone, Debug, PartialEq, Identifiable, Queryable, Serialize, Deserialize)]
+pub struct OrderShare {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub share_key: Uuid,
+    pub ticket_quantity: i64,
+    pub amount_in_cents: i64,
+    pub paid_by_user_id: Option<Uuid>,
+    pub payment_id: Option<Uuid>,
+    pub paid_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl OrderShare {
+    pub(crate) fn create(
+        order_id: Uuid,
+        ticket_quantity: i64,
+        amount_in_cents: i64,
+    ) -> NewOrderShare {
+        NewOrderShare {
+            order_id,
+            share_key: Uuid::new_v4(),
+            ticket_quantity,
+            amount_in_cents,
+        }
+    }
+
+    pub fn find_by_share_key(
+        share_key: Uuid,
+        conn: &PgConnection,
+    ) -> Result<OrderShare, DatabaseError> {
+        order_shares::table
+            .filter(order_shares::share_key.eq(share_key))
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not find order share")
+    }
+
+    pub fn find_for_order(
+        order_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<OrderShare>, DatabaseError> {
+        order_shares::table
+            .filter(order_shares::order_id.eq(order_id))
+            .order_by(order_shares::created_at.asc())
+            .then_order_by(order_shares::id.asc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load order shares")
+    }
+
+    pub fn order(&self, conn: &PgConnection) -> Result<Order, DatabaseError> {
+        Order::find(self.order_id, conn)
+    }
+
+    /// Records the payment made for this share. Each share can only be paid once.
+    pub fn mark_paid(
+        &self,
+        user_id: Uuid,
+        payment_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<OrderShare, DatabaseError> {
+        let rows_affected = diesel::update(
+            order_shares::table
+                .filter(order_shares::id.eq(self.id))
+                .filter(order_shares::paid_at.is_null()),
+        )
+        .set((
+            order_shares::paid_by_user_id.eq(user_id),
+            order_shares::payment_id.eq(payment_id),
+            order_shares::paid_at.eq(Utc::now().naive_utc()),
+            order_shares::updated_at.eq(dsl::now),
+        ))
+        .execute(conn)
+        .to_db_error(ErrorCode::UpdateError, "Could not mark order share paid")?;
+        if rows_affected == 0 {
+            return DatabaseError::business_process_error("This share has already been paid");
+        }
+
+        order_shares::table
+            .find(self.id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not find order share")
+    }
+}
+
+#[derive(Insertable, Clone)]
+#[table_name = "order_shares"]
+pub(crate) struct NewOrderShare {
+    order_id: Uuid,
+    share_key: Uuid,
+    ticket_quantity: i64,
+    amount_in_cents: i64,
+}
+
+impl NewOrderShare {
+    pub(crate) fn commit(self, conn: &PgConnection) -> Result<OrderShare, DatabaseError> {
+        diesel::insert_into(order_shares::table)
+            .values(&self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create order share")
+    }
+}
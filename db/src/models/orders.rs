@@ -10,10 +10,12 @@ use diesel::sql_types::{BigInt, Bool, Integer, Nullable, Text, Uuid as dUuid};
 use itertools::Itertools;
 use log::Level;
 use models::*;
-use schema::{events, order_items, orders, organizations, payments, users};
+use schema::{
+    domain_actions, events, order_items, order_shares, orders, organizations, payments,
+    refunded_tickets, ticket_instances, users, wallets,
+};
 use serde_json;
 use std::borrow::Cow;
-use std::cmp;
 use std::collections::HashMap;
 use time::Duration;
 use utils::errors::*;
@@ -23,6 +25,8 @@ use validators::*;
 
 const CART_EXPIRY_TIME_MINUTES: i64 = 15;
 const ORDER_NUMBER_LENGTH: usize = 8;
+const GIFT_CARD_MIN_VALUE_IN_CENTS: i64 = 500;
+const GIFT_CARD_MAX_VALUE_IN_CENTS: i64 = 50_000;
 
 #[derive(Associations, Debug, Identifiable, PartialEq, Queryable)]
 #[belongs_to(User)]
@@ -130,7 +134,16 @@ impl Order {
             );
         }
 
-        // delete children order items
+        // delete children order items, including the taxes on fee items
+        let child_item_ids: Vec<Uuid> = order_items::table
+            .filter(order_items::parent_id.eq(item_id))
+            .select(order_items::id)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load child order items")?;
+        diesel::delete(order_items::table.filter(order_items::parent_id.eq_any(child_item_ids)))
+            .execute(conn)
+            .map(|_| ())
+            .to_db_error(ErrorCode::DeleteError, "Could not delete child order item")?;
         diesel::delete(order_items::table.filter(order_items::parent_id.eq(item_id)))
             .execute(conn)
             .map(|_| ())
@@ -142,12 +155,105 @@ impl Order {
             .to_db_error(ErrorCode::DeleteError, "Could not delete order item")
     }
 
+    /// Ticket items for the event that have not been refunded yet, optionally limited to a single
+    /// ticket type. Tickets transferred to someone else are left out, see
+    /// `transferred_ticket_ids`.
+    pub fn refundable_ticket_items(
+        &self,
+        event_id: Uuid,
+        ticket_type_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<Vec<RefundItem>, DatabaseError> {
+        Ok(self
+            .unrefunded_tickets(event_id, ticket_type_id, conn)?
+            .into_iter()
+            .filter(|(_, _, transferred)| !transferred)
+            .map(|(order_item_id, ticket_instance_id, _)| RefundItem {
+                order_item_id,
+                ticket_instance_id: Some(ticket_instance_id),
+            })
+            .collect())
+    }
+
+    /// Tickets for the event that have not been refunded yet but were transferred out of the
+    /// buyer's wallet, so they can no longer be refunded on this order
+    pub fn transferred_ticket_ids(
+        &self,
+        event_id: Uuid,
+        ticket_type_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<Vec<Uuid>, DatabaseError> {
+        Ok(self
+            .unrefunded_tickets(event_id, ticket_type_id, conn)?
+            .into_iter()
+            .filter(|(_, _, transferred)| *transferred)
+            .map(|(_, ticket_instance_id, _)| ticket_instance_id)
+            .collect())
+    }
+
+    fn unrefunded_tickets(
+        &self,
+        event_id: Uuid,
+        ticket_type_id: Option<Uuid>,
+        conn: &PgConnection,
+    ) -> Result<Vec<(Uuid, Uuid, bool)>, DatabaseError> {
+        let mut query = ticket_instances::table
+            .inner_join(
+                order_items::table
+                    .on(ticket_instances::order_item_id.eq(order_items::id.nullable())),
+            )
+            .inner_join(orders::table.on(order_items::order_id.eq(orders::id)))
+            .inner_join(wallets::table.on(ticket_instances::wallet_id.eq(wallets::id)))
+            .left_join(
+                refunded_tickets::table
+                    .on(refunded_tickets::ticket_instance_id.eq(ticket_instances::id)),
+            )
+            .filter(order_items::order_id.eq(self.id))
+            .filter(order_items::item_type.eq(OrderItemTypes::Tickets))
+            .filter(order_items::event_id.eq(event_id))
+            .filter(refunded_tickets::ticket_refunded_at.is_null())
+            .filter(ticket_instances::status.ne(TicketInstanceStatus::Nullified))
+            .select((
+                order_items::id,
+                ticket_instances::id,
+                sql::<Bool>(&format!("coalesce({}, true)", TRANSFERRED_SQL)),
+            ))
+            .into_boxed();
+        if let Some(ticket_type_id) = ticket_type_id {
+            query = query.filter(order_items::ticket_type_id.eq(ticket_type_id));
+        }
+
+        query
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load tickets to refund")
+    }
+
     pub fn refund(
         &self,
         refund_items: Vec<RefundItem>,
         conn: &PgConnection,
     ) -> Result<u32, DatabaseError> {
-        let mut total_to_be_refunded: u32 = 0;
+        self.refund_units(refund_items, false, conn)
+    }
+
+    /// Refunds tickets for a cancelled event. Unlike `refund` the buyer is refunded for tickets
+    /// they have since transferred, and tickets bought on resale are refunded through the resale
+    /// buyer's order, as nobody holding these tickets can use them anymore.
+    pub fn refund_for_cancellation(
+        &self,
+        refund_items: Vec<RefundItem>,
+        conn: &PgConnection,
+    ) -> Result<u32, DatabaseError> {
+        self.refund_units(refund_items, true, conn)
+    }
+
+    fn refund_units(
+        &self,
+        refund_items: Vec<RefundItem>,
+        event_cancelled: bool,
+        conn: &PgConnection,
+    ) -> Result<u32, DatabaseError> {
+        let mut total_to_be_refunded: i64 = 0;
         for refund_item in refund_items {
             let mut order_item = OrderItem::find(refund_item.order_item_id, conn)?;
 
@@ -157,6 +263,22 @@ impl Order {
                 );
             }
 
+            if order_item.item_type == OrderItemTypes::GiftCard {
+                return DatabaseError::business_process_error("Gift cards cannot be refunded");
+            } else if order_item.item_type == OrderItemTypes::Resale && !event_cancelled {
+                return DatabaseError::business_process_error(
+                    "Tickets bought on resale cannot be refunded",
+                );
+            } else if order_item.item_type == OrderItemTypes::Tax {
+                return DatabaseError::business_process_error(
+                    "Taxes are refunded with the item they were charged on",
+                );
+            } else if order_item.item_type == OrderItemTypes::Discount {
+                return DatabaseError::business_process_error(
+                    "Discounts are refunded with the ticket they were applied to",
+                );
+            }
+
             let ticket_instance = match refund_item.ticket_instance_id {
                 Some(id) => Some(TicketInstance::find(id, conn)?),
                 None => None,
@@ -164,6 +286,7 @@ impl Order {
 
             if order_item.item_type == OrderItemTypes::Tickets
                 || order_item.item_type == OrderItemTypes::PerUnitFees
+                || order_item.item_type == OrderItemTypes::Resale
             {
                 match ticket_instance {
                     None => {
@@ -183,7 +306,7 @@ impl Order {
                                 && order_item.item_type == OrderItemTypes::PerUnitFees)
                         {
                             return DatabaseError::business_process_error("Already refunded");
-                        } else if ticket_instance.was_transferred(conn)? {
+                        } else if !event_cancelled && ticket_instance.was_transferred(conn)? {
                             return DatabaseError::business_process_error(
                                 "Ticket was transferred so ineligible for refund",
                             );
@@ -196,6 +319,9 @@ impl Order {
                         if ticket_instance.status != TicketInstanceStatus::Redeemed {
                             ticket_instance.release(conn)?;
                         }
+                        if !only_refund_fees {
+                            ResaleListing::cancel_active_for_tickets(&[ticket_instance.id], conn)?;
+                        }
 
                         total_to_be_refunded += order_item.refund_one_unit(refund_fees, conn)?;
                     }
@@ -209,7 +335,10 @@ impl Order {
             total_to_be_refunded += event_fee_item.refund_one_unit(true, conn)?;
         }
 
-        Ok(total_to_be_refunded)
+        if total_to_be_refunded < 0 {
+            return DatabaseError::business_process_error("Refund amount cannot be negative");
+        }
+        Ok(total_to_be_refunded as u32)
     }
 
     fn event_fee_items_with_no_associated_items(
@@ -224,7 +353,7 @@ impl Order {
                 select id from order_items oi2
                 where oi2.order_id = order_items.order_id
                 and oi2.event_id = order_items.event_id
-                and item_type <> 'EventFees'
+                and item_type not in ('EventFees', 'Tax')
                 and oi2.refunded_quantity <> oi2.quantity
             )"))
             .select(order_items::all_columns)
@@ -235,6 +364,147 @@ impl Order {
             )
     }
 
+    /// Exchanges purchased tickets for tickets of another ticket type from the same event or from
+    /// another event in the same series. The old tickets are nullified and refunded on their
+    /// order item while the new tickets are added at current pricing, so the order total changes
+    /// by the difference in price and per ticket fees. Per event fees are left as they are. The
+    /// returned exchange records the difference, which still needs to be charged or refunded.
+    pub fn exchange(
+        &mut self,
+        ticket_instance_ids: &[Uuid],
+        ticket_type_id: Uuid,
+        current_user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<OrderExchange, DatabaseError> {
+        if self.status != OrderStatus::Paid {
+            return DatabaseError::business_process_error(
+                "Only orders that have been paid for can be exchanged",
+            );
+        }
+
+        let mut unique_ticket_instance_ids = ticket_instance_ids.to_vec();
+        unique_ticket_instance_ids.sort();
+        unique_ticket_instance_ids.dedup();
+        if unique_ticket_instance_ids.is_empty() {
+            return DatabaseError::validation_error(
+                "ticket_instance_ids",
+                "At least one ticket must be exchanged",
+            );
+        }
+
+        let mut tickets = Vec::with_capacity(unique_ticket_instance_ids.len());
+        for ticket_instance_id in unique_ticket_instance_ids {
+            tickets.push(TicketInstance::find(ticket_instance_id, conn)?);
+        }
+        let order_item_id = tickets[0].order_item_id;
+        if tickets.iter().any(|t| t.order_item_id != order_item_id) {
+            return DatabaseError::business_process_error(
+                "Tickets being exchanged must have been bought together",
+            );
+        }
+        let mut from_item = match order_item_id {
+            Some(order_item_id) => OrderItem::find(order_item_id, conn)?,
+            None => {
+                return DatabaseError::business_process_error(
+                    "Ticket does not belong to this order",
+                );
+            }
+        };
+        if from_item.order_id != self.id || from_item.item_type != OrderItemTypes::Tickets {
+            return DatabaseError::business_process_error("Ticket does not belong to this order");
+        }
+        for ticket in &tickets {
+            if ticket.status != TicketInstanceStatus::Purchased {
+                return DatabaseError::business_process_error(
+                    "Only tickets that have not been redeemed or refunded can be exchanged",
+                );
+            } else if ticket.was_transferred(conn)? {
+                return DatabaseError::business_process_error(
+                    "Ticket was transferred so ineligible for exchange",
+                );
+            }
+        }
+
+        let ticket_type = TicketType::find(ticket_type_id, conn)?;
+        if from_item.ticket_type_id == Some(ticket_type.id) {
+            return DatabaseError::business_process_error(
+                "Tickets can only be exchanged for a different ticket type",
+            );
+        }
+        let from_event = match from_item.event_id {
+            Some(event_id) => Event::find(event_id, conn)?,
+            None => {
+                return DatabaseError::no_results("Order item does not have a valid event");
+            }
+        };
+        let to_event = Event::find(ticket_type.event_id, conn)?;
+        if from_event.id != to_event.id
+            && (from_event.event_series_id.is_none()
+                || from_event.event_series_id != to_event.event_series_id)
+        {
+            return DatabaseError::business_process_error(
+                "Tickets can only be exchanged for tickets to the same event or another date in its series",
+            );
+        }
+
+        self.lock_version(conn)?;
+        let total_before_exchange = self.calculate_total(conn)?;
+
+        for ticket in &tickets {
+            from_item.refund_one_unit(true, conn)?;
+            ticket.nullify(conn)?;
+        }
+        let ticket_ids: Vec<Uuid> = tickets.iter().map(|t| t.id).collect();
+        ResaleListing::cancel_active_for_tickets(&ticket_ids, conn)?;
+
+        let quantity = tickets.len() as u32;
+        let ticket_pricing = TicketPricing::get_current_ticket_pricing(
+            ticket_type.id,
+            self.box_office_pricing,
+            false,
+            conn,
+        )?;
+        let to_item = NewTicketsOrderItem {
+            order_id: self.id,
+            item_type: OrderItemTypes::Tickets,
+            quantity: quantity as i64,
+            ticket_type_id: ticket_type.id,
+            ticket_pricing_id: ticket_pricing.id,
+            event_id: Some(ticket_type.event_id),
+            unit_price_in_cents: ticket_pricing.price_in_cents,
+            hold_id: None,
+            code_id: None,
+        }
+        .commit(conn)?;
+        TicketInstance::reserve_tickets(
+            &to_item,
+            Some(Utc::now().naive_utc() + Duration::minutes(CART_EXPIRY_TIME_MINUTES)),
+            ticket_type.id,
+            None,
+            quantity,
+            conn,
+        )?;
+        to_item.update_fees(self, conn)?;
+        let mut taxable_items = vec![to_item.clone()];
+        taxable_items.extend(to_item.find_fee_item(conn)?);
+        self.create_tax_items(&taxable_items, conn)?;
+        TicketInstance::mark_as_purchased(&to_item, self.user_id, conn)?;
+
+        OrderExchange::create(
+            self.id,
+            from_item.id,
+            to_item.id,
+            quantity as i64,
+            self.calculate_total(conn)? - total_before_exchange,
+            current_user_id,
+        )
+        .commit(conn)
+    }
+
+    pub fn exchanges(&self, conn: &PgConnection) -> Result<Vec<OrderExchange>, DatabaseError> {
+        OrderExchange::find_for_order(self.id, conn)
+    }
+
     pub fn details(
         &self,
         organization_ids: Vec<Uuid>,
@@ -267,7 +537,31 @@ impl Order {
             .to_db_error(ErrorCode::QueryError, "Error loading payments")
     }
 
+    /// Creates a draft order for the user that is not linked to their cart. These orders issue
+    /// sent comps to their sender, they are not sales and are left out of the sales reports.
+    pub(crate) fn create_back_office(
+        user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Order, DatabaseError> {
+        NewOrder {
+            user_id,
+            status: OrderStatus::Draft,
+            expires_at: None,
+            order_type: OrderTypes::BackOffice.to_string(),
+        }
+        .commit(conn)
+    }
+
     pub fn find_or_create_cart(user: &User, conn: &PgConnection) -> Result<Order, DatabaseError> {
+        // A cart partially paid with account credit stays the user's cart when they go back to
+        // changing it, the credit is returned until they check out again
+        if let Some(mut cart) = Order::find_cart_for_checkout(user.id, conn)? {
+            if cart.status == OrderStatus::PartiallyPaid && cart.paid_with_credit_only(conn)? {
+                cart.return_credit_payments(conn)?;
+                return Ok(cart);
+            }
+        }
+
         // Do a quick check to find the cart linked to the user.
         let cart = Order::find_cart_for_user(user.id, conn)?;
 
@@ -320,6 +614,117 @@ impl Order {
         Order::find(cart_id.unwrap(), conn)
     }
 
+    /// Schedules a reminder to be sent `delay` after the cart expires, in case it is not
+    /// checked out. Carts without tickets are not reminded and a cart only has one reminder
+    /// waiting to be sent at a time.
+    pub fn schedule_cart_reminder(
+        &self,
+        delay: Duration,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        let expires_at = match self.expires_at {
+            Some(expires_at) => expires_at,
+            None => return Ok(()),
+        };
+        if !self
+            .items(conn)?
+            .iter()
+            .any(|i| i.item_type == OrderItemTypes::Tickets)
+        {
+            return Ok(());
+        }
+
+        // A reminder that is already due is the one being sent, so it does not count
+        let reminder_waiting: bool = select(exists(
+            domain_actions::table
+                .filter(domain_actions::domain_action_type.eq(DomainActionTypes::SendCartReminder))
+                .filter(domain_actions::status.eq(DomainActionStatus::Pending))
+                .filter(domain_actions::main_table.eq(Tables::Orders.table_name()))
+                .filter(domain_actions::main_table_id.eq(self.id))
+                .filter(domain_actions::scheduled_at.gt(dsl::now)),
+        ))
+        .get_result(conn)
+        .to_db_error(ErrorCode::QueryError, "Could not check for cart reminders")?;
+        if reminder_waiting {
+            return Ok(());
+        }
+
+        DomainAction::create(
+            None,
+            DomainActionTypes::SendCartReminder,
+            None,
+            json!({ "order_id": self.id }),
+            Tables::Orders.table_name(),
+            self.id,
+            expires_at + delay,
+            expires_at + delay + Duration::days(1),
+            3,
+        )
+        .commit(conn)?;
+
+        Ok(())
+    }
+
+    /// Adds the tickets from this expired cart back into the user's current cart, reserving
+    /// them again with the same redemption codes and seats.
+    pub fn restore_to_cart(
+        &self,
+        user: &User,
+        conn: &PgConnection,
+    ) -> Result<Order, DatabaseError> {
+        if self.user_id != user.id {
+            return DatabaseError::business_process_error("Order does not belong to this user");
+        }
+        if self.status != OrderStatus::Draft {
+            return DatabaseError::business_process_error("Order has already been checked out");
+        }
+
+        let mut cart = Order::find_or_create_cart(user, conn)?;
+        if cart.id == self.id {
+            return Ok(cart);
+        }
+
+        let mut items: Vec<UpdateOrderItem> = vec![];
+        for item in self.items(conn)? {
+            let ticket_type_id = match item.ticket_type_id {
+                Some(ticket_type_id) if item.item_type == OrderItemTypes::Tickets => ticket_type_id,
+                _ => continue,
+            };
+            let redemption_code = match (item.hold_id, item.code_id) {
+                (Some(hold_id), _) => Some(Hold::find(hold_id, conn)?.redemption_code),
+                (None, Some(code_id)) => Some(Code::find(code_id, conn)?.redemption_code),
+                (None, None) => None,
+            };
+            // Seats stay linked to the expired item until another cart reserves them
+            let seat_ids: Vec<Uuid> = TicketInstance::find_for_order_item(item.id, conn)?
+                .into_iter()
+                .filter_map(|t| t.seat_id)
+                .collect();
+            let seat_ids = if seat_ids.is_empty() {
+                None
+            } else if seat_ids.len() as i64 != item.quantity {
+                return DatabaseError::validation_error(
+                    "seat_ids",
+                    "One or more of the requested seats are not available",
+                );
+            } else {
+                Some(seat_ids)
+            };
+            items.push(UpdateOrderItem {
+                ticket_type_id,
+                quantity: item.quantity as u32,
+                redemption_code,
+                seat_ids,
+            });
+        }
+        if items.is_empty() {
+            return DatabaseError::business_process_error("Order does not contain any tickets");
+        }
+
+        cart.update_quantities(&items, self.box_office_pricing, false, conn)?;
+        Order::find(cart.id, conn)
+    }
+
     pub fn find_cart_for_user(
         user_id: Uuid,
         conn: &PgConnection,
@@ -460,17 +865,31 @@ impl Order {
 
         let mut mapped = vec![];
         for (index, item) in items.iter().enumerate() {
+            if let Some(seat_ids) = item.seat_ids.as_ref() {
+                if seat_ids.len() as u32 != item.quantity {
+                    return DatabaseError::validation_error(
+                        "seat_ids",
+                        "Number of seats must match the quantity requested",
+                    );
+                }
+            }
             mapped.push(match &item.redemption_code {
                 Some(r) => match Hold::find_by_redemption_code(r, conn).optional()? {
-                    Some(hold) => (Some(index), Some(hold.id), Some(hold), item),
-                    None => {
-                        return DatabaseError::validation_error(
-                            "redemption_code",
-                            "Redemption code is not valid",
-                        );
-                    }
+                    Some(hold) => (Some(index), Some(hold.id), Some(hold), None, item),
+                    None => match Code::find_by_redemption_code(r, conn).optional()? {
+                        Some(code) => {
+                            code.confirm_valid_for_ticket_type(item.ticket_type_id, conn)?;
+                            (Some(index), None, None, Some(code.id), item)
+                        }
+                        None => {
+                            return DatabaseError::validation_error(
+                                "redemption_code",
+                                "Redemption code is not valid",
+                            );
+                        }
+                    },
                 },
-                None => (Some(index), None, None, item),
+                None => (Some(index), None, None, None, item),
             });
         }
 
@@ -485,22 +904,53 @@ impl Order {
                     Option<usize>,
                     Option<Uuid>,
                     Option<Hold>,
+                    Option<Uuid>,
                     &UpdateOrderItem,
                 )> = mapped
                     .iter()
-                    .filter(|(index, hold_id, _, item)| {
+                    .filter(|(index, hold_id, _, code_id, item)| {
                         index.is_some()
                             && Some(item.ticket_type_id) == current_line.ticket_type_id
                             && *hold_id == current_line.hold_id
+                            && *code_id == current_line.code_id
                     })
                     .collect();
                 let matching_result = matching_result.first();
 
                 if let Some(matching_result) = matching_result {
                     jlog!(Level::Debug, "Found an existing cart item, replacing");
-                    let (index, hold_id, hold, mut matching_line) = matching_result;
+                    let (index, hold_id, hold, code_id, mut matching_line) = matching_result;
                     index_to_remove = *index;
-                    if current_line.quantity as u32 > matching_line.quantity {
+                    if let Some(seat_ids) = matching_line.seat_ids.as_ref() {
+                        jlog!(Level::Debug, "Replacing reserved seats for cart item");
+                        TicketInstance::release_tickets(
+                            &current_line,
+                            current_line.quantity as u32,
+                            conn,
+                        )?;
+                        if seat_ids.is_empty() {
+                            jlog!(Level::Debug, "Cart item has no seats, deleting it");
+                            self.destroy_item(current_line.id, conn)?;
+                        } else {
+                            let ticket_type =
+                                TicketType::find(current_line.ticket_type_id.unwrap(), conn)?;
+                            check_ticket_limits.push(LimitCheck {
+                                limit_per_person: ticket_type.limit_per_person.clone(),
+                                ticket_type_id: ticket_type.id.clone(),
+                                event_id: ticket_type.event_id.clone(),
+                            });
+                            TicketInstance::reserve_seats(
+                                &current_line,
+                                self.expires_at,
+                                ticket_type.id,
+                                *hold_id,
+                                seat_ids,
+                                conn,
+                            )?;
+                            current_line.quantity = seat_ids.len() as i64;
+                            current_line.update(conn)?;
+                        }
+                    } else if current_line.quantity as u32 > matching_line.quantity {
                         jlog!(Level::Debug, "Reducing quantity of cart item");
                         TicketInstance::release_tickets(
                             &current_line,
@@ -536,17 +986,10 @@ impl Order {
 
                         // TODO: Move this to an external processer
                         if Some(ticket_pricing.id) != current_line.ticket_pricing_id {
-                            let mut price_in_cents = ticket_pricing.price_in_cents;
-                            if let Some(h) = hold.as_ref() {
-                                let discount = h.discount_in_cents;
-                                let hold_type = h.hold_type;
-                                price_in_cents = match hold_type {
-                                    HoldTypes::Discount => {
-                                        cmp::max(0, price_in_cents - discount.unwrap_or(0))
-                                    }
-                                    HoldTypes::Comp => 0,
-                                }
-                            }
+                            let price_in_cents = match hold.as_ref() {
+                                Some(h) => h.discounted_price(ticket_pricing.price_in_cents),
+                                None => ticket_pricing.price_in_cents,
+                            };
 
                             let order_item = NewTicketsOrderItem {
                                 order_id: self.id,
@@ -557,7 +1000,7 @@ impl Order {
                                 event_id: Some(ticket_type.event_id),
                                 unit_price_in_cents: price_in_cents,
                                 hold_id: *hold_id,
-                                code_id: None,
+                                code_id: *code_id,
                             }
                             .commit(conn)?;
                             TicketInstance::reserve_tickets(
@@ -602,7 +1045,7 @@ impl Order {
             self.set_expiry(conn)?;
         }
 
-        for (index, hold_id, hold, new_line) in mapped {
+        for (index, hold_id, hold, code_id, new_line) in mapped {
             if new_line.quantity == 0 || index.is_none() {
                 continue;
             }
@@ -622,15 +1065,10 @@ impl Order {
                 event_id: ticket_type.event_id.clone(),
             });
 
-            let mut price_in_cents = ticket_pricing.price_in_cents;
-            if let Some(h) = hold.as_ref() {
-                let discount = h.discount_in_cents;
-                let hold_type = h.hold_type;
-                price_in_cents = match hold_type {
-                    HoldTypes::Discount => cmp::max(0, price_in_cents - discount.unwrap_or(0)),
-                    HoldTypes::Comp => 0,
-                }
-            }
+            let price_in_cents = match hold.as_ref() {
+                Some(h) => h.discounted_price(ticket_pricing.price_in_cents),
+                None => ticket_pricing.price_in_cents,
+            };
             // TODO: Move this to an external processer
             let order_item = NewTicketsOrderItem {
                 order_id: self.id,
@@ -641,18 +1079,28 @@ impl Order {
                 event_id: Some(ticket_type.event_id),
                 unit_price_in_cents: price_in_cents,
                 hold_id: hold_id,
-                code_id: None,
+                code_id: code_id,
             }
             .commit(conn)?;
 
-            TicketInstance::reserve_tickets(
-                &order_item,
-                self.expires_at,
-                new_line.ticket_type_id,
-                hold_id,
-                new_line.quantity,
-                conn,
-            )?;
+            match new_line.seat_ids.as_ref() {
+                Some(seat_ids) => TicketInstance::reserve_seats(
+                    &order_item,
+                    self.expires_at,
+                    new_line.ticket_type_id,
+                    hold_id,
+                    seat_ids,
+                    conn,
+                )?,
+                None => TicketInstance::reserve_tickets(
+                    &order_item,
+                    self.expires_at,
+                    new_line.ticket_type_id,
+                    hold_id,
+                    new_line.quantity,
+                    conn,
+                )?,
+            };
         }
 
         // if the cart is empty at this point, it is effectively a new cart, remove expiration
@@ -697,6 +1145,342 @@ impl Order {
         Ok(())
     }
 
+    /// Finds the user's cart, including one that has been partially paid for, e.g. with account
+    /// credit, and is waiting for the remainder of its payment
+    pub fn find_cart_for_checkout(
+        user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Option<Order>, DatabaseError> {
+        users::table
+            .inner_join(orders::table.on(users::last_cart_id.eq(orders::id.nullable())))
+            .filter(users::id.eq(user_id))
+            .filter(orders::user_id.eq(user_id))
+            .filter(orders::status.eq_any(vec![OrderStatus::Draft, OrderStatus::PartiallyPaid]))
+            .filter(orders::order_type.eq("Cart"))
+            .filter(
+                orders::expires_at
+                    .is_null()
+                    .or(orders::expires_at.ge(dsl::now.nullable())),
+            )
+            .select(orders::all_columns)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load cart for user")
+            .optional()
+    }
+
+    /// Replaces the gift cards in the cart. Gift cards do not belong to an event so they are
+    /// managed separately from the ticket items updated by `update_quantities`.
+    pub fn update_gift_cards(
+        &mut self,
+        items: &[UpdateGiftCardItem],
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        if self.status != OrderStatus::Draft {
+            return DatabaseError::business_process_error(
+                "Gift cards can only be changed on a draft order",
+            );
+        }
+
+        let mut validation_errors = Ok(());
+        for item in items {
+            validation_errors = append_validation_error(
+                validation_errors,
+                "value_in_cents",
+                validate_greater_than(
+                    item.value_in_cents,
+                    GIFT_CARD_MIN_VALUE_IN_CENTS,
+                    "value_in_cents_too_small",
+                    "Gift card value is below the minimum allowed",
+                ),
+            );
+            validation_errors = append_validation_error(
+                validation_errors,
+                "value_in_cents",
+                validate_greater_than(
+                    GIFT_CARD_MAX_VALUE_IN_CENTS,
+                    item.value_in_cents,
+                    "value_in_cents_too_large",
+                    "Gift card value is above the maximum allowed",
+                ),
+            );
+        }
+        validation_errors?;
+
+        self.lock_version(conn)?;
+
+        for current_line in self.items(conn)? {
+            if current_line.item_type == OrderItemTypes::GiftCard {
+                self.destroy_item(current_line.id, conn)?;
+            }
+        }
+
+        for item in items.iter().filter(|i| i.quantity > 0) {
+            NewGiftCardOrderItem {
+                order_id: self.id,
+                item_type: OrderItemTypes::GiftCard,
+                quantity: item.quantity as i64,
+                unit_price_in_cents: item.value_in_cents,
+            }
+            .commit(conn)?;
+        }
+
+        let has_items = self.has_items(conn)?;
+        if has_items && self.expires_at.is_none() {
+            self.set_expiry(conn)?;
+        } else if !has_items && self.expires_at.is_some() {
+            self.remove_expiry(conn)?;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the resale listings in the cart. Each listing is held for this cart until it
+    /// expires so no one else can buy the same ticket in the meantime.
+    pub fn update_resale_listings(
+        &mut self,
+        resale_listing_ids: &[Uuid],
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        if self.status != OrderStatus::Draft {
+            return DatabaseError::business_process_error(
+                "Resale tickets can only be changed on a draft order",
+            );
+        }
+
+        self.lock_version(conn)?;
+
+        for current_line in self.items(conn)? {
+            if current_line.item_type == OrderItemTypes::Resale {
+                self.destroy_item(current_line.id, conn)?;
+            }
+        }
+
+        for resale_listing_id in resale_listing_ids.iter().unique() {
+            let listing = ResaleListing::find(*resale_listing_id, conn)?;
+            if listing.seller_id == self.user_id {
+                return DatabaseError::business_process_error(
+                    "You cannot buy your own resale listing",
+                );
+            } else if listing.status != ResaleListingStatus::Active {
+                return DatabaseError::business_process_error(
+                    "This resale listing is no longer available",
+                );
+            } else if listing.is_reserved(conn)? {
+                return DatabaseError::business_process_error(
+                    "This resale listing is already in another cart",
+                );
+            }
+
+            let ticket = TicketInstance::find(listing.ticket_instance_id, conn)?;
+            let ticket_type =
+                TicketType::find(Asset::find(ticket.asset_id, conn)?.ticket_type_id, conn)?;
+            let order_item = NewResaleOrderItem {
+                order_id: self.id,
+                item_type: OrderItemTypes::Resale,
+                ticket_type_id: Some(ticket_type.id),
+                event_id: Some(ticket_type.event_id),
+                quantity: 1,
+                unit_price_in_cents: listing.price_in_cents,
+            }
+            .commit(conn)?;
+            listing.reserve(order_item.id, conn)?;
+        }
+
+        let has_items = self.has_items(conn)?;
+        if has_items && self.expires_at.is_none() {
+            self.set_expiry(conn)?;
+        } else if !has_items && self.expires_at.is_some() {
+            self.remove_expiry(conn)?;
+        }
+
+        Ok(())
+    }
+
+    /// Splits the cart between several payers. Each share covers a number of the order's tickets
+    /// and the matching part of the total, with any remainder from the division added to the
+    /// first share. The order is marked partially paid, holding its tickets until every share is
+    /// paid or the payment window ends.
+    pub fn split(
+        &mut self,
+        ticket_quantities: &[u32],
+        conn: &PgConnection,
+    ) -> Result<Vec<OrderShare>, DatabaseError> {
+        if self.status != OrderStatus::Draft {
+            return DatabaseError::business_process_error(
+                "Only orders that have not been paid for can be split",
+            );
+        }
+
+        let ticket_count: i64 = self
+            .items(conn)?
+            .iter()
+            .filter(|i| i.item_type == OrderItemTypes::Tickets)
+            .map(|i| i.quantity - i.refunded_quantity)
+            .sum();
+        if ticket_count == 0 {
+            return DatabaseError::business_process_error("Order does not contain any tickets");
+        }
+
+        let validation_errors = append_validation_error(
+            Ok(()),
+            "ticket_quantities",
+            validate_greater_than(
+                ticket_quantities.len(),
+                2,
+                "ticket_quantities_too_few_shares",
+                "Order must be split into at least two shares",
+            ),
+        );
+        let share_ticket_count: i64 = ticket_quantities.iter().map(|q| *q as i64).sum();
+        let validation_errors = append_validation_error(
+            validation_errors,
+            "ticket_quantities",
+            if ticket_quantities.iter().any(|q| *q == 0) {
+                Err(create_validation_error(
+                    "ticket_quantities_empty_share",
+                    "Each share must include at least one ticket",
+                ))
+            } else if share_ticket_count != ticket_count {
+                let mut validation_error = create_validation_error(
+                    "ticket_quantities_mismatch",
+                    "Share ticket quantities must add up to the number of tickets in the order",
+                );
+                validation_error.add_param(Cow::from("ticket_count"), &ticket_count);
+                Err(validation_error)
+            } else {
+                Ok(())
+            },
+        );
+        validation_errors?;
+
+        let total = self.calculate_total(conn)?;
+        let amounts: Vec<i64> = ticket_quantities
+            .iter()
+            .map(|q| total * *q as i64 / ticket_count)
+            .collect();
+        let remainder = total - amounts.iter().sum::<i64>();
+
+        self.lock_version(conn)?;
+        self.mark_partially_paid(conn)?;
+
+        let mut shares = Vec::with_capacity(ticket_quantities.len());
+        for (index, (quantity, amount)) in ticket_quantities.iter().zip(amounts).enumerate() {
+            let amount = if index == 0 {
+                amount + remainder
+            } else {
+                amount
+            };
+            shares.push(OrderShare::create(self.id, *quantity as i64, amount).commit(conn)?);
+        }
+
+        if let Some(expires_at) = self.expires_at {
+            DomainAction::create(
+                None,
+                DomainActionTypes::ExpireOrderShares,
+                None,
+                json!({ "order_id": self.id }),
+                Tables::Orders.table_name(),
+                self.id,
+                expires_at,
+                expires_at + Duration::days(1),
+                3,
+            )
+            .commit(conn)?;
+        }
+
+        Ok(shares)
+    }
+
+    pub fn shares(&self, conn: &PgConnection) -> Result<Vec<OrderShare>, DatabaseError> {
+        OrderShare::find_for_order(self.id, conn)
+    }
+
+    /// Payments for the shares paid by the users holding the tickets, so that refunds for
+    /// tickets allocated to a share go back to whoever paid for it
+    pub fn share_payment_ids_for_tickets(
+        &self,
+        ticket_instance_ids: &[Uuid],
+        conn: &PgConnection,
+    ) -> Result<Vec<Uuid>, DatabaseError> {
+        let payment_ids: Vec<Option<Uuid>> = order_shares::table
+            .inner_join(wallets::table.on(order_shares::paid_by_user_id.eq(wallets::user_id)))
+            .inner_join(ticket_instances::table.on(ticket_instances::wallet_id.eq(wallets::id)))
+            .filter(order_shares::order_id.eq(self.id))
+            .filter(ticket_instances::id.eq_any(ticket_instance_ids))
+            .select(order_shares::payment_id)
+            .distinct()
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load share payments")?;
+        Ok(payment_ids.into_iter().filter_map(|id| id).collect())
+    }
+
+    /// Cancels a split order whose payment window ended before every share was paid. Its
+    /// reservations have already lapsed so the tickets are available again, any payments made
+    /// for shares still need to be refunded.
+    pub fn cancel_unpaid_split(&mut self, conn: &PgConnection) -> Result<(), DatabaseError> {
+        if self.status != OrderStatus::PartiallyPaid || self.shares(conn)?.is_empty() {
+            return DatabaseError::business_process_error(
+                "Only split orders that are waiting for payment can be cancelled",
+            );
+        }
+
+        self.update_status(OrderStatus::Cancelled, conn)
+    }
+
+    /// Moves the tickets covered by each paid share into the wallet of the user who paid for it.
+    /// Tickets for shares paid by someone else, such as the order's owner, stay with the owner.
+    fn allocate_shares(&self, conn: &PgConnection) -> Result<(), DatabaseError> {
+        let shares = self.shares(conn)?;
+        if shares.is_empty() {
+            return Ok(());
+        }
+
+        let mut tickets = Vec::new();
+        for item in self
+            .items(conn)?
+            .iter()
+            .filter(|i| i.item_type == OrderItemTypes::Tickets)
+        {
+            tickets.append(&mut TicketInstance::find_for_order_item(item.id, conn)?);
+        }
+        let mut tickets = tickets.into_iter();
+
+        for share in shares {
+            let share_tickets: Vec<TicketInstance> = tickets
+                .by_ref()
+                .take(share.ticket_quantity as usize)
+                .collect();
+            let paid_by_user_id = match share.paid_by_user_id {
+                Some(paid_by_user_id) if paid_by_user_id != self.user_id => paid_by_user_id,
+                _ => continue,
+            };
+
+            let wallet = Wallet::find_default_for_user(paid_by_user_id, conn)?;
+            for ticket in share_tickets {
+                ticket.set_wallet(&wallet, conn)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the user has paid for another order that was created after this one, e.g. a new
+    /// cart after this one expired
+    pub fn superseded_by_paid_order(&self, conn: &PgConnection) -> Result<bool, DatabaseError> {
+        select(exists(
+            orders::table
+                .filter(orders::user_id.eq(self.user_id))
+                .filter(orders::id.ne(self.id))
+                .filter(orders::status.eq(OrderStatus::Paid))
+                .filter(orders::created_at.gt(self.created_at)),
+        ))
+        .get_result(conn)
+        .to_db_error(
+            ErrorCode::QueryError,
+            "Could not check for later paid orders",
+        )
+    }
+
     pub fn has_items(&self, conn: &PgConnection) -> Result<bool, DatabaseError> {
         select(exists(
             order_items::table.filter(order_items::order_id.eq(self.id)),
@@ -713,7 +1497,9 @@ impl Order {
 
         for o in items {
             match o.item_type {
-                OrderItemTypes::EventFees => self.destroy_item(o.id, conn)?,
+                OrderItemTypes::EventFees | OrderItemTypes::Discount | OrderItemTypes::Tax => {
+                    self.destroy_item(o.id, conn)?
+                }
                 _ => {}
             }
         }
@@ -773,6 +1559,63 @@ impl Order {
             }
         }
 
+        self.update_discounts(conn)?;
+        self.create_tax_items(&self.items(conn)?, conn)
+    }
+
+    /// Adds tax items for the given items using the tax rules of each item's event
+    fn create_tax_items(
+        &self,
+        items: &[OrderItem],
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        let mut tax_rules_per_event: HashMap<Uuid, Vec<TaxRule>> = HashMap::new();
+        for item in items {
+            let event_id = match item.event_id {
+                Some(event_id) => event_id,
+                None => continue,
+            };
+            if !tax_rules_per_event.contains_key(&event_id) {
+                let event = Event::find(event_id, conn)?;
+                tax_rules_per_event.insert(event_id, TaxRule::find_for_event(&event, conn)?);
+            }
+            item.create_tax_items(&tax_rules_per_event[&event_id], conn)?;
+        }
+        Ok(())
+    }
+
+    /// Tax on the items that have not been refunded, including taxes that are part of the price
+    pub fn tax_in_cents(&self, conn: &PgConnection) -> Result<i64, DatabaseError> {
+        Ok(self
+            .items(conn)?
+            .iter()
+            .filter(|i| i.item_type == OrderItemTypes::Tax)
+            .map(|i| i.tax_in_cents * (i.quantity - i.refunded_quantity))
+            .sum())
+    }
+
+    /// Adds a discount item for each ticket item that qualifies for its code's discount. Codes
+    /// can depend on the quantity or total of everything bought with them so discounts are kept
+    /// separate from the ticket price.
+    fn update_discounts(&self, conn: &PgConnection) -> Result<(), DatabaseError> {
+        let items = self.items(conn)?;
+        let code_ids: Vec<Uuid> = items.iter().filter_map(|i| i.code_id).unique().collect();
+        for code_id in code_ids {
+            let code = Code::find(code_id, conn)?;
+            for (order_item_id, quantity, discount_in_cents) in code.discounts_for_items(&items) {
+                let order_item = items.iter().find(|i| i.id == order_item_id);
+                NewDiscountOrderItem {
+                    order_id: self.id,
+                    item_type: OrderItemTypes::Discount,
+                    event_id: order_item.and_then(|i| i.event_id),
+                    quantity,
+                    unit_price_in_cents: -discount_in_cents,
+                    parent_id: Some(order_item_id),
+                }
+                .commit(conn)?;
+            }
+        }
+
         Ok(())
     }
 
@@ -889,6 +1732,8 @@ impl Order {
             items: self.items_for_display(conn)?,
             limited_tickets_remaining,
             total_in_cents: self.calculate_total(conn)?,
+            tax_in_cents: self.tax_in_cents(conn)?,
+            total_paid_in_cents: self.total_paid(conn)?,
             seconds_until_expiry,
             user_id: self.user_id,
             note: self.note.clone(),
@@ -946,6 +1791,7 @@ impl Order {
             PaymentStatus::Completed,
             PaymentMethods::External,
             "External".to_string(),
+            None,
             external_reference,
             amount,
             None,
@@ -958,6 +1804,7 @@ impl Order {
         current_user_id: Uuid,
         amount: i64,
         provider: String,
+        merchant_account_id: Option<String>,
         external_reference: String,
         status: PaymentStatus,
         provider_data: serde_json::Value,
@@ -969,6 +1816,7 @@ impl Order {
             status,
             PaymentMethods::CreditCard,
             provider,
+            merchant_account_id,
             Some(external_reference),
             amount,
             Some(provider_data),
@@ -977,6 +1825,163 @@ impl Order {
         self.add_payment(payment, current_user_id, conn)
     }
 
+    /// Records the card payment for the difference owed on an exchange. The order stays paid so
+    /// the payment is added without going through the checkout states.
+    pub fn add_exchange_payment(
+        &self,
+        exchange: &OrderExchange,
+        current_user_id: Uuid,
+        provider: String,
+        merchant_account_id: Option<String>,
+        external_reference: String,
+        status: PaymentStatus,
+        provider_data: serde_json::Value,
+        conn: &PgConnection,
+    ) -> Result<Payment, DatabaseError> {
+        if exchange.order_id != self.id {
+            return DatabaseError::business_process_error("Exchange does not belong to this order");
+        } else if exchange.difference_in_cents <= 0 {
+            return DatabaseError::business_process_error(
+                "Exchange does not have a difference to be paid",
+            );
+        }
+
+        Payment::create(
+            self.id,
+            current_user_id,
+            status,
+            PaymentMethods::CreditCard,
+            provider,
+            merchant_account_id,
+            Some(external_reference),
+            exchange.difference_in_cents,
+            Some(provider_data),
+        )
+        .commit(Some(current_user_id), conn)
+    }
+
+    /// Pays for some or all of the order with the account credit of the order's user. Any
+    /// remainder can be paid with another payment method, the order stays partially paid until
+    /// then.
+    pub fn add_credit_payment(
+        &mut self,
+        current_user_id: Uuid,
+        amount: i64,
+        conn: &PgConnection,
+    ) -> Result<Payment, DatabaseError> {
+        if current_user_id != self.user_id {
+            return DatabaseError::business_process_error(
+                "Only the user who owns the order can pay with their account credit",
+            );
+        } else if amount <= 0 {
+            return DatabaseError::business_process_error(
+                "Credit payment amount must be greater than zero",
+            );
+        } else if amount > self.calculate_total(conn)? - self.total_paid(conn)? {
+            return DatabaseError::business_process_error(
+                "Credit payment amount is greater than the amount due",
+            );
+        }
+
+        let credit_transaction = CreditTransaction::create(
+            self.user_id,
+            CreditTransactionTypes::Payment,
+            -amount,
+            Some(self.id),
+            None,
+            Some(current_user_id),
+        )
+        .commit(conn)?;
+
+        let payment = Payment::create(
+            self.id,
+            current_user_id,
+            PaymentStatus::Completed,
+            PaymentMethods::Credit,
+            "Credit".to_string(),
+            None,
+            Some(credit_transaction.id.to_string()),
+            amount,
+            None,
+        );
+
+        let payment = self.add_payment(payment, current_user_id, conn)?;
+        if self.status == OrderStatus::PartiallyPaid {
+            if let Some(expires_at) = self.expires_at {
+                DomainAction::create(
+                    None,
+                    DomainActionTypes::ReturnCartCredit,
+                    None,
+                    json!({ "order_id": self.id }),
+                    Tables::Orders.table_name(),
+                    self.id,
+                    expires_at,
+                    expires_at + Duration::days(1),
+                    3,
+                )
+                .commit(conn)?;
+            }
+        }
+        Ok(payment)
+    }
+
+    /// Whether the order has been partially paid for with account credit alone. Split orders are
+    /// refunded by their payers' payment methods when they expire so are excluded.
+    pub fn paid_with_credit_only(&self, conn: &PgConnection) -> Result<bool, DatabaseError> {
+        if !self.shares(conn)?.is_empty() {
+            return Ok(false);
+        }
+        let payments: Vec<Payment> = self
+            .payments(conn)?
+            .into_iter()
+            .filter(|p| p.status == PaymentStatus::Completed)
+            .collect();
+        Ok(!payments.is_empty()
+            && payments
+                .iter()
+                .all(|p| p.payment_method == PaymentMethods::Credit))
+    }
+
+    /// Returns the account credit paid towards a cart that was not checked out, either because
+    /// the user went back to changing it or because it expired. The cart goes back to being a
+    /// draft.
+    pub fn return_credit_payments(&mut self, conn: &PgConnection) -> Result<(), DatabaseError> {
+        if self.status != OrderStatus::PartiallyPaid || !self.paid_with_credit_only(conn)? {
+            return DatabaseError::business_process_error(
+                "Only carts partially paid with account credit can have their credit returned",
+            );
+        }
+        self.lock_version(conn)?;
+
+        for payment in self.payments(conn)? {
+            if payment.status != PaymentStatus::Completed {
+                continue;
+            }
+            let amount_to_return = payment.amount - payment.refunded_amount(conn)?;
+            if amount_to_return <= 0 {
+                continue;
+            }
+
+            let credit_transaction = CreditTransaction::create(
+                self.user_id,
+                CreditTransactionTypes::Refund,
+                amount_to_return,
+                Some(self.id),
+                None,
+                None,
+            )
+            .commit(conn)?;
+            payment.log_refund(
+                self.user_id,
+                amount_to_return as u32,
+                Some(json!({ "credit_transaction_id": credit_transaction.id })),
+                conn,
+            )?;
+        }
+
+        self.update_status(OrderStatus::Draft, conn)
+    }
+
     fn add_payment(
         &mut self,
         payment: NewPayment,
@@ -998,7 +2003,7 @@ impl Order {
             }
         }
 
-        let p = payment.commit(current_user_id, conn)?;
+        let p = payment.commit(Some(current_user_id), conn)?;
         self.complete_if_fully_paid(conn)?;
         Ok(p)
     }
@@ -1040,6 +2045,7 @@ impl Order {
 
         self.updated_at = db_record.updated_at;
         self.status = db_record.status;
+        self.expires_at = db_record.expires_at;
 
         //Extend the reserved_until time for tickets associated with this order
         let order_items = OrderItem::find_for_order(db_record.id, conn)?;
@@ -1055,13 +2061,25 @@ impl Order {
         &mut self,
         conn: &PgConnection,
     ) -> Result<(), DatabaseError> {
-        if self.total_paid(conn)? >= self.calculate_total(conn)? {
+        // Orders that are already paid, e.g. one being charged for an exchange, keep their
+        // tickets as they are
+        if self.status != OrderStatus::Paid
+            && self.total_paid(conn)? >= self.calculate_total(conn)?
+        {
             self.update_status(OrderStatus::Paid, conn)?;
             //Mark tickets as Purchased
             let order_items = OrderItem::find_for_order(self.id, conn)?;
             for item in &order_items {
                 TicketInstance::mark_as_purchased(item, self.user_id, conn)?;
+                if item.item_type == OrderItemTypes::GiftCard {
+                    for _ in 0..item.quantity {
+                        GiftCard::create_for_order_item(item, conn)?;
+                    }
+                } else if item.item_type == OrderItemTypes::Resale {
+                    ResaleListing::complete_sale(item, self.user_id, conn)?;
+                }
             }
+            self.allocate_shares(conn)?;
             let cart_user: Option<User> = users::table
                 .filter(users::last_cart_id.eq(self.id))
                 .get_result(conn)
@@ -1078,6 +2096,8 @@ impl Order {
         Ok(())
     }
 
+    /// Amount paid for the order less any refunds. Disputed payments still count as paid until
+    /// the dispute is lost and the funds are withdrawn.
     pub fn total_paid(&self, conn: &PgConnection) -> Result<i64, DatabaseError> {
         #[derive(QueryableByName)]
         struct ResultForSum {
@@ -1085,7 +2105,7 @@ impl Order {
             s: Option<i64>,
         };
         let query = diesel::sql_query(
-            "SELECT CAST(SUM(amount) as BigInt) as s FROM payments WHERE order_id = $1 AND status='Completed';",
+            "SELECT CAST(SUM(amount) as BigInt) as s FROM payments WHERE order_id = $1 AND status IN ('Completed', 'Disputed', 'Refunded');",
         )
         .bind::<diesel::sql_types::Uuid, _>(self.id);
 
@@ -1244,6 +2264,8 @@ pub struct DisplayOrder {
     pub items: Vec<DisplayOrderItem>,
     pub limited_tickets_remaining: Vec<TicketsRemaining>,
     pub total_in_cents: i64,
+    pub tax_in_cents: i64,
+    pub total_paid_in_cents: i64,
     pub user_id: Uuid,
     pub note: Option<String>,
     pub order_number: String,
@@ -1255,6 +2277,14 @@ pub struct UpdateOrderItem {
     pub ticket_type_id: Uuid,
     pub quantity: u32,
     pub redemption_code: Option<String>,
+    #[serde(default)]
+    pub seat_ids: Option<Vec<Uuid>>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct UpdateGiftCardItem {
+    pub value_in_cents: i64,
+    pub quantity: u32,
 }
 
 #[test]
@@ -47,6 +47,9 @@ pub struct Organization {
     pub fee_schedule_id: Uuid,
     pub client_event_fee_in_cents: i64,
     pub company_event_fee_in_cents: i64,
+    pub payment_provider: String,
+    pub merchant_account_id: Option<String>,
+    pub resale_price_cap_percent: Option<i32>,
 }
 
 #[derive(Serialize)]
@@ -530,6 +533,53 @@ impl Organization {
             )
     }
 
+    /// Routes charges for this organization's events through the given provider and merchant
+    /// account. Validating that the provider is available is left to the caller.
+    pub fn set_payment_provider(
+        &self,
+        payment_provider: &str,
+        merchant_account_id: Option<String>,
+        conn: &PgConnection,
+    ) -> Result<Organization, DatabaseError> {
+        diesel::update(self)
+            .set((
+                organizations::payment_provider.eq(payment_provider),
+                organizations::merchant_account_id.eq(merchant_account_id),
+                organizations::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(
+                ErrorCode::UpdateError,
+                "Could not set the payment provider for this organization",
+            )
+    }
+
+    /// Allows fans to resell tickets to this organization's events for up to the given percentage
+    /// of the price they paid. Resale is disabled when no cap is set.
+    pub fn set_resale_price_cap(
+        &self,
+        resale_price_cap_percent: Option<i32>,
+        conn: &PgConnection,
+    ) -> Result<Organization, DatabaseError> {
+        if resale_price_cap_percent.map_or(false, |cap| cap < 0) {
+            return DatabaseError::validation_error(
+                "resale_price_cap_percent",
+                "Resale price cap cannot be negative",
+            );
+        }
+
+        diesel::update(self)
+            .set((
+                organizations::resale_price_cap_percent.eq(resale_price_cap_percent),
+                organizations::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(
+                ErrorCode::UpdateError,
+                "Could not set the resale price cap for this organization",
+            )
+    }
+
     pub fn search_fans(
         &self,
         query: Option<String>,
@@ -18,6 +18,9 @@ pub struct PaymentMethod {
     pub provider_data: serde_json::Value,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// Merchant account the repeat charge token was created on, `None` for the provider's
+    /// default account
+    pub merchant_account_id: Option<String>,
 }
 
 #[derive(AsChangeset, Default, Deserialize)]
@@ -32,6 +35,7 @@ impl PaymentMethod {
         name: String,
         is_default: bool,
         provider: String,
+        merchant_account_id: Option<String>,
         data: serde_json::Value,
     ) -> NewPaymentMethod {
         NewPaymentMethod {
@@ -39,6 +43,7 @@ impl PaymentMethod {
             name,
             is_default,
             provider,
+            merchant_account_id,
             provider_data: data,
         }
     }
@@ -119,6 +124,7 @@ pub struct NewPaymentMethod {
     name: String,
     is_default: bool,
     provider: String,
+    merchant_account_id: Option<String>,
     provider_data: serde_json::Value,
 }
 
@@ -24,6 +24,7 @@ pub struct Payment {
     raw_data: Option<serde_json::Value>,
     created_at: NaiveDateTime,
     updated_at: NaiveDateTime,
+    pub merchant_account_id: Option<String>,
 }
 
 impl Payment {
@@ -33,6 +34,7 @@ impl Payment {
         status: PaymentStatus,
         payment_method: PaymentMethods,
         provider: String,
+        merchant_account_id: Option<String>,
         external_reference: Option<String>,
         amount: i64,
         raw_data: Option<serde_json::Value>,
@@ -43,18 +45,71 @@ impl Payment {
             status,
             payment_method,
             provider,
+            merchant_account_id,
             external_reference,
             amount,
             raw_data,
         }
     }
 
+    pub fn find_by_external_reference(
+        provider: &str,
+        external_reference: &str,
+        conn: &PgConnection,
+    ) -> Result<Option<Payment>, DatabaseError> {
+        // Refunds share the external reference of the payment they were made against
+        payments::table
+            .filter(payments::provider.eq(provider))
+            .filter(payments::external_reference.eq(external_reference))
+            .filter(payments::status.ne(PaymentStatus::Refunded))
+            .order_by(payments::created_at.asc())
+            .first(conn)
+            .optional()
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not retrieve payment by external reference",
+            )
+    }
+
+    /// Total amount refunded against this payment so far
+    pub fn refunded_amount(&self, conn: &PgConnection) -> Result<i64, DatabaseError> {
+        let refunds: Vec<i64> = payments::table
+            .filter(payments::order_id.eq(self.order_id))
+            .filter(payments::provider.eq(&self.provider))
+            .filter(payments::external_reference.eq(&self.external_reference))
+            .filter(payments::status.eq(PaymentStatus::Refunded))
+            .select(payments::amount)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load refunds for payment")?;
+        Ok(-refunds.iter().sum::<i64>())
+    }
+
     pub fn log_refund(
         &self,
         current_user_id: Uuid,
         refund_amount: u32,
         refund_data: Option<serde_json::Value>,
         conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        self.record_refund(Some(current_user_id), refund_amount, refund_data, conn)
+    }
+
+    /// Records a refund that was made directly with the payment provider, e.g. from its dashboard
+    pub fn log_external_refund(
+        &self,
+        refund_amount: u32,
+        refund_data: Option<serde_json::Value>,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        self.record_refund(None, refund_amount, refund_data, conn)
+    }
+
+    fn record_refund(
+        &self,
+        current_user_id: Option<Uuid>,
+        refund_amount: u32,
+        refund_data: Option<serde_json::Value>,
+        conn: &PgConnection,
     ) -> Result<(), DatabaseError> {
         Payment::create(
             self.order_id,
@@ -62,6 +117,7 @@ impl Payment {
             PaymentStatus::Refunded,
             self.payment_method,
             self.provider.clone(),
+            self.merchant_account_id.clone(),
             self.external_reference.clone(),
             -(refund_amount as i64),
             refund_data.clone(),
@@ -73,13 +129,76 @@ impl Payment {
             "Payment was refunded".to_string(),
             Tables::Payments,
             Some(self.id),
-            Some(current_user_id),
+            current_user_id,
             refund_data,
         )
         .commit(conn)?;
         Ok(())
     }
 
+    pub fn mark_disputed(
+        &self,
+        raw_data: serde_json::Value,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        if self.status == PaymentStatus::Disputed {
+            return Ok(());
+        }
+        self.update_status(PaymentStatus::Disputed, conn)?;
+
+        DomainEvent::create(
+            DomainEventTypes::PaymentDisputed,
+            "Payment was disputed".to_string(),
+            Tables::Payments,
+            Some(self.id),
+            None,
+            Some(raw_data),
+        )
+        .commit(conn)?;
+        Ok(())
+    }
+
+    /// Only payments that were authorized but never captured can fail
+    pub fn mark_failed(
+        &self,
+        raw_data: serde_json::Value,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        if self.status != PaymentStatus::Authorized {
+            return Ok(());
+        }
+        self.update_status(PaymentStatus::Failed, conn)?;
+
+        DomainEvent::create(
+            DomainEventTypes::PaymentFailed,
+            "Payment failed".to_string(),
+            Tables::Payments,
+            Some(self.id),
+            None,
+            Some(raw_data),
+        )
+        .commit(conn)?;
+        Ok(())
+    }
+
+    fn update_status(
+        &self,
+        status: PaymentStatus,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        diesel::update(self)
+            .set((
+                payments::status.eq(status),
+                payments::updated_at.eq(dsl::now),
+            ))
+            .execute(conn)
+            .to_db_error(
+                ErrorCode::UpdateError,
+                "Could not change the status of payment",
+            )?;
+        Ok(())
+    }
+
     pub fn mark_complete(
         &self,
         raw_data: serde_json::Value,
@@ -140,13 +259,14 @@ pub struct NewPayment {
     external_reference: Option<String>,
     amount: i64,
     provider: String,
+    merchant_account_id: Option<String>,
     raw_data: Option<serde_json::Value>,
 }
 
 impl NewPayment {
     pub(crate) fn commit(
         self,
-        current_user_id: Uuid,
+        current_user_id: Option<Uuid>,
         conn: &PgConnection,
     ) -> Result<Payment, DatabaseError> {
         let res: Payment = diesel::insert_into(payments::table)
@@ -159,7 +279,7 @@ impl NewPayment {
             "Payment created".to_string(),
             Tables::Payments,
             Some(res.id),
-            Some(current_user_id),
+            current_user_id,
             self.raw_data,
         )
         .commit(conn)?;
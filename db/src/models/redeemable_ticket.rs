@@ -43,4 +43,10 @@ pub struct RedeemableTicket {
     pub venue_id: Option<Uuid>,
     #[sql_type = "Nullable<Text>"]
     pub venue_name: Option<String>,
+    #[sql_type = "Nullable<Text>"]
+    pub section_name: Option<String>,
+    #[sql_type = "Nullable<Text>"]
+    pub row_name: Option<String>,
+    #[sql_type = "Nullable<Text>"]
+    pub seat_number: Option<String>,
 }
@@ -1,7 +1,7 @@
 use chrono::prelude::*;
 use diesel;
 use diesel::prelude::*;
-use diesel::sql_types::{BigInt, Nullable, Text, Timestamp, Uuid as dUuid};
+use diesel::sql_types::{BigInt, Bool, Nullable, Text, Timestamp, Uuid as dUuid};
 use itertools::Itertools;
 use models::*;
 use std::collections::HashMap;
@@ -21,19 +21,28 @@ pub struct TransactionReportRow {
     #[sql_type = "BigInt"]
     pub refunded_quantity: i64,
     #[sql_type = "BigInt"]
+    pub exchanged_quantity: i64,
+    #[sql_type = "BigInt"]
     pub unit_price_in_cents: i64,
     #[sql_type = "BigInt"]
     pub gross: i64,
     #[sql_type = "BigInt"]
+    pub discount_in_cents: i64,
+    #[sql_type = "BigInt"]
     pub company_fee_in_cents: i64,
     #[sql_type = "BigInt"]
     pub client_fee_in_cents: i64,
+    #[sql_type = "BigInt"]
+    pub tax_in_cents: i64,
     #[sql_type = "Text"]
     pub order_type: OrderTypes,
     #[sql_type = "Nullable<Text>"]
     pub payment_method: Option<PaymentMethods>,
     #[sql_type = "Timestamp"]
     pub transaction_date: NaiveDateTime,
+    /// Set on the tickets an exchange added to the order, dated when they were exchanged
+    #[sql_type = "Nullable<dUuid>"]
+    pub order_exchange_id: Option<Uuid>,
     #[sql_type = "Nullable<Text>"]
     pub redemption_code: Option<String>,
     #[sql_type = "dUuid"]
@@ -50,6 +59,8 @@ pub struct EventSummarySalesResult {
     pub sales: Vec<EventSummarySalesRow>,
     pub ticket_fees: Vec<EventSummaryFeesRow>,
     pub other_fees: Vec<EventSummaryOtherFees>,
+    pub taxes: Vec<EventSummaryTaxesRow>,
+    pub resales: Option<EventSummaryResalesRow>,
 }
 
 impl Default for EventSummarySalesResult {
@@ -59,6 +70,8 @@ impl Default for EventSummarySalesResult {
             sales: vec![],
             ticket_fees: vec![],
             other_fees: vec![],
+            taxes: vec![],
+            resales: None,
         }
     }
 }
@@ -141,6 +154,45 @@ pub struct EventSummaryOtherFees {
     pub client_fee_in_cents: i64,
 }
 
+/// Tax collected per tax rule. Inclusive taxes were part of the ticket and fee prices.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Queryable, QueryableByName)]
+pub struct EventSummaryTaxesRow {
+    #[sql_type = "dUuid"]
+    pub event_id: Uuid,
+
+    #[sql_type = "Nullable<dUuid>"]
+    pub tax_rule_id: Option<Uuid>,
+    #[sql_type = "Text"]
+    pub tax_name: String,
+    #[sql_type = "BigInt"]
+    pub rate_in_basis_points: i64,
+    #[sql_type = "Bool"]
+    pub inclusive: bool,
+    #[sql_type = "BigInt"]
+    pub total_ticket_tax_in_cents: i64,
+    #[sql_type = "BigInt"]
+    pub total_fee_tax_in_cents: i64,
+    #[sql_type = "BigInt"]
+    pub total_tax_in_cents: i64,
+}
+
+/// Tickets resold by fans. The price less the fees was paid to the organization, which owes the
+/// seller payouts to the sellers.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Queryable, QueryableByName)]
+pub struct EventSummaryResalesRow {
+    #[sql_type = "dUuid"]
+    pub event_id: Uuid,
+
+    #[sql_type = "BigInt"]
+    pub resold_count: i64,
+    #[sql_type = "BigInt"]
+    pub total_resale_price_in_cents: i64,
+    #[sql_type = "BigInt"]
+    pub total_fee_in_cents: i64,
+    #[sql_type = "BigInt"]
+    pub total_seller_payout_in_cents: i64,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct TicketCountResult {
     pub sales: Option<TicketCountSalesRow>,
@@ -223,6 +275,36 @@ pub struct TicketCountSalesRow {
     pub client_online_fees_in_cents: i64,
 }
 
+/// Sales through a single code or hold. Remaining uses is the number of paid orders the code can
+/// still be used on, or the number of tickets left in the hold.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Queryable, QueryableByName)]
+pub struct PromoCodePerformanceRow {
+    #[sql_type = "Text"]
+    pub redemption_type: String,
+    #[sql_type = "dUuid"]
+    pub id: Uuid,
+    #[sql_type = "dUuid"]
+    pub event_id: Uuid,
+    #[sql_type = "Text"]
+    pub event_name: String,
+    #[sql_type = "Text"]
+    pub name: String,
+    #[sql_type = "Text"]
+    pub redemption_code: String,
+    #[sql_type = "Text"]
+    pub discount_type: String,
+    #[sql_type = "BigInt"]
+    pub tickets_sold: i64,
+    #[sql_type = "BigInt"]
+    pub gross: i64,
+    #[sql_type = "BigInt"]
+    pub discount_in_cents: i64,
+    #[sql_type = "BigInt"]
+    pub unique_buyers: i64,
+    #[sql_type = "BigInt"]
+    pub remaining_uses: i64,
+}
+
 impl Report {
     pub fn transaction_detail_report(
         event_id: Option<Uuid>,
@@ -243,6 +325,23 @@ impl Report {
         Ok(transaction_rows)
     }
 
+    pub fn promo_code_performance_report(
+        event_id: Option<Uuid>,
+        organization_id: Option<Uuid>,
+        start: Option<NaiveDateTime>,
+        end: Option<NaiveDateTime>,
+        conn: &PgConnection,
+    ) -> Result<Vec<PromoCodePerformanceRow>, DatabaseError> {
+        let query = include_str!("../queries/reports/reports_promo_code_performance.sql");
+        diesel::sql_query(query)
+            .bind::<Nullable<dUuid>, _>(event_id)
+            .bind::<Nullable<dUuid>, _>(organization_id)
+            .bind::<Nullable<Timestamp>, _>(start)
+            .bind::<Nullable<Timestamp>, _>(end)
+            .get_results(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not fetch report results")
+    }
+
     pub fn summary_event_report(
         event_id: Uuid,
         start: Option<NaiveDateTime>,
@@ -344,6 +443,44 @@ impl Report {
                 .push(row);
         }
 
+        //Now get the taxes results
+        let query_taxes = include_str!("../queries/reports/reports_event_summary_taxes.sql");
+        let q = diesel::sql_query(query_taxes)
+            .bind::<Nullable<dUuid>, _>(event_id)
+            .bind::<Nullable<dUuid>, _>(organization_id)
+            .bind::<Nullable<Timestamp>, _>(start)
+            .bind::<Nullable<Timestamp>, _>(end);
+
+        let taxes_rows: Vec<EventSummaryTaxesRow> = q
+            .get_results(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not fetch report tax results")?;
+
+        let mut taxes_hash: HashMap<Uuid, Vec<EventSummaryTaxesRow>> = HashMap::new();
+        for row in taxes_rows {
+            taxes_hash
+                .entry(row.event_id)
+                .or_insert(Vec::<EventSummaryTaxesRow>::new())
+                .push(row);
+        }
+
+        //Now get the resales results
+        let query_resales = include_str!("../queries/reports/reports_event_summary_resales.sql");
+        let q = diesel::sql_query(query_resales)
+            .bind::<Nullable<dUuid>, _>(event_id)
+            .bind::<Nullable<dUuid>, _>(organization_id)
+            .bind::<Nullable<Timestamp>, _>(start)
+            .bind::<Nullable<Timestamp>, _>(end);
+
+        let resales_rows: Vec<EventSummaryResalesRow> = q.get_results(conn).to_db_error(
+            ErrorCode::QueryError,
+            "Could not fetch report resale results",
+        )?;
+
+        let mut resales_hash: HashMap<Uuid, EventSummaryResalesRow> = HashMap::new();
+        for row in resales_rows {
+            resales_hash.insert(row.event_id, row);
+        }
+
         let mut result = Vec::<EventSummarySalesResult>::new();
 
         // assume that an event must have sales in order to have other fees
@@ -353,6 +490,8 @@ impl Report {
                 sales: sales.into_iter().collect_vec(),
                 ticket_fees: fees_hash.get(&event_id).unwrap_or(&vec![]).to_vec(),
                 other_fees: other_fees_hash.get(&event_id).unwrap_or(&vec![]).to_vec(),
+                taxes: taxes_hash.get(&event_id).unwrap_or(&vec![]).to_vec(),
+                resales: resales_hash.remove(&event_id),
             })
         }
         //Then get the fees summary
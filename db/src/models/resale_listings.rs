@@ -0,0 +1,367 @@
+use chrono::prelude::*;
+use diesel;
+use diesel::expression::dsl;
+use diesel::prelude::*;
+use models::*;
+use schema::{assets, resale_listings, ticket_instances, ticket_types};
+use time::Duration;
+use utils::errors::*;
+use uuid::Uuid;
+
+/// Blockchain transfers of resold tickets are retried for this long before needing attention
+const RESALE_TRANSFER_EXPIRY_DAYS: i64 = 7;
+const RESALE_TRANSFER_MAX_ATTEMPTS: i64 = 10;
+
+/// A ticket put up for sale by the fan holding it. Buyers add the listing to their cart and the
+/// ticket moves into their wallet once the order is paid.
+#[derive(Clone, Debug, PartialEq, Identifiable, Queryable, Serialize, Deserialize)]
+pub struct ResaleListing {
+    pub id: Uuid,
+    pub ticket_instance_id: Uuid,
+    pub seller_id: Uuid,
+    pub price_in_cents: i64,
+    pub status: ResaleListingStatus,
+    pub order_item_id: Option<Uuid>,
+    pub seller_payout_in_cents: Option<i64>,
+    pub sold_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl ResaleListing {
+    pub fn create(
+        ticket_instance_id: Uuid,
+        seller_id: Uuid,
+        price_in_cents: i64,
+    ) -> NewResaleListing {
+        NewResaleListing {
+            ticket_instance_id,
+            seller_id,
+            price_in_cents,
+            status: ResaleListingStatus::Active,
+        }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<ResaleListing, DatabaseError> {
+        resale_listings::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not find resale listing")
+    }
+
+    pub fn find_by_order_item_id(
+        order_item_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Option<ResaleListing>, DatabaseError> {
+        resale_listings::table
+            .filter(resale_listings::order_item_id.eq(order_item_id))
+            .first(conn)
+            .optional()
+            .to_db_error(ErrorCode::QueryError, "Could not find resale listing")
+    }
+
+    /// Listings that can still be bought for the event, cheapest first
+    pub fn find_active_for_event(
+        event_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<ResaleListing>, DatabaseError> {
+        resale_listings::table
+            .inner_join(
+                ticket_instances::table
+                    .on(resale_listings::ticket_instance_id.eq(ticket_instances::id)),
+            )
+            .inner_join(assets::table.on(ticket_instances::asset_id.eq(assets::id)))
+            .inner_join(ticket_types::table.on(assets::ticket_type_id.eq(ticket_types::id)))
+            .filter(ticket_types::event_id.eq(event_id))
+            .filter(resale_listings::status.eq(ResaleListingStatus::Active))
+            .select(resale_listings::all_columns)
+            .order_by(resale_listings::price_in_cents.asc())
+            .then_order_by(resale_listings::created_at.asc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load resale listings")
+    }
+
+    pub fn find_for_seller(
+        seller_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<ResaleListing>, DatabaseError> {
+        resale_listings::table
+            .filter(resale_listings::seller_id.eq(seller_id))
+            .order_by(resale_listings::created_at.desc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load resale listings")
+    }
+
+    pub(crate) fn any_active_for_tickets(
+        ticket_instance_ids: &[Uuid],
+        conn: &PgConnection,
+    ) -> Result<bool, DatabaseError> {
+        diesel::select(dsl::exists(
+            resale_listings::table
+                .filter(resale_listings::ticket_instance_id.eq_any(ticket_instance_ids))
+                .filter(resale_listings::status.eq(ResaleListingStatus::Active)),
+        ))
+        .get_result(conn)
+        .to_db_error(ErrorCode::QueryError, "Could not check for resale listings")
+    }
+
+    /// Whether the listing is in a buyer's cart that has not expired yet
+    pub fn is_reserved(&self, conn: &PgConnection) -> Result<bool, DatabaseError> {
+        let order_item_id = match self.order_item_id {
+            Some(order_item_id) => order_item_id,
+            None => return Ok(false),
+        };
+        let order = OrderItem::find(order_item_id, conn)?.order(conn)?;
+
+        Ok(
+            vec![OrderStatus::Draft, OrderStatus::PartiallyPaid].contains(&order.status)
+                && order
+                    .expires_at
+                    .map_or(false, |expires_at| expires_at >= Utc::now().naive_utc()),
+        )
+    }
+
+    pub fn cancel(
+        &self,
+        current_user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<ResaleListing, DatabaseError> {
+        if self.seller_id != current_user_id {
+            return DatabaseError::business_process_error(
+                "Only the seller can cancel this resale listing",
+            );
+        } else if self.status != ResaleListingStatus::Active {
+            return DatabaseError::business_process_error("This resale listing is not active");
+        } else if self.is_reserved(conn)? {
+            return DatabaseError::business_process_error(
+                "This resale listing is in a buyer's cart and cannot be cancelled",
+            );
+        }
+
+        diesel::update(self)
+            .set((
+                resale_listings::status.eq(ResaleListingStatus::Cancelled),
+                resale_listings::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not cancel resale listing")
+    }
+
+    /// Cancels the listings of tickets that are being refunded, exchanged or redeemed so they can
+    /// no longer be bought. Carts holding one of the listings fail to check out.
+    pub(crate) fn cancel_active_for_tickets(
+        ticket_instance_ids: &[Uuid],
+        conn: &PgConnection,
+    ) -> Result<usize, DatabaseError> {
+        diesel::update(
+            resale_listings::table
+                .filter(resale_listings::ticket_instance_id.eq_any(ticket_instance_ids))
+                .filter(resale_listings::status.eq(ResaleListingStatus::Active)),
+        )
+        .set((
+            resale_listings::status.eq(ResaleListingStatus::Cancelled),
+            resale_listings::updated_at.eq(dsl::now),
+        ))
+        .execute(conn)
+        .to_db_error(ErrorCode::UpdateError, "Could not cancel resale listings")
+    }
+
+    /// Holds the listing for the cart the order item belongs to until the cart expires. Checking
+    /// that the listing is still available is left to the caller.
+    pub(crate) fn reserve(
+        &self,
+        order_item_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<ResaleListing, DatabaseError> {
+        let listing: Option<ResaleListing> = diesel::update(
+            resale_listings::table
+                .filter(resale_listings::id.eq(self.id))
+                .filter(resale_listings::updated_at.eq(self.updated_at)),
+        )
+        .set((
+            resale_listings::order_item_id.eq(order_item_id),
+            resale_listings::updated_at.eq(dsl::now),
+        ))
+        .get_result(conn)
+        .optional()
+        .to_db_error(ErrorCode::UpdateError, "Could not reserve resale listing")?;
+
+        match listing {
+            Some(listing) => Ok(listing),
+            None => DatabaseError::concurrency_error(
+                "Could not reserve resale listing because it has been updated by another process",
+            ),
+        }
+    }
+
+    /// Completes the sale of the listing bought with the order item, moving the ticket to the
+    /// buyer. The buyer pays the organization, which owes the seller the price less its fees.
+    /// The payout is recorded as a `ResalePayout` for the organization to pay and is shown in the
+    /// event summary reports. The ticket's token is moved on the blockchain by a domain action
+    /// once the sale is committed, so that a failed transfer is retried rather than lost.
+    pub(crate) fn complete_sale(
+        order_item: &OrderItem,
+        buyer_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<ResaleListing, DatabaseError> {
+        let listing = match ResaleListing::find_by_order_item_id(order_item.id, conn)? {
+            Some(ref listing) if listing.status == ResaleListingStatus::Active => listing.clone(),
+            _ => {
+                return DatabaseError::business_process_error(
+                    "This resale listing is no longer available",
+                );
+            }
+        };
+
+        let ticket = TicketInstance::find(listing.ticket_instance_id, conn)?;
+        let seller_wallet = Wallet::find_default_for_user(listing.seller_id, conn)?;
+        if ticket.wallet_id != seller_wallet.id {
+            return DatabaseError::business_process_error(
+                "This resale listing is no longer available",
+            );
+        }
+        let buyer_wallet = Wallet::find_default_for_user(buyer_id, conn)?;
+        ticket.resell(buyer_wallet.id, conn)?;
+
+        let organization = Organization::find_by_asset_id(ticket.asset_id, conn)?;
+        let fee_in_cents = FeeSchedule::find(organization.fee_schedule_id, conn)?
+            .get_range(listing.price_in_cents, conn)
+            .optional()?
+            .map_or(0, |range| range.fee_in_cents);
+        let seller_payout_in_cents = (listing.price_in_cents - fee_in_cents).max(0);
+        if seller_payout_in_cents > 0 {
+            ResalePayout::create(
+                listing.id,
+                listing.seller_id,
+                organization.id,
+                seller_payout_in_cents,
+            )
+            .commit(conn)?;
+        }
+
+        let now = Utc::now().naive_utc();
+        DomainAction::create(
+            None,
+            DomainActionTypes::TransferResoldTicket,
+            None,
+            json!({
+                "resale_listing_id": listing.id,
+                "from_wallet_id": seller_wallet.id,
+                "to_wallet_id": buyer_wallet.id,
+            }),
+            Tables::TicketInstances.table_name(),
+            ticket.id,
+            now,
+            now + Duration::days(RESALE_TRANSFER_EXPIRY_DAYS),
+            RESALE_TRANSFER_MAX_ATTEMPTS,
+        )
+        .commit(conn)?;
+
+        DomainEvent::create(
+            DomainEventTypes::TransferTicketCompleted,
+            "Ticket resold".to_string(),
+            Tables::TicketInstances,
+            Some(ticket.id),
+            Some(buyer_id),
+            Some(json!({
+                "resale_listing_id": listing.id,
+                "receiver_wallet_id": buyer_wallet.id,
+            })),
+        )
+        .commit(conn)?;
+
+        diesel::update(&listing)
+            .set((
+                resale_listings::status.eq(ResaleListingStatus::Sold),
+                resale_listings::seller_payout_in_cents.eq(seller_payout_in_cents),
+                resale_listings::sold_at.eq(dsl::now.nullable()),
+                resale_listings::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not complete resale listing")
+    }
+}
+
+#[derive(Insertable, Clone)]
+#[table_name = "resale_listings"]
+pub struct NewResaleListing {
+    pub ticket_instance_id: Uuid,
+    pub seller_id: Uuid,
+    pub price_in_cents: i64,
+    pub status: ResaleListingStatus,
+}
+
+impl NewResaleListing {
+    pub fn commit(self, conn: &PgConnection) -> Result<ResaleListing, DatabaseError> {
+        if self.price_in_cents <= 0 {
+            return DatabaseError::validation_error(
+                "price_in_cents",
+                "Price must be greater than zero",
+            );
+        }
+
+        let ticket = TicketInstance::find(self.ticket_instance_id, conn)?;
+        let seller_wallet = Wallet::find_default_for_user(self.seller_id, conn)?;
+        if ticket.wallet_id != seller_wallet.id || ticket.status != TicketInstanceStatus::Purchased
+        {
+            return DatabaseError::business_process_error(
+                "Only purchased tickets in your wallet can be listed for resale",
+            );
+        } else if ResaleListing::any_active_for_tickets(&[ticket.id], conn)? {
+            return DatabaseError::business_process_error(
+                "This ticket is already listed for resale",
+            );
+        } else if ticket
+            .transfer_expiry_date
+            .map_or(false, |expiry| expiry > Utc::now().naive_utc())
+        {
+            return DatabaseError::business_process_error(
+                "Tickets that are being transferred cannot be listed for resale",
+            );
+        }
+
+        let organization = Organization::find_by_asset_id(ticket.asset_id, conn)?;
+        let resale_price_cap_percent = match organization.resale_price_cap_percent {
+            Some(resale_price_cap_percent) => resale_price_cap_percent,
+            None => {
+                return DatabaseError::business_process_error(
+                    "Resale is not available for this event",
+                );
+            }
+        };
+
+        // The cap is relative to the price originally paid for the ticket
+        let order_item = match ticket.order_item_id {
+            Some(order_item_id) => OrderItem::find(order_item_id, conn)?,
+            None => {
+                return DatabaseError::business_process_error(
+                    "This ticket cannot be listed for resale",
+                );
+            }
+        };
+        if let Some(event_id) = order_item.event_id {
+            let event = Event::find(event_id, conn)?;
+            if event
+                .event_start
+                .map_or(false, |event_start| event_start <= Utc::now().naive_utc())
+            {
+                return DatabaseError::business_process_error(
+                    "Tickets can only be listed for resale before the event starts",
+                );
+            }
+        }
+        let max_price_in_cents =
+            order_item.unit_price_in_cents * resale_price_cap_percent as i64 / 100;
+        if self.price_in_cents > max_price_in_cents {
+            return DatabaseError::validation_error(
+                "price_in_cents",
+                "Price is above the resale price cap for this event",
+            );
+        }
+
+        diesel::insert_into(resale_listings::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create resale listing")
+    }
+}
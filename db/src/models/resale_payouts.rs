@@ -0,0 +1,109 @@
+use chrono::prelude::*;
+use diesel;
+use diesel::expression::dsl;
+use diesel::prelude::*;
+use models::*;
+use schema::resale_payouts;
+use utils::errors::*;
+use uuid::Uuid;
+
+/// What an organization owes a fan for a ticket they resold. The buyer's payment goes to the
+/// organization, which pays the seller outside of the platform and then marks the payout paid.
+#[derive(Clone, Debug, PartialEq, Identifiable, Queryable, Serialize, Deserialize)]
+pub struct ResalePayout {
+    pub id: Uuid,
+    pub resale_listing_id: Uuid,
+    pub seller_id: Uuid,
+    pub organization_id: Uuid,
+    pub amount_in_cents: i64,
+    pub paid_at: Option<NaiveDateTime>,
+    pub paid_by_user_id: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl ResalePayout {
+    pub(crate) fn create(
+        resale_listing_id: Uuid,
+        seller_id: Uuid,
+        organization_id: Uuid,
+        amount_in_cents: i64,
+    ) -> NewResalePayout {
+        NewResalePayout {
+            resale_listing_id,
+            seller_id,
+            organization_id,
+            amount_in_cents,
+        }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<ResalePayout, DatabaseError> {
+        resale_payouts::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not find resale payout")
+    }
+
+    /// Payouts the organization still owes, oldest first
+    pub fn find_unpaid_for_organization(
+        organization_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<ResalePayout>, DatabaseError> {
+        resale_payouts::table
+            .filter(resale_payouts::organization_id.eq(organization_id))
+            .filter(resale_payouts::paid_at.is_null())
+            .order_by(resale_payouts::created_at.asc())
+            .then_order_by(resale_payouts::id.asc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load resale payouts")
+    }
+
+    pub fn organization(&self, conn: &PgConnection) -> Result<Organization, DatabaseError> {
+        Organization::find(self.organization_id, conn)
+    }
+
+    /// Records that the seller has been paid. Each payout can only be paid once.
+    pub fn mark_paid(
+        &self,
+        current_user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<ResalePayout, DatabaseError> {
+        let rows_affected = diesel::update(
+            resale_payouts::table
+                .filter(resale_payouts::id.eq(self.id))
+                .filter(resale_payouts::paid_at.is_null()),
+        )
+        .set((
+            resale_payouts::paid_at.eq(Utc::now().naive_utc()),
+            resale_payouts::paid_by_user_id.eq(current_user_id),
+            resale_payouts::updated_at.eq(dsl::now),
+        ))
+        .execute(conn)
+        .to_db_error(ErrorCode::UpdateError, "Could not mark resale payout paid")?;
+        if rows_affected == 0 {
+            return DatabaseError::business_process_error(
+                "This resale payout has already been paid",
+            );
+        }
+
+        ResalePayout::find(self.id, conn)
+    }
+}
+
+#[derive(Insertable, Clone)]
+#[table_name = "resale_payouts"]
+pub(crate) struct NewResalePayout {
+    resale_listing_id: Uuid,
+    seller_id: Uuid,
+    organization_id: Uuid,
+    amount_in_cents: i64,
+}
+
+impl NewResalePayout {
+    pub(crate) fn commit(self, conn: &PgConnection) -> Result<ResalePayout, DatabaseError> {
+        diesel::insert_into(resale_payouts::table)
+            .values(&self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create resale payout")
+    }
+}
@@ -0,0 +1,141 @@
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::expression::dsl;
+use diesel::prelude::*;
+use models::*;
+use schema::{seat_sections, seats};
+use utils::errors::ConvertToDatabaseError;
+use utils::errors::DatabaseError;
+use utils::errors::ErrorCode;
+use uuid::Uuid;
+
+#[derive(Clone, Associations, Identifiable, Queryable, Serialize, Deserialize, PartialEq, Debug)]
+#[belongs_to(Venue)]
+#[table_name = "seat_sections"]
+pub struct SeatSection {
+    pub id: Uuid,
+    pub venue_id: Uuid,
+    pub stage_id: Option<Uuid>,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(AsChangeset, Default, Deserialize)]
+#[table_name = "seat_sections"]
+pub struct SeatSectionEditableAttributes {
+    #[serde(default, deserialize_with = "deserialize_unless_blank")]
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "double_option_deserialize_unless_blank")]
+    pub stage_id: Option<Option<Uuid>>,
+}
+
+#[derive(Default, Insertable, Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[table_name = "seat_sections"]
+pub struct NewSeatSection {
+    pub venue_id: Uuid,
+    #[serde(default, deserialize_with = "deserialize_unless_blank")]
+    pub stage_id: Option<Uuid>,
+    pub name: String,
+}
+
+impl NewSeatSection {
+    pub fn commit(&self, connection: &PgConnection) -> Result<SeatSection, DatabaseError> {
+        if let Some(stage_id) = self.stage_id {
+            if Stage::find(stage_id, connection)?.venue_id != self.venue_id {
+                return DatabaseError::validation_error(
+                    "stage_id",
+                    "Stage must belong to the same venue as the seat section",
+                );
+            }
+        }
+
+        diesel::insert_into(seat_sections::table)
+            .values(self)
+            .get_result(connection)
+            .to_db_error(ErrorCode::InsertError, "Could not create seat section")
+    }
+}
+
+impl SeatSection {
+    pub fn create(venue_id: Uuid, stage_id: Option<Uuid>, name: String) -> NewSeatSection {
+        NewSeatSection {
+            venue_id,
+            stage_id,
+            name,
+        }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<SeatSection, DatabaseError> {
+        DatabaseError::wrap(
+            ErrorCode::QueryError,
+            "Error loading seat section",
+            seat_sections::table.find(id).first::<SeatSection>(conn),
+        )
+    }
+
+    pub fn find_by_venue_id(
+        venue_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<SeatSection>, DatabaseError> {
+        seat_sections::table
+            .filter(seat_sections::venue_id.eq(venue_id))
+            .order_by(seat_sections::name)
+            .select(seat_sections::all_columns)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Unable to load all seat sections")
+    }
+
+    pub fn seats(&self, conn: &PgConnection) -> Result<Vec<Seat>, DatabaseError> {
+        seats::table
+            .filter(seats::seat_section_id.eq(self.id))
+            .order_by((seats::row_name, seats::seat_number))
+            .select(seats::all_columns)
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Unable to load seats for seat section",
+            )
+    }
+
+    pub fn add_seats(
+        &self,
+        new_seats: Vec<NewSeat>,
+        conn: &PgConnection,
+    ) -> Result<Vec<Seat>, DatabaseError> {
+        let new_seats: Vec<NewSeat> = new_seats
+            .into_iter()
+            .map(|s| NewSeat {
+                seat_section_id: self.id,
+                ..s
+            })
+            .collect();
+
+        diesel::insert_into(seats::table)
+            .values(&new_seats)
+            .get_results(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create seats")
+    }
+
+    pub fn update(
+        &self,
+        attributes: SeatSectionEditableAttributes,
+        conn: &PgConnection,
+    ) -> Result<SeatSection, DatabaseError> {
+        DatabaseError::wrap(
+            ErrorCode::UpdateError,
+            "Could not update seat section",
+            diesel::update(self)
+                .set((attributes, seat_sections::updated_at.eq(dsl::now)))
+                .get_result(conn),
+        )
+    }
+
+    pub fn destroy(&self, conn: &PgConnection) -> Result<usize, DatabaseError> {
+        DatabaseError::wrap(
+            ErrorCode::DeleteError,
+            "Failed to delete seat section",
+            diesel::delete(self).execute(conn),
+        )
+    }
+}
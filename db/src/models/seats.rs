@@ -0,0 +1,116 @@
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::prelude::*;
+use models::*;
+use schema::{seat_sections, seats};
+use utils::errors::ConvertToDatabaseError;
+use utils::errors::DatabaseError;
+use utils::errors::ErrorCode;
+use uuid::Uuid;
+
+#[derive(Clone, Associations, Identifiable, Queryable, Serialize, Deserialize, PartialEq, Debug)]
+#[belongs_to(SeatSection)]
+#[table_name = "seats"]
+pub struct Seat {
+    pub id: Uuid,
+    pub seat_section_id: Uuid,
+    pub row_name: String,
+    pub seat_number: String,
+    pub is_accessible: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Default, Insertable, Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[table_name = "seats"]
+pub struct NewSeat {
+    #[serde(default)]
+    pub seat_section_id: Uuid,
+    pub row_name: String,
+    pub seat_number: String,
+    #[serde(default)]
+    pub is_accessible: bool,
+}
+
+impl NewSeat {
+    pub fn commit(&self, connection: &PgConnection) -> Result<Seat, DatabaseError> {
+        diesel::insert_into(seats::table)
+            .values(self)
+            .get_result(connection)
+            .to_db_error(ErrorCode::InsertError, "Could not create seat")
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DisplaySeat {
+    pub id: Uuid,
+    pub section_name: String,
+    pub row_name: String,
+    pub seat_number: String,
+    pub is_accessible: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DisplayTicketTypeSeat {
+    pub seat: DisplaySeat,
+    pub ticket_instance_id: Uuid,
+    pub status: TicketInstanceStatus,
+    pub available: bool,
+}
+
+impl Seat {
+    pub fn create(
+        seat_section_id: Uuid,
+        row_name: String,
+        seat_number: String,
+        is_accessible: bool,
+    ) -> NewSeat {
+        NewSeat {
+            seat_section_id,
+            row_name,
+            seat_number,
+            is_accessible,
+        }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<Seat, DatabaseError> {
+        DatabaseError::wrap(
+            ErrorCode::QueryError,
+            "Error loading seat",
+            seats::table.find(id).first::<Seat>(conn),
+        )
+    }
+
+    pub fn find_by_ids_for_venue(
+        ids: &[Uuid],
+        venue_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<Seat>, DatabaseError> {
+        seats::table
+            .inner_join(seat_sections::table)
+            .filter(seats::id.eq_any(ids))
+            .filter(seat_sections::venue_id.eq(venue_id))
+            .select(seats::all_columns)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Unable to load seats")
+    }
+
+    pub fn for_display(&self, conn: &PgConnection) -> Result<DisplaySeat, DatabaseError> {
+        let section = SeatSection::find(self.seat_section_id, conn)?;
+        Ok(DisplaySeat {
+            id: self.id,
+            section_name: section.name,
+            row_name: self.row_name.clone(),
+            seat_number: self.seat_number.clone(),
+            is_accessible: self.is_accessible,
+        })
+    }
+
+    pub fn destroy(&self, conn: &PgConnection) -> Result<usize, DatabaseError> {
+        DatabaseError::wrap(
+            ErrorCode::DeleteError,
+            "Failed to delete seat",
+            diesel::delete(self).execute(conn),
+        )
+    }
+}
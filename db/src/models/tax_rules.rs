@@ -0,0 +1,223 @@
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::dsl::{exists, select};
+use diesel::expression::dsl;
+use diesel::prelude::*;
+use models::*;
+use schema::{order_items, orders, tax_rules};
+use utils::errors::*;
+use utils::tax_for_price;
+use uuid::Uuid;
+
+/// Sales tax or VAT charged on orders for events at a venue. Rules set on a venue replace the
+/// rules of the venue's region. Inclusive taxes are already part of the price so they are only
+/// recorded, exclusive taxes are charged on top of it.
+#[derive(Clone, Identifiable, Queryable, Serialize, Deserialize, PartialEq, Debug)]
+#[table_name = "tax_rules"]
+pub struct TaxRule {
+    pub id: Uuid,
+    pub region_id: Option<Uuid>,
+    pub venue_id: Option<Uuid>,
+    pub name: String,
+    pub rate_in_basis_points: i32,
+    pub inclusive: bool,
+    pub applies_to_tickets: bool,
+    pub applies_to_fees: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(AsChangeset, Default, Deserialize)]
+#[table_name = "tax_rules"]
+pub struct TaxRuleEditableAttributes {
+    #[serde(default, deserialize_with = "deserialize_unless_blank")]
+    pub name: Option<String>,
+    pub rate_in_basis_points: Option<i32>,
+    pub inclusive: Option<bool>,
+    pub applies_to_tickets: Option<bool>,
+    pub applies_to_fees: Option<bool>,
+}
+
+#[derive(Insertable, Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[table_name = "tax_rules"]
+pub struct NewTaxRule {
+    pub region_id: Option<Uuid>,
+    pub venue_id: Option<Uuid>,
+    pub name: String,
+    pub rate_in_basis_points: i32,
+    pub inclusive: bool,
+    pub applies_to_tickets: bool,
+    pub applies_to_fees: bool,
+}
+
+impl NewTaxRule {
+    pub fn commit(&self, conn: &PgConnection) -> Result<TaxRule, DatabaseError> {
+        TaxRule::validate(Some(&self.name), Some(self.rate_in_basis_points))?;
+
+        diesel::insert_into(tax_rules::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create tax rule")
+    }
+}
+
+impl TaxRule {
+    pub fn create_for_region(
+        region_id: Uuid,
+        name: String,
+        rate_in_basis_points: i32,
+        inclusive: bool,
+        applies_to_tickets: bool,
+        applies_to_fees: bool,
+    ) -> NewTaxRule {
+        NewTaxRule {
+            region_id: Some(region_id),
+            venue_id: None,
+            name,
+            rate_in_basis_points,
+            inclusive,
+            applies_to_tickets,
+            applies_to_fees,
+        }
+    }
+
+    pub fn create_for_venue(
+        venue_id: Uuid,
+        name: String,
+        rate_in_basis_points: i32,
+        inclusive: bool,
+        applies_to_tickets: bool,
+        applies_to_fees: bool,
+    ) -> NewTaxRule {
+        NewTaxRule {
+            region_id: None,
+            venue_id: Some(venue_id),
+            name,
+            rate_in_basis_points,
+            inclusive,
+            applies_to_tickets,
+            applies_to_fees,
+        }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<TaxRule, DatabaseError> {
+        tax_rules::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not find tax rule")
+    }
+
+    pub fn find_for_region(
+        region_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<TaxRule>, DatabaseError> {
+        tax_rules::table
+            .filter(tax_rules::region_id.eq(region_id))
+            .order_by(tax_rules::name)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load tax rules")
+    }
+
+    pub fn find_for_venue(
+        venue_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<TaxRule>, DatabaseError> {
+        tax_rules::table
+            .filter(tax_rules::venue_id.eq(venue_id))
+            .order_by(tax_rules::name)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load tax rules")
+    }
+
+    /// Rules charged on orders for the event, taken from its venue or failing that its region
+    pub fn find_for_event(
+        event: &Event,
+        conn: &PgConnection,
+    ) -> Result<Vec<TaxRule>, DatabaseError> {
+        let venue = match event.venue_id {
+            Some(venue_id) => Venue::find(venue_id, conn)?,
+            None => return Ok(vec![]),
+        };
+        let tax_rules = TaxRule::find_for_venue(venue.id, conn)?;
+        if !tax_rules.is_empty() {
+            return Ok(tax_rules);
+        }
+        TaxRule::find_for_region(venue.region_id, conn)
+    }
+
+    pub fn update(
+        &self,
+        attributes: TaxRuleEditableAttributes,
+        conn: &PgConnection,
+    ) -> Result<TaxRule, DatabaseError> {
+        TaxRule::validate(attributes.name.as_ref(), attributes.rate_in_basis_points)?;
+
+        diesel::update(self)
+            .set((attributes, tax_rules::updated_at.eq(dsl::now)))
+            .get_result(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not update tax rule")
+    }
+
+    /// Rules that have been charged on orders are kept for reporting, their rate can be set to
+    /// zero instead
+    pub fn destroy(&self, conn: &PgConnection) -> Result<usize, DatabaseError> {
+        let charged = select(exists(
+            order_items::table
+                .inner_join(orders::table)
+                .filter(order_items::tax_rule_id.eq(self.id))
+                .filter(orders::status.ne(OrderStatus::Draft)),
+        ))
+        .get_result::<bool>(conn)
+        .to_db_error(
+            ErrorCode::QueryError,
+            "Could not check if tax rule was charged",
+        )?;
+        if charged {
+            return DatabaseError::business_process_error(
+                "Tax rule has been charged on orders and cannot be deleted",
+            );
+        }
+
+        diesel::delete(self)
+            .execute(conn)
+            .to_db_error(ErrorCode::DeleteError, "Failed to delete tax rule")
+    }
+
+    pub fn applies_to(&self, item_type: &OrderItemTypes) -> bool {
+        match item_type {
+            OrderItemTypes::Tickets | OrderItemTypes::Discount => self.applies_to_tickets,
+            OrderItemTypes::PerUnitFees | OrderItemTypes::EventFees => self.applies_to_fees,
+            _ => false,
+        }
+    }
+
+    /// Tax on the amount rounded half away from zero to the nearest cent. For inclusive rules the tax is the portion
+    /// of the amount that is tax. Discounts are negative so the tax on them is too.
+    pub fn tax_for(&self, amount_in_cents: i64) -> i64 {
+        tax_for_price(
+            amount_in_cents,
+            self.rate_in_basis_points as i64,
+            self.inclusive,
+        )
+    }
+
+    fn validate(
+        name: Option<&String>,
+        rate_in_basis_points: Option<i32>,
+    ) -> Result<(), DatabaseError> {
+        if let Some(name) = name {
+            if name.trim().is_empty() {
+                return DatabaseError::validation_error("name", "Tax rule name is required");
+            }
+        }
+        if let Some(rate_in_basis_points) = rate_in_basis_points {
+            if rate_in_basis_points < 0 || rate_in_basis_points > 10000 {
+                return DatabaseError::validation_error(
+                    "rate_in_basis_points",
+                    "Tax rate must be between 0 and 10000 basis points",
+                );
+            }
+        }
+        Ok(())
+    }
+}
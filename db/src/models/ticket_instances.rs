@@ -10,13 +10,22 @@ use models::*;
 use rand;
 use rand::Rng;
 use schema::{
-    assets, events, order_items, orders, ticket_instances, ticket_types, users, venues, wallets,
+    assets, events, order_items, orders, seat_sections, seats, ticket_instances, ticket_types,
+    users, venues, wallets,
 };
 use tari_client::*;
 use time::Duration;
 use utils::errors::*;
 use uuid::Uuid;
 
+/// Whether a ticket joined to its order and wallet is held by someone other than the buyer or the
+/// payer of one of the order's shares
+pub(crate) const TRANSFERRED_SQL: &'static str = "orders.user_id <> wallets.user_id
+    AND NOT EXISTS (
+        SELECT 1 FROM order_shares
+        WHERE order_shares.order_id = orders.id AND order_shares.paid_by_user_id = wallets.user_id
+    )";
+
 #[derive(Debug, Identifiable, PartialEq, Deserialize, Serialize, Queryable, QueryableByName)]
 #[table_name = "ticket_instances"]
 pub struct TicketInstance {
@@ -33,6 +42,7 @@ pub struct TicketInstance {
     pub status: TicketInstanceStatus,
     created_at: NaiveDateTime,
     updated_at: NaiveDateTime,
+    pub seat_id: Option<Uuid>,
 }
 
 impl TicketInstance {
@@ -58,9 +68,76 @@ impl TicketInstance {
             return DatabaseError::validation_error("quantity", "Could not release the ticket");
         }
 
+        let asset = Asset::find(self.asset_id, conn)?;
+        WaitlistEntry::schedule_processing(asset.ticket_type_id, None, conn)?;
+
+        Ok(())
+    }
+
+    /// Nullifies a purchased ticket that was exchanged, returning it to the organization's wallet
+    pub(crate) fn nullify(&self, conn: &PgConnection) -> Result<(), DatabaseError> {
+        let organization = Organization::find_by_asset_id(self.asset_id, conn)?;
+        let wallet = Wallet::find_default_for_organization(organization.id, conn)?;
+        diesel::update(ticket_instances::table.filter(ticket_instances::id.eq(self.id)))
+            .set((
+                ticket_instances::status.eq(TicketInstanceStatus::Nullified),
+                ticket_instances::wallet_id.eq(wallet.id),
+                ticket_instances::updated_at.eq(dsl::now),
+            ))
+            .execute(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not nullify ticket")?;
         Ok(())
     }
 
+    /// Moves a ticket bought on resale into the buyer's wallet. A new redeem key is generated so
+    /// the seller can no longer use the ticket.
+    pub(crate) fn resell(
+        &self,
+        buyer_wallet_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        #[derive(AsChangeset)]
+        #[changeset_options(treat_none_as_null = "true")]
+        #[table_name = "ticket_instances"]
+        struct Update {
+            transfer_key: Option<Uuid>,
+            transfer_expiry_date: Option<NaiveDateTime>,
+        };
+
+        let update_count = diesel::update(
+            ticket_instances::table
+                .filter(ticket_instances::id.eq(self.id))
+                .filter(ticket_instances::wallet_id.eq(self.wallet_id))
+                .filter(ticket_instances::status.eq(TicketInstanceStatus::Purchased)),
+        )
+        .set((
+            Update {
+                transfer_key: None,
+                transfer_expiry_date: None,
+            },
+            ticket_instances::wallet_id.eq(buyer_wallet_id),
+            ticket_instances::redeem_key.eq(generate_redeem_key(9)),
+            ticket_instances::updated_at.eq(dsl::now),
+        ))
+        .execute(conn)
+        .to_db_error(ErrorCode::UpdateError, "Could not resell ticket")?;
+
+        if update_count != 1 {
+            return DatabaseError::business_process_error(
+                "Ticket is no longer available for resale",
+            );
+        }
+
+        WalletPassRegistration::schedule_update(
+            Tables::TicketInstances,
+            self.id,
+            json!({ "previous_wallet_id": self.wallet_id }),
+            conn,
+        )
+    }
+
+    /// Whether the ticket has left the buyer's wallet. Tickets allocated to the payer of a share
+    /// of a split order belong to that payer.
     pub fn was_transferred(&self, conn: &PgConnection) -> Result<bool, DatabaseError> {
         ticket_instances::table
             .inner_join(wallets::table.on(ticket_instances::wallet_id.eq(wallets::id)))
@@ -68,15 +145,17 @@ impl TicketInstance {
                 order_items::table
                     .on(ticket_instances::order_item_id.eq(order_items::id.nullable())),
             )
-            .left_join(
-                orders::table.on(order_items::order_id.eq(orders::id)),
-            )
+            .left_join(orders::table.on(order_items::order_id.eq(orders::id)))
             .filter(ticket_instances::id.eq(self.id))
-            .select(
-                sql::<Bool>("case when order_items.id is null then true else orders.user_id <> wallets.user_id end")
-            )
+            .select(sql::<Bool>(&format!(
+                "case when order_items.id is null then true else coalesce({}, true) end",
+                TRANSFERRED_SQL
+            )))
             .get_result(conn)
-            .to_db_error(ErrorCode::QueryError, "Unable to check if ticket instance was transferred")
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Unable to check if ticket instance was transferred",
+            )
     }
 
     pub fn find_for_display(
@@ -92,6 +171,8 @@ impl TicketInstance {
             .inner_join(ticket_types::table.on(assets::ticket_type_id.eq(ticket_types::id)))
             .inner_join(wallets::table.on(ticket_instances::wallet_id.eq(wallets::id)))
             .inner_join(events::table.on(ticket_types::event_id.eq(events::id)))
+            .left_join(seats::table.on(ticket_instances::seat_id.eq(seats::id.nullable())))
+            .left_join(seat_sections::table.on(seats::seat_section_id.eq(seat_sections::id)))
             .filter(ticket_instances::id.eq(id))
             .select((
                 ticket_instances::id,
@@ -125,6 +206,11 @@ impl TicketInstance {
                         AS BOOLEAN)
                              AS pending_transfer",
                 ),
+                seats::id.nullable(),
+                seat_sections::name.nullable(),
+                seats::row_name.nullable(),
+                seats::seat_number.nullable(),
+                seats::is_accessible.nullable(),
             ))
             .first::<DisplayTicketIntermediary>(conn)
             .to_db_error(ErrorCode::QueryError, "Unable to load ticket")?;
@@ -182,6 +268,8 @@ impl TicketInstance {
                 .inner_join(ticket_types::table.on(assets::ticket_type_id.eq(ticket_types::id)))
                 .inner_join(wallets::table.on(ticket_instances::wallet_id.eq(wallets::id)))
                 .inner_join(events::table.on(ticket_types::event_id.eq(events::id)))
+                .left_join(seats::table.on(ticket_instances::seat_id.eq(seats::id.nullable())))
+                .left_join(seat_sections::table.on(seats::seat_section_id.eq(seat_sections::id)))
                 .filter(events::event_start.ge(
                     start_time.unwrap_or_else(|| NaiveDate::from_ymd(1970, 1, 1).and_hms(0, 0, 0)),
                 ))
@@ -227,6 +315,11 @@ impl TicketInstance {
                         END AS BOOLEAN)
                              AS pending_transfer",
                 ),
+                seats::id.nullable(),
+                seat_sections::name.nullable(),
+                seats::row_name.nullable(),
+                seats::seat_number.nullable(),
+                seats::is_accessible.nullable(),
             ))
             .order_by(events::event_start.asc())
             .then_order_by(events::name.asc())
@@ -290,6 +383,7 @@ impl TicketInstance {
         Ok(())
     }
 
+    /// Reserves general admission tickets, seated tickets are only reserved by `reserve_seats`
     pub fn reserve_tickets(
         order_item: &OrderItem,
         expires_at: Option<NaiveDateTime>,
@@ -324,6 +418,40 @@ impl TicketInstance {
         Ok(tickets)
     }
 
+    pub fn reserve_seats(
+        order_item: &OrderItem,
+        expires_at: Option<NaiveDateTime>,
+        ticket_type_id: Uuid,
+        ticket_holding_id: Option<Uuid>,
+        seat_ids: &[Uuid],
+        conn: &PgConnection,
+    ) -> Result<Vec<TicketInstance>, DatabaseError> {
+        let order_expires_at = expires_at.ok_or(DatabaseError::new(
+            ErrorCode::BusinessProcessError,
+            Some("Expiration date was not set on cart prior to reserving tickets".to_string()),
+        ))?;
+
+        let query = include_str!("../queries/reserve_seats.sql");
+        let q = diesel::sql_query(query)
+            .bind::<sql_types::Uuid, _>(order_item.id)
+            .bind::<sql_types::Timestamp, _>(order_expires_at)
+            .bind::<sql_types::Uuid, _>(ticket_type_id)
+            .bind::<sql_types::Nullable<sql_types::Uuid>, _>(ticket_holding_id)
+            .bind::<sql_types::Array<sql_types::Uuid>, _>(seat_ids);
+        let tickets: Vec<TicketInstance> = q
+            .get_results(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not reserve seats")?;
+
+        if tickets.len() != seat_ids.len() {
+            return DatabaseError::validation_error(
+                "seat_ids",
+                "One or more of the requested seats are not available",
+            );
+        }
+
+        Ok(tickets)
+    }
+
     pub fn release_tickets(
         order_item: &OrderItem,
         quantity: u32,
@@ -346,6 +474,11 @@ impl TicketInstance {
                 "Could not release the correct amount of tickets",
             );
         }
+
+        if let Some(ticket_type_id) = order_item.ticket_type_id {
+            WaitlistEntry::schedule_processing(ticket_type_id, None, conn)?;
+        }
+
         Ok(tickets)
     }
 
@@ -452,6 +585,21 @@ impl TicketInstance {
             .to_db_error(ErrorCode::QueryError, "Could not load Ticket Instances")
     }
 
+    /// Tickets for the event that have been purchased and not yet redeemed
+    pub fn find_purchased_for_event(
+        event_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<TicketInstance>, DatabaseError> {
+        ticket_instances::table
+            .inner_join(assets::table.inner_join(ticket_types::table))
+            .filter(ticket_types::event_id.eq(event_id))
+            .filter(ticket_instances::status.eq(TicketInstanceStatus::Purchased))
+            .select(ticket_instances::all_columns)
+            .order_by(ticket_instances::id)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load Ticket Instances")
+    }
+
     pub fn update_reserved_time(
         order_item: &OrderItem,
         reserved_time: NaiveDateTime,
@@ -545,6 +693,7 @@ impl TicketInstance {
                 .set(ticket_instances::status.eq(TicketInstanceStatus::Redeemed))
                 .execute(conn)
                 .to_db_error(ErrorCode::UpdateError, "Could not set ticket to Redeemed")?;
+            ResaleListing::cancel_active_for_tickets(&[ticket_id], conn)?;
         } else if ticket.status == TicketInstanceStatus::Redeemed {
             return Ok(RedeemResults::TicketAlreadyRedeemed);
         } else {
@@ -568,6 +717,8 @@ impl TicketInstance {
             .inner_join(wallets::table.on(ticket_instances::wallet_id.eq(wallets::id)))
             .inner_join(events::table.on(ticket_types::event_id.eq(events::id)))
             .left_join(venues::table.on(events::venue_id.eq(venues::id.nullable())))
+            .left_join(seats::table.on(ticket_instances::seat_id.eq(seats::id.nullable())))
+            .left_join(seat_sections::table.on(seats::seat_section_id.eq(seat_sections::id)))
             .inner_join(users::table.on(sql(
                 "coalesce(orders.on_behalf_of_user_id, wallets.user_id) = users.id",
             )))
@@ -600,6 +751,9 @@ impl TicketInstance {
                 events::event_start,
                 events::venue_id,
                 venues::name.nullable(),
+                seat_sections::name.nullable(),
+                seats::row_name.nullable(),
+                seats::seat_number.nullable(),
             ))
             .first::<RedeemableTicket>(conn)
             .to_db_error(ErrorCode::QueryError, "Unable to load ticket")?;
@@ -649,6 +803,12 @@ impl TicketInstance {
             ));
         }
 
+        if ResaleListing::any_active_for_tickets(&ticket_ids, conn)? {
+            return DatabaseError::business_process_error(
+                "Tickets listed for resale cannot be transferred",
+            );
+        }
+
         //Generate transfer_key and store keys and set transfer_expiry date
         let transfer_key = Uuid::new_v4();
         let transfer_expiry_date =
@@ -773,7 +933,7 @@ impl TicketInstance {
             .execute(conn)
             .to_db_error(ErrorCode::UpdateError, "Could not update ticket instance")?;
             DomainEvent::create(
-                DomainEventTypes::TransferTicketStarted,
+                DomainEventTypes::TransferTicketCompleted,
                 "Transfer ticket completed".to_string(),
                 Tables::TicketInstances,
                 Some(t_id.clone()),
@@ -781,6 +941,12 @@ impl TicketInstance {
                 Some(json!({"receiver_wallet_id": receiver_wallet_id.clone()})),
             )
             .commit(conn)?;
+            WalletPassRegistration::schedule_update(
+                Tables::TicketInstances,
+                *t_id,
+                json!({ "previous_wallet_id": sender_wallet.id }),
+                conn,
+            )?;
         }
 
         if update_count != transfer_authorization.num_tickets as usize {
@@ -828,6 +994,7 @@ pub struct DisplayTicket {
     pub status: TicketInstanceStatus,
     pub redeem_key: Option<String>,
     pub pending_transfer: bool,
+    pub seat: Option<DisplaySeat>,
 }
 
 #[derive(Queryable, QueryableByName)]
@@ -856,6 +1023,16 @@ pub struct DisplayTicketIntermediary {
     pub redeem_date: Option<NaiveDateTime>,
     #[sql_type = "Bool"]
     pub pending_transfer: bool,
+    #[sql_type = "Nullable<dUuid>"]
+    pub seat_id: Option<Uuid>,
+    #[sql_type = "Nullable<Text>"]
+    pub section_name: Option<String>,
+    #[sql_type = "Nullable<Text>"]
+    pub row_name: Option<String>,
+    #[sql_type = "Nullable<Text>"]
+    pub seat_number: Option<String>,
+    #[sql_type = "Nullable<Bool>"]
+    pub is_accessible: Option<bool>,
 }
 
 impl From<DisplayTicketIntermediary> for DisplayTicket {
@@ -868,6 +1045,17 @@ impl From<DisplayTicketIntermediary> for DisplayTicket {
             ticket_intermediary.redeem_key.clone()
         };
 
+        let seat = match ticket_intermediary.seat_id {
+            Some(seat_id) => Some(DisplaySeat {
+                id: seat_id,
+                section_name: ticket_intermediary.section_name.unwrap_or_default(),
+                row_name: ticket_intermediary.row_name.unwrap_or_default(),
+                seat_number: ticket_intermediary.seat_number.unwrap_or_default(),
+                is_accessible: ticket_intermediary.is_accessible.unwrap_or(false),
+            }),
+            None => None,
+        };
+
         DisplayTicket {
             id: ticket_intermediary.id,
             order_id: ticket_intermediary.order_id,
@@ -877,6 +1065,7 @@ impl From<DisplayTicketIntermediary> for DisplayTicket {
             status: ticket_intermediary.status.clone(),
             pending_transfer: ticket_intermediary.pending_transfer,
             redeem_key,
+            seat,
         }
     }
 }
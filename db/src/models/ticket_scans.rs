@@ -0,0 +1,201 @@
+use chrono::prelude::*;
+use diesel;
+use diesel::prelude::*;
+use models::*;
+use schema::{assets, ticket_instances, ticket_scans, ticket_types};
+use utils::errors::*;
+use uuid::Uuid;
+
+#[derive(Associations, Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+#[belongs_to(Event)]
+#[table_name = "ticket_scans"]
+pub struct TicketScan {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub ticket_instance_id: Option<Uuid>,
+    pub scanned_by_user_id: Option<Uuid>,
+    pub device_id: Option<String>,
+    pub gate_name: Option<String>,
+    pub direction: TicketScanDirections,
+    pub result: TicketScanResults,
+    pub scanned_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name = "ticket_scans"]
+pub struct NewTicketScan {
+    pub event_id: Uuid,
+    pub ticket_instance_id: Option<Uuid>,
+    pub scanned_by_user_id: Option<Uuid>,
+    pub device_id: Option<String>,
+    pub gate_name: Option<String>,
+    pub direction: TicketScanDirections,
+    pub result: TicketScanResults,
+    pub scanned_at: NaiveDateTime,
+}
+
+impl NewTicketScan {
+    pub fn commit(&self, conn: &PgConnection) -> Result<TicketScan, DatabaseError> {
+        diesel::insert_into(ticket_scans::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not create ticket scan")
+    }
+}
+
+impl TicketScan {
+    pub fn create(
+        event_id: Uuid,
+        ticket_instance_id: Option<Uuid>,
+        scanned_by_user_id: Option<Uuid>,
+        device_id: Option<String>,
+        gate_name: Option<String>,
+        direction: TicketScanDirections,
+        result: TicketScanResults,
+        scanned_at: NaiveDateTime,
+    ) -> NewTicketScan {
+        NewTicketScan {
+            event_id,
+            ticket_instance_id,
+            scanned_by_user_id,
+            device_id,
+            gate_name,
+            direction,
+            result,
+            scanned_at,
+        }
+    }
+
+    /// Scans the ticket in or out of the event and records the attempt. A redeemed ticket can only
+    /// be scanned out and back in again if its ticket type allows re-entry.
+    pub fn scan(
+        event_id: Uuid,
+        ticket_instance_id: Uuid,
+        redeem_key: String,
+        direction: TicketScanDirections,
+        scanned_by_user_id: Option<Uuid>,
+        device_id: Option<String>,
+        gate_name: Option<String>,
+        conn: &PgConnection,
+    ) -> Result<TicketScan, DatabaseError> {
+        let ticket = TicketInstance::find(ticket_instance_id, conn)?;
+        let key_valid = ticket.redeem_key.as_ref() == Some(&redeem_key);
+
+        let result = match direction {
+            TicketScanDirections::In => {
+                match TicketInstance::redeem_ticket(ticket.id, redeem_key, conn)? {
+                    RedeemResults::TicketRedeemSuccess => TicketScanResults::Redeemed,
+                    RedeemResults::TicketAlreadyRedeemed => {
+                        if key_valid
+                            && TicketScan::allows_reentry(ticket.id, conn)?
+                            && TicketScan::is_scanned_out(ticket.id, conn)?
+                        {
+                            TicketScanResults::ReEntered
+                        } else {
+                            TicketScanResults::AlreadyRedeemed
+                        }
+                    }
+                    RedeemResults::TicketInvalid => TicketScanResults::Invalid,
+                }
+            }
+            TicketScanDirections::Out => {
+                if key_valid
+                    && ticket.status == TicketInstanceStatus::Redeemed
+                    && TicketScan::allows_reentry(ticket.id, conn)?
+                    && !TicketScan::is_scanned_out(ticket.id, conn)?
+                {
+                    TicketScanResults::ScannedOut
+                } else {
+                    TicketScanResults::Invalid
+                }
+            }
+        };
+
+        TicketScan::create(
+            event_id,
+            Some(ticket.id),
+            scanned_by_user_id,
+            device_id,
+            gate_name,
+            direction,
+            result,
+            Utc::now().naive_utc(),
+        )
+        .commit(conn)
+    }
+
+    pub fn find_for_event(
+        event_id: Uuid,
+        page: u32,
+        limit: u32,
+        conn: &PgConnection,
+    ) -> Result<Payload<TicketScan>, DatabaseError> {
+        let total: i64 = ticket_scans::table
+            .filter(ticket_scans::event_id.eq(event_id))
+            .count()
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not get total ticket scans")?;
+
+        let mut payload = Payload::new(
+            ticket_scans::table
+                .filter(ticket_scans::event_id.eq(event_id))
+                .order_by(ticket_scans::scanned_at.desc())
+                .limit(limit as i64)
+                .offset((page * limit) as i64)
+                .load(conn)
+                .to_db_error(ErrorCode::QueryError, "Could not load ticket scans")?,
+            Paging::new(page, limit),
+        );
+        payload.paging.total = total as u64;
+        Ok(payload)
+    }
+
+    pub fn find_for_ticket_instance(
+        ticket_instance_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<TicketScan>, DatabaseError> {
+        ticket_scans::table
+            .filter(ticket_scans::ticket_instance_id.eq(ticket_instance_id))
+            .order_by(ticket_scans::scanned_at.desc())
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load ticket scans")
+    }
+
+    fn allows_reentry(
+        ticket_instance_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<bool, DatabaseError> {
+        ticket_instances::table
+            .inner_join(assets::table.inner_join(ticket_types::table))
+            .filter(ticket_instances::id.eq(ticket_instance_id))
+            .select(ticket_types::allow_reentry)
+            .first(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load re-entry rule for ticket",
+            )
+    }
+
+    /// Whether the most recent successful scan of the ticket took it out of the event
+    fn is_scanned_out(
+        ticket_instance_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<bool, DatabaseError> {
+        let last_result: Option<TicketScanResults> = ticket_scans::table
+            .filter(ticket_scans::ticket_instance_id.eq(ticket_instance_id))
+            .filter(ticket_scans::result.eq_any(vec![
+                TicketScanResults::Redeemed,
+                TicketScanResults::ReEntered,
+                TicketScanResults::ScannedOut,
+            ]))
+            .order_by(ticket_scans::scanned_at.desc())
+            .select(ticket_scans::result)
+            .first(conn)
+            .optional()
+            .to_db_error(ErrorCode::QueryError, "Could not load last ticket scan")?;
+
+        Ok(last_result == Some(TicketScanResults::ScannedOut))
+    }
+}
@@ -1,11 +1,12 @@
-use chrono::NaiveDateTime;
+use chrono::prelude::*;
 use diesel;
 use diesel::dsl;
 use diesel::prelude::*;
+use diesel::sql_types;
 use models::*;
 use schema::{
-    assets, events, fee_schedules, organizations, ticket_instances, ticket_pricing,
-    ticket_type_codes, ticket_types,
+    assets, events, fee_schedules, organizations, seat_sections, seats, ticket_instances,
+    ticket_pricing, ticket_type_codes, ticket_types,
 };
 use utils::errors::*;
 use uuid::Uuid;
@@ -29,6 +30,7 @@ pub struct TicketType {
     updated_at: NaiveDateTime,
     pub price_in_cents: i64,
     pub cancelled_at: Option<NaiveDateTime>,
+    pub allow_reentry: bool,
 }
 
 #[derive(AsChangeset, Default, Deserialize)]
@@ -41,6 +43,7 @@ pub struct TicketTypeEditableAttributes {
     pub increment: Option<i32>,
     pub limit_per_person: Option<i32>,
     pub price_in_cents: Option<i64>,
+    pub allow_reentry: Option<bool>,
 }
 
 impl TicketType {
@@ -119,7 +122,16 @@ impl TicketType {
         Ok(result)
     }
 
-    pub fn cancel(&self, conn: &PgConnection) -> Result<TicketType, DatabaseError> {
+    /// Cancels the ticket type and schedules refunds for the orders that purchased it
+    pub fn cancel(
+        &self,
+        current_user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<TicketType, DatabaseError> {
+        if self.status == TicketTypeStatus::Cancelled {
+            return DatabaseError::business_process_error("Ticket type has already been cancelled");
+        }
+
         let result: TicketType = diesel::update(self)
             .set((
                 ticket_types::status.eq(TicketTypeStatus::Cancelled),
@@ -129,6 +141,9 @@ impl TicketType {
             .get_result(conn)
             .to_db_error(ErrorCode::UpdateError, "Could not update ticket_types")?;
 
+        EventCancellation::create(result.event_id, Some(result.id), current_user_id)
+            .commit(conn)?;
+
         Ok(result)
     }
 
@@ -269,6 +284,136 @@ impl TicketType {
         Ok(valid_ticket_count as u32)
     }
 
+    pub fn assign_seats(
+        &self,
+        seat_ids: &[Uuid],
+        conn: &PgConnection,
+    ) -> Result<Vec<TicketInstance>, DatabaseError> {
+        let mut unique_seat_ids = seat_ids.to_vec();
+        unique_seat_ids.sort();
+        unique_seat_ids.dedup();
+        if unique_seat_ids.len() != seat_ids.len() {
+            return DatabaseError::validation_error("seat_ids", "Seats must be unique");
+        }
+
+        let venue_id = match Event::find(self.event_id, conn)?.venue_id {
+            Some(venue_id) => venue_id,
+            None => {
+                return DatabaseError::validation_error(
+                    "seat_ids",
+                    "Event must have a venue before seats can be assigned",
+                )
+            }
+        };
+
+        if Seat::find_by_ids_for_venue(seat_ids, venue_id, conn)?.len() != seat_ids.len() {
+            return DatabaseError::validation_error(
+                "seat_ids",
+                "Seats must belong to the event's venue",
+            );
+        }
+
+        let assigned_count: i64 = ticket_instances::table
+            .inner_join(assets::table.inner_join(ticket_types::table))
+            .filter(ticket_types::event_id.eq(self.event_id))
+            .filter(ticket_instances::seat_id.eq_any(seat_ids))
+            .filter(ticket_instances::status.ne(TicketInstanceStatus::Nullified))
+            .select(dsl::count(ticket_instances::id))
+            .first(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not check seat assignments for event",
+            )?;
+        if assigned_count > 0 {
+            return DatabaseError::validation_error(
+                "seat_ids",
+                "One or more seats have already been assigned for this event",
+            );
+        }
+
+        let query = include_str!("../queries/assign_seats_to_tickets.sql");
+        let tickets: Vec<TicketInstance> = diesel::sql_query(query)
+            .bind::<sql_types::Uuid, _>(self.id)
+            .bind::<sql_types::Array<sql_types::Uuid>, _>(seat_ids)
+            .get_results(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not assign seats to tickets")?;
+
+        if tickets.len() != seat_ids.len() {
+            return DatabaseError::validation_error(
+                "seat_ids",
+                "Not enough unassigned tickets are available for the requested seats",
+            );
+        }
+
+        Ok(tickets)
+    }
+
+    pub fn seats(&self, conn: &PgConnection) -> Result<Vec<DisplayTicketTypeSeat>, DatabaseError> {
+        let rows: Vec<(
+            Uuid,
+            TicketInstanceStatus,
+            Option<NaiveDateTime>,
+            Uuid,
+            String,
+            String,
+            String,
+            bool,
+        )> = ticket_instances::table
+            .inner_join(assets::table)
+            .inner_join(seats::table.inner_join(seat_sections::table))
+            .filter(assets::ticket_type_id.eq(self.id))
+            .filter(ticket_instances::status.ne(TicketInstanceStatus::Nullified))
+            .select((
+                ticket_instances::id,
+                ticket_instances::status,
+                ticket_instances::reserved_until,
+                seats::id,
+                seat_sections::name,
+                seats::row_name,
+                seats::seat_number,
+                seats::is_accessible,
+            ))
+            .order_by((seat_sections::name, seats::row_name, seats::seat_number))
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load seats for ticket type",
+            )?;
+
+        let now = Utc::now().naive_utc();
+        Ok(rows
+            .into_iter()
+            .map(
+                |(
+                    ticket_instance_id,
+                    status,
+                    reserved_until,
+                    seat_id,
+                    section_name,
+                    row_name,
+                    seat_number,
+                    is_accessible,
+                )| {
+                    let available = status == TicketInstanceStatus::Available
+                        || (status == TicketInstanceStatus::Reserved
+                            && reserved_until.map_or(false, |r| r < now));
+                    DisplayTicketTypeSeat {
+                        seat: DisplaySeat {
+                            id: seat_id,
+                            section_name,
+                            row_name,
+                            seat_number,
+                            is_accessible,
+                        },
+                        ticket_instance_id,
+                        status,
+                        available,
+                    }
+                },
+            )
+            .collect())
+    }
+
     pub fn current_ticket_pricing(
         &self,
         box_office_pricing: bool,
@@ -4,7 +4,7 @@ use diesel;
 use diesel::expression::dsl;
 use diesel::expression::sql_literal::sql;
 use diesel::prelude::*;
-use diesel::sql_types::BigInt;
+use diesel::sql_types::{BigInt, Nullable, Text, Timestamp, Uuid as dUuid};
 use models::*;
 use schema::{events, organization_users, organizations, users};
 use std::collections::HashMap;
@@ -50,6 +50,7 @@ pub struct User {
     pub last_cart_id: Option<Uuid>,
     pub accepted_terms_date: Option<NaiveDateTime>,
     pub invited_at: Option<NaiveDateTime>,
+    pub marketing_opt_in: bool,
 }
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
@@ -63,6 +64,7 @@ pub struct DisplayUser {
     pub thumb_profile_pic_url: Option<String>,
     pub cover_photo_url: Option<String>,
     pub is_org_owner: bool,
+    pub marketing_opt_in: bool,
 }
 
 #[derive(AsChangeset, Default, Deserialize, Validate, Clone)]
@@ -81,6 +83,7 @@ pub struct UserEditableAttributes {
     pub thumb_profile_pic_url: Option<Option<String>>,
     #[validate(url(message = "Cover photo URL is invalid"))]
     pub cover_photo_url: Option<Option<String>>,
+    pub marketing_opt_in: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
@@ -200,47 +203,52 @@ impl User {
         sort_direction: SortingDir,
         conn: &PgConnection,
     ) -> Result<Payload<HistoryItem>, DatabaseError> {
-        use schema::*;
-        let query = order_items::table
-            .inner_join(orders::table.on(order_items::order_id.eq(orders::id)))
-            .inner_join(events::table.on(order_items::event_id.eq(events::id.nullable())))
-            .filter(orders::status.eq(OrderStatus::Paid))
-            .filter(orders::user_id.eq(self.id))
-            .filter(events::organization_id.eq(organization.id))
-            .group_by((orders::id, orders::order_date, events::name))
-            .select((
-                orders::id,
-                orders::order_date,
-                events::name,
-                sql::<BigInt>(
-                    "cast(COALESCE(sum(
-                    CASE WHEN order_items.item_type = 'Tickets'
-                    THEN (order_items.quantity - order_items.refunded_quantity)
-                    ELSE 0 END
-                    ), 0) as BigInt)",
-                ),
-                sql::<BigInt>(
-                    "cast(sum(order_items.unit_price_in_cents * (order_items.quantity - order_items.refunded_quantity)) as bigint)",
-                ),
-                sql::<BigInt>("count(*) over()"),
-            ))
-            .order_by(sql::<()>(&format!("orders.order_date {}", sort_direction)))
-            .limit(limit as i64)
-            .offset((limit * page) as i64);
-
-        #[derive(Queryable)]
+        let query = include_str!("../queries/find_history_for_organization_fan.sql");
+        // Add sorting order as raw sql string
+        // SECURITY: ensure that you don't inject unescaped external strings into the SQL query
+        let query = query.replace(
+            "{sort_direction}",
+            match sort_direction {
+                SortingDir::Asc => "ASC",
+                SortingDir::Desc => "DESC",
+            },
+        );
+
+        #[derive(QueryableByName)]
         struct R {
+            #[sql_type = "Text"]
+            history_type: String,
+            #[sql_type = "dUuid"]
             order_id: Uuid,
-            order_date: NaiveDateTime,
+            #[sql_type = "Timestamp"]
+            history_date: NaiveDateTime,
+            #[sql_type = "Text"]
             event_name: String,
-            ticket_sales: i64,
-            revenue_in_cents: i64,
+            #[sql_type = "Nullable<BigInt>"]
+            ticket_sales: Option<i64>,
+            #[sql_type = "Nullable<BigInt>"]
+            revenue_in_cents: Option<i64>,
+            #[sql_type = "Nullable<Text>"]
+            from_ticket_type_name: Option<String>,
+            #[sql_type = "Nullable<Text>"]
+            to_ticket_type_name: Option<String>,
+            #[sql_type = "Nullable<BigInt>"]
+            quantity: Option<i64>,
+            #[sql_type = "Nullable<BigInt>"]
+            difference_in_cents: Option<i64>,
+            #[sql_type = "BigInt"]
             total_rows: i64,
         }
-        let results: Vec<R> = query.get_results(conn).to_db_error(
-            ErrorCode::QueryError,
-            "Could not load history for organization fan",
-        )?;
+        let results: Vec<R> = diesel::sql_query(query)
+            .bind::<dUuid, _>(self.id)
+            .bind::<dUuid, _>(organization.id)
+            .bind::<BigInt, _>(limit as i64)
+            .bind::<BigInt, _>((limit * page) as i64)
+            .get_results(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load history for organization fan",
+            )?;
 
         let paging = Paging::new(page, limit);
         let mut total: u64 = 0;
@@ -250,12 +258,23 @@ impl User {
 
         let history = results
             .into_iter()
-            .map(|r| HistoryItem::Purchase {
-                order_id: r.order_id,
-                order_date: r.order_date,
-                event_name: r.event_name,
-                ticket_sales: r.ticket_sales as u32,
-                revenue_in_cents: r.revenue_in_cents as u32,
+            .map(|r| match r.history_type.as_str() {
+                "Exchange" => HistoryItem::Exchange {
+                    order_id: r.order_id,
+                    exchange_date: r.history_date,
+                    event_name: r.event_name,
+                    from_ticket_type_name: r.from_ticket_type_name.unwrap_or_default(),
+                    to_ticket_type_name: r.to_ticket_type_name.unwrap_or_default(),
+                    quantity: r.quantity.unwrap_or(0) as u32,
+                    difference_in_cents: r.difference_in_cents.unwrap_or(0),
+                },
+                _ => HistoryItem::Purchase {
+                    order_id: r.order_id,
+                    order_date: r.history_date,
+                    event_name: r.event_name,
+                    ticket_sales: r.ticket_sales.unwrap_or(0) as u32,
+                    revenue_in_cents: r.revenue_in_cents.unwrap_or(0) as u32,
+                },
             })
             .collect();
 
@@ -470,20 +489,23 @@ impl User {
         PaymentMethod::find_default_for_user(self.id, conn)
     }
 
+    /// Stored payment method for the provider, repeat charge tokens can only be used on the
+    /// merchant account they were created on
     pub fn payment_method(
         &self,
         name: String,
+        merchant_account_id: Option<String>,
         conn: &PgConnection,
     ) -> Result<PaymentMethod, DatabaseError> {
-        let mut payment_methods = PaymentMethod::find_for_user(self.id, Some(name), conn)?;
-        if payment_methods.is_empty() {
-            Err(DatabaseError::new(
-                ErrorCode::NoResults,
-                Some("No payment method found for user".to_string()),
-            ))
-        } else {
-            Ok(payment_methods.remove(0))
-        }
+        PaymentMethod::find_for_user(self.id, Some(name), conn)?
+            .into_iter()
+            .find(|payment_method| payment_method.merchant_account_id == merchant_account_id)
+            .ok_or_else(|| {
+                DatabaseError::new(
+                    ErrorCode::NoResults,
+                    Some("No payment method found for user".to_string()),
+                )
+            })
     }
 
     fn update_role(
@@ -613,6 +635,7 @@ impl From<User> for DisplayUser {
             thumb_profile_pic_url: user.thumb_profile_pic_url,
             cover_photo_url: user.cover_photo_url,
             is_org_owner: false,
+            marketing_opt_in: user.marketing_opt_in,
         }
     }
 }
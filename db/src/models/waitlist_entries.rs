@@ -0,0 +1,485 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use diesel;
+use diesel::dsl::{self, sql};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable};
+use models::*;
+use rand;
+use rand::Rng;
+use schema::{assets, domain_actions, ticket_instances, waitlist_entries};
+use utils::errors::*;
+use uuid::Uuid;
+
+pub const WAITLIST_OFFER_EXPIRY_TIME_MINUTES: i64 = 30;
+
+#[derive(Associations, Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+#[belongs_to(TicketType)]
+#[belongs_to(User)]
+#[table_name = "waitlist_entries"]
+pub struct WaitlistEntry {
+    pub id: Uuid,
+    pub ticket_type_id: Uuid,
+    pub user_id: Uuid,
+    pub quantity: i64,
+    pub status: WaitlistEntryStatus,
+    pub hold_id: Option<Uuid>,
+    pub offer_expires_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name = "waitlist_entries"]
+pub struct NewWaitlistEntry {
+    pub ticket_type_id: Uuid,
+    pub user_id: Uuid,
+    pub quantity: i64,
+}
+
+impl NewWaitlistEntry {
+    pub fn commit(&self, conn: &PgConnection) -> Result<WaitlistEntry, DatabaseError> {
+        let ticket_type = TicketType::find(self.ticket_type_id, conn)?;
+        if ticket_type.status != TicketTypeStatus::Published
+            || ticket_type.remaining_ticket_count(conn)? > 0
+        {
+            return DatabaseError::business_process_error(
+                "Waitlist is only available for sold out ticket types",
+            );
+        }
+
+        if self.quantity < 1
+            || (ticket_type.limit_per_person > 0
+                && self.quantity > ticket_type.limit_per_person as i64)
+        {
+            return DatabaseError::validation_error(
+                "quantity",
+                "Quantity must be at least 1 and within the limit per person",
+            );
+        }
+
+        if WaitlistEntry::find_active_for_user(self.ticket_type_id, self.user_id, conn)?.is_some() {
+            return DatabaseError::business_process_error(
+                "User is already on the waitlist for this ticket type",
+            );
+        }
+
+        let entry: WaitlistEntry = diesel::insert_into(waitlist_entries::table)
+            .values(self)
+            .get_result(conn)
+            .to_db_error(ErrorCode::InsertError, "Could not join waitlist")?;
+
+        // Tickets reserved in carts may become available when the carts expire
+        WaitlistEntry::schedule_processing(
+            self.ticket_type_id,
+            WaitlistEntry::next_reservation_expiry(self.ticket_type_id, conn)?,
+            conn,
+        )?;
+
+        Ok(entry)
+    }
+}
+
+impl WaitlistEntry {
+    pub fn create(ticket_type_id: Uuid, user_id: Uuid, quantity: u32) -> NewWaitlistEntry {
+        NewWaitlistEntry {
+            ticket_type_id,
+            user_id,
+            quantity: quantity as i64,
+        }
+    }
+
+    pub fn find(id: Uuid, conn: &PgConnection) -> Result<WaitlistEntry, DatabaseError> {
+        waitlist_entries::table
+            .find(id)
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load waitlist entry")
+    }
+
+    pub fn find_active_for_user(
+        ticket_type_id: Uuid,
+        user_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Option<WaitlistEntry>, DatabaseError> {
+        waitlist_entries::table
+            .filter(waitlist_entries::ticket_type_id.eq(ticket_type_id))
+            .filter(waitlist_entries::user_id.eq(user_id))
+            .filter(waitlist_entries::status.eq_any(vec![
+                WaitlistEntryStatus::Waiting,
+                WaitlistEntryStatus::Offered,
+            ]))
+            .first(conn)
+            .optional()
+            .to_db_error(ErrorCode::QueryError, "Could not load waitlist entry")
+    }
+
+    pub fn find_active_by_ticket_type_id(
+        ticket_type_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<WaitlistEntry>, DatabaseError> {
+        waitlist_entries::table
+            .filter(waitlist_entries::ticket_type_id.eq(ticket_type_id))
+            .filter(waitlist_entries::status.eq_any(vec![
+                WaitlistEntryStatus::Waiting,
+                WaitlistEntryStatus::Offered,
+            ]))
+            .order_by(waitlist_entries::created_at)
+            .load(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load waitlist entries")
+    }
+
+    /// Returns the position of this entry in the queue, starting at 1. Entries that are not
+    /// waiting have no position.
+    pub fn position(&self, conn: &PgConnection) -> Result<Option<u32>, DatabaseError> {
+        if self.status != WaitlistEntryStatus::Waiting {
+            return Ok(None);
+        }
+
+        let ahead: i64 = waitlist_entries::table
+            .filter(waitlist_entries::ticket_type_id.eq(self.ticket_type_id))
+            .filter(waitlist_entries::status.eq(WaitlistEntryStatus::Waiting))
+            .filter(waitlist_entries::created_at.lt(self.created_at))
+            .select(dsl::count(waitlist_entries::id))
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load waitlist position")?;
+
+        Ok(Some(ahead as u32 + 1))
+    }
+
+    pub fn hold(&self, conn: &PgConnection) -> Result<Option<Hold>, DatabaseError> {
+        match self.hold_id {
+            Some(hold_id) => Ok(Some(Hold::find(hold_id, conn)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Removes the user from the waitlist, returning any offered tickets that have not been
+    /// purchased to the main pool.
+    pub fn cancel(&self, conn: &PgConnection) -> Result<WaitlistEntry, DatabaseError> {
+        match self.status {
+            WaitlistEntryStatus::Waiting => {}
+            WaitlistEntryStatus::Offered => {
+                self.release_offer(conn)?;
+            }
+            _ => {
+                return DatabaseError::business_process_error("Waitlist entry is no longer active");
+            }
+        }
+
+        let entry = self.update_status(WaitlistEntryStatus::Cancelled, conn)?;
+        WaitlistEntry::schedule_processing(self.ticket_type_id, None, conn)?;
+        Ok(entry)
+    }
+
+    /// Queues a `ProcessWaitlist` domain action for the ticket type if there are users on the
+    /// waitlist and no pending action will run by `scheduled_at` (defaults to now).
+    pub fn schedule_processing(
+        ticket_type_id: Uuid,
+        scheduled_at: Option<NaiveDateTime>,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        let active_count: i64 = waitlist_entries::table
+            .filter(waitlist_entries::ticket_type_id.eq(ticket_type_id))
+            .filter(waitlist_entries::status.eq_any(vec![
+                WaitlistEntryStatus::Waiting,
+                WaitlistEntryStatus::Offered,
+            ]))
+            .select(dsl::count(waitlist_entries::id))
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Could not load waitlist entries")?;
+        if active_count == 0 {
+            return Ok(());
+        }
+
+        let scheduled_at = scheduled_at.unwrap_or_else(|| Utc::now().naive_utc());
+        let pending_count: i64 = domain_actions::table
+            .filter(domain_actions::domain_action_type.eq(DomainActionTypes::ProcessWaitlist))
+            .filter(domain_actions::status.eq(DomainActionStatus::Pending))
+            .filter(domain_actions::main_table_id.eq(ticket_type_id))
+            .filter(domain_actions::scheduled_at.le(scheduled_at))
+            .filter(domain_actions::expires_at.gt(dsl::now))
+            .select(dsl::count(domain_actions::id))
+            .first(conn)
+            .to_db_error(ErrorCode::QueryError, "Error loading domain actions")?;
+        if pending_count > 0 {
+            return Ok(());
+        }
+
+        DomainAction::create(
+            None,
+            DomainActionTypes::ProcessWaitlist,
+            None,
+            json!({ "ticket_type_id": ticket_type_id }),
+            Tables::TicketTypes.table_name(),
+            ticket_type_id,
+            scheduled_at,
+            scheduled_at + Duration::days(1),
+            3,
+        )
+        .commit(conn)?;
+
+        Ok(())
+    }
+
+    /// Expires lapsed offers and makes new offers to waiting users, in the order they joined,
+    /// for as long as there are enough unreserved tickets. Returns the entries that received a
+    /// new offer so that they can be notified.
+    pub fn process(
+        ticket_type_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<WaitlistEntry>, DatabaseError> {
+        let now = Utc::now().naive_utc();
+        let mut next_run: Option<NaiveDateTime> = None;
+
+        for entry in WaitlistEntry::find_active_by_ticket_type_id(ticket_type_id, conn)? {
+            if entry.status != WaitlistEntryStatus::Offered
+                || entry.offer_expires_at.map_or(false, |e| e > now)
+            {
+                continue;
+            }
+
+            match entry.release_offer(conn)? {
+                Some(reserved_until) => {
+                    // Tickets from the offer are still in a cart, check again once it expires
+                    next_run = earliest(next_run, Some(reserved_until));
+                }
+                None => {
+                    let (purchased, _, _) = entry.hold_ticket_counts(conn)?;
+                    if purchased > 0 {
+                        entry.update_status(WaitlistEntryStatus::Redeemed, conn)?;
+                    } else {
+                        entry.update_status(WaitlistEntryStatus::Expired, conn)?;
+                        DomainEvent::create(
+                            DomainEventTypes::WaitlistOfferExpired,
+                            "Waitlist offer expired".to_string(),
+                            Tables::WaitlistEntries,
+                            Some(entry.id),
+                            Some(entry.user_id),
+                            None,
+                        )
+                        .commit(conn)?;
+                    }
+                }
+            }
+        }
+
+        let ticket_type = TicketType::find(ticket_type_id, conn)?;
+        let mut available = WaitlistEntry::available_ticket_count(ticket_type_id, conn)?;
+        let mut offers = vec![];
+        for entry in WaitlistEntry::find_active_by_ticket_type_id(ticket_type_id, conn)? {
+            match entry.status {
+                WaitlistEntryStatus::Offered => {
+                    next_run = earliest(next_run, entry.offer_expires_at);
+                    continue;
+                }
+                WaitlistEntryStatus::Waiting => {}
+                _ => continue,
+            }
+
+            // Offers are made strictly in the order users joined the waitlist
+            if entry.quantity > available || ticket_type.status != TicketTypeStatus::Published {
+                break;
+            }
+
+            offers.push(entry.make_offer(&ticket_type, conn)?);
+            next_run = earliest(next_run, offers.last().and_then(|e| e.offer_expires_at));
+            available -= entry.quantity;
+        }
+
+        next_run = earliest(
+            next_run,
+            WaitlistEntry::next_reservation_expiry(ticket_type_id, conn)?,
+        );
+        if let Some(next_run) = next_run {
+            WaitlistEntry::schedule_processing(ticket_type_id, Some(next_run), conn)?;
+        }
+
+        Ok(offers)
+    }
+
+    fn make_offer(
+        &self,
+        ticket_type: &TicketType,
+        conn: &PgConnection,
+    ) -> Result<WaitlistEntry, DatabaseError> {
+        let offer_expires_at =
+            Utc::now().naive_utc() + Duration::minutes(WAITLIST_OFFER_EXPIRY_TIME_MINUTES);
+        let hold = Hold::create_hold(
+            format!("Waitlist offer {}", self.id),
+            ticket_type.event_id,
+            generate_offer_code(),
+            Some(0),
+            None,
+            Some(offer_expires_at),
+            Some(self.quantity as u32),
+            HoldTypes::Discount,
+            ticket_type.id,
+            None,
+        )
+        .commit(conn)?;
+        hold.set_quantity(self.quantity as u32, conn)?;
+
+        let entry: WaitlistEntry = diesel::update(
+            waitlist_entries::table
+                .filter(waitlist_entries::id.eq(self.id))
+                .filter(waitlist_entries::updated_at.eq(self.updated_at)),
+        )
+        .set((
+            waitlist_entries::status.eq(WaitlistEntryStatus::Offered),
+            waitlist_entries::hold_id.eq(hold.id),
+            waitlist_entries::offer_expires_at.eq(offer_expires_at),
+            waitlist_entries::updated_at.eq(dsl::now),
+        ))
+        .get_result(conn)
+        .to_db_error(ErrorCode::UpdateError, "Could not update waitlist entry")?;
+
+        DomainEvent::create(
+            DomainEventTypes::WaitlistOfferCreated,
+            "Waitlist offer created".to_string(),
+            Tables::WaitlistEntries,
+            Some(entry.id),
+            Some(entry.user_id),
+            Some(json!({ "hold_id": hold.id, "offer_expires_at": offer_expires_at })),
+        )
+        .commit(conn)?;
+
+        Ok(entry)
+    }
+
+    /// Returns unpurchased tickets in the offer hold to the main pool. If tickets from the hold
+    /// are still reserved in a cart, the time that reservation expires is returned.
+    fn release_offer(&self, conn: &PgConnection) -> Result<Option<NaiveDateTime>, DatabaseError> {
+        let hold = match self.hold(conn)? {
+            Some(hold) => hold,
+            None => return Ok(None),
+        };
+
+        let (_, releasable, reserved_until) = self.hold_ticket_counts(conn)?;
+        if releasable > 0 {
+            TicketInstance::release_from_hold(hold.id, hold.ticket_type_id, releasable, conn)?;
+        }
+
+        Ok(reserved_until)
+    }
+
+    /// Returns the number of purchased tickets in the offer hold, the number that can be
+    /// released and the latest expiry of tickets currently reserved in a cart.
+    fn hold_ticket_counts(
+        &self,
+        conn: &PgConnection,
+    ) -> Result<(u32, u32, Option<NaiveDateTime>), DatabaseError> {
+        let hold_id = match self.hold_id {
+            Some(hold_id) => hold_id,
+            None => return Ok((0, 0, None)),
+        };
+
+        let (purchased, releasable, reserved_until): (
+            Option<i64>,
+            Option<i64>,
+            Option<NaiveDateTime>,
+        ) = ticket_instances::table
+            .filter(ticket_instances::hold_id.eq(hold_id))
+            .select((
+                sql::<Nullable<BigInt>>(
+                    "SUM(CASE WHEN status IN ('Purchased', 'Redeemed') THEN 1 ELSE 0 END)",
+                ),
+                sql::<Nullable<BigInt>>(
+                    "SUM(CASE WHEN status = 'Available'
+                        OR (status = 'Reserved' AND reserved_until < now()) THEN 1 ELSE 0 END)",
+                ),
+                sql::<Nullable<diesel::sql_types::Timestamp>>(
+                    "MAX(CASE WHEN status = 'Reserved' AND reserved_until >= now()
+                        THEN reserved_until ELSE NULL END)",
+                ),
+            ))
+            .first(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load ticket counts for waitlist offer",
+            )?;
+
+        Ok((
+            purchased.unwrap_or(0) as u32,
+            releasable.unwrap_or(0) as u32,
+            reserved_until,
+        ))
+    }
+
+    /// Number of tickets in the main pool that are not held and not reserved by an active cart
+    fn available_ticket_count(
+        ticket_type_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<i64, DatabaseError> {
+        ticket_instances::table
+            .inner_join(assets::table)
+            .filter(assets::ticket_type_id.eq(ticket_type_id))
+            .filter(ticket_instances::hold_id.is_null())
+            .filter(ticket_instances::status.eq_any(vec![
+                TicketInstanceStatus::Available,
+                TicketInstanceStatus::Reserved,
+            ]))
+            .filter(
+                ticket_instances::order_item_id
+                    .is_null()
+                    .or(ticket_instances::reserved_until.lt(dsl::now.nullable())),
+            )
+            .select(dsl::count(ticket_instances::id))
+            .first(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load available tickets for waitlist",
+            )
+    }
+
+    /// Earliest time a ticket of this type reserved in a cart will be released
+    fn next_reservation_expiry(
+        ticket_type_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Option<NaiveDateTime>, DatabaseError> {
+        ticket_instances::table
+            .inner_join(assets::table)
+            .filter(assets::ticket_type_id.eq(ticket_type_id))
+            .filter(ticket_instances::hold_id.is_null())
+            .filter(ticket_instances::status.eq(TicketInstanceStatus::Reserved))
+            .filter(ticket_instances::reserved_until.ge(dsl::now.nullable()))
+            .select(dsl::min(ticket_instances::reserved_until))
+            .first(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load reservations for waitlist",
+            )
+    }
+
+    fn update_status(
+        &self,
+        status: WaitlistEntryStatus,
+        conn: &PgConnection,
+    ) -> Result<WaitlistEntry, DatabaseError> {
+        diesel::update(self)
+            .set((
+                waitlist_entries::status.eq(status),
+                waitlist_entries::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(ErrorCode::UpdateError, "Could not update waitlist entry")
+    }
+}
+
+fn earliest(a: Option<NaiveDateTime>, b: Option<NaiveDateTime>) -> Option<NaiveDateTime> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if a < b { a } else { b }),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+fn generate_offer_code() -> String {
+    let hash_char_list = vec![
+        '2', '3', '4', '5', '6', '7', '8', '9', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'J', 'K',
+        'M', 'N', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
+    ];
+    let code: String = (0..8)
+        .map(|_| hash_char_list[rand::thread_rng().gen_range(0, hash_char_list.len())])
+        .collect();
+    format!("WL{}", code)
+}
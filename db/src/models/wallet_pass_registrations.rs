@@ -0,0 +1,213 @@
+use chrono::prelude::*;
+use diesel;
+use diesel::dsl;
+use diesel::prelude::*;
+use diesel::sql_types::{Nullable, Text, Timestamp, Uuid as dUuid};
+use models::*;
+use schema::{assets, ticket_instances, ticket_types, wallet_pass_registrations};
+use serde_json;
+use time::Duration;
+use utils::errors::*;
+use uuid::Uuid;
+
+/// A device that added a ticket's pass to its wallet and wants to be told when the pass changes
+#[derive(Associations, Clone, Debug, Deserialize, Identifiable, PartialEq, Queryable, Serialize)]
+#[belongs_to(TicketInstance)]
+#[table_name = "wallet_pass_registrations"]
+pub struct WalletPassRegistration {
+    pub id: Uuid,
+    pub ticket_instance_id: Uuid,
+    pub device_library_identifier: String,
+    pub push_token: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name = "wallet_pass_registrations"]
+pub struct NewWalletPassRegistration {
+    pub ticket_instance_id: Uuid,
+    pub device_library_identifier: String,
+    pub push_token: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, QueryableByName, Serialize)]
+pub struct UpdatedWalletPass {
+    #[sql_type = "dUuid"]
+    pub ticket_instance_id: Uuid,
+    #[sql_type = "Timestamp"]
+    pub updated_at: NaiveDateTime,
+}
+
+impl NewWalletPassRegistration {
+    /// Registering the same pass on a device again refreshes its push token
+    pub fn commit(&self, conn: &PgConnection) -> Result<WalletPassRegistration, DatabaseError> {
+        diesel::insert_into(wallet_pass_registrations::table)
+            .values(self)
+            .on_conflict((
+                wallet_pass_registrations::device_library_identifier,
+                wallet_pass_registrations::ticket_instance_id,
+            ))
+            .do_update()
+            .set((
+                wallet_pass_registrations::push_token.eq(&self.push_token),
+                wallet_pass_registrations::updated_at.eq(dsl::now),
+            ))
+            .get_result(conn)
+            .to_db_error(
+                ErrorCode::InsertError,
+                "Could not create wallet pass registration",
+            )
+    }
+}
+
+impl WalletPassRegistration {
+    pub fn create(
+        ticket_instance_id: Uuid,
+        device_library_identifier: String,
+        push_token: String,
+    ) -> NewWalletPassRegistration {
+        NewWalletPassRegistration {
+            ticket_instance_id,
+            device_library_identifier,
+            push_token,
+        }
+    }
+
+    /// Schedules pushing the latest version of the passes for the ticket or event to the wallets
+    /// holding them
+    pub fn schedule_update(
+        main_table: Tables,
+        main_table_id: Uuid,
+        payload: serde_json::Value,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        let now = Utc::now().naive_utc();
+        DomainAction::create(
+            None,
+            DomainActionTypes::UpdateWalletPasses,
+            None,
+            payload,
+            main_table.table_name(),
+            main_table_id,
+            now,
+            now + Duration::days(1),
+            3,
+        )
+        .commit(conn)?;
+
+        Ok(())
+    }
+
+    /// Passes issued before the event's redeem date are issued without a barcode, schedules
+    /// pushing them again once the redeem date is reached
+    pub fn schedule_redeem_date_update(
+        event: &Event,
+        conn: &PgConnection,
+    ) -> Result<(), DatabaseError> {
+        let redeem_date = match event.redeem_date {
+            Some(redeem_date) if redeem_date > Utc::now().naive_utc() => redeem_date,
+            _ => return Ok(()),
+        };
+        DomainAction::create(
+            None,
+            DomainActionTypes::UpdateWalletPasses,
+            None,
+            json!({ "redeem_date": redeem_date }),
+            Tables::Events.table_name(),
+            event.id,
+            redeem_date,
+            redeem_date + Duration::days(1),
+            3,
+        )
+        .commit(conn)?;
+
+        Ok(())
+    }
+
+    pub fn find_for_ticket_instance(
+        ticket_instance_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<WalletPassRegistration>, DatabaseError> {
+        wallet_pass_registrations::table
+            .filter(wallet_pass_registrations::ticket_instance_id.eq(ticket_instance_id))
+            .order_by(wallet_pass_registrations::created_at)
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load wallet pass registrations",
+            )
+    }
+
+    pub fn find_for_event(
+        event_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<Vec<WalletPassRegistration>, DatabaseError> {
+        wallet_pass_registrations::table
+            .inner_join(
+                ticket_instances::table.inner_join(assets::table.inner_join(ticket_types::table)),
+            )
+            .filter(ticket_types::event_id.eq(event_id))
+            .select(wallet_pass_registrations::all_columns)
+            .order_by(wallet_pass_registrations::created_at)
+            .load(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load wallet pass registrations",
+            )
+    }
+
+    /// Passes registered on the device along with when they last changed, limited to those that
+    /// changed after `updated_since` if it is provided. A pass changes when its ticket or event does.
+    pub fn find_updated_for_device(
+        device_library_identifier: &str,
+        updated_since: Option<NaiveDateTime>,
+        conn: &PgConnection,
+    ) -> Result<Vec<UpdatedWalletPass>, DatabaseError> {
+        let query = include_str!("../queries/find_updated_wallet_passes_for_device.sql");
+        diesel::sql_query(query)
+            .bind::<Text, _>(device_library_identifier)
+            .bind::<Nullable<Timestamp>, _>(updated_since)
+            .get_results(conn)
+            .to_db_error(
+                ErrorCode::QueryError,
+                "Could not load updated wallet passes",
+            )
+    }
+
+    pub fn destroy(
+        device_library_identifier: &str,
+        ticket_instance_id: Uuid,
+        conn: &PgConnection,
+    ) -> Result<usize, DatabaseError> {
+        diesel::delete(
+            wallet_pass_registrations::table
+                .filter(
+                    wallet_pass_registrations::device_library_identifier
+                        .eq(device_library_identifier),
+                )
+                .filter(wallet_pass_registrations::ticket_instance_id.eq(ticket_instance_id)),
+        )
+        .execute(conn)
+        .to_db_error(
+            ErrorCode::DeleteError,
+            "Could not remove wallet pass registration",
+        )
+    }
+
+    /// Removes every registration for a push token that is no longer active
+    pub fn destroy_for_push_token(
+        push_token: &str,
+        conn: &PgConnection,
+    ) -> Result<usize, DatabaseError> {
+        diesel::delete(
+            wallet_pass_registrations::table
+                .filter(wallet_pass_registrations::push_token.eq(push_token)),
+        )
+        .execute(conn)
+        .to_db_error(
+            ErrorCode::DeleteError,
+            "Could not remove wallet pass registrations",
+        )
+    }
+}
@@ -0,0 +1,33 @@
+UPDATE ticket_instances
+SET
+    seat_id = s.seat_id,
+    updated_at = now()
+FROM (SELECT t.id, seats.seat_id
+      FROM (SELECT t.id, row_number() OVER (ORDER BY t.token_id) AS position
+            FROM ticket_instances AS t
+                   INNER JOIN assets AS a ON t.asset_id = a.id
+            WHERE a.ticket_type_id = $1
+              AND t.seat_id IS NULL
+              AND t.status = 'Available'
+              AND t.order_item_id IS NULL
+            ORDER BY t.token_id
+            LIMIT cardinality($2)) AS t
+             INNER JOIN (SELECT seat_id, position
+                         FROM unnest($2) WITH ORDINALITY AS u(seat_id, position)) AS seats
+                        ON seats.position = t.position) AS s
+WHERE ticket_instances.id = s.id
+    RETURNING
+      ticket_instances.id,
+      ticket_instances.asset_id,
+      ticket_instances.token_id,
+      ticket_instances.hold_id,
+      ticket_instances.order_item_id,
+      ticket_instances.wallet_id,
+      ticket_instances.reserved_until,
+      ticket_instances.status,
+      ticket_instances.redeem_key,
+      ticket_instances.transfer_key,
+      ticket_instances.transfer_expiry_date,
+      ticket_instances.created_at,
+      ticket_instances.updated_at,
+      ticket_instances.seat_id;
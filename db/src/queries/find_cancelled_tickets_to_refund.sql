@@ -0,0 +1,21 @@
+SELECT COALESCE(sale.order_item_id, t.order_item_id) AS order_item_id,
+       t.id                                          AS ticket_instance_id
+FROM ticket_instances t
+       INNER JOIN assets a ON t.asset_id = a.id
+       INNER JOIN ticket_types tt ON a.ticket_type_id = tt.id
+       LEFT JOIN refunded_tickets rt ON rt.ticket_instance_id = t.id
+       LEFT JOIN LATERAL (
+  SELECT rl.order_item_id
+  FROM resale_listings rl
+  WHERE rl.ticket_instance_id = t.id
+    AND rl.status = 'Sold'
+  ORDER BY rl.sold_at DESC
+  LIMIT 1
+  ) sale ON TRUE
+       INNER JOIN order_items oi ON oi.id = COALESCE(sale.order_item_id, t.order_item_id)
+WHERE oi.order_id = $1
+  AND tt.event_id = $2
+  AND ($3 IS NULL OR tt.id = $3)
+  AND t.status IN ('Purchased', 'Redeemed')
+  AND rt.ticket_refunded_at IS NULL
+ORDER BY t.id;
@@ -0,0 +1,45 @@
+SELECT history.*, CAST(count(*) over() AS BIGINT) AS total_rows
+FROM (
+       SELECT 'Purchase'                                                  AS history_type,
+              o.id                                                        AS order_id,
+              o.order_date                                                AS history_date,
+              e.name                                                      AS event_name,
+              CAST(COALESCE(SUM(CASE WHEN oi.item_type = 'Tickets'
+                                     THEN oi.quantity - oi.refunded_quantity
+                                     ELSE 0 END), 0) AS BIGINT)           AS ticket_sales,
+              CAST(SUM(oi.unit_price_in_cents * (oi.quantity - oi.refunded_quantity)) AS BIGINT) AS revenue_in_cents,
+              CAST(NULL AS TEXT)                                          AS from_ticket_type_name,
+              CAST(NULL AS TEXT)                                          AS to_ticket_type_name,
+              CAST(NULL AS BIGINT)                                        AS quantity,
+              CAST(NULL AS BIGINT)                                        AS difference_in_cents
+       FROM order_items oi
+              JOIN orders o ON oi.order_id = o.id
+              JOIN events e ON oi.event_id = e.id
+       WHERE o.status = 'Paid'
+         AND o.user_id = $1
+         AND e.organization_id = $2
+       GROUP BY o.id, o.order_date, e.name
+       UNION ALL
+       SELECT 'Exchange'           AS history_type,
+              o.id                 AS order_id,
+              ox.created_at        AS history_date,
+              e.name               AS event_name,
+              CAST(NULL AS BIGINT) AS ticket_sales,
+              CAST(NULL AS BIGINT) AS revenue_in_cents,
+              from_tt.name         AS from_ticket_type_name,
+              to_tt.name           AS to_ticket_type_name,
+              ox.quantity          AS quantity,
+              ox.difference_in_cents AS difference_in_cents
+       FROM order_exchanges ox
+              JOIN orders o ON ox.order_id = o.id
+              JOIN order_items from_oi ON ox.from_order_item_id = from_oi.id
+              JOIN ticket_types from_tt ON from_oi.ticket_type_id = from_tt.id
+              JOIN order_items to_oi ON ox.to_order_item_id = to_oi.id
+              JOIN ticket_types to_tt ON to_oi.ticket_type_id = to_tt.id
+              JOIN events e ON to_tt.event_id = e.id
+       WHERE o.user_id = $1
+         AND e.organization_id = $2
+     ) AS history
+ORDER BY history.history_date {sort_direction}
+LIMIT $3
+OFFSET $4;
@@ -0,0 +1,30 @@
+SELECT revocations.ticket_instance_id, max(revocations.revoked_at) AS revoked_at
+FROM (
+       SELECT rt.ticket_instance_id, rt.ticket_refunded_at AS revoked_at
+       FROM refunded_tickets rt
+              JOIN ticket_instances ti ON ti.id = rt.ticket_instance_id
+              JOIN assets a ON a.id = ti.asset_id
+              JOIN ticket_types tt ON tt.id = a.ticket_type_id
+       WHERE tt.event_id = $1
+         AND rt.ticket_refunded_at IS NOT NULL
+       UNION ALL
+       SELECT de.main_id AS ticket_instance_id, de.created_at AS revoked_at
+       FROM domain_events de
+              JOIN ticket_instances ti ON ti.id = de.main_id
+              JOIN assets a ON a.id = ti.asset_id
+              JOIN ticket_types tt ON tt.id = a.ticket_type_id
+       WHERE tt.event_id = $1
+         AND de.main_table = $2
+         AND de.event_type = $3
+       UNION ALL
+       SELECT ti.id AS ticket_instance_id, ti.updated_at AS revoked_at
+       FROM ticket_instances ti
+              JOIN assets a ON a.id = ti.asset_id
+              JOIN ticket_types tt ON tt.id = a.ticket_type_id
+       WHERE tt.event_id = $1
+         AND ti.status = 'Nullified'
+         -- Exchanged tickets, unsold tickets nullified when inventory is reduced have no order item
+         AND ti.order_item_id IS NOT NULL
+     ) AS revocations
+GROUP BY revocations.ticket_instance_id
+ORDER BY revocations.ticket_instance_id;
@@ -0,0 +1,10 @@
+-- Passes also change when the event's redeem date is reached as their barcode is added then
+SELECT wpr.ticket_instance_id, GREATEST(ti.updated_at, e.updated_at, CASE WHEN e.redeem_date <= now() THEN e.redeem_date END) AS updated_at
+FROM wallet_pass_registrations wpr
+       JOIN ticket_instances ti ON ti.id = wpr.ticket_instance_id
+       JOIN assets a ON a.id = ti.asset_id
+       JOIN ticket_types tt ON tt.id = a.ticket_type_id
+       JOIN events e ON e.id = tt.event_id
+WHERE wpr.device_library_identifier = $1
+  AND ($2 IS NULL OR GREATEST(ti.updated_at, e.updated_at, CASE WHEN e.redeem_date <= now() THEN e.redeem_date END) > $2)
+ORDER BY wpr.ticket_instance_id;
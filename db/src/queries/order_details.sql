@@ -16,16 +16,18 @@ FROM (
             ELSE e.name || ' - ' || tt.name
         END AS description,
         CASE
-            WHEN oi.quantity = oi.refunded_quantity or rt.ticket_refunded_at is not null THEN 0
+            WHEN oi.quantity = oi.refunded_quantity or rt.ticket_refunded_at is not null or ti.status = 'Nullified' THEN 0
             WHEN oi.item_type = 'EventFees' THEN 0
             ELSE oi.unit_price_in_cents
         END as ticket_price_in_cents,
         CASE
-            WHEN oi.quantity = oi.refunded_quantity or rt.fee_refunded_at is not null THEN 0
+            WHEN oi.quantity = oi.refunded_quantity or rt.fee_refunded_at is not null or ti.status = 'Nullified' THEN 0
             WHEN fi.unit_price_in_cents is null THEN oi.company_fee_in_cents + oi.client_fee_in_cents
             ELSE fi.unit_price_in_cents
         END as fees_price_in_cents,
         CASE
+            -- Only exchanged tickets are nullified once they have been sold
+            WHEN ti.status = 'Nullified' THEN 'Exchanged'
             WHEN oi.quantity = oi.refunded_quantity or rt.ticket_refunded_at is not null THEN 'Refunded'
             WHEN w.user_id <> o.user_id THEN 'Transferred'
             WHEN ti.status is null THEN 'Purchased'
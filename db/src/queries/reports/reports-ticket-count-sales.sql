@@ -18,7 +18,8 @@ FROM ticket_types tt
        LEFT JOIN order_items oi_t_fees ON oi_t_fees.parent_id = oi.id AND oi_t_fees.item_type = 'PerUnitFees'
   --   -- Per event fees
        LEFT JOIN order_items oi_e_fees ON oi_e_fees.order_id = oi.order_id AND oi_e_fees.item_type = 'EventFees'
-       RIGHT JOIN orders o on oi.order_id = o.id AND o.status = 'Paid'
+  --   -- Comps sent to their recipients are not sales
+       RIGHT JOIN orders o on oi.order_id = o.id AND o.status = 'Paid' AND o.order_type <> 'BackOffice'
 WHERE e.id IS NOT NULL
   AND ($1 IS NULL OR e.id = $1)
   AND ($2 IS NULL OR e.organization_id = $2)
@@ -15,13 +15,15 @@ SELECT oi.event_id,
        CAST(COALESCE(AVG(oi_fees.client_fee_in_cents * (oi_fees.quantity - oi.refunded_quantity)), 0) AS BIGINT)  AS client_fee_in_cents
 FROM orders
        LEFT JOIN order_items oi on orders.id = oi.order_id
-       LEFT JOIN order_items oi_fees on oi.id = oi_fees.parent_id
+       LEFT JOIN order_items oi_fees on oi.id = oi_fees.parent_id AND oi_fees.item_type = 'PerUnitFees'
        LEFT JOIN ticket_types tt ON (oi.ticket_type_id = tt.id)
        LEFT JOIN ticket_pricing tp ON (oi.ticket_pricing_id = tp.id)
        LEFT JOIN payments p on orders.id = p.order_id
        LEFT JOIN holds h on oi.hold_id = h.id
        LEFT JOIN events e on oi.event_id = e.id
 WHERE orders.status = 'Paid'
+  -- Comps sent to their recipients are not sales
+  AND orders.order_type <> 'BackOffice'
   AND ($1 is null or oi.event_id = $1)
   AND ($2 is null or e.organization_id = $2)
   AND oi.item_type = 'Tickets'
@@ -0,0 +1,16 @@
+SELECT tt.event_id,
+       CAST(COUNT(rl.id) AS BIGINT)                                              AS resold_count,
+       CAST(COALESCE(SUM(rl.price_in_cents), 0) AS BIGINT)                       AS total_resale_price_in_cents,
+       CAST(COALESCE(SUM(rl.price_in_cents - rl.seller_payout_in_cents), 0) AS BIGINT) AS total_fee_in_cents,
+       CAST(COALESCE(SUM(rl.seller_payout_in_cents), 0) AS BIGINT)               AS total_seller_payout_in_cents
+FROM resale_listings rl
+       INNER JOIN ticket_instances ti on rl.ticket_instance_id = ti.id
+       INNER JOIN assets a on ti.asset_id = a.id
+       INNER JOIN ticket_types tt on a.ticket_type_id = tt.id
+       INNER JOIN events e on tt.event_id = e.id
+WHERE rl.status = 'Sold'
+  AND ($1 is null or tt.event_id = $1)
+  AND ($2 is null or e.organization_id = $2)
+  AND ($3 IS NULL OR rl.sold_at >= $3)
+  AND ($4 IS NULL OR rl.sold_at <= $4)
+GROUP BY tt.event_id;
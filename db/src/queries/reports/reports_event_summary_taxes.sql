@@ -0,0 +1,22 @@
+SELECT oi.event_id,
+       oi.tax_rule_id,
+       COALESCE(tr.name, 'Tax')                                                        AS tax_name,
+       CAST(COALESCE(tr.rate_in_basis_points, 0) AS BIGINT)                            AS rate_in_basis_points,
+       COALESCE(tr.inclusive, oi.unit_price_in_cents = 0)                              AS inclusive,
+       CAST(COALESCE(SUM(oi.tax_in_cents * (oi.quantity - oi.refunded_quantity))
+                         FILTER (WHERE parent.item_type IN ('Tickets', 'Discount')), 0) AS BIGINT)   AS total_ticket_tax_in_cents,
+       CAST(COALESCE(SUM(oi.tax_in_cents * (oi.quantity - oi.refunded_quantity))
+                         FILTER (WHERE parent.item_type IN ('PerUnitFees', 'EventFees')), 0) AS BIGINT) AS total_fee_tax_in_cents,
+       CAST(COALESCE(SUM(oi.tax_in_cents * (oi.quantity - oi.refunded_quantity)), 0) AS BIGINT)     AS total_tax_in_cents
+FROM orders
+       LEFT JOIN order_items oi on orders.id = oi.order_id
+       LEFT JOIN order_items parent on oi.parent_id = parent.id
+       LEFT JOIN tax_rules tr on oi.tax_rule_id = tr.id
+       LEFT JOIN events e on oi.event_id = e.id
+WHERE orders.status = 'Paid'
+  AND ($1 is null or oi.event_id = $1)
+  AND ($2 is null or e.organization_id = $2)
+  AND oi.item_type = 'Tax'
+  AND ($3 IS NULL OR orders.paid_at >= $3)
+  AND ($4 IS NULL OR orders.paid_at <= $4)
+GROUP BY oi.event_id, oi.tax_rule_id, tr.name, tr.rate_in_basis_points, COALESCE(tr.inclusive, oi.unit_price_in_cents = 0);
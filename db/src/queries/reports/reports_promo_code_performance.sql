@@ -0,0 +1,75 @@
+-- Tickets bought with a code or hold in paid orders, net of refunds
+WITH sales AS (
+    SELECT oi.code_id,
+           oi.hold_id,
+           orders.user_id,
+           oi.quantity - oi.refunded_quantity AS tickets_sold,
+           (oi.quantity - oi.refunded_quantity) * oi.unit_price_in_cents +
+           (COALESCE(oi_discounts.quantity, 0) - COALESCE(oi_discounts.refunded_quantity, 0)) * COALESCE(oi_discounts.unit_price_in_cents, 0) AS gross,
+           -- Hold discounts are taken off the ticket price, code discounts are separate order items
+           CASE WHEN h.hold_type = 'Discount'
+             THEN (oi.quantity - oi.refunded_quantity) * GREATEST(tp.price_in_cents - oi.unit_price_in_cents, 0)
+             ELSE 0 END -
+           (COALESCE(oi_discounts.quantity, 0) - COALESCE(oi_discounts.refunded_quantity, 0)) * COALESCE(oi_discounts.unit_price_in_cents, 0) AS discount
+    FROM orders
+           JOIN order_items oi ON orders.id = oi.order_id AND oi.item_type = 'Tickets'
+           LEFT JOIN order_items oi_discounts ON oi.id = oi_discounts.parent_id AND oi_discounts.item_type = 'Discount'
+           LEFT JOIN ticket_pricing tp ON oi.ticket_pricing_id = tp.id
+           LEFT JOIN holds h ON oi.hold_id = h.id
+    WHERE orders.status = 'Paid'
+      -- Comps sent to their recipients are not sales
+      AND orders.order_type <> 'BackOffice'
+      AND (oi.code_id IS NOT NULL OR oi.hold_id IS NOT NULL)
+      AND ($3 IS NULL OR orders.paid_at >= $3)
+      AND ($4 IS NULL OR orders.paid_at <= $4)
+)
+SELECT 'Code'                                       AS redemption_type,
+       c.id,
+       c.event_id,
+       e.name                                       AS event_name,
+       c.name,
+       c.redemption_code,
+       c.code_type                                  AS discount_type,
+       CAST(COALESCE(SUM(s.tickets_sold), 0) AS BIGINT) AS tickets_sold,
+       CAST(COALESCE(SUM(s.gross), 0) AS BIGINT)        AS gross,
+       CAST(COALESCE(SUM(s.discount), 0) AS BIGINT)     AS discount_in_cents,
+       COUNT(DISTINCT s.user_id)                    AS unique_buyers,
+       CAST(GREATEST(c.max_uses - (
+         SELECT COUNT(DISTINCT o.id)
+         FROM order_items u
+                JOIN orders o ON o.id = u.order_id
+         WHERE u.code_id = c.id
+           AND o.status = 'Paid'
+       ), 0) AS BIGINT)                             AS remaining_uses
+FROM codes c
+       JOIN events e ON c.event_id = e.id
+       LEFT JOIN sales s ON s.code_id = c.id
+WHERE ($1 IS NULL OR c.event_id = $1)
+  AND ($2 IS NULL OR e.organization_id = $2)
+GROUP BY c.id, e.id
+UNION ALL
+SELECT 'Hold'                                       AS redemption_type,
+       h.id,
+       h.event_id,
+       e.name                                       AS event_name,
+       h.name,
+       h.redemption_code,
+       h.hold_type                                  AS discount_type,
+       CAST(COALESCE(SUM(s.tickets_sold), 0) AS BIGINT) AS tickets_sold,
+       CAST(COALESCE(SUM(s.gross), 0) AS BIGINT)        AS gross,
+       CAST(COALESCE(SUM(s.discount), 0) AS BIGINT)     AS discount_in_cents,
+       COUNT(DISTINCT s.user_id)                    AS unique_buyers,
+       -- Tickets still held
+       (
+         SELECT COUNT(ti.id)
+         FROM ticket_instances ti
+         WHERE ti.hold_id = h.id
+           AND ti.status IN ('Available', 'Reserved')
+       )                                            AS remaining_uses
+FROM holds h
+       JOIN events e ON h.event_id = e.id
+       LEFT JOIN sales s ON s.hold_id = h.id
+WHERE ($1 IS NULL OR h.event_id = $1)
+  AND ($2 IS NULL OR e.organization_id = $2)
+GROUP BY h.id, e.id
+ORDER BY event_name, redemption_type, name;
@@ -1,31 +1,56 @@
 SELECT e.name                                               AS event_name,
        COALESCE(tt.name, 'Per Event Fees')                  AS ticket_name,
        CAST(oi.quantity AS BIGINT)                          AS quantity,
-       CAST(COALESCE(oi.refunded_quantity, 0) AS BIGINT)    AS refunded_quantity,
+       -- Exchanged tickets are refunded on their order item but reported separately
+       CAST(COALESCE(oi.refunded_quantity, 0) - COALESCE(ex_from.quantity, 0) AS BIGINT) AS refunded_quantity,
+       CAST(COALESCE(ex_from.quantity, 0) AS BIGINT)        AS exchanged_quantity,
        CAST(oi.unit_price_in_cents AS BIGINT)               AS unit_price_in_cents,
        CAST(COALESCE(oi.company_fee_in_cents, 0) AS BIGINT) AS company_fee_in_cents,
        CAST(COALESCE(oi.client_fee_in_cents, 0) AS BIGINT)  AS client_fee_in_cents,
-       orders.paid_at                                       AS transaction_date,
+       -- Tax on the tickets along with their fees and discounts, including taxes that are part of the price
+       CAST(COALESCE((SELECT SUM(t.tax_in_cents * (t.quantity - t.refunded_quantity))
+                      FROM order_items t
+                      WHERE t.item_type = 'Tax'
+                        AND t.parent_id IN (oi.id, oi_fees.id, oi_discounts.id)), 0) AS BIGINT) AS tax_in_cents,
+       COALESCE(ex_to.created_at, orders.paid_at)           AS transaction_date,
+       ex_to.id                                             AS order_exchange_id,
        orders.order_type,
        p.payment_method,
-       h.redemption_code,
+       COALESCE(h.redemption_code, c.redemption_code)       AS redemption_code,
        orders.id                                            AS order_id,
        oi.event_id,
        orders.user_id,
        CAST (
          (oi.quantity - oi.refunded_quantity) * oi.unit_price_in_cents +
-         (COALESCE(oi_fees.quantity, 0) - COALESCE(oi_fees.refunded_quantity, 0)) * COALESCE(oi_fees.unit_price_in_cents, 0)
-       AS BIGINT) AS gross
+         (COALESCE(oi_fees.quantity, 0) - COALESCE(oi_fees.refunded_quantity, 0)) * COALESCE(oi_fees.unit_price_in_cents, 0) +
+         (COALESCE(oi_discounts.quantity, 0) - COALESCE(oi_discounts.refunded_quantity, 0)) * COALESCE(oi_discounts.unit_price_in_cents, 0)
+       AS BIGINT) AS gross,
+       -- Hold discounts are taken off the ticket price, code discounts are separate order items
+       CAST (
+         CASE WHEN h.hold_type = 'Discount'
+           THEN (oi.quantity - oi.refunded_quantity) * GREATEST(tp.price_in_cents - oi.unit_price_in_cents, 0)
+           ELSE 0 END -
+         (COALESCE(oi_discounts.quantity, 0) - COALESCE(oi_discounts.refunded_quantity, 0)) * COALESCE(oi_discounts.unit_price_in_cents, 0)
+       AS BIGINT) AS discount_in_cents
 FROM orders
        LEFT JOIN order_items oi on orders.id = oi.order_id
-       LEFT JOIN order_items oi_fees on oi.id = oi_fees.parent_id
+       LEFT JOIN order_items oi_fees on oi.id = oi_fees.parent_id AND oi_fees.item_type = 'PerUnitFees'
+       LEFT JOIN order_items oi_discounts on oi.id = oi_discounts.parent_id AND oi_discounts.item_type = 'Discount'
        LEFT JOIN ticket_types tt ON (oi.ticket_type_id = tt.id)
+       LEFT JOIN ticket_pricing tp ON (oi.ticket_pricing_id = tp.id)
        LEFT JOIN payments p on orders.id = p.order_id
        LEFT JOIN holds h on oi.hold_id = h.id
+       LEFT JOIN codes c on oi.code_id = c.id
        LEFT JOIN events e on oi.event_id = e.id
+       LEFT JOIN (SELECT from_order_item_id, SUM(quantity) AS quantity
+                  FROM order_exchanges
+                  GROUP BY from_order_item_id) ex_from on oi.id = ex_from.from_order_item_id
+       LEFT JOIN order_exchanges ex_to on oi.id = ex_to.to_order_item_id
 WHERE orders.status = 'Paid'
+  -- Back office orders only issue comps sent to their recipients, they are not sales
+  AND orders.order_type <> 'BackOffice'
   AND ($1 IS NULL OR oi.event_id = $1)
   AND ($2 IS NULL OR e.organization_id = $2)
-  AND ($3 IS NULL OR orders.paid_at >= $3)
-  AND ($4 IS NULL OR orders.paid_at <= $4)
+  AND ($3 IS NULL OR COALESCE(ex_to.created_at, orders.paid_at) >= $3)
+  AND ($4 IS NULL OR COALESCE(ex_to.created_at, orders.paid_at) <= $4)
   AND (oi.item_type = 'Tickets' OR oi.item_type = 'EventFees')
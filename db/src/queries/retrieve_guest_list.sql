@@ -20,7 +20,10 @@ SELECT ti.id,
        e.event_start AS event_start,
        v.id          AS venue_id,
        v.name        AS venue_name,
-       e.redeem_date AS redeem_date
+       e.redeem_date AS redeem_date,
+       ss.name       AS section_name,
+       s.row_name    AS row_name,
+       s.seat_number AS seat_number
 
 FROM ticket_instances ti
        INNER JOIN assets a ON ti.asset_id = a.id
@@ -31,6 +34,8 @@ FROM ticket_instances ti
        INNER JOIN users u ON coalesce(o.on_behalf_of_user_id, w.user_id) = u.id
        INNER JOIN events e ON t2.event_id = e.id
        INNER JOIN venues v ON e.venue_id = v.id
+       LEFT JOIN seats s ON ti.seat_id = s.id
+       LEFT JOIN seat_sections ss ON s.seat_section_id = ss.id
 WHERE t2.event_id = $1
   AND (u.first_name ILIKE '%'||$2||'%'
          OR u.last_name ILIKE '%'||$2||'%'
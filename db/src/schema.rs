@@ -33,6 +33,16 @@ table! {
     }
 }
 
+table! {
+    code_campaigns (id) {
+        id -> Uuid,
+        event_id -> Uuid,
+        name -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     codes (id) {
         id -> Uuid,
@@ -47,6 +57,24 @@ table! {
         max_tickets_per_user -> Nullable<Int8>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        discount_as_percentage -> Nullable<Int8>,
+        buy_quantity -> Nullable<Int8>,
+        get_quantity -> Nullable<Int8>,
+        minimum_order_total_in_cents -> Nullable<Int8>,
+        code_campaign_id -> Nullable<Uuid>,
+    }
+}
+
+table! {
+    credit_transactions (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        transaction_type -> Text,
+        amount_in_cents -> Int8,
+        order_id -> Nullable<Uuid>,
+        gift_card_id -> Nullable<Uuid>,
+        created_by -> Nullable<Uuid>,
+        created_at -> Timestamp,
     }
 }
 
@@ -101,6 +129,17 @@ table! {
     }
 }
 
+table! {
+    event_cancellations (id) {
+        id -> Uuid,
+        event_id -> Uuid,
+        ticket_type_id -> Nullable<Uuid>,
+        cancelled_by_user_id -> Uuid,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     event_interest (id) {
         id -> Uuid,
@@ -111,6 +150,43 @@ table! {
     }
 }
 
+table! {
+    event_reschedules (id) {
+        id -> Uuid,
+        event_id -> Uuid,
+        previous_event_start -> Nullable<Timestamp>,
+        new_event_start -> Nullable<Timestamp>,
+        refund_deadline -> Timestamp,
+        rescheduled_by_user_id -> Nullable<Uuid>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    event_series (id) {
+        id -> Uuid,
+        organization_id -> Uuid,
+        name -> Text,
+        frequency -> Text,
+        interval -> Int4,
+        occurrence_count -> Int4,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    event_signing_keys (id) {
+        id -> Uuid,
+        event_id -> Uuid,
+        secret_key -> Text,
+        public_key -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     events (id) {
         id -> Uuid,
@@ -141,6 +217,8 @@ table! {
         settlement_amount_in_cents -> Nullable<Int8>,
         event_end -> Nullable<Timestamp>,
         sendgrid_list_id -> Nullable<Int8>,
+        reschedule_refund_window_days -> Int4,
+        event_series_id -> Nullable<Uuid>,
     }
 }
 
@@ -179,6 +257,19 @@ table! {
     }
 }
 
+table! {
+    gift_cards (id) {
+        id -> Uuid,
+        order_item_id -> Uuid,
+        redemption_code -> Text,
+        value_in_cents -> Int8,
+        redeemed_by_user_id -> Nullable<Uuid>,
+        redeemed_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     holds (id) {
         id -> Uuid,
@@ -195,6 +286,41 @@ table! {
         phone -> Nullable<Text>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        discount_as_percentage -> Nullable<Int8>,
+        claim_key -> Nullable<Uuid>,
+        claim_sent_at -> Nullable<Timestamp>,
+        claimed_at -> Nullable<Timestamp>,
+        claimed_by_user_id -> Nullable<Uuid>,
+        created_by_user_id -> Nullable<Uuid>,
+    }
+}
+
+table! {
+    order_exchanges (id) {
+        id -> Uuid,
+        order_id -> Uuid,
+        from_order_item_id -> Uuid,
+        to_order_item_id -> Uuid,
+        quantity -> Int8,
+        difference_in_cents -> Int8,
+        created_by -> Uuid,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    order_idempotency_keys (id) {
+        id -> Uuid,
+        order_id -> Uuid,
+        user_id -> Uuid,
+        operation -> Text,
+        idempotency_key -> Text,
+        request_hash -> Text,
+        response_status -> Nullable<Int4>,
+        response_body -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
     }
 }
 
@@ -217,6 +343,23 @@ table! {
         company_fee_in_cents -> Int8,
         client_fee_in_cents -> Int8,
         refunded_quantity -> Int8,
+        tax_rule_id -> Nullable<Uuid>,
+        tax_in_cents -> Int8,
+    }
+}
+
+table! {
+    order_shares (id) {
+        id -> Uuid,
+        order_id -> Uuid,
+        share_key -> Uuid,
+        ticket_quantity -> Int8,
+        amount_in_cents -> Int8,
+        paid_by_user_id -> Nullable<Uuid>,
+        payment_id -> Nullable<Uuid>,
+        paid_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
     }
 }
 
@@ -284,6 +427,9 @@ table! {
         fee_schedule_id -> Uuid,
         client_event_fee_in_cents -> Int8,
         company_event_fee_in_cents -> Int8,
+        payment_provider -> Text,
+        merchant_account_id -> Nullable<Text>,
+        resale_price_cap_percent -> Nullable<Int4>,
     }
 }
 
@@ -297,6 +443,7 @@ table! {
         provider_data -> Json,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        merchant_account_id -> Nullable<Text>,
     }
 }
 
@@ -313,6 +460,7 @@ table! {
         raw_data -> Nullable<Json>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        merchant_account_id -> Nullable<Text>,
     }
 }
 
@@ -348,6 +496,58 @@ table! {
     }
 }
 
+table! {
+    resale_listings (id) {
+        id -> Uuid,
+        ticket_instance_id -> Uuid,
+        seller_id -> Uuid,
+        price_in_cents -> Int8,
+        status -> Text,
+        order_item_id -> Nullable<Uuid>,
+        seller_payout_in_cents -> Nullable<Int8>,
+        sold_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    resale_payouts (id) {
+        id -> Uuid,
+        resale_listing_id -> Uuid,
+        seller_id -> Uuid,
+        organization_id -> Uuid,
+        amount_in_cents -> Int8,
+        paid_at -> Nullable<Timestamp>,
+        paid_by_user_id -> Nullable<Uuid>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    seat_sections (id) {
+        id -> Uuid,
+        venue_id -> Uuid,
+        stage_id -> Nullable<Uuid>,
+        name -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    seats (id) {
+        id -> Uuid,
+        seat_section_id -> Uuid,
+        row_name -> Text,
+        seat_number -> Text,
+        is_accessible -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     stages (id) {
         id -> Uuid,
@@ -360,6 +560,21 @@ table! {
     }
 }
 
+table! {
+    tax_rules (id) {
+        id -> Uuid,
+        region_id -> Nullable<Uuid>,
+        venue_id -> Nullable<Uuid>,
+        name -> Text,
+        rate_in_basis_points -> Int4,
+        inclusive -> Bool,
+        applies_to_tickets -> Bool,
+        applies_to_fees -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     ticket_instances (id) {
         id -> Uuid,
@@ -375,6 +590,7 @@ table! {
         status -> Text,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        seat_id -> Nullable<Uuid>,
     }
 }
 
@@ -393,6 +609,22 @@ table! {
     }
 }
 
+table! {
+    ticket_scans (id) {
+        id -> Uuid,
+        event_id -> Uuid,
+        ticket_instance_id -> Nullable<Uuid>,
+        scanned_by_user_id -> Nullable<Uuid>,
+        device_id -> Nullable<Text>,
+        gate_name -> Nullable<Text>,
+        direction -> Text,
+        result -> Text,
+        scanned_at -> Timestamp,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     ticket_type_codes (id) {
         id -> Uuid,
@@ -418,6 +650,7 @@ table! {
         updated_at -> Timestamp,
         price_in_cents -> Int8,
         cancelled_at -> Nullable<Timestamp>,
+        allow_reentry -> Bool,
     }
 }
 
@@ -443,6 +676,7 @@ table! {
         last_cart_id -> Nullable<Uuid>,
         accepted_terms_date -> Nullable<Timestamp>,
         invited_at -> Nullable<Timestamp>,
+        marketing_opt_in -> Bool,
     }
 }
 
@@ -469,6 +703,31 @@ table! {
     }
 }
 
+table! {
+    waitlist_entries (id) {
+        id -> Uuid,
+        ticket_type_id -> Uuid,
+        user_id -> Uuid,
+        quantity -> Int8,
+        status -> Text,
+        hold_id -> Nullable<Uuid>,
+        offer_expires_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    wallet_pass_registrations (id) {
+        id -> Uuid,
+        ticket_instance_id -> Uuid,
+        device_library_identifier -> Text,
+        push_token -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     wallets (id) {
         id -> Uuid,
@@ -485,27 +744,51 @@ table! {
 
 joinable!(artists -> organizations (organization_id));
 joinable!(assets -> ticket_types (ticket_type_id));
+joinable!(code_campaigns -> events (event_id));
+joinable!(codes -> code_campaigns (code_campaign_id));
 joinable!(codes -> events (event_id));
+joinable!(credit_transactions -> gift_cards (gift_card_id));
+joinable!(credit_transactions -> orders (order_id));
+joinable!(credit_transactions -> users (user_id));
 joinable!(domain_actions -> domain_events (domain_event_id));
 joinable!(domain_events -> users (user_id));
 joinable!(event_artists -> artists (artist_id));
 joinable!(event_artists -> events (event_id));
+joinable!(event_cancellations -> events (event_id));
+joinable!(event_cancellations -> ticket_types (ticket_type_id));
+joinable!(event_cancellations -> users (cancelled_by_user_id));
 joinable!(event_artists -> stages (stage_id));
 joinable!(event_interest -> events (event_id));
 joinable!(event_interest -> users (user_id));
+joinable!(event_reschedules -> events (event_id));
+joinable!(event_reschedules -> users (rescheduled_by_user_id));
+joinable!(event_series -> organizations (organization_id));
+joinable!(event_signing_keys -> events (event_id));
+joinable!(events -> event_series (event_series_id));
 joinable!(events -> organizations (organization_id));
 joinable!(events -> venues (venue_id));
 joinable!(external_logins -> users (user_id));
 joinable!(fee_schedule_ranges -> fee_schedules (fee_schedule_id));
+joinable!(gift_cards -> order_items (order_item_id));
+joinable!(gift_cards -> users (redeemed_by_user_id));
 joinable!(holds -> events (event_id));
 joinable!(holds -> ticket_types (ticket_type_id));
+joinable!(holds -> users (claimed_by_user_id));
+joinable!(order_exchanges -> orders (order_id));
+joinable!(order_exchanges -> users (created_by));
+joinable!(order_idempotency_keys -> orders (order_id));
+joinable!(order_idempotency_keys -> users (user_id));
 joinable!(order_items -> codes (code_id));
 joinable!(order_items -> events (event_id));
 joinable!(order_items -> fee_schedule_ranges (fee_schedule_range_id));
 joinable!(order_items -> holds (hold_id));
 joinable!(order_items -> orders (order_id));
+joinable!(order_items -> tax_rules (tax_rule_id));
 joinable!(order_items -> ticket_pricing (ticket_pricing_id));
 joinable!(order_items -> ticket_types (ticket_type_id));
+joinable!(order_shares -> orders (order_id));
+joinable!(order_shares -> payments (payment_id));
+joinable!(order_shares -> users (paid_by_user_id));
 joinable!(organization_invites -> organizations (organization_id));
 joinable!(organization_users -> organizations (organization_id));
 joinable!(organization_users -> users (user_id));
@@ -516,33 +799,61 @@ joinable!(payments -> users (created_by));
 joinable!(push_notification_tokens -> users (user_id));
 joinable!(refunded_tickets -> order_items (order_item_id));
 joinable!(refunded_tickets -> ticket_instances (ticket_instance_id));
+joinable!(resale_listings -> order_items (order_item_id));
+joinable!(resale_listings -> ticket_instances (ticket_instance_id));
+joinable!(resale_listings -> users (seller_id));
+joinable!(resale_payouts -> organizations (organization_id));
+joinable!(resale_payouts -> resale_listings (resale_listing_id));
+joinable!(seat_sections -> stages (stage_id));
+joinable!(seat_sections -> venues (venue_id));
+joinable!(seats -> seat_sections (seat_section_id));
+joinable!(tax_rules -> regions (region_id));
+joinable!(tax_rules -> venues (venue_id));
 joinable!(ticket_instances -> assets (asset_id));
 joinable!(ticket_instances -> holds (hold_id));
 joinable!(ticket_instances -> order_items (order_item_id));
+joinable!(ticket_instances -> seats (seat_id));
 joinable!(ticket_instances -> wallets (wallet_id));
 joinable!(ticket_pricing -> ticket_types (ticket_type_id));
+joinable!(ticket_scans -> events (event_id));
+joinable!(ticket_scans -> ticket_instances (ticket_instance_id));
+joinable!(ticket_scans -> users (scanned_by_user_id));
 joinable!(ticket_type_codes -> codes (code_id));
 joinable!(ticket_type_codes -> ticket_types (ticket_type_id));
 joinable!(ticket_types -> events (event_id));
 joinable!(venues -> organizations (organization_id));
 joinable!(venues -> regions (region_id));
+joinable!(waitlist_entries -> holds (hold_id));
+joinable!(waitlist_entries -> ticket_types (ticket_type_id));
+joinable!(waitlist_entries -> users (user_id));
+joinable!(wallet_pass_registrations -> ticket_instances (ticket_instance_id));
 joinable!(wallets -> organizations (organization_id));
 joinable!(wallets -> users (user_id));
 
 allow_tables_to_appear_in_same_query!(
     artists,
     assets,
+    code_campaigns,
     codes,
+    credit_transactions,
     domain_actions,
     domain_events,
     event_artists,
+    event_cancellations,
     event_interest,
+    event_reschedules,
+    event_series,
+    event_signing_keys,
     events,
     external_logins,
     fee_schedule_ranges,
     fee_schedules,
+    gift_cards,
     holds,
+    order_exchanges,
+    order_idempotency_keys,
     order_items,
+    order_shares,
     orders,
     organization_invites,
     organization_users,
@@ -552,12 +863,20 @@ allow_tables_to_appear_in_same_query!(
     push_notification_tokens,
     refunded_tickets,
     regions,
+    resale_listings,
+    resale_payouts,
+    seat_sections,
+    seats,
     stages,
+    tax_rules,
     ticket_instances,
     ticket_pricing,
+    ticket_scans,
     ticket_type_codes,
     ticket_types,
     users,
     venues,
+    waitlist_entries,
+    wallet_pass_registrations,
     wallets,
 );
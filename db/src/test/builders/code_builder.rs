@@ -17,6 +17,10 @@ pub struct CodeBuilder<'a> {
     ticket_type_ids: Vec<Uuid>,
     code_type: CodeTypes,
     discount_in_cents: Option<u32>,
+    discount_as_percentage: Option<u32>,
+    buy_quantity: Option<u32>,
+    get_quantity: Option<u32>,
+    minimum_order_total_in_cents: Option<u32>,
     max_uses: u32,
     max_tickets_per_user: Option<u32>,
 }
@@ -38,6 +42,10 @@ impl<'a> CodeBuilder<'a> {
             event_id: None,
             code_type: CodeTypes::Discount,
             discount_in_cents: Some(100),
+            discount_as_percentage: None,
+            buy_quantity: None,
+            get_quantity: None,
+            minimum_order_total_in_cents: None,
             max_tickets_per_user: None,
             max_uses: 10,
         }
@@ -63,6 +71,23 @@ impl<'a> CodeBuilder<'a> {
         self
     }
 
+    pub fn with_discount_as_percentage(mut self, discount_as_percentage: Option<u32>) -> Self {
+        self.discount_in_cents = None;
+        self.discount_as_percentage = discount_as_percentage;
+        self
+    }
+
+    pub fn with_buy_and_get_quantity(mut self, buy_quantity: u32, get_quantity: u32) -> Self {
+        self.buy_quantity = Some(buy_quantity);
+        self.get_quantity = Some(get_quantity);
+        self
+    }
+
+    pub fn with_minimum_order_total_in_cents(mut self, minimum_order_total_in_cents: u32) -> Self {
+        self.minimum_order_total_in_cents = Some(minimum_order_total_in_cents);
+        self
+    }
+
     pub fn with_redemption_code(mut self, redemption_code: String) -> Self {
         self.redemption_code = redemption_code;
         self
@@ -104,6 +129,10 @@ impl<'a> CodeBuilder<'a> {
             self.redemption_code,
             self.max_uses,
             self.discount_in_cents,
+            self.discount_as_percentage,
+            self.buy_quantity,
+            self.get_quantity,
+            self.minimum_order_total_in_cents,
             start_date,
             end_date,
             self.max_tickets_per_user,
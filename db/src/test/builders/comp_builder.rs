@@ -57,6 +57,7 @@ impl<'a> CompBuilder<'a> {
             None,
             None,
             self.quantity,
+            None,
             self.connection,
         )
         .unwrap()
@@ -130,7 +130,7 @@ impl<'a> EventBuilder<'a> {
             attributes.is_external = Some(true);
         }
 
-        let event = event.update(attributes, self.connection).unwrap();
+        let event = event.update(None, attributes, self.connection).unwrap();
 
         if self.with_tickets {
             let early_bird_start = NaiveDateTime::from(Utc::now().naive_utc() - Duration::days(2));
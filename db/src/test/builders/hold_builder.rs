@@ -10,6 +10,7 @@ pub struct HoldBuilder<'a> {
     event_id: Option<Uuid>,
     ticket_type_id: Option<Uuid>,
     hold_type: HoldTypes,
+    discount_as_percentage: Option<u32>,
     connection: &'a PgConnection,
 }
 
@@ -21,6 +22,7 @@ impl<'a> HoldBuilder<'a> {
             redemption_code: format!("REDEEM{}", x).into(),
             connection,
             hold_type: HoldTypes::Discount,
+            discount_as_percentage: None,
             event_id: None,
             ticket_type_id: None,
         }
@@ -31,6 +33,11 @@ impl<'a> HoldBuilder<'a> {
         self
     }
 
+    pub fn with_discount_as_percentage(mut self, discount_as_percentage: u32) -> Self {
+        self.discount_as_percentage = Some(discount_as_percentage);
+        self
+    }
+
     pub fn with_name(mut self, name: String) -> Self {
         self.name = name;
         self
@@ -79,15 +86,17 @@ impl<'a> HoldBuilder<'a> {
             self.name,
             self.event_id.unwrap(),
             self.redemption_code,
-            if self.hold_type == HoldTypes::Discount {
+            if self.hold_type == HoldTypes::Discount && self.discount_as_percentage.is_none() {
                 Some(10)
             } else {
                 None
             },
+            self.discount_as_percentage,
             None,
             None,
             self.hold_type,
             ticket_type_id,
+            None,
         )
         .commit(self.connection)
         .unwrap();
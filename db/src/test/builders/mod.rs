@@ -9,6 +9,7 @@ pub use self::organization_builder::*;
 pub use self::organization_invite_builder::*;
 pub use self::payment_method_builder::*;
 pub use self::region_builder::*;
+pub use self::seat_section_builder::*;
 pub use self::stage_builder::*;
 pub use self::user_builder::*;
 pub use self::venue_builder::*;
@@ -24,6 +25,7 @@ mod organization_builder;
 mod organization_invite_builder;
 mod payment_method_builder;
 mod region_builder;
+mod seat_section_builder;
 mod stage_builder;
 mod user_builder;
 mod venue_builder;
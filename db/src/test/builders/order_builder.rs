@@ -86,6 +86,7 @@ impl<'a> OrderBuilder<'a> {
                 ticket_type_id: self.ticket_type_id.unwrap(),
                 quantity: self.quantity,
                 redemption_code: redemption_code,
+                seat_ids: None,
             }],
             false,
             false,
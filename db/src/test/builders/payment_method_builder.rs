@@ -50,6 +50,7 @@ impl<'a> PaymentMethodBuilder<'a> {
             self.name,
             self.is_default,
             "cus_example".into(),
+            None,
             "abc".into(),
         )
         .commit(user_id, self.connection)
@@ -0,0 +1,60 @@
+use diesel::prelude::*;
+use models::*;
+use uuid::Uuid;
+
+pub struct SeatSectionBuilder<'a> {
+    name: String,
+    venue_id: Uuid,
+    stage_id: Option<Uuid>,
+    seat_count: u32,
+    connection: &'a PgConnection,
+}
+
+impl<'a> SeatSectionBuilder<'a> {
+    pub fn new(connection: &PgConnection) -> SeatSectionBuilder {
+        let x: i32 = rand::random::<i32>();
+
+        SeatSectionBuilder {
+            connection,
+            name: format!("Section {}", x).into(),
+            venue_id: Uuid::nil(),
+            stage_id: None,
+            seat_count: 0,
+        }
+    }
+
+    pub fn with_name(mut self, name: String) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn with_venue_id(mut self, venue_id: Uuid) -> Self {
+        self.venue_id = venue_id;
+        self
+    }
+
+    pub fn with_stage_id(mut self, stage_id: Uuid) -> Self {
+        self.stage_id = Some(stage_id);
+        self
+    }
+
+    pub fn with_seats(mut self, seat_count: u32) -> Self {
+        self.seat_count = seat_count;
+        self
+    }
+
+    pub fn finish(self) -> SeatSection {
+        let seat_section = SeatSection::create(self.venue_id, self.stage_id, self.name)
+            .commit(self.connection)
+            .unwrap();
+
+        if self.seat_count > 0 {
+            let seats = (1..=self.seat_count)
+                .map(|x| Seat::create(seat_section.id, "A".to_string(), x.to_string(), false))
+                .collect();
+            seat_section.add_seats(seats, self.connection).unwrap();
+        }
+
+        seat_section
+    }
+}
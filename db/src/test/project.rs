@@ -113,6 +113,10 @@ impl TestProject {
         StageBuilder::new(&self.connection)
     }
 
+    pub fn create_seat_section(&self) -> SeatSectionBuilder {
+        SeatSectionBuilder::new(&self.connection)
+    }
+
     pub fn create_fee_schedule(&self) -> FeeScheduleBuilder {
         FeeScheduleBuilder::new(&self.connection)
     }
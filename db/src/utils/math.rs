@@ -1,4 +1,4 @@
-use std::cmp::Ord;
+use std::cmp::{self, Ord};
 
 pub fn clamp<T: Ord>(i: T, min: T, max: T) -> T {
     if i < min {
@@ -9,3 +9,39 @@ pub fn clamp<T: Ord>(i: T, min: T, max: T) -> T {
     }
     i
 }
+
+/// Amount taken off a price by either a fixed or a percentage discount. The discount never
+/// exceeds the price.
+pub fn discount_for_price(
+    price_in_cents: i64,
+    discount_in_cents: Option<i64>,
+    discount_as_percentage: Option<i64>,
+) -> i64 {
+    let discount = match discount_as_percentage {
+        Some(percentage) => price_in_cents * percentage / 100,
+        None => discount_in_cents.unwrap_or(0),
+    };
+    clamp(discount, 0, cmp::max(price_in_cents, 0))
+}
+
+/// Tax on a price at a rate in basis points, rounded half away from zero to the nearest cent.
+/// For inclusive taxes the price already contains the tax so the tax is the portion of it that
+/// is tax.
+pub fn tax_for_price(price_in_cents: i64, rate_in_basis_points: i64, inclusive: bool) -> i64 {
+    let denominator = if inclusive {
+        10000 + rate_in_basis_points
+    } else {
+        10000
+    };
+    divide_rounded(price_in_cents * rate_in_basis_points, denominator)
+}
+
+fn divide_rounded(numerator: i64, denominator: i64) -> i64 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    if remainder.abs() * 2 >= denominator {
+        quotient + numerator.signum()
+    } else {
+        quotient
+    }
+}
@@ -0,0 +1,30 @@
+use std::borrow::Cow;
+use validator::ValidationError;
+use validators::*;
+
+pub fn discount_valid(
+    discount_in_cents: Option<i64>,
+    discount_as_percentage: Option<i64>,
+) -> Result<(), ValidationError> {
+    if discount_in_cents.is_some() && discount_as_percentage.is_some() {
+        let mut validation_error = create_validation_error(
+            "only_one_discount_allowed",
+            "Discount can either be in cents or a percentage but not both",
+        );
+        validation_error.add_param(Cow::from("discount_in_cents"), &discount_in_cents);
+        validation_error.add_param(Cow::from("discount_as_percentage"), &discount_as_percentage);
+        return Err(validation_error);
+    }
+    if let Some(discount_as_percentage) = discount_as_percentage {
+        if discount_as_percentage < 1 || discount_as_percentage > 100 {
+            let mut validation_error = create_validation_error(
+                "discount_as_percentage_out_of_range",
+                "Discount percentage must be between 1 and 100",
+            );
+            validation_error
+                .add_param(Cow::from("discount_as_percentage"), &discount_as_percentage);
+            return Err(validation_error);
+        }
+    }
+    Ok(())
+}
@@ -1,12 +1,16 @@
+mod discount_validator;
 mod n_date_before_m_date_validator;
 mod number_validators;
 mod redemption_code_uniqueness_validator;
 mod start_date_before_end_date_validator;
 mod url_array_validator;
 
+pub use self::discount_validator::discount_valid;
 pub use self::n_date_before_m_date_validator::n_date_valid;
 pub use self::number_validators::validate_greater_than;
-pub use self::redemption_code_uniqueness_validator::redemption_code_unique_per_event_validation;
+pub use self::redemption_code_uniqueness_validator::{
+    redemption_code_unique_per_event_validation, unique_redemption_code,
+};
 pub use self::start_date_before_end_date_validator::start_date_valid;
 pub use self::url_array_validator::validate_urls;
 
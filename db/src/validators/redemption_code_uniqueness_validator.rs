@@ -3,12 +3,15 @@ use diesel::prelude::*;
 use diesel::sql_types::{Text, Uuid as dUuid};
 use std::borrow::Cow;
 use utils::errors::*;
+use utils::rand::random_alpha_string;
 use uuid::Uuid;
 use validator::*;
 use validators::*;
 
 sql_function!(fn redemption_code_unique_per_event(id: dUuid, table: Text, redemption_code: Text) -> Bool);
 
+const MAX_REDEMPTION_CODE_ATTEMPTS: u32 = 10;
+
 pub fn redemption_code_unique_per_event_validation(
     id: Option<Uuid>,
     table: String,
@@ -39,3 +42,22 @@ pub fn redemption_code_unique_per_event_validation(
     }
     Ok(Ok(()))
 }
+
+/// Generates a random redemption code that is not yet used by any code or hold
+pub fn unique_redemption_code(length: usize, conn: &PgConnection) -> Result<String, DatabaseError> {
+    for _ in 0..MAX_REDEMPTION_CODE_ATTEMPTS {
+        let redemption_code = random_alpha_string(length).to_uppercase();
+        if redemption_code_unique_per_event_validation(
+            None,
+            "codes".into(),
+            redemption_code.clone(),
+            conn,
+        )?
+        .is_ok()
+        {
+            return Ok(redemption_code);
+        }
+    }
+
+    DatabaseError::business_process_error("Could not generate a unique redemption code")
+}
@@ -0,0 +1,161 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::models::*;
+use bigneon_db::utils::errors::ErrorCode::ValidationError;
+use chrono::prelude::*;
+use time::Duration;
+use uuid::Uuid;
+
+fn template(event: &Event) -> NewCode {
+    Code::create(
+        "Campaign".to_string(),
+        event.id,
+        CodeTypes::Discount,
+        String::new(),
+        1,
+        Some(20),
+        None,
+        None,
+        None,
+        None,
+        NaiveDateTime::from(Utc::now().naive_utc() - Duration::days(1)),
+        NaiveDateTime::from(Utc::now().naive_utc() + Duration::days(1)),
+        None,
+    )
+}
+
+#[test]
+fn generate_codes() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().with_tickets().finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let campaign = CodeCampaign::create(event.id, "Partner".to_string())
+        .commit(connection)
+        .unwrap();
+
+    let codes = campaign
+        .generate_codes(5, template(&event), &[ticket_type.id], connection)
+        .unwrap();
+    assert_eq!(codes.len(), 5);
+    for code in &codes {
+        assert_eq!(code.code_campaign_id, Some(campaign.id));
+        assert_eq!(code.name, "Partner".to_string());
+        assert_eq!(code.max_uses, 1);
+        assert_eq!(code.discount_in_cents, Some(20));
+        let ticket_type_ids: Vec<Uuid> = TicketType::find_for_code(code.id, connection)
+            .unwrap()
+            .iter()
+            .map(|tt| tt.id)
+            .collect();
+        assert_eq!(ticket_type_ids, vec![ticket_type.id]);
+    }
+
+    let mut redemption_codes: Vec<String> =
+        codes.iter().map(|c| c.redemption_code.clone()).collect();
+    redemption_codes.sort();
+    redemption_codes.dedup();
+    assert_eq!(redemption_codes.len(), 5);
+    assert_eq!(campaign.codes(connection).unwrap().len(), 5);
+}
+
+#[test]
+fn generate_codes_with_invalid_quantity() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().with_tickets().finish();
+    let campaign = CodeCampaign::create(event.id, "Partner".to_string())
+        .commit(connection)
+        .unwrap();
+
+    for quantity in vec![0, MAX_CAMPAIGN_CODES + 1] {
+        let result = campaign.generate_codes(quantity, template(&event), &[], connection);
+        match result {
+            Ok(_) => panic!("Expected validation error"),
+            Err(error) => match &error.error_code {
+                ValidationError { errors } => {
+                    assert!(errors.contains_key("quantity"));
+                }
+                _ => panic!("Expected validation error"),
+            },
+        }
+    }
+    assert!(campaign.codes(connection).unwrap().is_empty());
+}
+
+#[test]
+fn find_for_event() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().finish();
+    let campaign = CodeCampaign::create(event.id, "Partner".to_string())
+        .commit(connection)
+        .unwrap();
+    let _other_campaign =
+        CodeCampaign::create(project.create_event().finish().id, "Other".to_string())
+            .commit(connection)
+            .unwrap();
+
+    assert_eq!(
+        CodeCampaign::find_for_event(event.id, connection).unwrap(),
+        vec![campaign.clone()]
+    );
+    assert_eq!(
+        CodeCampaign::find(campaign.id, connection).unwrap(),
+        campaign
+    );
+}
+
+#[test]
+fn code_usage() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let campaign = CodeCampaign::create(event.id, "Partner".to_string())
+        .commit(connection)
+        .unwrap();
+    let codes = campaign
+        .generate_codes(2, template(&event), &[ticket_type.id], connection)
+        .unwrap();
+    let used_code = &codes[0];
+
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: Some(used_code.redemption_code.clone()),
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+
+    // Unpaid orders are not counted
+    let usage = campaign.code_usage(connection).unwrap();
+    assert!(usage.iter().all(|u| u.uses == 0 && u.tickets_sold == 0));
+
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(Some("Test".to_string()), user.id, total, connection)
+        .unwrap();
+
+    let usage = campaign.code_usage(connection).unwrap();
+    assert_eq!(usage.len(), 2);
+    let used = usage.iter().find(|u| u.code_id == used_code.id).unwrap();
+    assert_eq!(used.redemption_code, used_code.redemption_code);
+    assert_eq!(used.max_uses, 1);
+    assert_eq!(used.uses, 1);
+    assert_eq!(used.tickets_sold, 2);
+    assert!(used.last_used_at.is_some());
+    let unused = usage.iter().find(|u| u.code_id == codes[1].id).unwrap();
+    assert_eq!(unused.uses, 0);
+    assert_eq!(unused.tickets_sold, 0);
+    assert_eq!(unused.last_used_at, None);
+}
@@ -19,6 +19,10 @@ fn create() {
         "REDEMPTION".into(),
         10,
         Some(100),
+        None,
+        None,
+        None,
+        None,
         start_date,
         end_date,
         None,
@@ -40,6 +44,10 @@ pub fn create_with_validation_errors() {
         "A".into(),
         10,
         None,
+        None,
+        None,
+        None,
+        None,
         start_date,
         end_date,
         None,
@@ -105,6 +113,10 @@ pub fn create_with_validation_errors() {
         code.redemption_code,
         10,
         Some(100),
+        None,
+        None,
+        None,
+        None,
         start_date,
         end_date,
         None,
@@ -143,6 +155,10 @@ pub fn create_with_validation_errors() {
         hold.redemption_code,
         10,
         Some(100),
+        None,
+        None,
+        None,
+        None,
         start_date,
         end_date,
         None,
@@ -180,6 +196,10 @@ pub fn create_with_validation_errors() {
         "NEWUNUSEDCODE".into(),
         10,
         None,
+        None,
+        None,
+        None,
+        None,
         start_date,
         end_date,
         None,
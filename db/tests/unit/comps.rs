@@ -15,6 +15,7 @@ fn create() {
         None,
         None,
         5,
+        None,
         db.get_connection(),
     )
     .unwrap();
@@ -36,6 +37,7 @@ pub fn create_with_validation_errors() {
         None,
         None,
         11,
+        None,
         db.get_connection(),
     );
 
@@ -0,0 +1,124 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+
+#[test]
+fn commit() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+
+    let credit_transaction = CreditTransaction::create(
+        user.id,
+        CreditTransactionTypes::Refund,
+        1000,
+        None,
+        None,
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+    assert_eq!(credit_transaction.user_id, user.id);
+    assert_eq!(credit_transaction.amount_in_cents, 1000);
+
+    // Zero amounts are not recorded
+    assert!(CreditTransaction::create(
+        user.id,
+        CreditTransactionTypes::Refund,
+        0,
+        None,
+        None,
+        None,
+    )
+    .commit(connection)
+    .is_err());
+
+    // Balance cannot go below zero
+    assert!(CreditTransaction::create(
+        user.id,
+        CreditTransactionTypes::Payment,
+        -1500,
+        None,
+        None,
+        None,
+    )
+    .commit(connection)
+    .is_err());
+
+    CreditTransaction::create(
+        user.id,
+        CreditTransactionTypes::Payment,
+        -1000,
+        None,
+        None,
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+    assert_eq!(
+        CreditTransaction::balance_for_user(user.id, connection).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn balance_for_user() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let user2 = project.create_user().finish();
+    assert_eq!(
+        CreditTransaction::balance_for_user(user.id, connection).unwrap(),
+        0
+    );
+
+    for amount in vec![1000, 2500, -500] {
+        CreditTransaction::create(
+            user.id,
+            if amount > 0 {
+                CreditTransactionTypes::Refund
+            } else {
+                CreditTransactionTypes::Payment
+            },
+            amount,
+            None,
+            None,
+            None,
+        )
+        .commit(connection)
+        .unwrap();
+    }
+    assert_eq!(
+        CreditTransaction::balance_for_user(user.id, connection).unwrap(),
+        3000
+    );
+    assert_eq!(
+        CreditTransaction::balance_for_user(user2.id, connection).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn find_for_user() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let user2 = project.create_user().finish();
+    let credit_transaction = CreditTransaction::create(
+        user.id,
+        CreditTransactionTypes::Refund,
+        1000,
+        None,
+        None,
+        Some(user2.id),
+    )
+    .commit(connection)
+    .unwrap();
+
+    assert_eq!(
+        CreditTransaction::find_for_user(user.id, connection).unwrap(),
+        vec![credit_transaction]
+    );
+    assert!(CreditTransaction::find_for_user(user2.id, connection)
+        .unwrap()
+        .is_empty());
+}
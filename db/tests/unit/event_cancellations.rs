@@ -0,0 +1,245 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::models::*;
+use chrono::prelude::*;
+use time::Duration;
+
+#[test]
+fn cancel_event() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_a_specific_number_of_tickets(10)
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let order = project
+        .create_order()
+        .for_event(&event)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    // Unpaid carts are not refunded
+    let buyer = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&buyer, connection).unwrap();
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 1,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+
+    let event = event.cancel(user.id, connection).unwrap();
+    assert!(event.cancelled_at.is_some());
+    assert!(event.clone().cancel(user.id, connection).is_err());
+
+    let cancellations = EventCancellation::find_for_event(event.id, connection).unwrap();
+    assert_eq!(cancellations.len(), 1);
+    let cancellation = &cancellations[0];
+    assert_eq!(cancellation.ticket_type_id, None);
+    assert_eq!(cancellation.cancelled_by_user_id, user.id);
+    assert_eq!(
+        cancellation.progress(connection).unwrap(),
+        EventCancellationProgress {
+            order_count: 1,
+            pending_count: 1,
+            refunded_count: 0,
+            failed_count: 0,
+            unrefunded_ticket_count: 2,
+        }
+    );
+
+    let refund_items = cancellation.refund_items(&order, connection).unwrap();
+    assert_eq!(refund_items.len(), 2);
+    assert!(refund_items.iter().all(|i| i.ticket_instance_id.is_some()));
+
+    // Unsold inventory is withdrawn
+    assert_eq!(ticket_type.valid_ticket_count(connection).unwrap(), 3);
+
+    assert_eq!(
+        order.refund(refund_items, connection).unwrap(),
+        order.calculate_total(connection).unwrap() as u32
+    );
+    assert!(cancellation
+        .refund_items(&order, connection)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        cancellation
+            .progress(connection)
+            .unwrap()
+            .unrefunded_ticket_count,
+        0
+    );
+
+    // Refunded tickets are released back to inventory until withdrawn
+    assert_eq!(ticket_type.valid_ticket_count(connection).unwrap(), 3);
+    cancellation.withdraw_inventory(connection).unwrap();
+    assert_eq!(ticket_type.valid_ticket_count(connection).unwrap(), 1);
+}
+
+#[test]
+fn cancel_ticket_type() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_ticket_type_count(2)
+        .with_ticket_pricing()
+        .finish();
+    let ticket_types = event.ticket_types(connection).unwrap();
+    let order = project
+        .create_order()
+        .for_event(&event)
+        .quantity(2)
+        .is_paid()
+        .finish();
+
+    // Order only contains tickets for the first ticket type
+    ticket_types[1].cancel(user.id, connection).unwrap();
+    let cancellation = &EventCancellation::find_for_event(event.id, connection).unwrap()[0];
+    assert_eq!(cancellation.ticket_type_id, Some(ticket_types[1].id));
+    assert_eq!(cancellation.progress(connection).unwrap().order_count, 0);
+    assert!(cancellation
+        .refund_items(&order, connection)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        ticket_types[1].remaining_ticket_count(connection).unwrap(),
+        0
+    );
+    assert!(ticket_types[0].remaining_ticket_count(connection).unwrap() > 0);
+
+    let ticket_type = TicketType::find(ticket_types[1].id, connection).unwrap();
+    assert!(ticket_type.cancel(user.id, connection).is_err());
+
+    ticket_types[0].cancel(user.id, connection).unwrap();
+    let cancellation = &EventCancellation::find_for_event(event.id, connection).unwrap()[0];
+    assert_eq!(cancellation.ticket_type_id, Some(ticket_types[0].id));
+    assert_eq!(cancellation.progress(connection).unwrap().order_count, 1);
+    assert_eq!(
+        cancellation.refund_items(&order, connection).unwrap().len(),
+        2
+    );
+}
+
+#[test]
+fn refund_items_include_transferred_and_resold_tickets() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let buyer = project.create_user().finish();
+    let receiver = project.create_user().finish();
+    let resale_buyer = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(user.id))
+        .finish()
+        .set_resale_price_cap(Some(100), connection)
+        .unwrap();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_event_start(Utc::now().naive_utc() + Duration::days(7))
+        .with_a_specific_number_of_tickets(10)
+        .with_ticket_pricing()
+        .finish();
+    let order = project
+        .create_order()
+        .for_event(&event)
+        .for_user(&buyer)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let mut tickets = TicketInstance::find_for_user(buyer.id, connection).unwrap();
+    let transferred_ticket = tickets.remove(0);
+    let resold_ticket = tickets.remove(0);
+
+    let sender_wallet = Wallet::find_default_for_user(buyer.id, connection).unwrap();
+    let receiver_wallet = Wallet::find_default_for_user(receiver.id, connection).unwrap();
+    let transfer_auth = TicketInstance::authorize_ticket_transfer(
+        buyer.id,
+        vec![transferred_ticket.id],
+        3600,
+        connection,
+    )
+    .unwrap();
+    TicketInstance::receive_ticket_transfer(
+        transfer_auth,
+        &sender_wallet,
+        &receiver_wallet.id,
+        connection,
+    )
+    .unwrap();
+
+    let order_item = OrderItem::find(resold_ticket.order_item_id.unwrap(), connection).unwrap();
+    let resale_listing =
+        ResaleListing::create(resold_ticket.id, buyer.id, order_item.unit_price_in_cents)
+            .commit(connection)
+            .unwrap();
+    let mut resale_order = Order::find_or_create_cart(&resale_buyer, connection).unwrap();
+    resale_order
+        .update_resale_listings(&[resale_listing.id], connection)
+        .unwrap();
+    let total = resale_order.calculate_total(connection).unwrap();
+    resale_order
+        .add_external_payment(Some("test".to_string()), resale_buyer.id, total, connection)
+        .unwrap();
+
+    let event = event.cancel(user.id, connection).unwrap();
+    let cancellation = &EventCancellation::find_for_event(event.id, connection).unwrap()[0];
+    let progress = cancellation.progress(connection).unwrap();
+    assert_eq!(progress.order_count, 2);
+    assert_eq!(progress.unrefunded_ticket_count, 2);
+
+    // The buyer is refunded for the ticket they transferred
+    let refund_items = cancellation.refund_items(&order, connection).unwrap();
+    assert_eq!(refund_items.len(), 1);
+    assert_eq!(
+        refund_items[0].ticket_instance_id,
+        Some(transferred_ticket.id)
+    );
+    assert!(
+        order
+            .refund_for_cancellation(refund_items, connection)
+            .unwrap()
+            > 0
+    );
+
+    // The resold ticket is refunded to the resale buyer
+    let refund_items = cancellation
+        .refund_items(&resale_order, connection)
+        .unwrap();
+    assert_eq!(refund_items.len(), 1);
+    assert_eq!(refund_items[0].ticket_instance_id, Some(resold_ticket.id));
+    assert_eq!(
+        resale_order
+            .refund_for_cancellation(refund_items, connection)
+            .unwrap() as i64,
+        total
+    );
+
+    assert!(cancellation
+        .refund_items(&order, connection)
+        .unwrap()
+        .is_empty());
+    assert!(cancellation
+        .refund_items(&resale_order, connection)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        cancellation
+            .progress(connection)
+            .unwrap()
+            .unrefunded_ticket_count,
+        0
+    );
+}
@@ -0,0 +1,228 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::models::*;
+use bigneon_db::utils::errors::ErrorCode::ValidationError;
+use chrono::prelude::*;
+use uuid::Uuid;
+
+#[test]
+fn reschedule_published_event() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let order = project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let previous_event_start = event.event_start;
+    let new_event_start = NaiveDate::from_ymd(2016, 8, 8).and_hms(9, 10, 11);
+
+    // Other changes are not a reschedule
+    let event = event
+        .update(
+            Some(user.id),
+            EventEditableAttributes {
+                name: Some("New name".to_string()),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    assert!(EventReschedule::find_for_event(event.id, connection)
+        .unwrap()
+        .is_empty());
+
+    let event = event
+        .update(
+            Some(user.id),
+            EventEditableAttributes {
+                event_start: Some(new_event_start),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    assert_eq!(
+        event.override_status,
+        Some(EventOverrideStatus::Rescheduled)
+    );
+
+    let reschedules = EventReschedule::find_for_event(event.id, connection).unwrap();
+    assert_eq!(reschedules.len(), 1);
+    let reschedule = &reschedules[0];
+    assert_eq!(reschedule.previous_event_start, previous_event_start);
+    assert_eq!(reschedule.new_event_start, Some(new_event_start));
+    assert_eq!(reschedule.rescheduled_by_user_id, Some(user.id));
+    assert!(reschedule.refund_window_open());
+    assert_eq!(
+        EventReschedule::find_open_for_event(event.id, connection).unwrap(),
+        Some(reschedule.clone())
+    );
+
+    let ticket_holders = reschedule.ticket_holders(connection).unwrap();
+    assert_eq!(ticket_holders.len(), 1);
+    assert_eq!(ticket_holders[0].0.id, user.id);
+    assert!(ticket_holders[0].1);
+    assert_eq!(
+        order
+            .refundable_ticket_items(event.id, None, connection)
+            .unwrap()
+            .len(),
+        2
+    );
+
+    // Transfer recipients hold tickets but have no order to refund them from
+    let receiver = project.create_user().finish();
+    let ticket = &TicketInstance::find_for_user(user.id, connection).unwrap()[0];
+    let sender_wallet = Wallet::find_default_for_user(user.id, connection).unwrap();
+    let receiver_wallet = Wallet::find_default_for_user(receiver.id, connection).unwrap();
+    let transfer_auth =
+        TicketInstance::authorize_ticket_transfer(user.id, vec![ticket.id], 3600, connection)
+            .unwrap();
+    TicketInstance::receive_ticket_transfer(
+        transfer_auth,
+        &sender_wallet,
+        &receiver_wallet.id,
+        connection,
+    )
+    .unwrap();
+    let mut ticket_holders: Vec<(Uuid, bool)> = reschedule
+        .ticket_holders(connection)
+        .unwrap()
+        .into_iter()
+        .map(|(u, has_order)| (u.id, has_order))
+        .collect();
+    ticket_holders.sort();
+    let mut expected_ticket_holders = vec![(user.id, true), (receiver.id, false)];
+    expected_ticket_holders.sort();
+    assert_eq!(ticket_holders, expected_ticket_holders);
+}
+
+#[test]
+fn reschedule_with_closed_refund_window() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().finish();
+    let event = event
+        .update(
+            None,
+            EventEditableAttributes {
+                reschedule_refund_window_days: Some(0),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    let event = event
+        .update(
+            None,
+            EventEditableAttributes {
+                event_start: Some(NaiveDate::from_ymd(2016, 8, 8).and_hms(9, 10, 11)),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+
+    let reschedule = &EventReschedule::find_for_event(event.id, connection).unwrap()[0];
+    assert!(!reschedule.refund_window_open());
+    assert!(EventReschedule::find_open_for_event(event.id, connection)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn reschedule_draft_event() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_status(EventStatus::Draft)
+        .finish();
+    event
+        .update(
+            None,
+            EventEditableAttributes {
+                event_start: Some(NaiveDate::from_ymd(2016, 8, 8).and_hms(9, 10, 11)),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+
+    assert!(EventReschedule::find_for_event(event.id, connection)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn reschedule_refund_window_days_validation() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .finish();
+
+    for days in vec![-1, MAX_RESCHEDULE_REFUND_WINDOW_DAYS + 1] {
+        let result = event.update(
+            None,
+            EventEditableAttributes {
+                reschedule_refund_window_days: Some(days),
+                ..Default::default()
+            },
+            connection,
+        );
+        match result {
+            Ok(_) => panic!("Expected validation error"),
+            Err(error) => match &error.error_code {
+                ValidationError { errors } => {
+                    assert!(errors.contains_key("reschedule_refund_window_days"));
+                }
+                _ => panic!("Expected validation error"),
+            },
+        }
+
+        let mut new_event = Event::create(
+            "Event",
+            EventStatus::Draft,
+            organization.id,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        new_event.reschedule_refund_window_days = Some(days);
+        match new_event.commit(connection) {
+            Ok(_) => panic!("Expected validation error"),
+            Err(error) => match &error.error_code {
+                ValidationError { errors } => {
+                    assert!(errors.contains_key("reschedule_refund_window_days"));
+                }
+                _ => panic!("Expected validation error"),
+            },
+        }
+    }
+
+    // Rescheduled events do not have to offer refunds
+    let event = event
+        .update(
+            None,
+            EventEditableAttributes {
+                reschedule_refund_window_days: Some(0),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    assert_eq!(event.reschedule_refund_window_days, 0);
+}
@@ -0,0 +1,298 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::models::*;
+use bigneon_db::utils::errors::ErrorCode::ValidationError;
+use chrono::prelude::*;
+use time::Duration;
+
+#[test]
+fn create_from_event() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event_start = Utc::now().naive_utc() + Duration::days(7);
+    let event = project
+        .create_event()
+        .with_event_start(event_start)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let artist = project.create_artist().finish();
+    event.add_artist(artist.id, connection).unwrap();
+    let hold = project.create_hold().with_event(&event).finish();
+
+    let series =
+        EventSeries::create_from_event(&event, RecurrenceFrequency::Weekly, 2, 3, connection)
+            .unwrap();
+    assert_eq!(series.organization_id, event.organization_id);
+    assert_eq!(series.name, event.name);
+
+    let occurrences = series.occurrences(connection).unwrap();
+    assert_eq!(occurrences.len(), 3);
+    assert_eq!(occurrences[0].id, event.id);
+    assert_eq!(
+        occurrences[1].event_start,
+        Some(event_start + Duration::weeks(2))
+    );
+    assert_eq!(
+        occurrences[2].event_start,
+        Some(event_start + Duration::weeks(4))
+    );
+    assert!(occurrences
+        .iter()
+        .all(|o| o.event_series_id == Some(series.id)));
+
+    let ticket_types = event.ticket_types(connection).unwrap();
+    let occurrence = &occurrences[1];
+    let occurrence_ticket_types = occurrence.ticket_types(connection).unwrap();
+    assert_eq!(occurrence_ticket_types.len(), ticket_types.len());
+    assert_eq!(occurrence_ticket_types[0].name, ticket_types[0].name);
+    assert_eq!(
+        occurrence_ticket_types[0].start_date,
+        ticket_types[0].start_date + Duration::weeks(2)
+    );
+    assert_eq!(
+        occurrence_ticket_types[0]
+            .valid_ticket_count(connection)
+            .unwrap(),
+        ticket_types[0].valid_ticket_count(connection).unwrap()
+    );
+    assert_eq!(
+        occurrence_ticket_types[0]
+            .ticket_pricing(connection)
+            .unwrap()
+            .len(),
+        ticket_types[0].ticket_pricing(connection).unwrap().len()
+    );
+
+    let occurrence_holds = Hold::find_for_event(occurrence.id, connection).unwrap();
+    assert_eq!(occurrence_holds.len(), 1);
+    assert_eq!(occurrence_holds[0].name, hold.name);
+    assert_ne!(occurrence_holds[0].redemption_code, hold.redemption_code);
+    assert_eq!(occurrence_holds[0].quantity(connection).unwrap().0, 10);
+
+    let occurrence_artists = occurrence.artists(connection).unwrap();
+    assert_eq!(occurrence_artists.len(), 1);
+    assert_eq!(occurrence_artists[0].artist.id, artist.id);
+
+    // Events can only belong to one series
+    let event = Event::find(event.id, connection).unwrap();
+    assert!(
+        EventSeries::create_from_event(&event, RecurrenceFrequency::Daily, 1, 2, connection)
+            .is_err()
+    );
+}
+
+#[test]
+fn create_from_event_with_invalid_occurrence_count() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().finish();
+
+    let result =
+        EventSeries::create_from_event(&event, RecurrenceFrequency::Weekly, 1, 0, connection);
+    match result {
+        Ok(_) => panic!("Expected validation error"),
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("occurrence_count"));
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+}
+
+#[test]
+fn occurrence_start() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let first_start = NaiveDate::from_ymd(2019, 1, 31).and_hms(20, 0, 0);
+
+    let series = EventSeries::create(
+        organization.id,
+        "Series".to_string(),
+        RecurrenceFrequency::Monthly,
+        1,
+        12,
+    )
+    .commit(connection)
+    .unwrap();
+    assert_eq!(
+        series.occurrence_start(first_start, 1),
+        NaiveDate::from_ymd(2019, 2, 28).and_hms(20, 0, 0)
+    );
+    assert_eq!(
+        series.occurrence_start(first_start, 12),
+        NaiveDate::from_ymd(2020, 1, 31).and_hms(20, 0, 0)
+    );
+
+    let series = EventSeries::create(
+        organization.id,
+        "Series".to_string(),
+        RecurrenceFrequency::Daily,
+        3,
+        12,
+    )
+    .commit(connection)
+    .unwrap();
+    assert_eq!(
+        series.occurrence_start(first_start, 2),
+        NaiveDate::from_ymd(2019, 2, 6).and_hms(20, 0, 0)
+    );
+}
+
+#[test]
+fn update_future_occurrences() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event_start = Utc::now().naive_utc() - Duration::hours(12);
+    let event = project
+        .create_event()
+        .with_event_start(event_start)
+        .finish();
+    let series =
+        EventSeries::create_from_event(&event, RecurrenceFrequency::Daily, 1, 3, connection)
+            .unwrap();
+
+    let updated_events = series
+        .update_future_occurrences(
+            None,
+            EventEditableAttributes {
+                name: Some("Club night".to_string()),
+                event_start: Some(event_start),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    assert_eq!(updated_events.len(), 2);
+
+    let occurrences = series.occurrences(connection).unwrap();
+    assert_eq!(occurrences[0].name, event.name);
+    assert_eq!(occurrences[1].name, "Club night");
+    assert_eq!(occurrences[2].name, "Club night");
+    // Occurrence dates are not overwritten
+    assert_eq!(
+        occurrences[2].event_start,
+        Some(event_start + Duration::days(2))
+    );
+}
+
+#[test]
+fn update_future_ticket_types() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event_start = Utc::now().naive_utc() - Duration::hours(12);
+    let event = project
+        .create_event()
+        .with_event_start(event_start)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let series =
+        EventSeries::create_from_event(&event, RecurrenceFrequency::Daily, 1, 3, connection)
+            .unwrap();
+
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let ticket_type = ticket_type
+        .update(
+            TicketTypeEditableAttributes {
+                limit_per_person: Some(7),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    let standard_pricing = ticket_type
+        .ticket_pricing(connection)
+        .unwrap()
+        .into_iter()
+        .find(|p| p.name == "Standard")
+        .unwrap()
+        .update(
+            TicketPricingEditableAttributes {
+                price_in_cents: Some(200),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    let wallet_id = event.issuer_wallet(connection).unwrap().id;
+    let vip_ticket_type = event
+        .add_ticket_type(
+            "VIP".to_string(),
+            None,
+            10,
+            ticket_type.start_date,
+            ticket_type.end_date,
+            wallet_id,
+            None,
+            2,
+            500,
+            connection,
+        )
+        .unwrap();
+
+    let updated_events = series
+        .update_future_ticket_types(&event, connection)
+        .unwrap();
+    assert_eq!(updated_events.len(), 2);
+
+    let occurrences = series.occurrences(connection).unwrap();
+    let occurrence_ticket_types = occurrences[1].ticket_types(connection).unwrap();
+    assert_eq!(occurrence_ticket_types.len(), 2);
+    let occurrence_ticket_type = occurrence_ticket_types
+        .iter()
+        .find(|t| t.name == ticket_type.name)
+        .unwrap();
+    assert_eq!(occurrence_ticket_type.limit_per_person, 7);
+    let occurrence_pricing = occurrence_ticket_type.ticket_pricing(connection).unwrap();
+    assert_eq!(occurrence_pricing.len(), 2);
+    let occurrence_standard_pricing = occurrence_pricing
+        .iter()
+        .find(|p| p.name == "Standard")
+        .unwrap();
+    assert_eq!(occurrence_standard_pricing.price_in_cents, 200);
+    // Dates are shifted to the occurrence
+    assert_eq!(
+        occurrence_standard_pricing.start_date,
+        standard_pricing.start_date + Duration::days(1)
+    );
+    let occurrence_vip_ticket_type = occurrence_ticket_types
+        .iter()
+        .find(|t| t.name == "VIP")
+        .unwrap();
+    assert_eq!(
+        occurrence_vip_ticket_type.start_date,
+        vip_ticket_type.start_date + Duration::days(1)
+    );
+    assert_eq!(
+        occurrence_vip_ticket_type
+            .valid_ticket_count(connection)
+            .unwrap(),
+        10
+    );
+
+    // Only occurrences of the series can be copied from
+    let other_event = project.create_event().finish();
+    assert!(series
+        .update_future_ticket_types(&other_event, connection)
+        .is_err());
+}
+
+#[test]
+fn group_by_series() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().finish();
+    let other_event = project.create_event().finish();
+    let series =
+        EventSeries::create_from_event(&event, RecurrenceFrequency::Weekly, 1, 3, connection)
+            .unwrap();
+
+    let mut events = series.occurrences(connection).unwrap();
+    events.push(other_event.clone());
+    let grouped_events = Event::group_by_series(events);
+    assert_eq!(grouped_events.len(), 2);
+    assert_eq!(grouped_events[0].id, event.id);
+    assert_eq!(grouped_events[1].id, other_event.id);
+}
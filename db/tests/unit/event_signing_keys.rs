@@ -0,0 +1,295 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::models::*;
+use chrono::prelude::*;
+use time::Duration;
+
+#[test]
+fn find_or_create_for_event() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().finish();
+
+    let signing_key = EventSigningKey::find_or_create_for_event(event.id, connection).unwrap();
+    assert_eq!(signing_key.event_id, event.id);
+    assert_eq!(
+        EventSigningKey::find_or_create_for_event(event.id, connection).unwrap(),
+        signing_key
+    );
+
+    let other_event = project.create_event().finish();
+    let other_signing_key =
+        EventSigningKey::find_or_create_for_event(other_event.id, connection).unwrap();
+    assert_ne!(other_signing_key.public_key, signing_key.public_key);
+}
+
+#[test]
+fn sign_ticket() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_event_start(Utc::now().naive_utc() + Duration::days(1))
+        .with_ticket_pricing()
+        .finish();
+    let user = project.create_user().finish();
+    project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .is_paid()
+        .finish();
+    let ticket = TicketInstance::find_for_user(user.id, connection)
+        .unwrap()
+        .remove(0);
+
+    let signing_key = EventSigningKey::find_or_create_for_event(event.id, connection).unwrap();
+    let expires_at = EventSigningKey::payload_expiry(&event);
+    let data = signing_key.sign_ticket(&ticket, expires_at).unwrap();
+
+    let payload = signing_key.verify(&data).unwrap();
+    assert_eq!(payload.ticket_instance_id, ticket.id);
+    assert_eq!(payload.event_id, event.id);
+    assert_eq!(payload.token_id, ticket.token_id);
+    assert_eq!(payload.expires_at, expires_at.timestamp());
+
+    // Tampered payloads and payloads for other events are rejected
+    let tampered = data.replacen(&ticket.id.to_string(), &user.id.to_string(), 1);
+    assert!(signing_key.verify(&tampered).is_none());
+    assert!(signing_key.verify("not a payload").is_none());
+    let other_event = project.create_event().finish();
+    let other_signing_key =
+        EventSigningKey::find_or_create_for_event(other_event.id, connection).unwrap();
+    assert!(other_signing_key.verify(&data).is_none());
+}
+
+#[test]
+fn signed_payload_for_ticket() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_event_start(Utc::now().naive_utc() + Duration::days(1))
+        .with_ticket_pricing()
+        .finish();
+    let user = project.create_user().finish();
+    let other_user = project.create_user().finish();
+    project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .is_paid()
+        .finish();
+    let ticket = TicketInstance::find_for_user(user.id, connection)
+        .unwrap()
+        .remove(0);
+
+    let payload =
+        EventSigningKey::signed_payload_for_ticket(&ticket, &event, user.id, connection).unwrap();
+    assert!(payload.is_some());
+
+    // Only the holder of the ticket can get its payload
+    let payload =
+        EventSigningKey::signed_payload_for_ticket(&ticket, &event, other_user.id, connection)
+            .unwrap();
+    assert!(payload.is_none());
+}
+
+#[test]
+fn redeem_offline_scans() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_event_start(Utc::now().naive_utc() + Duration::days(1))
+        .with_ticket_pricing()
+        .finish();
+    let user = project.create_user().finish();
+    project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let tickets = TicketInstance::find_for_user(user.id, connection).unwrap();
+    let signing_key = EventSigningKey::find_or_create_for_event(event.id, connection).unwrap();
+    let expires_at = EventSigningKey::payload_expiry(&event);
+    let payload1 = signing_key.sign_ticket(&tickets[0], expires_at).unwrap();
+    let payload2 = signing_key.sign_ticket(&tickets[1], expires_at).unwrap();
+    TicketInstance::redeem_ticket(
+        tickets[1].id,
+        tickets[1].redeem_key.clone().unwrap(),
+        connection,
+    )
+    .unwrap();
+
+    let scanned_at = Utc::now().naive_utc();
+    let results = signing_key
+        .redeem_offline_scans(
+            vec![
+                OfflineScan {
+                    payload: payload1.clone(),
+                    scanned_at: scanned_at + Duration::minutes(5),
+                },
+                OfflineScan {
+                    payload: payload1.clone(),
+                    scanned_at,
+                },
+                OfflineScan {
+                    payload: payload2.clone(),
+                    scanned_at,
+                },
+                OfflineScan {
+                    payload: "invalid".to_string(),
+                    scanned_at,
+                },
+                OfflineScan {
+                    payload: payload1.clone(),
+                    scanned_at: expires_at + Duration::minutes(1),
+                },
+            ],
+            None,
+            Some("Scanner 1".to_string()),
+            None,
+            connection,
+        )
+        .unwrap();
+
+    // Scans are resolved in the order they happened
+    let results: Vec<(Option<_>, NaiveDateTime, OfflineScanResults)> = results
+        .into_iter()
+        .map(|r| (r.ticket_instance_id, r.scanned_at, r.result))
+        .collect();
+    assert_eq!(
+        results,
+        vec![
+            (
+                Some(tickets[0].id),
+                scanned_at,
+                OfflineScanResults::Redeemed
+            ),
+            (
+                Some(tickets[1].id),
+                scanned_at,
+                OfflineScanResults::AlreadyRedeemed
+            ),
+            (None, scanned_at, OfflineScanResults::Invalid),
+            (
+                Some(tickets[0].id),
+                scanned_at + Duration::minutes(5),
+                OfflineScanResults::Duplicate
+            ),
+            (
+                Some(tickets[0].id),
+                expires_at + Duration::minutes(1),
+                OfflineScanResults::Duplicate
+            ),
+        ]
+    );
+    assert_eq!(
+        TicketInstance::find(tickets[0].id, connection)
+            .unwrap()
+            .status,
+        TicketInstanceStatus::Redeemed
+    );
+
+    let scans = TicketScan::find_for_event(event.id, 0, 100, connection).unwrap();
+    assert_eq!(scans.paging.total, 5);
+    assert!(scans
+        .data
+        .iter()
+        .all(|s| s.device_id == Some("Scanner 1".to_string())));
+}
+
+#[test]
+fn revoked_tickets() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_event_start(Utc::now().naive_utc() + Duration::days(1))
+        .with_ticket_pricing()
+        .finish();
+    let user = project.create_user().finish();
+    let order = project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let tickets = TicketInstance::find_for_user(user.id, connection).unwrap();
+    let signing_key = EventSigningKey::find_or_create_for_event(event.id, connection).unwrap();
+    assert!(signing_key.revoked_tickets(connection).unwrap().is_empty());
+
+    let refund_items: Vec<RefundItem> = order
+        .refundable_ticket_items(event.id, None, connection)
+        .unwrap()
+        .into_iter()
+        .filter(|i| i.ticket_instance_id == Some(tickets[0].id))
+        .collect();
+    order.refund(refund_items, connection).unwrap();
+
+    let revoked_tickets = signing_key.revoked_tickets(connection).unwrap();
+    assert_eq!(revoked_tickets.len(), 1);
+    assert_eq!(revoked_tickets[0].ticket_instance_id, tickets[0].id);
+
+    let data = signing_key
+        .sign_ticket(&tickets[0], EventSigningKey::payload_expiry(&event))
+        .unwrap();
+    let results = signing_key
+        .redeem_offline_scans(
+            vec![OfflineScan {
+                payload: data,
+                scanned_at: Utc::now().naive_utc(),
+            }],
+            None,
+            Some("Scanner 1".to_string()),
+            None,
+            connection,
+        )
+        .unwrap();
+    assert_ne!(results[0].result, OfflineScanResults::Redeemed);
+}
+
+#[test]
+fn revoked_tickets_after_transfer() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_event_start(Utc::now().naive_utc() + Duration::days(1))
+        .with_ticket_pricing()
+        .finish();
+    let user = project.create_user().finish();
+    let user2 = project.create_user().finish();
+    project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .is_paid()
+        .finish();
+    let ticket = TicketInstance::find_for_user(user.id, connection)
+        .unwrap()
+        .remove(0);
+    let signing_key = EventSigningKey::find_or_create_for_event(event.id, connection).unwrap();
+
+    // The sender still holds the ticket until the transfer is received
+    let sender_wallet = Wallet::find_default_for_user(user.id, connection).unwrap();
+    let receiver_wallet = Wallet::find_default_for_user(user2.id, connection).unwrap();
+    let transfer_auth =
+        TicketInstance::authorize_ticket_transfer(user.id, vec![ticket.id], 3600, connection)
+            .unwrap();
+    assert!(signing_key.revoked_tickets(connection).unwrap().is_empty());
+
+    TicketInstance::receive_ticket_transfer(
+        transfer_auth,
+        &sender_wallet,
+        &receiver_wallet.id,
+        connection,
+    )
+    .unwrap();
+    let revoked_tickets = signing_key.revoked_tickets(connection).unwrap();
+    assert_eq!(revoked_tickets.len(), 1);
+    assert_eq!(revoked_tickets[0].ticket_instance_id, ticket.id);
+}
@@ -47,7 +47,9 @@ fn update() {
         door_time: Some(NaiveDate::from_ymd(2016, 7, 8).and_hms(4, 10, 11)),
         ..Default::default()
     };
-    let event = event.update(parameters, project.get_connection()).unwrap();
+    let event = event
+        .update(None, parameters, project.get_connection())
+        .unwrap();
     assert_eq!(
         event.door_time,
         Some(NaiveDate::from_ymd(2016, 7, 8).and_hms(4, 10, 11))
@@ -182,7 +184,9 @@ fn publish_in_future() {
         publish_date: Some(Some(NaiveDate::from_ymd(2054, 7, 8).and_hms(4, 10, 11))),
         ..Default::default()
     };
-    let event = event.update(parameters, project.get_connection()).unwrap();
+    let event = event
+        .update(None, parameters, project.get_connection())
+        .unwrap();
 
     let event = event.publish(project.get_connection()).unwrap();
 
@@ -219,7 +223,9 @@ fn publish_change_publish_date() {
         publish_date: Some(Some(NaiveDate::from_ymd(2054, 7, 8).and_hms(4, 10, 11))),
         ..Default::default()
     };
-    let event = event.update(parameters, project.get_connection()).unwrap();
+    let event = event
+        .update(None, parameters, project.get_connection())
+        .unwrap();
 
     let event = event.publish(project.get_connection()).unwrap();
 
@@ -230,7 +236,9 @@ fn publish_change_publish_date() {
         ..Default::default()
     };
 
-    let event = event.update(parameters, project.get_connection()).unwrap();
+    let event = event
+        .update(None, parameters, project.get_connection())
+        .unwrap();
 
     assert_eq!(
         event.publish_date,
@@ -242,7 +250,9 @@ fn publish_change_publish_date() {
         ..Default::default()
     };
 
-    let event = event.update(parameters, project.get_connection()).unwrap();
+    let event = event
+        .update(None, parameters, project.get_connection())
+        .unwrap();
 
     assert!(event.publish_date.unwrap() > now);
 
@@ -321,7 +331,7 @@ fn cancel() {
         .with_venue(&venue)
         .finish();
 
-    let event = event.cancel(&project.get_connection()).unwrap();
+    let event = event.cancel(user.id, &project.get_connection()).unwrap();
     assert!(!event.cancelled_at.is_none());
 }
 
@@ -354,6 +364,7 @@ fn get_sales_by_date_range() {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -373,6 +384,7 @@ fn get_sales_by_date_range() {
                 ticket_type_id: ticket_type.id,
                 quantity: 5,
                 redemption_code: None,
+                seat_ids: None,
             }],
             false,
             false,
@@ -480,7 +492,9 @@ fn find_individuals() {
         door_time: Some(NaiveDate::from_ymd(2016, 7, 8).and_hms(4, 10, 11)),
         ..Default::default()
     };
-    let event = event.update(parameters, project.get_connection()).unwrap();
+    let event = event
+        .update(None, parameters, project.get_connection())
+        .unwrap();
 
     //find event
     let found_event = Event::find(event.id, project.get_connection()).unwrap();
@@ -1078,7 +1092,7 @@ fn find_active_for_venue() {
         .add_artist(artist1.id, project.get_connection())
         .unwrap();
     //Cancel first event
-    event.cancel(connection).unwrap();
+    event.cancel(user.id, connection).unwrap();
 
     //find all active events via venue
     let found_events =
@@ -1239,3 +1253,68 @@ fn get_all_localized_times() {
     assert_eq!(localized_times.event_end, None);
     assert_ne!(localized_times.door_time, None);
 }
+
+#[test]
+fn duplicate() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event_start = Utc::now().naive_utc() + Duration::days(7);
+    let event = project
+        .create_event()
+        .with_event_start(event_start)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let stage = project.create_stage().finish();
+    let artist = project.create_artist().finish();
+    EventArtist::create(event.id, artist.id, 1, Some(event_start), 1, Some(stage.id))
+        .commit(connection)
+        .unwrap();
+    let hold = project.create_hold().with_event(&event).finish();
+    project
+        .create_comp()
+        .with_hold(&hold)
+        .with_quantity(1)
+        .finish();
+    let code = project
+        .create_code()
+        .with_event(&event)
+        .for_ticket_type(&ticket_type)
+        .finish();
+
+    let new_event_start = event_start + Duration::days(30);
+    let new_event = event
+        .duplicate(Some(new_event_start), EventStatus::Draft, None, connection)
+        .unwrap();
+    assert_ne!(new_event.id, event.id);
+    assert_eq!(new_event.name, event.name);
+    assert_eq!(new_event.status, EventStatus::Draft);
+    assert_eq!(new_event.event_start, Some(new_event_start));
+
+    let new_ticket_types = new_event.ticket_types(connection).unwrap();
+    assert_eq!(new_ticket_types.len(), 1);
+    assert_eq!(new_ticket_types[0].name, ticket_type.name);
+    assert_eq!(
+        new_ticket_types[0].end_date,
+        ticket_type.end_date + Duration::days(30)
+    );
+
+    let new_holds = Hold::find_for_event(new_event.id, connection).unwrap();
+    assert_eq!(new_holds.len(), 1);
+    assert_eq!(new_holds[0].name, hold.name);
+    assert_ne!(new_holds[0].redemption_code, hold.redemption_code);
+    assert_eq!(new_holds[0].parent_hold_id, None);
+
+    let new_codes = Code::find_for_event(new_event.id, None, connection).unwrap();
+    assert_eq!(new_codes.len(), 1);
+    assert_eq!(new_codes[0].name, code.name);
+    assert_ne!(new_codes[0].redemption_code, code.redemption_code);
+    assert_eq!(new_codes[0].ticket_type_ids, vec![new_ticket_types[0].id]);
+
+    let new_artists = new_event.artists(connection).unwrap();
+    assert_eq!(new_artists.len(), 1);
+    assert_eq!(new_artists[0].artist.id, artist.id);
+    assert_eq!(new_artists[0].stage_id, Some(stage.id));
+    assert_eq!(new_artists[0].set_time, Some(new_event_start));
+}
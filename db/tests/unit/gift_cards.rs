@@ -0,0 +1,85 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+use diesel::PgConnection;
+
+fn purchase_gift_card(user: &User, conn: &PgConnection) -> (Order, GiftCard) {
+    let mut cart = Order::find_or_create_cart(user, conn).unwrap();
+    cart.update_gift_cards(
+        &[UpdateGiftCardItem {
+            value_in_cents: 2500,
+            quantity: 1,
+        }],
+        conn,
+    )
+    .unwrap();
+    cart.add_external_payment(Some("test".to_string()), user.id, 2500, conn)
+        .unwrap();
+    let gift_card = GiftCard::find_for_order(cart.id, conn).unwrap().remove(0);
+    (cart, gift_card)
+}
+
+#[test]
+fn find_by_redemption_code() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let (_, gift_card) = purchase_gift_card(&user, connection);
+
+    let found_gift_card =
+        GiftCard::find_by_redemption_code(&gift_card.redemption_code.to_lowercase(), connection)
+            .unwrap();
+    assert_eq!(found_gift_card, gift_card);
+
+    let result = GiftCard::find_by_redemption_code("NOTACODE", connection);
+    assert_eq!(result.unwrap_err().error_code, ErrorCode::NoResults);
+}
+
+#[test]
+fn find_purchased_by_user() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let user2 = project.create_user().finish();
+    let (_, gift_card) = purchase_gift_card(&user, connection);
+
+    assert_eq!(
+        GiftCard::find_purchased_by_user(user.id, connection).unwrap(),
+        vec![gift_card]
+    );
+    assert!(GiftCard::find_purchased_by_user(user2.id, connection)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn redeem() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let recipient = project.create_user().finish();
+    let (_, gift_card) = purchase_gift_card(&user, connection);
+
+    let credit_transaction = gift_card.redeem(&recipient, connection).unwrap();
+    assert_eq!(credit_transaction.user_id, recipient.id);
+    assert_eq!(
+        credit_transaction.transaction_type,
+        CreditTransactionTypes::GiftCard
+    );
+    assert_eq!(credit_transaction.gift_card_id, Some(gift_card.id));
+    assert_eq!(
+        CreditTransaction::balance_for_user(recipient.id, connection).unwrap(),
+        2500
+    );
+
+    let gift_card =
+        GiftCard::find_by_redemption_code(&gift_card.redemption_code, connection).unwrap();
+    assert_eq!(gift_card.redeemed_by_user_id, Some(recipient.id));
+    assert!(gift_card.redeemed_at.is_some());
+
+    // Gift cards can only be redeemed once
+    assert!(gift_card.redeem(&user, connection).is_err());
+    assert_eq!(
+        CreditTransaction::balance_for_user(user.id, connection).unwrap(),
+        0
+    );
+}
@@ -1,6 +1,9 @@
 use bigneon_db::dev::TestProject;
 use bigneon_db::models::*;
+use bigneon_db::utils::errors::ErrorCode;
 use bigneon_db::utils::errors::ErrorCode::ValidationError;
+use chrono::prelude::*;
+use time::Duration;
 use uuid::Uuid;
 
 #[test]
@@ -13,9 +16,11 @@ fn create() {
         "IHAVEACODE".to_string(),
         Some(0),
         None,
+        None,
         Some(4),
         HoldTypes::Discount,
         event.ticket_types(db.get_connection()).unwrap()[0].id,
+        None,
     )
     .commit(db.get_connection())
     .unwrap();
@@ -31,9 +36,11 @@ fn create_with_validation_errors() {
         "IHAVEACODE".to_string(),
         None,
         None,
+        None,
         Some(4),
         HoldTypes::Discount,
         event.ticket_types(db.get_connection()).unwrap()[0].id,
+        None,
     )
     .commit(db.get_connection());
 
@@ -59,9 +66,11 @@ fn create_with_validation_errors() {
         hold.redemption_code,
         Some(0),
         None,
+        None,
         Some(4),
         HoldTypes::Discount,
         event.ticket_types(db.get_connection()).unwrap()[0].id,
+        None,
     )
     .commit(db.get_connection());
     match result {
@@ -86,9 +95,11 @@ fn create_with_validation_errors() {
         code.redemption_code,
         Some(0),
         None,
+        None,
         Some(4),
         HoldTypes::Discount,
         event.ticket_types(db.get_connection()).unwrap()[0].id,
+        None,
     )
     .commit(db.get_connection());
     match result {
@@ -104,6 +115,37 @@ fn create_with_validation_errors() {
             _ => panic!("Expected validation error"),
         },
     }
+
+    // Both discount amount and percentage set
+    let result = Hold::create_hold(
+        "test".to_string(),
+        event.id,
+        "IHAVEAPERCENTAGE".to_string(),
+        Some(10),
+        Some(10),
+        None,
+        Some(4),
+        HoldTypes::Discount,
+        event.ticket_types(db.get_connection()).unwrap()[0].id,
+        None,
+    )
+    .commit(db.get_connection());
+    match result {
+        Ok(_) => {
+            panic!("Expected validation error");
+        }
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("discount_as_percentage"));
+                assert_eq!(errors["discount_as_percentage"].len(), 1);
+                assert_eq!(
+                    errors["discount_as_percentage"][0].code,
+                    "only_one_discount_allowed"
+                );
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
 }
 
 #[test]
@@ -224,6 +266,7 @@ fn update_with_validation_errors() {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: Some(comp.redemption_code),
+            seat_ids: None,
         }],
         false,
         false,
@@ -332,3 +375,279 @@ fn find_by_parent_hold_id() {
     .unwrap()
     .is_empty());
 }
+
+#[test]
+fn create_with_end_at_schedules_expiry() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().with_tickets().finish();
+    let ticket_type_id = event.ticket_types(connection).unwrap()[0].id;
+    let hold = Hold::create_hold(
+        "test".to_string(),
+        event.id,
+        "IHAVEACODE".to_string(),
+        Some(0),
+        None,
+        None,
+        None,
+        HoldTypes::Discount,
+        ticket_type_id,
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+    assert!(!DomainAction::has_pending_action(
+        DomainActionTypes::ExpireHold,
+        Tables::Holds.table_name(),
+        hold.id,
+        connection
+    )
+    .unwrap());
+
+    let update_patch = UpdateHoldAttributes {
+        end_at: Some(Some(Utc::now().naive_utc() + Duration::days(1))),
+        ..Default::default()
+    };
+    let hold = hold.update(update_patch, connection).unwrap();
+    assert!(DomainAction::has_pending_action(
+        DomainActionTypes::ExpireHold,
+        Tables::Holds.table_name(),
+        hold.id,
+        connection
+    )
+    .unwrap());
+}
+
+#[test]
+fn expire() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let hold = Hold::create_hold(
+        "test".to_string(),
+        event.id,
+        "IHAVEACODE".to_string(),
+        Some(0),
+        None,
+        Some(Utc::now().naive_utc() + Duration::days(1)),
+        None,
+        HoldTypes::Discount,
+        ticket_type.id,
+        None,
+    )
+    .commit(connection)
+    .unwrap();
+    hold.set_quantity(10, connection).unwrap();
+
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: Some(hold.redemption_code.clone()),
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+
+    // Hold has not ended yet
+    assert_eq!(hold.expire(connection).unwrap(), 0);
+    assert_eq!(hold.quantity(connection).unwrap(), (10, 10));
+
+    let update_patch = UpdateHoldAttributes {
+        end_at: Some(Some(Utc::now().naive_utc() - Duration::minutes(1))),
+        ..Default::default()
+    };
+    let hold = hold.update(update_patch, connection).unwrap();
+    assert_eq!(hold.expire(connection).unwrap(), 8);
+    // Tickets in the cart stay in the hold until their reservation lapses
+    assert_eq!(hold.quantity(connection).unwrap(), (2, 2));
+
+    let domain_events = DomainEvent::find(
+        Tables::Holds,
+        Some(hold.id),
+        Some(DomainEventTypes::HoldExpired),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(domain_events.len(), 1);
+    assert_eq!(
+        domain_events[0].event_data,
+        Some(json!({"ticket_type_id": ticket_type.id, "quantity_released": 8}))
+    );
+
+    // Nothing left to release
+    assert_eq!(hold.expire(connection).unwrap(), 0);
+}
+
+#[test]
+fn import_comps() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let hold = project.create_hold().finish();
+    assert_eq!(hold.quantity(connection).unwrap(), (10, 10));
+
+    let recipients = vec![
+        CompRecipient {
+            name: "Jane".to_string(),
+            email: Some("jane@tari.com".to_string()),
+            phone: None,
+            quantity: 4,
+        },
+        CompRecipient {
+            name: "John".to_string(),
+            email: None,
+            phone: Some("555-555-5555".to_string()),
+            quantity: 6,
+        },
+    ];
+
+    // Invalid rows and rows beyond the hold's remaining quantity are reported
+    let mut invalid_recipients = recipients.clone();
+    invalid_recipients.push(CompRecipient {
+        name: "".to_string(),
+        email: Some("invalid".to_string()),
+        phone: None,
+        quantity: 1,
+    });
+    let row_errors = hold
+        .import_comps(invalid_recipients, None, connection)
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(row_errors.len(), 1);
+    assert_eq!(row_errors[0].row, 3);
+    assert_eq!(row_errors[0].errors["name"][0].code, "required");
+    assert_eq!(row_errors[0].errors["email"][0].code, "email");
+    assert_eq!(
+        row_errors[0].errors["quantity"][0].code,
+        "insufficient_quantity"
+    );
+    assert!(hold.comps(connection).unwrap().is_empty());
+
+    let comps = hold
+        .import_comps(recipients, Some(user.id), connection)
+        .unwrap()
+        .unwrap();
+    assert_eq!(comps.len(), 2);
+    assert_eq!(comps[0].created_by_user_id, Some(user.id));
+    assert_eq!(comps[0].name, "Jane".to_string());
+    assert_eq!(comps[0].email, Some("jane@tari.com".to_string()));
+    assert_eq!(comps[0].hold_type, HoldTypes::Comp);
+    assert_eq!(comps[0].parent_hold_id, Some(hold.id));
+    assert_eq!(comps[0].quantity(connection).unwrap(), (4, 4));
+    assert_eq!(comps[1].phone, Some("555-555-5555".to_string()));
+    assert_eq!(comps[1].quantity(connection).unwrap(), (6, 6));
+    assert_ne!(comps[0].redemption_code, comps[1].redemption_code);
+}
+
+#[test]
+fn prepare_claim() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let sender = project.create_user().finish();
+    let comp = project.create_comp().with_quantity(2).finish();
+    assert_eq!(comp.claim_key, None);
+    assert_eq!(comp.claim_sent_at, None);
+
+    let (comp, tickets) = comp.prepare_claim(sender.id, connection).unwrap();
+    let claim_key = comp.claim_key.unwrap();
+    assert!(comp.claim_sent_at.is_some());
+    assert_eq!(
+        Hold::find_by_claim_key(claim_key, connection).unwrap().id,
+        comp.id
+    );
+
+    // The comp's tickets are issued to the sender until they are claimed
+    assert_eq!(tickets.len(), 2);
+    assert_eq!(
+        TicketInstance::find_for_user(sender.id, connection)
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(comp.quantity(connection).unwrap(), (2, 0));
+
+    // Sending again reuses the existing claim link and does not issue more tickets
+    let (comp, tickets) = comp.prepare_claim(sender.id, connection).unwrap();
+    assert_eq!(comp.claim_key, Some(claim_key));
+    assert!(tickets.is_empty());
+
+    // Comps without an email or phone cannot be sent
+    let comp = comp
+        .update(
+            UpdateHoldAttributes {
+                email: Some(None),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    assert!(comp.prepare_claim(sender.id, connection).is_err());
+}
+
+#[test]
+fn claim() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let sender = project.create_user().finish();
+    let user = project.create_user().finish();
+    let user2 = project.create_user().finish();
+    let comp = project.create_comp().with_quantity(2).finish();
+
+    // Comps that have not been sent have no tickets to claim
+    assert!(comp.claim(&user, connection).is_err());
+
+    let (comp, _) = comp.prepare_claim(sender.id, connection).unwrap();
+    let (sender_wallet, tickets) = comp.claim(&user, connection).unwrap();
+    assert_eq!(
+        sender_wallet.id,
+        Wallet::find_default_for_user(sender.id, connection)
+            .unwrap()
+            .id
+    );
+    assert_eq!(tickets.len(), 2);
+    let user_tickets = TicketInstance::find_for_user(user.id, connection).unwrap();
+    assert_eq!(user_tickets.len(), 2);
+    assert!(TicketInstance::find_for_user(sender.id, connection)
+        .unwrap()
+        .is_empty());
+    for ticket in user_tickets {
+        let domain_events = DomainEvent::find(
+            Tables::TicketInstances,
+            Some(ticket.id),
+            Some(DomainEventTypes::TransferTicketCompleted),
+            connection,
+        )
+        .unwrap();
+        assert_eq!(domain_events.len(), 1);
+    }
+
+    let comp = Hold::find(comp.id, connection).unwrap();
+    assert!(comp.claimed_at.is_some());
+    assert_eq!(comp.claimed_by_user_id, Some(user.id));
+    assert_eq!(comp.quantity(connection).unwrap(), (2, 0));
+
+    // Comps are not sales
+    let report =
+        Report::transaction_detail_report(Some(comp.event_id), None, None, None, connection)
+            .unwrap();
+    assert!(report.is_empty());
+
+    // A comp can only be claimed once
+    let result = comp.claim(&user2, connection);
+    assert_eq!(
+        result.unwrap_err().error_code,
+        ErrorCode::BusinessProcessError
+    );
+    assert!(comp.prepare_claim(sender.id, connection).is_err());
+}
@@ -1,29 +1,47 @@
 pub mod artists;
 pub mod assets;
+pub mod code_campaigns;
 pub mod codes;
 pub mod comps;
 pub mod concerns;
+pub mod credit_transactions;
 pub mod domain_actions;
 pub mod domain_events;
 pub mod event_artists;
+pub mod event_cancellations;
 pub mod event_interest;
+pub mod event_reschedules;
+pub mod event_series;
+pub mod event_signing_keys;
 pub mod events;
 pub mod fee_schedule_ranges;
 pub mod fee_schedules;
+pub mod gift_cards;
 pub mod holds;
+pub mod order_idempotency_keys;
 pub mod order_items;
+pub mod order_shares;
 pub mod orders;
 pub mod organization_invites;
 pub mod organization_users;
 pub mod organizations;
 pub mod payment_methods;
+pub mod payments;
 pub mod push_notification_tokens;
 pub mod refunded_tickets;
 pub mod regions;
+pub mod reports;
+pub mod resale_listings;
+pub mod resale_payouts;
+pub mod seat_sections;
 pub mod stages;
+pub mod tax_rules;
 pub mod ticket_instances;
 pub mod ticket_pricing;
+pub mod ticket_scans;
 pub mod ticket_type_codes;
 pub mod ticket_types;
 pub mod users;
 pub mod venues;
+pub mod waitlist_entries;
+pub mod wallet_pass_registrations;
@@ -0,0 +1,95 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::models::*;
+
+#[test]
+fn create() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let order = project.create_order().for_user(&user).finish();
+    let request = json!({"amount": 100, "method": {"type": "Free"}});
+
+    let key = OrderIdempotencyKey::create(
+        order.id,
+        user.id,
+        IdempotencyKeyOperations::Checkout,
+        "retry-key".to_string(),
+        &request,
+    )
+    .commit(connection)
+    .unwrap()
+    .unwrap();
+    assert_eq!(key.order_id, order.id);
+    assert!(key.is_locked());
+    assert!(key.matches_request(&request));
+    assert!(!key.matches_request(&json!({"amount": 200, "method": {"type": "Free"}})));
+
+    // Key has already been claimed
+    let result = OrderIdempotencyKey::create(
+        order.id,
+        user.id,
+        IdempotencyKeyOperations::Checkout,
+        "retry-key".to_string(),
+        &request,
+    )
+    .commit(connection)
+    .unwrap();
+    assert!(result.is_none());
+
+    // Same key for a different operation
+    let result = OrderIdempotencyKey::create(
+        order.id,
+        user.id,
+        IdempotencyKeyOperations::Refund,
+        "retry-key".to_string(),
+        &request,
+    )
+    .commit(connection)
+    .unwrap();
+    assert!(result.is_some());
+}
+
+#[test]
+fn set_response() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let order = project.create_order().for_user(&user).finish();
+    let request = json!({"amount": 100});
+
+    let key = OrderIdempotencyKey::create(
+        order.id,
+        user.id,
+        IdempotencyKeyOperations::Checkout,
+        "retry-key".to_string(),
+        &request,
+    )
+    .commit(connection)
+    .unwrap()
+    .unwrap();
+    let key = key
+        .set_response(200, "{\"id\":1}".to_string(), connection)
+        .unwrap();
+    assert!(!key.is_locked());
+
+    let found = OrderIdempotencyKey::find(
+        user.id,
+        IdempotencyKeyOperations::Checkout,
+        "retry-key",
+        connection,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(found.response_status, Some(200));
+    assert_eq!(found.response_body, Some("{\"id\":1}".to_string()));
+
+    key.destroy(connection).unwrap();
+    assert!(OrderIdempotencyKey::find(
+        user.id,
+        IdempotencyKeyOperations::Checkout,
+        "retry-key",
+        connection
+    )
+    .unwrap()
+    .is_none());
+}
@@ -28,6 +28,7 @@ fn find_fee_item() {
             ticket_type_id: ticket.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -66,6 +67,7 @@ fn order() {
             ticket_type_id: ticket.id,
             quantity: 1,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -105,6 +107,7 @@ fn update_with_validation_errors() {
             ticket_type_id: ticket_type.id,
             quantity: 4,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -132,6 +135,7 @@ fn update_with_validation_errors() {
             ticket_type_id: ticket_type.id,
             quantity: 5,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -144,6 +148,7 @@ fn update_with_validation_errors() {
             ticket_type_id: ticket_type.id,
             quantity: 6,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -176,6 +181,7 @@ fn update_with_validation_errors() {
             ticket_type_id: ticket_type.id,
             quantity: 4,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -193,6 +199,7 @@ fn update_with_validation_errors() {
             ticket_type_id: ticket_type.id,
             quantity: 1,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -225,6 +232,7 @@ fn calculate_quantity() {
             ticket_type_id: ticket.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -0,0 +1,72 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+use diesel::PgConnection;
+use uuid::Uuid;
+
+fn split_cart(user: &User, project: &TestProject, conn: &PgConnection) -> (Order, Vec<OrderShare>) {
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let mut cart = Order::find_or_create_cart(user, conn).unwrap();
+    let ticket = &event.ticket_types(conn).unwrap()[0];
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket.id,
+            quantity: 2,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        conn,
+    )
+    .unwrap();
+    let shares = cart.split(&[1, 1], conn).unwrap();
+    (cart, shares)
+}
+
+#[test]
+fn find_by_share_key() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let (cart, shares) = split_cart(&user, &project, connection);
+
+    let found_share = OrderShare::find_by_share_key(shares[1].share_key, connection).unwrap();
+    assert_eq!(found_share, shares[1]);
+    assert_eq!(found_share.order(connection).unwrap().id, cart.id);
+
+    let result = OrderShare::find_by_share_key(Uuid::new_v4(), connection);
+    assert_eq!(result.unwrap_err().error_code, ErrorCode::NoResults);
+}
+
+#[test]
+fn mark_paid() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let user2 = project.create_user().finish();
+    let (mut cart, shares) = split_cart(&user, &project, connection);
+    let payment = cart
+        .add_external_payment(
+            Some("test".to_string()),
+            user2.id,
+            shares[1].amount_in_cents,
+            connection,
+        )
+        .unwrap();
+
+    let share = shares[1]
+        .mark_paid(user2.id, payment.id, connection)
+        .unwrap();
+    assert_eq!(share.paid_by_user_id, Some(user2.id));
+    assert_eq!(share.payment_id, Some(payment.id));
+    assert!(share.paid_at.is_some());
+
+    // Shares can only be paid once
+    assert!(shares[1]
+        .mark_paid(user.id, payment.id, connection)
+        .is_err());
+}
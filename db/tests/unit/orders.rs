@@ -1,6 +1,6 @@
 use bigneon_db::dev::TestProject;
 use bigneon_db::models::*;
-use bigneon_db::schema::orders;
+use bigneon_db::schema::{domain_actions, orders, ticket_instances};
 use bigneon_db::utils::errors::ErrorCode::ValidationError;
 use chrono::prelude::*;
 use diesel;
@@ -52,6 +52,7 @@ fn add_tickets() {
             ticket_type_id: ticket.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -87,6 +88,7 @@ fn add_tickets() {
             ticket_type_id: ticket.id,
             quantity: 15,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -104,6 +106,168 @@ fn add_tickets() {
     assert_eq!(order_item.calculate_quantity(connection), Ok(15));
 }
 
+#[test]
+fn add_tickets_with_seats() {
+    let project = TestProject::new();
+    let creator = project.create_user().finish();
+
+    let connection = project.get_connection();
+    let venue = project.create_venue().finish();
+    let organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(creator.id))
+        .finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_venue(&venue)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let seat_section = project
+        .create_seat_section()
+        .with_venue_id(venue.id)
+        .with_seats(3)
+        .finish();
+    let seat_ids: Vec<Uuid> = seat_section
+        .seats(connection)
+        .unwrap()
+        .iter()
+        .map(|s| s.id)
+        .collect();
+    ticket_type.assign_seats(&seat_ids, connection).unwrap();
+
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+
+    // Quantity must match the seats requested
+    let result = cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 1,
+            redemption_code: None,
+            seat_ids: Some(seat_ids[0..2].to_vec()),
+        }],
+        false,
+        false,
+        connection,
+    );
+    match result {
+        Ok(_) => {
+            panic!("Expected validation error");
+        }
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("seat_ids"));
+                assert_eq!(
+                    errors["seat_ids"][0].message,
+                    Some("Number of seats must match the quantity requested".into())
+                );
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: None,
+            seat_ids: Some(seat_ids[0..2].to_vec()),
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let items = cart.items(connection).unwrap();
+    let order_item = items
+        .iter()
+        .find(|i| i.ticket_type_id == Some(ticket_type.id))
+        .unwrap();
+    let mut reserved_seat_ids: Vec<Uuid> =
+        TicketInstance::find_for_order_item(order_item.id, connection)
+            .unwrap()
+            .iter()
+            .map(|t| t.seat_id.unwrap())
+            .collect();
+    reserved_seat_ids.sort();
+    let mut expected_seat_ids = seat_ids[0..2].to_vec();
+    expected_seat_ids.sort();
+    assert_eq!(reserved_seat_ids, expected_seat_ids);
+
+    // Another user cannot reserve the same seat
+    let user2 = project.create_user().finish();
+    let mut cart2 = Order::find_or_create_cart(&user2, connection).unwrap();
+    assert!(cart2
+        .update_quantities(
+            &[UpdateOrderItem {
+                ticket_type_id: ticket_type.id,
+                quantity: 1,
+                redemption_code: None,
+                seat_ids: Some(vec![seat_ids[0]]),
+            }],
+            false,
+            false,
+            connection,
+        )
+        .is_err());
+
+    // Changing seats releases the previous ones
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 1,
+            redemption_code: None,
+            seat_ids: Some(vec![seat_ids[2]]),
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let items = cart.items(connection).unwrap();
+    let order_item = items
+        .iter()
+        .find(|i| i.ticket_type_id == Some(ticket_type.id))
+        .unwrap();
+    let tickets = TicketInstance::find_for_order_item(order_item.id, connection).unwrap();
+    assert_eq!(tickets.len(), 1);
+    assert_eq!(tickets[0].seat_id, Some(seat_ids[2]));
+
+    let (_, _, display_ticket) =
+        TicketInstance::find_for_display(tickets[0].id, connection).unwrap();
+    let seat = display_ticket.seat.unwrap();
+    assert_eq!(seat.id, seat_ids[2]);
+    assert_eq!(seat.section_name, seat_section.name);
+
+    // Seats are only reserved when they are requested
+    let user3 = project.create_user().finish();
+    let mut cart3 = Order::find_or_create_cart(&user3, connection).unwrap();
+    cart3
+        .update_quantities(
+            &[UpdateOrderItem {
+                ticket_type_id: ticket_type.id,
+                quantity: 10,
+                redemption_code: None,
+                seat_ids: None,
+            }],
+            false,
+            false,
+            connection,
+        )
+        .unwrap();
+    let items = cart3.items(connection).unwrap();
+    let order_item = items
+        .iter()
+        .find(|i| i.ticket_type_id == Some(ticket_type.id))
+        .unwrap();
+    let tickets = TicketInstance::find_for_order_item(order_item.id, connection).unwrap();
+    assert_eq!(tickets.len(), 10);
+    assert!(tickets.iter().all(|t| t.seat_id.is_none()));
+}
+
 #[test]
 fn details() {
     let project = TestProject::new();
@@ -128,6 +292,7 @@ fn details() {
             ticket_type_id: ticket_type.id,
             quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -304,6 +469,7 @@ fn refund() {
             ticket_type_id: ticket_type.id,
             quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -399,11 +565,13 @@ fn organizations() {
                 ticket_type_id: ticket_type.id,
                 quantity: 10,
                 redemption_code: None,
+                seat_ids: None,
             },
             UpdateOrderItem {
                 ticket_type_id: ticket_type2.id,
                 quantity: 10,
                 redemption_code: None,
+                seat_ids: None,
             },
         ],
         false,
@@ -435,6 +603,7 @@ fn payments() {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -486,6 +655,7 @@ fn add_tickets_with_increment() {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -511,6 +681,7 @@ fn add_tickets_with_increment() {
             ticket_type_id: ticket_type.id,
             quantity: 4,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -523,6 +694,7 @@ fn add_tickets_with_increment() {
             ticket_type_id: ticket_type.id,
             quantity: 12,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -563,6 +735,7 @@ fn clear_cart() {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -615,6 +788,7 @@ fn replace_tickets_for_box_office() {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -649,6 +823,7 @@ fn replace_tickets_for_box_office() {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         true,
         true,
@@ -698,6 +873,7 @@ fn replace_tickets() {
             ticket_type_id: ticket.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -733,6 +909,7 @@ fn replace_tickets() {
             ticket_type_id: ticket.id,
             quantity: 15,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         true,
@@ -767,6 +944,7 @@ fn remove_tickets() {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -803,6 +981,7 @@ fn remove_tickets() {
                 ticket_type_id: ticket_type.id,
                 quantity: 6,
                 redemption_code: None,
+                seat_ids: None,
             }],
             false,
             false,
@@ -831,6 +1010,7 @@ fn remove_tickets() {
             ticket_type_id: ticket_type.id,
             quantity: 0,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -858,6 +1038,7 @@ fn clear_tickets() {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -894,6 +1075,7 @@ fn remove_tickets_with_increment() {
             ticket_type_id: ticket_type.id,
             quantity: 8,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -912,6 +1094,7 @@ fn remove_tickets_with_increment() {
             ticket_type_id: ticket_type.id,
             quantity: 4,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -931,6 +1114,7 @@ fn remove_tickets_with_increment() {
             ticket_type_id: ticket_type.id,
             quantity: 5,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -971,6 +1155,7 @@ fn find_item() {
                 ticket_type_id: ticket_type.id,
                 quantity: 5,
                 redemption_code: None,
+                seat_ids: None,
             }],
             false,
             false,
@@ -984,6 +1169,7 @@ fn find_item() {
                 ticket_type_id: ticket_type.id,
                 quantity: 10,
                 redemption_code: None,
+                seat_ids: None,
             }],
             false,
             false,
@@ -1055,367 +1241,1928 @@ fn find_cart_for_user() {
 }
 
 #[test]
-fn has_items() {
+fn find_cart_for_checkout() {
     let project = TestProject::new();
-    let connection = project.get_connection();
     let user = project.create_user().finish();
-    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
-
-    // Without items
-    assert!(!cart.has_items(connection).unwrap());
-
-    // With items
     let event = project
         .create_event()
         .with_tickets()
         .with_ticket_pricing()
         .finish();
-    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let conn = project.get_connection();
+    assert!(Order::find_cart_for_checkout(user.id, conn)
+        .unwrap()
+        .is_none());
+
+    let mut cart = Order::find_or_create_cart(&user, conn).unwrap();
+    let ticket_type = &event.ticket_types(conn).unwrap()[0];
     cart.update_quantities(
         &[UpdateOrderItem {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
-        connection,
+        conn,
     )
     .unwrap();
-    assert!(cart.has_items(connection).unwrap());
-}
-
-#[test]
-fn destroy() {
-    let project = TestProject::new();
-    let connection = project.get_connection();
-    let user = project.create_user().finish();
-    let cart = Order::find_or_create_cart(&user, connection).unwrap();
-    let cart_result = Order::find_cart_for_user(user.id, connection).unwrap();
-    assert_eq!(cart_result.unwrap(), cart);
+    let cart_result = Order::find_cart_for_checkout(user.id, conn);
+    assert_eq!(cart_result.unwrap().unwrap().id, cart.id);
 
-    cart.destroy(connection).unwrap();
-    let cart_result = Order::find_cart_for_user(user.id, connection).unwrap();
-    assert!(cart_result.is_none());
+    // Partially paid carts can still be checked out but are no longer the user's cart
+    cart.add_external_payment(Some("test".to_string()), user.id, 500, conn)
+        .unwrap();
+    assert_eq!(cart.status, OrderStatus::PartiallyPaid);
+    assert!(Order::find_cart_for_user(user.id, conn).unwrap().is_none());
+    assert_eq!(
+        Order::find_cart_for_checkout(user.id, conn)
+            .unwrap()
+            .unwrap()
+            .id,
+        cart.id
+    );
 }
 
 #[test]
-fn calculate_cart_total() {
+fn schedule_cart_reminder() {
     let project = TestProject::new();
-    let creator = project.create_user().finish();
-
-    let organization = project
-        .create_organization()
-        .with_fee_schedule(&project.create_fee_schedule().finish(creator.id))
-        .finish();
-    let event = project
-        .create_event()
-        .with_organization(&organization)
-        .with_tickets()
-        .with_ticket_pricing()
-        .finish();
+    let connection = project.get_connection();
     let user = project.create_user().finish();
-    let conn = project.get_connection();
-    let mut cart = Order::find_or_create_cart(&user, conn).unwrap();
-    let ticket = &event.ticket_types(conn).unwrap()[0];
+    let event = project.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
     cart.update_quantities(
         &[UpdateOrderItem {
-            ticket_type_id: ticket.id,
-            quantity: 10,
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
-        conn,
+        connection,
     )
     .unwrap();
+    let cart = Order::find(cart.id, connection).unwrap();
+    assert!(!DomainAction::has_pending_action(
+        DomainActionTypes::SendCartReminder,
+        Tables::Orders.table_name(),
+        cart.id,
+        connection
+    )
+    .unwrap());
 
-    let total = cart.calculate_total(conn).unwrap();
-    assert_eq!(total, 1700);
+    cart.schedule_cart_reminder(Duration::minutes(60), connection)
+        .unwrap();
+    assert!(DomainAction::has_pending_action(
+        DomainActionTypes::SendCartReminder,
+        Tables::Orders.table_name(),
+        cart.id,
+        connection
+    )
+    .unwrap());
+    // Not due until an hour after the cart expires
+    assert!(
+        DomainAction::find_pending(Some(DomainActionTypes::SendCartReminder), connection)
+            .unwrap()
+            .is_empty()
+    );
+
+    // Only one reminder waits for the cart at a time
+    cart.schedule_cart_reminder(Duration::minutes(60), connection)
+        .unwrap();
+    let reminder_count: i64 = domain_actions::table
+        .filter(domain_actions::domain_action_type.eq(DomainActionTypes::SendCartReminder))
+        .filter(domain_actions::main_table_id.eq(cart.id))
+        .count()
+        .get_result(connection)
+        .unwrap();
+    assert_eq!(reminder_count, 1);
+
+    // Carts without tickets are not reminded
+    let user2 = project.create_user().finish();
+    let mut cart2 = Order::find_or_create_cart(&user2, connection).unwrap();
+    cart2
+        .update_quantities(
+            &[UpdateOrderItem {
+                ticket_type_id: ticket_type.id,
+                quantity: 1,
+                redemption_code: None,
+                seat_ids: None,
+            }],
+            false,
+            false,
+            connection,
+        )
+        .unwrap();
+    cart2.clear_cart(connection).unwrap();
+    let cart2 = Order::find(cart2.id, connection).unwrap();
+    cart2
+        .schedule_cart_reminder(Duration::minutes(60), connection)
+        .unwrap();
+    assert!(!DomainAction::has_pending_action(
+        DomainActionTypes::SendCartReminder,
+        Tables::Orders.table_name(),
+        cart2.id,
+        connection
+    )
+    .unwrap());
+}
 
+#[test]
+fn superseded_by_paid_order() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
     cart.update_quantities(
         &[UpdateOrderItem {
-            ticket_type_id: ticket.id,
-            quantity: 30,
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
-        conn,
+        connection,
     )
     .unwrap();
-    let total = cart.calculate_total(conn).unwrap();
-    assert_eq!(total, 5100);
+    let one_minute_ago = NaiveDateTime::from(Utc::now().naive_utc() - Duration::minutes(1));
+    let cart = diesel::update(orders::table.filter(orders::id.eq(cart.id)))
+        .set((
+            orders::expires_at.eq(one_minute_ago),
+            orders::created_at.eq(one_minute_ago),
+        ))
+        .get_result::<Order>(connection)
+        .unwrap();
+    assert!(!cart.superseded_by_paid_order(connection).unwrap());
+
+    // A newer cart that has not been paid for
+    let user = User::find(user.id, connection).unwrap();
+    let mut new_cart = Order::find_or_create_cart(&user, connection).unwrap();
+    new_cart
+        .update_quantities(
+            &[UpdateOrderItem {
+                ticket_type_id: ticket_type.id,
+                quantity: 1,
+                redemption_code: None,
+                seat_ids: None,
+            }],
+            false,
+            false,
+            connection,
+        )
+        .unwrap();
+    assert!(!cart.superseded_by_paid_order(connection).unwrap());
+
+    let total = new_cart.calculate_total(connection).unwrap();
+    new_cart
+        .add_external_payment(Some("test".to_string()), user.id, total, connection)
+        .unwrap();
+    assert!(cart.superseded_by_paid_order(connection).unwrap());
+    assert!(!new_cart.superseded_by_paid_order(connection).unwrap());
 }
 
 #[test]
-fn add_external_payment() {
+fn restore_to_cart() {
     let project = TestProject::new();
+    let connection = project.get_connection();
     let user = project.create_user().finish();
-    let event = project
-        .create_event()
-        .with_tickets()
-        .with_ticket_pricing()
-        .finish();
-    let conn = project.get_connection();
-    let mut cart = Order::find_or_create_cart(&user, conn).unwrap();
-    let ticket = &event.ticket_types(conn).unwrap()[0];
+    let user2 = project.create_user().finish();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
     cart.update_quantities(
         &[UpdateOrderItem {
-            ticket_type_id: ticket.id,
-            quantity: 10,
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
-        conn,
+        connection,
     )
     .unwrap();
-    assert_eq!(cart.calculate_total(conn).unwrap(), 2000);
-    assert!(cart.paid_at.is_none());
 
-    // Partially paid
-    cart.add_external_payment(Some("test".to_string()), user.id, 1500, conn)
-        .unwrap();
-    assert_eq!(cart.status, OrderStatus::PartiallyPaid);
-    assert!(cart.paid_at.is_none());
+    // Restoring an active cart returns it unchanged
+    let restored = cart.restore_to_cart(&user, connection).unwrap();
+    assert_eq!(restored.id, cart.id);
 
-    // Fully paid
-    cart.add_external_payment(Some("test2".to_string()), user.id, 500, conn)
+    let one_minute_ago = NaiveDateTime::from(Utc::now().naive_utc() - Duration::minutes(1));
+    let cart = diesel::update(orders::table.filter(orders::id.eq(cart.id)))
+        .set(orders::expires_at.eq(one_minute_ago))
+        .get_result::<Order>(connection)
         .unwrap();
-    assert_eq!(cart.status, OrderStatus::Paid);
-    assert!(cart.paid_at.is_some());
+    assert!(cart.restore_to_cart(&user2, connection).is_err());
+
+    let restored = cart.restore_to_cart(&user, connection).unwrap();
+    assert_ne!(restored.id, cart.id);
+    let items: Vec<OrderItem> = restored
+        .items(connection)
+        .unwrap()
+        .into_iter()
+        .filter(|i| i.item_type == OrderItemTypes::Tickets)
+        .collect();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].ticket_type_id, Some(ticket_type.id));
+    assert_eq!(items[0].quantity, 2);
+    assert!(restored.expires_at.unwrap() > Utc::now().naive_utc());
 }
 
 #[test]
-fn find_for_user_for_display() {
+fn restore_to_cart_with_seats() {
     let project = TestProject::new();
-    let user = project.create_user().finish();
-    let mut order1 = project.create_order().for_user(&user).finish();
-    order1
-        .add_external_payment(
-            Some("test".to_string()),
-            user.id,
-            2000,
-            project.get_connection(),
+    let connection = project.get_connection();
+    let venue = project.create_venue().finish();
+    let event = project
+        .create_event()
+        .with_venue(&venue)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let seat_section = project
+        .create_seat_section()
+        .with_venue_id(venue.id)
+        .with_seats(3)
+        .finish();
+    let seat_ids: Vec<Uuid> = seat_section
+        .seats(connection)
+        .unwrap()
+        .iter()
+        .map(|s| s.id)
+        .collect();
+    ticket_type.assign_seats(&seat_ids, connection).unwrap();
+
+    let user = project.create_user().finish();
+    let expired_cart = |seat_ids: &[Uuid]| {
+        let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+        cart.update_quantities(
+            &[UpdateOrderItem {
+                ticket_type_id: ticket_type.id,
+                quantity: seat_ids.len() as u32,
+                redemption_code: None,
+                seat_ids: Some(seat_ids.to_vec()),
+            }],
+            false,
+            false,
+            connection,
         )
         .unwrap();
-    let mut order2 = project.create_order().for_user(&user).finish();
-    order2
-        .add_external_payment(
-            Some("test".to_string()),
-            user.id,
-            500,
-            project.get_connection(),
+        let one_minute_ago = NaiveDateTime::from(Utc::now().naive_utc() - Duration::minutes(1));
+        let order_item_ids: Vec<Uuid> = cart
+            .items(connection)
+            .unwrap()
+            .iter()
+            .map(|i| i.id)
+            .collect();
+        diesel::update(
+            ticket_instances::table.filter(ticket_instances::order_item_id.eq_any(order_item_ids)),
         )
+        .set(ticket_instances::reserved_until.eq(one_minute_ago))
+        .execute(connection)
         .unwrap();
+        diesel::update(orders::table.filter(orders::id.eq(cart.id)))
+            .set(orders::expires_at.eq(one_minute_ago))
+            .get_result::<Order>(connection)
+            .unwrap()
+    };
 
-    assert_eq!(order1.status, OrderStatus::Paid);
-    assert_eq!(order2.status, OrderStatus::PartiallyPaid);
-
-    let display_orders =
-        Order::find_for_user_for_display(user.id, project.get_connection()).unwrap();
-    let ids: Vec<Uuid> = display_orders.iter().map(|o| o.id).collect();
-    //The order of the ids is not certain so this test fails from time to time.
-    //It is ordered by updated_at which is the same for the two orders
-
-    assert!(
-        (order1.id == ids[0] && order2.id == ids[1])
-            || (order1.id == ids[1] && order2.id == ids[0])
-    );
+    let cart = expired_cart(&seat_ids[0..2]);
+    let restored = cart.restore_to_cart(&user, connection).unwrap();
+    let items: Vec<OrderItem> = restored
+        .items(connection)
+        .unwrap()
+        .into_iter()
+        .filter(|i| i.item_type == OrderItemTypes::Tickets)
+        .collect();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].quantity, 2);
+    let mut restored_seat_ids: Vec<Uuid> =
+        TicketInstance::find_for_order_item(items[0].id, connection)
+            .unwrap()
+            .iter()
+            .map(|t| t.seat_id.unwrap())
+            .collect();
+    restored_seat_ids.sort();
+    let mut expected_seat_ids = seat_ids[0..2].to_vec();
+    expected_seat_ids.sort();
+    assert_eq!(restored_seat_ids, expected_seat_ids);
+
+    // Seats reserved by someone else since the cart expired cannot be restored
+    restored.clear_cart(connection).unwrap();
+    let cart = expired_cart(&seat_ids[1..3]);
+    let user2 = project.create_user().finish();
+    let mut cart2 = Order::find_or_create_cart(&user2, connection).unwrap();
+    cart2
+        .update_quantities(
+            &[UpdateOrderItem {
+                ticket_type_id: ticket_type.id,
+                quantity: 1,
+                redemption_code: None,
+                seat_ids: Some(vec![seat_ids[2]]),
+            }],
+            false,
+            false,
+            connection,
+        )
+        .unwrap();
+    match cart.restore_to_cart(&user, connection) {
+        Ok(_) => panic!("Expected validation error"),
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("seat_ids"));
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
 }
 
 #[test]
-fn for_display() {
+fn has_items() {
     let project = TestProject::new();
     let connection = project.get_connection();
-    let order = project.create_order().finish();
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
 
-    // 1 minute from now expires
-    let one_minute_from_now = NaiveDateTime::from(Utc::now().naive_utc() + Duration::minutes(1));
-    let mut order = diesel::update(orders::table.filter(orders::id.eq(order.id)))
-        .set(orders::expires_at.eq(one_minute_from_now))
-        .get_result::<Order>(connection)
-        .unwrap();
-    let display_order = order.for_display(connection).unwrap();
-    // Check both 59 and 60 for the purposes of the test to avoid timing errors
-    assert!(vec![59, 60].contains(&display_order.seconds_until_expiry.unwrap()));
+    // Without items
+    assert!(!cart.has_items(connection).unwrap());
 
-    // No expiration
-    order.remove_expiry(connection).unwrap();
-    let display_order = order.for_display(connection).unwrap();
-    assert_eq!(None, display_order.seconds_until_expiry);
+    // With items
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 10,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    assert!(cart.has_items(connection).unwrap());
+}
 
-    // 1 minute ago expires
-    let one_minute_ago = NaiveDateTime::from(Utc::now().naive_utc() - Duration::minutes(1));
-    let order = diesel::update(orders::table.filter(orders::id.eq(order.id)))
-        .set(orders::expires_at.eq(one_minute_ago))
-        .get_result::<Order>(connection)
-        .unwrap();
-    let display_order = order.for_display(connection).unwrap();
-    assert_eq!(Some(0), display_order.seconds_until_expiry);
+#[test]
+fn destroy() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let cart = Order::find_or_create_cart(&user, connection).unwrap();
+    let cart_result = Order::find_cart_for_user(user.id, connection).unwrap();
+    assert_eq!(cart_result.unwrap(), cart);
+
+    cart.destroy(connection).unwrap();
+    let cart_result = Order::find_cart_for_user(user.id, connection).unwrap();
+    assert!(cart_result.is_none());
 }
 
 #[test]
-fn adding_event_fees() {
+fn calculate_cart_total() {
     let project = TestProject::new();
     let creator = project.create_user().finish();
 
-    let connection = project.get_connection();
     let organization = project
         .create_organization()
         .with_fee_schedule(&project.create_fee_schedule().finish(creator.id))
-        .with_event_fee()
-        .finish();
-    let event1 = project
-        .create_event()
-        .with_organization(&organization)
-        .with_tickets()
-        .with_ticket_pricing()
-        .finish();
-    let event2 = project
-        .create_event()
-        .with_organization(&organization)
-        .with_tickets()
-        .with_ticket_pricing()
         .finish();
-    let event3 = project
+    let event = project
         .create_event()
         .with_organization(&organization)
         .with_tickets()
         .with_ticket_pricing()
         .finish();
-    let organization2 = project
-        .create_organization()
-        .with_fee_schedule(&project.create_fee_schedule().finish(creator.id))
-        .with_event_fee()
-        .finish();
-    let event4 = project
-        .create_event()
-        .with_organization(&organization2)
-        .with_tickets()
-        .with_ticket_pricing()
-        .finish();
     let user = project.create_user().finish();
-    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
-    let ticket1 = &event1.ticket_types(connection).unwrap()[0];
-    let ticket2 = &event2.ticket_types(connection).unwrap()[0];
-    let ticket3 = &event3.ticket_types(connection).unwrap()[0];
-    let ticket4 = &event4.ticket_types(connection).unwrap()[0];
+    let conn = project.get_connection();
+    let mut cart = Order::find_or_create_cart(&user, conn).unwrap();
+    let ticket = &event.ticket_types(conn).unwrap()[0];
     cart.update_quantities(
         &[UpdateOrderItem {
-            ticket_type_id: ticket1.id,
+            ticket_type_id: ticket.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
-        connection,
+        conn,
     )
     .unwrap();
 
-    let order_items = OrderItem::find_for_order(cart.id, connection).unwrap();
-
-    let mut event_fees_count = 0;
-    for o in &order_items {
-        if o.item_type == OrderItemTypes::EventFees {
-            event_fees_count += 1;
-        }
-    }
-    assert_eq!(event_fees_count, 1);
+    let total = cart.calculate_total(conn).unwrap();
+    assert_eq!(total, 1700);
 
-    //Add some more of the same event and some of a second event
     cart.update_quantities(
         &[UpdateOrderItem {
-            ticket_type_id: ticket1.id,
-            quantity: 15,
+            ticket_type_id: ticket.id,
+            quantity: 30,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
-        connection,
+        conn,
     )
     .unwrap();
+    let total = cart.calculate_total(conn).unwrap();
+    assert_eq!(total, 5100);
+}
+
+#[test]
+fn add_external_payment() {
+    let project = TestProject::new();
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let conn = project.get_connection();
+    let mut cart = Order::find_or_create_cart(&user, conn).unwrap();
+    let ticket = &event.ticket_types(conn).unwrap()[0];
     cart.update_quantities(
         &[UpdateOrderItem {
-            ticket_type_id: ticket2.id,
-            quantity: 5,
+            ticket_type_id: ticket.id,
+            quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
-        connection,
+        conn,
     )
     .unwrap();
-    let order_items = OrderItem::find_for_order(cart.id, connection).unwrap();
-
-    let mut event_fees_count = 0;
+    assert_eq!(cart.calculate_total(conn).unwrap(), 2000);
+    assert!(cart.paid_at.is_none());
 
-    for o in &order_items {
-        if o.item_type == OrderItemTypes::EventFees {
-            event_fees_count += 1;
-        }
-    }
-    assert_eq!(event_fees_count, 2);
+    // Partially paid
+    cart.add_external_payment(Some("test".to_string()), user.id, 1500, conn)
+        .unwrap();
+    assert_eq!(cart.status, OrderStatus::PartiallyPaid);
+    assert!(cart.paid_at.is_none());
 
-    //Add tickets with null event fee and null organization event_fee
+    // Fully paid
+    cart.add_external_payment(Some("test2".to_string()), user.id, 500, conn)
+        .unwrap();
+    assert_eq!(cart.status, OrderStatus::Paid);
+    assert!(cart.paid_at.is_some());
+}
 
+#[test]
+fn add_credit_payment() {
+    let project = TestProject::new();
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let conn = project.get_connection();
+    let mut cart = Order::find_or_create_cart(&user, conn).unwrap();
+    let ticket = &event.ticket_types(conn).unwrap()[0];
     cart.update_quantities(
         &[UpdateOrderItem {
-            ticket_type_id: ticket3.id,
-            quantity: 5,
+            ticket_type_id: ticket.id,
+            quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
-        connection,
+        conn,
     )
     .unwrap();
-    let order_items = OrderItem::find_for_order(cart.id, connection).unwrap();
+    assert_eq!(cart.calculate_total(conn).unwrap(), 2000);
+    CreditTransaction::create(
+        user.id,
+        CreditTransactionTypes::GiftCard,
+        1500,
+        None,
+        None,
+        None,
+    )
+    .commit(conn)
+    .unwrap();
 
-    let mut event_fees_count = 0;
-    for o in &order_items {
-        if o.item_type == OrderItemTypes::EventFees {
-            event_fees_count += 1;
-        }
-    }
-    assert_eq!(event_fees_count, 3);
+    // Not enough credit to cover the order
+    assert!(cart.add_credit_payment(user.id, 2000, conn).is_err());
 
-    //Add tickets with null event fee and but default organization event_fee
+    // Another user's credit cannot be used
+    let other_user = project.create_user().finish();
+    assert!(cart.add_credit_payment(other_user.id, 500, conn).is_err());
 
-    cart.update_quantities(
-        &[UpdateOrderItem {
-            ticket_type_id: ticket4.id,
-            quantity: 5,
+    // Credit covers part of the order leaving it partially paid
+    let payment = cart.add_credit_payment(user.id, 1500, conn).unwrap();
+    assert_eq!(payment.payment_method, PaymentMethods::Credit);
+    assert_eq!(payment.amount, 1500);
+    assert_eq!(cart.status, OrderStatus::PartiallyPaid);
+    assert_eq!(
+        CreditTransaction::balance_for_user(user.id, conn).unwrap(),
+        0
+    );
+
+    // Remainder paid with another method
+    cart.add_external_payment(Some("test".to_string()), user.id, 500, conn)
+        .unwrap();
+    assert_eq!(cart.status, OrderStatus::Paid);
+}
+
+#[test]
+fn find_or_create_cart_returns_credit_of_partially_paid_cart() {
+    let project = TestProject::new();
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let conn = project.get_connection();
+    let mut cart = Order::find_or_create_cart(&user, conn).unwrap();
+    let ticket = &event.ticket_types(conn).unwrap()[0];
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket.id,
+            quantity: 10,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        conn,
+    )
+    .unwrap();
+    CreditTransaction::create(
+        user.id,
+        CreditTransactionTypes::GiftCard,
+        1500,
+        None,
+        None,
+        None,
+    )
+    .commit(conn)
+    .unwrap();
+    cart.add_credit_payment(user.id, 1500, conn).unwrap();
+    assert_eq!(cart.status, OrderStatus::PartiallyPaid);
+    assert!(DomainAction::has_pending_action(
+        DomainActionTypes::ReturnCartCredit,
+        Tables::Orders.table_name(),
+        cart.id,
+        conn
+    )
+    .unwrap());
+
+    // Changing the cart keeps it as the user's cart and returns the credit until checkout
+    let user = User::find(user.id, conn).unwrap();
+    let mut found_cart = Order::find_or_create_cart(&user, conn).unwrap();
+    assert_eq!(found_cart.id, cart.id);
+    assert_eq!(found_cart.status, OrderStatus::Draft);
+    assert_eq!(found_cart.total_paid(conn).unwrap(), 0);
+    assert_eq!(
+        CreditTransaction::balance_for_user(user.id, conn).unwrap(),
+        1500
+    );
+
+    // Paying again only counts the new payments
+    found_cart.add_credit_payment(user.id, 1500, conn).unwrap();
+    assert_eq!(found_cart.status, OrderStatus::PartiallyPaid);
+    found_cart
+        .add_external_payment(Some("test".to_string()), user.id, 500, conn)
+        .unwrap();
+    assert_eq!(found_cart.status, OrderStatus::Paid);
+    assert_eq!(
+        CreditTransaction::balance_for_user(user.id, conn).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn return_credit_payments() {
+    let project = TestProject::new();
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let conn = project.get_connection();
+    let mut cart = Order::find_or_create_cart(&user, conn).unwrap();
+    let ticket = &event.ticket_types(conn).unwrap()[0];
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket.id,
+            quantity: 10,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        conn,
+    )
+    .unwrap();
+    CreditTransaction::create(
+        user.id,
+        CreditTransactionTypes::GiftCard,
+        1000,
+        None,
+        None,
+        None,
+    )
+    .commit(conn)
+    .unwrap();
+
+    // Nothing to return before any credit is paid
+    assert!(cart.return_credit_payments(conn).is_err());
+
+    cart.add_credit_payment(user.id, 1000, conn).unwrap();
+    assert!(cart.paid_with_credit_only(conn).unwrap());
+    assert_eq!(
+        CreditTransaction::balance_for_user(user.id, conn).unwrap(),
+        0
+    );
+
+    // The cart expired without being checked out
+    cart.return_credit_payments(conn).unwrap();
+    assert_eq!(cart.status, OrderStatus::Draft);
+    assert_eq!(cart.total_paid(conn).unwrap(), 0);
+    assert_eq!(
+        CreditTransaction::balance_for_user(user.id, conn).unwrap(),
+        1000
+    );
+    let refunds: Vec<Payment> = cart
+        .payments(conn)
+        .unwrap()
+        .into_iter()
+        .filter(|p| p.status == PaymentStatus::Refunded)
+        .collect();
+    assert_eq!(refunds.len(), 1);
+    assert_eq!(refunds[0].amount, -1000);
+
+    // Carts partly paid with other methods keep their payments
+    cart.add_external_payment(Some("test".to_string()), user.id, 500, conn)
+        .unwrap();
+    assert_eq!(cart.status, OrderStatus::PartiallyPaid);
+    assert!(!cart.paid_with_credit_only(conn).unwrap());
+    assert!(cart.return_credit_payments(conn).is_err());
+}
+
+#[test]
+fn update_gift_cards() {
+    let project = TestProject::new();
+    let user = project.create_user().finish();
+    let conn = project.get_connection();
+    let mut cart = Order::find_or_create_cart(&user, conn).unwrap();
+    assert!(cart.expires_at.is_none());
+
+    // Value outside of the allowed range
+    let result = cart.update_gift_cards(
+        &[UpdateGiftCardItem {
+            value_in_cents: 100,
+            quantity: 1,
+        }],
+        conn,
+    );
+    match result {
+        Ok(_) => {
+            panic!("Expected validation error");
+        }
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("value_in_cents"));
+                assert_eq!(errors["value_in_cents"][0].code, "value_in_cents_too_small");
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+
+    cart.update_gift_cards(
+        &[UpdateGiftCardItem {
+            value_in_cents: 2500,
+            quantity: 2,
+        }],
+        conn,
+    )
+    .unwrap();
+    assert!(cart.expires_at.is_some());
+    assert_eq!(cart.calculate_total(conn).unwrap(), 5000);
+    let items = cart.items(conn).unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].item_type, OrderItemTypes::GiftCard);
+    assert_eq!(items[0].quantity, 2);
+
+    // Gift cards are issued once the order is paid
+    cart.add_external_payment(Some("test".to_string()), user.id, 5000, conn)
+        .unwrap();
+    assert_eq!(cart.status, OrderStatus::Paid);
+    let gift_cards = GiftCard::find_for_order(cart.id, conn).unwrap();
+    assert_eq!(gift_cards.len(), 2);
+    assert!(gift_cards.iter().all(|g| g.value_in_cents == 2500));
+    assert_ne!(gift_cards[0].redemption_code, gift_cards[1].redemption_code);
+
+    // Gift cards cannot be refunded
+    let refund_items = vec![RefundItem {
+        order_item_id: items[0].id,
+        ticket_instance_id: None,
+    }];
+    assert!(cart.refund(refund_items, conn).is_err());
+}
+
+#[test]
+fn exchange() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let creator = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(creator.id))
+        .finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let vip_ticket_type = event
+        .add_ticket_type(
+            "VIP".to_string(),
+            None,
+            10,
+            Utc::now().naive_utc() - Duration::days(1),
+            Utc::now().naive_utc() + Duration::days(2),
+            event.issuer_wallet(connection).unwrap().id,
+            None,
+            0,
+            300,
+            connection,
+        )
+        .unwrap();
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
         connection,
     )
     .unwrap();
-    let order_items = OrderItem::find_for_order(cart.id, connection).unwrap();
 
-    let mut event_fees_count = 0;
-    for o in &order_items {
-        if o.item_type == OrderItemTypes::EventFees {
-            event_fees_count += 1;
+    // Only paid orders can be exchanged
+    let items = cart.items(connection).unwrap();
+    let order_item = items
+        .iter()
+        .find(|i| i.ticket_type_id == Some(ticket_type.id))
+        .unwrap();
+    let tickets = TicketInstance::find_for_order_item(order_item.id, connection).unwrap();
+    assert!(cart
+        .exchange(&[tickets[0].id], vip_ticket_type.id, user.id, connection)
+        .is_err());
+
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(Some("Test".to_string()), user.id, total, connection)
+        .unwrap();
+    let mut order = Order::find(cart.id, connection).unwrap();
+    assert_eq!(order.status, OrderStatus::Paid);
+
+    // Tickets must be exchanged for a different ticket type
+    assert!(order
+        .exchange(&[tickets[0].id], ticket_type.id, user.id, connection)
+        .is_err());
+
+    let exchange = order
+        .exchange(&[tickets[0].id], vip_ticket_type.id, user.id, connection)
+        .unwrap();
+    assert_eq!(exchange.order_id, order.id);
+    assert_eq!(exchange.from_order_item_id, order_item.id);
+    assert_eq!(exchange.quantity, 1);
+    assert_eq!(exchange.created_by, user.id);
+    assert!(exchange.difference_in_cents > 0);
+    assert_eq!(
+        exchange.difference_in_cents,
+        order.calculate_total(connection).unwrap() - total
+    );
+    assert_eq!(order.exchanges(connection).unwrap(), vec![exchange.clone()]);
+
+    let old_ticket = TicketInstance::find(tickets[0].id, connection).unwrap();
+    assert_eq!(old_ticket.status, TicketInstanceStatus::Nullified);
+    assert_eq!(
+        OrderItem::find(order_item.id, connection)
+            .unwrap()
+            .refunded_quantity,
+        1
+    );
+    let new_tickets =
+        TicketInstance::find_for_order_item(exchange.to_order_item_id, connection).unwrap();
+    assert_eq!(new_tickets.len(), 1);
+    assert_eq!(new_tickets[0].status, TicketInstanceStatus::Purchased);
+    assert_eq!(
+        new_tickets[0].wallet_id,
+        Wallet::find_default_for_user(user.id, connection)
+            .unwrap()
+            .id
+    );
+
+    // Both legs show up in the order details and transaction report
+    let details = order.details(vec![organization.id], connection).unwrap();
+    assert!(details
+        .iter()
+        .any(|d| d.ticket_instance_id == Some(tickets[0].id) && d.status == "Exchanged"));
+    assert!(details
+        .iter()
+        .any(|d| d.ticket_instance_id == Some(new_tickets[0].id) && d.status == "Purchased"));
+    let rows =
+        Report::transaction_detail_report(Some(event.id), None, None, None, connection).unwrap();
+    assert!(rows
+        .iter()
+        .any(|r| r.order_exchange_id == Some(exchange.id) && r.quantity == 1));
+    assert!(rows.iter().any(|r| r.order_exchange_id.is_none()
+        && r.exchanged_quantity == 1
+        && r.refunded_quantity == 0));
+
+    // Payloads of the old ticket are revoked for offline scanning
+    let revoked_tickets = EventSigningKey::find_or_create_for_event(event.id, connection)
+        .unwrap()
+        .revoked_tickets(connection)
+        .unwrap();
+    assert!(revoked_tickets
+        .iter()
+        .any(|r| r.ticket_instance_id == tickets[0].id));
+
+    // The exchange shows up in the fan's history
+    let history = user
+        .get_history_for_organization(&organization, 0, 100, SortingDir::Desc, connection)
+        .unwrap();
+    assert_eq!(history.paging.total, 2);
+    assert!(history.data.contains(&HistoryItem::Exchange {
+        order_id: order.id,
+        exchange_date: exchange.created_at,
+        event_name: event.name.clone(),
+        from_ticket_type_name: ticket_type.name.clone(),
+        to_ticket_type_name: vip_ticket_type.name.clone(),
+        quantity: 1,
+        difference_in_cents: exchange.difference_in_cents,
+    }));
+
+    // Exchanged tickets cannot be exchanged again
+    assert!(order
+        .exchange(&[tickets[0].id], vip_ticket_type.id, user.id, connection)
+        .is_err());
+}
+
+#[test]
+fn split() {
+    let project = TestProject::new();
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let conn = project.get_connection();
+    let mut cart = Order::find_or_create_cart(&user, conn).unwrap();
+    let ticket = &event.ticket_types(conn).unwrap()[0];
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket.id,
+            quantity: 3,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        conn,
+    )
+    .unwrap();
+    let total = cart.calculate_total(conn).unwrap();
+
+    // Share quantities must cover every ticket
+    match cart.split(&[1, 1], conn) {
+        Ok(_) => {
+            panic!("Expected validation error");
+        }
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("ticket_quantities"));
+                assert_eq!(
+                    errors["ticket_quantities"][0].code,
+                    "ticket_quantities_mismatch"
+                );
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+
+    // At least two shares are needed
+    match cart.split(&[3], conn) {
+        Ok(_) => {
+            panic!("Expected validation error");
         }
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("ticket_quantities"));
+                assert_eq!(
+                    errors["ticket_quantities"][0].code,
+                    "ticket_quantities_too_few_shares"
+                );
+            }
+            _ => panic!("Expected validation error"),
+        },
     }
-    assert_eq!(event_fees_count, 4);
+    assert_eq!(cart.status, OrderStatus::Draft);
+
+    let shares = cart.split(&[1, 2], conn).unwrap();
+    assert_eq!(shares.len(), 2);
+    assert_eq!(shares[0].ticket_quantity, 1);
+    assert_eq!(shares[1].ticket_quantity, 2);
+    assert_eq!(shares[1].amount_in_cents, total * 2 / 3);
+    assert_eq!(shares[0].amount_in_cents + shares[1].amount_in_cents, total);
+    assert_eq!(cart.status, OrderStatus::PartiallyPaid);
+    assert_eq!(cart.shares(conn).unwrap(), shares);
+
+    // Shares expire with the extended payment window rather than the cart
+    let expires_at = Order::find(cart.id, conn).unwrap().expires_at.unwrap();
+    assert!(expires_at > Utc::now().naive_utc() + Duration::hours(23));
+    assert_eq!(cart.expires_at, Some(expires_at));
+    let expire_action: DomainAction = domain_actions::table
+        .filter(domain_actions::domain_action_type.eq(DomainActionTypes::ExpireOrderShares))
+        .filter(domain_actions::main_table_id.eq(cart.id))
+        .first(conn)
+        .unwrap();
+    assert_eq!(expire_action.status, DomainActionStatus::Pending);
+    assert_eq!(expire_action.scheduled_at, expires_at);
+
+    // Orders can only be split once
+    assert!(cart.split(&[1, 2], conn).is_err());
 }
 
 #[test]
-pub fn update() {
+fn split_allocates_tickets_to_payers() {
     let project = TestProject::new();
-    let order = project.create_order().finish();
+    let user = project.create_user().finish();
+    let user2 = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let conn = project.get_connection();
+    let mut cart = Order::find_or_create_cart(&user, conn).unwrap();
+    let ticket = &event.ticket_types(conn).unwrap()[0];
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket.id,
+            quantity: 3,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        conn,
+    )
+    .unwrap();
+    let shares = cart.split(&[1, 2], conn).unwrap();
+
+    for (share, payer) in shares.iter().zip(&[&user, &user2]) {
+        let payment = cart
+            .add_credit_card_payment(
+                payer.id,
+                share.amount_in_cents,
+                "test".to_string(),
+                None,
+                share.share_key.to_string(),
+                PaymentStatus::Authorized,
+                json!(null),
+                conn,
+            )
+            .unwrap();
+        share.mark_paid(payer.id, payment.id, conn).unwrap();
+        payment.mark_complete(json!(null), payer.id, conn).unwrap();
+        cart = Order::find(cart.id, conn).unwrap();
+    }
+    assert_eq!(cart.status, OrderStatus::Paid);
 
-    let attrs = UpdateOrderAttributes {
-        note: Some(Some("Client will pick up at 18h00".to_string())),
-    };
-    let updated = order.update(attrs, &project.connection).unwrap();
+    let user_wallet = Wallet::find_default_for_user(user.id, conn).unwrap();
+    let user2_wallet = Wallet::find_default_for_user(user2.id, conn).unwrap();
+    let order_item = cart
+        .items(conn)
+        .unwrap()
+        .into_iter()
+        .find(|i| i.item_type == OrderItemTypes::Tickets)
+        .unwrap();
+    let tickets = TicketInstance::find_for_order_item(order_item.id, conn).unwrap();
     assert_eq!(
-        updated.note,
-        Some("Client will pick up at 18h00".to_string())
+        tickets
+            .iter()
+            .filter(|t| t.wallet_id == user_wallet.id)
+            .count(),
+        1
+    );
+    assert_eq!(
+        tickets
+            .iter()
+            .filter(|t| t.wallet_id == user2_wallet.id)
+            .count(),
+        2
+    );
+
+    // Tickets allocated to a payer are theirs to refund, and the refund goes to their payment
+    let user2_ticket = tickets
+        .iter()
+        .find(|t| t.wallet_id == user2_wallet.id)
+        .unwrap();
+    assert!(!user2_ticket.was_transferred(conn).unwrap());
+    let user2_share = OrderShare::find_by_share_key(shares[1].share_key, conn).unwrap();
+    assert_eq!(
+        cart.share_payment_ids_for_tickets(&[user2_ticket.id], conn)
+            .unwrap(),
+        vec![user2_share.payment_id.unwrap()]
+    );
+    assert_eq!(
+        cart.refundable_ticket_items(event.id, None, conn)
+            .unwrap()
+            .len(),
+        3
+    );
+}
+
+#[test]
+fn cancel_unpaid_split() {
+    let project = TestProject::new();
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let conn = project.get_connection();
+    let mut cart = Order::find_or_create_cart(&user, conn).unwrap();
+    let ticket = &event.ticket_types(conn).unwrap()[0];
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket.id,
+            quantity: 2,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        conn,
+    )
+    .unwrap();
+
+    // Orders that were not split cannot be cancelled this way
+    assert!(cart.cancel_unpaid_split(conn).is_err());
+
+    cart.split(&[1, 1], conn).unwrap();
+    cart.cancel_unpaid_split(conn).unwrap();
+    assert_eq!(cart.status, OrderStatus::Cancelled);
+}
+
+#[test]
+fn find_for_user_for_display() {
+    let project = TestProject::new();
+    let user = project.create_user().finish();
+    let mut order1 = project.create_order().for_user(&user).finish();
+    order1
+        .add_external_payment(
+            Some("test".to_string()),
+            user.id,
+            2000,
+            project.get_connection(),
+        )
+        .unwrap();
+    let mut order2 = project.create_order().for_user(&user).finish();
+    order2
+        .add_external_payment(
+            Some("test".to_string()),
+            user.id,
+            500,
+            project.get_connection(),
+        )
+        .unwrap();
+
+    assert_eq!(order1.status, OrderStatus::Paid);
+    assert_eq!(order2.status, OrderStatus::PartiallyPaid);
+
+    let display_orders =
+        Order::find_for_user_for_display(user.id, project.get_connection()).unwrap();
+    let ids: Vec<Uuid> = display_orders.iter().map(|o| o.id).collect();
+    //The order of the ids is not certain so this test fails from time to time.
+    //It is ordered by updated_at which is the same for the two orders
+
+    assert!(
+        (order1.id == ids[0] && order2.id == ids[1])
+            || (order1.id == ids[1] && order2.id == ids[0])
+    );
+}
+
+#[test]
+fn for_display() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let order = project.create_order().finish();
+
+    // 1 minute from now expires
+    let one_minute_from_now = NaiveDateTime::from(Utc::now().naive_utc() + Duration::minutes(1));
+    let mut order = diesel::update(orders::table.filter(orders::id.eq(order.id)))
+        .set(orders::expires_at.eq(one_minute_from_now))
+        .get_result::<Order>(connection)
+        .unwrap();
+    let display_order = order.for_display(connection).unwrap();
+    // Check both 59 and 60 for the purposes of the test to avoid timing errors
+    assert!(vec![59, 60].contains(&display_order.seconds_until_expiry.unwrap()));
+
+    // No expiration
+    order.remove_expiry(connection).unwrap();
+    let display_order = order.for_display(connection).unwrap();
+    assert_eq!(None, display_order.seconds_until_expiry);
+
+    // 1 minute ago expires
+    let one_minute_ago = NaiveDateTime::from(Utc::now().naive_utc() - Duration::minutes(1));
+    let order = diesel::update(orders::table.filter(orders::id.eq(order.id)))
+        .set(orders::expires_at.eq(one_minute_ago))
+        .get_result::<Order>(connection)
+        .unwrap();
+    let display_order = order.for_display(connection).unwrap();
+    assert_eq!(Some(0), display_order.seconds_until_expiry);
+}
+
+#[test]
+fn adding_event_fees() {
+    let project = TestProject::new();
+    let creator = project.create_user().finish();
+
+    let connection = project.get_connection();
+    let organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(creator.id))
+        .with_event_fee()
+        .finish();
+    let event1 = project
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let event2 = project
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let event3 = project
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let organization2 = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(creator.id))
+        .with_event_fee()
+        .finish();
+    let event4 = project
+        .create_event()
+        .with_organization(&organization2)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    let ticket1 = &event1.ticket_types(connection).unwrap()[0];
+    let ticket2 = &event2.ticket_types(connection).unwrap()[0];
+    let ticket3 = &event3.ticket_types(connection).unwrap()[0];
+    let ticket4 = &event4.ticket_types(connection).unwrap()[0];
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket1.id,
+            quantity: 10,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+
+    let order_items = OrderItem::find_for_order(cart.id, connection).unwrap();
+
+    let mut event_fees_count = 0;
+    for o in &order_items {
+        if o.item_type == OrderItemTypes::EventFees {
+            event_fees_count += 1;
+        }
+    }
+    assert_eq!(event_fees_count, 1);
+
+    //Add some more of the same event and some of a second event
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket1.id,
+            quantity: 15,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket2.id,
+            quantity: 5,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let order_items = OrderItem::find_for_order(cart.id, connection).unwrap();
+
+    let mut event_fees_count = 0;
+
+    for o in &order_items {
+        if o.item_type == OrderItemTypes::EventFees {
+            event_fees_count += 1;
+        }
+    }
+    assert_eq!(event_fees_count, 2);
+
+    //Add tickets with null event fee and null organization event_fee
+
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket3.id,
+            quantity: 5,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let order_items = OrderItem::find_for_order(cart.id, connection).unwrap();
+
+    let mut event_fees_count = 0;
+    for o in &order_items {
+        if o.item_type == OrderItemTypes::EventFees {
+            event_fees_count += 1;
+        }
+    }
+    assert_eq!(event_fees_count, 3);
+
+    //Add tickets with null event fee and but default organization event_fee
+
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket4.id,
+            quantity: 5,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let order_items = OrderItem::find_for_order(cart.id, connection).unwrap();
+
+    let mut event_fees_count = 0;
+    for o in &order_items {
+        if o.item_type == OrderItemTypes::EventFees {
+            event_fees_count += 1;
+        }
+    }
+    assert_eq!(event_fees_count, 4);
+}
+
+#[test]
+pub fn update() {
+    let project = TestProject::new();
+    let order = project.create_order().finish();
+
+    let attrs = UpdateOrderAttributes {
+        note: Some(Some("Client will pick up at 18h00".to_string())),
+    };
+    let updated = order.update(attrs, &project.connection).unwrap();
+    assert_eq!(
+        updated.note,
+        Some("Client will pick up at 18h00".to_string())
+    );
+}
+
+#[test]
+fn update_quantities_with_percentage_hold() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let ticket_pricing = ticket_type
+        .current_ticket_pricing(false, connection)
+        .unwrap();
+    let hold = project
+        .create_hold()
+        .with_ticket_type_id(ticket_type.id)
+        .with_discount_as_percentage(50)
+        .finish();
+    hold.set_quantity(10, connection).unwrap();
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: Some(hold.redemption_code.clone()),
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+
+    let items = cart.items(connection).unwrap();
+    let order_item = items
+        .iter()
+        .find(|i| i.item_type == OrderItemTypes::Tickets)
+        .unwrap();
+    assert_eq!(order_item.hold_id, Some(hold.id));
+    assert_eq!(
+        order_item.unit_price_in_cents,
+        ticket_pricing.price_in_cents / 2
+    );
+}
+
+#[test]
+fn update_quantities_with_code() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let ticket_pricing = ticket_type
+        .current_ticket_pricing(false, connection)
+        .unwrap();
+    let code = project
+        .create_code()
+        .with_event(&event)
+        .for_ticket_type(&ticket_type)
+        .with_discount_in_cents(Some(20))
+        .finish();
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: Some(code.redemption_code.clone()),
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+
+    let items = cart.items(connection).unwrap();
+    let order_item = items
+        .iter()
+        .find(|i| i.item_type == OrderItemTypes::Tickets)
+        .unwrap();
+    assert_eq!(order_item.code_id, Some(code.id));
+    assert_eq!(
+        order_item.unit_price_in_cents,
+        ticket_pricing.price_in_cents
+    );
+
+    let discount_item = order_item.find_discount_item(connection).unwrap().unwrap();
+    assert_eq!(discount_item.item_type, OrderItemTypes::Discount);
+    assert_eq!(discount_item.quantity, 2);
+    assert_eq!(discount_item.unit_price_in_cents, -20);
+
+    let total_without_discount: i64 = items
+        .iter()
+        .filter(|i| i.item_type != OrderItemTypes::Discount)
+        .map(|i| i.unit_price_in_cents * i.quantity)
+        .sum();
+    assert_eq!(
+        cart.calculate_total(connection).unwrap(),
+        total_without_discount - 40
+    );
+
+    // Codes only apply to their ticket types
+    let other_ticket_type = event
+        .add_ticket_type(
+            "Other".to_string(),
+            None,
+            10,
+            Utc::now().naive_utc(),
+            Utc::now().naive_utc() + Duration::days(2),
+            event.issuer_wallet(connection).unwrap().id,
+            None,
+            0,
+            100,
+            connection,
+        )
+        .unwrap();
+    let result = cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: other_ticket_type.id,
+            quantity: 1,
+            redemption_code: Some(code.redemption_code.clone()),
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    );
+    match result {
+        Ok(_) => panic!("Expected validation error"),
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("redemption_code"));
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+}
+
+#[test]
+fn update_quantities_with_buy_and_get_code() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let ticket_pricing = ticket_type
+        .current_ticket_pricing(false, connection)
+        .unwrap();
+    let code = project
+        .create_code()
+        .with_event(&event)
+        .for_ticket_type(&ticket_type)
+        .with_discount_as_percentage(Some(100))
+        .with_buy_and_get_quantity(2, 1)
+        .finish();
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 5,
+            redemption_code: Some(code.redemption_code.clone()),
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+
+    // One free ticket for every three bought
+    let items = cart.items(connection).unwrap();
+    let order_item = items
+        .iter()
+        .find(|i| i.item_type == OrderItemTypes::Tickets)
+        .unwrap();
+    let discount_item = order_item.find_discount_item(connection).unwrap().unwrap();
+    assert_eq!(discount_item.quantity, 1);
+    assert_eq!(
+        discount_item.unit_price_in_cents,
+        -ticket_pricing.price_in_cents
+    );
+
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 6,
+            redemption_code: Some(code.redemption_code.clone()),
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let discount_item = order_item.find_discount_item(connection).unwrap().unwrap();
+    assert_eq!(discount_item.quantity, 2);
+}
+
+#[test]
+fn update_quantities_with_minimum_order_total_code() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let ticket_pricing = ticket_type
+        .current_ticket_pricing(false, connection)
+        .unwrap();
+    let code = project
+        .create_code()
+        .with_event(&event)
+        .for_ticket_type(&ticket_type)
+        .with_discount_as_percentage(Some(10))
+        .with_minimum_order_total_in_cents(ticket_pricing.price_in_cents as u32 * 3)
+        .finish();
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: Some(code.redemption_code.clone()),
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let order_item = cart
+        .items(connection)
+        .unwrap()
+        .into_iter()
+        .find(|i| i.item_type == OrderItemTypes::Tickets)
+        .unwrap();
+    assert!(order_item.find_discount_item(connection).unwrap().is_none());
+
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 3,
+            redemption_code: Some(code.redemption_code.clone()),
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let discount_item = order_item.find_discount_item(connection).unwrap().unwrap();
+    assert_eq!(discount_item.quantity, 3);
+    assert_eq!(
+        discount_item.unit_price_in_cents,
+        -(ticket_pricing.price_in_cents / 10)
+    );
+}
+
+#[test]
+fn update_quantities_with_minimum_order_total_code_counts_whole_order() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_ticket_type_count(2)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_types = event.ticket_types(connection).unwrap();
+    let ticket_type = &ticket_types[0];
+    let other_ticket_type = &ticket_types[1];
+    let ticket_pricing = ticket_type
+        .current_ticket_pricing(false, connection)
+        .unwrap();
+    let other_ticket_pricing = other_ticket_type
+        .current_ticket_pricing(false, connection)
+        .unwrap();
+    let code = project
+        .create_code()
+        .with_event(&event)
+        .for_ticket_type(&ticket_type)
+        .with_discount_as_percentage(Some(10))
+        .with_minimum_order_total_in_cents(
+            (ticket_pricing.price_in_cents * 2 + other_ticket_pricing.price_in_cents) as u32,
+        )
+        .finish();
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        &[
+            UpdateOrderItem {
+                ticket_type_id: ticket_type.id,
+                quantity: 2,
+                redemption_code: Some(code.redemption_code.clone()),
+                seat_ids: None,
+            },
+            UpdateOrderItem {
+                ticket_type_id: other_ticket_type.id,
+                quantity: 1,
+                redemption_code: None,
+                seat_ids: None,
+            },
+        ],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+
+    // Tickets bought without the code count towards the minimum but are not discounted
+    let items = cart.items(connection).unwrap();
+    let order_item = items
+        .iter()
+        .find(|i| i.ticket_type_id == Some(ticket_type.id))
+        .unwrap();
+    let discount_item = order_item.find_discount_item(connection).unwrap().unwrap();
+    assert_eq!(discount_item.quantity, 2);
+    let other_order_item = items
+        .iter()
+        .find(|i| i.ticket_type_id == Some(other_ticket_type.id))
+        .unwrap();
+    assert!(other_order_item
+        .find_discount_item(connection)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn refund_with_code_discount() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let code = project
+        .create_code()
+        .with_event(&event)
+        .for_ticket_type(&ticket_type)
+        .with_discount_in_cents(Some(20))
+        .finish();
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: Some(code.redemption_code.clone()),
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(Some("Test".to_string()), user.id, total, connection)
+        .unwrap();
+
+    let order_item = cart
+        .items(connection)
+        .unwrap()
+        .into_iter()
+        .find(|i| i.item_type == OrderItemTypes::Tickets)
+        .unwrap();
+    let ticket = &TicketInstance::find_for_order_item(order_item.id, connection).unwrap()[0];
+    let fee_in_cents = order_item
+        .find_fee_item(connection)
+        .unwrap()
+        .map(|f| f.unit_price_in_cents)
+        .unwrap_or(0);
+
+    let refund_amount = cart
+        .refund(
+            vec![RefundItem {
+                order_item_id: order_item.id,
+                ticket_instance_id: Some(ticket.id),
+            }],
+            connection,
+        )
+        .unwrap();
+    assert_eq!(
+        refund_amount as i64,
+        order_item.unit_price_in_cents - 20 + fee_in_cents
+    );
+
+    let discount_item = order_item.find_discount_item(connection).unwrap().unwrap();
+    assert_eq!(discount_item.refunded_quantity, 1);
+
+    // Discounts are only refunded along with their ticket
+    let refund_items = vec![RefundItem {
+        order_item_id: discount_item.id,
+        ticket_instance_id: None,
+    }];
+    assert!(cart.refund(refund_items, connection).is_err());
+}
+
+#[test]
+fn update_quantities_with_tax() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let venue = project.create_venue().finish();
+    let event = project
+        .create_event()
+        .with_venue(&venue)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    let update_order_items = [UpdateOrderItem {
+        ticket_type_id: ticket_type.id,
+        quantity: 2,
+        redemption_code: None,
+        seat_ids: None,
+    }];
+    cart.update_quantities(&update_order_items, false, false, connection)
+        .unwrap();
+    let total_before_tax = cart.calculate_total(connection).unwrap();
+    assert_eq!(cart.tax_in_cents(connection).unwrap(), 0);
+
+    let tax_rule = TaxRule::create_for_venue(venue.id, "HST".to_string(), 1300, false, true, true)
+        .commit(connection)
+        .unwrap();
+    cart.update_quantities(&update_order_items, false, false, connection)
+        .unwrap();
+
+    let items = cart.items(connection).unwrap();
+    let ticket_item = items
+        .iter()
+        .find(|i| i.item_type == OrderItemTypes::Tickets)
+        .unwrap();
+    let fee_item = ticket_item.find_fee_item(connection).unwrap().unwrap();
+    let ticket_tax_item = &ticket_item.find_tax_items(connection).unwrap()[0];
+    assert_eq!(ticket_tax_item.tax_rule_id, Some(tax_rule.id));
+    assert_eq!(ticket_tax_item.quantity, 2);
+    assert_eq!(
+        ticket_tax_item.unit_price_in_cents,
+        tax_rule.tax_for(ticket_item.unit_price_in_cents)
+    );
+    let fee_tax_item = &fee_item.find_tax_items(connection).unwrap()[0];
+    assert_eq!(
+        fee_tax_item.unit_price_in_cents,
+        tax_rule.tax_for(fee_item.unit_price_in_cents)
+    );
+
+    // Exclusive taxes are charged on top of the price
+    let tax_in_cents: i64 = items
+        .iter()
+        .filter(|i| i.item_type == OrderItemTypes::Tax)
+        .map(|i| i.unit_price_in_cents * i.quantity)
+        .sum();
+    assert!(tax_in_cents > 0);
+    assert_eq!(cart.tax_in_cents(connection).unwrap(), tax_in_cents);
+    assert_eq!(
+        cart.calculate_total(connection).unwrap(),
+        total_before_tax + tax_in_cents
+    );
+    let display_order = cart.for_display(connection).unwrap();
+    assert_eq!(display_order.tax_in_cents, tax_in_cents);
+    assert!(display_order
+        .items
+        .iter()
+        .any(|i| i.item_type == OrderItemTypes::Tax && i.description == "Tax - HST"));
+
+    // Inclusive taxes are only recorded
+    tax_rule
+        .update(
+            TaxRuleEditableAttributes {
+                inclusive: Some(true),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    cart.update_quantities(&update_order_items, false, false, connection)
+        .unwrap();
+    assert_eq!(cart.calculate_total(connection).unwrap(), total_before_tax);
+    assert!(cart.tax_in_cents(connection).unwrap() > 0);
+    let display_order = cart.for_display(connection).unwrap();
+    assert!(display_order
+        .items
+        .iter()
+        .any(|i| i.item_type == OrderItemTypes::Tax && i.description == "Included Tax - HST"));
+
+    // Removing the tickets removes their taxes
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 0,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    assert!(cart.items(connection).unwrap().is_empty());
+}
+
+#[test]
+fn refund_with_tax() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let venue = project.create_venue().finish();
+    let event = project
+        .create_event()
+        .with_venue(&venue)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let tax_rule = TaxRule::create_for_venue(venue.id, "HST".to_string(), 1300, false, true, true)
+        .commit(connection)
+        .unwrap();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(Some("Test".to_string()), user.id, total, connection)
+        .unwrap();
+
+    let order_item = cart
+        .items(connection)
+        .unwrap()
+        .into_iter()
+        .find(|i| i.item_type == OrderItemTypes::Tickets)
+        .unwrap();
+    let ticket = &TicketInstance::find_for_order_item(order_item.id, connection).unwrap()[0];
+    let fee_item = order_item.find_fee_item(connection).unwrap().unwrap();
+    let ticket_tax_item = &order_item.find_tax_items(connection).unwrap()[0];
+
+    // Tax items are refunded with their item
+    assert!(cart
+        .refund(
+            vec![RefundItem {
+                order_item_id: ticket_tax_item.id,
+                ticket_instance_id: None,
+            }],
+            connection,
+        )
+        .is_err());
+
+    let refund_amount = cart
+        .refund(
+            vec![RefundItem {
+                order_item_id: order_item.id,
+                ticket_instance_id: Some(ticket.id),
+            }],
+            connection,
+        )
+        .unwrap();
+    assert_eq!(
+        refund_amount as i64,
+        order_item.unit_price_in_cents
+            + fee_item.unit_price_in_cents
+            + tax_rule.tax_for(order_item.unit_price_in_cents)
+            + tax_rule.tax_for(fee_item.unit_price_in_cents)
+    );
+    let ticket_tax_item = OrderItem::find(ticket_tax_item.id, connection).unwrap();
+    assert_eq!(ticket_tax_item.refunded_quantity, 1);
+    let fee_tax_item = &fee_item.find_tax_items(connection).unwrap()[0];
+    assert_eq!(fee_tax_item.refunded_quantity, 1);
+    assert_eq!(
+        cart.calculate_total(connection).unwrap(),
+        total - refund_amount as i64
     );
 }
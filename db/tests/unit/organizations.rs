@@ -132,11 +132,13 @@ fn find_by_order_item_ids() {
                 ticket_type_id: ticket_type.id,
                 quantity: 10,
                 redemption_code: None,
+                seat_ids: None,
             },
             UpdateOrderItem {
                 ticket_type_id: ticket_type2.id,
                 quantity: 10,
                 redemption_code: None,
+                seat_ids: None,
             },
         ],
         false,
@@ -201,6 +203,7 @@ fn has_fan() {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -801,6 +804,46 @@ fn add_fee_schedule() {
     assert_eq!(organization.fee_schedule_id, fee_structure.id);
 }
 
+#[test]
+fn set_payment_provider() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    assert_eq!(organization.payment_provider, "stripe".to_string());
+    assert_eq!(organization.merchant_account_id, None);
+
+    let organization = organization
+        .set_payment_provider("braintree", Some("merchant-1".to_string()), connection)
+        .unwrap();
+    let organization = Organization::find(organization.id, connection).unwrap();
+    assert_eq!(organization.payment_provider, "braintree".to_string());
+    assert_eq!(
+        organization.merchant_account_id,
+        Some("merchant-1".to_string())
+    );
+}
+
+#[test]
+fn set_resale_price_cap() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    assert_eq!(organization.resale_price_cap_percent, None);
+
+    let organization = organization
+        .set_resale_price_cap(Some(120), connection)
+        .unwrap();
+    let organization = Organization::find(organization.id, connection).unwrap();
+    assert_eq!(organization.resale_price_cap_percent, Some(120));
+
+    assert!(organization
+        .set_resale_price_cap(Some(-1), connection)
+        .is_err());
+
+    let organization = organization.set_resale_price_cap(None, connection).unwrap();
+    assert_eq!(organization.resale_price_cap_percent, None);
+}
+
 #[test]
 fn search_fans() {
     let project = TestProject::new();
@@ -826,6 +869,7 @@ fn search_fans() {
             ticket_type_id: ticket_type.id,
             quantity: 5,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -14,6 +14,7 @@ fn create() {
         "stripe".into(),
         true,
         "cus_example".into(),
+        None,
         "abc".into(),
     )
     .commit(user.id, connection)
@@ -0,0 +1,151 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::models::*;
+use diesel::PgConnection;
+
+fn create_credit_card_payment(
+    project: &TestProject,
+    status: PaymentStatus,
+    connection: &PgConnection,
+) -> Payment {
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 10,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_credit_card_payment(
+        user.id,
+        total,
+        "stripe".to_string(),
+        Some("merchant-1".to_string()),
+        "ch_test".to_string(),
+        status,
+        json!({}),
+        connection,
+    )
+    .unwrap()
+}
+
+#[test]
+fn find_by_external_reference() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let payment = create_credit_card_payment(&project, PaymentStatus::Completed, connection);
+    payment.log_external_refund(100, None, connection).unwrap();
+
+    let found = Payment::find_by_external_reference("stripe", "ch_test", connection)
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.id, payment.id);
+    assert!(
+        Payment::find_by_external_reference("braintree", "ch_test", connection)
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        Payment::find_by_external_reference("stripe", "ch_other", connection)
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn log_external_refund() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let payment = create_credit_card_payment(&project, PaymentStatus::Completed, connection);
+    assert_eq!(payment.merchant_account_id, Some("merchant-1".to_string()));
+    assert_eq!(payment.refunded_amount(connection).unwrap(), 0);
+
+    payment
+        .log_external_refund(500, Some(json!({"amount_refunded": 500})), connection)
+        .unwrap();
+    assert_eq!(payment.refunded_amount(connection).unwrap(), 500);
+    let user = project.create_user().finish();
+    payment.log_refund(user.id, 250, None, connection).unwrap();
+    assert_eq!(payment.refunded_amount(connection).unwrap(), 750);
+
+    let events = DomainEvent::find(
+        Tables::Payments,
+        Some(payment.id),
+        Some(DomainEventTypes::PaymentRefund),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(events.len(), 2);
+}
+
+#[test]
+fn mark_disputed() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let payment = create_credit_card_payment(&project, PaymentStatus::Completed, connection);
+    let order = Order::find(payment.order_id, connection).unwrap();
+    let total_paid = order.total_paid(connection).unwrap();
+    payment
+        .mark_disputed(json!({"id": "dp_test"}), connection)
+        .unwrap();
+
+    let payment = Payment::find_by_external_reference("stripe", "ch_test", connection)
+        .unwrap()
+        .unwrap();
+    assert_eq!(payment.status, PaymentStatus::Disputed);
+    // Disputed payments remain paid
+    assert_eq!(order.total_paid(connection).unwrap(), total_paid);
+    assert_eq!(total_paid, payment.amount);
+
+    // Repeated deliveries are ignored
+    payment
+        .mark_disputed(json!({"id": "dp_test"}), connection)
+        .unwrap();
+    let events = DomainEvent::find(
+        Tables::Payments,
+        Some(payment.id),
+        Some(DomainEventTypes::PaymentDisputed),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(events.len(), 1);
+}
+
+#[test]
+fn mark_failed() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let payment = create_credit_card_payment(&project, PaymentStatus::Completed, connection);
+
+    // Captured payments cannot fail
+    payment.mark_failed(json!({}), connection).unwrap();
+    let payment = Payment::find_by_external_reference("stripe", "ch_test", connection)
+        .unwrap()
+        .unwrap();
+    assert_eq!(payment.status, PaymentStatus::Completed);
+}
+
+#[test]
+fn mark_failed_authorized_payment() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let payment = create_credit_card_payment(&project, PaymentStatus::Authorized, connection);
+    payment.mark_failed(json!({}), connection).unwrap();
+
+    let payment = Payment::find_by_external_reference("stripe", "ch_test", connection)
+        .unwrap()
+        .unwrap();
+    assert_eq!(payment.status, PaymentStatus::Failed);
+}
@@ -0,0 +1,157 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::models::*;
+
+#[test]
+fn promo_code_performance_report() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let organization = project.create_organization().finish();
+    let event = project
+        .create_event()
+        .with_organization(&organization)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let code = project
+        .create_code()
+        .with_event(&event)
+        .for_ticket_type(&ticket_type)
+        .with_discount_in_cents(Some(20))
+        .with_max_uses(5)
+        .finish();
+    let hold = project
+        .create_hold()
+        .with_ticket_type_id(ticket_type.id)
+        .finish();
+
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        &[
+            UpdateOrderItem {
+                ticket_type_id: ticket_type.id,
+                quantity: 2,
+                redemption_code: Some(code.redemption_code.clone()),
+                seat_ids: None,
+            },
+            UpdateOrderItem {
+                ticket_type_id: ticket_type.id,
+                quantity: 3,
+                redemption_code: Some(hold.redemption_code.clone()),
+                seat_ids: None,
+            },
+        ],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let items = cart.items(connection).unwrap();
+    let code_item = items.iter().find(|i| i.code_id == Some(code.id)).unwrap();
+    let hold_item = items.iter().find(|i| i.hold_id == Some(hold.id)).unwrap();
+    let ticket_pricing = ticket_type
+        .current_ticket_pricing(false, connection)
+        .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(Some("Test".to_string()), user.id, total, connection)
+        .unwrap();
+
+    let report = Report::promo_code_performance_report(
+        Some(event.id),
+        Some(organization.id),
+        None,
+        None,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(report.len(), 2);
+
+    let code_row = report.iter().find(|r| r.id == code.id).unwrap();
+    assert_eq!(code_row.redemption_type, "Code".to_string());
+    assert_eq!(code_row.redemption_code, code.redemption_code);
+    assert_eq!(code_row.tickets_sold, 2);
+    assert_eq!(code_row.gross, 2 * (code_item.unit_price_in_cents - 20));
+    assert_eq!(code_row.discount_in_cents, 40);
+    assert_eq!(code_row.unique_buyers, 1);
+    assert_eq!(code_row.remaining_uses, 4);
+
+    let hold_row = report.iter().find(|r| r.id == hold.id).unwrap();
+    assert_eq!(hold_row.redemption_type, "Hold".to_string());
+    assert_eq!(hold_row.tickets_sold, 3);
+    assert_eq!(hold_row.gross, 3 * hold_item.unit_price_in_cents);
+    assert_eq!(
+        hold_row.discount_in_cents,
+        3 * (ticket_pricing.price_in_cents - hold_item.unit_price_in_cents)
+    );
+    assert_eq!(hold_row.unique_buyers, 1);
+    assert_eq!(hold_row.remaining_uses, 7);
+}
+
+#[test]
+fn summary_event_report_taxes() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let venue = project.create_venue().finish();
+    let event = project
+        .create_event()
+        .with_venue(&venue)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let tax_rule = TaxRule::create_for_venue(venue.id, "VAT".to_string(), 2000, true, true, true)
+        .commit(connection)
+        .unwrap();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(Some("test".to_string()), user.id, total, connection)
+        .unwrap();
+
+    let order_item = cart
+        .items(connection)
+        .unwrap()
+        .into_iter()
+        .find(|i| i.item_type == OrderItemTypes::Tickets)
+        .unwrap();
+    let fee_item = order_item.find_fee_item(connection).unwrap().unwrap();
+    let ticket_tax_in_cents = tax_rule.tax_for(order_item.unit_price_in_cents) * 2;
+    let fee_tax_in_cents = tax_rule.tax_for(fee_item.unit_price_in_cents) * 2;
+
+    let result = Report::summary_event_report(event.id, None, None, connection).unwrap();
+    assert_eq!(result.taxes.len(), 1);
+    let taxes = &result.taxes[0];
+    assert_eq!(taxes.tax_rule_id, Some(tax_rule.id));
+    assert_eq!(taxes.tax_name, "VAT".to_string());
+    assert!(taxes.inclusive);
+    assert_eq!(taxes.total_ticket_tax_in_cents, ticket_tax_in_cents);
+    assert_eq!(taxes.total_fee_tax_in_cents, fee_tax_in_cents);
+    assert_eq!(
+        taxes.total_tax_in_cents,
+        ticket_tax_in_cents + fee_tax_in_cents
+    );
+
+    let rows =
+        Report::transaction_detail_report(Some(event.id), None, None, None, connection).unwrap();
+    let ticket_row = rows
+        .iter()
+        .find(|r| r.ticket_name == ticket_type.name)
+        .unwrap();
+    assert_eq!(
+        ticket_row.tax_in_cents,
+        ticket_tax_in_cents + fee_tax_in_cents
+    );
+}
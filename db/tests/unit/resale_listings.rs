@@ -0,0 +1,349 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+use bigneon_db::utils::errors::ErrorCode::ValidationError;
+use chrono::prelude::*;
+use diesel::PgConnection;
+use time::Duration;
+use uuid::Uuid;
+
+fn purchased_ticket(
+    organization: &Organization,
+    user: &User,
+    project: &TestProject,
+    conn: &PgConnection,
+) -> (TicketInstance, OrderItem) {
+    let event = project
+        .create_event()
+        .with_organization(organization)
+        .with_event_start(Utc::now().naive_utc() + Duration::days(7))
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let mut cart = Order::find_or_create_cart(user, conn).unwrap();
+    let ticket_type = &event.ticket_types(conn).unwrap()[0];
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 1,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        conn,
+    )
+    .unwrap();
+    let total = cart.calculate_total(conn).unwrap();
+    cart.add_external_payment(Some("test".to_string()), user.id, total, conn)
+        .unwrap();
+
+    let order_item = cart
+        .items(conn)
+        .unwrap()
+        .into_iter()
+        .find(|i| i.item_type == OrderItemTypes::Tickets)
+        .unwrap();
+    let ticket = TicketInstance::find_for_order_item(order_item.id, conn)
+        .unwrap()
+        .remove(0);
+    (ticket, order_item)
+}
+
+#[test]
+fn commit() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let admin = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(admin.id))
+        .finish();
+    let seller = project.create_user().finish();
+    let (ticket, order_item) = purchased_ticket(&organization, &seller, &project, connection);
+
+    // Resale is disabled until the organization sets a price cap
+    let result = ResaleListing::create(ticket.id, seller.id, order_item.unit_price_in_cents)
+        .commit(connection);
+    assert_eq!(
+        result.unwrap_err().cause,
+        Some("Resale is not available for this event".to_string())
+    );
+
+    organization
+        .set_resale_price_cap(Some(110), connection)
+        .unwrap();
+    let max_price_in_cents = order_item.unit_price_in_cents * 110 / 100;
+    let result =
+        ResaleListing::create(ticket.id, seller.id, max_price_in_cents + 1).commit(connection);
+    match result {
+        Ok(_) => {
+            panic!("Expected validation error");
+        }
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("price_in_cents"));
+                assert_eq!(
+                    errors["price_in_cents"][0].code,
+                    "Price is above the resale price cap for this event"
+                );
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+
+    // Only the ticket holder can list it
+    let other_user = project.create_user().finish();
+    assert!(
+        ResaleListing::create(ticket.id, other_user.id, max_price_in_cents)
+            .commit(connection)
+            .is_err()
+    );
+
+    let resale_listing = ResaleListing::create(ticket.id, seller.id, max_price_in_cents)
+        .commit(connection)
+        .unwrap();
+    assert_eq!(resale_listing.status, ResaleListingStatus::Active);
+    assert_eq!(resale_listing.price_in_cents, max_price_in_cents);
+    assert_eq!(resale_listing.seller_id, seller.id);
+
+    // A ticket can only have one active listing
+    assert!(
+        ResaleListing::create(ticket.id, seller.id, max_price_in_cents)
+            .commit(connection)
+            .is_err()
+    );
+
+    // Listed tickets cannot be transferred
+    assert!(TicketInstance::authorize_ticket_transfer(
+        seller.id,
+        vec![ticket.id],
+        3600,
+        connection
+    )
+    .is_err());
+}
+
+#[test]
+fn cancel() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let admin = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(admin.id))
+        .finish()
+        .set_resale_price_cap(Some(100), connection)
+        .unwrap();
+    let seller = project.create_user().finish();
+    let (ticket, order_item) = purchased_ticket(&organization, &seller, &project, connection);
+    let resale_listing =
+        ResaleListing::create(ticket.id, seller.id, order_item.unit_price_in_cents)
+            .commit(connection)
+            .unwrap();
+
+    // Listings in a buyer's cart cannot be cancelled
+    let buyer = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&buyer, connection).unwrap();
+    cart.update_resale_listings(&[resale_listing.id], connection)
+        .unwrap();
+    let resale_listing = ResaleListing::find(resale_listing.id, connection).unwrap();
+    assert!(resale_listing.is_reserved(connection).unwrap());
+    assert!(resale_listing.cancel(seller.id, connection).is_err());
+
+    cart.update_resale_listings(&[], connection).unwrap();
+    let resale_listing = ResaleListing::find(resale_listing.id, connection).unwrap();
+    assert!(!resale_listing.is_reserved(connection).unwrap());
+    assert!(resale_listing.cancel(buyer.id, connection).is_err());
+
+    let resale_listing = resale_listing.cancel(seller.id, connection).unwrap();
+    assert_eq!(resale_listing.status, ResaleListingStatus::Cancelled);
+    assert!(
+        ResaleListing::find_active_for_event(order_item.event_id.unwrap(), connection)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn cancelled_when_ticket_refunded_or_redeemed() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let admin = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(admin.id))
+        .finish()
+        .set_resale_price_cap(Some(100), connection)
+        .unwrap();
+    let seller = project.create_user().finish();
+
+    let (ticket, order_item) = purchased_ticket(&organization, &seller, &project, connection);
+    let resale_listing =
+        ResaleListing::create(ticket.id, seller.id, order_item.unit_price_in_cents)
+            .commit(connection)
+            .unwrap();
+    let order = Order::find(order_item.order_id, connection).unwrap();
+    order
+        .refund(
+            vec![RefundItem {
+                order_item_id: order_item.id,
+                ticket_instance_id: Some(ticket.id),
+            }],
+            connection,
+        )
+        .unwrap();
+    let resale_listing = ResaleListing::find(resale_listing.id, connection).unwrap();
+    assert_eq!(resale_listing.status, ResaleListingStatus::Cancelled);
+
+    let (ticket, order_item) = purchased_ticket(&organization, &seller, &project, connection);
+    let resale_listing =
+        ResaleListing::create(ticket.id, seller.id, order_item.unit_price_in_cents)
+            .commit(connection)
+            .unwrap();
+    TicketInstance::redeem_ticket(ticket.id, ticket.redeem_key.clone().unwrap(), connection)
+        .unwrap();
+    let resale_listing = ResaleListing::find(resale_listing.id, connection).unwrap();
+    assert_eq!(resale_listing.status, ResaleListingStatus::Cancelled);
+}
+
+#[test]
+fn purchase() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let admin = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(admin.id))
+        .finish()
+        .set_resale_price_cap(Some(100), connection)
+        .unwrap();
+    let seller = project.create_user().finish();
+    let (ticket, order_item) = purchased_ticket(&organization, &seller, &project, connection);
+    let resale_listing =
+        ResaleListing::create(ticket.id, seller.id, order_item.unit_price_in_cents)
+            .commit(connection)
+            .unwrap();
+    assert_eq!(
+        ResaleListing::find_active_for_event(order_item.event_id.unwrap(), connection).unwrap(),
+        vec![resale_listing.clone()]
+    );
+
+    // Sellers cannot buy their own listing
+    let mut seller_cart = Order::find_or_create_cart(&seller, connection).unwrap();
+    assert!(seller_cart
+        .update_resale_listings(&[resale_listing.id], connection)
+        .is_err());
+
+    let buyer = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&buyer, connection).unwrap();
+    cart.update_resale_listings(&[resale_listing.id], connection)
+        .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    assert_eq!(total, resale_listing.price_in_cents);
+
+    // Another buyer cannot add a listing held by a cart
+    let other_buyer = project.create_user().finish();
+    let mut other_cart = Order::find_or_create_cart(&other_buyer, connection).unwrap();
+    assert!(other_cart
+        .update_resale_listings(&[resale_listing.id], connection)
+        .is_err());
+
+    cart.add_external_payment(Some("test".to_string()), buyer.id, total, connection)
+        .unwrap();
+    assert_eq!(cart.status, OrderStatus::Paid);
+
+    let fee_in_cents = FeeSchedule::find(organization.fee_schedule_id, connection)
+        .unwrap()
+        .get_range(resale_listing.price_in_cents, connection)
+        .unwrap()
+        .fee_in_cents;
+    let resale_listing = ResaleListing::find(resale_listing.id, connection).unwrap();
+    assert_eq!(resale_listing.status, ResaleListingStatus::Sold);
+    assert!(resale_listing.sold_at.is_some());
+    assert_eq!(
+        resale_listing.seller_payout_in_cents,
+        Some(resale_listing.price_in_cents - fee_in_cents)
+    );
+    // The organization owes the seller the payout, it is not credited to their account
+    assert_eq!(
+        CreditTransaction::balance_for_user(seller.id, connection).unwrap(),
+        0
+    );
+    let resale_payouts =
+        ResalePayout::find_unpaid_for_organization(organization.id, connection).unwrap();
+    assert_eq!(resale_payouts.len(), 1);
+    assert_eq!(resale_payouts[0].resale_listing_id, resale_listing.id);
+    assert_eq!(resale_payouts[0].seller_id, seller.id);
+    assert_eq!(
+        resale_payouts[0].amount_in_cents,
+        resale_listing.price_in_cents - fee_in_cents
+    );
+    let resales =
+        Report::summary_event_report(order_item.event_id.unwrap(), None, None, connection)
+            .unwrap()
+            .resales
+            .unwrap();
+    assert_eq!(resales.resold_count, 1);
+    assert_eq!(
+        resales.total_resale_price_in_cents,
+        resale_listing.price_in_cents
+    );
+    assert_eq!(resales.total_fee_in_cents, fee_in_cents);
+    assert_eq!(
+        resales.total_seller_payout_in_cents,
+        resale_listing.price_in_cents - fee_in_cents
+    );
+
+    let resold_ticket = TicketInstance::find(ticket.id, connection).unwrap();
+    let buyer_wallet = Wallet::find_default_for_user(buyer.id, connection).unwrap();
+    assert_eq!(resold_ticket.wallet_id, buyer_wallet.id);
+    assert_eq!(resold_ticket.status, TicketInstanceStatus::Purchased);
+    assert_ne!(resold_ticket.redeem_key, ticket.redeem_key);
+
+    // The token is moved on the blockchain by a domain action
+    let seller_wallet = Wallet::find_default_for_user(seller.id, connection).unwrap();
+    let domain_actions =
+        DomainAction::find_pending(Some(DomainActionTypes::TransferResoldTicket), connection)
+            .unwrap();
+    assert_eq!(domain_actions.len(), 1);
+    assert_eq!(domain_actions[0].main_table_id, ticket.id);
+    assert_eq!(
+        domain_actions[0].payload,
+        json!({
+            "resale_listing_id": resale_listing.id,
+            "from_wallet_id": seller_wallet.id,
+            "to_wallet_id": buyer_wallet.id,
+        })
+    );
+
+    // Payloads the seller downloaded before the sale are revoked for offline scanning
+    let revoked_ticket_ids: Vec<Uuid> =
+        EventSigningKey::find_or_create_for_event(order_item.event_id.unwrap(), connection)
+            .unwrap()
+            .revoked_tickets(connection)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.ticket_instance_id)
+            .collect();
+    assert_eq!(revoked_ticket_ids, vec![ticket.id]);
+
+    // The ticket now belongs to the buyer so it can no longer be refunded on the seller's order
+    assert!(resold_ticket.was_transferred(connection).unwrap());
+
+    // Resale purchases are not refundable
+    let resale_item = cart
+        .items(connection)
+        .unwrap()
+        .into_iter()
+        .find(|i| i.item_type == OrderItemTypes::Resale)
+        .unwrap();
+    assert!(cart
+        .refund(
+            vec![RefundItem {
+                order_item_id: resale_item.id,
+                ticket_instance_id: Some(ticket.id),
+            }],
+            connection,
+        )
+        .is_err());
+}
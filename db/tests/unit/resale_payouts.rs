@@ -0,0 +1,133 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+use chrono::prelude::*;
+use diesel::PgConnection;
+use time::Duration;
+
+fn sold_resale_payout(
+    organization: &Organization,
+    project: &TestProject,
+    conn: &PgConnection,
+) -> ResalePayout {
+    let event = project
+        .create_event()
+        .with_organization(organization)
+        .with_event_start(Utc::now().naive_utc() + Duration::days(7))
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let seller = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&seller, conn).unwrap();
+    let ticket_type = &event.ticket_types(conn).unwrap()[0];
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 1,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        conn,
+    )
+    .unwrap();
+    let total = cart.calculate_total(conn).unwrap();
+    cart.add_external_payment(Some("test".to_string()), seller.id, total, conn)
+        .unwrap();
+    let order_item = cart
+        .items(conn)
+        .unwrap()
+        .into_iter()
+        .find(|i| i.item_type == OrderItemTypes::Tickets)
+        .unwrap();
+    let ticket = TicketInstance::find_for_order_item(order_item.id, conn)
+        .unwrap()
+        .remove(0);
+    let resale_listing =
+        ResaleListing::create(ticket.id, seller.id, order_item.unit_price_in_cents)
+            .commit(conn)
+            .unwrap();
+
+    let buyer = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&buyer, conn).unwrap();
+    cart.update_resale_listings(&[resale_listing.id], conn)
+        .unwrap();
+    let total = cart.calculate_total(conn).unwrap();
+    cart.add_external_payment(Some("test".to_string()), buyer.id, total, conn)
+        .unwrap();
+
+    ResalePayout::find_unpaid_for_organization(organization.id, conn)
+        .unwrap()
+        .into_iter()
+        .find(|p| p.resale_listing_id == resale_listing.id)
+        .unwrap()
+}
+
+#[test]
+fn find_unpaid_for_organization() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let admin = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(admin.id))
+        .finish()
+        .set_resale_price_cap(Some(100), connection)
+        .unwrap();
+    let other_organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(admin.id))
+        .finish()
+        .set_resale_price_cap(Some(100), connection)
+        .unwrap();
+    let resale_payout = sold_resale_payout(&organization, &project, connection);
+    let resale_payout2 = sold_resale_payout(&organization, &project, connection);
+    let other_resale_payout = sold_resale_payout(&other_organization, &project, connection);
+
+    assert_eq!(
+        ResalePayout::find_unpaid_for_organization(organization.id, connection).unwrap(),
+        vec![resale_payout.clone(), resale_payout2.clone()]
+    );
+    assert_eq!(
+        ResalePayout::find_unpaid_for_organization(other_organization.id, connection).unwrap(),
+        vec![other_resale_payout]
+    );
+
+    // Paid payouts are no longer owed
+    let user = project.create_user().finish();
+    resale_payout.mark_paid(user.id, connection).unwrap();
+    assert_eq!(
+        ResalePayout::find_unpaid_for_organization(organization.id, connection).unwrap(),
+        vec![resale_payout2]
+    );
+}
+
+#[test]
+fn mark_paid() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let admin = project.create_user().finish();
+    let organization = project
+        .create_organization()
+        .with_fee_schedule(&project.create_fee_schedule().finish(admin.id))
+        .finish()
+        .set_resale_price_cap(Some(100), connection)
+        .unwrap();
+    let resale_payout = sold_resale_payout(&organization, &project, connection);
+    assert!(resale_payout.paid_at.is_none());
+    assert!(resale_payout.paid_by_user_id.is_none());
+
+    let user = project.create_user().finish();
+    let paid_resale_payout = resale_payout.mark_paid(user.id, connection).unwrap();
+    assert!(paid_resale_payout.paid_at.is_some());
+    assert_eq!(paid_resale_payout.paid_by_user_id, Some(user.id));
+
+    // A payout cannot be paid twice
+    let result = resale_payout.mark_paid(user.id, connection);
+    assert_eq!(
+        result.unwrap_err().cause,
+        Some("This resale payout has already been paid".to_string())
+    );
+    let resale_payout = ResalePayout::find(resale_payout.id, connection).unwrap();
+    assert_eq!(resale_payout, paid_resale_payout);
+}
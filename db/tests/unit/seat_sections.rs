@@ -0,0 +1,164 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+use bigneon_db::utils::errors::ErrorCode::ValidationError;
+
+#[test]
+fn commit() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let venue = project.create_venue().finish();
+    let stage = project.create_stage().with_venue_id(venue.id).finish();
+    let seat_section = SeatSection::create(venue.id, Some(stage.id), "Balcony".to_string())
+        .commit(connection)
+        .unwrap();
+
+    assert_eq!(seat_section.name, "Balcony".to_string());
+    assert_eq!(seat_section.venue_id, venue.id);
+    assert_eq!(seat_section.stage_id, Some(stage.id));
+}
+
+#[test]
+fn commit_with_stage_from_other_venue() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let venue = project.create_venue().finish();
+    let venue2 = project.create_venue().finish();
+    let stage = project.create_stage().with_venue_id(venue2.id).finish();
+    let result =
+        SeatSection::create(venue.id, Some(stage.id), "Balcony".to_string()).commit(connection);
+
+    match result {
+        Ok(_) => {
+            panic!("Expected validation error");
+        }
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("stage_id"));
+                assert_eq!(
+                    errors["stage_id"][0].message,
+                    Some("Stage must belong to the same venue as the seat section".into())
+                );
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+}
+
+#[test]
+fn update() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let venue = project.create_venue().finish();
+    let seat_section = project
+        .create_seat_section()
+        .with_venue_id(venue.id)
+        .finish();
+    let new_name = "Floor".to_string();
+
+    let parameters = SeatSectionEditableAttributes {
+        name: Some(new_name.clone()),
+        ..Default::default()
+    };
+
+    let updated_seat_section = seat_section.update(parameters, connection).unwrap();
+    assert_eq!(updated_seat_section.name, new_name);
+    assert_eq!(updated_seat_section.stage_id, None);
+}
+
+#[test]
+fn find() {
+    let project = TestProject::new();
+    let venue = project.create_venue().finish();
+    let seat_section = project
+        .create_seat_section()
+        .with_venue_id(venue.id)
+        .finish();
+
+    let found_seat_section = SeatSection::find(seat_section.id, project.get_connection()).unwrap();
+    assert_eq!(seat_section, found_seat_section);
+}
+
+#[test]
+fn find_by_venue_id() {
+    let project = TestProject::new();
+    let venue = project.create_venue().finish();
+    let venue2 = project.create_venue().finish();
+    let seat_section = project
+        .create_seat_section()
+        .with_name("Section 1".to_string())
+        .with_venue_id(venue.id)
+        .finish();
+    let seat_section2 = project
+        .create_seat_section()
+        .with_name("Section 2".to_string())
+        .with_venue_id(venue.id)
+        .finish();
+    project
+        .create_seat_section()
+        .with_venue_id(venue2.id)
+        .finish();
+
+    let found_seat_sections =
+        SeatSection::find_by_venue_id(venue.id, project.get_connection()).unwrap();
+    assert_eq!(found_seat_sections, vec![seat_section, seat_section2]);
+}
+
+#[test]
+fn add_seats() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let venue = project.create_venue().finish();
+    let seat_section = project
+        .create_seat_section()
+        .with_venue_id(venue.id)
+        .finish();
+
+    let seats = seat_section
+        .add_seats(
+            vec![
+                Seat::create(seat_section.id, "B".to_string(), "1".to_string(), true),
+                Seat::create(seat_section.id, "A".to_string(), "1".to_string(), false),
+            ],
+            connection,
+        )
+        .unwrap();
+    assert_eq!(seats.len(), 2);
+
+    let found_seats = seat_section.seats(connection).unwrap();
+    assert_eq!(found_seats.len(), 2);
+    assert_eq!(found_seats[0].row_name, "A".to_string());
+    assert!(!found_seats[0].is_accessible);
+    assert_eq!(found_seats[1].row_name, "B".to_string());
+    assert!(found_seats[1].is_accessible);
+
+    let display_seat = found_seats[1].for_display(connection).unwrap();
+    assert_eq!(display_seat.section_name, seat_section.name);
+    assert_eq!(display_seat.seat_number, "1".to_string());
+
+    // Duplicate seats in the same section are rejected
+    assert!(seat_section
+        .add_seats(
+            vec![Seat::create(
+                seat_section.id,
+                "A".to_string(),
+                "1".to_string(),
+                false
+            )],
+            connection,
+        )
+        .is_err());
+}
+
+#[test]
+fn destroy() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let venue = project.create_venue().finish();
+    let seat_section = project
+        .create_seat_section()
+        .with_venue_id(venue.id)
+        .with_seats(2)
+        .finish();
+    assert!(seat_section.destroy(connection).unwrap() > 0);
+    assert!(SeatSection::find(seat_section.id, connection).is_err());
+}
@@ -0,0 +1,167 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::prelude::*;
+use bigneon_db::utils::errors::ErrorCode::ValidationError;
+
+#[test]
+fn commit() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let region = project.create_region().finish();
+
+    let result =
+        TaxRule::create_for_region(region.id, "GST".to_string(), 10001, false, true, false)
+            .commit(connection);
+    match result {
+        Ok(_) => {
+            panic!("Expected validation error");
+        }
+        Err(error) => match &error.error_code {
+            ValidationError { errors } => {
+                assert!(errors.contains_key("rate_in_basis_points"));
+                assert_eq!(
+                    errors["rate_in_basis_points"][0].code,
+                    "Tax rate must be between 0 and 10000 basis points"
+                );
+            }
+            _ => panic!("Expected validation error"),
+        },
+    }
+
+    let tax_rule =
+        TaxRule::create_for_region(region.id, "GST".to_string(), 500, false, true, false)
+            .commit(connection)
+            .unwrap();
+    assert_eq!(tax_rule.region_id, Some(region.id));
+    assert_eq!(tax_rule.venue_id, None);
+    assert_eq!(tax_rule.rate_in_basis_points, 500);
+    assert!(tax_rule.applies_to_tickets);
+    assert!(!tax_rule.applies_to_fees);
+}
+
+#[test]
+fn update() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let venue = project.create_venue().finish();
+    let tax_rule = TaxRule::create_for_venue(venue.id, "VAT".to_string(), 2000, true, true, false)
+        .commit(connection)
+        .unwrap();
+
+    let parameters = TaxRuleEditableAttributes {
+        rate_in_basis_points: Some(-1),
+        ..Default::default()
+    };
+    assert!(tax_rule.update(parameters, connection).is_err());
+
+    let parameters = TaxRuleEditableAttributes {
+        rate_in_basis_points: Some(2100),
+        applies_to_fees: Some(true),
+        ..Default::default()
+    };
+    let tax_rule = tax_rule.update(parameters, connection).unwrap();
+    assert_eq!(tax_rule.rate_in_basis_points, 2100);
+    assert!(tax_rule.applies_to_fees);
+    assert!(tax_rule.inclusive);
+}
+
+#[test]
+fn find_for_event() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let region = project.create_region().finish();
+    let venue = project.create_venue().with_region(&region).finish();
+    let event = project.create_event().with_venue(&venue).finish();
+    assert!(TaxRule::find_for_event(&event, connection)
+        .unwrap()
+        .is_empty());
+
+    let region_tax_rule =
+        TaxRule::create_for_region(region.id, "GST".to_string(), 500, false, true, false)
+            .commit(connection)
+            .unwrap();
+    assert_eq!(
+        TaxRule::find_for_event(&event, connection).unwrap(),
+        vec![region_tax_rule]
+    );
+
+    // Venue rules replace the region's rules
+    let venue_tax_rule =
+        TaxRule::create_for_venue(venue.id, "HST".to_string(), 1300, false, true, false)
+            .commit(connection)
+            .unwrap();
+    assert_eq!(
+        TaxRule::find_for_event(&event, connection).unwrap(),
+        vec![venue_tax_rule]
+    );
+
+    let event_without_venue = project.create_event().finish();
+    assert!(TaxRule::find_for_event(&event_without_venue, connection)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn tax_for() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let venue = project.create_venue().finish();
+    let exclusive_tax_rule =
+        TaxRule::create_for_venue(venue.id, "HST".to_string(), 1300, false, true, false)
+            .commit(connection)
+            .unwrap();
+    assert_eq!(exclusive_tax_rule.tax_for(150), 20);
+    assert_eq!(exclusive_tax_rule.tax_for(-20), -3);
+    // Half cents round away from zero
+    assert_eq!(exclusive_tax_rule.tax_for(50), 7);
+    assert_eq!(exclusive_tax_rule.tax_for(-50), -7);
+    assert_eq!(exclusive_tax_rule.tax_for(49), 6);
+
+    let inclusive_tax_rule =
+        TaxRule::create_for_venue(venue.id, "VAT".to_string(), 2000, true, true, false)
+            .commit(connection)
+            .unwrap();
+    assert_eq!(inclusive_tax_rule.tax_for(150), 25);
+}
+
+#[test]
+fn destroy() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let venue = project.create_venue().finish();
+    let event = project
+        .create_event()
+        .with_venue(&venue)
+        .with_tickets()
+        .with_ticket_pricing()
+        .finish();
+    let unused_tax_rule =
+        TaxRule::create_for_venue(venue.id, "Unused".to_string(), 0, false, true, false)
+            .commit(connection)
+            .unwrap();
+    assert_eq!(unused_tax_rule.destroy(connection).unwrap(), 1);
+
+    let tax_rule = TaxRule::create_for_venue(venue.id, "HST".to_string(), 1300, false, true, false)
+        .commit(connection)
+        .unwrap();
+    let user = project.create_user().finish();
+    let mut cart = Order::find_or_create_cart(&user, connection).unwrap();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    cart.update_quantities(
+        &[UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 1,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+    let total = cart.calculate_total(connection).unwrap();
+    cart.add_external_payment(Some("test".to_string()), user.id, total, connection)
+        .unwrap();
+
+    // Taxes that have been charged are kept for reporting
+    assert!(tax_rule.destroy(connection).is_err());
+}
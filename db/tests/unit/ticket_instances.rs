@@ -282,6 +282,7 @@ fn find() {
             ticket_type_id: ticket_type.id,
             quantity: 1,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -310,6 +311,7 @@ fn find() {
         status: TicketInstanceStatus::Reserved,
         redeem_key: ticket.redeem_key,
         pending_transfer: false,
+        seat: None,
     };
     assert_eq!(
         (display_event, None, expected_ticket),
@@ -343,6 +345,7 @@ fn find_for_user() {
             ticket_type_id: ticket_type.id,
             quantity: 5,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -374,6 +377,7 @@ fn release_tickets() {
                 ticket_type_id,
                 quantity: 10,
                 redemption_code: None,
+                seat_ids: None,
             }],
             false,
             false,
@@ -488,7 +492,9 @@ fn show_redeemable_ticket() {
         ..Default::default()
     };
 
-    let event = event.update(new_event_redeem_date, connection).unwrap();
+    let event = event
+        .update(None, new_event_redeem_date, connection)
+        .unwrap();
 
     let result = TicketInstance::show_redeemable_ticket(ticket.id, connection).unwrap();
     assert!(result.redeem_key.is_none());
@@ -501,7 +507,9 @@ fn show_redeemable_ticket() {
         ..Default::default()
     };
 
-    let event = event.update(new_event_redeem_date, connection).unwrap();
+    let event = event
+        .update(None, new_event_redeem_date, connection)
+        .unwrap();
 
     let result = TicketInstance::show_redeemable_ticket(ticket.id, connection).unwrap();
     assert!(result.redeem_key.is_some());
@@ -547,6 +555,7 @@ fn authorize_ticket_transfer() {
             ticket_type_id: ticket_type.id,
             quantity: 5,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -608,6 +617,7 @@ fn receive_ticket_transfer() {
             ticket_type_id: ticket_type.id,
             quantity: 5,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -0,0 +1,180 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::models::*;
+
+#[test]
+fn scan() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let user = project.create_user().finish();
+    let door_person = project.create_user().finish();
+    project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .is_paid()
+        .finish();
+    let ticket = TicketInstance::find_for_user(user.id, connection)
+        .unwrap()
+        .remove(0);
+    let redeem_key = ticket.redeem_key.clone().unwrap();
+
+    let scan = TicketScan::scan(
+        event.id,
+        ticket.id,
+        "WrongKey".to_string(),
+        TicketScanDirections::In,
+        Some(door_person.id),
+        Some("Scanner 1".to_string()),
+        Some("North Gate".to_string()),
+        connection,
+    )
+    .unwrap();
+    assert_eq!(scan.result, TicketScanResults::Invalid);
+    assert_eq!(scan.ticket_instance_id, Some(ticket.id));
+    assert_eq!(scan.scanned_by_user_id, Some(door_person.id));
+    assert_eq!(scan.device_id, Some("Scanner 1".to_string()));
+    assert_eq!(scan.gate_name, Some("North Gate".to_string()));
+
+    let scan = TicketScan::scan(
+        event.id,
+        ticket.id,
+        redeem_key.clone(),
+        TicketScanDirections::In,
+        Some(door_person.id),
+        None,
+        None,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(scan.result, TicketScanResults::Redeemed);
+
+    // Re-entry is not allowed for the ticket type
+    let scan = TicketScan::scan(
+        event.id,
+        ticket.id,
+        redeem_key.clone(),
+        TicketScanDirections::Out,
+        Some(door_person.id),
+        None,
+        None,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(scan.result, TicketScanResults::Invalid);
+    let scan = TicketScan::scan(
+        event.id,
+        ticket.id,
+        redeem_key,
+        TicketScanDirections::In,
+        Some(door_person.id),
+        None,
+        None,
+        connection,
+    )
+    .unwrap();
+    assert_eq!(scan.result, TicketScanResults::AlreadyRedeemed);
+
+    let scans = TicketScan::find_for_ticket_instance(ticket.id, connection).unwrap();
+    assert_eq!(scans.len(), 4);
+}
+
+#[test]
+fn scan_with_reentry() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    ticket_type
+        .update(
+            TicketTypeEditableAttributes {
+                allow_reentry: Some(true),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    let user = project.create_user().finish();
+    project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .is_paid()
+        .finish();
+    let ticket = TicketInstance::find_for_user(user.id, connection)
+        .unwrap()
+        .remove(0);
+    let redeem_key = ticket.redeem_key.clone().unwrap();
+    let scan = |direction: TicketScanDirections| {
+        TicketScan::scan(
+            event.id,
+            ticket.id,
+            redeem_key.clone(),
+            direction,
+            None,
+            None,
+            None,
+            connection,
+        )
+        .unwrap()
+        .result
+    };
+
+    // Tickets cannot be scanned out before they are redeemed
+    assert_eq!(scan(TicketScanDirections::Out), TicketScanResults::Invalid);
+    assert_eq!(scan(TicketScanDirections::In), TicketScanResults::Redeemed);
+    assert_eq!(
+        scan(TicketScanDirections::In),
+        TicketScanResults::AlreadyRedeemed
+    );
+    assert_eq!(
+        scan(TicketScanDirections::Out),
+        TicketScanResults::ScannedOut
+    );
+    assert_eq!(scan(TicketScanDirections::Out), TicketScanResults::Invalid);
+    assert_eq!(scan(TicketScanDirections::In), TicketScanResults::ReEntered);
+    assert_eq!(
+        scan(TicketScanDirections::In),
+        TicketScanResults::AlreadyRedeemed
+    );
+}
+
+#[test]
+fn find_for_event() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().with_ticket_pricing().finish();
+    let other_event = project.create_event().with_ticket_pricing().finish();
+    let user = project.create_user().finish();
+    project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .quantity(3)
+        .is_paid()
+        .finish();
+    for ticket in TicketInstance::find_for_user(user.id, connection).unwrap() {
+        TicketScan::scan(
+            event.id,
+            ticket.id,
+            ticket.redeem_key.unwrap(),
+            TicketScanDirections::In,
+            None,
+            None,
+            None,
+            connection,
+        )
+        .unwrap();
+    }
+
+    let scans = TicketScan::find_for_event(event.id, 0, 2, connection).unwrap();
+    assert_eq!(scans.paging.total, 3);
+    assert_eq!(scans.data.len(), 2);
+    assert!(scans
+        .data
+        .iter()
+        .all(|s| s.result == TicketScanResults::Redeemed));
+
+    let scans = TicketScan::find_for_event(other_event.id, 0, 2, connection).unwrap();
+    assert_eq!(scans.paging.total, 0);
+}
@@ -330,6 +330,7 @@ pub fn remaining_ticket_count() {
                 ticket_type_id: ticket_type.id,
                 quantity: 20,
                 redemption_code: None,
+                seat_ids: None,
             }],
             false,
             false,
@@ -344,6 +345,7 @@ pub fn remaining_ticket_count() {
                 ticket_type_id: ticket_type.id,
                 quantity: 16,
                 redemption_code: None,
+                seat_ids: None,
             }],
             false,
             false,
@@ -382,8 +384,9 @@ fn cancel() {
     let connection = db.get_connection();
     let event = db.create_event().with_tickets().finish();
     let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let user = db.create_user().finish();
 
-    let cancelled_ticket_type = ticket_type.cancel(connection).unwrap();
+    let cancelled_ticket_type = ticket_type.cancel(user.id, connection).unwrap();
 
     assert_eq!(cancelled_ticket_type.status, TicketTypeStatus::Cancelled);
 }
@@ -440,3 +443,61 @@ fn find() {
     let found_ticket_type = TicketType::find(ticket_type.id, &db.get_connection()).unwrap();
     assert_eq!(&found_ticket_type, ticket_type);
 }
+
+#[test]
+fn assign_seats() {
+    let db = TestProject::new();
+    let connection = db.get_connection();
+    let venue = db.create_venue().finish();
+    let other_venue = db.create_venue().finish();
+    let event = db
+        .create_event()
+        .with_venue(&venue)
+        .with_a_specific_number_of_tickets(3)
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let seat_section = db
+        .create_seat_section()
+        .with_venue_id(venue.id)
+        .with_seats(4)
+        .finish();
+    let other_seat_section = db
+        .create_seat_section()
+        .with_venue_id(other_venue.id)
+        .with_seats(1)
+        .finish();
+    let seat_ids: Vec<Uuid> = seat_section
+        .seats(connection)
+        .unwrap()
+        .iter()
+        .map(|s| s.id)
+        .collect();
+    let other_seat_id = other_seat_section.seats(connection).unwrap()[0].id;
+
+    // Seats from another venue cannot be assigned
+    assert!(ticket_type
+        .assign_seats(&[other_seat_id], connection)
+        .is_err());
+
+    // More seats than tickets
+    assert!(ticket_type.assign_seats(&seat_ids, connection).is_err());
+
+    let tickets = ticket_type
+        .assign_seats(&seat_ids[0..2], connection)
+        .unwrap();
+    assert_eq!(tickets.len(), 2);
+    let mut assigned_seat_ids: Vec<Uuid> = tickets.iter().map(|t| t.seat_id.unwrap()).collect();
+    assigned_seat_ids.sort();
+    let mut expected_seat_ids = seat_ids[0..2].to_vec();
+    expected_seat_ids.sort();
+    assert_eq!(assigned_seat_ids, expected_seat_ids);
+
+    // Seats can only be assigned once per event
+    assert!(ticket_type
+        .assign_seats(&seat_ids[0..1], connection)
+        .is_err());
+
+    let seats = ticket_type.seats(connection).unwrap();
+    assert_eq!(seats.len(), 2);
+    assert!(seats.iter().all(|s| s.available));
+}
@@ -164,6 +164,7 @@ fn get_profile_for_organization() {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -218,6 +219,7 @@ fn get_profile_for_organization() {
             ticket_type_id: ticket_type.id,
             quantity: 1,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -253,6 +255,7 @@ fn get_profile_for_organization() {
             ticket_type_id: ticket_type2.id,
             quantity: 1,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -314,6 +317,7 @@ fn get_history_for_organization() {
             ticket_type_id: ticket_type.id,
             quantity: 10,
             redemption_code: None,
+            seat_ids: None,
         }],
         false,
         false,
@@ -358,6 +362,7 @@ fn get_history_for_organization() {
                 ticket_type_id: ticket_type.id,
                 quantity: 1,
                 redemption_code: None,
+                seat_ids: None,
             }],
             false,
             false,
@@ -429,7 +434,7 @@ fn payment_method() {
     let project = TestProject::new();
     let user = project.create_user().finish();
     assert!(user
-        .payment_method("Nothing".into(), project.get_connection())
+        .payment_method("Nothing".into(), None, project.get_connection())
         .is_err());
 
     let payment_method = project
@@ -439,9 +444,18 @@ fn payment_method() {
         .finish();
     assert_eq!(
         payment_method,
-        user.payment_method(payment_method.name.clone(), project.get_connection())
+        user.payment_method(payment_method.name.clone(), None, project.get_connection())
             .unwrap(),
     );
+
+    // Stored tokens are not shared between merchant accounts
+    assert!(user
+        .payment_method(
+            payment_method.name.clone(),
+            Some("merchant-1".to_string()),
+            project.get_connection()
+        )
+        .is_err());
 }
 
 #[test]
@@ -543,6 +557,12 @@ fn update() {
 
     let updated_user = user.update(&attributes.into(), connection).unwrap();
     assert_eq!(updated_user.email, Some(email.into()));
+    assert!(!updated_user.marketing_opt_in);
+
+    let mut attributes: UserEditableAttributes = Default::default();
+    attributes.marketing_opt_in = Some(true);
+    let updated_user = updated_user.update(&attributes, connection).unwrap();
+    assert!(updated_user.marketing_opt_in);
 }
 
 #[test]
@@ -0,0 +1,200 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::models::*;
+use bigneon_db::schema::waitlist_entries;
+use bigneon_db::utils::errors::ErrorCode;
+use chrono::prelude::*;
+use diesel;
+use diesel::prelude::*;
+use time::Duration;
+
+#[test]
+fn create() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_a_specific_number_of_tickets(2)
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let buyer = project.create_user().finish();
+    let user = project.create_user().finish();
+
+    // Tickets are still available
+    let result = WaitlistEntry::create(ticket_type.id, user.id, 1).commit(connection);
+    assert_eq!(
+        result.unwrap_err().error_code,
+        ErrorCode::BusinessProcessError
+    );
+
+    let mut cart = Order::find_or_create_cart(&buyer, connection).unwrap();
+    cart.update_quantities(
+        &vec![UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+
+    let entry = WaitlistEntry::create(ticket_type.id, user.id, 1)
+        .commit(connection)
+        .unwrap();
+    assert_eq!(entry.status, WaitlistEntryStatus::Waiting);
+    assert_eq!(entry.position(connection).unwrap(), Some(1));
+    assert_eq!(
+        WaitlistEntry::find_active_for_user(ticket_type.id, user.id, connection).unwrap(),
+        Some(entry.clone())
+    );
+    assert!(DomainAction::has_pending_action(
+        DomainActionTypes::ProcessWaitlist,
+        Tables::TicketTypes.table_name(),
+        ticket_type.id,
+        connection
+    )
+    .unwrap());
+
+    // Only one active entry per user
+    let result = WaitlistEntry::create(ticket_type.id, user.id, 1).commit(connection);
+    assert_eq!(
+        result.unwrap_err().error_code,
+        ErrorCode::BusinessProcessError
+    );
+
+    let user2 = project.create_user().finish();
+    let entry2 = WaitlistEntry::create(ticket_type.id, user2.id, 1)
+        .commit(connection)
+        .unwrap();
+    assert_eq!(entry2.position(connection).unwrap(), Some(2));
+}
+
+#[test]
+fn process() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_a_specific_number_of_tickets(2)
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let buyer = project.create_user().finish();
+    let user = project.create_user().finish();
+    let user2 = project.create_user().finish();
+
+    let mut cart = Order::find_or_create_cart(&buyer, connection).unwrap();
+    cart.update_quantities(
+        &vec![UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 2,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+
+    let entry = WaitlistEntry::create(ticket_type.id, user.id, 1)
+        .commit(connection)
+        .unwrap();
+    let entry2 = WaitlistEntry::create(ticket_type.id, user2.id, 1)
+        .commit(connection)
+        .unwrap();
+
+    // Nothing to offer while the tickets are in a cart
+    assert!(WaitlistEntry::process(ticket_type.id, connection)
+        .unwrap()
+        .is_empty());
+
+    cart.update_quantities(
+        &vec![UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 1,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+
+    let offers = WaitlistEntry::process(ticket_type.id, connection).unwrap();
+    assert_eq!(offers.len(), 1);
+    let entry = WaitlistEntry::find(entry.id, connection).unwrap();
+    assert_eq!(offers[0], entry);
+    assert_eq!(entry.status, WaitlistEntryStatus::Offered);
+    let hold = entry.hold(connection).unwrap().unwrap();
+    assert_eq!(hold.ticket_type_id, ticket_type.id);
+    assert_eq!(hold.quantity(connection).unwrap(), (1, 1));
+    assert_eq!(
+        WaitlistEntry::find(entry2.id, connection).unwrap().status,
+        WaitlistEntryStatus::Waiting
+    );
+
+    // Offer lapses and moves on to the next user
+    diesel::update(waitlist_entries::table.find(entry.id))
+        .set(waitlist_entries::offer_expires_at.eq(Utc::now().naive_utc() - Duration::minutes(1)))
+        .execute(connection)
+        .unwrap();
+    let offers = WaitlistEntry::process(ticket_type.id, connection).unwrap();
+    assert_eq!(offers.len(), 1);
+    assert_eq!(offers[0].id, entry2.id);
+    assert_eq!(
+        WaitlistEntry::find(entry.id, connection).unwrap().status,
+        WaitlistEntryStatus::Expired
+    );
+    assert_eq!(hold.quantity(connection).unwrap(), (0, 0));
+    let hold2 = offers[0].hold(connection).unwrap().unwrap();
+    assert_eq!(hold2.quantity(connection).unwrap(), (1, 1));
+}
+
+#[test]
+fn cancel() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project
+        .create_event()
+        .with_a_specific_number_of_tickets(1)
+        .with_ticket_pricing()
+        .finish();
+    let ticket_type = &event.ticket_types(connection).unwrap()[0];
+    let buyer = project.create_user().finish();
+    let user = project.create_user().finish();
+
+    let mut cart = Order::find_or_create_cart(&buyer, connection).unwrap();
+    cart.update_quantities(
+        &vec![UpdateOrderItem {
+            ticket_type_id: ticket_type.id,
+            quantity: 1,
+            redemption_code: None,
+            seat_ids: None,
+        }],
+        false,
+        false,
+        connection,
+    )
+    .unwrap();
+
+    let entry = WaitlistEntry::create(ticket_type.id, user.id, 1)
+        .commit(connection)
+        .unwrap();
+    cart.update_quantities(&vec![], false, true, connection)
+        .unwrap();
+    WaitlistEntry::process(ticket_type.id, connection).unwrap();
+    let entry = WaitlistEntry::find(entry.id, connection).unwrap();
+    let hold = entry.hold(connection).unwrap().unwrap();
+    assert_eq!(hold.quantity(connection).unwrap(), (1, 1));
+
+    let entry = entry.cancel(connection).unwrap();
+    assert_eq!(entry.status, WaitlistEntryStatus::Cancelled);
+    assert_eq!(hold.quantity(connection).unwrap(), (0, 0));
+    assert_eq!(ticket_type.remaining_ticket_count(connection).unwrap(), 1);
+    assert!(entry.cancel(connection).is_err());
+}
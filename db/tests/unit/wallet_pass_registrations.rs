@@ -0,0 +1,262 @@
+use bigneon_db::dev::TestProject;
+use bigneon_db::models::*;
+use bigneon_db::schema::{events, ticket_instances};
+use chrono::prelude::*;
+use diesel;
+use diesel::prelude::*;
+use time::Duration;
+
+#[test]
+fn commit() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_ticket_pricing()
+        .with_tickets()
+        .finish();
+    project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .is_paid()
+        .finish();
+    let ticket = &TicketInstance::find_for_user(user.id, connection).unwrap()[0];
+
+    let registration =
+        WalletPassRegistration::create(ticket.id, "device".to_string(), "token".to_string())
+            .commit(connection)
+            .unwrap();
+    assert_eq!(registration.ticket_instance_id, ticket.id);
+    assert_eq!(registration.push_token, "token".to_string());
+
+    // Registering again refreshes the push token
+    let updated =
+        WalletPassRegistration::create(ticket.id, "device".to_string(), "token2".to_string())
+            .commit(connection)
+            .unwrap();
+    assert_eq!(updated.id, registration.id);
+    assert_eq!(updated.push_token, "token2".to_string());
+    assert_eq!(
+        WalletPassRegistration::find_for_ticket_instance(ticket.id, connection).unwrap(),
+        vec![updated.clone()]
+    );
+    assert_eq!(
+        WalletPassRegistration::find_for_event(event.id, connection).unwrap(),
+        vec![updated]
+    );
+}
+
+#[test]
+fn find_updated_for_device() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_ticket_pricing()
+        .with_tickets()
+        .finish();
+    project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let tickets = TicketInstance::find_for_user(user.id, connection).unwrap();
+    for ticket in &tickets {
+        WalletPassRegistration::create(ticket.id, "device".to_string(), "token".to_string())
+            .commit(connection)
+            .unwrap();
+    }
+
+    let updated_passes =
+        WalletPassRegistration::find_updated_for_device("device", None, connection).unwrap();
+    assert_eq!(updated_passes.len(), 2);
+    assert!(
+        WalletPassRegistration::find_updated_for_device("other-device", None, connection)
+            .unwrap()
+            .is_empty()
+    );
+
+    let updated_since = Utc::now().naive_utc() + Duration::minutes(1);
+    assert!(WalletPassRegistration::find_updated_for_device(
+        "device",
+        Some(updated_since),
+        connection
+    )
+    .unwrap()
+    .is_empty());
+
+    // Passes change when the redeem date is reached as the barcode is added
+    let two_hours_ago = Utc::now().naive_utc() - Duration::hours(2);
+    let one_hour_ago = Utc::now().naive_utc() - Duration::hours(1);
+    diesel::update(
+        ticket_instances::table
+            .filter(ticket_instances::id.eq_any(tickets.iter().map(|t| t.id).collect::<Vec<_>>())),
+    )
+    .set(ticket_instances::updated_at.eq(two_hours_ago))
+    .execute(connection)
+    .unwrap();
+    diesel::update(events::table.filter(events::id.eq(event.id)))
+        .set((
+            events::updated_at.eq(two_hours_ago),
+            events::redeem_date.eq(Some(one_hour_ago)),
+        ))
+        .execute(connection)
+        .unwrap();
+    let updated_since = Utc::now().naive_utc() - Duration::minutes(90);
+    assert_eq!(
+        WalletPassRegistration::find_updated_for_device("device", Some(updated_since), connection)
+            .unwrap()
+            .len(),
+        2
+    );
+
+    diesel::update(events::table.filter(events::id.eq(event.id)))
+        .set((
+            events::updated_at.eq(two_hours_ago),
+            events::redeem_date.eq(None::<NaiveDateTime>),
+        ))
+        .execute(connection)
+        .unwrap();
+    assert!(WalletPassRegistration::find_updated_for_device(
+        "device",
+        Some(updated_since),
+        connection
+    )
+    .unwrap()
+    .is_empty());
+}
+
+#[test]
+fn destroy() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_ticket_pricing()
+        .with_tickets()
+        .finish();
+    project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .is_paid()
+        .finish();
+    let ticket = &TicketInstance::find_for_user(user.id, connection).unwrap()[0];
+    WalletPassRegistration::create(ticket.id, "device".to_string(), "token".to_string())
+        .commit(connection)
+        .unwrap();
+
+    assert_eq!(
+        WalletPassRegistration::destroy("device", ticket.id, connection).unwrap(),
+        1
+    );
+    assert!(
+        WalletPassRegistration::find_for_ticket_instance(ticket.id, connection)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn destroy_for_push_token() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let user = project.create_user().finish();
+    let event = project
+        .create_event()
+        .with_ticket_pricing()
+        .with_tickets()
+        .finish();
+    project
+        .create_order()
+        .for_event(&event)
+        .for_user(&user)
+        .quantity(2)
+        .is_paid()
+        .finish();
+    let tickets = TicketInstance::find_for_user(user.id, connection).unwrap();
+    WalletPassRegistration::create(tickets[0].id, "device".to_string(), "token".to_string())
+        .commit(connection)
+        .unwrap();
+    WalletPassRegistration::create(tickets[1].id, "device".to_string(), "token".to_string())
+        .commit(connection)
+        .unwrap();
+    let other_registration =
+        WalletPassRegistration::create(tickets[1].id, "device2".to_string(), "token2".to_string())
+            .commit(connection)
+            .unwrap();
+
+    assert_eq!(
+        WalletPassRegistration::destroy_for_push_token("token", connection).unwrap(),
+        2
+    );
+    assert_eq!(
+        WalletPassRegistration::find_for_event(event.id, connection).unwrap(),
+        vec![other_registration]
+    );
+}
+
+#[test]
+fn schedule_update() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().finish();
+
+    WalletPassRegistration::schedule_update(Tables::Events, event.id, json!({}), connection)
+        .unwrap();
+    assert!(DomainAction::has_pending_action(
+        DomainActionTypes::UpdateWalletPasses,
+        Tables::Events.table_name(),
+        event.id,
+        connection
+    )
+    .unwrap());
+}
+
+#[test]
+fn schedule_redeem_date_update() {
+    let project = TestProject::new();
+    let connection = project.get_connection();
+    let event = project.create_event().finish();
+    let has_pending_update = || {
+        DomainAction::has_pending_action(
+            DomainActionTypes::UpdateWalletPasses,
+            Tables::Events.table_name(),
+            event.id,
+            connection,
+        )
+        .unwrap()
+    };
+    assert!(!has_pending_update());
+
+    // Redeem dates that have passed already show the barcode
+    let event = event
+        .update(
+            None,
+            EventEditableAttributes {
+                redeem_date: Some(Utc::now().naive_utc() - Duration::hours(1)),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    assert!(!has_pending_update());
+
+    event
+        .update(
+            None,
+            EventEditableAttributes {
+                redeem_date: Some(Utc::now().naive_utc() + Duration::days(1)),
+                ..Default::default()
+            },
+            connection,
+        )
+        .unwrap();
+    assert!(has_pending_update());
+}
@@ -6,11 +6,30 @@ use StripeError;
 
 pub struct StripeClient {
     api_key: String,
+    stripe_account: Option<String>,
 }
 
 impl StripeClient {
     pub fn new(api_key: String) -> StripeClient {
-        StripeClient { api_key }
+        StripeClient {
+            api_key,
+            stripe_account: None,
+        }
+    }
+
+    /// Makes requests on behalf of a connected account
+    pub fn with_account(mut self, stripe_account: Option<String>) -> StripeClient {
+        self.stripe_account = stripe_account;
+        self
+    }
+
+    fn post(&self, url: &str) -> reqwest::RequestBuilder {
+        let client = reqwest::Client::new();
+        let mut request = client.post(url).basic_auth(&self.api_key, Some(""));
+        if let Some(ref stripe_account) = self.stripe_account {
+            request = request.header("Stripe-Account", stripe_account.as_str());
+        }
+        request
     }
 
     pub fn charge(
@@ -62,10 +81,8 @@ impl StripeClient {
         for key_value in metadata {
             params.push((format!("metadata[{}]", key_value.0), key_value.1));
         }
-        let client = reqwest::Client::new();
-        let mut resp = client
+        let mut resp = self
             .post("https://api.stripe.com/v1/charges")
-            .basic_auth(&self.api_key, Some(""))
             .form(&params)
             .send()?;
         match resp.status() {
@@ -79,10 +96,8 @@ impl StripeClient {
     pub fn refund(&self, charge_id: &str) -> Result<RefundResult, StripeError> {
         let params = vec![("charge".to_string(), charge_id.to_string())];
 
-        let client = reqwest::Client::new();
-        let mut resp = client
+        let mut resp = self
             .post("https://api.stripe.com/v1/refunds")
-            .basic_auth(&self.api_key, Some(""))
             .form(&params)
             .send()?;
         match resp.status() {
@@ -103,10 +118,8 @@ impl StripeClient {
             ("amount".to_string(), amount.to_string()),
         ];
 
-        let client = reqwest::Client::new();
-        let mut resp = client
+        let mut resp = self
             .post("https://api.stripe.com/v1/refunds")
-            .basic_auth(&self.api_key, Some(""))
             .form(&params)
             .send()?;
         match resp.status() {
@@ -118,14 +131,11 @@ impl StripeClient {
     }
 
     pub fn complete(&self, charge_id: &str) -> Result<ChargeResult, StripeError> {
-        let client = reqwest::Client::new();
-
-        let mut resp = client
+        let mut resp = self
             .post(&format!(
                 "https://api.stripe.com/v1/charges/{}/capture",
                 charge_id
             ))
-            .basic_auth(&self.api_key, Some(""))
             .send()?;
         match resp.status() {
             reqwest::StatusCode::OK => {
@@ -150,13 +160,11 @@ impl StripeClient {
         for key_value in metadata {
             params.push((format!("metadata[{}]", key_value.0), key_value.1));
         }
-        let client = reqwest::Client::new();
-        let mut resp = client
+        let mut resp = self
             .post(&format!(
                 "https://api.stripe.com/v1/customers/{}",
                 client_id,
             ))
-            .basic_auth(&self.api_key, Some(""))
             .form(&params)
             .send()?;
         match resp.status() {
@@ -181,10 +189,8 @@ impl StripeClient {
         for key_value in metadata {
             params.push((format!("metadata[{}]", key_value.0), key_value.1));
         }
-        let client = reqwest::Client::new();
-        let mut resp = client
+        let mut resp = self
             .post("https://api.stripe.com/v1/customers")
-            .basic_auth(&self.api_key, Some(""))
             .form(&params)
             .send()?;
         match resp.status() {
@@ -0,0 +1,129 @@
+use hex;
+use ring::{digest, hmac};
+use serde_json;
+use std::time::{SystemTime, UNIX_EPOCH};
+use StripeError;
+
+/// Signatures older than this are rejected to prevent replay attacks
+pub const WEBHOOK_TOLERANCE_SECONDS: i64 = 300;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WebhookEvent {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub data: WebhookEventData,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WebhookEventData {
+    pub object: serde_json::Value,
+}
+
+impl WebhookEvent {
+    /// Verifies the `Stripe-Signature` header against the raw request body before parsing it
+    pub fn construct(
+        payload: &str,
+        signature_header: &str,
+        webhook_secret: &str,
+    ) -> Result<WebhookEvent, StripeError> {
+        let mut timestamp = None;
+        let mut signatures = Vec::new();
+        for part in signature_header.split(',') {
+            let mut pair = part.trim().splitn(2, '=');
+            match (pair.next(), pair.next()) {
+                (Some("t"), Some(value)) => timestamp = value.parse::<i64>().ok(),
+                (Some("v1"), Some(value)) => signatures.push(value),
+                _ => {}
+            }
+        }
+
+        let timestamp = match timestamp {
+            Some(t) => t,
+            None => return Err(signature_error("Stripe signature is missing a timestamp")),
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        if (now - timestamp).abs() > WEBHOOK_TOLERANCE_SECONDS {
+            return Err(signature_error(
+                "Stripe signature timestamp is outside the tolerance zone",
+            ));
+        }
+
+        let key = hmac::VerificationKey::new(&digest::SHA256, webhook_secret.as_bytes());
+        let signed_payload = format!("{}.{}", timestamp, payload);
+        let verified = signatures
+            .iter()
+            .any(|signature| match hex::decode(signature) {
+                Ok(signature) => hmac::verify(&key, signed_payload.as_bytes(), &signature).is_ok(),
+                Err(_) => false,
+            });
+        if !verified {
+            return Err(signature_error("Stripe signature could not be verified"));
+        }
+
+        Ok(serde_json::from_str(payload)?)
+    }
+
+    /// String field on the event's object, e.g. the charge id
+    pub fn object_str(&self, field: &str) -> Option<&str> {
+        self.data.object.get(field).and_then(|v| v.as_str())
+    }
+
+    pub fn object_i64(&self, field: &str) -> Option<i64> {
+        self.data.object.get(field).and_then(|v| v.as_i64())
+    }
+}
+
+fn signature_error(description: &str) -> StripeError {
+    StripeError {
+        description: description.to_string(),
+        cause: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(payload: &str, secret: &str, timestamp: i64) -> String {
+        let key = hmac::SigningKey::new(&digest::SHA256, secret.as_bytes());
+        let signature = hmac::sign(&key, format!("{}.{}", timestamp, payload).as_bytes());
+        format!("t={},v1={}", timestamp, hex::encode(signature.as_ref()))
+    }
+
+    fn now() -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64
+    }
+
+    #[test]
+    fn construct() {
+        let payload = r#"{"id":"evt_1","type":"charge.refunded","data":{"object":{"id":"ch_1","amount_refunded":100}}}"#;
+        let header = sign(payload, "whsec_test", now());
+        let event = WebhookEvent::construct(payload, &header, "whsec_test").unwrap();
+        assert_eq!(event.id, "evt_1");
+        assert_eq!(event.event_type, "charge.refunded");
+        assert_eq!(event.object_str("id"), Some("ch_1"));
+        assert_eq!(event.object_i64("amount_refunded"), Some(100));
+
+        // Wrong secret
+        assert!(WebhookEvent::construct(payload, &header, "whsec_other").is_err());
+
+        // Tampered payload
+        let tampered = payload.replace("100", "200");
+        assert!(WebhookEvent::construct(&tampered, &header, "whsec_test").is_err());
+
+        // Stale timestamp
+        let header = sign(
+            payload,
+            "whsec_test",
+            now() - WEBHOOK_TOLERANCE_SECONDS - 10,
+        );
+        assert!(WebhookEvent::construct(payload, &header, "whsec_test").is_err());
+    }
+}